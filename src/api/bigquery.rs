@@ -0,0 +1,181 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::instrument;
+
+use crate::{util::http_client, Status};
+
+/// Client for streaming rows into BigQuery tables of a dataset.
+///
+/// It authenticates as the service account of the GCP runtime through the
+/// metadata server. Outside GCP the `ESPY_BIGQUERY_TOKEN` environment variable
+/// can provide an access token instead, e.g. from
+/// `gcloud auth print-access-token`.
+pub struct BigQueryApi {
+    project_id: String,
+    dataset: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+/// Column of a BigQuery table schema.
+pub struct Column {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub repeated: bool,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: &'static str) -> Column {
+        Column {
+            name,
+            kind,
+            repeated: false,
+        }
+    }
+
+    pub const fn repeated(name: &'static str, kind: &'static str) -> Column {
+        Column {
+            name,
+            kind,
+            repeated: true,
+        }
+    }
+}
+
+impl BigQueryApi {
+    pub async fn connect(project_id: &str, dataset: &str) -> Result<BigQueryApi, Status> {
+        let client = http_client::shared();
+        let token = match env::var("ESPY_BIGQUERY_TOKEN") {
+            Ok(token) => token,
+            Err(_) => {
+                client
+                    .get(METADATA_TOKEN_URL)
+                    .header("Metadata-Flavor", "Google")
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<TokenResponse>()
+                    .await?
+                    .access_token
+            }
+        };
+
+        Ok(BigQueryApi {
+            project_id: String::from(project_id),
+            dataset: String::from(dataset),
+            token,
+            client,
+        })
+    }
+
+    /// Creates `table` with `schema` unless it already exists.
+    #[instrument(level = "trace", skip(self, schema))]
+    pub async fn ensure_table(&self, table: &str, schema: &[Column]) -> Result<(), Status> {
+        let fields = schema
+            .iter()
+            .map(|column| {
+                json!({
+                    "name": column.name,
+                    "type": column.kind,
+                    "mode": if column.repeated { "REPEATED" } else { "NULLABLE" },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let resp = self
+            .client
+            .post(format!(
+                "{BIGQUERY_URL}/projects/{}/datasets/{}/tables",
+                self.project_id, self.dataset
+            ))
+            .bearer_auth(&self.token)
+            .json(&json!({
+                "tableReference": {
+                    "projectId": self.project_id,
+                    "datasetId": self.dataset,
+                    "tableId": table,
+                },
+                "schema": { "fields": fields },
+            }))
+            .send()
+            .await?;
+
+        match resp.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::CONFLICT => Ok(()),
+            status => Err(Status::internal(format!(
+                "Failed to create BigQuery table '{table}' ({status}): {}",
+                resp.text().await.unwrap_or_default()
+            ))),
+        }
+    }
+
+    /// Streams `rows` into `table`. Each row is paired with an insert id that
+    /// BigQuery uses to drop duplicates when an insert is retried.
+    #[instrument(level = "trace", skip(self, rows), fields(rows = rows.len()))]
+    pub async fn insert<T: Serialize>(
+        &self,
+        table: &str,
+        rows: &[(String, T)],
+    ) -> Result<(), Status> {
+        for chunk in rows.chunks(INSERT_BATCH_SIZE) {
+            let rows = chunk
+                .iter()
+                .map(|(insert_id, row)| json!({ "insertId": insert_id, "json": row }))
+                .collect::<Vec<_>>();
+
+            let resp = self
+                .client
+                .post(format!(
+                    "{BIGQUERY_URL}/projects/{}/datasets/{}/tables/{table}/insertAll",
+                    self.project_id, self.dataset
+                ))
+                .bearer_auth(&self.token)
+                .json(&json!({ "rows": rows }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<InsertAllResponse>()
+                .await?;
+
+            if let Some(error) = resp.insert_errors.first() {
+                return Err(Status::internal(format!(
+                    "BigQuery rejected {} rows of '{table}', first at index {}: {:?}",
+                    resp.insert_errors.len(),
+                    error.index,
+                    error.errors
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+struct InsertAllResponse {
+    #[serde(default)]
+    insert_errors: Vec<InsertError>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct InsertError {
+    #[serde(default)]
+    index: u64,
+    #[serde(default)]
+    errors: Vec<serde_json::Value>,
+}
+
+const BIGQUERY_URL: &str = "https://bigquery.googleapis.com/bigquery/v2";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+// BigQuery recommends up to 500 rows per streaming insert request.
+const INSERT_BATCH_SIZE: usize = 500;
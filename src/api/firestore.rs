@@ -1,19 +1,105 @@
-use firestore::FirestoreDb;
+use std::env;
 
-use crate::Status;
+use firestore::{FirestoreDb, FirestoreDbOptions};
+
+use crate::{
+    library::firestore::{access::Service, cache::FirestoreCache},
+    Status,
+};
 
 pub struct FirestoreApi {
     db: FirestoreDb,
+    service: Service,
+    cache: FirestoreCache,
+    collection_prefix: String,
+}
+
+/// Firestore database that a connection uses. Separate environments, e.g.
+/// staging or load tests, use their own database or prefix their collections
+/// so that they do not collide with production.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FirestoreConfig {
+    pub project_id: String,
+
+    /// Id of a named database. The default database is used if it is not set.
+    pub database_id: Option<String>,
+
+    /// Prefix of all root collection names, e.g. "staging_" stores games in
+    /// 'staging_games'. User subcollections are nested under the prefixed
+    /// 'users' collection and are not prefixed.
+    pub collection_prefix: String,
+}
+
+impl FirestoreConfig {
+    /// Returns the configuration of the production database, overridden by
+    /// the environment variables:
+    ///   * `ESPY_FIRESTORE_PROJECT`
+    ///   * `ESPY_FIRESTORE_DATABASE`
+    ///   * `ESPY_FIRESTORE_COLLECTION_PREFIX`
+    pub fn from_env() -> Self {
+        FirestoreConfig {
+            project_id: env::var("ESPY_FIRESTORE_PROJECT").unwrap_or(PROJECT_ID.to_owned()),
+            database_id: env::var("ESPY_FIRESTORE_DATABASE").ok(),
+            collection_prefix: env::var("ESPY_FIRESTORE_COLLECTION_PREFIX").unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for FirestoreConfig {
+    fn default() -> Self {
+        FirestoreConfig {
+            project_id: PROJECT_ID.to_owned(),
+            database_id: None,
+            collection_prefix: String::default(),
+        }
+    }
 }
 
 impl FirestoreApi {
+    /// Connects to Firestore with the identity of an offline job.
     pub async fn connect() -> Result<Self, Status> {
+        Self::connect_as(Service::Offline).await
+    }
+
+    /// Connects to Firestore with the identity of `service` that determines
+    /// which global collections it can write.
+    pub async fn connect_as(service: Service) -> Result<Self, Status> {
+        Self::connect_with(service, FirestoreConfig::from_env()).await
+    }
+
+    /// Connects to the Firestore database of `config` with the identity of
+    /// `service`.
+    pub async fn connect_with(service: Service, config: FirestoreConfig) -> Result<Self, Status> {
+        let mut options = FirestoreDbOptions::new(config.project_id);
+        if let Some(database_id) = config.database_id {
+            options = options.with_database_id(database_id);
+        }
+
         Ok(FirestoreApi {
-            db: FirestoreDb::new("espy-library").await?,
+            db: FirestoreDb::with_options(options).await?,
+            service,
+            cache: FirestoreCache::default(),
+            collection_prefix: config.collection_prefix,
         })
     }
 
     pub fn db(&self) -> &FirestoreDb {
         &self.db
     }
+
+    pub fn service(&self) -> Service {
+        self.service
+    }
+
+    /// Returns the cache of frequently read documents on this connection.
+    pub fn cache(&self) -> &FirestoreCache {
+        &self.cache
+    }
+
+    /// Returns the name of the root `collection` in the configured database.
+    pub fn collection(&self, collection: &str) -> String {
+        format!("{}{collection}", self.collection_prefix)
+    }
 }
+
+const PROJECT_ID: &str = "espy-library";
@@ -1,23 +1,30 @@
 use crate::api;
-use crate::documents::StoreEntry;
+use crate::documents::{StoreEntry, StorePrice};
 use crate::traits::Storefront;
+use crate::util::http_client;
 use crate::Status;
 use async_trait::async_trait;
+use chrono::Utc;
 use tracing::info;
 
 pub struct GogApi {
     token: api::GogToken,
+    client: reqwest::Client,
 }
 
 impl GogApi {
     pub fn new(token: api::GogToken) -> GogApi {
-        GogApi { token }
+        GogApi {
+            token,
+            client: http_client::shared(),
+        }
     }
 
     pub async fn get_game_ids(&self) -> Result<GogGamesList, Status> {
         let uri = format!("{}/user/data/games", GOG_API_HOST);
 
-        let game_list = reqwest::Client::new()
+        let game_list = self
+            .client
             .get(&uri)
             .header(
                 "Authorization",
@@ -30,6 +37,73 @@ impl GogApi {
 
         Ok(game_list)
     }
+
+    /// Returns user's total playtime in minutes for a GOG game. Returns None
+    /// if the game was never played through GOG Galaxy.
+    pub async fn get_playtime(&self, product_id: &str) -> Result<Option<u64>, Status> {
+        let uri = format!(
+            "{GOG_GAMEPLAY_HOST}/games/{product_id}/users/{}/sessions",
+            self.token.user_id()
+        );
+
+        let resp = self
+            .client
+            .get(&uri)
+            .header(
+                "Authorization",
+                format!("Bearer {}", &self.token.access_token),
+            )
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let sessions = resp.json::<GogGameSessions>().await?;
+        Ok(Some(sessions.time_sum))
+    }
+
+    /// Returns the current US price of a GOG product. Returns None if the
+    /// product is not sold in the store.
+    pub async fn get_price(product_id: &str) -> Result<Option<StorePrice>, Status> {
+        let uri = format!("{GOG_PRICES_HOST}/products/{product_id}/prices?countryCode=US");
+
+        let resp = http_client::shared().get(&uri).send().await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let resp = resp.json::<GogPricesResponse>().await?;
+        let price = match resp.embedded.prices.into_iter().next() {
+            Some(price) => price,
+            None => return Ok(None),
+        };
+
+        let (initial, final_price) = match (
+            parse_gog_price(&price.base_price),
+            parse_gog_price(&price.final_price),
+        ) {
+            (Some(initial), Some(final_price)) => (initial, final_price),
+            _ => {
+                return Err(Status::internal(format!(
+                    "({product_id}) Unexpected GOG price format: '{}' / '{}'",
+                    price.base_price, price.final_price
+                )))
+            }
+        };
+
+        Ok(Some(StorePrice {
+            store: GogApi::id(),
+            currency: price.currency.code,
+            initial,
+            final_price,
+            discount_percent: match initial {
+                0 => 0,
+                initial => (initial - final_price.min(initial)) * 100 / initial,
+            },
+            timestamp: Utc::now().timestamp(),
+        }))
+    }
 }
 
 #[async_trait]
@@ -44,7 +118,8 @@ impl Storefront for GogApi {
         for page in 1.. {
             let uri =
                 format!("{GOG_API_HOST}/account/getFilteredProducts?mediaType=1&page={page}",);
-            let resp = reqwest::Client::new()
+            let resp = self
+                .client
                 .get(&uri)
                 .header(
                     "Authorization",
@@ -91,6 +166,42 @@ pub struct GogGamesList {
     owned: Vec<u32>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GogGameSessions {
+    #[serde(default)]
+    time_sum: u64,
+}
+
+/// Parses a GOG price string like "1999 USD" into cents.
+fn parse_gog_price(price: &str) -> Option<u64> {
+    price.split_whitespace().next()?.parse().ok()
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GogPricesResponse {
+    #[serde(rename = "_embedded")]
+    embedded: GogPricesEmbedded,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GogPricesEmbedded {
+    #[serde(default)]
+    prices: Vec<GogPrice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct GogPrice {
+    currency: GogCurrency,
+    base_price: String,
+    final_price: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GogCurrency {
+    code: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum GogProductListResponse {
@@ -137,3 +248,5 @@ struct GogProduct {
 }
 
 const GOG_API_HOST: &str = "https://embed.gog.com";
+const GOG_GAMEPLAY_HOST: &str = "https://gameplay.gog.com";
+const GOG_PRICES_HOST: &str = "https://api.gog.com";
@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 
-use reqwest::{header, ClientBuilder};
+use reqwest::header;
 use soup::prelude::*;
 
-use crate::{documents::GogData, Status};
+use crate::{documents::GogData, util::http_client, Status};
 
 pub struct GogScrape {}
 
@@ -15,7 +15,7 @@ impl GogScrape {
             header::HeaderValue::from_static("en-US;en"),
         );
 
-        let client = ClientBuilder::new()
+        let client = http_client::builder()
             .default_headers(request_headers)
             .cookie_store(true)
             .build()
@@ -82,6 +82,7 @@ impl GogScrape {
             release_date,
             logo,
             critic_score,
+            votes: extract_votes(&text),
             genres: genres.into_iter().collect(),
             tags: tags.into_iter().collect(),
             description,
@@ -143,6 +144,16 @@ fn extract_score(input: &str) -> Option<u64> {
     }
 }
 
+/// Extracts the number of user votes from the structured data of the page.
+fn extract_votes(input: &str) -> Option<u64> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#""ratingCount"\s*:\s*"?(?P<votes>\d+)"#).unwrap();
+    }
+    RE.captures(input)
+        .and_then(|cap| cap.name("votes"))
+        .and_then(|votes| votes.as_str().parse().ok())
+}
+
 const LOGO: &str = "productcard-player__logo";
 const DETAILS_ROW: &str = "details__row";
 const DETAILS_CELL: &str = "details__link";
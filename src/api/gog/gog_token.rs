@@ -1,4 +1,5 @@
 // use crate::espy;
+use crate::util::http_client;
 use crate::Status;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -31,7 +32,12 @@ impl GogToken {
         let params = format!("client_id={GOG_GALAXY_CLIENT_ID}&client_secret={GOG_GALAXY_SECRET}&grant_type=authorization_code&code={oauth_code}&redirect_uri={GOG_GALAXY_REDIRECT_URI}%2Ftoken");
         let uri = format!("{GOG_AUTH_HOST}/token?{params}");
 
-        let resp = reqwest::get(&uri).await?.json::<GogAuthResponse>().await?;
+        let resp = http_client::shared()
+            .get(&uri)
+            .send()
+            .await?
+            .json::<GogAuthResponse>()
+            .await?;
         let internal_token = match resp {
             GogAuthResponse::Ok(internal_token) => internal_token,
             GogAuthResponse::Err(err) => {
@@ -67,7 +73,12 @@ impl GogToken {
             &self.refresh_token);
         let uri = format!("{GOG_AUTH_HOST}/token?{params}");
 
-        let resp = reqwest::get(&uri).await?.json::<GogAuthResponse>().await?;
+        let resp = http_client::shared()
+            .get(&uri)
+            .send()
+            .await?
+            .json::<GogAuthResponse>()
+            .await?;
         let internal_token = match resp {
             GogAuthResponse::Ok(internal_token) => internal_token,
             GogAuthResponse::Err(err) => {
@@ -80,6 +91,11 @@ impl GogToken {
         Ok(())
     }
 
+    /// Returns the GOG user id that the token was issued for.
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
     /// Returns true if the current user GOG access token has not expired yet.
     /// Typically, it is valid for 2 hours.
     fn is_fresh_token(&self) -> bool {
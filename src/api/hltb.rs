@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use serde_json::json;
+use tracing::warn;
+
+use crate::{documents::Playtime, util::http_client};
+
+pub struct HltbApi {}
+
+impl HltbApi {
+    /// Returns playtime estimates from HowLongToBeat for a game matching the
+    /// `title` and release `year`.
+    pub async fn get_playtime(title: &str, year: i32) -> Option<Playtime> {
+        let body = json!({
+            "searchType": "games",
+            "searchTerms": title.split_whitespace().collect::<Vec<_>>(),
+            "searchPage": 1,
+            "size": 20,
+            "searchOptions": {
+                "games": {
+                    "userId": 0,
+                    "platform": "",
+                    "sortCategory": "popular",
+                    "rangeCategory": "main",
+                    "rangeTime": {"min": null, "max": null},
+                    "gameplay": {"perspective": "", "flow": "", "genre": ""},
+                    "rangeYear": {"min": "", "max": ""},
+                    "modifier": "",
+                },
+                "users": {"sortCategory": "postcount"},
+                "filter": "",
+                "sort": 0,
+                "randomizer": 0,
+            },
+        });
+
+        let resp = http_client::shared()
+            .post(HLTB_SEARCH_URL)
+            .header("Referer", HLTB_HOST)
+            .header("User-Agent", USER_AGENT)
+            .json(&body)
+            .send()
+            .await;
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(status) => {
+                warn!("{status}");
+                return None;
+            }
+        };
+        let resp = match resp.json::<HltbSearchResponse>().await {
+            Ok(resp) => resp,
+            Err(status) => {
+                warn!("{status}");
+                return None;
+            }
+        };
+
+        let title = title.to_lowercase();
+        let candidate = resp
+            .data
+            .iter()
+            .find(|e| e.game_name.to_lowercase() == title && e.release_world == year)
+            .or_else(|| {
+                resp.data
+                    .iter()
+                    .find(|e| e.game_name.to_lowercase() == title)
+            });
+
+        match candidate {
+            Some(entry) => Some(Playtime {
+                main: to_hours(entry.comp_main),
+                extra: to_hours(entry.comp_plus),
+                completionist: to_hours(entry.comp_100),
+            }),
+            None => None,
+        }
+    }
+}
+
+// HLTB reports times in seconds.
+fn to_hours(secs: u64) -> Option<u64> {
+    match secs {
+        0 => None,
+        secs => Some(((secs as f64) / 3600.0).round() as u64),
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct HltbSearchResponse {
+    #[serde(default)]
+    data: Vec<HltbGame>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct HltbGame {
+    #[serde(default)]
+    game_name: String,
+
+    #[serde(default)]
+    release_world: i32,
+
+    #[serde(default)]
+    comp_main: u64,
+
+    #[serde(default)]
+    comp_plus: u64,
+
+    #[serde(default)]
+    comp_100: u64,
+}
+
+const HLTB_HOST: &str = "https://howlongtobeat.com";
+const HLTB_SEARCH_URL: &str = "https://howlongtobeat.com/api/search";
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
@@ -1,4 +1,7 @@
-use crate::{logging::IgdbRequestCounter, Status};
+use crate::{
+    logging::{IgdbCounters, IgdbRequestCounter},
+    Status,
+};
 use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
 use tracing::info;
@@ -6,51 +9,84 @@ use tracing::info;
 use super::IgdbConnection;
 
 /// Sends a POST request to an IGDB service endpoint.
+///
+/// Requests that are throttled by IGDB are retried after backing off the rate
+/// limiter of the connection.
 pub async fn post<T: DeserializeOwned>(
     connection: &IgdbConnection,
     endpoint: &str,
     body: &str,
 ) -> Result<T, Status> {
-    connection.qps.wait();
+    let mut attempts = 0;
+    loop {
+        connection.qps.wait_async().await;
 
-    let counter = IgdbRequestCounter::new(endpoint);
+        let counter = IgdbRequestCounter::new(endpoint);
 
-    let _permit = connection.qps.connection().await;
-    let uri = format!("{IGDB_SERVICE_URL}/{endpoint}/");
-    let resp = reqwest::Client::new()
-        .post(&uri)
-        .header("Client-ID", &connection.client_id)
-        .header(
-            "Authorization",
-            format!("Bearer {}", &connection.oauth_token),
-        )
-        .body(String::from(body))
-        .send()
-        .await;
+        let _permit = connection.qps.connection().await;
+        let uri = format!("{IGDB_SERVICE_URL}/{endpoint}/");
+        let resp = connection
+            .client
+            .post(&uri)
+            .header("Client-ID", &connection.client_id)
+            .header(
+                "Authorization",
+                format!("Bearer {}", &connection.oauth_token),
+            )
+            .body(String::from(body))
+            .send()
+            .await;
 
-    let resp = match resp {
-        Ok(resp) => resp,
-        Err(e) => {
-            let status =
-                Status::internal(format!("Request failed: {e}\nuri: {uri}\nquery: {body}"));
-            counter.log_error(&status);
-            return Err(status);
-        }
-    };
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(e) => {
+                let status = Status::new(
+                    &format!("Request failed\nuri: {uri}\nquery: {body}"),
+                    Status::from(e),
+                );
+                counter.log_error(&status);
+                return Err(status);
+            }
+        };
+
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            let backoff = connection.qps.throttled();
+            IgdbCounters::throttled(endpoint, backoff, connection.qps.effective_quota());
 
-    let text = resp.text().await?;
-    match serde_json::from_str::<T>(&text) {
-        Ok(resp) => {
-            counter.log();
-            Ok(resp)
+            attempts += 1;
+            if attempts >= MAX_THROTTLED_ATTEMPTS {
+                let status = Status::resource_exhausted(format!(
+                    "Request throttled {attempts} times\nuri: {uri}\nquery: {body}"
+                ));
+                counter.log_error(&status);
+                return Err(status);
+            }
+            continue;
         }
-        Err(_) => {
-            let status = Status::internal(format!(
-                "Failed to parse response: {text}\nuri: {uri}\nquery: {body}"
+
+        if resp.status().is_server_error() {
+            let status = Status::unavailable(format!(
+                "IGDB responded with {}\nuri: {uri}\nquery: {body}",
+                resp.status()
             ));
             counter.log_error(&status);
-            Err(status)
+            return Err(status);
         }
+
+        let text = resp.text().await?;
+        return match serde_json::from_str::<T>(&text) {
+            Ok(resp) => {
+                counter.log();
+                Ok(resp)
+            }
+            Err(_) => {
+                let status = Status::internal(format!(
+                    "Failed to parse response: {text}\nuri: {uri}\nquery: {body}"
+                ));
+                counter.log_error(&status);
+                Err(status)
+            }
+        };
     }
 }
 
@@ -61,11 +97,12 @@ pub async fn create_webhook(
     method: &str,
     secret: &str,
 ) -> Result<(), Status> {
-    connection.qps.wait();
+    connection.qps.wait_async().await;
 
     let _permit = connection.qps.connection().await;
     let uri = format!("{IGDB_SERVICE_URL}/{endpoint}/webhooks");
-    let resp = reqwest::Client::new()
+    let resp = connection
+        .client
         .post(&uri)
         .header("Client-ID", &connection.client_id)
         .header(
@@ -93,3 +130,6 @@ pub async fn create_webhook(
 }
 
 const IGDB_SERVICE_URL: &str = "https://api.igdb.com/v4";
+
+// Number of times a request is sent while IGDB throttles it.
+const MAX_THROTTLED_ATTEMPTS: u32 = 5;
@@ -6,9 +6,9 @@ use super::{
     docs::{IgdbCollection, IgdbCompany, IgdbExternalGame},
     resolve::{
         COLLECTIONS_ENDPOINT, COMPANIES_ENDPOINT, EXTERNAL_GAMES_ENDPOINT, FRANCHISES_ENDPOINT,
-        GAMES_ENDPOINT, GENRES_ENDPOINT, KEYWORDS_ENDPOINT,
+        GAMES_ENDPOINT, GENRES_ENDPOINT, KEYWORDS_ENDPOINT, THEMES_ENDPOINT,
     },
-    IgdbApi, IgdbGame, IgdbGenre,
+    IgdbApi, IgdbGame, IgdbGenre, IgdbTheme,
 };
 
 pub struct IgdbBatchApi {
@@ -27,10 +27,30 @@ impl IgdbBatchApi {
         offset: u64,
     ) -> Result<Vec<IgdbGame>, Status> {
         let connection = self.service.connection()?;
+        let platforms = self.service.platform_scope().igdb_condition();
         post::<Vec<IgdbGame>>(
             &connection,
             GAMES_ENDPOINT,
-            &format!("fields *; where (platforms = (6,13) | platforms = null) & updated_at >= {updated_since} & (follows > 0 | hypes > 0) & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
+            &format!("fields *; where ({platforms} | platforms = null) & updated_at >= {updated_since} & (follows > 0 | hypes > 0) & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
+        )
+        .await
+    }
+
+    /// Returns games with a first release date in [`released_after`,
+    /// `released_before`) regardless of when they were updated.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn collect_igdb_games_released(
+        &self,
+        released_after: i64,
+        released_before: i64,
+        offset: u64,
+    ) -> Result<Vec<IgdbGame>, Status> {
+        let connection = self.service.connection()?;
+        let platforms = self.service.platform_scope().igdb_condition();
+        post::<Vec<IgdbGame>>(
+            &connection,
+            GAMES_ENDPOINT,
+            &format!("fields *; where ({platforms} | platforms = null) & first_release_date >= {released_after} & first_release_date < {released_before} & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
         )
         .await
     }
@@ -42,10 +62,11 @@ impl IgdbBatchApi {
         offset: u64,
     ) -> Result<Vec<IgdbGame>, Status> {
         let connection = self.service.connection()?;
+        let platforms = self.service.platform_scope().igdb_condition();
         post::<Vec<IgdbGame>>(
             &connection,
             GAMES_ENDPOINT,
-            &format!("fields *; where platforms = (6,13) & collection = {collection_id} & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
+            &format!("fields *; where {platforms} & collection = {collection_id} & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
         )
         .await
     }
@@ -57,10 +78,11 @@ impl IgdbBatchApi {
         offset: u64,
     ) -> Result<Vec<IgdbGame>, Status> {
         let connection = self.service.connection()?;
+        let platforms = self.service.platform_scope().igdb_condition();
         post::<Vec<IgdbGame>>(
             &connection,
             GAMES_ENDPOINT,
-            &format!("fields *; where platforms = (6,13) & (franchise = {franchise_id} | franchises = ({franchise_id})) & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
+            &format!("fields *; where {platforms} & (franchise = {franchise_id} | franchises = ({franchise_id})) & (category = 0 | category = 1 | category = 2 | category = 4 | category = 8 | category = 9); limit 500; offset {offset};"),
         )
         .await
     }
@@ -154,6 +176,36 @@ impl IgdbBatchApi {
         .await
     }
 
+    #[instrument(level = "trace", skip(self))]
+    pub async fn collect_themes(&self) -> Result<Vec<IgdbTheme>, Status> {
+        let connection = self.service.connection()?;
+        post::<Vec<IgdbTheme>>(
+            &connection,
+            THEMES_ENDPOINT,
+            &format!("fields *; limit 500;"),
+        )
+        .await
+    }
+
+    /// Returns the theme ids of the games with `ids`. Only the id, name and
+    /// themes of the returned IgdbGames are set.
+    #[instrument(level = "trace", skip(self, ids))]
+    pub async fn collect_game_themes(&self, ids: &[u64]) -> Result<Vec<IgdbGame>, Status> {
+        let connection = self.service.connection()?;
+        post::<Vec<IgdbGame>>(
+            &connection,
+            GAMES_ENDPOINT,
+            &format!(
+                "fields id, name, themes; where id = ({}); limit 500;",
+                ids.iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        )
+        .await
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub async fn collect_keywords(&self, offset: u64) -> Result<Vec<Keyword>, Status> {
         let connection = self.service.connection()?;
@@ -1,8 +1,18 @@
+use std::time::SystemTime;
+
 use crate::util::rate_limiter::RateLimiter;
 
+use super::resolve::ResolveBudgets;
+
 #[derive(Debug)]
 pub struct IgdbConnection {
     pub client_id: String,
     pub oauth_token: String,
+
+    /// Time that `oauth_token` expires. Tokens are not refreshed, so the
+    /// service needs a restart before then.
+    pub oauth_expires_at: SystemTime,
     pub qps: RateLimiter,
+    pub budgets: ResolveBudgets,
+    pub client: reqwest::Client,
 }
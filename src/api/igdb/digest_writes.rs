@@ -0,0 +1,344 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, SystemTime},
+};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use tracing::{instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{Collection, CollectionType, Company, CompanyRole, GameDigest},
+    library::firestore::{self, access},
+    logging::DigestWriteCounters,
+    Status,
+};
+
+use super::resolve::update_digest;
+
+lazy_static! {
+    static ref PENDING: Mutex<HashMap<DigestDoc, PendingWrite>> = Mutex::new(HashMap::new());
+}
+
+static COALESCING: AtomicBool = AtomicBool::new(false);
+
+/// A document that holds the digests of its games.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(super) enum DigestDoc {
+    Company(u64),
+    Collection(u64),
+    Franchise(u64),
+}
+
+/// A fresh digest of a game that needs to be applied on a DigestDoc.
+#[derive(Clone, Debug)]
+pub(super) struct DigestUpdate {
+    pub digest: GameDigest,
+    pub keep: bool,
+
+    /// Role of the company in the game. Ignored for collections.
+    pub role: CompanyRole,
+}
+
+#[derive(Debug)]
+struct PendingWrite {
+    name: String,
+    slug: String,
+    updates: Vec<DigestUpdate>,
+
+    /// Number of flushes that failed to write the updates.
+    failures: u32,
+}
+
+/// Digest updates of one or more documents that are submitted together.
+#[derive(Debug, Default)]
+pub(super) struct DigestBatch {
+    pending: HashMap<DigestDoc, PendingWrite>,
+}
+
+impl DigestBatch {
+    /// Adds `update` on `doc`. `name` and `slug` are used if `doc` needs to be
+    /// created.
+    pub fn add(&mut self, doc: DigestDoc, name: &str, slug: &str, update: DigestUpdate) {
+        self.pending
+            .entry(doc)
+            .or_insert_with(|| PendingWrite {
+                name: name.to_owned(),
+                slug: slug.to_owned(),
+                updates: vec![],
+                failures: 0,
+            })
+            .updates
+            .push(update);
+    }
+}
+
+/// Spawns a background task that flushes buffered digest updates every
+/// `FLUSH_WINDOW`.
+///
+/// Once the flusher is running, digest updates of companies and collections
+/// are buffered and all updates of the same document within a window are
+/// merged into a single write. Without it updates are written immediately,
+/// which is what batch jobs and the http server need. Services that run the
+/// flusher need to call `flush_digests()` on shutdown, so that the updates of
+/// the last window are not lost.
+pub fn spawn_digest_flusher(firestore: Arc<FirestoreApi>) {
+    COALESCING.store(true, Ordering::SeqCst);
+
+    tokio::spawn(
+        async move {
+            let mut interval = tokio::time::interval(FLUSH_WINDOW);
+            loop {
+                interval.tick().await;
+                flush_digests(&firestore).await;
+            }
+        }
+        .instrument(trace_span!("spawn_digest_flusher")),
+    );
+}
+
+/// Writes all buffered digest updates to Firestore.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn flush_digests(firestore: &FirestoreApi) {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(PoisonError::into_inner));
+    if pending.is_empty() {
+        return;
+    }
+
+    let start = SystemTime::now();
+    let docs = pending.len();
+    let updates = pending.values().map(|p| p.updates.len()).sum();
+    let writes = write(firestore, pending).await;
+
+    DigestWriteCounters::flush(
+        docs,
+        updates,
+        writes,
+        SystemTime::now().duration_since(start).unwrap_or_default(),
+    );
+}
+
+/// Applies the updates of `batch`, either immediately or on the next flush if
+/// coalescing is enabled.
+pub(super) async fn submit(firestore: &FirestoreApi, batch: DigestBatch) {
+    if batch.pending.is_empty() {
+        return;
+    }
+
+    // Updates of dry runs are not buffered, as the flusher writes them with
+    // its own connection.
+    if COALESCING.load(Ordering::SeqCst) && !access::is_dry_run(firestore) {
+        let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+        for (doc, write) in batch.pending {
+            match pending.get_mut(&doc) {
+                Some(buffered) => buffered.updates.extend(write.updates),
+                None => {
+                    pending.insert(doc, write);
+                }
+            }
+        }
+        return;
+    }
+
+    write(firestore, batch.pending).await;
+}
+
+/// Applies all `pending` updates with a batch read and a batched write per
+/// document type, instead of a read and a write per document. Returns the
+/// number of documents that were written.
+async fn write(firestore: &FirestoreApi, pending: HashMap<DigestDoc, PendingWrite>) -> usize {
+    let mut companies = HashMap::new();
+    let mut collections = HashMap::new();
+    let mut franchises = HashMap::new();
+    for (doc, pending) in pending {
+        match doc {
+            DigestDoc::Company(id) => companies.insert(id, pending),
+            DigestDoc::Collection(id) => collections.insert(id, pending),
+            DigestDoc::Franchise(id) => franchises.insert(id, pending),
+        };
+    }
+
+    let (companies, collections, franchises) = tokio::join!(
+        write_companies(firestore, companies),
+        write_collections(firestore, CollectionType::Collection, collections),
+        write_collections(firestore, CollectionType::Franchise, franchises),
+    );
+
+    companies + collections + franchises
+}
+
+async fn write_companies(
+    firestore: &FirestoreApi,
+    mut pending: HashMap<u64, PendingWrite>,
+) -> usize {
+    if pending.is_empty() {
+        return 0;
+    }
+
+    let ids = pending.keys().copied().collect_vec();
+    let result = match firestore::companies::batch_read(firestore, &ids).await {
+        Ok(result) => result,
+        Err(status) => {
+            return write_failed(firestore, "companies", pending, DigestDoc::Company, status)
+        }
+    };
+
+    let mut modified = vec![];
+    for mut company in result.documents.into_iter().chain(
+        // Companies that were missing. They are only written if any update
+        // keeps a game.
+        result.not_found.into_iter().filter_map(|id| {
+            pending.get(&id).map(|pending| Company {
+                id,
+                name: pending.name.clone(),
+                slug: pending.slug.clone(),
+                ..Default::default()
+            })
+        }),
+    ) {
+        let pending = match pending.get(&company.id) {
+            Some(pending) => pending,
+            None => continue,
+        };
+
+        let mut changed = false;
+        for update in &pending.updates {
+            let digests = match update.role {
+                CompanyRole::Developer => &mut company.developed,
+                CompanyRole::Publisher => &mut company.published,
+                _ => continue,
+            };
+            changed |= update_digest(digests, update.digest.clone(), update.keep);
+        }
+        if changed {
+            modified.push(company);
+        }
+    }
+
+    match firestore::companies::batch_write(firestore, &modified).await {
+        Ok(()) => modified.len(),
+        Err(status) => {
+            pending.retain(|id, _| modified.iter().any(|company| company.id == *id));
+            write_failed(firestore, "companies", pending, DigestDoc::Company, status)
+        }
+    }
+}
+
+async fn write_collections(
+    firestore: &FirestoreApi,
+    collection_type: CollectionType,
+    mut pending: HashMap<u64, PendingWrite>,
+) -> usize {
+    if pending.is_empty() {
+        return 0;
+    }
+
+    let (doc_type, digest_doc): (_, fn(u64) -> DigestDoc) = match collection_type {
+        CollectionType::Franchise => ("franchises", DigestDoc::Franchise),
+        _ => ("collections", DigestDoc::Collection),
+    };
+    let ids = pending.keys().copied().collect_vec();
+    let result = match collection_type {
+        CollectionType::Franchise => firestore::franchises::batch_read(firestore, &ids).await,
+        _ => firestore::collections::batch_read(firestore, &ids).await,
+    };
+    let result = match result {
+        Ok(result) => result,
+        Err(status) => return write_failed(firestore, doc_type, pending, digest_doc, status),
+    };
+
+    let mut modified = vec![];
+    for mut collection in result.documents.into_iter().chain(
+        // Collections that were missing. They are only written if any update
+        // keeps a game.
+        result.not_found.into_iter().filter_map(|id| {
+            pending.get(&id).map(|pending| Collection {
+                id,
+                name: pending.name.clone(),
+                slug: pending.slug.clone(),
+                ..Default::default()
+            })
+        }),
+    ) {
+        let pending = match pending.get(&collection.id) {
+            Some(pending) => pending,
+            None => continue,
+        };
+
+        let mut changed = false;
+        for update in &pending.updates {
+            changed |= update_digest(&mut collection.games, update.digest.clone(), update.keep);
+        }
+        if changed {
+            modified.push(collection);
+        }
+    }
+
+    let result = match collection_type {
+        CollectionType::Franchise => firestore::franchises::batch_write(firestore, &modified).await,
+        _ => firestore::collections::batch_write(firestore, &modified).await,
+    };
+    match result {
+        Ok(()) => modified.len(),
+        Err(status) => {
+            pending.retain(|id, _| modified.iter().any(|collection| collection.id == *id));
+            write_failed(firestore, doc_type, pending, digest_doc, status)
+        }
+    }
+}
+
+/// Reports that digest updates of `failed` docs could not be written and puts
+/// them back in the buffer for the next flush, unless they already failed
+/// `MAX_FLUSH_FAILURES` times. Returns 0 written docs.
+fn write_failed(
+    firestore: &FirestoreApi,
+    doc_type: &str,
+    failed: HashMap<u64, PendingWrite>,
+    digest_doc: fn(u64) -> DigestDoc,
+    status: Status,
+) -> usize {
+    warn!(
+        "Failed to update digests of {} {doc_type}: {status}",
+        failed.len()
+    );
+    for id in failed.keys() {
+        DigestWriteCounters::write_fail(&format!("{doc_type}/{id}"));
+    }
+
+    // Updates are only requeued while the flusher is running to write them.
+    if !COALESCING.load(Ordering::SeqCst) || access::is_dry_run(firestore) {
+        return 0;
+    }
+
+    let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+    for (id, mut write) in failed {
+        write.failures += 1;
+        if write.failures >= MAX_FLUSH_FAILURES {
+            warn!(
+                "Dropping digest updates of {doc_type}/{id} after {} failed flushes",
+                write.failures
+            );
+            continue;
+        }
+
+        // Updates that were buffered since are newer and need to be applied
+        // after the failed ones.
+        let doc = digest_doc(id);
+        if let Some(newer) = pending.remove(&doc) {
+            write.updates.extend(newer.updates);
+        }
+        pending.insert(doc, write);
+    }
+    0
+}
+
+// Time during which digest updates are buffered before they are written.
+const FLUSH_WINDOW: Duration = Duration::from_secs(5);
+
+// Flushes that can fail for a document before its updates are dropped.
+const MAX_FLUSH_FAILURES: u32 = 5;
@@ -3,7 +3,7 @@ use std::{collections::HashSet, fmt};
 use chrono::{Datelike, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
-use crate::documents::GameCategory;
+use crate::documents::{GameCategory, GameStatus, Image, PlatformScope};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct IgdbGame {
@@ -70,6 +70,14 @@ pub struct IgdbGame {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub keywords: Vec<u64>,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub themes: Vec<u64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub player_perspectives: Vec<u64>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub expansions: Vec<u64>,
@@ -145,75 +153,98 @@ pub struct IgdbGame {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub websites: Vec<u64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternative_names: Vec<u64>,
+
+    // Last time the entry was updated in IGDB.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
 }
 
 impl IgdbGame {
-    pub fn is_pc_game(&self) -> bool {
-        self.platforms.contains(&6) || self.platforms.contains(&13)
+    /// Returns true if the game is released on any platform in `scope`.
+    pub fn is_in_scope(&self, scope: &PlatformScope) -> bool {
+        scope.contains(&self.platforms)
     }
 
     pub fn is_main_category(&self) -> bool {
-        let game_category = GameCategory::from(self.category);
-        matches!(
-            game_category,
-            GameCategory::Main
-                | GameCategory::Dlc
-                | GameCategory::Expansion
-                | GameCategory::StandaloneExpansion
-                | GameCategory::Remake
-                | GameCategory::Remaster
-        )
+        GameCategory::from(self.category).is_main_category()
+    }
+
+    /// Returns true if the game is cancelled or is still unreleased without
+    /// any update in IGDB for `STALE_YEARS`.
+    pub fn is_abandoned(&self, now: i64) -> bool {
+        if GameStatus::from(self.status) == GameStatus::Cancelled {
+            return true;
+        }
+
+        let unreleased = match self.first_release_date {
+            Some(date) => date == 0 || date > now,
+            None => true,
+        };
+        let stale = match self.updated_at {
+            Some(updated_at) => now - updated_at > STALE_YEARS * YEAR_SECS,
+            None => false,
+        };
+        unreleased && stale
     }
 
     pub fn release_year(&self) -> i32 {
         NaiveDateTime::from_timestamp_opt(self.first_release_date.unwrap_or(0), 0)
-            .unwrap()
+            .unwrap_or_default()
             .year()
     }
 
     pub fn diff(&self, other: &IgdbGame) -> IgdbGameDiff {
         IgdbGameDiff {
-            name: self.name != other.name,
-            category: self.category != other.category,
-            status: self.status != other.status,
-
-            url: self.url != other.url,
-            summary: self.summary != other.summary,
-            storyline: self.storyline != other.storyline,
-
-            first_release_date: self.first_release_date != other.first_release_date,
-            aggregated_rating: self.aggregated_rating != other.aggregated_rating,
-            total_rating: self.total_rating != other.total_rating,
-
-            follows: self.follows != other.follows,
-            hypes: self.hypes != other.hypes,
-
-            genres: vec_diff(&self.genres, &other.genres),
-            keywords: vec_diff(&self.keywords, &other.keywords),
-            expansions: vec_diff(&self.expansions, &other.expansions),
-            standalone_expansions: vec_diff(
+            name: value_diff(&self.name, &other.name),
+            category: value_diff(&self.category, &other.category),
+            status: value_diff(&self.status, &other.status),
+
+            url: value_diff(&self.url, &other.url),
+            summary: text_diff(&self.summary, &other.summary),
+            storyline: text_diff(&self.storyline, &other.storyline),
+
+            first_release_date: value_diff(&self.first_release_date, &other.first_release_date),
+            release_dates: set_diff(&self.release_dates, &other.release_dates),
+            aggregated_rating: value_diff(&self.aggregated_rating, &other.aggregated_rating),
+            total_rating: value_diff(&self.total_rating, &other.total_rating),
+
+            follows: value_diff(&self.follows, &other.follows),
+            hypes: value_diff(&self.hypes, &other.hypes),
+
+            genres: set_diff(&self.genres, &other.genres),
+            keywords: set_diff(&self.keywords, &other.keywords),
+            themes: set_diff(&self.themes, &other.themes),
+            player_perspectives: set_diff(&self.player_perspectives, &other.player_perspectives),
+            expansions: set_diff(&self.expansions, &other.expansions),
+            standalone_expansions: set_diff(
                 &self.standalone_expansions,
                 &other.standalone_expansions,
             ),
-            dlcs: vec_diff(&self.dlcs, &other.dlcs),
-            remakes: vec_diff(&self.remakes, &other.remakes),
-            remasters: vec_diff(&self.remasters, &other.remasters),
-            bundles: vec_diff(&self.bundles, &other.bundles),
-
-            parent_game: self.parent_game != other.parent_game,
-            version_parent: self.version_parent != other.version_parent,
-            version_title: self.version_title != other.version_title,
-
-            collection: self.collection != other.collection,
-            collections: vec_diff(&self.collections, &other.collections),
-            franchise: self.franchise != other.franchise,
-            franchises: vec_diff(&self.franchises, &other.franchises),
-            involved_companies: vec_diff(&self.involved_companies, &other.involved_companies),
-
-            cover: self.cover != other.cover,
-            screenshots: vec_diff(&self.screenshots, &other.screenshots),
-            artworks: vec_diff(&self.artworks, &other.artworks),
-            websites: vec_diff(&self.websites, &other.websites),
+            dlcs: set_diff(&self.dlcs, &other.dlcs),
+            remakes: set_diff(&self.remakes, &other.remakes),
+            remasters: set_diff(&self.remasters, &other.remasters),
+            bundles: set_diff(&self.bundles, &other.bundles),
+
+            parent_game: value_diff(&self.parent_game, &other.parent_game),
+            version_parent: value_diff(&self.version_parent, &other.version_parent),
+            version_title: value_diff(&self.version_title, &other.version_title),
+
+            collection: value_diff(&self.collection, &other.collection),
+            collections: set_diff(&self.collections, &other.collections),
+            franchise: value_diff(&self.franchise, &other.franchise),
+            franchises: set_diff(&self.franchises, &other.franchises),
+            involved_companies: set_diff(&self.involved_companies, &other.involved_companies),
+
+            cover: value_diff(&self.cover, &other.cover),
+            screenshots: set_diff(&self.screenshots, &other.screenshots),
+            artworks: set_diff(&self.artworks, &other.artworks),
+            websites: set_diff(&self.websites, &other.websites),
+            alternative_names: set_diff(&self.alternative_names, &other.alternative_names),
         }
     }
 }
@@ -294,6 +325,9 @@ pub struct IgdbCompany {
     #[serde(default)]
     pub logo: Option<u64>,
 
+    #[serde(default)]
+    pub description: String,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub developed: Vec<u64>,
@@ -321,6 +355,29 @@ pub struct IgdbCollection {
     pub games: Vec<u64>,
 }
 
+/// Cover image of a game in IGDB together with its id, so that covers of
+/// many games can be fetched in a single request.
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct IgdbCover {
+    pub id: u64,
+
+    #[serde(flatten)]
+    pub image: Image,
+}
+
+/// Theme of a game in IGDB, e.g. "Horror", "Science fiction" or "Open
+/// world".
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct IgdbTheme {
+    pub id: u64,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub slug: String,
+}
+
 #[derive(Deserialize, Default, Debug)]
 pub struct IgdbGenre {
     pub id: u64,
@@ -341,6 +398,15 @@ pub struct IgdbWebsite {
     pub url: String,
 }
 
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct IgdbAlternativeName {
+    #[serde(default)]
+    pub game: u64,
+
+    #[serde(default)]
+    pub name: String,
+}
+
 #[derive(Deserialize, Default, Debug, Clone)]
 pub struct IgdbAnnotation {
     pub id: u64,
@@ -370,81 +436,103 @@ pub struct ReleaseDateStatus {
     pub name: String,
 }
 
+/// Changes between two versions of an IgdbGame. Only changed fields are set.
+/// Scalar fields carry their old and new values, while id lists carry the ids
+/// that were added and removed.
 #[derive(Serialize, Default, Debug, Clone)]
 pub struct IgdbGameDiff {
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub name: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub category: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub status: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub url: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub summary: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub storyline: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub first_release_date: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub aggregated_rating: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub total_rating: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub follows: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub hypes: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub genres: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub keywords: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub expansions: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub standalone_expansions: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub dlcs: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub remakes: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub remasters: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub bundles: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub parent_game: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub version_parent: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub version_title: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub collection: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub collections: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub franchise: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub franchises: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub involved_companies: bool,
-
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub cover: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub screenshots: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub artworks: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
-    pub websites: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<ValueChange<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ValueChange<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ValueChange<u64>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<ValueChange<String>>,
+    // Long texts are truncated to keep diffs compact.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ValueChange<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storyline: Option<ValueChange<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_release_date: Option<ValueChange<Option<i64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_dates: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregated_rating: Option<ValueChange<Option<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_rating: Option<ValueChange<Option<f64>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follows: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hypes: Option<ValueChange<Option<u64>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genres: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub themes: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player_perspectives: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expansions: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standalone_expansions: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dlcs: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remakes: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remasters: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundles: Option<SetChange>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_game: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_parent: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_title: Option<ValueChange<Option<String>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collections: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub franchise: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub franchises: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub involved_companies: Option<SetChange>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover: Option<ValueChange<Option<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshots: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artworks: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websites: Option<SetChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alternative_names: Option<SetChange>,
 }
 
-fn is_default<T: Default + PartialEq>(t: &T) -> bool {
-    t == &T::default()
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ValueChange<T> {
+    pub old: T,
+    pub new: T,
+}
+
+#[derive(Serialize, Default, Debug, Clone, PartialEq)]
+pub struct SetChange {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<u64>,
 }
 
 impl fmt::Display for IgdbGameDiff {
@@ -457,68 +545,217 @@ impl fmt::Display for IgdbGameDiff {
 }
 
 impl IgdbGameDiff {
+    /// Returns true if any of the fields that determine the release date of
+    /// the game changed.
+    pub fn release_date_changed(&self) -> bool {
+        self.first_release_date.is_some() || self.release_dates.is_some()
+    }
+
     pub fn empty(&self) -> bool {
         !self.is_not_empty()
     }
 
     pub fn is_not_empty(&self) -> bool {
-        self.name
-            || self.category
-            || self.status
-            || self.url
-            || self.summary
-            || self.storyline
-            || self.first_release_date
-            || self.aggregated_rating
-            || self.follows
-            || self.hypes
-            || self.genres
-            || self.keywords
-            || self.expansions
-            || self.standalone_expansions
-            || self.dlcs
-            || self.remakes
-            || self.remasters
-            || self.bundles
-            || self.parent_game
-            || self.version_parent
-            || self.version_title
-            || self.collection
-            || self.collections
-            || self.franchise
-            || self.franchises
-            || self.involved_companies
-            || self.cover
-            || self.screenshots
-            || self.artworks
-            || self.websites
+        self.name.is_some()
+            || self.category.is_some()
+            || self.status.is_some()
+            || self.url.is_some()
+            || self.summary.is_some()
+            || self.storyline.is_some()
+            || self.first_release_date.is_some()
+            || self.release_dates.is_some()
+            || self.aggregated_rating.is_some()
+            || self.follows.is_some()
+            || self.hypes.is_some()
+            || self.genres.is_some()
+            || self.keywords.is_some()
+            || self.themes.is_some()
+            || self.player_perspectives.is_some()
+            || self.expansions.is_some()
+            || self.standalone_expansions.is_some()
+            || self.dlcs.is_some()
+            || self.remakes.is_some()
+            || self.remasters.is_some()
+            || self.bundles.is_some()
+            || self.parent_game.is_some()
+            || self.version_parent.is_some()
+            || self.version_title.is_some()
+            || self.collection.is_some()
+            || self.collections.is_some()
+            || self.franchise.is_some()
+            || self.franchises.is_some()
+            || self.involved_companies.is_some()
+            || self.cover.is_some()
+            || self.screenshots.is_some()
+            || self.artworks.is_some()
+            || self.websites.is_some()
+            || self.alternative_names.is_some()
     }
 
     pub fn needs_resolve(&self) -> bool {
-        self.first_release_date
-            || self.genres
-            || self.keywords
-            || self.expansions
-            || self.standalone_expansions
-            || self.dlcs
-            || self.remakes
-            || self.remasters
-            || self.parent_game
-            || self.version_parent
-            || self.collection
-            || self.collections
-            || self.franchise
-            || self.franchises
-            || self.involved_companies
-            || self.cover
-            || self.screenshots
-            || self.artworks
-            || self.websites
+        self.category.is_some()
+            || self.first_release_date.is_some()
+            || self.release_dates.is_some()
+            || self.genres.is_some()
+            || self.keywords.is_some()
+            || self.themes.is_some()
+            || self.player_perspectives.is_some()
+            || self.expansions.is_some()
+            || self.standalone_expansions.is_some()
+            || self.dlcs.is_some()
+            || self.remakes.is_some()
+            || self.remasters.is_some()
+            || self.parent_game.is_some()
+            || self.version_parent.is_some()
+            || self.collection.is_some()
+            || self.collections.is_some()
+            || self.franchise.is_some()
+            || self.franchises.is_some()
+            || self.involved_companies.is_some()
+            || self.cover.is_some()
+            || self.screenshots.is_some()
+            || self.artworks.is_some()
+            || self.websites.is_some()
+            || self.alternative_names.is_some()
     }
 }
 
-fn vec_diff(left: &[u64], right: &[u64]) -> bool {
-    let left = HashSet::<u64>::from_iter(left.iter().cloned());
-    let right = HashSet::<u64>::from_iter(right.iter().cloned());
-    left.intersection(&right).count() != left.len()
+fn value_diff<T: PartialEq + Clone>(old: &T, new: &T) -> Option<ValueChange<T>> {
+    match old != new {
+        true => Some(ValueChange {
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        false => None,
+    }
+}
+
+fn text_diff(old: &str, new: &str) -> Option<ValueChange<String>> {
+    match old != new {
+        true => Some(ValueChange {
+            old: truncate(old),
+            new: truncate(new),
+        }),
+        false => None,
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TEXT_DIFF_LEN) {
+        Some((i, _)) => format!("{}...", &text[..i]),
+        None => text.to_owned(),
+    }
+}
+
+fn set_diff(old: &[u64], new: &[u64]) -> Option<SetChange> {
+    let old_ids = HashSet::<u64>::from_iter(old.iter().cloned());
+    let new_ids = HashSet::<u64>::from_iter(new.iter().cloned());
+
+    let mut change = SetChange {
+        added: new_ids.difference(&old_ids).cloned().collect(),
+        removed: old_ids.difference(&new_ids).cloned().collect(),
+    };
+    change.added.sort();
+    change.removed.sort();
+
+    match change.added.is_empty() && change.removed.is_empty() {
+        true => None,
+        false => Some(change),
+    }
+}
+
+const MAX_TEXT_DIFF_LEN: usize = 64;
+
+// Years without an IGDB update after which an unreleased game is considered
+// abandoned.
+const STALE_YEARS: i64 = 5;
+const YEAR_SECS: i64 = 365 * 24 * 60 * 60;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn cancelled_is_abandoned() {
+        let game = IgdbGame {
+            status: 6,
+            updated_at: Some(NOW),
+            ..Default::default()
+        };
+        assert!(game.is_abandoned(NOW));
+    }
+
+    #[test]
+    fn stale_unreleased_is_abandoned() {
+        let game = IgdbGame {
+            updated_at: Some(NOW - 6 * YEAR_SECS),
+            ..Default::default()
+        };
+        assert!(game.is_abandoned(NOW));
+
+        let game = IgdbGame {
+            first_release_date: Some(NOW + YEAR_SECS),
+            updated_at: Some(NOW - 6 * YEAR_SECS),
+            ..Default::default()
+        };
+        assert!(game.is_abandoned(NOW));
+    }
+
+    #[test]
+    fn diff_carries_changed_values() {
+        let old = IgdbGame {
+            name: "Hades".to_owned(),
+            first_release_date: Some(NOW),
+            genres: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let new = IgdbGame {
+            name: "Hades".to_owned(),
+            first_release_date: Some(NOW + YEAR_SECS),
+            genres: vec![3, 2, 4],
+            summary: "x".repeat(100),
+            ..Default::default()
+        };
+
+        let diff = old.diff(&new);
+        assert!(diff.name.is_none());
+        assert_eq!(
+            diff.first_release_date,
+            Some(ValueChange {
+                old: Some(NOW),
+                new: Some(NOW + YEAR_SECS),
+            })
+        );
+        assert_eq!(
+            diff.genres,
+            Some(SetChange {
+                added: vec![4],
+                removed: vec![1],
+            })
+        );
+        assert_eq!(
+            diff.summary.unwrap().new.len(),
+            MAX_TEXT_DIFF_LEN + "...".len()
+        );
+        assert!(diff.needs_resolve());
+        assert!(old.diff(&old).empty());
+    }
+
+    #[test]
+    fn released_or_recently_updated_is_not_abandoned() {
+        let game = IgdbGame {
+            first_release_date: Some(NOW - 10 * YEAR_SECS),
+            updated_at: Some(NOW - 6 * YEAR_SECS),
+            ..Default::default()
+        };
+        assert!(!game.is_abandoned(NOW));
+
+        let game = IgdbGame {
+            updated_at: Some(NOW - YEAR_SECS),
+            ..Default::default()
+        };
+        assert!(!game.is_abandoned(NOW));
+    }
 }
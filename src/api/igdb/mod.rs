@@ -1,16 +1,27 @@
+// IGDB responses are external input. Malformed ones must fail the resolve
+// with a Status error instead of panicking the task that resolves the game.
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 mod backend;
 mod batch;
 mod connection;
+mod digest_writes;
 mod docs;
 mod ranking;
 mod resolve;
 mod search;
 mod service;
+mod single_flight;
 mod webhooks;
 
 pub use batch::IgdbBatchApi;
 use connection::IgdbConnection;
-pub use docs::{IgdbExternalGame, IgdbGame, IgdbGameDiff, IgdbGenre};
+pub use digest_writes::{flush_digests, spawn_digest_flusher};
+pub use docs::{
+    IgdbCollection, IgdbCompany, IgdbExternalGame, IgdbGame, IgdbGameDiff, IgdbGenre, IgdbTheme,
+    SetChange, ValueChange,
+};
+pub use resolve::{remove_digests, update_digests, ResolveBudgets};
 pub use search::IgdbSearch;
 pub use service::IgdbApi;
 pub use webhooks::IgdbWebhooksApi;
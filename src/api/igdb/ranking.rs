@@ -1,11 +1,26 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use phf::phf_map;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::documents::GameEntry;
+
 use super::IgdbGame;
 
 /// Sorts GameEntries by title relevance in descending order.
-pub fn sorted_by_relevance(title: &str, igdb_games: Vec<IgdbGame>) -> Vec<IgdbGame> {
+///
+/// A game is as relevant as the closest match between `title` and its name or
+/// any of its `aliases` that are keyed by game id.
+pub fn sorted_by_relevance(
+    title: &str,
+    igdb_games: Vec<IgdbGame>,
+    aliases: &HashMap<u64, Vec<String>>,
+) -> Vec<IgdbGame> {
     let mut candidates = igdb_games
         .into_iter()
         .map(|game| Candidate {
-            score: edit_distance(title, &game.name),
+            score: relevance(title, &game, aliases),
             game,
         })
         .collect::<Vec<_>>();
@@ -20,12 +35,13 @@ pub fn sorted_by_relevance(title: &str, igdb_games: Vec<IgdbGame>) -> Vec<IgdbGa
 pub fn sorted_by_relevance_with_threshold(
     title: &str,
     igdb_games: Vec<IgdbGame>,
+    aliases: &HashMap<u64, Vec<String>>,
     threshold: f64,
 ) -> Vec<IgdbGame> {
     let mut candidates = igdb_games
         .into_iter()
         .map(|game| Candidate {
-            score: edit_distance(title, &game.name),
+            score: relevance(title, &game, aliases),
             game,
         })
         .filter(|c| c.score <= threshold)
@@ -38,6 +54,60 @@ pub fn sorted_by_relevance_with_threshold(
         .collect()
 }
 
+/// Returns all candidates that are tied for the best relevance to `title`, as
+/// long as it is within `threshold`.
+pub fn best_matches(
+    title: &str,
+    igdb_games: Vec<IgdbGame>,
+    aliases: &HashMap<u64, Vec<String>>,
+    threshold: f64,
+) -> Vec<IgdbGame> {
+    best_scored(
+        igdb_games
+            .into_iter()
+            .map(|game| (relevance(title, &game, aliases), game)),
+        threshold,
+    )
+}
+
+/// Returns all catalog entries that are tied for the best relevance to
+/// `title`, as long as it is within `threshold`. Catalog entries are only
+/// matched by name.
+pub fn best_catalog_matches(
+    title: &str,
+    game_entries: Vec<GameEntry>,
+    threshold: f64,
+) -> Vec<GameEntry> {
+    let title = normalize(title);
+    best_scored(
+        game_entries.into_iter().map(|game_entry| {
+            (
+                edit_distance(&title, &normalize(&game_entry.name)),
+                game_entry,
+            )
+        }),
+        threshold,
+    )
+}
+
+// Returns the items that are tied for the lowest score, as long as it is
+// within `threshold`.
+fn best_scored<T>(scored: impl Iterator<Item = (f64, T)>, threshold: f64) -> Vec<T> {
+    let candidates = scored
+        .filter(|(score, _)| *score <= threshold)
+        .collect::<Vec<_>>();
+    let best = candidates
+        .iter()
+        .map(|(score, _)| *score)
+        .fold(f64::INFINITY, f64::min);
+
+    candidates
+        .into_iter()
+        .filter(|(score, _)| *score == best)
+        .map(|(_, item)| item)
+        .collect()
+}
+
 // Internal struct that is only exposed for debug reasons (search by title) in
 // the command line tool.
 #[derive(Debug)]
@@ -52,6 +122,58 @@ impl std::fmt::Display for Candidate {
     }
 }
 
+// Returns the smallest edit distance between `title` and the game's name or
+// aliases.
+fn relevance(title: &str, game: &IgdbGame, aliases: &HashMap<u64, Vec<String>>) -> f64 {
+    let title = normalize(title);
+    std::iter::once(&game.name)
+        .chain(aliases.get(&game.id).into_iter().flatten())
+        .map(|name| edit_distance(&title, &normalize(name)))
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Returns `title` in the form that titles are compared in.
+//
+// Titles are lowercased, accents and compatibility characters, e.g. full-width
+// letters or "Ⅶ", are folded to their base letters, punctuation is folded to
+// spaces and tokens with known variant spellings are replaced by a single
+// spelling, so that "NieR:Automata" matches "Nier Automata".
+fn normalize(title: &str) -> String {
+    let mut folded = String::with_capacity(title.len());
+    for c in title.nfkd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            '\'' | '’' | '`' => {}
+            '&' => folded.push_str(" and "),
+            c if c.is_alphanumeric() => folded.extend(c.to_lowercase()),
+            _ => folded.push(' '),
+        }
+    }
+
+    folded
+        .split_whitespace()
+        .map(|token| TOKEN_ALIASES.get(token).copied().unwrap_or(token))
+        .join(" ")
+}
+
+// Tokens of titles that are spelled in different ways. Single letter numerals
+// are left out as they are also used as letters, e.g. "Mega Man X".
+static TOKEN_ALIASES: phf::Map<&'static str, &'static str> = phf_map! {
+    "ii" => "2",
+    "iii" => "3",
+    "iv" => "4",
+    "vi" => "6",
+    "vii" => "7",
+    "viii" => "8",
+    "ix" => "9",
+    "xi" => "11",
+    "xii" => "12",
+    "xiii" => "13",
+    "xiv" => "14",
+    "xv" => "15",
+    "xvi" => "16",
+    "vs" => "versus",
+};
+
 // Returns edit distance between two strings.
 fn edit_distance(a: &str, b: &str) -> f64 {
     let a_len = a.chars().count();
@@ -89,7 +211,7 @@ fn edit_distance(a: &str, b: &str) -> f64 {
         }
     }
 
-    *matrix.last().unwrap() as f64 / std::cmp::max(a_len, b_len) as f64
+    matrix.last().copied().unwrap_or_default() as f64 / std::cmp::max(a_len, b_len) as f64
 }
 
 #[cfg(test)]
@@ -127,6 +249,58 @@ mod tests {
         assert_eq!(edit_distance("", "hello"), 5.0);
     }
 
+    fn game(id: u64, name: &str) -> IgdbGame {
+        IgdbGame {
+            id,
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn relevance_without_aliases() {
+        let aliases = HashMap::new();
+        assert_eq!(relevance("Doom", &game(1, "Doom"), &aliases), 0.0);
+        assert_eq!(relevance("doom", &game(1, "DOOM"), &aliases), 0.0);
+    }
+
+    #[test]
+    fn relevance_matches_aliases() {
+        let aliases =
+            HashMap::from([(1, vec!["GTA V".to_owned(), "Grand Theft Auto 5".to_owned()])]);
+        assert_eq!(
+            relevance("GTA V", &game(1, "Grand Theft Auto V"), &aliases),
+            0.0
+        );
+        assert!(relevance("GTA V", &game(2, "Grand Theft Auto V"), &aliases) > 0.5);
+    }
+
+    #[test]
+    fn sorted_by_relevance_prefers_alias_match() {
+        let aliases = HashMap::from([(2, vec!["Biohazard".to_owned()])]);
+        let sorted = sorted_by_relevance(
+            "Biohazard",
+            vec![game(1, "Biohazard Zone"), game(2, "Resident Evil")],
+            &aliases,
+        );
+        assert_eq!(sorted.iter().map(|g| g.id).collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn best_catalog_matches_by_name() {
+        let entry = |id: u64, name: &str| GameEntry {
+            id,
+            name: name.to_owned(),
+            ..Default::default()
+        };
+        let matches = best_catalog_matches(
+            "NieR:Automata",
+            vec![entry(1, "Nier Automata"), entry(2, "NieR Replicant")],
+            0.15,
+        );
+        assert_eq!(matches.iter().map(|g| g.id).collect::<Vec<_>>(), vec![1]);
+    }
+
     #[test]
     fn edit_distance_emoji() {
         assert_eq!(edit_distance("😊", ""), 1.0);
@@ -134,4 +308,63 @@ mod tests {
         assert_delta!(edit_distance("😊🦀", "🦀😊🦀"), 0.33, 0.004);
         assert_eq!(edit_distance("😊🦀", "😊🦀"), 0.0);
     }
+
+    #[test]
+    fn normalize_tricky_titles() {
+        for (title, name) in [
+            ("Nier Automata", "NieR:Automata"),
+            ("nier automata", "ＮｉｅＲ：Ａｕｔｏｍａｔａ"),
+            ("Pokemon Sword", "Pokémon Sword"),
+            ("Assassins Creed", "Assassin’s Creed"),
+            ("Ratchet and Clank", "Ratchet & Clank"),
+            ("Half Life 2", "Half-Life 2"),
+            ("Final Fantasy 7", "FINAL FANTASY VII"),
+            ("Final Fantasy 7", "Final Fantasy Ⅶ"),
+            ("Marvel versus Capcom", "Marvel vs. Capcom"),
+            ("ファイナルファンタジーⅦ", "ファイナルファンタジーVII"),
+        ] {
+            assert_eq!(normalize(title), normalize(name), "{title} vs {name}");
+        }
+    }
+
+    #[test]
+    fn normalize_keeps_distinct_titles() {
+        assert_ne!(normalize("Mega Man X"), normalize("Mega Man 10"));
+        assert_ne!(normalize("Hades"), normalize("Hades II"));
+    }
+
+    #[test]
+    fn relevance_matches_transliterated_aliases() {
+        let aliases = HashMap::from([(1, vec!["ファイナルファンタジーVII".to_owned()])]);
+        assert_eq!(
+            relevance(
+                "ファイナルファンタジーⅦ",
+                &game(1, "Final Fantasy VII"),
+                &aliases
+            ),
+            0.0
+        );
+        assert_eq!(
+            relevance("Nier Automata", &game(2, "NieR:Automata"), &aliases),
+            0.0
+        );
+    }
+
+    #[test]
+    fn best_matches_returns_ties() {
+        let games = vec![
+            game(1, "Resident Evil 4"),
+            game(2, "Resident Evil 4"),
+            game(3, "Resident Evil 5"),
+        ];
+
+        let best = best_matches("resident evil 4", games, &HashMap::new(), 0.15);
+        assert_eq!(best.iter().map(|g| g.id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn best_matches_respects_threshold() {
+        let games = vec![game(1, "Hades II")];
+        assert!(best_matches("hades", games, &HashMap::new(), 0.15).is_empty());
+    }
 }
@@ -1,23 +1,29 @@
 use std::{
     cmp::Ordering,
-    time::{SystemTime, UNIX_EPOCH},
+    collections::HashMap,
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
-    api::{FirestoreApi, MetacriticApi, SteamDataApi, SteamScrape},
+    api::{FirestoreApi, HltbApi, MetacriticApi, PcgwApi, SteamDataApi, SteamScrape, SteamSpyApi},
     documents::{
-        Collection, CollectionDigest, CollectionType, Company, CompanyDigest, CompanyRole,
-        GameCategory, GameDigest, GameEntry, Image, SteamData, Website, WebsiteAuthority,
+        Collection, CollectionDigest, CollectionType, CompanyDigest, CompanyRole, GameCategory,
+        GameDigest, GameEntry, Image, ReleasePrecision, ResolveSource, SteamData, TechInfo,
+        Website, WebsiteAuthority,
     },
     library::firestore,
     Status,
 };
 use async_recursion::async_recursion;
+use chrono::Utc;
 use itertools::Itertools;
+use tokio::task::JoinHandle;
 use tracing::{error, instrument, trace_span, warn, Instrument};
 
 use super::{
     backend::post,
+    digest_writes::{self, DigestBatch, DigestDoc, DigestUpdate},
     docs::{self, IgdbInvolvedCompany},
     IgdbConnection, IgdbGame,
 };
@@ -50,19 +56,25 @@ pub async fn resolve_game_digest(
             }
         };
 
-    // Spawn a task to retrieve steam data.
-    let steam_handle = match external_games.iter().find(|e| e.is_steam()) {
+    // Spawn tasks to retrieve steam data and SteamSpy estimates.
+    let (steam_handle, steam_spy_handle) = match external_games.iter().find(|e| e.is_steam()) {
         Some(steam_external) => {
             let steam_appid = steam_external.store_id.clone();
-            Some(tokio::spawn(
+            let steam_handle = tokio::spawn(
                 async move {
                     let steam = SteamDataApi::new();
                     steam.retrieve_steam_data(&steam_appid).await
                 }
                 .instrument(trace_span!("spawn_steam_request")),
-            ))
+            );
+            let steam_appid = steam_external.store_id.clone();
+            let steam_spy_handle = tokio::spawn(
+                async move { SteamSpyApi::get_app(&steam_appid).await }
+                    .instrument(trace_span!("spawn_steam_spy_request")),
+            );
+            (Some(steam_handle), Some(steam_spy_handle))
         }
-        None => None,
+        None => (None, None),
     };
 
     // Spawn a task to retrieve metacritic score.
@@ -131,24 +143,62 @@ pub async fn resolve_game_digest(
 
     let mut steam_data = None;
     if let Some(handle) = steam_handle {
-        match handle.await {
-            Ok(result) => match result {
-                Ok(data) => steam_data = Some(data),
-                Err(status) => warn!("{status}"),
-            },
-            Err(status) => warn!("{status}"),
+        match await_source(
+            handle,
+            connection.budgets.steam_data,
+            ResolveSource::SteamData,
+            &mut game_entry.missing_sources,
+        )
+        .await
+        {
+            Some(Ok(data)) => steam_data = Some(data),
+            Some(Err(status)) => source_failed(
+                ResolveSource::SteamData,
+                status,
+                &mut game_entry.missing_sources,
+            ),
+            None => {}
         }
     }
 
-    game_entry.release_date = get_release_timestamp(connection, &igdb_game, &steam_data)
-        .await?
-        .unwrap_or_default();
+    let (release_date, release_precision) =
+        get_release_timestamp(connection, &igdb_game, &steam_data).await?;
+    game_entry.release_date = release_date.unwrap_or_default();
+    game_entry.release_precision = release_precision;
 
     if let Some(steam_data) = steam_data {
         game_entry.add_steam_data(steam_data);
     }
+    if let Some(handle) = steam_spy_handle {
+        match await_source(
+            handle,
+            connection.budgets.steam_spy,
+            ResolveSource::SteamSpy,
+            &mut game_entry.missing_sources,
+        )
+        .await
+        {
+            Some(Ok(Some(steam_spy))) => game_entry
+                .scores
+                .add_steam_spy(&steam_spy, game_entry.release_date),
+            Some(Ok(None)) => {}
+            Some(Err(status)) => source_failed(
+                ResolveSource::SteamSpy,
+                status,
+                &mut game_entry.missing_sources,
+            ),
+            None => {}
+        }
+    }
     game_entry.resolve_genres();
 
+    if !game_entry.igdb_game.themes.is_empty() {
+        match get_themes(connection, &game_entry.igdb_game.themes).await {
+            Ok(themes) => game_entry.themes = themes.into_iter().map(|theme| theme.name).collect(),
+            Err(status) => warn!("Failed to retrieve themes: {status}"),
+        }
+    }
+
     match firestore::genres::read(firestore, game_entry.id).await {
         Ok(genres) => game_entry.espy_genres = genres.espy_genres,
         Err(Status::NotFound(_)) => {
@@ -157,15 +207,17 @@ pub async fn resolve_game_digest(
         Err(status) => error!("Genre lookup failed: {status}"),
     }
 
-    match metacritic_handle.await {
-        Ok(response) => {
-            if let Some(metacritic) = response {
-                game_entry
-                    .scores
-                    .add_metacritic(metacritic, game_entry.release_date);
-            }
-        }
-        Err(status) => warn!("{status}"),
+    let metacritic = await_source(
+        metacritic_handle,
+        connection.budgets.metacritic,
+        ResolveSource::Metacritic,
+        &mut game_entry.missing_sources,
+    )
+    .await;
+    if let Some(Some(metacritic)) = metacritic {
+        game_entry
+            .scores
+            .add_metacritic(metacritic, game_entry.release_date);
     }
 
     if game_entry.scores.metacritic.is_none() {
@@ -185,15 +237,46 @@ pub async fn resolve_game_digest(
         }
     }
 
+    // Games that predate Metacritic fall back to archived critic scores.
+    if game_entry.scores.metacritic.is_none()
+        && game_entry.release_date > 0
+        && game_entry.release_date < METACRITIC_LAUNCH
+    {
+        match firestore::legacy_scores::read(&firestore, game_entry.id).await {
+            Ok(legacy) => game_entry.scores.add_legacy(&legacy),
+            Err(Status::NotFound(_)) => {
+                // pass: no score found
+            }
+            Err(status) => {
+                error!("Legacy score lookup failed: {status}");
+            }
+        }
+    }
+
     if let Some(gog_external) = external_games.into_iter().find(|e| e.is_gog()) {
         if let Some(gog_data) = gog_external.gog_data {
             game_entry.add_gog_data(gog_data);
         }
     }
 
+    // Curator state is not derived from IGDB, so it is carried over from the
+    // stored entry instead of being reset by re-resolves.
+    match firestore::games::read(firestore, game_entry.id).await {
+        Ok(stored) => game_entry.keep_curated_state(&stored),
+        Err(Status::NotFound(_)) => {}
+        Err(status) => error!("Stored entry lookup failed: {status}"),
+    }
+
+    // Curator overrides are applied last, so that re-resolves do not clobber
+    // curated corrections.
+    match firestore::overrides::read(firestore, game_entry.id).await {
+        Ok(game_override) => game_override.apply(&mut game_entry),
+        Err(Status::NotFound(_)) => {}
+        Err(status) => error!("Override lookup failed: {status}"),
+    }
+
     // TODO: Remove these updates from the critical path.
-    update_companies(firestore, &game_entry).await;
-    update_collections(firestore, &game_entry).await;
+    update_digests(firestore, &game_entry).await;
 
     Ok(game_entry)
 }
@@ -229,116 +312,358 @@ pub async fn resolve_game_info(
         None => None,
     };
 
-    if !igdb_game.keywords.is_empty() {
-        game_entry.keywords = get_keywords(firestore, &igdb_game.keywords).await?;
-    }
-
-    if igdb_game.websites.len() > 0 {
-        if let Ok(websites) = get_websites(connection, &igdb_game.websites).await {
-            game_entry.websites.extend(
-                websites
-                    .into_iter()
-                    .map(|website| Website {
-                        url: website.url,
-                        authority: match website.category {
-                            1 => WebsiteAuthority::Official,
-                            3 => WebsiteAuthority::Wikipedia,
-                            9 => WebsiteAuthority::Youtube,
-                            13 => WebsiteAuthority::Steam,
-                            16 => WebsiteAuthority::Egs,
-                            17 => WebsiteAuthority::Gog,
-                            _ => WebsiteAuthority::Null,
-                        },
-                    })
-                    .filter(|website| match website.authority {
-                        WebsiteAuthority::Null => false,
-                        _ => true,
-                    }),
-            );
-        }
-    }
+    // Spawn a task to retrieve playtime estimates.
+    let title = game_entry.name.clone();
+    let year = game_entry.release_year();
+    let hltb_handle = tokio::spawn(
+        async move { HltbApi::get_playtime(&title, year).await }
+            .instrument(trace_span!("spawn_hltb_request")),
+    );
 
+    // Spawn a task to retrieve tech details from PCGamingWiki, unless they are
+    // cached.
+    let (cached_tech_info, pcgw_handle) =
+        match firestore::tech_info::read(firestore, game_entry.id).await {
+            Ok(tech_info) if !is_stale(&tech_info) => (Some(tech_info), None),
+            Ok(_) | Err(Status::NotFound(_)) => {
+                let title = game_entry.name.clone();
+                let steam_appid = game_entry.steam_data.as_ref().map(|data| data.steam_appid);
+                (
+                    None,
+                    Some(tokio::spawn(
+                        async move { PcgwApi::get_tech_info(&title, steam_appid).await }
+                            .instrument(trace_span!("spawn_pcgw_request")),
+                    )),
+                )
+            }
+            Err(status) => {
+                warn!("Tech info lookup failed: {status}");
+                (None, None)
+            }
+        };
+
+    // Follow-up lookups are independent of each other and run concurrently.
+    let game_id = game_entry.id;
+    let parent_id = igdb_game.parent_game.or(igdb_game.version_parent);
+    let is_bundle = matches!(
+        game_entry.category,
+        GameCategory::Bundle | GameCategory::Version
+    );
     // Skip screenshots if they already exist from steam data.
-    if !igdb_game.screenshots.is_empty() && game_entry.steam_data.is_none() {
-        if let Ok(screenshots) = get_screenshots(connection, &igdb_game.screenshots).await {
-            game_entry.screenshots = screenshots;
-        }
-    }
-    if !igdb_game.artworks.is_empty() {
-        if let Ok(artwork) = get_artwork(connection, &igdb_game.artworks).await {
-            game_entry.artwork = artwork;
-        }
-    }
+    let screenshot_ids: &[u64] = match game_entry.steam_data {
+        Some(_) => &[],
+        None => &igdb_game.screenshots,
+    };
 
-    let parent_id = match igdb_game.parent_game {
-        Some(parent) => Some(parent),
-        None => match igdb_game.version_parent {
-            Some(parent) => Some(parent),
-            None => None,
+    let (
+        keywords,
+        alternative_names,
+        websites,
+        screenshots,
+        artwork,
+        parent,
+        expansions,
+        standalone_expansions,
+        dlcs,
+        remakes,
+        remasters,
+        contents,
+    ) = tokio::join!(
+        unless_empty(
+            &igdb_game.keywords,
+            get_keywords(firestore, &igdb_game.keywords)
+        ),
+        unless_empty(
+            &igdb_game.alternative_names,
+            get_alternative_names(connection, &igdb_game.alternative_names),
+        ),
+        unless_empty(
+            &igdb_game.websites,
+            get_websites(connection, &igdb_game.websites)
+        ),
+        unless_empty(screenshot_ids, get_screenshots(connection, screenshot_ids)),
+        unless_empty(
+            &igdb_game.artworks,
+            get_artwork(connection, &igdb_game.artworks)
+        ),
+        async {
+            match parent_id {
+                Some(id) => Some(get_digest(connection, firestore, id).await),
+                None => None,
+            }
         },
-    };
+        unless_empty(
+            &igdb_game.expansions,
+            get_digests(connection, firestore, &igdb_game.expansions),
+        ),
+        unless_empty(
+            &igdb_game.standalone_expansions,
+            get_digests(connection, firestore, &igdb_game.standalone_expansions),
+        ),
+        unless_empty(
+            &igdb_game.dlcs,
+            get_digests(connection, firestore, &igdb_game.dlcs)
+        ),
+        unless_empty(
+            &igdb_game.remakes,
+            get_digests(connection, firestore, &igdb_game.remakes)
+        ),
+        unless_empty(
+            &igdb_game.remasters,
+            get_digests(connection, firestore, &igdb_game.remasters),
+        ),
+        async {
+            match is_bundle {
+                true => get_bundle_contents(connection, firestore, game_id).await,
+                false => Ok(None),
+            }
+        },
+    );
 
-    if let Some(id) = parent_id {
-        if let Ok(game) = get_digest(connection, firestore, id).await {
-            game_entry.parent = Some(game);
-        };
+    if let Some(keywords) = keywords {
+        game_entry.keywords = keywords?;
+    }
+    if let Some(Ok(names)) = alternative_names {
+        game_entry.alternative_names = names.into_iter().map(|e| e.name).collect();
+    }
+    if let Some(Ok(websites)) = websites {
+        game_entry.websites.extend(
+            websites
+                .into_iter()
+                .map(|website| Website {
+                    url: website.url,
+                    authority: match website.category {
+                        1 => WebsiteAuthority::Official,
+                        3 => WebsiteAuthority::Wikipedia,
+                        9 => WebsiteAuthority::Youtube,
+                        13 => WebsiteAuthority::Steam,
+                        16 => WebsiteAuthority::Egs,
+                        17 => WebsiteAuthority::Gog,
+                        _ => WebsiteAuthority::Null,
+                    },
+                })
+                .filter(|website| match website.authority {
+                    WebsiteAuthority::Null => false,
+                    _ => true,
+                }),
+        );
+    }
+    if let Some(Ok(screenshots)) = screenshots {
+        game_entry.screenshots = screenshots;
+    }
+    if let Some(Ok(artwork)) = artwork {
+        game_entry.artwork = artwork;
     }
-    if !igdb_game.expansions.is_empty() {
-        if let Ok(digests) = get_digests(connection, firestore, &igdb_game.expansions).await {
-            game_entry.expansions = digests;
+    if let Some(Ok(game)) = parent {
+        game_entry.parent = Some(game);
+    }
+    if let Some(Ok(digests)) = expansions {
+        game_entry.expansions = digests;
+    }
+    if let Some(Ok(mut digests)) = standalone_expansions {
+        game_entry.expansions.append(&mut digests);
+    }
+    if let Some(Ok(digests)) = dlcs {
+        game_entry.dlcs = digests;
+    }
+    if let Some(Ok(digests)) = remakes {
+        game_entry.remakes = digests;
+    }
+    if let Some(Ok(digests)) = remasters {
+        game_entry.remasters = digests;
+    }
+    if let Some(digests) = contents? {
+        game_entry.contents = digests;
+    }
+
+    if let Some(handle) = steam_handle {
+        let steam_scrape_data = await_source(
+            handle,
+            connection.budgets.steam_scrape,
+            ResolveSource::SteamScrape,
+            &mut game_entry.missing_sources,
+        )
+        .await;
+        if let Some(Some(steam_scrape_data)) = steam_scrape_data {
+            if let Some(steam_data) = &mut game_entry.steam_data {
+                steam_data.user_tags = steam_scrape_data.user_tags;
+            }
         }
     }
-    if !igdb_game.standalone_expansions.is_empty() {
-        if let Ok(mut digests) =
-            get_digests(connection, firestore, &igdb_game.standalone_expansions).await
+
+    if let Some(playtime) = await_source(
+        hltb_handle,
+        connection.budgets.hltb,
+        ResolveSource::Hltb,
+        &mut game_entry.missing_sources,
+    )
+    .await
+    {
+        game_entry.playtime = playtime;
+    }
+
+    if cached_tech_info.is_some() {
+        game_entry.tech_info = cached_tech_info;
+    }
+    if let Some(handle) = pcgw_handle {
+        match await_source(
+            handle,
+            connection.budgets.pcgw,
+            ResolveSource::PcGamingWiki,
+            &mut game_entry.missing_sources,
+        )
+        .await
         {
-            game_entry.expansions.append(&mut digests);
+            Some(Ok(Some(tech_info))) => {
+                if let Err(status) =
+                    firestore::tech_info::write(firestore, game_entry.id, &tech_info).await
+                {
+                    warn!("{status}");
+                }
+                game_entry.tech_info = Some(tech_info);
+            }
+            Some(Ok(None)) => {}
+            Some(Err(status)) => source_failed(
+                ResolveSource::PcGamingWiki,
+                status,
+                &mut game_entry.missing_sources,
+            ),
+            None => {}
         }
     }
-    if !igdb_game.dlcs.is_empty() {
-        if let Ok(digests) = get_digests(connection, firestore, &igdb_game.dlcs).await {
-            game_entry.dlcs = digests;
+
+    // Games without a Wikipedia link on IGDB may have a page that was
+    // discovered in an earlier resolve.
+    if game_entry.get_wikipedia_url().is_none() {
+        match firestore::wikipedia::read(firestore, game_entry.id).await {
+            Ok(page) => game_entry.websites.push(Website {
+                url: page.url,
+                authority: WebsiteAuthority::Wikipedia,
+            }),
+            Err(Status::NotFound(_)) => {}
+            Err(status) => warn!("Wikipedia page lookup failed: {status}"),
         }
     }
-    if !igdb_game.remakes.is_empty() {
-        if let Ok(digests) = get_digests(connection, firestore, &igdb_game.remakes).await {
-            game_entry.remakes = digests;
+
+    // Wikipedia is scraped off the critical path, only for games that have no
+    // other score or no companies.
+    if game_entry.needs_wikipedia() {
+        game_entry.missing_sources.push(ResolveSource::Wikipedia);
+    }
+
+    Ok(())
+}
+
+/// Time budgets for the external sources that are queried while resolving a
+/// game. Sources that do not respond within their budget are skipped and the
+/// resolve returns a partial GameEntry.
+#[derive(Clone, Debug)]
+pub struct ResolveBudgets {
+    pub steam_data: Duration,
+    pub steam_scrape: Duration,
+    pub steam_spy: Duration,
+    pub metacritic: Duration,
+    pub hltb: Duration,
+    pub pcgw: Duration,
+}
+
+impl Default for ResolveBudgets {
+    fn default() -> Self {
+        ResolveBudgets {
+            steam_data: Duration::from_secs(10),
+            steam_scrape: Duration::from_secs(5),
+            steam_spy: Duration::from_secs(5),
+            metacritic: Duration::from_secs(5),
+            hltb: Duration::from_secs(5),
+            pcgw: Duration::from_secs(5),
         }
     }
-    if !igdb_game.remasters.is_empty() {
-        if let Ok(digests) = get_digests(connection, firestore, &igdb_game.remasters).await {
-            game_entry.remasters = digests;
+}
+
+impl ResolveBudgets {
+    /// Returns budgets that allow `budget` for every external source.
+    pub fn uniform(budget: Duration) -> Self {
+        ResolveBudgets {
+            steam_data: budget,
+            steam_scrape: budget,
+            steam_spy: budget,
+            metacritic: budget,
+            hltb: budget,
+            pcgw: budget,
         }
     }
-    if matches!(
-        game_entry.category,
-        GameCategory::Bundle | GameCategory::Version
-    ) {
-        let game_ids = get_bundle_games_ids(connection, game_entry.id)
-            .await?
-            .into_iter()
-            .map(|e| e.id)
-            .collect_vec();
+}
 
-        if let Ok(digests) = get_digests(connection, firestore, &game_ids).await {
-            game_entry.contents = digests;
+/// Returns true if cached PCGamingWiki details are due for a refresh.
+fn is_stale(tech_info: &TechInfo) -> bool {
+    Utc::now().timestamp() - tech_info.last_updated > TECH_INFO_TTL_SECS
+}
+
+// PCGamingWiki pages change rarely after release.
+const TECH_INFO_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Awaits the spawned task of an external `source` for at most `budget`.
+///
+/// If the task fails or does not complete in time it is aborted, the `source`
+/// is added in `missing_sources` and None is returned.
+async fn await_source<T>(
+    mut handle: JoinHandle<T>,
+    budget: Duration,
+    source: ResolveSource,
+    missing_sources: &mut Vec<ResolveSource>,
+) -> Option<T> {
+    match tokio::time::timeout(budget, &mut handle).await {
+        Ok(Ok(result)) => Some(result),
+        Ok(Err(status)) => {
+            warn!("{source:?} task failed: {status}");
+            missing_sources.push(source);
+            None
+        }
+        Err(_) => {
+            warn!("{source:?} did not respond within {budget:?}");
+            handle.abort();
+            missing_sources.push(source);
+            None
         }
     }
+}
 
-    if let Some(handle) = steam_handle {
-        match handle.await {
-            Ok(result) => {
-                if let Some(steam_scrape_data) = result {
-                    if let Some(steam_data) = &mut game_entry.steam_data {
-                        steam_data.user_tags = steam_scrape_data.user_tags;
-                    }
-                }
-            }
-            Err(status) => warn!("{status}"),
+/// Records `source` for backfill if it failed with an error that may go away
+/// when retried. Sources that failed permanently are not backfilled.
+fn source_failed(source: ResolveSource, status: Status, missing_sources: &mut Vec<ResolveSource>) {
+    match status.is_retryable() {
+        true => {
+            warn!("{source:?} failed: {status}");
+            missing_sources.push(source);
         }
+        false => warn!("{source:?} failed permanently: {status}"),
     }
+}
 
-    Ok(())
+/// Awaits `lookup` only if there are `ids` to look up.
+async fn unless_empty<T>(
+    ids: &[u64],
+    lookup: impl Future<Output = Result<T, Status>>,
+) -> Option<Result<T, Status>> {
+    match ids.is_empty() {
+        true => None,
+        false => Some(lookup.await),
+    }
+}
+
+/// Returns the digests of the games contained in bundle `bundle_id`, or None
+/// if they fail to read.
+async fn get_bundle_contents(
+    connection: &IgdbConnection,
+    firestore: &FirestoreApi,
+    bundle_id: u64,
+) -> Result<Option<Vec<GameDigest>>, Status> {
+    let game_ids = get_bundle_games_ids(connection, bundle_id)
+        .await?
+        .into_iter()
+        .map(|e| e.id)
+        .collect_vec();
+    Ok(get_digests(connection, firestore, &game_ids).await.ok())
 }
 
 /// Returns IgdbGames included in the bundle of `bundle_id`.
@@ -368,6 +693,47 @@ pub async fn get_cover(connection: &IgdbConnection, id: u64) -> Result<Option<Im
     Ok(result.into_iter().next())
 }
 
+/// Returns game covers keyed by their id from the igdb/covers endpoint.
+#[instrument(level = "trace", skip(connection))]
+pub async fn get_covers(
+    connection: &IgdbConnection,
+    ids: &[u64],
+) -> Result<HashMap<u64, Image>, Status> {
+    let result: Vec<docs::IgdbCover> = post(
+        connection,
+        COVERS_ENDPOINT,
+        &format!(
+            "fields *; where id = ({}); limit 500;",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    )
+    .await?;
+
+    Ok(result
+        .into_iter()
+        .map(|cover| (cover.id, cover.image))
+        .collect())
+}
+
+/// Returns company logo based on id from the igdb/company_logos endpoint.
+#[instrument(level = "trace", skip(connection))]
+pub async fn get_company_logo(
+    connection: &IgdbConnection,
+    id: u64,
+) -> Result<Option<Image>, Status> {
+    let result: Vec<Image> = post(
+        connection,
+        COMPANY_LOGOS_ENDPOINT,
+        &format!("fields *; where id={id};"),
+    )
+    .await?;
+
+    Ok(result.into_iter().next())
+}
+
 #[instrument(level = "trace", skip(connection, firestore))]
 async fn get_digest(
     connection: &IgdbConnection,
@@ -429,6 +795,24 @@ pub async fn get_game(connection: &IgdbConnection, id: u64) -> Result<IgdbGame,
     }
 }
 
+/// Returns an IgdbGame based on its IGDB `slug`.
+#[instrument(level = "trace", skip(connection))]
+pub async fn get_game_by_slug(connection: &IgdbConnection, slug: &str) -> Result<IgdbGame, Status> {
+    let result: Vec<IgdbGame> = post(
+        connection,
+        GAMES_ENDPOINT,
+        &format!("fields *; where slug = \"{slug}\";"),
+    )
+    .await?;
+
+    match result.into_iter().next() {
+        Some(igdb_game) => Ok(igdb_game),
+        None => Err(Status::not_found(format!(
+            "Failed to retrieve game with slug='{slug}'"
+        ))),
+    }
+}
+
 #[instrument(level = "trace", skip(connection))]
 async fn get_games(connection: &IgdbConnection, ids: &[u64]) -> Result<Vec<IgdbGame>, Status> {
     post::<Vec<IgdbGame>>(
@@ -452,6 +836,47 @@ async fn get_keywords(firestore: &FirestoreApi, ids: &[u64]) -> Result<Vec<Strin
     Ok(result.documents.into_iter().map(|kw| kw.name).collect())
 }
 
+/// Returns game themes based on id from the igdb/themes endpoint.
+#[instrument(level = "trace", skip(connection))]
+pub async fn get_themes(
+    connection: &IgdbConnection,
+    ids: &[u64],
+) -> Result<Vec<docs::IgdbTheme>, Status> {
+    post(
+        connection,
+        THEMES_ENDPOINT,
+        &format!(
+            "fields *; where id = ({}); limit 500;",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    )
+    .await
+}
+
+/// Returns alternative game titles based on id from the
+/// igdb/alternative_names endpoint.
+#[instrument(level = "trace", skip(connection))]
+pub async fn get_alternative_names(
+    connection: &IgdbConnection,
+    ids: &[u64],
+) -> Result<Vec<docs::IgdbAlternativeName>, Status> {
+    Ok(post(
+        connection,
+        ALTERNATIVE_NAMES_ENDPOINT,
+        &format!(
+            "fields game, name; where id = ({}); limit 500;",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+    )
+    .await?)
+}
+
 /// Returns game screenshots based on id from the igdb/screenshots endpoint.
 #[instrument(level = "trace", skip(connection))]
 async fn get_artwork(connection: &IgdbConnection, ids: &[u64]) -> Result<Vec<Image>, Status> {
@@ -644,7 +1069,7 @@ async fn get_involved_companies(
 
     for involved_company in &involved_companies {
         if let Some(id) = involved_company.company {
-            match firestore::companies::read(firestore, id).await {
+            match firestore::companies::lookup(firestore, id).await {
                 Ok(igdb_company) => companies.push(CompanyDigest {
                     id: igdb_company.id,
                     name: igdb_company.name,
@@ -690,14 +1115,14 @@ async fn get_involved_companies(
     Ok(companies)
 }
 
-/// Returns the most appropriate game release timestamp. Trying to return the
-/// date of the earliest full release date.
+/// Returns the most appropriate game release timestamp and its precision.
+/// Trying to return the date of the earliest full release date.
 #[instrument(level = "trace", skip(connection, igdb_game, steam_data))]
 async fn get_release_timestamp(
     connection: &IgdbConnection,
     igdb_game: &IgdbGame,
     steam_data: &Option<SteamData>,
-) -> Result<Option<i64>, Status> {
+) -> Result<(Option<i64>, ReleasePrecision), Status> {
     let mut release_dates = match igdb_game.release_dates.is_empty() {
         false => {
             post::<Vec<docs::ReleaseDate>>(
@@ -753,18 +1178,44 @@ async fn get_release_timestamp(
         .iter()
         .filter(|release_date| release_date.date > 0);
 
-    let igdb_date = match release_dates.next() {
-        Some(release_date) => Some(release_date.date),
-        None => igdb_game.first_release_date,
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let (igdb_date, igdb_precision) = match release_dates.next() {
+        Some(release_date) => (
+            Some(release_date.date),
+            // Placeholder dates are sometimes entered as exact dates.
+            match ReleasePrecision::from_igdb_category(release_date.category) {
+                ReleasePrecision::Exact => {
+                    ReleasePrecision::from_placeholder(release_date.date, now as i64)
+                }
+                precision => precision,
+            },
+        ),
+        None => (
+            igdb_game.first_release_date,
+            match igdb_game.first_release_date {
+                Some(date) => ReleasePrecision::from_placeholder(date, now as i64),
+                None => ReleasePrecision::Unknown,
+            },
+        ),
     };
     let steam_date = match steam_data {
         Some(steam_data) => steam_data.release_timestamp(),
         None => None,
     };
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+
+    // Prefer Steam's estimate for upcoming games when IGDB only knows the year.
+    if let Some((steam_estimate, steam_precision)) = steam_data
+        .as_ref()
+        .and_then(|steam_data| steam_data.release_estimate())
+    {
+        if igdb_precision <= ReleasePrecision::Year && steam_precision > igdb_precision {
+            return Ok((Some(steam_estimate), steam_precision));
+        }
+    }
 
     Ok(
         if igdb_date.is_none()
@@ -773,109 +1224,147 @@ async fn get_release_timestamp(
                     || igdb_date.unwrap_or_default() == 0
                     || (igdb_date.unwrap_or_default() > steam_date.unwrap_or_default()))
         {
-            steam_date
+            (
+                steam_date,
+                match steam_date {
+                    Some(_) => ReleasePrecision::Exact,
+                    None => ReleasePrecision::Unknown,
+                },
+            )
         } else {
-            igdb_date
+            (igdb_date, igdb_precision)
         },
     )
 }
 
+/// Propagates a fresh digest of `game_entry` to the companies and collections
+/// that reference it.
+///
+/// If the game is no longer of a main category or it is archived its digest is
+/// removed instead.
+#[instrument(
+    level = "trace",
+    skip(firestore, game_entry),
+    fields(
+        game_id = %game_entry.id,
+        game_name = %game_entry.name,
+    )
+)]
+pub async fn update_digests(firestore: &FirestoreApi, game_entry: &GameEntry) {
+    let keep = game_entry.is_main_category() && !game_entry.archived;
+    let mut batch = DigestBatch::default();
+    update_companies(&mut batch, game_entry, keep);
+    update_collections(&mut batch, game_entry, keep);
+    digest_writes::submit(firestore, batch).await;
+}
+
+/// Removes the digest of `game_entry` from all companies and collections that
+/// reference it.
+#[instrument(
+    level = "trace",
+    skip(firestore, game_entry),
+    fields(
+        game_id = %game_entry.id,
+        game_name = %game_entry.name,
+    )
+)]
+pub async fn remove_digests(firestore: &FirestoreApi, game_entry: &GameEntry) {
+    let mut batch = DigestBatch::default();
+    update_companies(&mut batch, game_entry, false);
+    update_collections(&mut batch, game_entry, false);
+    digest_writes::submit(firestore, batch).await;
+}
+
 /// Make sure that any companies involved in the game are updated to include it.
-#[instrument(level = "trace", skip(firestore, game_entry))]
-async fn update_companies(firestore: &FirestoreApi, game_entry: &GameEntry) {
+fn update_companies(batch: &mut DigestBatch, game_entry: &GameEntry, keep: bool) {
+    let digest = GameDigest::from(game_entry.clone());
+
     for (companies, company_role) in [
         (&game_entry.developers, CompanyRole::Developer),
         (&game_entry.publishers, CompanyRole::Publisher),
     ] {
         for company in companies {
-            let company = match firestore::companies::read(&firestore, company.id).await {
-                // Update game in company.
-                Ok(mut company) => {
-                    update_digest(
-                        match company_role {
-                            CompanyRole::Developer => &mut company.developed,
-                            CompanyRole::Publisher => &mut company.published,
-                            _ => panic!("Unexpected company role"),
-                        },
-                        GameDigest::from(game_entry.clone()),
-                    );
-                    company
-                }
-                // Company was missing.
-                Err(Status::NotFound(_)) => Company {
-                    id: company.id,
-                    name: company.name.clone(),
-                    slug: company.slug.clone(),
-                    developed: match company_role {
-                        CompanyRole::Developer => vec![GameDigest::from(game_entry.clone())],
-                        _ => vec![],
-                    },
-                    published: match company_role {
-                        CompanyRole::Publisher => vec![GameDigest::from(game_entry.clone())],
-                        _ => vec![],
-                    },
-                    ..Default::default()
+            batch.add(
+                DigestDoc::Company(company.id),
+                &company.name,
+                &company.slug,
+                DigestUpdate {
+                    digest: digest.clone(),
+                    keep,
+                    role: company_role.clone(),
                 },
-                Err(status) => {
-                    warn!("Failed to read company={}: {status}", company.id);
-                    continue;
-                }
-            };
-
-            if let Err(status) = firestore::companies::write(&firestore, &company).await {
-                warn!("Failed to write company={}: {status}", company.id)
-            }
+            );
         }
     }
 }
 
 /// Update collections / franchises in the game with a fresh digest.
-#[instrument(level = "trace", skip(firestore, game_entry))]
-async fn update_collections(firestore: &FirestoreApi, game_entry: &GameEntry) {
-    for (collections, collection_type) in [
-        (&game_entry.collections, CollectionType::Collection),
-        (&game_entry.franchises, CollectionType::Franchise),
-    ] {
-        for collection in collections {
-            let collection = match read_collection(&firestore, collection_type, collection.id).await
-            {
-                Ok(mut collection) => {
-                    update_digest(&mut collection.games, GameDigest::from(game_entry.clone()));
-                    collection
-                }
-                Err(Status::NotFound(_)) => {
-                    // Collection was missing.
-                    Collection {
-                        id: collection.id,
-                        name: collection.name.clone(),
-                        slug: collection.slug.clone(),
-                        games: vec![GameDigest::from(game_entry.clone())],
-                        ..Default::default()
-                    }
-                }
-                Err(status) => {
-                    warn!("Failed to read collection={}: {status}", collection.id);
-                    continue;
-                }
-            };
-
-            if let Err(status) = write_collection(&firestore, collection_type, &collection).await {
-                warn!("Failed to write collection={}: {status}", collection.id);
-            }
-        }
+fn update_collections(batch: &mut DigestBatch, game_entry: &GameEntry, keep: bool) {
+    let digest = GameDigest::from(game_entry.clone());
+
+    for collection in &game_entry.collections {
+        batch.add(
+            DigestDoc::Collection(collection.id),
+            &collection.name,
+            &collection.slug,
+            DigestUpdate {
+                digest: digest.clone(),
+                keep,
+                role: CompanyRole::Unknown,
+            },
+        );
+    }
+    for franchise in &game_entry.franchises {
+        batch.add(
+            DigestDoc::Franchise(franchise.id),
+            &franchise.name,
+            &franchise.slug,
+            DigestUpdate {
+                digest: digest.clone(),
+                keep,
+                role: CompanyRole::Unknown,
+            },
+        );
     }
 }
 
-fn update_digest(digests: &mut Vec<GameDigest>, digest: GameDigest) {
-    match digests.iter_mut().find(|game| game.id == digest.id) {
-        // Update game in collection.
-        Some(game) => *game = digest,
+/// Updates `digests` with a fresh `digest` of a game. If `keep` is false the
+/// game is removed from `digests` instead.
+///
+/// The update is idempotent so that concurrent or repeated resolves of the
+/// same game converge to a single digest entry. Returns true if `digests` was
+/// modified.
+pub(super) fn update_digest(digests: &mut Vec<GameDigest>, digest: GameDigest, keep: bool) -> bool {
+    match digests.iter().position(|game| game.id == digest.id) {
+        Some(pos) => {
+            // Drop any duplicate entries of the same game.
+            let mut i = 0;
+            digests.retain(|game| {
+                let retain = i <= pos || game.id != digest.id;
+                i += 1;
+                retain
+            });
+
+            match keep {
+                // Update game in collection.
+                true => digests[pos] = digest,
+                // Game is no longer tracked.
+                false => {
+                    digests.remove(pos);
+                }
+            }
+            true
+        }
         // Game was missing from the collection.
-        None => digests.push(digest),
+        None if keep => {
+            digests.push(digest);
+            true
+        }
+        None => false,
     }
 }
 
-async fn read_collection(
+pub(super) async fn read_collection(
     firestore: &FirestoreApi,
     collection_type: CollectionType,
     id: u64,
@@ -887,7 +1376,7 @@ async fn read_collection(
     }
 }
 
-async fn write_collection(
+pub(super) async fn write_collection(
     firestore: &FirestoreApi,
     collection_type: CollectionType,
     collection: &Collection,
@@ -906,9 +1395,72 @@ pub const FRANCHISES_ENDPOINT: &str = "franchises";
 pub const COMPANIES_ENDPOINT: &str = "companies";
 pub const GENRES_ENDPOINT: &str = "genres";
 pub const KEYWORDS_ENDPOINT: &str = "keywords";
+pub const THEMES_ENDPOINT: &str = "themes";
+pub const ALTERNATIVE_NAMES_ENDPOINT: &str = "alternative_names";
 const RELEASE_DATES_ENDPOINT: &str = "release_dates";
 const COVERS_ENDPOINT: &str = "covers";
+const COMPANY_LOGOS_ENDPOINT: &str = "company_logos";
 const ARTWORKS_ENDPOINT: &str = "artworks";
 const SCREENSHOTS_ENDPOINT: &str = "screenshots";
 const WEBSITES_ENDPOINT: &str = "websites";
 const INVOLVED_COMPANIES_ENDPOINT: &str = "involved_companies";
+
+// 2000-01-01, roughly when Metacritic started covering games.
+const METACRITIC_LAUNCH: i64 = 946684800;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(id: u64, name: &str) -> GameDigest {
+        GameDigest {
+            id,
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn update_digest_adds_missing_game() {
+        let mut digests = vec![digest(1, "a")];
+
+        assert!(update_digest(&mut digests, digest(2, "b"), true));
+        assert_eq!(digests.len(), 2);
+    }
+
+    #[test]
+    fn update_digest_replaces_existing_game() {
+        let mut digests = vec![digest(1, "a"), digest(2, "b")];
+
+        assert!(update_digest(&mut digests, digest(1, "c"), true));
+        assert_eq!(digests.len(), 2);
+        assert_eq!(digests[0].name, "c");
+    }
+
+    #[test]
+    fn update_digest_removes_duplicates() {
+        let mut digests = vec![digest(1, "a"), digest(2, "b"), digest(1, "a")];
+
+        assert!(update_digest(&mut digests, digest(1, "c"), true));
+        assert_eq!(digests.len(), 2);
+        assert_eq!(digests[0].name, "c");
+        assert_eq!(digests[1].id, 2);
+    }
+
+    #[test]
+    fn update_digest_prunes_untracked_game() {
+        let mut digests = vec![digest(1, "a"), digest(2, "b"), digest(1, "a")];
+
+        assert!(update_digest(&mut digests, digest(1, "a"), false));
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].id, 2);
+    }
+
+    #[test]
+    fn update_digest_ignores_missing_untracked_game() {
+        let mut digests = vec![digest(2, "b")];
+
+        assert!(!update_digest(&mut digests, digest(1, "a"), false));
+        assert_eq!(digests.len(), 1);
+    }
+}
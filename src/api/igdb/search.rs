@@ -1,18 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     api::{FirestoreApi, IgdbApi},
-    documents::{GameDigest, GameEntry},
+    documents::{GameDigest, GameEntry, Image},
     library::firestore,
     Status,
 };
+use chrono::Utc;
 use itertools::Itertools;
-use tracing::{instrument, trace_span, warn, Instrument};
+use tracing::{instrument, warn};
 
 use super::{
     backend::post,
     ranking,
-    resolve::{get_cover, GAMES_ENDPOINT},
+    resolve::{get_alternative_names, get_covers, GAMES_ENDPOINT},
     IgdbGame,
 };
 
@@ -53,74 +54,203 @@ impl IgdbSearch {
             .collect_vec())
     }
 
+    /// Returns the single best match for `title` preferring games that are
+    /// already in the espy catalog.
+    ///
+    /// The catalog is searched first and IGDB is only queried if it has no
+    /// unambiguous match.
+    ///
+    /// Candidates need to match the title closely and be released in `year` if
+    /// one is provided. Ties that cannot be resolved are reported as not found,
+    /// as no answer is preferable to a wrong one.
+    #[instrument(level = "trace", skip(self, firestore))]
+    pub async fn lookup(
+        &self,
+        firestore: &FirestoreApi,
+        title: &str,
+        year: Option<i32>,
+    ) -> Result<GameEntry, Status> {
+        match lookup_catalog(firestore, title, year).await {
+            Ok(Some(game_entry)) => return Ok(game_entry),
+            Ok(None) => {}
+            Err(status) => warn!("Catalog lookup of '{title}' failed: {status}"),
+        }
+
+        let mut igdb_games = self.search(title).await?;
+        if let Some(year) = year {
+            igdb_games.retain(|game| game.release_year() == year);
+        }
+
+        let aliases = self.alternative_names(&igdb_games).await;
+        let mut candidates = ranking::best_matches(title, igdb_games, &aliases, LOOKUP_THRESHOLD);
+
+        let candidate_ids = candidates.iter().map(|e| e.id).collect_vec();
+        let mut result = firestore::games::batch_read(firestore, &candidate_ids).await?;
+
+        match (result.documents.len(), candidates.len()) {
+            (1, _) => Ok(result.documents.remove(0)),
+            (0, 1) => {
+                let igdb_game = candidates.remove(0);
+                let cover = match igdb_game.cover {
+                    Some(id) => self.igdb.get_cover(id).await?,
+                    None => None,
+                };
+                let mut game_entry = GameEntry::from(igdb_game);
+                game_entry.cover = cover;
+                Ok(game_entry)
+            }
+            (0, 0) => Err(Status::not_found(format!("No match found for '{title}'"))),
+            _ => Err(Status::not_found(format!(
+                "Ambiguous match for '{title}' between {} candidates",
+                candidates.len()
+            ))),
+        }
+    }
+
     /// Returns IgdbGames that match the `title` by searching in IGDB.
     #[instrument(level = "trace", skip(self))]
     pub async fn search_by_title(&self, title: &str) -> Result<Vec<IgdbGame>, Status> {
-        Ok(ranking::sorted_by_relevance(
-            title,
-            self.search(title).await?,
-        ))
+        let igdb_games = self.search(title).await?;
+        let aliases = self.alternative_names(&igdb_games).await;
+        Ok(ranking::sorted_by_relevance(title, igdb_games, &aliases))
     }
 
     /// Returns candidate GameEntries by searching IGDB based on game title.
     ///
     /// The returned GameEntries are shallow lookups similar to
-    /// `search_by_title()`, but have their cover image resolved.
+    /// `search_by_title()`, but have their cover image resolved. Abandoned
+    /// games are left out unless `include_archived` is set.
     #[instrument(level = "trace", skip(self))]
     pub async fn search_by_title_with_cover(
         &self,
         title: &str,
         base_games_only: bool,
+        include_archived: bool,
     ) -> Result<Vec<GameEntry>, Status> {
         let mut igdb_games = self.search(title).await?;
         if base_games_only {
             igdb_games.retain(|game| game.parent_game.is_none());
         }
+        if !include_archived {
+            let now = Utc::now().timestamp();
+            igdb_games.retain(|game| !game.is_abandoned(now));
+        }
 
-        let igdb_games = ranking::sorted_by_relevance_with_threshold(title, igdb_games, 1.0);
+        let aliases = self.alternative_names(&igdb_games).await;
+        let igdb_games =
+            ranking::sorted_by_relevance_with_threshold(title, igdb_games, &aliases, 1.0);
 
         // TODO: get covers from firestore intead of IGDB.
-        let connection = self.igdb.connection()?;
-        let mut handles = vec![];
-        for game in igdb_games {
-            let connection = Arc::clone(&connection);
-            handles.push(tokio::spawn(
-                async move {
-                    let cover = match game.cover {
-                        Some(id) => match get_cover(&connection, id).await {
-                            Ok(cover) => cover,
-                            Err(e) => {
-                                warn!("Failed to retrieve cover: {e}");
-                                None
-                            }
-                        },
-                        None => None,
-                    };
-
-                    let mut game_entry = GameEntry::from(game);
-                    game_entry.cover = cover;
-                    game_entry
-                }
-                .instrument(trace_span!("spawn_get_cover")),
-            ));
-        }
+        let covers = self.covers(&igdb_games).await;
 
-        Ok(futures::future::join_all(handles)
-            .await
+        Ok(igdb_games
             .into_iter()
-            .filter_map(|x| x.ok())
-            .collect::<Vec<_>>())
+            .map(|game| {
+                let cover = game.cover.and_then(|id| covers.get(&id).cloned());
+                let mut game_entry = GameEntry::from(game);
+                game_entry.cover = cover;
+                game_entry
+            })
+            .collect())
+    }
+
+    /// Returns covers of `igdb_games` keyed by cover id in a single IGDB
+    /// request. Games without a cover are still valid candidates, so errors
+    /// are not propagated.
+    #[instrument(level = "trace", skip(self, igdb_games))]
+    async fn covers(&self, igdb_games: &[IgdbGame]) -> HashMap<u64, Image> {
+        let ids = igdb_games
+            .iter()
+            .filter_map(|game| game.cover)
+            .collect_vec();
+        if ids.is_empty() {
+            return HashMap::new();
+        }
+
+        let connection = match self.igdb.connection() {
+            Ok(connection) => connection,
+            Err(status) => {
+                warn!("{status}");
+                return HashMap::new();
+            }
+        };
+        match get_covers(&connection, &ids).await {
+            Ok(covers) => covers,
+            Err(status) => {
+                warn!("Failed to retrieve covers: {status}");
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Returns alternative names of `igdb_games` keyed by game id. Failing to
+    /// retrieve them only degrades ranking, so errors are not propagated.
+    #[instrument(level = "trace", skip(self, igdb_games))]
+    async fn alternative_names(&self, igdb_games: &[IgdbGame]) -> HashMap<u64, Vec<String>> {
+        let ids = igdb_games
+            .iter()
+            .flat_map(|game| game.alternative_names.iter().cloned())
+            .collect_vec();
+        if ids.is_empty() {
+            return HashMap::new();
+        }
+
+        let connection = match self.igdb.connection() {
+            Ok(connection) => connection,
+            Err(status) => {
+                warn!("{status}");
+                return HashMap::new();
+            }
+        };
+        match get_alternative_names(&connection, &ids).await {
+            Ok(names) => names
+                .into_iter()
+                .map(|alias| (alias.game, alias.name))
+                .into_group_map(),
+            Err(status) => {
+                warn!("Failed to retrieve alternative names: {status}");
+                HashMap::new()
+            }
+        }
     }
 
     #[instrument(level = "trace", skip(self))]
     async fn search(&self, title: &str) -> Result<Vec<IgdbGame>, Status> {
         let title = title.replace("\"", "");
         let connection = self.igdb.connection()?;
+        let platforms = self.igdb.platform_scope().igdb_condition();
         post::<Vec<IgdbGame>>(
             &connection,
             GAMES_ENDPOINT,
-            &format!("search \"{title}\"; fields *; where platforms = (6,13);"),
+            &format!("search \"{title}\"; fields *; where {platforms};"),
         )
         .await
     }
 }
+
+/// Returns the single catalog game that matches `title` and `year`, if any.
+async fn lookup_catalog(
+    firestore: &FirestoreApi,
+    title: &str,
+    year: Option<i32>,
+) -> Result<Option<GameEntry>, Status> {
+    let mut game_entries =
+        firestore::games::search_by_title(firestore, title, CATALOG_CANDIDATES).await?;
+    if let Some(year) = year {
+        game_entries.retain(|game_entry| game_entry.release_year() == year);
+    }
+
+    let mut matches = ranking::best_catalog_matches(title, game_entries, LOOKUP_THRESHOLD);
+    match matches.len() {
+        1 => Ok(Some(matches.remove(0))),
+        _ => Ok(None),
+    }
+}
+
+// Number of catalog games whose slug prefix matches a lookup title that are
+// ranked against it.
+const CATALOG_CANDIDATES: u32 = 20;
+
+// Normalized edit distance that a lookup candidate is allowed to have from the
+// requested title.
+const LOOKUP_THRESHOLD: f64 = 0.15;
@@ -1,23 +1,33 @@
 use crate::{
     api::FirestoreApi,
-    documents::{GameDigest, GameEntry, Image, StoreEntry},
+    documents::{GameDigest, GameEntry, Image, PlatformScope, StoreEntry},
     library::firestore,
     logging::{IgdbCounters, IgdbResolveCounter},
-    util::rate_limiter::RateLimiter,
+    util::{http_client, rate_limiter::RateLimiter},
     webhooks::filtering::{GameFilter, RejectionReason},
     Status,
 };
 use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use tracing::{instrument, warn};
 
-use super::{backend::post, docs, resolve::*, IgdbConnection, IgdbGame};
+use super::{
+    backend::post, docs, resolve::*, single_flight::InFlightResolves, IgdbConnection, IgdbGame,
+};
 
 #[derive(Clone)]
 pub struct IgdbApi {
     secret: String,
     client_id: String,
     connection: Option<Arc<IgdbConnection>>,
+    budgets: ResolveBudgets,
+    platform_scope: PlatformScope,
+
+    /// Shared by clones, so that all resolves of a service are deduplicated.
+    in_flight: Arc<InFlightResolves>,
 }
 
 impl IgdbApi {
@@ -26,9 +36,28 @@ impl IgdbApi {
             secret: String::from(secret),
             client_id: String::from(client_id),
             connection: None,
+            budgets: ResolveBudgets::default(),
+            platform_scope: PlatformScope::default(),
+            in_flight: Arc::new(InFlightResolves::default()),
         }
     }
 
+    /// Overrides the time budgets of external sources during resolves. Needs
+    /// to be set before `connect()`.
+    pub fn set_resolve_budgets(&mut self, budgets: ResolveBudgets) {
+        self.budgets = budgets;
+    }
+
+    /// Overrides the platforms of games that are retrieved from IGDB, e.g.
+    /// with the scope document of the deployment.
+    pub fn set_platform_scope(&mut self, platform_scope: PlatformScope) {
+        self.platform_scope = platform_scope;
+    }
+
+    pub fn platform_scope(&self) -> &PlatformScope {
+        &self.platform_scope
+    }
+
     /// Authenticate with twtich/igdb OAuth2 server and retrieve session token.
     /// Authentication is valid for the lifetime of this instane or until the
     /// retrieved token expires.
@@ -38,7 +67,7 @@ impl IgdbApi {
             self.client_id, self.secret
         );
 
-        let resp = reqwest::Client::new()
+        let resp = http_client::shared()
             .post(&uri)
             .send()
             .await?
@@ -48,7 +77,11 @@ impl IgdbApi {
         self.connection = Some(Arc::new(IgdbConnection {
             client_id: self.client_id.clone(),
             oauth_token: resp.access_token,
+            oauth_expires_at: SystemTime::now()
+                + Duration::from_secs(resp.expires_in.max(0) as u64),
             qps: RateLimiter::new(4, Duration::from_secs(1), 6),
+            budgets: self.budgets.clone(),
+            client: http_client::shared(),
         }));
 
         Ok(())
@@ -74,6 +107,38 @@ impl IgdbApi {
         get_game(&connection, id).await
     }
 
+    /// Returns IgdbGames for `ids` that only have their category and status
+    /// fields populated. Up to 500 ids can be looked up in a single request.
+    #[instrument(level = "trace", skip(self, ids))]
+    pub async fn get_categories(&self, ids: &[u64]) -> Result<Vec<IgdbGame>, Status> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let connection = self.connection()?;
+        post(
+            &connection,
+            GAMES_ENDPOINT,
+            &format!(
+                "fields name, category, status, version_parent; where id = ({}); limit 500;",
+                ids.iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        )
+        .await
+    }
+
+    /// Returns an IgdbGame based on its IGDB slug. Accepts also a full IGDB
+    /// game URL, e.g. 'https://www.igdb.com/games/{slug}'.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_by_slug(&self, slug_or_url: &str) -> Result<IgdbGame, Status> {
+        let slug = extract_slug(slug_or_url)?;
+        let connection = self.connection()?;
+        get_game_by_slug(&connection, slug).await
+    }
+
     /// Returns an IgdbGame based on external id info in IGDB.
     #[instrument(level = "trace", skip(self))]
     pub async fn get_by_store_entry(&self, store_entry: &StoreEntry) -> Result<IgdbGame, Status> {
@@ -115,6 +180,12 @@ impl IgdbApi {
         get_cover(&connection, id).await
     }
 
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_company_logo(&self, id: u64) -> Result<Option<Image>, Status> {
+        let connection = self.connection()?;
+        get_company_logo(&connection, id).await
+    }
+
     /// Returns a GameDigest for an IgdbGame.
     #[instrument(
         level = "trace",
@@ -147,6 +218,22 @@ impl IgdbApi {
         &self,
         firestore: Arc<FirestoreApi>,
         igdb_game: IgdbGame,
+    ) -> Result<GameEntry, Status> {
+        let igdb = self.clone();
+        self.in_flight
+            .run(igdb_game.id, igdb_game.updated_at, async move {
+                igdb.resolve_and_store(firestore, igdb_game).await
+            })
+            .await
+    }
+
+    /// Fully resolves `igdb_game` and stores it in Firestore. Callers should
+    /// go through `resolve()`, so that concurrent resolves of the same game
+    /// are deduplicated.
+    async fn resolve_and_store(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        igdb_game: IgdbGame,
     ) -> Result<GameEntry, Status> {
         let connection = self.connection()?;
 
@@ -169,6 +256,8 @@ impl IgdbApi {
 
         if let Err(e) = firestore::games::write(&firestore, &mut game_entry).await {
             warn!("Failed to save '{}' in Firestore: {e}", game_entry.name);
+        } else if let Err(e) = firestore::backfill_queue::add(&firestore, &game_entry).await {
+            warn!("Failed to schedule backfill for '{}': {e}", game_entry.name);
         }
 
         Ok(game_entry)
@@ -218,6 +307,28 @@ impl IgdbApi {
     }
 }
 
+/// Returns the game slug from an IGDB game URL or the input itself if it is
+/// already a slug.
+fn extract_slug(slug_or_url: &str) -> Result<&str, Status> {
+    let input = slug_or_url.trim();
+    let input = input.split(['?', '#']).next().unwrap_or_default();
+    let slug = match input.split_once("/games/") {
+        Some((_, path)) => path.split('/').next().unwrap_or_default(),
+        None => input.trim_end_matches('/'),
+    };
+
+    match !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        true => Ok(slug),
+        false => Err(Status::invalid_argument(format!(
+            "'{slug_or_url}' is not a valid IGDB slug or URL"
+        ))),
+    }
+}
+
 pub const TWITCH_OAUTH_URL: &str = "https://id.twitch.tv/oauth2/token";
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -225,3 +336,33 @@ struct TwitchOAuthResponse {
     access_token: String,
     expires_in: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_slug_from_slug() {
+        assert_eq!(extract_slug("hades").unwrap(), "hades");
+        assert_eq!(extract_slug(" disco-elysium ").unwrap(), "disco-elysium");
+    }
+
+    #[test]
+    fn extract_slug_from_url() {
+        assert_eq!(
+            extract_slug("https://www.igdb.com/games/the-witcher-3-wild-hunt").unwrap(),
+            "the-witcher-3-wild-hunt"
+        );
+        assert_eq!(
+            extract_slug("https://www.igdb.com/games/hades/?tab=media#screenshots").unwrap(),
+            "hades"
+        );
+    }
+
+    #[test]
+    fn extract_slug_invalid() {
+        assert!(extract_slug("").is_err());
+        assert!(extract_slug("https://www.igdb.com/games/").is_err());
+        assert!(extract_slug("hades\" | fields *").is_err());
+    }
+}
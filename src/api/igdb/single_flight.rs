@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Mutex, PoisonError},
+};
+
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+
+use crate::{documents::GameEntry, logging::IgdbCounters, Status};
+
+type SharedResolve = Shared<BoxFuture<'static, Result<GameEntry, Status>>>;
+
+/// Resolves of games that are in flight, keyed by game id.
+///
+/// Concurrent resolves of the same game, e.g. a webhook update while a user
+/// sync requests the same game, share a single resolve instead of racing on
+/// its writes.
+#[derive(Default)]
+pub struct InFlightResolves {
+    resolves: Mutex<HashMap<u64, Flight>>,
+}
+
+struct Flight {
+    shared: SharedResolve,
+
+    // IGDB `updated_at` of the payload that is being resolved.
+    updated_at: Option<i64>,
+}
+
+impl InFlightResolves {
+    /// Returns the result of the in-flight resolve of `game_id` if there is
+    /// one, otherwise runs `resolve` and shares its result with any resolves
+    /// of the same game that start before it completes.
+    ///
+    /// If the in-flight resolve is of a payload older than `updated_at`,
+    /// `resolve` runs after it completes instead, so that newer payloads are
+    /// not lost and their writes land last.
+    pub async fn run<F>(
+        &self,
+        game_id: u64,
+        updated_at: Option<i64>,
+        resolve: F,
+    ) -> Result<GameEntry, Status>
+    where
+        F: Future<Output = Result<GameEntry, Status>> + Send + 'static,
+    {
+        let shared = {
+            let mut resolves = self.resolves.lock().unwrap_or_else(PoisonError::into_inner);
+            match resolves.get(&game_id) {
+                Some(flight) if updated_at <= flight.updated_at => {
+                    IgdbCounters::resolve_joined(game_id);
+                    flight.shared.clone()
+                }
+                flight => {
+                    let previous = flight.map(|flight| flight.shared.clone());
+                    let shared = async move {
+                        if let Some(previous) = previous {
+                            let _ = previous.await;
+                        }
+                        resolve.await
+                    }
+                    .boxed()
+                    .shared();
+                    resolves.insert(
+                        game_id,
+                        Flight {
+                            shared: shared.clone(),
+                            updated_at,
+                        },
+                    );
+                    shared
+                }
+            }
+        };
+
+        let result = shared.clone().await;
+
+        // Only the resolve that completed is removed, as a new one may have
+        // started for the same game since.
+        let mut resolves = self.resolves.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(current) = resolves.get(&game_id) {
+            if current.shared.ptr_eq(&shared) {
+                resolves.remove(&game_id);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn concurrent_resolves_share_one_flight() {
+        let in_flight = InFlightResolves::default();
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let resolve = |name: &str| {
+            let runs = Arc::clone(&runs);
+            let name = name.to_owned();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                Ok(GameEntry {
+                    id: 7,
+                    name,
+                    ..Default::default()
+                })
+            }
+        };
+
+        let (first, second) = tokio::join!(
+            in_flight.run(7, Some(1), resolve("first")),
+            in_flight.run(7, Some(1), resolve("second")),
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(first.map(|e| e.name).ok(), Some("first".to_owned()));
+        assert_eq!(second.map(|e| e.name).ok(), Some("first".to_owned()));
+
+        // Resolves that start after completion run again.
+        assert!(in_flight.run(7, Some(1), resolve("third")).await.is_ok());
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        // Resolves of newer payloads run again after the in-flight one.
+        let (older, newer) = tokio::join!(
+            in_flight.run(7, Some(1), resolve("older")),
+            in_flight.run(7, Some(2), resolve("newer")),
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+        assert_eq!(older.map(|e| e.name).ok(), Some("older".to_owned()));
+        assert_eq!(newer.map(|e| e.name).ok(), Some("newer".to_owned()));
+    }
+}
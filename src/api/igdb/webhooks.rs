@@ -3,7 +3,10 @@ use tracing::instrument;
 
 use super::{
     backend::create_webhook,
-    resolve::{EXTERNAL_GAMES_ENDPOINT, GAMES_ENDPOINT, GENRES_ENDPOINT, KEYWORDS_ENDPOINT},
+    resolve::{
+        COLLECTIONS_ENDPOINT, COMPANIES_ENDPOINT, EXTERNAL_GAMES_ENDPOINT, FRANCHISES_ENDPOINT,
+        GAMES_ENDPOINT, GENRES_ENDPOINT, KEYWORDS_ENDPOINT,
+    },
     IgdbApi,
 };
 
@@ -86,6 +89,54 @@ impl IgdbWebhooksApi {
             "update",
             secret,
         )
+        .await?;
+        create_webhook(
+            &connection,
+            COMPANIES_ENDPOINT,
+            &format!("{webhook_url}/companies"),
+            "create",
+            secret,
+        )
+        .await?;
+        create_webhook(
+            &connection,
+            COMPANIES_ENDPOINT,
+            &format!("{webhook_url}/companies"),
+            "update",
+            secret,
+        )
+        .await?;
+        create_webhook(
+            &connection,
+            COLLECTIONS_ENDPOINT,
+            &format!("{webhook_url}/collections"),
+            "create",
+            secret,
+        )
+        .await?;
+        create_webhook(
+            &connection,
+            COLLECTIONS_ENDPOINT,
+            &format!("{webhook_url}/collections"),
+            "update",
+            secret,
+        )
+        .await?;
+        create_webhook(
+            &connection,
+            FRANCHISES_ENDPOINT,
+            &format!("{webhook_url}/franchises"),
+            "create",
+            secret,
+        )
+        .await?;
+        create_webhook(
+            &connection,
+            FRANCHISES_ENDPOINT,
+            &format!("{webhook_url}/franchises"),
+            "update",
+            secret,
+        )
         .await
     }
 }
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    documents::{PriceComparison, ShopPrice},
+    util::http_client,
+    Status,
+};
+
+/// Client for IsThereAnyDeal that compares game prices across stores.
+pub struct ItadApi {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl ItadApi {
+    pub fn new(api_key: &str) -> ItadApi {
+        ItadApi {
+            api_key: String::from(api_key),
+            client: http_client::shared(),
+        }
+    }
+
+    /// Returns true if an API key is configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    /// Returns the ITAD plain, i.e. its game identifier, for a Steam app.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_plain_by_steam_id(&self, steam_appid: &str) -> Result<Option<String>, Status> {
+        self.get_plain(&[
+            ("shop", "steam"),
+            ("game_id", &format!("app/{steam_appid}")),
+        ])
+        .await
+    }
+
+    /// Returns the ITAD plain of a game by its title.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_plain_by_title(&self, title: &str) -> Result<Option<String>, Status> {
+        self.get_plain(&[("title", title)]).await
+    }
+
+    /// Returns the current best price and the historical low of a game in the
+    /// US store region.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_prices(&self, plain: &str) -> Result<PriceComparison, Status> {
+        let uri = format!(
+            "{ITAD_HOST}/v01/game/prices/?key={}&plains={plain}&region=us&country=US",
+            self.api_key
+        );
+        let current = self
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .json::<ItadResponse<HashMap<String, ItadPrices>>>()
+            .await?;
+
+        let uri = format!(
+            "{ITAD_HOST}/v01/game/lowest/?key={}&plains={plain}&region=us&country=US",
+            self.api_key
+        );
+        let lowest = self
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .json::<ItadResponse<HashMap<String, ItadLowest>>>()
+            .await?;
+
+        Ok(PriceComparison {
+            currency: String::from("USD"),
+            best: current
+                .data
+                .get(plain)
+                .and_then(|prices| {
+                    prices
+                        .list
+                        .iter()
+                        .min_by(|a, b| a.price_new.total_cmp(&b.price_new))
+                })
+                .map(|price| ShopPrice {
+                    shop: price.shop.name.clone(),
+                    price: to_cents(price.price_new),
+                    discount_percent: price.price_cut,
+                    url: Some(price.url.clone()),
+                    timestamp: None,
+                }),
+            historical_low: lowest.data.get(plain).and_then(|lowest| {
+                Some(ShopPrice {
+                    shop: lowest.shop.as_ref()?.name.clone(),
+                    price: to_cents(lowest.price?),
+                    discount_percent: lowest.cut.unwrap_or_default(),
+                    url: None,
+                    timestamp: lowest.added,
+                })
+            }),
+        })
+    }
+
+    async fn get_plain(&self, query: &[(&str, &str)]) -> Result<Option<String>, Status> {
+        let uri = format!("{ITAD_HOST}/v02/game/plain/?key={}", self.api_key);
+        let resp = self
+            .client
+            .get(&uri)
+            .query(query)
+            .send()
+            .await?
+            .json::<ItadResponse<ItadPlain>>()
+            .await?;
+        Ok(resp.data.plain)
+    }
+}
+
+fn to_cents(price: f64) -> u64 {
+    (price * 100.0).round() as u64
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadResponse<T> {
+    data: T,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadPlain {
+    #[serde(default)]
+    plain: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadPrices {
+    #[serde(default)]
+    list: Vec<ItadPrice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadPrice {
+    price_new: f64,
+
+    #[serde(default)]
+    price_cut: u64,
+
+    url: String,
+    shop: ItadShop,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadLowest {
+    #[serde(default)]
+    shop: Option<ItadShop>,
+
+    #[serde(default)]
+    price: Option<f64>,
+
+    #[serde(default)]
+    cut: Option<u64>,
+
+    #[serde(default)]
+    added: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ItadShop {
+    id: String,
+    name: String,
+}
+
+const ITAD_HOST: &str = "https://api.isthereanydeal.com";
@@ -1,6 +1,8 @@
 use soup::prelude::*;
 use tracing::warn;
 
+use crate::util::http_client;
+
 #[derive(Default, Clone, Debug)]
 pub struct MetacriticData {
     pub score: u64,
@@ -13,7 +15,7 @@ impl MetacriticApi {
     pub async fn get_score(slug: &str) -> Option<MetacriticData> {
         let uri = format!("https://www.metacritic.com/game/{slug}/");
 
-        let resp = match reqwest::get(&uri).await {
+        let resp = match http_client::shared().get(&uri).send().await {
             Ok(resp) => resp,
             Err(status) => {
                 warn!("{status}");
@@ -1,13 +1,23 @@
+mod bigquery;
 mod firestore;
 mod gog;
+mod hltb;
 mod igdb;
+mod itad;
 mod metacritic;
+mod pcgw;
 mod steam;
+mod wikidata;
 mod wikipedia_scrape;
 
-pub use firestore::FirestoreApi;
+pub use bigquery::{BigQueryApi, Column};
+pub use firestore::{FirestoreApi, FirestoreConfig};
 pub use gog::*;
+pub use hltb::HltbApi;
 pub use igdb::*;
+pub use itad::ItadApi;
 pub use metacritic::{MetacriticApi, MetacriticData};
+pub use pcgw::PcgwApi;
 pub use steam::*;
+pub use wikidata::WikidataApi;
 pub use wikipedia_scrape::{WikipediaScrape, WikipediaScrapeData};
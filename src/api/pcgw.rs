@@ -0,0 +1,170 @@
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{
+    documents::{FeatureSupport, TechInfo},
+    util::http_client,
+    Status,
+};
+
+pub struct PcgwApi {}
+
+impl PcgwApi {
+    /// Returns the technical details of a game on PCGamingWiki, looked up by
+    /// its `steam_appid` if it has one or by its `title` otherwise. Returns
+    /// None if PCGamingWiki has no page for the game.
+    #[instrument(level = "trace")]
+    pub async fn get_tech_info(
+        title: &str,
+        steam_appid: Option<u64>,
+    ) -> Result<Option<TechInfo>, Status> {
+        let condition = match steam_appid {
+            Some(steam_appid) => format!("Infobox_game.Steam_AppID HOLDS \"{steam_appid}\""),
+            None => format!("Infobox_game._pageName=\"{}\"", escape(title)),
+        };
+
+        let resp = http_client::shared()
+            .get(PCGW_API_URL)
+            .query(&[
+                ("action", "cargoquery"),
+                ("format", "json"),
+                ("limit", "1"),
+                ("tables", TABLES),
+                ("join_on", JOIN_ON),
+                ("fields", FIELDS),
+                ("where", condition.as_str()),
+            ])
+            .send()
+            .await?;
+        let text = resp.text().await?;
+        let resp = serde_json::from_str::<CargoQueryResponse>(&text).map_err(|e| {
+            let msg = format!(
+                "({title}) Parse error: {}\n PCGamingWiki response: {}",
+                e, &text
+            );
+            Status::internal(msg)
+        })?;
+
+        Ok(resp
+            .cargoquery
+            .into_iter()
+            .next()
+            .map(|row| row.title.into_tech_info(Utc::now().timestamp())))
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct CargoQueryResponse {
+    #[serde(default)]
+    cargoquery: Vec<CargoQueryRow>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct CargoQueryRow {
+    title: PcgwFields,
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct PcgwFields {
+    #[serde(default)]
+    ultrawide: Option<String>,
+
+    #[serde(default)]
+    controller: Option<String>,
+
+    #[serde(default)]
+    drm: Option<String>,
+
+    #[serde(default)]
+    cloud_steam: Option<String>,
+
+    #[serde(default)]
+    cloud_gog: Option<String>,
+
+    #[serde(default)]
+    cloud_epic: Option<String>,
+}
+
+impl PcgwFields {
+    fn into_tech_info(self, timestamp: i64) -> TechInfo {
+        let cloud = [&self.cloud_steam, &self.cloud_gog, &self.cloud_epic]
+            .into_iter()
+            .map(|value| support(value))
+            .collect::<Vec<_>>();
+
+        TechInfo {
+            last_updated: timestamp,
+            ultrawide: support(&self.ultrawide),
+            controller: support(&self.controller),
+            cloud_saves: match cloud {
+                cloud if cloud.contains(&FeatureSupport::Yes) => FeatureSupport::Yes,
+                cloud if cloud.contains(&FeatureSupport::No) => FeatureSupport::No,
+                _ => FeatureSupport::Unknown,
+            },
+            drm: self
+                .drm
+                .unwrap_or_default()
+                .split(',')
+                .map(|drm| drm.trim())
+                .filter(|drm| !drm.is_empty())
+                .map(|drm| drm.to_owned())
+                .collect(),
+        }
+    }
+}
+
+fn support(value: &Option<String>) -> FeatureSupport {
+    match value.as_deref().map(|value| value.trim().to_lowercase()) {
+        Some(value) => match value.as_str() {
+            "true" => FeatureSupport::Yes,
+            "false" => FeatureSupport::No,
+            "limited" => FeatureSupport::Limited,
+            "hackable" => FeatureSupport::Hackable,
+            _ => FeatureSupport::Unknown,
+        },
+        None => FeatureSupport::Unknown,
+    }
+}
+
+/// Escapes `text` for use in a Cargo string literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+const PCGW_API_URL: &str = "https://www.pcgamingwiki.com/w/api.php";
+
+const TABLES: &str = "Infobox_game,Video,Input,Availability,Cloud";
+const JOIN_ON: &str = "Infobox_game._pageID=Video._pageID,Infobox_game._pageID=Input._pageID,Infobox_game._pageID=Availability._pageID,Infobox_game._pageID=Cloud._pageID";
+const FIELDS: &str = "Video.Ultrawidescreen=ultrawide,Input.Controller_support=controller,Availability.Uses_DRM=drm,Cloud.Steam=cloud_steam,Cloud.GOG_Galaxy=cloud_gog,Cloud.Epic_Games_Launcher=cloud_epic";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_into_tech_info() {
+        let fields = PcgwFields {
+            ultrawide: Some("hackable".to_owned()),
+            controller: Some("true".to_owned()),
+            drm: Some("Steam, Denuvo".to_owned()),
+            cloud_steam: Some("false".to_owned()),
+            cloud_gog: Some("true".to_owned()),
+            cloud_epic: None,
+        };
+
+        let tech_info = fields.into_tech_info(1);
+        assert_eq!(tech_info.ultrawide, FeatureSupport::Hackable);
+        assert_eq!(tech_info.controller, FeatureSupport::Yes);
+        assert_eq!(tech_info.cloud_saves, FeatureSupport::Yes);
+        assert_eq!(tech_info.drm, vec!["Steam", "Denuvo"]);
+    }
+
+    #[test]
+    fn missing_fields_are_unknown() {
+        let tech_info = PcgwFields::default().into_tech_info(1);
+        assert_eq!(tech_info.ultrawide, FeatureSupport::Unknown);
+        assert_eq!(tech_info.cloud_saves, FeatureSupport::Unknown);
+        assert!(tech_info.drm.is_empty());
+    }
+}
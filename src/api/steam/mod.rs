@@ -1,7 +1,9 @@
 mod steam;
 mod steam_data;
 mod steam_scrape;
+mod steam_spy;
 
 pub use steam::SteamApi;
 pub use steam_data::SteamDataApi;
 pub use steam_scrape::{SteamScrape, SteamScrapeData};
+pub use steam_spy::{SteamSpyApi, SteamSpyData};
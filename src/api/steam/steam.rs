@@ -1,15 +1,18 @@
 use crate::{
-    documents::{SteamData, SteamScore, StoreEntry},
+    documents::{SteamData, SteamScore, StoreEntry, StorePrice},
     traits::Storefront,
+    util::http_client,
     Status,
 };
 use async_trait::async_trait;
+use chrono::Utc;
 use std::collections::HashMap;
 use tracing::{info, instrument};
 
 pub struct SteamApi {
     steam_key: String,
     steam_user_id: String,
+    client: reqwest::Client,
 }
 
 impl SteamApi {
@@ -17,15 +20,40 @@ impl SteamApi {
         SteamApi {
             steam_key: String::from(steam_key),
             steam_user_id: String::from(steam_user_id),
+            client: http_client::shared(),
         }
     }
 
+    /// Returns the store details of a Steam app in the store's default region.
+    /// Returns None if the store does not list the app, e.g. because it is
+    /// delisted or region-locked.
     #[instrument(level = "trace")]
-    pub async fn get_app_details(steam_appid: &str) -> Result<SteamData, Status> {
-        let uri =
-            format!("https://store.steampowered.com/api/appdetails?appids={steam_appid}&l=english");
+    pub async fn get_app_details(steam_appid: &str) -> Result<Option<SteamData>, Status> {
+        Self::app_details(steam_appid, None).await
+    }
+
+    /// Returns the store details of a Steam app in the store region of country
+    /// code `cc`. Returns None if the store does not list the app in the
+    /// region.
+    #[instrument(level = "trace")]
+    pub async fn get_regional_app_details(
+        steam_appid: &str,
+        cc: &str,
+    ) -> Result<Option<SteamData>, Status> {
+        Self::app_details(steam_appid, Some(cc)).await
+    }
+
+    async fn app_details(steam_appid: &str, cc: Option<&str>) -> Result<Option<SteamData>, Status> {
+        let uri = match cc {
+            Some(cc) => format!(
+                "https://store.steampowered.com/api/appdetails?appids={steam_appid}&l=english&cc={cc}"
+            ),
+            None => format!(
+                "https://store.steampowered.com/api/appdetails?appids={steam_appid}&l=english"
+            ),
+        };
 
-        let resp = reqwest::get(&uri).await?;
+        let resp = http_client::shared().get(&uri).send().await?;
         let text = resp.text().await?;
         let (_, resp) = serde_json::from_str::<HashMap<String, SteamAppDetailsResponse>>(&text)
             .map_err(|e| {
@@ -39,14 +67,57 @@ impl SteamApi {
             .next()
             .unwrap();
 
-        Ok(resp.data)
+        match resp.success {
+            true => Ok(resp.data),
+            false => Ok(None),
+        }
+    }
+
+    /// Returns the current price of a Steam app in the store's default region.
+    /// Returns None for free or unreleased apps that have no price.
+    #[instrument(level = "trace")]
+    pub async fn get_app_price(steam_appid: &str) -> Result<Option<StorePrice>, Status> {
+        let uri = format!(
+            "https://store.steampowered.com/api/appdetails?appids={steam_appid}&filters=price_overview"
+        );
+
+        let resp = http_client::shared().get(&uri).send().await?;
+        let text = resp.text().await?;
+        let resp = serde_json::from_str::<HashMap<String, SteamAppPriceResponse>>(&text)
+            .map_err(|e| {
+                let msg = format!(
+                    "({steam_appid}) Parse error: {}\n Steam response: {}",
+                    e, &text
+                );
+                Status::internal(msg)
+            })?
+            .into_values()
+            .next();
+
+        // Apps without a price return an empty array instead of an object.
+        let price = match resp {
+            Some(resp) if resp.success => resp
+                .data
+                .get("price_overview")
+                .and_then(|p| serde_json::from_value::<SteamPriceOverview>(p.clone()).ok()),
+            _ => None,
+        };
+
+        Ok(price.map(|price| StorePrice {
+            store: String::from("steam"),
+            currency: price.currency,
+            initial: price.initial,
+            final_price: price.final_price,
+            discount_percent: price.discount_percent,
+            timestamp: Utc::now().timestamp(),
+        }))
     }
 
     #[instrument(level = "trace")]
     pub async fn get_app_score(steam_appid: &str) -> Result<SteamScore, Status> {
         let uri = format!("https://store.steampowered.com/appreviews/{steam_appid}?json=1");
 
-        let resp = reqwest::get(&uri).await?;
+        let resp = http_client::shared().get(&uri).send().await?;
         let text = resp.text().await?;
         let resp = serde_json::from_str::<SteamAppReviewsResponse>(&text).map_err(|e| {
             let msg = format!(
@@ -65,27 +136,96 @@ impl SteamApi {
             review_score_desc: resp.query_summary.review_score_desc,
         })
     }
-}
 
-#[async_trait]
-impl Storefront for SteamApi {
-    fn id() -> String {
-        String::from("steam")
+    /// Returns user's total playtime in minutes for each owned game keyed by
+    /// its Steam appid.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_playtimes(&self) -> Result<HashMap<String, u64>, Status> {
+        Ok(self
+            .get_owned_games_response()
+            .await?
+            .games
+            .into_iter()
+            .map(|entry| (format!("{}", entry.appid), entry.playtime_forever as u64))
+            .collect())
     }
 
-    async fn get_owned_games(&self) -> Result<Vec<StoreEntry>, Status> {
+    /// Returns the number of unlocked and total achievements of the user for a
+    /// Steam game. Returns None if the game has no achievements.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_achievements(&self, steam_appid: &str) -> Result<Option<(u64, u64)>, Status> {
+        let uri = format!(
+            "{STEAM_HOST}{STEAM_GETPLAYERACHIEVEMENTS_SERVICE}?key={}&steamid={}&appid={steam_appid}&format=json",
+            self.steam_key, self.steam_user_id
+        );
+
+        // Steam responds with an error status for games without stats, but
+        // the body still has the expected shape.
+        let resp = self
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .json::<PlayerStatsResponse>()
+            .await?;
+        let stats = resp.playerstats;
+        match stats.success && !stats.achievements.is_empty() {
+            true => Ok(Some((
+                stats.achievements.iter().filter(|a| a.achieved > 0).count() as u64,
+                stats.achievements.len() as u64,
+            ))),
+            false => Ok(None),
+        }
+    }
+
+    /// Returns the number of published items in the Steam Workshop of a game.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn get_workshop_items(&self, steam_appid: &str) -> Result<u64, Status> {
+        let uri = format!(
+            "{STEAM_HOST}{STEAM_QUERYFILES_SERVICE}?key={}&appid={steam_appid}&query_type=0&numperpage=1&totalonly=true&format=json",
+            self.steam_key
+        );
+
+        let resp = self
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .json::<QueryFilesResponse>()
+            .await?;
+        Ok(resp.response.total)
+    }
+
+    async fn get_owned_games_response(&self) -> Result<GetOwnedGamesResponse, Status> {
         let uri = format!(
             "{STEAM_HOST}{STEAM_GETOWNEDGAMES_SERVICE}?key={}&steamid={}&include_appinfo=true&format=json",
             self.steam_key, self.steam_user_id
         );
 
-        let resp = reqwest::get(&uri).await?.json::<SteamResponse>().await?;
+        let resp = self
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .json::<SteamResponse>()
+            .await?;
         info! {
             "steam games: {}", resp.response.game_count
         }
+        Ok(resp.response)
+    }
+}
 
-        Ok(resp
-            .response
+#[async_trait]
+impl Storefront for SteamApi {
+    fn id() -> String {
+        String::from("steam")
+    }
+
+    async fn get_owned_games(&self) -> Result<Vec<StoreEntry>, Status> {
+        Ok(self
+            .get_owned_games_response()
+            .await?
             .games
             .into_iter()
             .map(|entry| StoreEntry {
@@ -119,10 +259,62 @@ struct GameEntry {
     img_icon_url: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerStatsResponse {
+    playerstats: PlayerStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerStats {
+    #[serde(default)]
+    success: bool,
+
+    #[serde(default)]
+    achievements: Vec<PlayerAchievement>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerAchievement {
+    achieved: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QueryFilesResponse {
+    response: QueryFilesTotal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QueryFilesTotal {
+    #[serde(default)]
+    total: u64,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct SteamAppDetailsResponse {
     success: bool,
-    data: SteamData,
+
+    #[serde(default)]
+    data: Option<SteamData>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct SteamAppPriceResponse {
+    success: bool,
+
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct SteamPriceOverview {
+    currency: String,
+    initial: u64,
+
+    #[serde(rename = "final")]
+    final_price: u64,
+
+    #[serde(default)]
+    discount_percent: u64,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -151,3 +343,5 @@ struct SteamAppReviewsQuerySummary {
 
 const STEAM_HOST: &str = "http://api.steampowered.com";
 const STEAM_GETOWNEDGAMES_SERVICE: &str = "/IPlayerService/GetOwnedGames/v0001/";
+const STEAM_GETPLAYERACHIEVEMENTS_SERVICE: &str = "/ISteamUserStats/GetPlayerAchievements/v0001/";
+const STEAM_QUERYFILES_SERVICE: &str = "/IPublishedFileService/QueryFiles/v1/";
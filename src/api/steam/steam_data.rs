@@ -1,5 +1,8 @@
 use crate::{
-    documents::SteamData, logging::SteamFetchCounter, util::rate_limiter::RateLimiter, Status,
+    documents::{SteamAvailability, SteamData},
+    logging::SteamFetchCounter,
+    util::rate_limiter::RateLimiter,
+    Status,
 };
 use std::time::Duration;
 use tracing::instrument;
@@ -17,6 +20,11 @@ impl SteamDataApi {
         }
     }
 
+    /// Returns the Steam data of `steam_appid`.
+    ///
+    /// Apps that are not listed in the default store region are looked up in
+    /// other regions. Apps that cannot be found in any of them are returned
+    /// as delisted, with no store data other than their appid.
     #[instrument(level = "trace", skip(self))]
     pub async fn retrieve_steam_data(&self, steam_appid: &str) -> Result<SteamData, Status> {
         let counter = SteamFetchCounter::new();
@@ -30,18 +38,53 @@ impl SteamDataApi {
             }
         };
         self.qps.wait();
-        let steam_data = match SteamApi::get_app_details(steam_appid).await {
-            Ok(mut steam_data) => {
-                steam_data.score = score;
-                steam_data
-            }
+        let mut steam_data = match SteamApi::get_app_details(steam_appid).await {
+            Ok(Some(steam_data)) => steam_data,
+            Ok(None) => self.retrieve_unavailable(steam_appid, &counter).await?,
             Err(status) => {
                 counter.log_error(&status);
                 return Err(status);
             }
         };
+        steam_data.score = score;
 
         counter.log();
         Ok(steam_data)
     }
+
+    /// Classifies an app that the default store region does not list, by
+    /// looking it up in other store regions.
+    async fn retrieve_unavailable(
+        &self,
+        steam_appid: &str,
+        counter: &SteamFetchCounter,
+    ) -> Result<SteamData, Status> {
+        for cc in SECONDARY_REGIONS {
+            self.qps.wait();
+            match SteamApi::get_regional_app_details(steam_appid, cc).await {
+                Ok(Some(steam_data)) => {
+                    counter.log_availability(SteamAvailability::RegionLocked);
+                    return Ok(SteamData {
+                        availability: SteamAvailability::RegionLocked,
+                        ..steam_data
+                    });
+                }
+                Ok(None) => {}
+                Err(status) => counter.log_warning("fetch_region_fail", &status),
+            }
+        }
+
+        counter.log_availability(SteamAvailability::Delisted);
+        Ok(SteamData {
+            steam_appid: steam_appid.parse().map_err(|_| {
+                Status::invalid_argument(format!("Invalid Steam appid '{steam_appid}'"))
+            })?,
+            availability: SteamAvailability::Delisted,
+            ..Default::default()
+        })
+    }
 }
+
+// Store regions where apps that are not listed in the default region are
+// looked up.
+const SECONDARY_REGIONS: [&str; 2] = ["us", "de"];
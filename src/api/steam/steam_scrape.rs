@@ -1,7 +1,9 @@
-use reqwest::{header, ClientBuilder};
+use reqwest::header;
 use soup::prelude::*;
 use tracing::warn;
 
+use crate::util::http_client;
+
 #[derive(Default, Clone, Debug)]
 pub struct SteamScrapeData {
     pub user_tags: Vec<String>,
@@ -17,7 +19,7 @@ impl SteamScrape {
             header::HeaderValue::from_static("birthtime=0; path=/; max-age=315360000"),
         );
 
-        let client = ClientBuilder::new()
+        let client = http_client::builder()
             .default_headers(request_headers)
             .cookie_store(true)
             .build()
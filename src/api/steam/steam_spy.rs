@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{util::http_client, Status};
+
+/// Ownership and player estimates of a Steam app from SteamSpy.
+#[derive(Default, Clone, Debug)]
+pub struct SteamSpyData {
+    /// Midpoint of the estimated owners range.
+    pub owners: u64,
+
+    /// Peak concurrent players of the previous day.
+    pub ccu: u64,
+}
+
+pub struct SteamSpyApi {}
+
+impl SteamSpyApi {
+    /// Returns SteamSpy estimates for `steam_appid`. Returns None if SteamSpy
+    /// has no data for the app.
+    #[instrument(level = "trace")]
+    pub async fn get_app(steam_appid: &str) -> Result<Option<SteamSpyData>, Status> {
+        let uri = format!("https://steamspy.com/api.php?request=appdetails&appid={steam_appid}");
+
+        let resp = http_client::shared().get(&uri).send().await?;
+        let text = resp.text().await?;
+        let resp = serde_json::from_str::<SteamSpyResponse>(&text).map_err(|e| {
+            let msg = format!(
+                "({steam_appid}) Parse error: {}\n SteamSpy response: {}",
+                e, &text
+            );
+            Status::internal(msg)
+        })?;
+
+        let data = SteamSpyData {
+            owners: parse_owners(&resp.owners),
+            ccu: resp.ccu,
+        };
+        match data.owners == 0 && data.ccu == 0 {
+            true => Ok(None),
+            false => Ok(Some(data)),
+        }
+    }
+}
+
+/// Returns the midpoint of an owners range formatted like
+/// "1,000,000 .. 2,000,000".
+fn parse_owners(owners: &str) -> u64 {
+    let bounds = owners
+        .split("..")
+        .filter_map(|bound| bound.trim().replace(',', "").parse::<u64>().ok())
+        .collect::<Vec<_>>();
+    match bounds.as_slice() {
+        [low, high] => (low + high) / 2,
+        [value] => *value,
+        _ => 0,
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct SteamSpyResponse {
+    #[serde(default)]
+    owners: String,
+
+    #[serde(default)]
+    ccu: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owners_range() {
+        assert_eq!(parse_owners("1,000,000 .. 2,000,000"), 1_500_000);
+        assert_eq!(parse_owners("0 .. 20,000"), 10_000);
+        assert_eq!(parse_owners(""), 0);
+    }
+}
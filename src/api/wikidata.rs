@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{util::http_client, Status};
+
+pub struct WikidataApi {}
+
+impl WikidataApi {
+    /// Returns the URL of the English Wikipedia page of the video game titled
+    /// `title` that was released in `year`, if Wikidata knows of one.
+    #[instrument(level = "trace")]
+    pub async fn find_wikipedia_url(title: &str, year: i32) -> Result<Option<String>, Status> {
+        let query = format!(
+            r#"SELECT ?article WHERE {{
+  ?game wdt:P31/wdt:P279* wd:Q7889;
+        rdfs:label "{}"@en;
+        wdt:P577 ?date.
+  FILTER(YEAR(?date) = {year})
+  ?article schema:about ?game;
+           schema:isPartOf <https://en.wikipedia.org/>.
+}} LIMIT 1"#,
+            escape(title)
+        );
+
+        let resp = http_client::shared()
+            .get(WIKIDATA_SPARQL_URL)
+            .query(&[("query", query.as_str()), ("format", "json")])
+            .send()
+            .await?;
+        let resp = resp.json::<SparqlResponse>().await?;
+
+        Ok(resp
+            .results
+            .bindings
+            .into_iter()
+            .next()
+            .map(|binding| binding.article.value))
+    }
+}
+
+/// Escapes `text` for use in a SPARQL string literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Deserialize, Debug)]
+struct SparqlResponse {
+    results: SparqlResults,
+}
+
+#[derive(Deserialize, Debug)]
+struct SparqlResults {
+    bindings: Vec<SparqlBinding>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SparqlBinding {
+    article: SparqlValue,
+}
+
+#[derive(Deserialize, Debug)]
+struct SparqlValue {
+    value: String,
+}
+
+const WIKIDATA_SPARQL_URL: &str = "https://query.wikidata.org/sparql";
@@ -1,16 +1,22 @@
 use soup::prelude::*;
 use tracing::warn;
 
+use crate::util::http_client;
+
 #[derive(Default, Clone, Debug)]
 pub struct WikipediaScrapeData {
-    pub score: u64,
+    pub score: Option<u64>,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
 }
 
 pub struct WikipediaScrape {}
 
 impl WikipediaScrape {
+    /// Scrapes the critic score and the companies listed in the Wikipedia page
+    /// at `uri`. Returns None if the page has neither.
     pub async fn scrape(uri: &str) -> Option<WikipediaScrapeData> {
-        let resp = match reqwest::get(uri).await {
+        let resp = match http_client::shared().get(uri).send().await {
             Ok(resp) => resp,
             Err(status) => {
                 warn!("{status}");
@@ -26,48 +32,105 @@ impl WikipediaScrape {
         };
         let soup = Soup::new(&text);
 
-        if let Some(table) = soup.class(AGGREGATORS_TABLE).find() {
-            for td in table.tag("td").find_all() {
-                println!("{}", td.text());
-                if let Some(score) = extract_score(&td.text()) {
-                    return Some(WikipediaScrapeData { score });
-                }
+        let data = WikipediaScrapeData {
+            score: extract_review_score(&soup),
+            developers: extract_infobox_list(&soup, "Developer(s)"),
+            publishers: extract_infobox_list(&soup, "Publisher(s)"),
+        };
+        match data.score.is_none() && data.developers.is_empty() && data.publishers.is_empty() {
+            true => None,
+            false => Some(data),
+        }
+    }
+}
+
+/// Returns the average critic score in the reviews table of the page.
+fn extract_review_score(soup: &Soup) -> Option<u64> {
+    if let Some(table) = soup.class(AGGREGATORS_TABLE).find() {
+        for td in table.tag("td").find_all() {
+            if let Some(score) = extract_score(&td.text()) {
+                return Some(score);
             }
-            return None;
         }
+        return None;
+    }
 
-        let mut scores = vec![];
-        if let Some(table) = soup.class(REVIEWS_TABLE).find() {
-            for td in table.tag("td").find_all() {
-                println!("td: {}", td.text());
-                if let Some(score) = extract_score(&td.text()) {
-                    println!("    {score}");
-                    scores.push(score);
-                } else {
-                    let span = td.tag("span").attr("role", "img").find();
-                    if let Some(span) = span {
-                        if let Some(title) = span.get("title") {
-                            println!("    {title}");
-                            if let Some(score) = extract_stars(&title) {
-                                println!("    {score}");
-                                scores.push(score);
-                            }
+    let mut scores = vec![];
+    if let Some(table) = soup.class(REVIEWS_TABLE).find() {
+        for td in table.tag("td").find_all() {
+            if let Some(score) = extract_score(&td.text()) {
+                scores.push(score);
+            } else {
+                let span = td.tag("span").attr("role", "img").find();
+                if let Some(span) = span {
+                    if let Some(title) = span.get("title") {
+                        if let Some(score) = extract_stars(&title) {
+                            scores.push(score);
                         }
                     }
                 }
             }
         }
+    }
 
-        if scores.len() < 2 {
-            return None;
+    if scores.len() < 2 {
+        return None;
+    }
+
+    let total = scores.len() as u64;
+    let score = scores.into_iter().reduce(|acc, e| acc + e).unwrap() / total;
+
+    Some(score)
+}
+
+/// Returns the entries of the infobox row titled `label`.
+fn extract_infobox_list(soup: &Soup, label: &str) -> Vec<String> {
+    let table = match soup.class(INFOBOX_TABLE).find() {
+        Some(table) => table,
+        None => return vec![],
+    };
+
+    for row in table.tag("tr").find_all() {
+        match row.tag("th").find() {
+            Some(th) if th.text().trim() == label => {}
+            _ => continue,
         }
+        let td = match row.tag("td").find() {
+            Some(td) => td,
+            None => return vec![],
+        };
 
-        let total = scores.len() as u64;
-        let score = scores.into_iter().reduce(|acc, e| acc + e).unwrap() / total;
-        println!("total: {score}");
+        let items = td
+            .tag("li")
+            .find_all()
+            .map(|li| li.text())
+            .collect::<Vec<_>>();
+        let items = match items.is_empty() {
+            true => td.text().lines().map(|line| line.to_owned()).collect(),
+            false => items,
+        };
+        return items
+            .iter()
+            .map(|item| strip_footnotes(item))
+            .filter(|item| !item.is_empty())
+            .collect();
+    }
+    vec![]
+}
 
-        Some(WikipediaScrapeData { score })
+/// Removes footnote markers like "[a]" or "[1]" from `text`.
+fn strip_footnotes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_footnote = false;
+    for c in text.chars() {
+        match c {
+            '[' => in_footnote = true,
+            ']' if in_footnote => in_footnote = false,
+            c if !in_footnote => stripped.push(c),
+            _ => {}
+        }
     }
+    stripped.trim().to_owned()
 }
 
 use lazy_static::lazy_static;
@@ -143,4 +206,5 @@ fn extract_stars(input: &str) -> Option<u64> {
 }
 
 const AGGREGATORS_TABLE: &str = "vgr-aggregators";
+const INFOBOX_TABLE: &str = "infobox";
 const REVIEWS_TABLE: &str = "vgr-reviews";
@@ -0,0 +1,168 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::{HashMap, HashSet};
+
+use clap::Parser;
+use espy_backend::{
+    api::{update_digests, FirestoreApi, IgdbApi, IgdbBatchApi},
+    documents::{GameDigest, GameEntry},
+    library::firestore::games,
+    util::{self, retry},
+    Tracing,
+};
+use firestore::{paths, FirestoreResult};
+use futures::{stream::BoxStream, StreamExt};
+use tracing::{error, info};
+
+/// Espy batch job that backfills IGDB themes, e.g. "Horror" or "Open world",
+/// of games in the catalog that were resolved before themes were fetched.
+///
+/// Themes are mapped into digest keywords, so digests of games whose keywords
+/// change are also updated in their companies and collections.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// Only report games that get themes without writing them back.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/backfill_themes")?;
+
+    let opts: Opts = Opts::parse();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+
+    let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    igdb.connect().await?;
+    let igdb_batch = IgdbBatchApi::new(igdb);
+
+    let themes = HashMap::<u64, String>::from_iter(
+        retry::with_retries(ATTEMPTS, || igdb_batch.collect_themes())
+            .await?
+            .into_iter()
+            .map(|theme| (theme.id, theme.name)),
+    );
+    info!("Found {} IGDB themes.", themes.len());
+
+    let firestore = FirestoreApi::connect().await?;
+    let mut game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+        .db()
+        .fluent()
+        .list()
+        .from(firestore.collection("games").as_str())
+        .obj()
+        .stream_all_with_errors()
+        .await?;
+
+    let mut total = 0;
+    let mut pending = vec![];
+    let mut backfilled = 0;
+    while let Some(game_entry) = game_entries.next().await {
+        total += 1;
+        let game_entry = match game_entry {
+            Ok(game_entry) => game_entry,
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+        if !game_entry.igdb_game.themes.is_empty() || game_entry.deleted_at.is_some() {
+            continue;
+        }
+
+        pending.push(game_entry);
+        if pending.len() >= IGDB_BATCH_SIZE {
+            backfilled += backfill(&firestore, &igdb_batch, &themes, &mut pending, &opts).await;
+        }
+    }
+    if !pending.is_empty() {
+        backfilled += backfill(&firestore, &igdb_batch, &themes, &mut pending, &opts).await;
+    }
+
+    info!("Scanned {total} games, backfilled themes for {backfilled} of them.");
+
+    Ok(())
+}
+
+/// Looks up the themes of `pending` games in IGDB and writes back the ones
+/// that have any. Returns the number of games that got themes.
+async fn backfill(
+    firestore: &FirestoreApi,
+    igdb_batch: &IgdbBatchApi,
+    themes: &HashMap<u64, String>,
+    pending: &mut Vec<GameEntry>,
+    opts: &Opts,
+) -> usize {
+    let ids = pending
+        .iter()
+        .map(|game_entry| game_entry.id)
+        .collect::<Vec<_>>();
+    let igdb_games =
+        match retry::with_retries(ATTEMPTS, || igdb_batch.collect_game_themes(&ids)).await {
+            Ok(igdb_games) => HashMap::<u64, Vec<u64>>::from_iter(
+                igdb_games
+                    .into_iter()
+                    .map(|igdb_game| (igdb_game.id, igdb_game.themes)),
+            ),
+            Err(status) => {
+                error!("Failed to collect themes of {} games: {status}", ids.len());
+                pending.clear();
+                return 0;
+            }
+        };
+
+    let mut backfilled = 0;
+    for mut game_entry in pending.drain(..) {
+        let theme_ids = match igdb_games.get(&game_entry.id) {
+            Some(theme_ids) if !theme_ids.is_empty() => theme_ids.clone(),
+            _ => continue,
+        };
+
+        let keywords = digest_keywords(&game_entry);
+        game_entry.themes = theme_ids
+            .iter()
+            .filter_map(|id| themes.get(id).cloned())
+            .collect();
+        game_entry.igdb_game.themes = theme_ids;
+        game_entry.update_tags();
+
+        println!(
+            "{} -- id={} -- {:?}",
+            game_entry.name, game_entry.id, game_entry.themes
+        );
+        backfilled += 1;
+        if opts.dry_run {
+            continue;
+        }
+
+        if let Err(status) = games::patch(
+            firestore,
+            &game_entry,
+            paths!(GameEntry::{igdb_game, themes, tags}),
+        )
+        .await
+        {
+            error!("Failed to patch '{}': {status}", game_entry.name);
+            continue;
+        }
+        if digest_keywords(&game_entry) != keywords {
+            update_digests(firestore, &game_entry).await;
+        }
+    }
+    backfilled
+}
+
+fn digest_keywords(game_entry: &GameEntry) -> HashSet<String> {
+    HashSet::from_iter(GameDigest::from(game_entry.clone()).keywords)
+}
+
+// IGDB returns up to 500 results per request.
+const IGDB_BATCH_SIZE: usize = 500;
+
+// Attempts of IGDB requests that fail with transient errors.
+const ATTEMPTS: u32 = 3;
@@ -1,9 +1,9 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 use clap::Parser;
 use espy_backend::{
-    documents::{Library, Notable},
-    Status, Tracing,
+    api::FirestoreApi, documents::Notable, library::firestore::library, Status, Tracing,
 };
-use firestore::FirestoreDb;
 use std::{
     collections::HashSet,
     time::{SystemTime, UNIX_EPOCH},
@@ -22,30 +22,30 @@ struct Opts {
 async fn main() -> Result<(), Status> {
     let opts: Opts = Opts::parse();
 
-    match opts.prod_tracing {
+    let _tracing = match opts.prod_tracing {
         false => Tracing::setup("build-notable")?,
         true => Tracing::setup_prod("build-notable")?,
-    }
+    };
 
     let user = &opts.user;
 
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap()
+        .unwrap_or_default()
         .as_secs();
 
-    let db = FirestoreDb::new("espy-library").await?;
+    let firestore = FirestoreApi::connect().await?;
+    let db = firestore.db();
 
-    let library: Option<Library> = db
-        .fluent()
-        .select()
-        .by_id_in(&format!("users/{user}/games"))
-        .obj()
-        .one("library")
-        .await?;
+    let library = library::read(&firestore, user).await?;
+    if library.entries.is_empty() {
+        return Err(Status::not_found(format!(
+            "Library of user '{user}' was not found"
+        )));
+    }
 
     let mut companies = HashSet::<String>::new();
-    for library_entry in library.unwrap().entries {
+    for library_entry in library.entries {
         for company in library_entry
             .digest
             .developers
@@ -64,7 +64,7 @@ async fn main() -> Result<(), Status> {
 
     db.fluent()
         .update()
-        .in_col("espy")
+        .in_col(&firestore.collection("espy"))
         .document_id("notable")
         .object(&notable)
         .execute()
@@ -1,18 +1,25 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 use std::{
-    collections::HashSet,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
     sync::Arc,
-    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use chrono::{Datelike, NaiveDateTime, Utc};
+use chrono::{Datelike, Duration, NaiveDateTime};
 use clap::Parser;
 use espy_backend::{
     api::{self, FirestoreApi},
     documents::{
-        Frontpage, GameCategory, GameDigest, GameEntry, GameStatus, ReleaseEvent, Timeline,
+        Explanation, Frontpage, GameCategory, GameDigest, GameEntry, GameStatus, InclusionReason,
+        ReleaseEvent, ReleasePrecision, ScoreConfidence, Timeline,
+    },
+    library::firestore::{frontpage, notable, platform_scope, timeline},
+    util::{
+        self,
+        clock::{Clock, SystemClock},
     },
-    library::firestore::{frontpage, notable, timeline},
-    util, Status, Tracing,
+    Status, Tracing,
 };
 use firestore::{path, FirestoreQueryDirection, FirestoreResult};
 use futures::{stream::BoxStream, TryStreamExt};
@@ -31,38 +38,37 @@ struct Opts {
     /// JSON file that contains application keys for espy service.
     #[clap(long, default_value = "false")]
     skip_update: bool,
+
+    /// Minimum espy score of games that are featured on their release
+    /// anniversaries.
+    #[clap(long, default_value = "85")]
+    anniversary_score: u64,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Status> {
     let opts: Opts = Opts::parse();
 
-    match opts.prod_tracing {
+    let _tracing = match opts.prod_tracing {
         false => Tracing::setup("build-timeline")?,
         true => Tracing::setup_prod("build-timeline")?,
-    }
+    };
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    let recent_past = SystemTime::now()
-        .checked_sub(Duration::from_secs(25 * MONTH_IN_SECONDS))
-        .unwrap()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let clock = SystemClock;
+    let now = clock.now() as u64;
+    let recent_past = now.saturating_sub(25 * MONTH_IN_SECONDS);
 
     let firestore = FirestoreApi::connect().await?;
 
     let notable = notable::read(&firestore).await?;
     let notable = HashSet::<String>::from_iter(notable.companies.into_iter());
+    let scope = platform_scope::read(&firestore).await?;
 
     let upcoming: BoxStream<FirestoreResult<GameEntry>> = firestore
         .db()
         .fluent()
         .select()
-        .from("games")
+        .from(firestore.collection("games").as_str())
         .filter(|q| {
             q.for_all([q
                 .field(path!(GameEntry::release_date))
@@ -78,8 +84,13 @@ async fn main() -> Result<(), Status> {
     let upcoming = upcoming.try_collect::<Vec<GameEntry>>().await?;
     info!("upcoming = {}", upcoming.len());
 
+    // Reasons that each game passed the filters, keyed by game id.
+    let mut explanations = HashMap::new();
+
     let upcoming = upcoming
         .into_iter()
+        .filter(|entry| !entry.archived)
+        .filter(|entry| entry.igdb_game.is_in_scope(&scope))
         .filter(|entry| match entry.category {
             GameCategory::Main
             | GameCategory::Expansion
@@ -88,17 +99,7 @@ async fn main() -> Result<(), Status> {
             | GameCategory::Remaster => true,
             _ => false,
         })
-        .filter(|entry| {
-            entry.scores.hype.unwrap_or_default() > UPCOMING_HYPE_THRESHOLD
-                || entry
-                    .developers
-                    .iter()
-                    .any(|dev| notable.contains(&dev.name))
-                || entry
-                    .publishers
-                    .iter()
-                    .any(|publ| notable.contains(&publ.name))
-        })
+        .filter(|entry| explain(&mut explanations, entry, upcoming_reasons(entry, &notable)))
         .collect_vec();
     info!("upcoming after filtering = {}", upcoming.len());
 
@@ -106,7 +107,7 @@ async fn main() -> Result<(), Status> {
         .db()
         .fluent()
         .select()
-        .from("games")
+        .from(firestore.collection("games").as_str())
         .filter(|q| {
             q.for_all([
                 q.field(path!(GameEntry::release_date)).less_than(now),
@@ -125,13 +126,15 @@ async fn main() -> Result<(), Status> {
     info!("recent = {}", recent.len());
 
     if !opts.skip_update {
-        if let Err(status) = update_recent(&opts.key_store, &mut recent).await {
+        if let Err(status) = update_recent(&opts.key_store, &mut recent, &clock).await {
             error!("Failed to update GameEntries: {status}");
         }
     }
 
     let recent = recent
         .into_iter()
+        .filter(|entry| !entry.archived)
+        .filter(|entry| entry.igdb_game.is_in_scope(&scope))
         .filter(|entry| match entry.category {
             GameCategory::Main
             | GameCategory::Expansion
@@ -140,49 +143,149 @@ async fn main() -> Result<(), Status> {
             | GameCategory::Remaster => true,
             _ => false,
         })
-        .filter(|entry| {
-            entry.scores.hype.unwrap_or_default() > UPCOMING_HYPE_THRESHOLD
-                || entry.scores.metacritic.is_some()
-                || entry
-                    .developers
-                    .iter()
-                    .any(|dev| notable.contains(&dev.name))
-                || entry
-                    .publishers
-                    .iter()
-                    .any(|publ| notable.contains(&publ.name))
-                || match entry.status {
-                    GameStatus::EarlyAccess => {
-                        entry.scores.popularity.unwrap_or_default()
-                            > EARLY_ACCESS_POPULARITY_THRESHOLD
-                    }
-                    _ => false,
-                }
-        })
+        .filter(|entry| explain(&mut explanations, entry, recent_reasons(entry, &notable)))
         .collect_vec();
     info!("recent after filtering = {}", recent.len());
 
-    build_frontpage(&firestore, &upcoming, &recent).await?;
-    build_timeline(&firestore, &upcoming, &recent).await?;
+    let anniversaries = match anniversaries(&firestore, opts.anniversary_score, &clock).await {
+        Ok(anniversaries) => anniversaries,
+        Err(status) => {
+            error!("Failed to collect anniversaries: {status}");
+            vec![]
+        }
+    };
+
+    build_frontpage(
+        &firestore,
+        &upcoming,
+        &recent,
+        anniversaries,
+        &explanations,
+        &clock,
+    )
+    .await?;
+    build_timeline(&firestore, &upcoming, &recent, &explanations, &clock).await?;
 
     Ok(())
 }
 
+/// Records the `reasons` that `entry` passed the filters in `explanations`.
+/// Returns true if there is any reason to include the game.
+fn explain(
+    explanations: &mut HashMap<u64, Vec<InclusionReason>>,
+    entry: &GameEntry,
+    reasons: Vec<InclusionReason>,
+) -> bool {
+    match reasons.is_empty() {
+        true => false,
+        false => {
+            explanations.insert(entry.id, reasons);
+            true
+        }
+    }
+}
+
+/// Returns the reasons that an upcoming game is included in the timeline.
+fn upcoming_reasons(entry: &GameEntry, notable: &HashSet<String>) -> Vec<InclusionReason> {
+    let mut reasons = vec![];
+    if let Some(hype) = entry.scores.hype {
+        if hype > UPCOMING_HYPE_THRESHOLD {
+            reasons.push(InclusionReason::Hype {
+                hype,
+                threshold: UPCOMING_HYPE_THRESHOLD,
+            });
+        }
+    }
+    reasons.extend(notable_reasons(entry, notable));
+    reasons
+}
+
+/// Returns the reasons that a recent game is included in the timeline.
+fn recent_reasons(entry: &GameEntry, notable: &HashSet<String>) -> Vec<InclusionReason> {
+    let mut reasons = upcoming_reasons(entry, notable);
+    if let Some(score) = entry.scores.metacritic {
+        reasons.push(InclusionReason::Metacritic { score });
+    }
+    if let GameStatus::EarlyAccess = entry.status {
+        let popularity = entry.scores.popularity.unwrap_or_default();
+        if popularity > EARLY_ACCESS_POPULARITY_THRESHOLD {
+            reasons.push(InclusionReason::EarlyAccessPopularity {
+                popularity,
+                threshold: EARLY_ACCESS_POPULARITY_THRESHOLD,
+            });
+        }
+    }
+    reasons
+}
+
+fn notable_reasons(entry: &GameEntry, notable: &HashSet<String>) -> Vec<InclusionReason> {
+    let developers = entry
+        .developers
+        .iter()
+        .filter(|dev| notable.contains(&dev.name))
+        .map(|dev| InclusionReason::NotableDeveloper {
+            company: dev.name.clone(),
+        });
+    let publishers = entry
+        .publishers
+        .iter()
+        .filter(|publ| notable.contains(&publ.name))
+        .map(|publ| InclusionReason::NotablePublisher {
+            company: publ.name.clone(),
+        });
+    developers.chain(publishers).collect()
+}
+
+/// Returns the explanations of `games` that are found in `explanations`.
+/// Returns true unless the game's score comes from too few reviews to be
+/// trusted, e.g. a 95 from 3 critic reviews.
+fn has_confident_score(game: &GameDigest) -> bool {
+    game.scores.confidence != Some(ScoreConfidence::Low)
+}
+
+/// Orders games by descending espy score, with low-volume scores after all
+/// others.
+fn by_confident_score(a: &GameDigest, b: &GameDigest) -> Ordering {
+    has_confident_score(b)
+        .cmp(&has_confident_score(a))
+        .then(b.scores.espy_score.cmp(&a.scores.espy_score))
+}
+
+fn explanations_of(
+    games: &[GameDigest],
+    explanations: &HashMap<u64, Vec<InclusionReason>>,
+) -> Vec<Explanation> {
+    games
+        .iter()
+        .filter_map(|game| {
+            explanations.get(&game.id).map(|reasons| Explanation {
+                game_id: game.id,
+                reasons: reasons.clone(),
+            })
+        })
+        .collect()
+}
+
 async fn build_frontpage(
     firestore: &FirestoreApi,
     future: &[GameEntry],
     past: &[GameEntry],
+    anniversaries: Vec<ReleaseEvent>,
+    explanations: &HashMap<u64, Vec<InclusionReason>>,
+    clock: &dyn Clock,
 ) -> Result<(), Status> {
-    let today = Utc::now().naive_utc();
+    let today = NaiveDateTime::from_timestamp_opt(clock.now(), 0).unwrap_or_default();
 
     let games = future.iter().chain(past.iter()).filter(|game_entry| {
-        let release_date = NaiveDateTime::from_timestamp_opt(game_entry.release_date, 0).unwrap();
+        let release_date =
+            NaiveDateTime::from_timestamp_opt(game_entry.release_date, 0).unwrap_or_default();
         let diff = today.signed_duration_since(release_date);
         diff.num_days().abs() <= 30
     });
 
     let release_group = |entry: &GameEntry| -> (String, String) {
-        let release_date = NaiveDateTime::from_timestamp_opt(entry.release_date, 0).unwrap();
+        let release_date =
+            NaiveDateTime::from_timestamp_opt(entry.release_date, 0).unwrap_or_default();
         (
             release_date.format("%-d %b").to_string(),
             release_date.format("%Y").to_string(),
@@ -197,19 +300,21 @@ async fn build_frontpage(
             let mut games = games
                 .map(|game| GameDigest::from(game.clone()))
                 .collect_vec();
-            games.sort_by(|a, b| b.scores.cmp(&a.scores));
+            games.sort_by(|a, b| {
+                has_confident_score(b)
+                    .cmp(&has_confident_score(a))
+                    .then(b.scores.cmp(&a.scores))
+            });
             ReleaseEvent {
                 label: key.0,
                 year: key.1,
+                explanations: explanations_of(&games, explanations),
                 games,
             }
         })
         .collect_vec();
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let now = clock.now() as u64;
 
     let frontpage = Frontpage {
         last_updated: now,
@@ -219,6 +324,7 @@ async fn build_frontpage(
         upcoming: vec![],
         new: vec![],
         hyped: vec![],
+        anniversaries,
     };
 
     frontpage::write(&firestore, &frontpage).await?;
@@ -233,23 +339,30 @@ async fn build_timeline(
     firestore: &FirestoreApi,
     future: &[GameEntry],
     past: &[GameEntry],
+    explanations: &HashMap<u64, Vec<InclusionReason>>,
+    clock: &dyn Clock,
 ) -> Result<(), Status> {
-    let today = Utc::now().naive_utc();
+    let today = NaiveDateTime::from_timestamp_opt(clock.now(), 0).unwrap_or_default();
     let release_group = |entry: &GameEntry| -> (String, String) {
-        let release_date = NaiveDateTime::from_timestamp_opt(entry.release_date, 0).unwrap();
+        let release_date =
+            NaiveDateTime::from_timestamp_opt(entry.release_date, 0).unwrap_or_default();
         let diff = today.signed_duration_since(release_date);
         let is_future = diff.num_days() < 0;
 
         let label = if diff.num_days().abs() <= 7 {
             release_date.format("%-d %b").to_string()
-        } else if is_future && release_date.month() == 12 && release_date.day() == 31 {
-            release_date.year().to_string()
-        } else if is_future && release_date.month() == 9 && release_date.day() == 30 {
-            "Q3".to_owned()
-        } else if is_future && release_date.month() == 6 && release_date.day() == 30 {
-            "Q2".to_owned()
-        } else if is_future && release_date.month() == 3 && release_date.day() == 31 {
-            "Q1".to_owned()
+        } else if is_future {
+            let precision = match entry.release_precision {
+                ReleasePrecision::Unknown => {
+                    ReleasePrecision::from_placeholder(entry.release_date, today.timestamp())
+                }
+                precision => precision,
+            };
+            match precision {
+                ReleasePrecision::Year => release_date.year().to_string(),
+                ReleasePrecision::Quarter => format!("Q{}", (release_date.month() + 2) / 3),
+                _ => release_date.format("%b").to_string(),
+            }
         } else {
             release_date.format("%b").to_string()
         };
@@ -269,6 +382,7 @@ async fn build_timeline(
             ReleaseEvent {
                 label: key.0,
                 year: key.1,
+                explanations: explanations_of(&games, explanations),
                 games,
             }
         })
@@ -282,19 +396,17 @@ async fn build_timeline(
                 let mut games = games
                     .map(|game| GameDigest::from(game.clone()))
                     .collect_vec();
-                games.sort_by(|a, b| b.scores.espy_score.cmp(&a.scores.espy_score));
+                games.sort_by(by_confident_score);
                 ReleaseEvent {
                     label: key.0,
                     year: key.1,
+                    explanations: explanations_of(&games, explanations),
                     games,
                 }
             }),
     );
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let now = clock.now() as u64;
 
     let timeline = Timeline {
         last_updated: now,
@@ -309,15 +421,86 @@ async fn build_timeline(
     Ok(())
 }
 
-async fn update_recent(keys_path: &str, recent: &mut [GameEntry]) -> Result<(), Status> {
-    let d7 = SystemTime::now()
-        .checked_sub(Duration::from_secs(7 * DAY_IN_SECONDS))
-        .unwrap()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+/// Returns beloved games that were released exactly 10, 20 or 25 years ago
+/// this week, grouped by anniversary.
+async fn anniversaries(
+    firestore: &FirestoreApi,
+    min_score: u64,
+    clock: &dyn Clock,
+) -> Result<Vec<ReleaseEvent>, Status> {
+    let today = NaiveDateTime::from_timestamp_opt(clock.now(), 0).unwrap_or_default();
+
+    let mut events = vec![];
+    for years in ANNIVERSARIES {
+        // Feb 29 has no anniversary on non-leap years, fall back to Feb 28.
+        let date = today
+            .with_year(today.year() - years)
+            .or_else(|| (today - Duration::days(1)).with_year(today.year() - years))
+            .unwrap_or_default();
+        let start = (date - Duration::days(3)).timestamp();
+        let end = (date + Duration::days(4)).timestamp();
+
+        let games: BoxStream<FirestoreResult<GameEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection("games").as_str())
+            .filter(|q| {
+                q.for_all([
+                    q.field(path!(GameEntry::release_date))
+                        .greater_than_or_equal(start),
+                    q.field(path!(GameEntry::release_date)).less_than(end),
+                ])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        let mut games = games
+            .try_collect::<Vec<GameEntry>>()
+            .await?
+            .into_iter()
+            .filter(|entry| entry.is_main_category() && !entry.is_tombstone())
+            .filter(|entry| entry.scores.espy_score.unwrap_or_default() >= min_score)
+            .map(GameDigest::from)
+            .collect_vec();
+        info!("{years} year anniversaries = {}", games.len());
+
+        if games.is_empty() {
+            continue;
+        }
+        games.sort_by(by_confident_score);
+        games.truncate(ANNIVERSARY_GAMES_LIMIT);
+
+        let explanations = games
+            .iter()
+            .map(|game| Explanation {
+                game_id: game.id,
+                reasons: vec![InclusionReason::Anniversary {
+                    years,
+                    espy_score: game.scores.espy_score.unwrap_or_default(),
+                    min_score,
+                }],
+            })
+            .collect();
+        events.push(ReleaseEvent {
+            label: format!("{years} years"),
+            year: date.format("%Y").to_string(),
+            games,
+            explanations,
+        });
+    }
 
-    let keys = util::keys::Keys::from_file(keys_path).unwrap();
+    Ok(events)
+}
+
+async fn update_recent(
+    keys_path: &str,
+    recent: &mut [GameEntry],
+    clock: &dyn Clock,
+) -> Result<(), Status> {
+    let d7 = (clock.now() as u64).saturating_sub(7 * DAY_IN_SECONDS);
+
+    let keys = util::keys::Keys::from_file(keys_path)?;
     let mut igdb = api::IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
     igdb.connect().await?;
 
@@ -345,3 +528,6 @@ const MONTH_IN_SECONDS: u64 = 30 * 24 * 60 * 60;
 
 const UPCOMING_HYPE_THRESHOLD: u64 = 1;
 const EARLY_ACCESS_POPULARITY_THRESHOLD: u64 = 5000;
+
+const ANNIVERSARIES: [i32; 3] = [10, 20, 25];
+const ANNIVERSARY_GAMES_LIMIT: usize = 5;
@@ -0,0 +1,143 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use clap::Parser;
+use espy_backend::{
+    api::FirestoreApi,
+    documents::{Company, FeedRelease, UserFeed},
+    library::firestore::{companies, deal_alerts, follows, user_data, user_feed, wishlist},
+    Status, Tracing,
+};
+use tracing::{error, warn};
+
+/// Espy batch job that builds the weekly feed of each user with upcoming
+/// wishlisted releases, deals on wishlisted games and new releases of followed
+/// companies.
+#[derive(Parser)]
+struct Opts {
+    /// Number of days ahead to look for upcoming wishlisted releases.
+    #[clap(long, default_value = "30")]
+    upcoming_days: i64,
+
+    /// Build the feed of a single user instead of all users.
+    #[clap(long)]
+    user: Option<String>,
+
+    /// Only report the feeds without writing them.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/build_user_feed")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let now = Utc::now().timestamp();
+    let mut companies = HashMap::<u64, Company>::new();
+
+    for user in user_data::list(&firestore).await? {
+        if let Some(user_id) = &opts.user {
+            if user.uid != *user_id {
+                continue;
+            }
+        }
+
+        let feed = match build_feed(&firestore, &user.uid, now, &opts, &mut companies).await {
+            Ok(feed) => feed,
+            Err(status) => {
+                error!("Failed to build feed of user '{}': {status}", user.uid);
+                continue;
+            }
+        };
+        println!(
+            "{} -- {} upcoming, {} price drops, {} new releases",
+            user.uid,
+            feed.upcoming.len(),
+            feed.price_drops.len(),
+            feed.new_releases.len()
+        );
+
+        if !opts.dry_run {
+            if let Err(status) = user_feed::write(&firestore, &user.uid, &feed).await {
+                error!("{status}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Composes the feed of `user_id` as of `now`. Followed companies are looked
+/// up in `companies` first, as popular ones are followed by many users.
+async fn build_feed(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    now: i64,
+    opts: &Opts,
+    companies: &mut HashMap<u64, Company>,
+) -> Result<UserFeed, Status> {
+    let week_ago = now - WEEK_SECS;
+    let upcoming_until = now + opts.upcoming_days * DAY_SECS;
+
+    let mut upcoming = wishlist::read(firestore, user_id)
+        .await?
+        .entries
+        .into_iter()
+        .map(|entry| entry.digest)
+        .filter(|digest| matches!(digest.release_date, Some(date) if date > now && date <= upcoming_until))
+        .collect::<Vec<_>>();
+    upcoming.sort_by_key(|digest| digest.release_date);
+
+    let price_drops = deal_alerts::read(firestore, user_id)
+        .await?
+        .alerts
+        .into_iter()
+        .filter(|alert| alert.timestamp >= week_ago)
+        .collect();
+
+    let mut new_releases = vec![];
+    let mut seen = HashSet::new();
+    for company_id in follows::read(firestore, user_id).await?.companies {
+        if !companies.contains_key(&company_id) {
+            match companies::read(firestore, company_id).await {
+                Ok(company) => {
+                    companies.insert(company_id, company);
+                }
+                Err(Status::NotFound(_)) => continue,
+                Err(status) => {
+                    warn!("Failed to read company={company_id}: {status}");
+                    continue;
+                }
+            }
+        }
+
+        let company = &companies[&company_id];
+        for digest in company.developed.iter().chain(company.published.iter()) {
+            if matches!(digest.release_date, Some(date) if date > week_ago && date <= now)
+                && seen.insert(digest.id)
+            {
+                new_releases.push(FeedRelease {
+                    company_id,
+                    company_name: company.name.clone(),
+                    digest: digest.clone(),
+                });
+            }
+        }
+    }
+    new_releases.sort_by_key(|release| release.digest.release_date);
+
+    Ok(UserFeed {
+        last_updated: now,
+        upcoming,
+        price_drops,
+        new_releases,
+    })
+}
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+const WEEK_SECS: i64 = 7 * DAY_SECS;
@@ -1,3 +1,5 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 use std::{
     cmp::min,
     sync::Arc,
@@ -9,7 +11,7 @@ use clap::Parser;
 use espy_backend::{
     api::FirestoreApi,
     documents::*,
-    library::firestore::{notable, year},
+    library::firestore::{notable, rejected, year},
     webhooks::filtering::{GameEntryClass, GameFilter},
     *,
 };
@@ -34,10 +36,10 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/refresh_game_entries")?;
+    let _tracing = Tracing::setup("utils/refresh_game_entries")?;
 
     let opts: Opts = Opts::parse();
-    let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
 
     let mut igdb = api::IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
     igdb.connect().await?;
@@ -74,7 +76,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from("games")
+            .from(firestore.collection("games").as_str())
             .filter(|q| {
                 q.for_all([
                     q.field(path!(GameEntry::release_date))
@@ -103,7 +105,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("Retained {} titles.", games.len());
 
         let notable = notable::read(&firestore).await?;
-        let classifier = GameFilter::new(notable);
+        let classifier = GameFilter::new(notable).with_blocklist(rejected::ids(&firestore).await?);
 
         let mut partitions = games
             .into_iter()
@@ -116,7 +118,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let review = AnnualReview {
             last_updated: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
-                .unwrap()
+                .unwrap_or_default()
                 .as_secs(),
             releases: partitions
                 .remove(&GameEntryClass::Main)
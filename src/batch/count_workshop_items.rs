@@ -0,0 +1,109 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::time::Duration;
+
+use clap::Parser;
+use espy_backend::{
+    api::{FirestoreApi, SteamApi},
+    documents::GameEntry,
+    library::firestore::games,
+    util::{self, rate_limiter::RateLimiter},
+    Tracing,
+};
+use firestore::{paths, FirestoreResult};
+use futures::{stream::BoxStream, StreamExt};
+use tracing::{error, info};
+
+/// Espy batch job that counts the Steam Workshop items of games in the catalog
+/// that support the Workshop.
+///
+/// Workshop support is part of the Steam store details, but the item count
+/// needs a separate Steam Web API query that is too slow to run on resolve.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// Only report item counts without writing them back.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/count_workshop_items")?;
+
+    let opts: Opts = Opts::parse();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+
+    let steam = SteamApi::new(&keys.steam.client_key, "");
+    let qps = RateLimiter::new(STEAM_QUOTA, Duration::from_secs(60), 4);
+
+    let firestore = FirestoreApi::connect().await?;
+    let mut game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+        .db()
+        .fluent()
+        .list()
+        .from(firestore.collection("games").as_str())
+        .obj()
+        .stream_all_with_errors()
+        .await?;
+
+    let mut total = 0;
+    let mut counted = 0;
+    while let Some(game_entry) = game_entries.next().await {
+        total += 1;
+        let mut game_entry = match game_entry {
+            Ok(game_entry) => game_entry,
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+        if !game_entry.is_moddable() || game_entry.deleted_at.is_some() {
+            continue;
+        }
+        let steam_appid = match &game_entry.steam_data {
+            Some(steam_data) => steam_data.steam_appid.to_string(),
+            None => continue,
+        };
+
+        qps.wait();
+        let items = match steam.get_workshop_items(&steam_appid).await {
+            Ok(items) => items,
+            Err(status) => {
+                error!(
+                    "Failed to count Workshop items of '{}': {status}",
+                    game_entry.name
+                );
+                continue;
+            }
+        };
+
+        println!(
+            "{} -- id={} -- workshop_items={items}",
+            game_entry.name, game_entry.id
+        );
+        counted += 1;
+        if opts.dry_run {
+            continue;
+        }
+
+        if let Some(steam_data) = &mut game_entry.steam_data {
+            steam_data.workshop_items = Some(items);
+        }
+        if let Err(status) =
+            games::patch(&firestore, &game_entry, paths!(GameEntry::{steam_data})).await
+        {
+            error!("Failed to patch '{}': {status}", game_entry.name);
+        }
+    }
+
+    info!("Scanned {total} games, counted Workshop items of {counted} of them.");
+
+    Ok(())
+}
+
+// Steam Web API requests per minute.
+const STEAM_QUOTA: i32 = 60;
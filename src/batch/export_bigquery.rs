@@ -0,0 +1,296 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use clap::Parser;
+use espy_backend::{
+    api::{BigQueryApi, Column, FirestoreApi},
+    documents::GameEntry,
+    library::firestore::{games, library, user_data, wishlist},
+    util::retry,
+    Status, Tracing,
+};
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Espy batch job that mirrors games, scores and anonymized library
+/// aggregates into BigQuery tables for analytics.
+///
+/// Each run appends a snapshot of the tables tagged with the date of the run.
+/// Rows use insert ids derived from the snapshot date, so rerunning a job on
+/// the same day does not produce duplicates.
+#[derive(Parser)]
+struct Opts {
+    /// GCP project that owns the BigQuery dataset.
+    #[clap(long)]
+    project: String,
+
+    /// BigQuery dataset that the tables are exported to.
+    #[clap(long, default_value = "espy")]
+    dataset: String,
+
+    /// Library aggregates of games owned or wishlisted by fewer users are not
+    /// exported, so that they cannot be traced back to individual users.
+    #[clap(long, default_value = "5")]
+    min_users: u64,
+
+    /// Only report the number of rows without exporting them.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/export_bigquery")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let snapshot = Utc::now().format("%Y-%m-%d").to_string();
+
+    let game_entries = games::list(&firestore).await?;
+    let game_rows = game_entries
+        .iter()
+        .filter(|game_entry| !game_entry.is_tombstone())
+        .map(|game_entry| GameRow::new(&snapshot, game_entry))
+        .collect::<Vec<_>>();
+    let score_rows = game_entries
+        .iter()
+        .filter(|game_entry| !game_entry.is_tombstone())
+        .map(|game_entry| ScoreRow::new(&snapshot, game_entry))
+        .collect::<Vec<_>>();
+    drop(game_entries);
+
+    let aggregate_rows = library_aggregates(&firestore, &snapshot, opts.min_users).await?;
+
+    info!(
+        "Exporting {} games, {} scores and {} library aggregates.",
+        game_rows.len(),
+        score_rows.len(),
+        aggregate_rows.len()
+    );
+    if opts.dry_run {
+        return Ok(());
+    }
+
+    let bigquery = BigQueryApi::connect(&opts.project, &opts.dataset).await?;
+    export(&bigquery, GAMES_TABLE, GAMES_SCHEMA, game_rows).await?;
+    export(&bigquery, SCORES_TABLE, SCORES_SCHEMA, score_rows).await?;
+    export(
+        &bigquery,
+        LIBRARY_AGGREGATES_TABLE,
+        LIBRARY_AGGREGATES_SCHEMA,
+        aggregate_rows,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn export<T: Serialize + Keyed>(
+    bigquery: &BigQueryApi,
+    table: &str,
+    schema: &[Column],
+    rows: Vec<T>,
+) -> Result<(), Status> {
+    bigquery.ensure_table(table, schema).await?;
+
+    let rows = rows
+        .into_iter()
+        .map(|row| (row.insert_id(), row))
+        .collect::<Vec<_>>();
+    retry::with_retries(ATTEMPTS, || bigquery.insert(table, &rows)).await?;
+
+    info!("Exported {} rows to '{table}'.", rows.len());
+    Ok(())
+}
+
+/// Counts how many users own or wishlist each game. Only counts are kept, no
+/// user ids, and games below `min_users` in both counts are dropped.
+async fn library_aggregates(
+    firestore: &FirestoreApi,
+    snapshot: &str,
+    min_users: u64,
+) -> Result<Vec<LibraryAggregateRow>, Status> {
+    let mut counts = HashMap::<u64, (u64, u64)>::new();
+
+    for user in user_data::list(firestore).await? {
+        match library::read(firestore, &user.uid).await {
+            Ok(library) => {
+                for entry in library.entries {
+                    counts.entry(entry.id).or_default().0 += 1;
+                }
+            }
+            Err(status) => warn!("Failed to read library of a user: {status}"),
+        }
+        match wishlist::read(firestore, &user.uid).await {
+            Ok(wishlist) => {
+                for entry in wishlist.entries {
+                    counts.entry(entry.id).or_default().1 += 1;
+                }
+            }
+            Err(status) => warn!("Failed to read wishlist of a user: {status}"),
+        }
+    }
+
+    Ok(counts
+        .into_iter()
+        .filter(|(_, (library_count, wishlist_count))| {
+            *library_count >= min_users || *wishlist_count >= min_users
+        })
+        .map(
+            |(game_id, (library_count, wishlist_count))| LibraryAggregateRow {
+                snapshot_date: snapshot.to_owned(),
+                game_id,
+                // Counts below the threshold are suppressed individually.
+                library_count: (library_count >= min_users).then_some(library_count),
+                wishlist_count: (wishlist_count >= min_users).then_some(wishlist_count),
+            },
+        )
+        .collect())
+}
+
+trait Keyed {
+    fn insert_id(&self) -> String;
+}
+
+#[derive(Serialize, Debug)]
+struct GameRow {
+    snapshot_date: String,
+    id: u64,
+    name: String,
+    category: String,
+    status: String,
+    release_date: i64,
+    release_year: i32,
+    archived: bool,
+    espy_genres: Vec<String>,
+    developers: Vec<String>,
+    publishers: Vec<String>,
+}
+
+impl GameRow {
+    fn new(snapshot: &str, game_entry: &GameEntry) -> Self {
+        GameRow {
+            snapshot_date: snapshot.to_owned(),
+            id: game_entry.id,
+            name: game_entry.name.clone(),
+            category: game_entry.category.to_string(),
+            status: game_entry.status.to_string(),
+            release_date: game_entry.release_date,
+            release_year: game_entry.release_year(),
+            archived: game_entry.archived,
+            espy_genres: game_entry
+                .espy_genres
+                .iter()
+                .map(|genre| format!("{genre:?}"))
+                .collect(),
+            developers: game_entry
+                .developers
+                .iter()
+                .map(|company| company.name.clone())
+                .collect(),
+            publishers: game_entry
+                .publishers
+                .iter()
+                .map(|company| company.name.clone())
+                .collect(),
+        }
+    }
+}
+
+impl Keyed for GameRow {
+    fn insert_id(&self) -> String {
+        format!("{}_{}", self.snapshot_date, self.id)
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ScoreRow {
+    snapshot_date: String,
+    game_id: u64,
+    espy_score: Option<u64>,
+    espy_tier: Option<String>,
+    metacritic: Option<u64>,
+    metacritic_reviews: Option<u64>,
+    thumbs: Option<u64>,
+    popularity: Option<u64>,
+    hype: Option<u64>,
+}
+
+impl ScoreRow {
+    fn new(snapshot: &str, game_entry: &GameEntry) -> Self {
+        let scores = &game_entry.scores;
+        ScoreRow {
+            snapshot_date: snapshot.to_owned(),
+            game_id: game_entry.id,
+            espy_score: scores.espy_score,
+            espy_tier: scores.espy_tier.as_ref().map(|tier| format!("{tier:?}")),
+            metacritic: scores.metacritic,
+            metacritic_reviews: scores.metacritic_reviews,
+            thumbs: scores.thumbs,
+            popularity: scores.popularity,
+            hype: scores.hype,
+        }
+    }
+}
+
+impl Keyed for ScoreRow {
+    fn insert_id(&self) -> String {
+        format!("{}_{}", self.snapshot_date, self.game_id)
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct LibraryAggregateRow {
+    snapshot_date: String,
+    game_id: u64,
+    library_count: Option<u64>,
+    wishlist_count: Option<u64>,
+}
+
+impl Keyed for LibraryAggregateRow {
+    fn insert_id(&self) -> String {
+        format!("{}_{}", self.snapshot_date, self.game_id)
+    }
+}
+
+const GAMES_TABLE: &str = "games";
+const GAMES_SCHEMA: &[Column] = &[
+    Column::new("snapshot_date", "DATE"),
+    Column::new("id", "INT64"),
+    Column::new("name", "STRING"),
+    Column::new("category", "STRING"),
+    Column::new("status", "STRING"),
+    Column::new("release_date", "INT64"),
+    Column::new("release_year", "INT64"),
+    Column::new("archived", "BOOL"),
+    Column::repeated("espy_genres", "STRING"),
+    Column::repeated("developers", "STRING"),
+    Column::repeated("publishers", "STRING"),
+];
+
+const SCORES_TABLE: &str = "scores";
+const SCORES_SCHEMA: &[Column] = &[
+    Column::new("snapshot_date", "DATE"),
+    Column::new("game_id", "INT64"),
+    Column::new("espy_score", "INT64"),
+    Column::new("espy_tier", "STRING"),
+    Column::new("metacritic", "INT64"),
+    Column::new("metacritic_reviews", "INT64"),
+    Column::new("thumbs", "INT64"),
+    Column::new("popularity", "INT64"),
+    Column::new("hype", "INT64"),
+];
+
+const LIBRARY_AGGREGATES_TABLE: &str = "library_aggregates";
+const LIBRARY_AGGREGATES_SCHEMA: &[Column] = &[
+    Column::new("snapshot_date", "DATE"),
+    Column::new("game_id", "INT64"),
+    Column::new("library_count", "INT64"),
+    Column::new("wishlist_count", "INT64"),
+];
+
+// Attempts of BigQuery inserts that fail with transient errors.
+const ATTEMPTS: u32 = 3;
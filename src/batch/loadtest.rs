@@ -0,0 +1,271 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use espy_backend::{
+    util::{self, http_client},
+    Tracing,
+};
+use serde::Deserialize;
+use tokio::{
+    sync::{mpsc, Semaphore},
+    time::{self, MissedTickBehavior},
+};
+use tracing::{info, warn};
+
+/// Espy tool that replays recorded traffic against a target environment at a
+/// fixed QPS and reports latency percentiles and error rates per route.
+///
+/// Traffic is read from a JSONL file where each line is a recorded request,
+/// e.g. `{"service": "webhooks", "path": "/add_game", "body": {...}}` or
+/// `{"service": "http", "path": "/search", "body": {"title": "hades"}}`.
+/// Requests are replayed in order and the file wraps around until the run
+/// ends.
+///
+/// It writes to the target environment. Only point it to a staging or load
+/// test environment.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// JSONL file with the recorded requests to replay.
+    #[clap(long)]
+    traffic: String,
+
+    /// Base URL of the http_server of the target environment.
+    #[clap(long)]
+    http_target: Option<String>,
+
+    /// Base URL of the webhook_handlers of the target environment.
+    #[clap(long)]
+    webhooks_target: Option<String>,
+
+    /// Requests sent per second.
+    #[clap(long, default_value = "10")]
+    qps: u32,
+
+    /// Duration of the run in seconds.
+    #[clap(long, default_value = "60")]
+    duration_secs: u64,
+
+    /// Maximum number of requests in flight. When it is reached the tool
+    /// waits, so the achieved QPS drops below the requested one.
+    #[clap(long, default_value = "64")]
+    max_in_flight: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct RecordedRequest {
+    service: Service,
+    path: String,
+
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Service {
+    Http,
+    Webhooks,
+}
+
+struct Sample {
+    route: String,
+    latency: Duration,
+    ok: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/loadtest")?;
+
+    let opts: Opts = Opts::parse();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+
+    let requests = read_traffic(&opts.traffic)?;
+    if requests.is_empty() {
+        return Err(format!("No requests found in '{}'", opts.traffic).into());
+    }
+    if opts.qps == 0 {
+        return Err("--qps must be positive".into());
+    }
+    info!(
+        "Replaying {} recorded requests at {} QPS for {}s.",
+        requests.len(),
+        opts.qps,
+        opts.duration_secs
+    );
+
+    let requests = requests
+        .into_iter()
+        .filter_map(|request| {
+            let target = match request.service {
+                Service::Http => opts.http_target.as_deref(),
+                Service::Webhooks => opts.webhooks_target.as_deref(),
+            };
+            match target {
+                Some(target) => Some(Arc::new((
+                    format!("{}{}", target.trim_end_matches('/'), request.path),
+                    request,
+                ))),
+                None => {
+                    warn!(
+                        "Skipping '{}' as no target is set for {:?}.",
+                        request.path, request.service
+                    );
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    if requests.is_empty() {
+        return Err("No recorded request matches a target".into());
+    }
+
+    let client = http_client::shared();
+    let secret = Arc::new(keys.igdb.webhook_secret);
+    let in_flight = Arc::new(Semaphore::new(opts.max_in_flight));
+    let (tx, mut rx) = mpsc::unbounded_channel::<Sample>();
+
+    let mut ticker = time::interval(Duration::from_secs_f64(1.0 / opts.qps as f64));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(opts.duration_secs);
+    let mut sent = 0;
+    for request in requests.iter().cycle() {
+        ticker.tick().await;
+        if Instant::now() >= deadline {
+            break;
+        }
+
+        let permit = match Arc::clone(&in_flight).acquire_owned().await {
+            Ok(permit) => permit,
+            Err(_) => break,
+        };
+        let request = Arc::clone(request);
+        let client = client.clone();
+        let secret = Arc::clone(&secret);
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let sample = send(&client, &request.0, &request.1, &secret).await;
+            let _ = tx.send(sample);
+            drop(permit);
+        });
+        sent += 1;
+    }
+    drop(tx);
+
+    let mut samples = BTreeMap::<String, Vec<Sample>>::new();
+    while let Some(sample) = rx.recv().await {
+        samples
+            .entry(sample.route.clone())
+            .or_default()
+            .push(sample);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "Sent {sent} requests in {:.1}s ({:.1} QPS achieved).",
+        elapsed.as_secs_f64(),
+        sent as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "{:<32} {:>8} {:>8} {:>9} {:>9} {:>9} {:>9}",
+        "route", "requests", "errors", "p50 ms", "p90 ms", "p99 ms", "max ms"
+    );
+    for (route, samples) in &samples {
+        report(route, samples);
+    }
+    report(
+        "total",
+        &samples.into_values().flatten().collect::<Vec<_>>(),
+    );
+
+    Ok(())
+}
+
+fn read_traffic(path: &str) -> Result<Vec<RecordedRequest>, std::io::Error> {
+    let mut requests = vec![];
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RecordedRequest>(&line) {
+            Ok(request) => requests.push(request),
+            Err(e) => warn!("Skipping malformed request on line {}: {e}", i + 1),
+        }
+    }
+    Ok(requests)
+}
+
+/// Sends `request` to `url` and returns its latency. Requests that fail or
+/// return a non-2xx status are counted as errors.
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    request: &RecordedRequest,
+    secret: &str,
+) -> Sample {
+    let mut builder = client.post(url).json(&request.body);
+    if let Service::Webhooks = request.service {
+        builder = builder.header("X-Secret", secret);
+    }
+
+    let start = Instant::now();
+    let ok = match builder.send().await {
+        Ok(response) => response.status().is_success(),
+        Err(e) => {
+            warn!("Request to '{}' failed: {e}", request.path);
+            false
+        }
+    };
+
+    Sample {
+        route: request.path.clone(),
+        latency: start.elapsed(),
+        ok,
+    }
+}
+
+fn report(route: &str, samples: &[Sample]) {
+    let mut latencies = samples
+        .iter()
+        .map(|sample| sample.latency)
+        .collect::<Vec<_>>();
+    latencies.sort();
+    let errors = samples.iter().filter(|sample| !sample.ok).count();
+
+    println!(
+        "{:<32} {:>8} {:>7.1}% {:>9} {:>9} {:>9} {:>9}",
+        route,
+        samples.len(),
+        100.0 * errors as f64 / samples.len().max(1) as f64,
+        percentile(&latencies, 50),
+        percentile(&latencies, 90),
+        percentile(&latencies, 99),
+        percentile(&latencies, 100),
+    );
+}
+
+/// Returns the `p`th percentile in milliseconds of the sorted `latencies`.
+fn percentile(latencies: &[Duration], p: usize) -> u128 {
+    match latencies.is_empty() {
+        true => 0,
+        false => {
+            let index = (latencies.len() * p).div_ceil(100).max(1) - 1;
+            latencies[index.min(latencies.len() - 1)].as_millis()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use chrono::Utc;
+use clap::Parser;
+use espy_backend::{
+    api::FirestoreApi,
+    library::{account, firestore::account_deletions},
+    Tracing,
+};
+use tracing::{error, info};
+
+/// Espy batch job that deletes accounts whose confirmed deletion request is
+/// past its grace period.
+#[derive(Parser)]
+struct Opts {
+    /// Only report accounts that are due for deletion without deleting them.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/purge_accounts")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let deletions = account_deletions::list_due(&firestore, Utc::now().timestamp()).await?;
+    info!("Found {} accounts due for deletion.", deletions.len());
+
+    let mut purged = 0;
+    for deletion in deletions {
+        println!(
+            "{} -- confirmed deletion scheduled at {}",
+            deletion.user_id, deletion.scheduled_at
+        );
+        if opts.dry_run {
+            continue;
+        }
+
+        match account::purge(&firestore, &deletion.user_id).await {
+            Ok(()) => purged += 1,
+            Err(status) => error!("{status}"),
+        }
+    }
+
+    if !opts.dry_run {
+        info!("Deleted {purged} accounts.");
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use clap::Parser;
+use espy_backend::{api::FirestoreApi, documents::GameEntry, Tracing};
+use firestore::{paths, FirestoreResult};
+use futures::{stream::BoxStream, StreamExt};
+use tracing::{error, info};
+
+/// Espy batch job for recomputing espy_score of all games in the catalog.
+#[derive(Parser)]
+struct Opts {
+    /// Only report games whose espy_score changes without writing them back.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/recompute_scores")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let mut game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+        .db()
+        .fluent()
+        .list()
+        .from(firestore.collection("games").as_str())
+        .obj()
+        .stream_all_with_errors()
+        .await?;
+
+    let mut total = 0;
+    let mut changed = vec![];
+    let mut written = 0;
+    while let Some(game_entry) = game_entries.next().await {
+        total += 1;
+        let mut game_entry = match game_entry {
+            Ok(game_entry) => game_entry,
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+
+        let espy_score = game_entry.scores.espy_score;
+        game_entry.scores.update_espy_score(game_entry.release_date);
+        if game_entry.scores.espy_score == espy_score {
+            continue;
+        }
+
+        println!(
+            "#{} -- {} -- id={} -- {:?} => {:?}",
+            total, game_entry.name, game_entry.id, espy_score, game_entry.scores.espy_score,
+        );
+        changed.push(game_entry);
+
+        if !opts.dry_run && changed.len() >= BATCH_SIZE {
+            written += write_scores(&firestore, &changed).await;
+            changed.clear();
+        }
+    }
+    if !opts.dry_run && !changed.is_empty() {
+        written += write_scores(&firestore, &changed).await;
+    }
+
+    info!("Scanned {total} games, updated espy_score for {written} of them.");
+
+    Ok(())
+}
+
+/// Writes back the `scores` field of `game_entries` in a single batch and
+/// returns the number of documents written.
+async fn write_scores(firestore: &FirestoreApi, game_entries: &[GameEntry]) -> usize {
+    let batch_writer = match firestore.db().create_simple_batch_writer().await {
+        Ok(batch_writer) => batch_writer,
+        Err(status) => {
+            error!("Failed to create batch writer: {status}");
+            return 0;
+        }
+    };
+    let mut batch = batch_writer.new_batch();
+
+    for game_entry in game_entries {
+        let result = firestore
+            .db()
+            .fluent()
+            .update()
+            .fields(paths!(GameEntry::{scores}))
+            .in_col(&firestore.collection("games"))
+            .document_id(game_entry.id.to_string())
+            .object(game_entry)
+            .add_to_batch(&mut batch);
+        if let Err(status) = result {
+            error!("Failed to add '{}' to batch: {status}", game_entry.name);
+        }
+    }
+
+    match batch.write().await {
+        Ok(_) => game_entries.len(),
+        Err(status) => {
+            error!("Batch write failed: {status}");
+            0
+        }
+    }
+}
+
+// Firestore limits batch writes to 500 documents.
+const BATCH_SIZE: usize = 500;
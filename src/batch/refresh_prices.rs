@@ -0,0 +1,184 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use clap::Parser;
+use espy_backend::{
+    api::{FirestoreApi, GogApi, SteamApi},
+    documents::{DealAlert, PriceHistory, StorePrice},
+    library::firestore::{deal_alerts, external_games, prices, user_data, wishlist},
+    notifications::{Dispatcher, Message},
+    util, Status, Tracing,
+};
+use tracing::{error, info, warn};
+
+/// Espy batch job that refreshes Steam and GOG prices of wishlisted games and
+/// alerts users about deals on their wishlist.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// Minimum discount (percent) that triggers a deal alert for users that
+    /// have not configured their own threshold.
+    #[clap(long, default_value = "50")]
+    discount_threshold: u64,
+
+    /// Only report price changes and deals without writing them.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/refresh_prices")?;
+
+    let opts: Opts = Opts::parse();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+    let firestore = FirestoreApi::connect().await?;
+    let dispatcher = Dispatcher::from_keys(&keys.notifications);
+
+    // Wishlisted game ids => users that wishlisted them with their threshold.
+    let mut wishlisted = HashMap::<u64, (String, Vec<(String, u64)>)>::new();
+    for user in user_data::list(&firestore).await? {
+        let wishlist = match wishlist::read(&firestore, &user.uid).await {
+            Ok(wishlist) => wishlist,
+            Err(status) => {
+                error!("Failed to read wishlist of user '{}': {status}", user.uid);
+                continue;
+            }
+        };
+
+        let threshold = user.deal_threshold.unwrap_or(opts.discount_threshold);
+        for entry in wishlist.entries {
+            wishlisted
+                .entry(entry.id)
+                .or_insert_with(|| (entry.digest.name.clone(), vec![]))
+                .1
+                .push((user.uid.clone(), threshold));
+        }
+    }
+    info!(
+        "Refreshing prices of {} wishlisted games.",
+        wishlisted.len()
+    );
+
+    let mut deals = HashMap::<String, Vec<DealAlert>>::new();
+    for (game_id, (name, users)) in wishlisted {
+        let store_prices = match fetch_prices(&firestore, game_id).await {
+            Ok(store_prices) => store_prices,
+            Err(status) => {
+                error!("Failed to retrieve prices of '{name}' ({game_id}): {status}");
+                continue;
+            }
+        };
+        if store_prices.is_empty() {
+            continue;
+        }
+
+        let mut price_history = match prices::read(&firestore, game_id).await {
+            Ok(price_history) => price_history,
+            Err(Status::NotFound(_)) => PriceHistory {
+                game_id,
+                ..Default::default()
+            },
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+        price_history.name = name.clone();
+        price_history.last_updated = Utc::now().timestamp();
+
+        for price in store_prices {
+            if !price_history.update(price.clone()) {
+                continue;
+            }
+            println!(
+                "{name} ({game_id}) -- {} -- {} {} (-{}%)",
+                price.store, price.final_price, price.currency, price.discount_percent
+            );
+
+            for (user_id, threshold) in &users {
+                if price.discount_percent > 0 && price.discount_percent >= *threshold {
+                    deals.entry(user_id.clone()).or_default().push(DealAlert {
+                        game_id,
+                        name: name.clone(),
+                        store: price.store.clone(),
+                        currency: price.currency.clone(),
+                        final_price: price.final_price,
+                        discount_percent: price.discount_percent,
+                        timestamp: price.timestamp,
+                        seen: false,
+                    });
+                }
+            }
+        }
+
+        if !opts.dry_run {
+            if let Err(status) = prices::write(&firestore, &price_history).await {
+                error!("{status}");
+            }
+        }
+    }
+
+    for (user_id, user_deals) in deals {
+        println!("{user_id} -- {} new deals", user_deals.len());
+        if opts.dry_run {
+            continue;
+        }
+
+        let mut alerts = match deal_alerts::read(&firestore, &user_id).await {
+            Ok(alerts) => alerts,
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+        let mut added = vec![];
+        for deal in user_deals {
+            let message = Message::from(&deal);
+            if alerts.add(deal) {
+                added.push(message);
+            }
+        }
+        if added.is_empty() {
+            continue;
+        }
+        if let Err(status) = deal_alerts::write(&firestore, &user_id, &alerts).await {
+            error!("{status}");
+            continue;
+        }
+        for message in &added {
+            dispatcher.deliver(&firestore, &user_id, message).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Retrieves the current prices of a game on the stores it is linked to.
+async fn fetch_prices(firestore: &FirestoreApi, game_id: u64) -> Result<Vec<StorePrice>, Status> {
+    let mut store_prices = vec![];
+    for external_game in external_games::get_external_games(firestore, game_id).await? {
+        let price = if external_game.is_steam() {
+            SteamApi::get_app_price(&external_game.store_id).await
+        } else if external_game.is_gog() {
+            GogApi::get_price(&external_game.store_id).await
+        } else {
+            continue;
+        };
+
+        match price {
+            Ok(Some(price)) => store_prices.push(price),
+            Ok(None) => {}
+            Err(status) => warn!(
+                "Failed to retrieve {} price of {game_id}: {status}",
+                external_game.store_name
+            ),
+        }
+    }
+    Ok(store_prices)
+}
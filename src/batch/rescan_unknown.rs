@@ -0,0 +1,141 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashMap;
+
+use clap::Parser;
+use espy_backend::{
+    api::{update_digests, FirestoreApi, IgdbApi},
+    documents::{GameCategory, GameEntry, GameStatus},
+    library::firestore::games,
+    util::{self, retry},
+    Status, Tracing,
+};
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use itertools::Itertools;
+use tracing::{error, info};
+
+/// Espy batch job that re-reads category and status from IGDB for games that
+/// were created before they were mapped and are still Unknown.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// Only report games whose category or status changes without writing
+    /// them back.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/rescan_unknown")?;
+
+    let opts: Opts = Opts::parse();
+
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+    let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    igdb.connect().await?;
+
+    let firestore = FirestoreApi::connect().await?;
+
+    let mut game_entries = HashMap::<u64, GameEntry>::new();
+    for field in [path!(GameEntry::category), path!(GameEntry::status)] {
+        for game_entry in query_unknown(&firestore, &field).await? {
+            game_entries.insert(game_entry.id, game_entry);
+        }
+    }
+    info!(
+        "Found {} games with Unknown category/status.",
+        game_entries.len()
+    );
+
+    let mut updated = 0;
+    for chunk in &game_entries.into_values().chunks(IGDB_BATCH_SIZE) {
+        let mut chunk = chunk.collect_vec();
+        let ids = chunk.iter().map(|game_entry| game_entry.id).collect_vec();
+        let igdb_games = match retry::with_retries(ATTEMPTS, || igdb.get_categories(&ids)).await {
+            Ok(igdb_games) => igdb_games,
+            Err(status) => {
+                error!("Failed to retrieve categories from IGDB: {status}");
+                continue;
+            }
+        };
+
+        for igdb_game in igdb_games {
+            let game_entry = match chunk.iter_mut().find(|e| e.id == igdb_game.id) {
+                Some(game_entry) => game_entry,
+                None => continue,
+            };
+
+            let category = match igdb_game.version_parent {
+                Some(_) => GameCategory::Version,
+                None => GameCategory::from(igdb_game.category),
+            };
+            let status = GameStatus::from(igdb_game.status);
+            if game_entry.category == category && game_entry.status == status {
+                continue;
+            }
+
+            println!(
+                "{} -- id={} -- {}/{} => {category}/{status}",
+                game_entry.name, game_entry.id, game_entry.category, game_entry.status,
+            );
+            if opts.dry_run {
+                continue;
+            }
+
+            game_entry.category = category;
+            game_entry.status = status;
+            game_entry.igdb_game.category = igdb_game.category;
+            game_entry.igdb_game.status = igdb_game.status;
+            game_entry.igdb_game.version_parent = igdb_game.version_parent;
+
+            if let Err(status) = games::patch(
+                &firestore,
+                game_entry,
+                vec![
+                    path!(GameEntry::category),
+                    path!(GameEntry::status),
+                    path!(GameEntry::igdb_game),
+                ],
+            )
+            .await
+            {
+                error!("Failed to patch '{}': {status}", game_entry.name);
+                continue;
+            }
+            // Category determines whether the game is tracked in company and
+            // collection digests.
+            update_digests(&firestore, game_entry).await;
+            updated += 1;
+        }
+    }
+
+    info!("Updated category/status for {updated} games.");
+
+    Ok(())
+}
+
+/// Returns game entries that have an Unknown value in `field`.
+async fn query_unknown(firestore: &FirestoreApi, field: &str) -> Result<Vec<GameEntry>, Status> {
+    let game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+        .db()
+        .fluent()
+        .select()
+        .from(firestore.collection("games").as_str())
+        .filter(|q| q.for_all([q.field(field).equal("Unknown")]))
+        .obj()
+        .stream_query_with_errors()
+        .await?;
+
+    Ok(game_entries.try_collect::<Vec<GameEntry>>().await?)
+}
+
+// IGDB returns up to 500 results per request.
+const IGDB_BATCH_SIZE: usize = 500;
+
+// Attempts of IGDB requests that fail with transient errors.
+const ATTEMPTS: u32 = 3;
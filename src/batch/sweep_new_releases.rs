@@ -0,0 +1,158 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use clap::Parser;
+use espy_backend::{
+    api::{FirestoreApi, IgdbApi, IgdbBatchApi, IgdbGame},
+    library::firestore::{backfill_queue, games, notable, platform_scope, rejected},
+    logging::IgdbCounters,
+    util::{self, retry},
+    webhooks::{filtering::GameFilter, prefiltering::IgdbPrefilter},
+    Tracing,
+};
+use itertools::Itertools;
+use tracing::{error, info, warn};
+
+/// Espy batch job that sweeps IGDB for recent and upcoming releases and
+/// resolves any that are missing from Firestore.
+///
+/// It is a safety net for IGDB webhooks that were not delivered. Games that
+/// the webhook pipeline would have accepted but are missing are reported as
+/// missed.
+#[derive(Parser)]
+struct Opts {
+    /// JSON file that contains application keys for espy service.
+    #[clap(long, default_value = "keys.json")]
+    key_store: String,
+
+    /// Sweep games released in the last N hours.
+    #[clap(long, default_value = "48")]
+    past_hours: i64,
+
+    /// Sweep games releasing in the next N days.
+    #[clap(long, default_value = "7")]
+    upcoming_days: i64,
+
+    /// Only report missing games without resolving them.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("batch/sweep_new_releases")?;
+
+    let opts: Opts = Opts::parse();
+    let keys = util::keys::Keys::from_file(&opts.key_store)?;
+
+    let firestore = Arc::new(FirestoreApi::connect().await?);
+
+    let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    igdb.set_platform_scope(platform_scope::read(&firestore).await?);
+    igdb.connect().await?;
+    let igdb_batch = IgdbBatchApi::new(igdb.clone());
+
+    let game_filter = GameFilter::new(notable::read(&firestore).await?)
+        .with_blocklist(rejected::ids(&firestore).await?);
+
+    let now = Utc::now().timestamp();
+    let released_after = now - opts.past_hours * 60 * 60;
+    let released_before = now + opts.upcoming_days * 24 * 60 * 60;
+
+    let mut igdb_games = vec![];
+    for offset in (0..).step_by(IGDB_BATCH_SIZE) {
+        let batch = retry::with_retries(ATTEMPTS, || {
+            igdb_batch.collect_igdb_games_released(released_after, released_before, offset as u64)
+        })
+        .await?;
+        let done = batch.len() < IGDB_BATCH_SIZE;
+        igdb_games.extend(batch);
+        if done {
+            break;
+        }
+    }
+    let checked = igdb_games.len();
+    info!("Found {checked} IGDB games releasing in the sweep window.");
+
+    let mut missing = vec![];
+    for chunk in &igdb_games.into_iter().chunks(FIRESTORE_BATCH_SIZE) {
+        let chunk = chunk.collect_vec();
+        let ids = chunk.iter().map(|game| game.id).collect_vec();
+        let result = games::batch_read(&firestore, &ids).await?;
+        missing.extend(
+            chunk
+                .into_iter()
+                .filter(|game| result.not_found.contains(&game.id)),
+        );
+    }
+    let missing_count = missing.len();
+    info!("{missing_count} games are missing from Firestore.");
+
+    let mut missed = 0;
+    for igdb_game in missing {
+        if !IgdbPrefilter::filter(&igdb_game, igdb.platform_scope()) {
+            continue;
+        }
+        if opts.dry_run {
+            println!("{} ({}) -- missing", igdb_game.name, igdb_game.id);
+            missed += 1;
+            continue;
+        }
+
+        if resolve(&firestore, &igdb, igdb_game, &game_filter).await {
+            missed += 1;
+        }
+    }
+
+    IgdbCounters::release_sweep(checked, missing_count, missed);
+    info!("Webhook pipeline missed {missed} games.");
+
+    Ok(())
+}
+
+/// Resolves and stores `igdb_game` if it passes the same filters as games
+/// that arrive through webhooks. Returns true if the game was stored.
+async fn resolve(
+    firestore: &Arc<FirestoreApi>,
+    igdb: &IgdbApi,
+    igdb_game: IgdbGame,
+    game_filter: &GameFilter,
+) -> bool {
+    let mut game_entry = match igdb
+        .resolve_only(Arc::clone(firestore), igdb_game, game_filter)
+        .await
+    {
+        Ok((_, Some(_))) => return false,
+        Ok((game_entry, None)) => game_entry,
+        Err(status) => {
+            error!("Failed to resolve game: {status}");
+            return false;
+        }
+    };
+
+    if let Err(status) = games::write(firestore, &mut game_entry).await {
+        error!("Failed to save '{}': {status}", game_entry.name);
+        return false;
+    }
+    if let Err(status) = backfill_queue::add(firestore, &game_entry).await {
+        warn!(
+            "Failed to schedule backfill for '{}': {status}",
+            game_entry.name
+        );
+    }
+
+    println!("{} ({}) -- resolved", game_entry.name, game_entry.id);
+    true
+}
+
+// IGDB returns up to 500 results per request.
+const IGDB_BATCH_SIZE: usize = 500;
+
+// Attempts of IGDB requests that fail with transient errors. Firestore calls
+// already retry on their own.
+const ATTEMPTS: u32 = 3;
+
+// Firestore batch reads are kept small to stay within request size limits.
+const FIRESTORE_BATCH_SIZE: usize = 100;
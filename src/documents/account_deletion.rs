@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'account_deletions' that tracks a user's request to
+/// delete their account. Deletion happens only after the request is confirmed
+/// with its token and the grace period has passed.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AccountDeletion {
+    pub user_id: String,
+
+    /// Token that the user needs to send back to confirm the deletion.
+    pub token: String,
+
+    /// Timestamp (in seconds) of the deletion request.
+    #[serde(default)]
+    pub requested_at: i64,
+
+    #[serde(default)]
+    pub confirmed: bool,
+
+    /// Timestamp (in seconds) after which a confirmed deletion is carried out.
+    #[serde(default)]
+    pub scheduled_at: i64,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+use super::ResolveSource;
+
+/// Document type under 'backfill_queue' that holds a partially resolved game
+/// and the external sources it is missing.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct BackfillEntry {
+    pub id: u64,
+    pub name: String,
+
+    #[serde(default)]
+    pub missing_sources: Vec<ResolveSource>,
+
+    /// Number of backfill attempts so far.
+    #[serde(default)]
+    pub attempts: u32,
+
+    /// Timestamp (in seconds) after which the entry can be backfilled.
+    #[serde(default)]
+    pub next_attempt: i64,
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use super::GameDigest;
+use super::{GameDigest, Image};
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Company {
@@ -12,6 +12,14 @@ pub struct Company {
     #[serde(default)]
     pub slug: String,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Image>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub developed: Vec<GameDigest>,
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'users/{user_id}/user_data/deals' that holds
+/// notifications about wishlisted games that went on sale.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DealAlerts {
+    #[serde(default)]
+    pub alerts: Vec<DealAlert>,
+}
+
+impl DealAlerts {
+    /// Adds `alert` unless the user was already notified about the same price
+    /// of the game on the store. Returns true if the alert was added.
+    pub fn add(&mut self, alert: DealAlert) -> bool {
+        if self.alerts.iter().any(|a| {
+            a.game_id == alert.game_id
+                && a.store == alert.store
+                && a.final_price == alert.final_price
+        }) {
+            return false;
+        }
+
+        self.alerts.push(alert);
+        if self.alerts.len() > MAX_ALERTS {
+            self.alerts.drain(..self.alerts.len() - MAX_ALERTS);
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DealAlert {
+    pub game_id: u64,
+    pub name: String,
+    pub store: String,
+
+    pub currency: String,
+
+    /// Price after discount in cents.
+    #[serde(rename = "final")]
+    pub final_price: u64,
+
+    pub discount_percent: u64,
+
+    /// Timestamp (in seconds) the deal was detected.
+    pub timestamp: i64,
+
+    #[serde(default)]
+    pub seen: bool,
+}
+
+const MAX_ALERTS: usize = 100;
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'follows/{user_id}' that holds the
+/// companies, franchises and collections a user follows for new releases.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Follows {
+    /// Owner of the document, so that follows can be queried across users.
+    #[serde(default)]
+    pub user_id: String,
+
+    #[serde(default)]
+    pub companies: Vec<u64>,
+
+    #[serde(default)]
+    pub franchises: Vec<u64>,
+
+    #[serde(default)]
+    pub collections: Vec<u64>,
+}
+
+impl Follows {
+    pub fn ids(&self, kind: FollowKind) -> &[u64] {
+        match kind {
+            FollowKind::Company => &self.companies,
+            FollowKind::Franchise => &self.franchises,
+            FollowKind::Collection => &self.collections,
+        }
+    }
+
+    /// Adds `id` in the followed entities of `kind`. Returns false if it was
+    /// already followed.
+    pub fn follow(&mut self, kind: FollowKind, id: u64) -> bool {
+        let ids = self.ids_mut(kind);
+        match ids.contains(&id) {
+            true => false,
+            false => {
+                ids.push(id);
+                true
+            }
+        }
+    }
+
+    /// Removes `id` from the followed entities of `kind`. Returns false if it
+    /// was not followed.
+    pub fn unfollow(&mut self, kind: FollowKind, id: u64) -> bool {
+        let ids = self.ids_mut(kind);
+        let len = ids.len();
+        ids.retain(|e| *e != id);
+        ids.len() != len
+    }
+
+    fn ids_mut(&mut self, kind: FollowKind) -> &mut Vec<u64> {
+        match kind {
+            FollowKind::Company => &mut self.companies,
+            FollowKind::Franchise => &mut self.franchises,
+            FollowKind::Collection => &mut self.collections,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum FollowKind {
+    Company,
+    Franchise,
+    Collection,
+}
@@ -38,4 +38,24 @@ pub struct Frontpage {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub hyped: Vec<GameDigest>,
+
+    // Beloved games released 10, 20 or 25 years ago this week. Each event is
+    // labelled with the anniversary, e.g. "10 years".
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub anniversaries: Vec<ReleaseEvent>,
+}
+
+impl Frontpage {
+    /// Removes the explanations of why games were included, that are only
+    /// served to curators.
+    pub fn strip_explanations(&mut self) {
+        for event in self
+            .releases
+            .iter_mut()
+            .chain(self.anniversaries.iter_mut())
+        {
+            event.explanations.clear();
+        }
+    }
 }
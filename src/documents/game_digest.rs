@@ -1,10 +1,15 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
+use chrono::{NaiveDateTime, Utc};
 use phf::phf_map;
 use serde::{Deserialize, Serialize};
 
-use super::{EspyGenre, GameCategory, GameEntry, GameStatus, IgdbGenre, Scores};
+use crate::genres;
+
+use super::{
+    EspyGenre, EspyGenreGroup, GameCategory, GameEntry, GameStatus, IgdbGenre, PlayerPerspective,
+    Playtime, PriceComparison, ReleasePrecision, Scores,
+};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct GameDigest {
@@ -17,6 +22,9 @@ pub struct GameDigest {
     #[serde(default)]
     pub status: GameStatus,
 
+    #[serde(default)]
+    pub archived: bool,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cover: Option<String>,
@@ -25,9 +33,16 @@ pub struct GameDigest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub release_date: Option<i64>,
 
+    #[serde(default)]
+    pub release_precision: ReleasePrecision,
+
     #[serde(default)]
     pub scores: Scores,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playtime: Option<Playtime>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<u64>,
@@ -52,24 +67,88 @@ pub struct GameDigest {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub espy_genres: Vec<EspyGenre>,
 
+    /// Groups that `espy_genres` roll up to.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub espy_genre_groups: Vec<EspyGenreGroup>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub igdb_genres: Vec<IgdbGenre>,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub player_perspectives: Vec<PlayerPerspective>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub keywords: Vec<String>,
+
+    /// True for games that support mods through the Steam Workshop.
+    #[serde(default)]
+    pub moddable: bool,
+
+    /// Attached on demand by `/prices/{game_id}`; digests are never stored
+    /// with prices.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prices: Option<PriceComparison>,
+}
+
+impl GameDigest {
+    /// Returns the countdown to the game's release if it is not released yet.
+    pub fn release_countdown(&self) -> Option<ReleaseCountdown> {
+        ReleaseCountdown::new(
+            self.release_date?,
+            self.release_precision,
+            Utc::now().timestamp(),
+        )
+    }
+}
+
+/// Countdown to the release of an upcoming game. It is computed when digests
+/// are returned and it is not stored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReleaseCountdown {
+    pub days_until_release: i64,
+    pub confidence: ReleasePrecision,
+}
+
+impl ReleaseCountdown {
+    /// Returns a countdown from `now` to `release_date` (both UNIX timestamps)
+    /// or None if the release date is unknown or in the past.
+    pub fn new(release_date: i64, precision: ReleasePrecision, now: i64) -> Option<Self> {
+        if release_date <= 0 || release_date <= now {
+            return None;
+        }
+
+        let release = NaiveDateTime::from_timestamp_opt(release_date, 0)?;
+        let today = NaiveDateTime::from_timestamp_opt(now, 0)?;
+
+        Some(ReleaseCountdown {
+            days_until_release: release
+                .date()
+                .signed_duration_since(today.date())
+                .num_days(),
+            confidence: match precision {
+                ReleasePrecision::Unknown => ReleasePrecision::from_placeholder(release_date, now),
+                precision => precision,
+            },
+        })
+    }
 }
 
 impl From<GameEntry> for GameDigest {
     fn from(game_entry: GameEntry) -> Self {
         let keywords = extract_keywords(&game_entry);
+        let moddable = game_entry.is_moddable();
 
         GameDigest {
             id: game_entry.id,
             name: game_entry.name,
             category: game_entry.category,
             status: game_entry.status,
+            archived: game_entry.archived,
 
             cover: match game_entry.cover {
                 Some(cover) => Some(cover.image_id),
@@ -80,7 +159,9 @@ impl From<GameEntry> for GameDigest {
                 0 => None,
                 x => Some(x),
             },
+            release_precision: game_entry.release_precision,
             scores: game_entry.scores.clone(),
+            playtime: game_entry.playtime,
 
             parent_id: match game_entry.parent {
                 Some(parent) => Some(parent.id),
@@ -119,9 +200,13 @@ impl From<GameEntry> for GameDigest {
                 .into_iter()
                 .collect(),
 
+            espy_genre_groups: genres::genre_groups(&game_entry.espy_genres),
             espy_genres: game_entry.espy_genres,
             igdb_genres: game_entry.igdb_genres,
+            player_perspectives: game_entry.player_perspectives,
             keywords,
+            moddable,
+            prices: None,
         }
     }
 }
@@ -129,17 +214,8 @@ impl From<GameEntry> for GameDigest {
 fn extract_keywords(game_entry: &GameEntry) -> Vec<String> {
     let mut keywords = HashSet::<String>::default();
 
-    let mut original_kws = vec![&game_entry.keywords];
-    if let Some(steam_data) = &game_entry.steam_data {
-        original_kws.push(&steam_data.user_tags);
-    }
-    if let Some(gog_data) = &game_entry.gog_data {
-        original_kws.push(&gog_data.tags);
-    }
-
-    let original_kws = original_kws.into_iter().flatten().collect_vec();
-    for kw in original_kws {
-        let kw = kw.to_lowercase().replace("-", "").replace(" ", "");
+    for tag in game_entry.collect_tags() {
+        let kw = tag.replace("-", "").replace(" ", "");
         for kw_set in KW_SETS {
             if let Some(kw) = kw_set.get(&kw) {
                 keywords.insert(kw.to_string());
@@ -172,6 +248,8 @@ static SETTING_KWS: phf::Map<&'static str, &'static str> = phf_map! {
     "mech" => "mechs",
 
     "scifi" => "sci-fi",
+    "sciencefiction" => "sci-fi",
+    "fantasy" => "fantasy",
     "cyberpunk" => "cyberpunk",
     "steampunk" => "steampunk",
     "darkfantasy" => "dark fantasy",
@@ -232,6 +310,12 @@ static GAMEPLAY_KWS: phf::Map<&'static str, &'static str> = phf_map! {
     "twinstickshooter" => "twin stick shooter",
     "soulslike" => "souls-like",
     "indie" => "indie",
+    "openworld" => "open world",
+    "sandbox" => "sandbox",
+    "survival" => "survival",
+    "stealth" => "stealth",
+    "4x" => "4X",
+    "4x(explore,expand,exploit,andexterminate)" => "4X",
 };
 
 static VISUAL_STYLE_KWS: phf::Map<&'static str, &'static str> = phf_map! {
@@ -257,6 +341,8 @@ static MATURE_KWS: phf::Map<&'static str, &'static str> = phf_map! {
     "nudity" => "nudity",
     "sexualcontent" => "sexual content",
     "familyfriendly" => "family friendly",
+    "kids" => "family friendly",
+    "erotic" => "NSFW",
 };
 
 static MULTIPLAYER_KWS: phf::Map<&'static str, &'static str> = phf_map! {
@@ -274,3 +360,136 @@ static TRIGGER_KWS: phf::Map<&'static str, &'static str> = phf_map! {
     "microtransaction" => "microtransaction",
     "paytoplay" => "pay-to-play",
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::SteamData;
+
+    fn timestamp(date: &str) -> i64 {
+        NaiveDateTime::parse_from_str(&format!("{date} 12:00:00"), "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn keywords_are_mapped_from_all_tags() {
+        let game_entry = GameEntry {
+            keywords: vec!["Time Travel".to_owned()],
+            steam_data: Some(SteamData {
+                user_tags: vec!["Rogue-like".to_owned(), "time travel".to_owned()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            game_entry.collect_tags(),
+            vec!["time travel".to_owned(), "rogue-like".to_owned()]
+        );
+        assert_eq!(
+            HashSet::<String>::from_iter(extract_keywords(&game_entry)),
+            HashSet::from_iter(["time travel".to_owned(), "roguelike".to_owned()])
+        );
+    }
+
+    #[test]
+    fn keywords_are_mapped_from_igdb_themes() {
+        let game_entry = GameEntry {
+            themes: vec![
+                "Science fiction".to_owned(),
+                "Open world".to_owned(),
+                "Horror".to_owned(),
+                "Comedy".to_owned(),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            HashSet::<String>::from_iter(extract_keywords(&game_entry)),
+            HashSet::from_iter([
+                "sci-fi".to_owned(),
+                "open world".to_owned(),
+                "horror".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn player_perspectives_are_resolved_from_igdb() {
+        let game_entry = GameEntry::from(crate::api::IgdbGame {
+            player_perspectives: vec![1, 3, 42],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            GameDigest::from(game_entry).player_perspectives,
+            vec![PlayerPerspective::FirstPerson, PlayerPerspective::Isometric]
+        );
+    }
+
+    #[test]
+    fn release_countdown_exact_date() {
+        let countdown = ReleaseCountdown::new(
+            timestamp("2024-02-14"),
+            ReleasePrecision::Exact,
+            timestamp("2024-02-04"),
+        )
+        .unwrap();
+
+        assert_eq!(countdown.days_until_release, 10);
+        assert_eq!(countdown.confidence, ReleasePrecision::Exact);
+    }
+
+    #[test]
+    fn release_countdown_keeps_stored_precision() {
+        let countdown = ReleaseCountdown::new(
+            timestamp("2024-09-30"),
+            ReleasePrecision::Month,
+            timestamp("2024-02-04"),
+        )
+        .unwrap();
+
+        assert_eq!(countdown.confidence, ReleasePrecision::Month);
+    }
+
+    #[test]
+    fn release_countdown_quarter_placeholder() {
+        let countdown = ReleaseCountdown::new(
+            timestamp("2024-09-30"),
+            ReleasePrecision::Unknown,
+            timestamp("2024-02-04"),
+        )
+        .unwrap();
+
+        assert_eq!(countdown.confidence, ReleasePrecision::Quarter);
+    }
+
+    #[test]
+    fn release_countdown_year_placeholder() {
+        let countdown = ReleaseCountdown::new(
+            timestamp("2025-12-31"),
+            ReleasePrecision::Unknown,
+            timestamp("2024-02-04"),
+        )
+        .unwrap();
+
+        assert_eq!(countdown.confidence, ReleasePrecision::Year);
+    }
+
+    #[test]
+    fn release_countdown_released_game() {
+        assert_eq!(
+            ReleaseCountdown::new(
+                timestamp("2023-12-31"),
+                ReleasePrecision::Year,
+                timestamp("2024-02-04")
+            ),
+            None
+        );
+        assert_eq!(
+            ReleaseCountdown::new(0, ReleasePrecision::Unknown, timestamp("2024-02-04")),
+            None
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'game_edits/{game_id}/edits' that records a curator
+/// change on a game, so that it can be reviewed and reverted.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct GameEdit {
+    pub id: String,
+    pub game_id: u64,
+
+    /// Curator or tool that applied the change.
+    pub curator: String,
+
+    pub kind: GameEditKind,
+
+    /// JSON encoded values of the edited field before and after the change.
+    #[serde(default)]
+    pub before: String,
+    #[serde(default)]
+    pub after: String,
+
+    /// Timestamp (in milliseconds) the change was applied.
+    pub timestamp: i64,
+}
+
+impl GameEdit {
+    pub fn new<T: Serialize>(
+        game_id: u64,
+        curator: &str,
+        kind: GameEditKind,
+        before: &T,
+        after: &T,
+        timestamp: i64,
+    ) -> Self {
+        GameEdit {
+            id: format!("{timestamp}_{kind:?}"),
+            game_id,
+            curator: curator.to_owned(),
+            kind,
+            before: serde_json::to_string(before).unwrap_or_default(),
+            after: serde_json::to_string(after).unwrap_or_default(),
+            timestamp,
+        }
+    }
+}
+
+/// Kind of curator change on a game.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum GameEditKind {
+    /// Archive state override of the game.
+    #[default]
+    ArchiveOverride,
+
+    /// Espy genres annotated on the game.
+    GenreAnnotation,
+
+    /// Duplicate game merged into another one.
+    Merge,
+
+    /// Fields of the game pinned by curators.
+    FieldOverride,
+}
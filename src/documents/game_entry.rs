@@ -1,9 +1,11 @@
+use std::collections::HashSet;
+
 use chrono::{Datelike, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::api::IgdbGame;
+use crate::{api::IgdbGame, util::clock::Clock};
 
-use super::{EspyGenre, GameDigest, GogData, Scores, SteamData};
+use super::{EspyGenre, GameDigest, GogData, Playtime, Scores, SteamData, TechInfo};
 
 /// Document type under 'games' collection that represents an espy game entry.
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -11,21 +13,62 @@ pub struct GameEntry {
     pub id: u64,
     pub name: String,
 
+    // Alternative titles from IGDB, e.g. localized titles or acronyms.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternative_names: Vec<String>,
+
     #[serde(default)]
     pub category: GameCategory,
 
     #[serde(default)]
     pub status: GameStatus,
 
+    // Cancelled or abandoned games are archived and left out of the timeline,
+    // company and collection digests and search by default.
+    #[serde(default)]
+    pub archived: bool,
+
+    // Set by curators to archive or unarchive a game regardless of heuristics.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_override: Option<bool>,
+
+    // Set when curators merge a duplicate IGDB entry into another game. The
+    // duplicate is kept as a tombstone, so that IGDB updates do not add it
+    // back.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merged_into: Option<u64>,
+
+    // Timestamp (in seconds) of when curators deleted the game. Deleted games
+    // are kept as tombstones, so that IGDB updates do not add them back.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+
     #[serde(default)]
     pub last_updated: i64,
 
     #[serde(default)]
     pub release_date: i64,
 
+    #[serde(default)]
+    pub release_precision: ReleasePrecision,
+
     #[serde(default)]
     pub scores: Scores,
 
+    // Estimated time to beat from HowLongToBeat.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playtime: Option<Playtime>,
+
+    // Technical details of the PC version from PCGamingWiki.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tech_info: Option<TechInfo>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cover: Option<Image>,
@@ -38,11 +81,29 @@ pub struct GameEntry {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub igdb_genres: Vec<IgdbGenre>,
 
+    // Camera perspectives from IGDB, e.g. first person or isometric.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub player_perspectives: Vec<PlayerPerspective>,
+
     // Keywords from IGDB.
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub keywords: Vec<String>,
 
+    // Themes from IGDB, e.g. "Horror" or "Open world".
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub themes: Vec<String>,
+
+    // Untruncated union of IGDB keywords and themes, Steam user tags and GOG
+    // tags, as they were last seen. Digest keywords are a curated mapping of
+    // these, so they can be recomputed on taxonomy changes without
+    // re-scraping stores.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub collections: Vec<CollectionDigest>,
@@ -107,6 +168,12 @@ pub struct GameEntry {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gog_data: Option<GogData>,
+
+    // External sources that were not retrieved in time during the last
+    // resolve and need to be backfilled.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_sources: Vec<ResolveSource>,
 }
 
 impl GameEntry {
@@ -137,11 +204,42 @@ impl GameEntry {
             })
     }
 
+    /// Returns the IGDB keywords, Steam user tags and GOG tags of the game
+    /// lowercased and deduped, in that order.
+    pub fn collect_tags(&self) -> Vec<String> {
+        let mut sources = vec![&self.keywords, &self.themes];
+        if let Some(steam_data) = &self.steam_data {
+            sources.push(&steam_data.user_tags);
+        }
+        if let Some(gog_data) = &self.gog_data {
+            sources.push(&gog_data.tags);
+        }
+
+        let mut seen = HashSet::new();
+        sources
+            .into_iter()
+            .flatten()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+            .collect()
+    }
+
+    pub fn update_tags(&mut self) {
+        self.tags = self.collect_tags();
+    }
+
     pub fn add_steam_data(&mut self, steam_data: SteamData) {
         self.scores.add_steam(&steam_data, self.release_date);
         self.steam_data = Some(steam_data);
     }
 
+    /// Returns true if the game supports mods through the Steam Workshop.
+    pub fn is_moddable(&self) -> bool {
+        self.steam_data
+            .as_ref()
+            .map_or(false, |steam_data| steam_data.has_workshop())
+    }
+
     pub fn add_gog_data(&mut self, gog_data: GogData) {
         self.scores.add_gog(&gog_data);
         self.gog_data = Some(gog_data);
@@ -154,16 +252,61 @@ impl GameEntry {
         self.scores.add_igdb(&igdb_game);
 
         self.igdb_game = igdb_game;
+        self.update_archived();
+    }
+
+    /// Recomputes the `archived` flag. A curator override takes precedence
+    /// over the abandonment heuristics.
+    pub fn update_archived(&mut self) {
+        self.archived = match (self.deleted_at, self.archived_override) {
+            (Some(_), _) => true,
+            (None, Some(archived)) => archived,
+            (None, None) => self.igdb_game.is_abandoned(Utc::now().timestamp()),
+        };
+    }
+
+    /// Copies curator state that is not derived from IGDB, e.g. archive
+    /// overrides and tombstones, from the `stored` entry of the same game.
+    pub fn keep_curated_state(&mut self, stored: &GameEntry) {
+        self.archived_override = stored.archived_override;
+        self.merged_into = stored.merged_into;
+        self.deleted_at = stored.deleted_at;
+        self.update_archived();
+    }
+
+    /// Returns true if the game was merged into another or deleted. Tombstones
+    /// are kept in Firestore but are not updated anymore.
+    pub fn is_tombstone(&self) -> bool {
+        self.merged_into.is_some() || self.deleted_at.is_some()
+    }
+
+    pub fn get_wikipedia_url(&self) -> Option<&str> {
+        self.websites
+            .iter()
+            .find(|website| matches!(website.authority, WebsiteAuthority::Wikipedia))
+            .map(|website| website.url.as_str())
+    }
+
+    /// Returns true if the game lacks a score or companies that its Wikipedia
+    /// page can provide.
+    pub fn needs_wikipedia(&self) -> bool {
+        self.scores.metacritic.is_none() || self.developers.is_empty()
     }
 
     pub fn release_year(&self) -> i32 {
         NaiveDateTime::from_timestamp_opt(self.release_date, 0)
-            .unwrap()
+            .unwrap_or_default()
             .year()
     }
 
-    pub fn is_released(&self) -> bool {
-        self.release_date > 0 && self.release_date < Utc::now().naive_utc().timestamp()
+    /// Returns true if the entry is of a category that is tracked in company
+    /// and collection digests.
+    pub fn is_main_category(&self) -> bool {
+        self.category.is_main_category() && self.igdb_game.is_main_category()
+    }
+
+    pub fn is_released(&self, clock: &dyn Clock) -> bool {
+        self.release_date > 0 && self.release_date < clock.now()
     }
 
     fn extract_category(igdb_game: &IgdbGame) -> GameCategory {
@@ -187,6 +330,12 @@ impl From<IgdbGame> for GameEntry {
                 Some(timestamp) => timestamp,
                 None => 0,
             },
+            release_precision: match igdb_game.first_release_date {
+                Some(timestamp) => {
+                    ReleasePrecision::from_placeholder(timestamp, Utc::now().timestamp())
+                }
+                None => ReleasePrecision::Unknown,
+            },
             scores: {
                 let mut scores = Scores::default();
                 scores.add_igdb(&igdb_game);
@@ -207,11 +356,19 @@ impl From<IgdbGame> for GameEntry {
                 },
             },
 
+            archived: igdb_game.is_abandoned(Utc::now().timestamp()),
+
             websites: vec![Website {
                 url: igdb_game.url.clone(),
                 authority: WebsiteAuthority::Igdb,
             }],
 
+            player_perspectives: igdb_game
+                .player_perspectives
+                .iter()
+                .filter_map(|id| PERSPECTIVES_BY_ID.get(id).copied())
+                .collect(),
+
             igdb_game,
 
             ..Default::default()
@@ -219,7 +376,19 @@ impl From<IgdbGame> for GameEntry {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+/// External sources that are queried when resolving a GameEntry.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ResolveSource {
+    SteamData,
+    SteamScrape,
+    SteamSpy,
+    Metacritic,
+    Wikipedia,
+    Hltb,
+    PcGamingWiki,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameCategory {
     Main,
     Dlc,
@@ -252,6 +421,20 @@ impl From<u64> for GameCategory {
     }
 }
 
+impl GameCategory {
+    pub fn is_main_category(&self) -> bool {
+        matches!(
+            self,
+            GameCategory::Main
+                | GameCategory::Dlc
+                | GameCategory::Expansion
+                | GameCategory::StandaloneExpansion
+                | GameCategory::Remake
+                | GameCategory::Remaster
+        )
+    }
+}
+
 impl Default for GameCategory {
     fn default() -> Self {
         GameCategory::Unknown
@@ -264,7 +447,7 @@ impl std::fmt::Display for GameCategory {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameStatus {
     Unknown,
     Released,
@@ -343,6 +526,54 @@ impl Default for CompanyRole {
     }
 }
 
+/// How precise a game's release date is.
+///
+/// IGDB encodes coarse release dates as the last day of the period, e.g. a
+/// game announced for "2026" is dated on Dec 31 2026 and one for "Q2 2026" on
+/// Jun 30 2026.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReleasePrecision {
+    #[default]
+    Unknown,
+    Year,
+    Quarter,
+    Month,
+    Exact,
+}
+
+impl ReleasePrecision {
+    /// Returns the precision of an IGDB release date based on its category.
+    pub fn from_igdb_category(category: u64) -> Self {
+        match category {
+            0 => ReleasePrecision::Exact,
+            1 => ReleasePrecision::Month,
+            2 => ReleasePrecision::Year,
+            3..=6 => ReleasePrecision::Quarter,
+            _ => ReleasePrecision::Unknown,
+        }
+    }
+
+    /// Returns the precision of a release date by detecting the placeholder
+    /// dates used by IGDB. Only dates after `now` can be placeholders.
+    pub fn from_placeholder(release_date: i64, now: i64) -> Self {
+        if release_date <= 0 {
+            return ReleasePrecision::Unknown;
+        }
+        if release_date <= now {
+            return ReleasePrecision::Exact;
+        }
+
+        match NaiveDateTime::from_timestamp_opt(release_date, 0) {
+            Some(date) => match (date.month(), date.day()) {
+                (12, 31) => ReleasePrecision::Year,
+                (3, 31) | (6, 30) | (9, 30) => ReleasePrecision::Quarter,
+                _ => ReleasePrecision::Exact,
+            },
+            None => ReleasePrecision::Unknown,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct CollectionDigest {
     pub id: u64,
@@ -418,6 +649,17 @@ pub enum IgdbGenre {
     MOBA,
 }
 
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlayerPerspective {
+    FirstPerson,
+    ThirdPerson,
+    Isometric,
+    SideView,
+    Text,
+    Auditory,
+    VirtualReality,
+}
+
 use phf::phf_map;
 
 static GENRES_BY_ID: phf::Map<u64, IgdbGenre> = phf_map! {
@@ -445,3 +687,13 @@ static GENRES_BY_ID: phf::Map<u64, IgdbGenre> = phf_map! {
     35u64 => IgdbGenre::CardAndBoard,
     36u64 => IgdbGenre::MOBA,
 };
+
+static PERSPECTIVES_BY_ID: phf::Map<u64, PlayerPerspective> = phf_map! {
+    1u64 => PlayerPerspective::FirstPerson,
+    2u64 => PlayerPerspective::ThirdPerson,
+    3u64 => PlayerPerspective::Isometric,
+    4u64 => PlayerPerspective::SideView,
+    5u64 => PlayerPerspective::Text,
+    6u64 => PlayerPerspective::Auditory,
+    7u64 => PlayerPerspective::VirtualReality,
+};
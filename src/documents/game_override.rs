@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::{EspyGenre, GameEntry, Image, ReleasePrecision};
+
+/// Document type under 'overrides' that pins curated fields of a game, so that
+/// re-resolving it from IGDB does not bring back the values they corrected.
+///
+/// Fields that are `None` are not overridden.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Override {
+    pub game_id: u64,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Timestamp (in seconds) of the release date. Pinned dates are exact.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<i64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub espy_genres: Option<Vec<EspyGenre>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover: Option<Image>,
+
+    /// Curator that pinned the fields.
+    #[serde(default)]
+    pub curator: String,
+
+    /// Timestamp (in milliseconds) the override was last changed.
+    #[serde(default)]
+    pub timestamp: i64,
+}
+
+impl Override {
+    /// Returns true if the override does not pin any field.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.release_date.is_none()
+            && self.espy_genres.is_none()
+            && self.cover.is_none()
+    }
+
+    /// Replaces the pinned fields of `game_entry`.
+    pub fn apply(&self, game_entry: &mut GameEntry) {
+        if let Some(name) = &self.name {
+            game_entry.name = name.clone();
+        }
+        if let Some(release_date) = self.release_date {
+            game_entry.release_date = release_date;
+            game_entry.release_precision = ReleasePrecision::Exact;
+        }
+        if let Some(espy_genres) = &self.espy_genres {
+            game_entry.espy_genres = espy_genres.clone();
+        }
+        if let Some(cover) = &self.cover {
+            game_entry.cover = Some(cover.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_replaces_only_pinned_fields() {
+        let mut game_entry = GameEntry {
+            id: 7,
+            name: "Zelda".to_owned(),
+            release_date: 100,
+            release_precision: ReleasePrecision::Year,
+            ..Default::default()
+        };
+
+        Override {
+            game_id: 7,
+            name: Some("The Legend of Zelda".to_owned()),
+            ..Default::default()
+        }
+        .apply(&mut game_entry);
+        assert_eq!(game_entry.name, "The Legend of Zelda");
+        assert_eq!(game_entry.release_date, 100);
+        assert_eq!(game_entry.release_precision, ReleasePrecision::Year);
+
+        Override {
+            game_id: 7,
+            release_date: Some(200),
+            ..Default::default()
+        }
+        .apply(&mut game_entry);
+        assert_eq!(game_entry.name, "The Legend of Zelda");
+        assert_eq!(game_entry.release_date, 200);
+        assert_eq!(game_entry.release_precision, ReleasePrecision::Exact);
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 /// Document type under 'genres' collection for quick lookup for
 /// game_id -> EspyGenres.
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Genre {
     pub game_id: u64,
 
@@ -11,7 +11,7 @@ pub struct Genre {
     pub espy_genres: Vec<EspyGenre>,
 }
 
-#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, Eq, PartialEq, Debug)]
 pub enum EspyGenre {
     #[default]
     Unknown = 0,
@@ -95,6 +95,25 @@ pub enum EspyGenre {
     MOBA = 62,
 }
 
+/// Top-level group of EspyGenres, e.g. all shooter subgenres roll up to
+/// `Shooter`. The mapping lives in `genres::genre_group()`.
+#[derive(
+    Serialize, Deserialize, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug,
+)]
+pub enum EspyGenreGroup {
+    #[default]
+    Unknown = 0,
+
+    Adventure = 1,
+    Arcade = 2,
+    Casual = 3,
+    Platformer = 4,
+    RPG = 5,
+    Shooter = 6,
+    Simulator = 7,
+    Strategy = 8,
+}
+
 impl EspyGenre {
     pub fn from_user_tag(description: &str) -> Self {
         match description {
@@ -15,6 +15,11 @@ pub struct GogData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub critic_score: Option<u64>,
 
+    /// Number of user votes behind the rating of the game on GOG.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub votes: Option<u64>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub genres: Vec<String>,
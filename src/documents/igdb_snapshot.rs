@@ -0,0 +1,71 @@
+use std::io::{Read, Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::{api::IgdbGame, Status};
+
+/// Document type under 'games/{game_id}/igdb_snapshots' that keeps the raw
+/// IgdbGame payload of a webhook update, so that bad resolves can be traced
+/// back to what IGDB sent.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct IgdbSnapshot {
+    pub game_id: u64,
+
+    /// Timestamp (in milliseconds) the update was received.
+    pub timestamp: i64,
+
+    /// Gzipped JSON of the IgdbGame, base64 encoded.
+    pub payload: String,
+}
+
+impl IgdbSnapshot {
+    pub fn new(igdb_game: &IgdbGame, timestamp: i64) -> Result<Self, Status> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&serde_json::to_vec(igdb_game)?)?;
+
+        Ok(IgdbSnapshot {
+            game_id: igdb_game.id,
+            timestamp,
+            payload: STANDARD.encode(encoder.finish()?),
+        })
+    }
+
+    /// Returns the IgdbGame stored in the snapshot.
+    pub fn decode(&self) -> Result<IgdbGame, Status> {
+        let compressed = STANDARD.decode(&self.payload).map_err(|e| {
+            Status::internal(format!(
+                "Invalid payload in snapshot {}/{}: {e}",
+                self.game_id, self.timestamp
+            ))
+        })?;
+
+        let mut json = vec![];
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut json)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_returns_encoded_game() {
+        let igdb_game = IgdbGame {
+            id: 1942,
+            name: "The Witcher 3: Wild Hunt".to_owned(),
+            first_release_date: Some(1431993600),
+            ..Default::default()
+        };
+
+        let snapshot = IgdbSnapshot::new(&igdb_game, 1_700_000_000_000).unwrap();
+        let decoded = snapshot.decode().unwrap();
+
+        assert_eq!(snapshot.game_id, 1942);
+        assert_eq!(decoded.id, igdb_game.id);
+        assert_eq!(decoded.name, igdb_game.name);
+        assert_eq!(decoded.first_release_date, igdb_game.first_release_date);
+    }
+}
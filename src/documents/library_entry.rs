@@ -6,13 +6,87 @@ use std::{
 
 use super::{GameCategory, GameDigest, GameEntry, StoreEntry};
 
-/// Document type under 'users/{user_id}/games/library' that includes user's
-/// library with games matched with an IGDB entry.
+/// User's library with games matched with an IGDB entry. Each entry is stored
+/// as a document under 'users/{user_id}/library', libraries that have not been
+/// migrated yet are a single document under 'users/{user_id}/games/library'.
+///
+/// Entries are read in the order they were added. Views that need a specific
+/// order sort them when the library is read, see `Library::sort()`.
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Library {
     pub entries: Vec<LibraryEntry>,
 }
 
+impl Library {
+    /// Sorts library entries in the order of `sort`.
+    pub fn sort(&mut self, sort: LibrarySort) {
+        match sort {
+            LibrarySort::ReleaseDate => self
+                .entries
+                .sort_by(|l, r| r.digest.release_date.cmp(&l.digest.release_date)),
+            LibrarySort::Name => self
+                .entries
+                .sort_by_cached_key(|e| e.digest.name.to_lowercase()),
+            LibrarySort::AddedDate => self.entries.sort_by(|l, r| r.added_date.cmp(&l.added_date)),
+            LibrarySort::Score => self
+                .entries
+                .sort_by(|l, r| r.digest.scores.espy_score.cmp(&l.digest.scores.espy_score)),
+        }
+    }
+
+    /// Moves the entry of game `duplicate_id` to the game of `target`. If the
+    /// target game is already in the library the store entries of the two are
+    /// combined. Returns true if the library changed.
+    pub fn merge_game(&mut self, duplicate_id: u64, target: &GameDigest) -> bool {
+        let index = match self.entries.iter().position(|e| e.id == duplicate_id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let duplicate = self.entries.remove(index);
+
+        match self.entries.iter_mut().find(|e| e.id == target.id) {
+            Some(existing) => {
+                for store_entry in duplicate.store_entries {
+                    if existing.store_entries.iter().all(|e| *e != store_entry) {
+                        existing.store_entries.push(store_entry);
+                    }
+                }
+                existing.added_date = match (existing.added_date, duplicate.added_date) {
+                    (Some(l), Some(r)) => Some(l.min(r)),
+                    (l, r) => l.or(r),
+                };
+            }
+            None => self.entries.insert(
+                index,
+                LibraryEntry {
+                    id: target.id,
+                    digest: target.clone(),
+                    ..duplicate
+                },
+            ),
+        }
+        true
+    }
+}
+
+/// Order of library entries when they are read.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LibrarySort {
+    /// Latest releases first.
+    #[default]
+    ReleaseDate,
+
+    /// Alphabetically by game name.
+    Name,
+
+    /// Most recently added first.
+    AddedDate,
+
+    /// Highest espy score first. Unscored games go last.
+    Score,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct LibraryEntry {
     pub id: u64,
@@ -25,6 +99,23 @@ pub struct LibraryEntry {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub added_date: Option<u64>,
+
+    /// Total playtime in minutes combined across storefronts.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playtime_minutes: Option<u64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub store_playtimes: Vec<StorePlaytime>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub achievements_unlocked: Option<u64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub achievements_total: Option<u64>,
 }
 
 impl LibraryEntry {
@@ -40,6 +131,7 @@ impl LibraryEntry {
                     .unwrap()
                     .as_secs(),
             ),
+            ..Default::default()
         }
     }
 
@@ -63,6 +155,33 @@ impl LibraryEntry {
         }
         entries
     }
+
+    /// Sets the playtime reported by a storefront and updates the combined
+    /// `playtime_minutes`. Returns true if the playtime changed.
+    pub fn set_store_playtime(&mut self, storefront_name: &str, minutes: u64) -> bool {
+        match self
+            .store_playtimes
+            .iter_mut()
+            .find(|playtime| playtime.storefront_name == storefront_name)
+        {
+            Some(playtime) if playtime.minutes == minutes => return false,
+            Some(playtime) => playtime.minutes = minutes,
+            None => self.store_playtimes.push(StorePlaytime {
+                storefront_name: storefront_name.to_owned(),
+                minutes,
+            }),
+        }
+
+        self.playtime_minutes = Some(self.store_playtimes.iter().map(|p| p.minutes).sum());
+        true
+    }
+}
+
+/// Playtime in minutes of a game as reported by a storefront.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct StorePlaytime {
+    pub storefront_name: String,
+    pub minutes: u64,
 }
 
 impl fmt::Display for LibraryEntry {
@@ -70,3 +189,94 @@ impl fmt::Display for LibraryEntry {
         write!(f, "LibraryEntry({}): '{}'", &self.id, &self.digest.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Scores;
+
+    fn entry(
+        id: u64,
+        name: &str,
+        release_date: i64,
+        added_date: u64,
+        score: Option<u64>,
+    ) -> LibraryEntry {
+        LibraryEntry {
+            id,
+            digest: GameDigest {
+                id,
+                name: name.to_owned(),
+                release_date: Some(release_date),
+                scores: Scores {
+                    espy_score: score,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            added_date: Some(added_date),
+            ..Default::default()
+        }
+    }
+
+    fn ids(library: &Library) -> Vec<u64> {
+        library.entries.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn sort_library() {
+        let mut library = Library {
+            entries: vec![
+                entry(1, "hades", 300, 10, None),
+                entry(2, "Baldur's Gate 3", 200, 30, Some(95)),
+                entry(3, "Celeste", 100, 20, Some(90)),
+            ],
+        };
+
+        library.sort(LibrarySort::ReleaseDate);
+        assert_eq!(ids(&library), vec![1, 2, 3]);
+        library.sort(LibrarySort::Name);
+        assert_eq!(ids(&library), vec![2, 3, 1]);
+        library.sort(LibrarySort::AddedDate);
+        assert_eq!(ids(&library), vec![2, 3, 1]);
+        library.sort(LibrarySort::Score);
+        assert_eq!(ids(&library), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn merge_game_moves_entry() {
+        let mut library = Library {
+            entries: vec![
+                entry(1, "hades", 300, 10, None),
+                entry(3, "Celeste", 100, 20, None),
+            ],
+        };
+        let target = entry(2, "Hades", 300, 0, None).digest;
+
+        assert!(library.merge_game(1, &target));
+        assert_eq!(ids(&library), vec![2, 3]);
+        assert_eq!(library.entries[0].digest.name, "Hades");
+        assert_eq!(library.entries[0].added_date, Some(10));
+        assert!(!library.merge_game(1, &target));
+    }
+
+    #[test]
+    fn merge_game_combines_with_target() {
+        let mut library = Library {
+            entries: vec![
+                entry(1, "hades", 300, 10, None),
+                entry(2, "Hades", 300, 30, None),
+            ],
+        };
+        library.entries[0].store_entries.push(StoreEntry {
+            id: "steam_1".to_owned(),
+            ..Default::default()
+        });
+        let target = library.entries[1].digest.clone();
+
+        assert!(library.merge_game(1, &target));
+        assert_eq!(ids(&library), vec![2]);
+        assert_eq!(library.entries[0].store_entries.len(), 1);
+        assert_eq!(library.entries[0].added_date, Some(10));
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::StoreEntry;
+
+/// Document type under 'users/{user_id}/library_events' that records a change
+/// of the games in a user's library.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct LibraryEvent {
+    pub id: String,
+    pub kind: LibraryEventKind,
+    pub game_id: u64,
+
+    #[serde(default)]
+    pub name: String,
+
+    /// Game that the store entry was matched to before a rematch.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_game_id: Option<u64>,
+
+    /// Store entry that was rematched.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_entry: Option<StoreEntry>,
+
+    /// Timestamp (in seconds) of the change.
+    pub timestamp: i64,
+}
+
+impl LibraryEvent {
+    pub fn new(kind: LibraryEventKind, game_id: u64, name: &str, timestamp: i64) -> Self {
+        LibraryEvent {
+            id: format!("{timestamp}_{game_id}_{kind:?}"),
+            kind,
+            game_id,
+            name: name.to_owned(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum LibraryEventKind {
+    /// Game was added in the library.
+    #[default]
+    Added,
+
+    /// Game was removed from the library.
+    Removed,
+
+    /// Store entry was matched to a different game.
+    Rematched,
+}
+
+/// Changes of a library between two points in time.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct LibraryDiff {
+    pub added: Vec<LibraryEvent>,
+    pub removed: Vec<LibraryEvent>,
+    pub rematched: Vec<LibraryEvent>,
+}
+
+impl LibraryDiff {
+    /// Builds the diff from `events` in the order they happened. Games that
+    /// were added and removed again within the period cancel out.
+    pub fn new(events: Vec<LibraryEvent>) -> Self {
+        let mut added = BTreeMap::new();
+        let mut removed = BTreeMap::new();
+        let mut rematched = vec![];
+
+        for event in events {
+            match event.kind {
+                LibraryEventKind::Added => {
+                    if removed.remove(&event.game_id).is_none() {
+                        added.insert(event.game_id, event);
+                    }
+                }
+                LibraryEventKind::Removed => {
+                    if added.remove(&event.game_id).is_none() {
+                        removed.insert(event.game_id, event);
+                    }
+                }
+                LibraryEventKind::Rematched => rematched.push(event),
+            }
+        }
+
+        LibraryDiff {
+            added: added.into_values().collect(),
+            removed: removed.into_values().collect(),
+            rematched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_cancels_out_changes_within_period() {
+        let event = |kind, game_id, timestamp| LibraryEvent::new(kind, game_id, "", timestamp);
+
+        let diff = LibraryDiff::new(vec![
+            event(LibraryEventKind::Added, 1, 10),
+            event(LibraryEventKind::Added, 2, 11),
+            event(LibraryEventKind::Removed, 3, 12),
+            event(LibraryEventKind::Removed, 2, 13),
+            event(LibraryEventKind::Added, 3, 14),
+            event(LibraryEventKind::Removed, 4, 15),
+            event(LibraryEventKind::Rematched, 5, 16),
+        ]);
+
+        assert_eq!(
+            diff.added.iter().map(|e| e.game_id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|e| e.game_id).collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(
+            diff.rematched.iter().map(|e| e.game_id).collect::<Vec<_>>(),
+            vec![5]
+        );
+    }
+}
@@ -1,43 +1,89 @@
+mod account_deletion;
 mod annual_review;
+mod backfill_entry;
 mod collection;
 mod company;
+mod deal_alert;
 mod external_game;
+mod follows;
 mod frontpage;
 mod game_digest;
+mod game_edit;
 mod game_entry;
+mod game_override;
 mod genre;
 mod gog_data;
+mod igdb_snapshot;
 mod keyword;
 mod library_entry;
+mod library_event;
 mod notable;
+mod notification;
+mod outbound_webhook;
+mod platform_scope;
+mod playtime;
+mod price;
 mod recent;
+mod rejected_game;
+mod report;
+mod resolve_task;
+mod retry_entry;
 mod scores;
+mod smart_filter;
 mod steam_data;
 mod store_entry;
 mod storefront;
+mod sync_token;
+mod tech_info;
 mod timeline;
 mod unresolved;
 mod user_data;
+mod user_feed;
 mod user_tags;
+mod webhook_delivery;
+mod wikipedia_page;
 
+pub use account_deletion::AccountDeletion;
 pub use annual_review::AnnualReview;
+pub use backfill_entry::BackfillEntry;
 pub use collection::Collection;
 pub use company::Company;
+pub use deal_alert::{DealAlert, DealAlerts};
 pub use external_game::ExternalGame;
+pub use follows::{FollowKind, Follows};
 pub use frontpage::Frontpage;
-pub use game_digest::GameDigest;
+pub use game_digest::{GameDigest, ReleaseCountdown};
+pub use game_edit::{GameEdit, GameEditKind};
 pub use game_entry::*;
+pub use game_override::Override;
 pub use genre::*;
 pub use gog_data::*;
+pub use igdb_snapshot::IgdbSnapshot;
 pub use keyword::Keyword;
-pub use library_entry::{Library, LibraryEntry};
+pub use library_entry::{Library, LibraryEntry, LibrarySort, StorePlaytime};
+pub use library_event::{LibraryDiff, LibraryEvent, LibraryEventKind};
 pub use notable::Notable;
+pub use notification::{Notification, NotificationEvent, Notifications};
+pub use outbound_webhook::{OutboundEvent, OutboundEventType, OutboundWebhook};
+pub use platform_scope::PlatformScope;
+pub use playtime::Playtime;
+pub use price::{PriceComparison, PriceHistory, ShopPrice, StorePrice};
 pub use recent::{Recent, RecentEntry};
+pub use rejected_game::RejectedGame;
+pub use report::{Report, ReportReason, ReportSuggestion};
+pub use resolve_task::{ResolveTask, ResolveTaskKind};
+pub use retry_entry::RetryEntry;
 pub use scores::*;
-pub use steam_data::{SteamData, SteamScore};
+pub use smart_filter::{SmartFilter, SmartFilters};
+pub use steam_data::{Metacritic, ReleaseDate, SteamAvailability, SteamData, SteamScore};
 pub use store_entry::{FailedEntries, StoreEntry};
-pub use storefront::Storefront;
+pub use storefront::{Storefront, StorefrontShard};
+pub use sync_token::{SyncToken, TokenScope};
+pub use tech_info::{FeatureSupport, TechInfo};
 pub use timeline::*;
 pub use unresolved::{Unresolved, UnresolvedEntries};
-pub use user_data::{Keys, UserData};
+pub use user_data::{ChannelKind, Keys, NotificationChannel, UserData};
+pub use user_feed::{FeedRelease, UserFeed};
 pub use user_tags::{UserAnnotations, UserTag};
+pub use webhook_delivery::{DeliveryStatus, WebhookDelivery};
+pub use wikipedia_page::WikipediaPage;
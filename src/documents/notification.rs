@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use super::{FollowKind, GameStatus, ReleasePrecision};
+
+/// Document type under 'users/{user_id}/user_data/notifications' that holds
+/// notifications about games in the user's library or wishlist.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Notifications {
+    #[serde(default)]
+    pub notifications: Vec<Notification>,
+}
+
+impl Notifications {
+    /// Adds `notification` replacing any unseen notification of the same kind
+    /// for the same game, so that repeated changes show up only once.
+    pub fn add(&mut self, notification: Notification) {
+        self.notifications.retain(|n| {
+            n.seen || n.game_id != notification.game_id || !n.event.same_kind(&notification.event)
+        });
+        self.notifications.push(notification);
+
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications
+                .drain(..self.notifications.len() - MAX_NOTIFICATIONS);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Notification {
+    pub game_id: u64,
+    pub name: String,
+
+    pub event: NotificationEvent,
+
+    /// Timestamp (in seconds) the notification was created.
+    pub timestamp: i64,
+
+    #[serde(default)]
+    pub seen: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum NotificationEvent {
+    /// Release date of the game moved. Dates are UNIX timestamps and 0 means
+    /// that the game has no release date.
+    ReleaseDateChanged {
+        old_release_date: i64,
+        new_release_date: i64,
+
+        #[serde(default)]
+        release_precision: ReleasePrecision,
+    },
+
+    /// A new game was added by a company, franchise or collection that the
+    /// user follows.
+    NewRelease {
+        followed_kind: FollowKind,
+        followed_id: u64,
+        followed_name: String,
+    },
+
+    /// Status of the game changed, e.g. it left early access.
+    StatusChanged {
+        old_status: GameStatus,
+        new_status: GameStatus,
+    },
+}
+
+impl NotificationEvent {
+    fn same_kind(&self, other: &NotificationEvent) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+const MAX_NOTIFICATIONS: usize = 100;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release_date_changed(game_id: u64, new_release_date: i64) -> Notification {
+        Notification {
+            game_id,
+            name: String::default(),
+            event: NotificationEvent::ReleaseDateChanged {
+                old_release_date: 0,
+                new_release_date,
+                release_precision: ReleasePrecision::Exact,
+            },
+            timestamp: 0,
+            seen: false,
+        }
+    }
+
+    #[test]
+    fn add_replaces_unseen_notification_of_game() {
+        let mut notifications = Notifications::default();
+        notifications.add(release_date_changed(1, 10));
+        notifications.add(release_date_changed(2, 10));
+        notifications.notifications[1].seen = true;
+
+        notifications.add(release_date_changed(1, 20));
+        notifications.add(release_date_changed(2, 20));
+
+        assert_eq!(notifications.notifications.len(), 3);
+        assert_eq!(notifications.notifications[0].game_id, 2);
+        assert!(notifications.notifications[0].seen);
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GameDigest, StoreEntry};
+
+/// Document type under 'outbound_webhooks' that registers a consumer URL that
+/// receives signed espy events.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct OutboundWebhook {
+    #[serde(default)]
+    pub id: String,
+
+    /// User id that owns the webhook or the name of an integration.
+    #[serde(default)]
+    pub owner: String,
+
+    pub url: String,
+
+    /// Shared secret used for signing event payloads.
+    pub secret: String,
+
+    #[serde(default)]
+    pub events: Vec<OutboundEventType>,
+
+    /// Games whose updates are delivered to the webhook.
+    #[serde(default)]
+    pub followed_games: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum OutboundEventType {
+    LibraryChanges,
+    GameUpdates,
+}
+
+/// Event payload that is delivered to outbound webhooks.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum OutboundEvent {
+    LibraryAdded {
+        user_id: String,
+        games: Vec<GameDigest>,
+    },
+    LibraryRemoved {
+        user_id: String,
+        store_entry: StoreEntry,
+    },
+    GameUpdated {
+        game: GameDigest,
+    },
+}
+
+impl OutboundEvent {
+    pub fn event_type(&self) -> OutboundEventType {
+        match self {
+            OutboundEvent::LibraryAdded { .. } | OutboundEvent::LibraryRemoved { .. } => {
+                OutboundEventType::LibraryChanges
+            }
+            OutboundEvent::GameUpdated { .. } => OutboundEventType::GameUpdates,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutboundEvent::LibraryAdded { .. } => "LibraryAdded",
+            OutboundEvent::LibraryRemoved { .. } => "LibraryRemoved",
+            OutboundEvent::GameUpdated { .. } => "GameUpdated",
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::env;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// IGDB platforms of the games that a deployment tracks. Games that are not
+/// released on any of them are left out of the catalog, search results and the
+/// timeline.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlatformScope {
+    #[serde(default)]
+    pub platforms: Vec<u64>,
+
+    #[serde(default)]
+    pub last_updated: u64,
+}
+
+impl Default for PlatformScope {
+    /// Deployments track PC games unless `ESPY_PLATFORMS` lists the IGDB
+    /// platform ids to track instead, e.g. "130,471".
+    fn default() -> Self {
+        let platforms = env::var("ESPY_PLATFORMS")
+            .ok()
+            .map(|platforms| parse_platforms(&platforms))
+            .filter(|platforms| !platforms.is_empty())
+            .unwrap_or_else(|| PC_PLATFORMS.to_vec());
+
+        PlatformScope {
+            platforms,
+            last_updated: 0,
+        }
+    }
+}
+
+impl PlatformScope {
+    /// Returns true if any of the IGDB `platforms` is in scope.
+    pub fn contains(&self, platforms: &[u64]) -> bool {
+        platforms
+            .iter()
+            .any(|platform| self.platforms.contains(platform))
+    }
+
+    /// Returns the IGDB query condition that matches games in scope.
+    pub fn igdb_condition(&self) -> String {
+        format!("platforms = ({})", self.platforms.iter().join(","))
+    }
+}
+
+fn parse_platforms(platforms: &str) -> Vec<u64> {
+    platforms
+        .split(',')
+        .filter_map(|platform| platform.trim().parse().ok())
+        .collect()
+}
+
+// IGDB ids of the PC (Windows) and Mac platforms.
+const PC_PLATFORMS: [u64; 2] = [6, 13];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_of_listed_platforms() {
+        let scope = PlatformScope {
+            platforms: parse_platforms("130, 471,x"),
+            last_updated: 0,
+        };
+
+        assert_eq!(scope.platforms, vec![130, 471]);
+        assert_eq!(scope.igdb_condition(), "platforms = (130,471)");
+        assert!(scope.contains(&[6, 130]));
+        assert!(!scope.contains(&[6, 13]));
+        assert!(!scope.contains(&[]));
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Estimated hours to complete a game as reported by HowLongToBeat.
+#[derive(Eq, PartialEq, Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Playtime {
+    // Hours for completing the main story.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main: Option<u64>,
+
+    // Hours for completing the main story and extras.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<u64>,
+
+    // Hours for 100% completion.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completionist: Option<u64>,
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'prices' that tracks the store prices of a wishlisted
+/// game. It is keyed on the IGDB id of the game.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PriceHistory {
+    pub game_id: u64,
+
+    #[serde(default)]
+    pub name: String,
+
+    /// Latest price of the game on each store it is sold.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub current: Vec<StorePrice>,
+
+    /// Price changes over time, oldest first. A new point is only added when
+    /// the price on a store changes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StorePrice>,
+
+    /// Timestamp (in seconds) of the last price refresh.
+    #[serde(default)]
+    pub last_updated: i64,
+}
+
+impl PriceHistory {
+    /// Updates the current price of the game on `price.store`. Returns true if
+    /// the price differs from the previously known one.
+    pub fn update(&mut self, price: StorePrice) -> bool {
+        let changed = match self.current.iter_mut().find(|p| p.store == price.store) {
+            Some(current) => {
+                let changed = !current.same_price(&price);
+                *current = price.clone();
+                changed
+            }
+            None => {
+                self.current.push(price.clone());
+                true
+            }
+        };
+
+        if changed {
+            self.history.push(price);
+            if self.history.len() > MAX_HISTORY_LEN {
+                self.history.drain(..self.history.len() - MAX_HISTORY_LEN);
+            }
+        }
+        changed
+    }
+}
+
+/// Price of a game on a store at a point in time.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct StorePrice {
+    pub store: String,
+
+    /// ISO 4217 currency code, e.g. "USD".
+    pub currency: String,
+
+    /// Price without discount in cents.
+    pub initial: u64,
+
+    /// Price after discount in cents.
+    #[serde(rename = "final")]
+    pub final_price: u64,
+
+    #[serde(default)]
+    pub discount_percent: u64,
+
+    /// Timestamp (in seconds) the price was retrieved.
+    #[serde(default)]
+    pub timestamp: i64,
+}
+
+impl StorePrice {
+    fn same_price(&self, other: &StorePrice) -> bool {
+        self.currency == other.currency
+            && self.initial == other.initial
+            && self.final_price == other.final_price
+    }
+}
+
+/// Cross-store price comparison of a game. It is retrieved from
+/// IsThereAnyDeal on demand and it is not stored.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PriceComparison {
+    pub currency: String,
+
+    /// Lowest current price across all stores.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best: Option<ShopPrice>,
+
+    /// Lowest price the game was ever sold for.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub historical_low: Option<ShopPrice>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ShopPrice {
+    pub shop: String,
+
+    /// Price in cents.
+    pub price: u64,
+
+    #[serde(default)]
+    pub discount_percent: u64,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Timestamp (in seconds) the price was recorded, for historical lows.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+}
+
+const MAX_HISTORY_LEN: usize = 200;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(store: &str, initial: u64, final_price: u64) -> StorePrice {
+        StorePrice {
+            store: store.to_owned(),
+            currency: "USD".to_owned(),
+            initial,
+            final_price,
+            discount_percent: 100 - final_price * 100 / initial,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn update_records_only_price_changes() {
+        let mut prices = PriceHistory::default();
+
+        assert!(prices.update(price("steam", 2000, 2000)));
+        assert!(!prices.update(price("steam", 2000, 2000)));
+        assert!(prices.update(price("gog", 2000, 2000)));
+        assert!(prices.update(price("steam", 2000, 1000)));
+
+        assert_eq!(prices.current.len(), 2);
+        assert_eq!(prices.history.len(), 3);
+        assert_eq!(prices.current[0].final_price, 1000);
+    }
+}
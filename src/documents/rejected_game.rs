@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'rejected' for a game that curators permanently
+/// blocklisted, e.g. spam or asset flips. Blocklisted games are dropped when
+/// IGDB webhooks deliver them again.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RejectedGame {
+    pub id: u64,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub reason: String,
+
+    /// Curator that blocklisted the game.
+    #[serde(default)]
+    pub curator: String,
+
+    /// Timestamp (in seconds) of when the game was blocklisted.
+    #[serde(default)]
+    pub timestamp: i64,
+}
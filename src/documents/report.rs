@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::ResolveSource;
+
+/// Document type under 'reports' that holds a user report about bad data on
+/// a game entry. There is at most one report per user for each game.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Report {
+    pub id: String,
+
+    pub game_id: u64,
+    pub user_id: String,
+
+    pub reason: ReportReason,
+
+    #[serde(default)]
+    pub comment: String,
+
+    /// Timestamp (in seconds) of the last time the report was submitted.
+    #[serde(default)]
+    pub timestamp: i64,
+
+    /// Correction suggested by automated triage of the report.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<ReportSuggestion>,
+}
+
+impl Report {
+    /// Returns the document id of a report, which dedups reports per user.
+    pub fn doc_id(game_id: u64, user_id: &str) -> String {
+        format!("{game_id}_{user_id}")
+    }
+}
+
+/// Kind of bad data reported on a game.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ReportReason {
+    WrongCover,
+    WrongReleaseDate,
+    WrongMatch,
+    WrongScore,
+
+    #[default]
+    Other,
+}
+
+/// Correction for a GameEntry field that is suggested by comparing its value
+/// against external sources.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct ReportSuggestion {
+    /// Name of the GameEntry field that the correction applies to.
+    pub field: String,
+
+    pub current_value: String,
+    pub suggested_value: String,
+
+    /// External source that the suggested value comes from.
+    pub source: Option<ResolveSource>,
+}
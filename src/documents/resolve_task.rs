@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::IgdbGame;
+
+/// Document type under 'resolve_queue' that holds a webhook update waiting to
+/// be resolved.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ResolveTask {
+    pub id: u64,
+
+    pub igdb_game: IgdbGame,
+
+    #[serde(default)]
+    pub kind: ResolveTaskKind,
+
+    /// Timestamp (in milliseconds) when the task was enqueued. A newer update
+    /// of the same game replaces the task and its timestamp.
+    #[serde(default)]
+    pub enqueued: i64,
+
+    /// Timestamp (in seconds) until which the task is leased by a consumer.
+    /// Tasks whose lease expires without an ack are delivered again.
+    #[serde(default)]
+    pub lease_until: i64,
+
+    /// Number of times the task was delivered.
+    #[serde(default)]
+    pub deliveries: u32,
+}
+
+/// The webhook that produced a resolve task.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveTaskKind {
+    #[default]
+    AddGame,
+    UpdateGame,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::IgdbGame;
+
+/// Document type under 'retry_queue' that holds a game update whose resolve
+/// failed and needs to be retried.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RetryEntry {
+    pub id: u64,
+
+    pub igdb_game: IgdbGame,
+
+    /// Number of failed resolve attempts so far.
+    #[serde(default)]
+    pub attempts: u32,
+
+    /// Timestamp (in seconds) after which the entry can be retried.
+    #[serde(default)]
+    pub next_attempt: i64,
+
+    #[serde(default)]
+    pub last_error: String,
+}
@@ -2,7 +2,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
-use crate::api::{IgdbGame, MetacriticData, WikipediaScrapeData};
+use crate::api::{IgdbGame, MetacriticData, SteamSpyData, WikipediaScrapeData};
 
 use super::{GogData, SteamData};
 
@@ -15,6 +15,23 @@ pub struct ScoresDoc {
     pub scores: Scores,
 }
 
+/// Critic score from an archival source (e.g. GameRankings) for games that
+/// predate Metacritic coverage.
+#[derive(Eq, PartialEq, Serialize, Deserialize, Default, Clone, Debug)]
+pub struct LegacyScore {
+    pub id: u64,
+    pub name: String,
+    pub score: u64,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_count: Option<u64>,
+
+    // Name of the archive the score was ingested from.
+    #[serde(default)]
+    pub source: String,
+}
+
 #[derive(Eq, PartialEq, Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Scores {
     // Thumbs up percentage from Steam.
@@ -22,11 +39,22 @@ pub struct Scores {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbs: Option<u64>,
 
-    // Popularity measured as total reviews on Steam.
+    // Number of Steam user reviews behind the thumbs up percentage.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steam_reviews: Option<u64>,
+
+    // Popularity measured as total reviews on Steam, or an estimate of it from
+    // SteamSpy owners or IGDB follows.
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub popularity: Option<u64>,
 
+    // Source of the popularity signal.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PopularitySource::is_steam")]
+    pub popularity_source: PopularitySource,
+
     // Hype measured by IGDB follows and hypes for games that are not released yet.
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,6 +70,16 @@ pub struct Scores {
     #[serde(skip_serializing_if = "MetacrtitcSource::is_metacritic")]
     pub metacritic_source: MetacrtitcSource,
 
+    // Number of critic reviews behind the Metacritic score.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metacritic_reviews: Option<u64>,
+
+    // Number of GOG user votes behind a score sourced from GOG.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gog_votes: Option<u64>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub espy_score: Option<u64>,
@@ -50,6 +88,11 @@ pub struct Scores {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(skip_deserializing)]
     pub espy_tier: Option<EspyTier>,
+
+    // Confidence in `espy_score` based on the number of reviews behind it.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ScoreConfidence>,
 }
 
 impl Ord for Scores {
@@ -72,28 +115,25 @@ impl Scores {
     pub fn add_metacritic(&mut self, metacritic: MetacriticData, release_date: i64) {
         self.metacritic = Some(metacritic.score);
         self.metacritic_source = MetacrtitcSource::Metacritic;
-        self.espy_score = if is_classic(release_date) {
-            self.metacritic
-        } else {
-            match self.metacritic {
-                Some(score) => {
-                    let multiplier = match metacritic.review_count {
-                        count if count >= 20 => 1.0,
-                        count if count >= 10 => 0.9,
-                        _ => 0.75,
-                    };
-                    Some((score as f64 * multiplier).round() as u64)
-                }
-                None => None,
-            }
-        };
-        self.espy_tier = EspyTier::create(&self);
+        self.metacritic_reviews = Some(metacritic.review_count);
+        self.update_espy_score(release_date);
+    }
+
+    pub fn add_wikipedia(&mut self, wikipedia: &WikipediaScrapeData) {
+        if let Some(score) = wikipedia.score {
+            self.metacritic = Some(score);
+            self.metacritic_source = MetacrtitcSource::Wikipedia;
+            self.espy_score = Some(score);
+            self.confidence = ScoreConfidence::create(self);
+        }
     }
 
-    pub fn add_wikipedia(&mut self, wikipedia: WikipediaScrapeData) {
-        self.metacritic = Some(wikipedia.score);
-        self.metacritic_source = MetacrtitcSource::Wikipedia;
-        self.espy_score = Some(wikipedia.score);
+    pub fn add_legacy(&mut self, legacy: &LegacyScore) {
+        self.metacritic = Some(legacy.score);
+        self.metacritic_source = MetacrtitcSource::Legacy;
+        self.metacritic_reviews = legacy.review_count;
+        self.espy_score = Some(legacy.score);
+        self.confidence = ScoreConfidence::create(self);
     }
 
     pub fn add_gog(&mut self, gog_data: &GogData) {
@@ -104,7 +144,9 @@ impl Scores {
         if let Some(score) = gog_data.critic_score {
             self.metacritic = Some(score);
             self.metacritic_source = MetacrtitcSource::Gog;
+            self.gog_votes = gog_data.votes;
             self.espy_score = Some(score);
+            self.confidence = ScoreConfidence::create(self);
         }
     }
 
@@ -113,13 +155,13 @@ impl Scores {
             if score.review_score > 0 {
                 self.thumbs = Some(score.review_score);
             }
+            if score.total_reviews > 0 {
+                self.steam_reviews = Some(score.total_reviews);
+            }
         }
 
         if let Some(rec) = &steam_data.recommendations {
-            self.popularity = match rec.total {
-                0 => None,
-                _ => Some(rec.total),
-            };
+            self.set_popularity(rec.total, PopularitySource::Steam);
         }
 
         if self.metacritic.is_none() {
@@ -127,32 +169,95 @@ impl Scores {
                 self.metacritic = Some(metacritic.score);
                 self.metacritic_source = MetacrtitcSource::Steam;
             }
+            self.update_espy_score(release_date);
+        }
+    }
 
-            self.espy_score = if is_classic(release_date) {
-                self.metacritic
-            } else {
-                match self.metacritic {
-                    Some(score) => {
-                        let pop_multiplier = match self.popularity {
-                            Some(pop) if pop >= 5000 => 1.0,
-                            Some(pop) if pop >= 3000 => 0.9,
-                            Some(pop) if pop >= 1000 => 0.75,
-                            _ => 0.5,
-                        };
-                        Some((score as f64 * pop_multiplier).round() as u64)
+    /// Recomputes `espy_score` and `espy_tier` from the signals already
+    /// collected in `Scores`.
+    ///
+    /// Scores sourced from Metacritic are weighted by their critic review
+    /// count, scores sourced from Steam by Steam popularity. Scores from
+    /// Wikipedia, GOG and legacy archives, as well as scores of classic games,
+    /// are used as is. Metacritic scores without a recorded review count keep
+    /// their current `espy_score`.
+    pub fn update_espy_score(&mut self, release_date: i64) {
+        self.espy_score = match self.metacritic {
+            Some(score) if is_classic(release_date) => Some(score),
+            // Older entries did not record the review count, so the multiplier
+            // that produced their score is unknown. Keep the stored score.
+            Some(_)
+                if self.metacritic_source == MetacrtitcSource::Metacritic
+                    && self.metacritic_reviews.is_none() =>
+            {
+                self.espy_score
+            }
+            Some(score) => {
+                let multiplier = match self.metacritic_source {
+                    MetacrtitcSource::Metacritic => {
+                        review_multiplier(self.metacritic_reviews.unwrap_or_default())
                     }
-                    None => None,
-                }
-            };
-            self.espy_tier = EspyTier::create(&self);
-        }
+                    MetacrtitcSource::Steam => popularity_multiplier(self.popularity),
+                    MetacrtitcSource::Wikipedia
+                    | MetacrtitcSource::Gog
+                    | MetacrtitcSource::Legacy => 1.0,
+                };
+                Some((score as f64 * multiplier).round() as u64)
+            }
+            None => None,
+        };
+        self.espy_tier = EspyTier::create(&self);
+        self.confidence = ScoreConfidence::create(&self);
+    }
+
+    /// Estimates popularity from SteamSpy owners and concurrent players for
+    /// games that have no Steam reviews.
+    pub fn add_steam_spy(&mut self, steam_spy: &SteamSpyData, release_date: i64) {
+        let estimate = (steam_spy.owners / OWNERS_PER_REVIEW).max(steam_spy.ccu * REVIEWS_PER_CCU);
+        self.set_popularity(estimate, PopularitySource::SteamSpy);
+        self.update_espy_score(release_date);
     }
 
     pub fn add_igdb(&mut self, igdb_game: &IgdbGame) {
         self.hype = igdb_game.hypes;
+        if let Some(follows) = igdb_game.follows {
+            self.set_popularity(follows, PopularitySource::Igdb);
+        }
+    }
+
+    /// Sets `popularity` unless it is already set from a source higher in the
+    /// fallback chain: Steam reviews, SteamSpy owners, IGDB follows.
+    fn set_popularity(&mut self, popularity: u64, source: PopularitySource) {
+        if popularity == 0 || (self.popularity.is_some() && self.popularity_source > source) {
+            return;
+        }
+        self.popularity = Some(popularity);
+        self.popularity_source = source;
     }
 }
 
+/// Source of the popularity signal, ordered by increasing precedence.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Default, Clone, Copy, Debug)]
+pub enum PopularitySource {
+    Igdb,
+    SteamSpy,
+    #[default]
+    Steam,
+}
+
+impl PopularitySource {
+    fn is_steam(&self) -> bool {
+        matches!(self, PopularitySource::Steam)
+    }
+}
+
+// Rough ratio of owners to reviews for Steam games, used to put SteamSpy owner
+// estimates on the same scale as review counts.
+const OWNERS_PER_REVIEW: u64 = 30;
+
+// Review count equivalent of one concurrent player.
+const REVIEWS_PER_CCU: u64 = 10;
+
 #[derive(Eq, PartialEq, Serialize, Deserialize, Default, Clone, Debug)]
 pub enum MetacrtitcSource {
     #[default]
@@ -160,6 +265,7 @@ pub enum MetacrtitcSource {
     Wikipedia,
     Steam,
     Gog,
+    Legacy,
 }
 
 impl MetacrtitcSource {
@@ -168,6 +274,23 @@ impl MetacrtitcSource {
     }
 }
 
+fn review_multiplier(review_count: u64) -> f64 {
+    match review_count {
+        count if count >= 20 => 1.0,
+        count if count >= 10 => 0.9,
+        _ => 0.75,
+    }
+}
+
+fn popularity_multiplier(popularity: Option<u64>) -> f64 {
+    match popularity {
+        Some(pop) if pop >= 5000 => 1.0,
+        Some(pop) if pop >= 3000 => 0.9,
+        Some(pop) if pop >= 1000 => 0.75,
+        _ => 0.5,
+    }
+}
+
 // Returns true if game was released before 2011.
 fn is_classic(release_date: i64) -> bool {
     const _41_YEARS: Duration = Duration::from_secs(41 * 365 * 24 * 60 * 60);
@@ -200,3 +323,150 @@ impl EspyTier {
         }
     }
 }
+
+/// Confidence in an espy score based on the number of reviews behind it, e.g.
+/// a 95 from 3 critic reviews has `Low` confidence.
+///
+/// Ordered by increasing confidence. Scores from sources that do not report
+/// review counts are `Unknown` and rank above `Low` ones.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ScoreConfidence {
+    Low,
+    Unknown,
+    Medium,
+    High,
+}
+
+impl ScoreConfidence {
+    pub fn create(scores: &Scores) -> Option<Self> {
+        scores.espy_score?;
+
+        let (count, medium, high) = match scores.metacritic_source {
+            MetacrtitcSource::Metacritic | MetacrtitcSource::Legacy => (
+                scores.metacritic_reviews,
+                MEDIUM_CRITIC_REVIEWS,
+                HIGH_CRITIC_REVIEWS,
+            ),
+            // Steam does not report the critic reviews behind its Metacritic
+            // score, so its user reviews stand in for them.
+            MetacrtitcSource::Steam => (
+                scores.steam_reviews,
+                MEDIUM_STEAM_REVIEWS,
+                HIGH_STEAM_REVIEWS,
+            ),
+            MetacrtitcSource::Gog => (scores.gog_votes, MEDIUM_GOG_VOTES, HIGH_GOG_VOTES),
+            MetacrtitcSource::Wikipedia => (None, 0, 0),
+        };
+
+        Some(match count {
+            Some(count) if count >= high => Self::High,
+            Some(count) if count >= medium => Self::Medium,
+            Some(_) => Self::Low,
+            None => Self::Unknown,
+        })
+    }
+}
+
+// Critic reviews behind a Metacritic score for each confidence tier.
+const MEDIUM_CRITIC_REVIEWS: u64 = 10;
+const HIGH_CRITIC_REVIEWS: u64 = 20;
+
+// Steam user reviews behind a Steam sourced score for each confidence tier.
+const MEDIUM_STEAM_REVIEWS: u64 = 500;
+const HIGH_STEAM_REVIEWS: u64 = 5000;
+
+// GOG user votes behind a GOG score for each confidence tier.
+const MEDIUM_GOG_VOTES: u64 = 30;
+const HIGH_GOG_VOTES: u64 = 200;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confidence_follows_review_count() {
+        let scores = |metacritic_reviews| Scores {
+            espy_score: Some(95),
+            metacritic_reviews,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(3))),
+            Some(ScoreConfidence::Low)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(12))),
+            Some(ScoreConfidence::Medium)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(40))),
+            Some(ScoreConfidence::High)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(None)),
+            Some(ScoreConfidence::Unknown)
+        );
+        assert_eq!(ScoreConfidence::create(&Scores::default()), None);
+    }
+
+    #[test]
+    fn gog_scores_use_votes() {
+        let mut scores = Scores::default();
+        scores.add_gog(&GogData {
+            critic_score: Some(90),
+            votes: Some(12),
+            ..Default::default()
+        });
+
+        assert_eq!(scores.gog_votes, Some(12));
+        assert_eq!(scores.confidence, Some(ScoreConfidence::Low));
+    }
+
+    #[test]
+    fn steam_scores_use_steam_reviews() {
+        let scores = |steam_reviews| Scores {
+            espy_score: Some(85),
+            metacritic_source: MetacrtitcSource::Steam,
+            metacritic_reviews: Some(40),
+            steam_reviews,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(120))),
+            Some(ScoreConfidence::Low)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(800))),
+            Some(ScoreConfidence::Medium)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(Some(12000))),
+            Some(ScoreConfidence::High)
+        );
+        assert_eq!(
+            ScoreConfidence::create(&scores(None)),
+            Some(ScoreConfidence::Unknown)
+        );
+    }
+
+    #[test]
+    fn metacritic_without_review_count_keeps_score() {
+        let mut scores = Scores {
+            metacritic: Some(90),
+            espy_score: Some(90),
+            popularity: Some(100),
+            ..Default::default()
+        };
+        scores.update_espy_score(RELEASE_2020);
+        assert_eq!(scores.espy_score, Some(90));
+
+        scores.metacritic_reviews = Some(5);
+        scores.update_espy_score(RELEASE_2020);
+        assert_eq!(scores.espy_score, Some(68));
+    }
+
+    // 2020-01-01T00:00:00Z
+    const RELEASE_2020: i64 = 1577836800;
+}
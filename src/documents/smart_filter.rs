@@ -0,0 +1,202 @@
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::genres;
+
+use super::{EspyGenre, EspyGenreGroup, GameDigest};
+
+/// Document type under 'users/{user_id}/user_data/filters' that holds the
+/// smart filters saved by the user.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SmartFilters {
+    #[serde(default)]
+    pub filters: Vec<SmartFilter>,
+}
+
+/// A named query over the user's library. Predicates that are not set match
+/// all entries.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SmartFilter {
+    pub name: String,
+
+    // Matches entries with any of the genres.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub espy_genres: Vec<EspyGenre>,
+
+    // Matches entries with a genre in any of the groups.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub espy_genre_groups: Vec<EspyGenreGroup>,
+
+    // Matches entries with all of the keywords.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<u64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_year: Option<i32>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_year: Option<i32>,
+
+    // Matches only entries that support mods through the Steam Workshop.
+    #[serde(default)]
+    pub moddable: bool,
+}
+
+impl SmartFilter {
+    /// Returns true if the `digest` satisfies all predicates of the filter.
+    pub fn matches(&self, digest: &GameDigest) -> bool {
+        self.matches_genres(digest)
+            && self.matches_genre_groups(digest)
+            && self.matches_keywords(digest)
+            && self.matches_score(digest)
+            && self.matches_year(digest)
+            && (!self.moddable || digest.moddable)
+    }
+
+    fn matches_genres(&self, digest: &GameDigest) -> bool {
+        self.espy_genres.is_empty()
+            || self
+                .espy_genres
+                .iter()
+                .any(|genre| digest.espy_genres.contains(genre))
+    }
+
+    fn matches_genre_groups(&self, digest: &GameDigest) -> bool {
+        if self.espy_genre_groups.is_empty() {
+            return true;
+        }
+
+        // Groups are derived from the genres, as digests stored before genre
+        // groups were introduced do not carry them.
+        genres::genre_groups(&digest.espy_genres)
+            .iter()
+            .any(|group| self.espy_genre_groups.contains(group))
+    }
+
+    fn matches_keywords(&self, digest: &GameDigest) -> bool {
+        self.keywords
+            .iter()
+            .all(|keyword| digest.keywords.contains(keyword))
+    }
+
+    fn matches_score(&self, digest: &GameDigest) -> bool {
+        match self.min_score {
+            Some(min_score) => {
+                matches!(digest.scores.espy_score, Some(score) if score >= min_score)
+            }
+            None => true,
+        }
+    }
+
+    fn matches_year(&self, digest: &GameDigest) -> bool {
+        if self.min_year.is_none() && self.max_year.is_none() {
+            return true;
+        }
+
+        let year = match digest
+            .release_date
+            .and_then(|date| NaiveDateTime::from_timestamp_opt(date, 0))
+        {
+            Some(date) => date.year(),
+            None => return false,
+        };
+        self.min_year.map_or(true, |min_year| year >= min_year)
+            && self.max_year.map_or(true, |max_year| year <= max_year)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Scores;
+
+    fn digest() -> GameDigest {
+        GameDigest {
+            id: 7,
+            name: "Game".to_owned(),
+            // 2015-06-15
+            release_date: Some(1434326400),
+            scores: Scores {
+                espy_score: Some(85),
+                ..Default::default()
+            },
+            espy_genres: vec![EspyGenre::Action],
+            keywords: vec!["pixel art".to_owned(), "roguelike".to_owned()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_all() {
+        assert!(SmartFilter::default().matches(&digest()));
+        assert!(SmartFilter::default().matches(&GameDigest::default()));
+    }
+
+    #[test]
+    fn matches_all_predicates() {
+        let filter = SmartFilter {
+            name: "Good roguelikes".to_owned(),
+            espy_genres: vec![EspyGenre::PointAndClick, EspyGenre::Action],
+            espy_genre_groups: vec![EspyGenreGroup::Adventure],
+            keywords: vec!["roguelike".to_owned()],
+            min_score: Some(80),
+            min_year: Some(2010),
+            max_year: Some(2015),
+            moddable: false,
+        };
+        assert!(filter.matches(&digest()));
+    }
+
+    #[test]
+    fn rejects_on_any_predicate() {
+        let genre = SmartFilter {
+            espy_genres: vec![EspyGenre::PointAndClick],
+            ..Default::default()
+        };
+        let group = SmartFilter {
+            espy_genre_groups: vec![EspyGenreGroup::Shooter, EspyGenreGroup::RPG],
+            ..Default::default()
+        };
+        let keywords = SmartFilter {
+            keywords: vec!["roguelike".to_owned(), "metroidvania".to_owned()],
+            ..Default::default()
+        };
+        let score = SmartFilter {
+            min_score: Some(90),
+            ..Default::default()
+        };
+        let year = SmartFilter {
+            max_year: Some(2014),
+            ..Default::default()
+        };
+        let moddable = SmartFilter {
+            moddable: true,
+            ..Default::default()
+        };
+
+        assert!(!genre.matches(&digest()));
+        assert!(!group.matches(&digest()));
+        assert!(!keywords.matches(&digest()));
+        assert!(!score.matches(&digest()));
+        assert!(!year.matches(&digest()));
+        assert!(!moddable.matches(&digest()));
+    }
+
+    #[test]
+    fn year_predicate_rejects_unknown_release() {
+        let filter = SmartFilter {
+            min_year: Some(2000),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&GameDigest::default()));
+    }
+}
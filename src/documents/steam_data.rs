@@ -1,6 +1,8 @@
-use chrono::NaiveDateTime;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+use super::ReleasePrecision;
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct SteamData {
     pub name: String,
@@ -49,6 +51,10 @@ pub struct SteamData {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub genres: Vec<Genre>,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<Category>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub user_tags: Vec<String>,
@@ -60,6 +66,36 @@ pub struct SteamData {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub movies: Vec<Movie>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SteamAvailability::is_available")]
+    pub availability: SteamAvailability,
+
+    /// Number of published items in the Steam Workshop of the game. It is not
+    /// part of the store details and is counted separately.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workshop_items: Option<u64>,
+}
+
+/// Whether the Steam store page of an app can be retrieved.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SteamAvailability {
+    #[default]
+    Available,
+
+    /// The app is not sold in the default store region but is listed in
+    /// other regions.
+    RegionLocked,
+
+    /// The app is not listed in any store region that was checked.
+    Delisted,
+}
+
+impl SteamAvailability {
+    pub fn is_available(&self) -> bool {
+        *self == SteamAvailability::Available
+    }
 }
 
 impl SteamData {
@@ -87,11 +123,77 @@ impl SteamData {
             None => None,
         }
     }
+
+    /// Returns the estimated release of an upcoming game from the free-text
+    /// date on its Steam page, e.g. "Q2 2026" or "March 2026".
+    ///
+    /// Coarse dates are encoded as the last day of their period following the
+    /// IGDB convention. Returns None if the game is not marked as coming soon
+    /// or the date text is not a date, e.g. "To be announced".
+    pub fn release_estimate(&self) -> Option<(i64, ReleasePrecision)> {
+        let release_date = self.release_date.as_ref()?;
+        if !release_date.coming_soon {
+            return None;
+        }
+
+        if let Some(timestamp) = self.release_timestamp() {
+            return Some((timestamp, ReleasePrecision::Exact));
+        }
+
+        let text = release_date.date.trim();
+        let (date, precision) = match text.split_whitespace().collect::<Vec<_>>()[..] {
+            [year] => (last_day(year.parse().ok()?, 12)?, ReleasePrecision::Year),
+            [quarter, year] if quarter.starts_with(['Q', 'q']) => {
+                let quarter = quarter[1..]
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|q| (1..=4).contains(q))?;
+                (
+                    last_day(year.parse().ok()?, quarter * 3)?,
+                    ReleasePrecision::Quarter,
+                )
+            }
+            [month, year] => {
+                let month = NaiveDate::parse_from_str(&format!("1 {month} 2000"), "%d %B %Y")
+                    .or_else(|_| NaiveDate::parse_from_str(&format!("1 {month} 2000"), "%d %b %Y"))
+                    .ok()?;
+                (
+                    last_day(year.parse().ok()?, month.month())?,
+                    ReleasePrecision::Month,
+                )
+            }
+            _ => return None,
+        };
+
+        Some((date.and_hms_opt(12, 0, 0)?.timestamp(), precision))
+    }
+
+    /// Returns true if the game supports the Steam Workshop for mods.
+    pub fn has_workshop(&self) -> bool {
+        self.categories
+            .iter()
+            .any(|category| category.id == WORKSHOP_CATEGORY)
+    }
 }
 
+/// Returns the last day of `month` in `year`.
+fn last_day(year: i32, month: u32) -> Option<NaiveDate> {
+    match month {
+        12 => NaiveDate::from_ymd_opt(year, 12, 31),
+        _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?.pred_opt(),
+    }
+}
+
+// Steam lists Workshop support as a category in appdetails.
+const WORKSHOP_CATEGORY: u64 = 30;
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct ReleaseDate {
+    /// True for games that are not released yet.
     pub coming_soon: bool,
+
+    /// Free-text release date as shown on the Steam page, which can be an
+    /// exact date, a period like "Q2 2026" or text like "Coming soon".
     pub date: String,
 }
 
@@ -124,6 +226,12 @@ pub struct Genre {
     pub description: String,
 }
 
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Category {
+    pub id: u64,
+    pub description: String,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Screenshot {
     pub id: u64,
@@ -143,3 +251,97 @@ pub struct Movie {
 pub struct WebM {
     pub max: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steam_data(coming_soon: bool, date: &str) -> SteamData {
+        SteamData {
+            release_date: Some(ReleaseDate {
+                coming_soon,
+                date: date.to_owned(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn date(timestamp: i64) -> NaiveDate {
+        NaiveDateTime::from_timestamp_opt(timestamp, 0)
+            .unwrap()
+            .date()
+    }
+
+    #[test]
+    fn release_estimate_exact_date() {
+        let (timestamp, precision) = steam_data(true, "14 Feb, 2026").release_estimate().unwrap();
+
+        assert_eq!(
+            date(timestamp),
+            NaiveDate::from_ymd_opt(2026, 2, 14).unwrap()
+        );
+        assert_eq!(precision, ReleasePrecision::Exact);
+    }
+
+    #[test]
+    fn release_estimate_month() {
+        let (timestamp, precision) = steam_data(true, "February 2026")
+            .release_estimate()
+            .unwrap();
+
+        assert_eq!(
+            date(timestamp),
+            NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()
+        );
+        assert_eq!(precision, ReleasePrecision::Month);
+    }
+
+    #[test]
+    fn release_estimate_quarter() {
+        let (timestamp, precision) = steam_data(true, "Q2 2026").release_estimate().unwrap();
+
+        assert_eq!(
+            date(timestamp),
+            NaiveDate::from_ymd_opt(2026, 6, 30).unwrap()
+        );
+        assert_eq!(precision, ReleasePrecision::Quarter);
+    }
+
+    #[test]
+    fn release_estimate_year() {
+        let (timestamp, precision) = steam_data(true, "2026").release_estimate().unwrap();
+
+        assert_eq!(date(timestamp).year(), 2026);
+        assert_eq!(precision, ReleasePrecision::Year);
+    }
+
+    #[test]
+    fn release_estimate_without_date() {
+        assert!(steam_data(true, "Coming soon").release_estimate().is_none());
+        assert!(steam_data(true, "To be announced")
+            .release_estimate()
+            .is_none());
+        assert!(steam_data(false, "2026").release_estimate().is_none());
+    }
+
+    #[test]
+    fn workshop_category() {
+        let steam_data: SteamData = serde_json::from_str(
+            r#"{
+                "name": "Skyrim",
+                "steam_appid": 489830,
+                "detailed_description": "",
+                "short_description": "",
+                "about_the_game": "",
+                "categories": [
+                    {"id": 2, "description": "Single-player"},
+                    {"id": 30, "description": "Steam Workshop"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(steam_data.has_workshop());
+        assert!(!SteamData::default().has_workshop());
+    }
+}
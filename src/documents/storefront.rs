@@ -8,3 +8,24 @@ pub struct Storefront {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub entries: Vec<StoreEntry>,
 }
+
+/// Document type under 'users/{user_id}/storefronts/{storefront_name}_{page}'
+/// with a page of the store entries that a user owns in a storefront.
+///
+/// Storefronts are sharded in pages, so that large accounts do not exceed the
+/// Firestore document size limit.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct StorefrontShard {
+    pub storefront_name: String,
+    pub page: u32,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<StoreEntry>,
+}
+
+impl StorefrontShard {
+    pub fn doc_id(&self) -> String {
+        format!("{}_{}", self.storefront_name, self.page)
+    }
+}
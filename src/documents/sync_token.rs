@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'sync_tokens' that grants a companion app scoped,
+/// short-lived access to a user's data.
+///
+/// Docs are keyed by the SHA-256 of the token, so that tokens cannot be
+/// recovered from Firestore. Deleting the doc revokes the token.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SyncToken {
+    pub id: String,
+    pub user_id: String,
+    pub scopes: Vec<TokenScope>,
+
+    /// User provided name of the token, e.g. the device it was minted for.
+    #[serde(default)]
+    pub label: String,
+
+    /// Timestamps (in seconds) of the token's creation and expiration.
+    pub created: i64,
+    pub expires_at: i64,
+}
+
+impl SyncToken {
+    /// Returns true if the token grants `scope` on the data of `user_id` at
+    /// `now`.
+    pub fn allows(&self, user_id: &str, scope: TokenScope, now: i64) -> bool {
+        self.user_id == user_id && self.scopes.contains(&scope) && now < self.expires_at
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TokenScope {
+    #[serde(rename = "library:read")]
+    LibraryRead,
+
+    #[serde(rename = "wishlist:write")]
+    WishlistWrite,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_allows_only_its_user_scopes_and_lifetime() {
+        let token = SyncToken {
+            user_id: "user".to_owned(),
+            scopes: vec![TokenScope::LibraryRead],
+            created: 100,
+            expires_at: 200,
+            ..Default::default()
+        };
+
+        assert!(token.allows("user", TokenScope::LibraryRead, 150));
+        assert!(!token.allows("user", TokenScope::WishlistWrite, 150));
+        assert!(!token.allows("other", TokenScope::LibraryRead, 150));
+        assert!(!token.allows("user", TokenScope::LibraryRead, 200));
+    }
+
+    #[test]
+    fn scopes_use_colon_names() {
+        assert_eq!(
+            serde_json::to_string(&vec![TokenScope::LibraryRead, TokenScope::WishlistWrite])
+                .unwrap(),
+            r#"["library:read","wishlist:write"]"#
+        );
+    }
+}
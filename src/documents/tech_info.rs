@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Technical details of the PC version of a game from PCGamingWiki.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TechInfo {
+    #[serde(default)]
+    pub last_updated: i64,
+
+    #[serde(default)]
+    pub ultrawide: FeatureSupport,
+
+    #[serde(default)]
+    pub controller: FeatureSupport,
+
+    #[serde(default)]
+    pub cloud_saves: FeatureSupport,
+
+    // DRM schemes the game uses, e.g. "Steam" or "Denuvo".
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub drm: Vec<String>,
+}
+
+/// Whether a game supports a feature, as recorded on PCGamingWiki.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeatureSupport {
+    #[default]
+    Unknown,
+    Yes,
+    No,
+    Limited,
+    Hackable,
+}
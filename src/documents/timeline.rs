@@ -14,6 +14,16 @@ pub struct Timeline {
     pub releases: Vec<ReleaseEvent>,
 }
 
+impl Timeline {
+    /// Removes the explanations of why games were included, that are only
+    /// served to curators.
+    pub fn strip_explanations(&mut self) {
+        for event in &mut self.releases {
+            event.explanations.clear();
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct ReleaseEvent {
     pub label: String,
@@ -23,4 +33,42 @@ pub struct ReleaseEvent {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub games: Vec<GameDigest>,
+
+    /// Why each of the `games` passed the filters of the event.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub explanations: Vec<Explanation>,
+}
+
+/// Reasons that a game was included in a frontpage or timeline event.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub game_id: u64,
+    pub reasons: Vec<InclusionReason>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InclusionReason {
+    /// IGDB hype is above the threshold.
+    Hype { hype: u64, threshold: u64 },
+
+    /// Developed by a company in the notable list.
+    NotableDeveloper { company: String },
+
+    /// Published by a company in the notable list.
+    NotablePublisher { company: String },
+
+    /// Has a metacritic score.
+    Metacritic { score: u64 },
+
+    /// Early access game with popularity above the threshold.
+    EarlyAccessPopularity { popularity: u64, threshold: u64 },
+
+    /// Released N years ago with an espy score above the minimum.
+    Anniversary {
+        years: i32,
+        espy_score: u64,
+        min_score: u64,
+    },
 }
@@ -8,6 +8,18 @@ pub struct UserData {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keys: Option<Keys>,
+
+    /// Minimum discount (percent) on a wishlisted game that triggers a deal
+    /// alert. The refresh_prices job default is used if not set.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deal_threshold: Option<u64>,
+
+    /// Channels that notifications are delivered to, in addition to the
+    /// in-app notifications.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notification_channels: Vec<NotificationChannel>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -28,3 +40,19 @@ pub struct Keys {
     #[serde(skip_serializing_if = "String::is_empty")]
     pub egs_auth_code: String,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NotificationChannel {
+    pub kind: ChannelKind,
+
+    /// Address of the user on the channel, i.e. an email address, a Discord
+    /// webhook URL or a Web Push registration token.
+    pub target: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+    Email,
+    Discord,
+    WebPush,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+use super::{DealAlert, GameDigest};
+
+/// Document type under 'users/{user_id}/user_data/feed' that holds the weekly
+/// feed of a user. It is rebuilt by the `build_user_feed` batch job.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct UserFeed {
+    /// Timestamp (in seconds) the feed was built.
+    #[serde(default)]
+    pub last_updated: i64,
+
+    /// Wishlisted games that release in the coming weeks.
+    #[serde(default)]
+    pub upcoming: Vec<GameDigest>,
+
+    /// Deals on wishlisted games that were detected in the last week.
+    #[serde(default)]
+    pub price_drops: Vec<DealAlert>,
+
+    /// Games of followed companies that were released in the last week.
+    #[serde(default)]
+    pub new_releases: Vec<FeedRelease>,
+}
+
+impl UserFeed {
+    pub fn is_empty(&self) -> bool {
+        self.upcoming.is_empty() && self.price_drops.is_empty() && self.new_releases.is_empty()
+    }
+}
+
+/// A new release of a company that the user follows.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct FeedRelease {
+    pub company_id: u64,
+    pub company_name: String,
+    pub digest: GameDigest,
+}
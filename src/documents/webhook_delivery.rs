@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Document type under 'webhook_deliveries' that logs the delivery of an event
+/// to an outbound webhook and holds its state while it is retried.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub url: String,
+    pub event: String,
+
+    /// Serialized JSON event that is delivered.
+    pub payload: String,
+
+    #[serde(default)]
+    pub status: DeliveryStatus,
+
+    /// Number of failed delivery attempts so far.
+    #[serde(default)]
+    pub attempts: u32,
+
+    /// Timestamp (in seconds) after which a pending delivery can be retried.
+    #[serde(default)]
+    pub next_attempt: i64,
+
+    #[serde(default)]
+    pub last_error: String,
+
+    /// Timestamp (in seconds) that the event was created.
+    #[serde(default)]
+    pub created: i64,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DeliveryStatus {
+    #[default]
+    Pending,
+    Delivered,
+    Failed,
+}
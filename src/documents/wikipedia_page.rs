@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The Wikipedia page of a game, either listed on IGDB or discovered through
+/// Wikidata.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct WikipediaPage {
+    pub id: u64,
+    pub name: String,
+    pub url: String,
+
+    // Companies listed in the infobox of the page.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub developers: Vec<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub publishers: Vec<String>,
+}
@@ -0,0 +1,110 @@
+use itertools::Itertools;
+
+use crate::documents::{EspyGenre, EspyGenreGroup};
+
+/// Returns the group that `genre` rolls up to.
+pub fn genre_group(genre: &EspyGenre) -> EspyGenreGroup {
+    match genre {
+        EspyGenre::Unknown => EspyGenreGroup::Unknown,
+
+        EspyGenre::PointAndClick
+        | EspyGenre::Action
+        | EspyGenre::IsometricAction
+        | EspyGenre::NarrativeAdventure
+        | EspyGenre::SurvivalAdventure
+        | EspyGenre::PuzzleAdventure
+        | EspyGenre::WalkingSimulator => EspyGenreGroup::Adventure,
+
+        EspyGenre::Fighting
+        | EspyGenre::BeatEmUp
+        | EspyGenre::Pinball
+        | EspyGenre::CardAndBoard
+        | EspyGenre::Deckbuilder => EspyGenreGroup::Arcade,
+
+        EspyGenre::LifeSim
+        | EspyGenre::FarmingSim
+        | EspyGenre::FishingSim
+        | EspyGenre::SailingSim
+        | EspyGenre::DatingSim
+        | EspyGenre::Puzzle
+        | EspyGenre::EndlessRunner
+        | EspyGenre::Rhythm
+        | EspyGenre::PartyGame
+        | EspyGenre::VisualNovel
+        | EspyGenre::Exploration => EspyGenreGroup::Casual,
+
+        EspyGenre::SideScroller
+        | EspyGenre::Metroidvania
+        | EspyGenre::Platformer3d
+        | EspyGenre::ShooterPlatformer
+        | EspyGenre::PrecisionPlatformer
+        | EspyGenre::PuzzlePlatformer => EspyGenreGroup::Platformer,
+
+        EspyGenre::CRPG
+        | EspyGenre::ARPG
+        | EspyGenre::ActionRpg
+        | EspyGenre::JRPG
+        | EspyGenre::FirstPersonRpg
+        | EspyGenre::TurnBasedRpg
+        | EspyGenre::RTwPRPG
+        | EspyGenre::DungeonCrawler
+        | EspyGenre::MMORPG => EspyGenreGroup::RPG,
+
+        EspyGenre::FirstPersonShooter
+        | EspyGenre::TopDownShooter
+        | EspyGenre::ThirdPersonShooter
+        | EspyGenre::SpaceShooter
+        | EspyGenre::Shmup
+        | EspyGenre::BattleRoyale => EspyGenreGroup::Shooter,
+
+        EspyGenre::CityBuilder
+        | EspyGenre::Tycoon
+        | EspyGenre::GodGame
+        | EspyGenre::Racing
+        | EspyGenre::Sports
+        | EspyGenre::FlightSimulator
+        | EspyGenre::CombatSimulator
+        | EspyGenre::NavalSimulator
+        | EspyGenre::DrivingSimulator
+        | EspyGenre::Survival => EspyGenreGroup::Simulator,
+
+        EspyGenre::TurnBasedStrategy
+        | EspyGenre::RealTimeStrategy
+        | EspyGenre::TurnBasedTactics
+        | EspyGenre::RealTimeTactics
+        | EspyGenre::GradStrategy
+        | EspyGenre::FourX
+        | EspyGenre::TowerDefense
+        | EspyGenre::MOBA => EspyGenreGroup::Strategy,
+    }
+}
+
+/// Returns the distinct groups that `genres` roll up to, in group order.
+pub fn genre_groups(genres: &[EspyGenre]) -> Vec<EspyGenreGroup> {
+    genres
+        .iter()
+        .map(genre_group)
+        .filter(|group| *group != EspyGenreGroup::Unknown)
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_are_deduped() {
+        assert_eq!(
+            genre_groups(&[
+                EspyGenre::ThirdPersonShooter,
+                EspyGenre::ActionRpg,
+                EspyGenre::FirstPersonShooter,
+                EspyGenre::Unknown,
+            ]),
+            vec![EspyGenreGroup::RPG, EspyGenreGroup::Shooter]
+        );
+        assert!(genre_groups(&[]).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+mod groups;
 mod predictor;
 
+pub use groups::{genre_group, genre_groups};
 pub use predictor::GenrePredictor;
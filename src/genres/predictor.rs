@@ -5,6 +5,7 @@ use tracing::instrument;
 
 use crate::{
     documents::{EspyGenre, GameEntry},
+    util::http_client,
     Status,
 };
 
@@ -19,7 +20,7 @@ impl GenrePredictor {
 
     #[instrument(level = "trace", skip(self, game_entry))]
     pub async fn predict(&self, game_entry: &GameEntry) -> Result<Vec<EspyGenre>, Status> {
-        let client = reqwest::Client::new();
+        let client = http_client::shared();
         let resp = client
             .post(format!("{}/genres", &self.url))
             .json(&GenrePredictRequest::new(game_entry))
@@ -42,7 +43,7 @@ impl GenrePredictor {
 
     #[instrument(level = "trace", skip(self, game_entry))]
     pub async fn debug(&self, game_entry: &GameEntry) -> Result<GenreDebugInfo, Status> {
-        let client = reqwest::Client::new();
+        let client = http_client::shared();
         let resp = client
             .post(format!("{}/genres_debug", &self.url))
             .json(&GenrePredictRequest::new(game_entry))
@@ -67,6 +68,7 @@ struct GenrePredictRequest {
 
     igdb_genres: Vec<String>,
     igdb_keywords: Vec<String>,
+    igdb_perspectives: Vec<String>,
     steam_genres: Vec<String>,
     steam_tags: Vec<String>,
     gog_genres: Vec<String>,
@@ -85,6 +87,11 @@ impl GenrePredictRequest {
                 .map(|genre| format!("{:?}", genre))
                 .collect(),
             igdb_keywords: game_entry.keywords.clone(),
+            igdb_perspectives: game_entry
+                .player_perspectives
+                .iter()
+                .map(|perspective| format!("{:?}", perspective))
+                .collect(),
 
             steam_genres: match &game_entry.steam_data {
                 Some(steam_data) => steam_data
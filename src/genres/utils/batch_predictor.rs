@@ -5,7 +5,7 @@ use std::{
 
 use chrono::NaiveDateTime;
 use clap::Parser;
-use documents::Genre;
+use documents::{GameEdit, GameEditKind, Genre};
 use espy_backend::{documents::GameEntry, *};
 use firestore::{path, FirestoreQueryDirection, FirestoreResult};
 use futures::{stream::BoxStream, StreamExt};
@@ -24,7 +24,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/batch_predictor")?;
+    let _tracing = Tracing::setup("utils/batch_predictor")?;
 
     let opts: Opts = Opts::parse();
     let mut cursor = opts.cursor;
@@ -38,7 +38,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from("games")
+            .from(firestore.collection("games").as_str())
             .filter(|q| {
                 q.for_all([q
                     .field(path!(GameEntry::release_date))
@@ -75,6 +75,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     let espy_genres = predictor.predict(&game_entry).await?;
                     if !espy_genres.is_empty() {
                         println!("  predicted genres={:?}", &espy_genres);
+                        let edit = GameEdit::new(
+                            game_entry.id,
+                            "batch_predictor",
+                            GameEditKind::GenreAnnotation,
+                            &game_entry.espy_genres,
+                            &espy_genres,
+                            start as i64,
+                        );
                         game_entry.espy_genres = espy_genres.clone();
 
                         library::firestore::genres::write(
@@ -87,6 +95,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                         .await?;
 
                         library::firestore::games::write(&firestore, &mut game_entry).await?;
+                        library::firestore::game_edits::add(&firestore, &edit).await?;
                     }
 
                     let finish = SystemTime::now()
@@ -23,7 +23,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("export/export_labeled_data")?;
+    let _tracing = Tracing::setup("export/export_labeled_data")?;
 
     let opts: Opts = Opts::parse();
 
@@ -73,6 +73,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 None => String::default(),
             },
             igdb_keywords: entry.keywords.join("|"),
+            igdb_perspectives: entry
+                .player_perspectives
+                .iter()
+                .map(|perspective| format!("{:?}", perspective))
+                .join("|"),
             steam_tags: match &entry.steam_data {
                 Some(steam_data) => steam_data.user_tags.join("|"),
                 None => String::default(),
@@ -120,6 +125,7 @@ struct LabeledExample {
     steam_genres: String,
     gog_genres: String,
     igdb_keywords: String,
+    igdb_perspectives: String,
     steam_tags: String,
     gog_tags: String,
     images: String,
@@ -25,7 +25,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("export/export_unlabeled_data")?;
+    let _tracing = Tracing::setup("export/export_unlabeled_data")?;
 
     let opts: Opts = Opts::parse();
 
@@ -49,7 +49,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .db()
         .fluent()
         .select()
-        .from("games")
+        .from(firestore.collection("games").as_str())
         .filter(|q| {
             q.for_all([
                 q.field(path!(GameEntry::release_date))
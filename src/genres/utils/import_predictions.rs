@@ -14,7 +14,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("export/import_labeled_data")?;
+    let _tracing = Tracing::setup("export/import_labeled_data")?;
 
     let opts: Opts = Opts::parse();
 
@@ -18,7 +18,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/predict")?;
+    let _tracing = Tracing::setup("utils/predict")?;
 
     let opts: Opts = Opts::parse();
 
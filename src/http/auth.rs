@@ -0,0 +1,176 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{util::http_client, Status};
+
+/// Verifies the Firebase ID tokens that signed-in users send as
+/// `Authorization: Bearer {token}`.
+///
+/// Tokens are checked against the public keys of the Firebase project, which
+/// are fetched from Google and cached until they expire.
+pub struct Authenticator {
+    project_id: String,
+    admins: HashSet<String>,
+    signing_keys: RwLock<SigningKeys>,
+}
+
+#[derive(Default)]
+struct SigningKeys {
+    keys: HashMap<String, DecodingKey>,
+    fetched_at: Option<SystemTime>,
+}
+
+impl Authenticator {
+    /// Creates an Authenticator for ID tokens issued by the Firebase project
+    /// with `project_id`, where the users in `admins` are curators. With an
+    /// empty `project_id` all tokens are rejected.
+    pub fn new(project_id: &str, admins: &[String]) -> Self {
+        Authenticator {
+            project_id: project_id.to_owned(),
+            admins: HashSet::from_iter(admins.iter().cloned()),
+            signing_keys: RwLock::new(SigningKeys::default()),
+        }
+    }
+
+    /// Returns true if the user with `uid` is a curator.
+    pub fn is_admin(&self, uid: &str) -> bool {
+        self.admins.contains(uid)
+    }
+
+    /// Returns the user id of a valid `id_token`.
+    #[instrument(level = "trace", skip(self, id_token))]
+    pub async fn verify(&self, id_token: &str) -> Result<String, Status> {
+        if self.project_id.is_empty() {
+            return Err(Status::unavailable(
+                "User authentication is not configured.",
+            ));
+        }
+
+        let header = decode_header(id_token)
+            .map_err(|e| Status::permission_denied(format!("Malformed ID token: {e}")))?;
+        let kid = match header.kid {
+            Some(kid) => kid,
+            None => return Err(Status::permission_denied("ID token has no key id.")),
+        };
+        let key = self.signing_key(&kid).await?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&[&self.project_id]);
+        validation.set_issuer(&[format!("{FIREBASE_ISSUER}/{}", self.project_id)]);
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| Status::permission_denied(format!("Invalid ID token: {e}")))?
+            .claims;
+
+        match claims.sub.is_empty() {
+            true => Err(Status::permission_denied("ID token has no subject.")),
+            false => Ok(claims.sub),
+        }
+    }
+
+    /// Returns the public key with `kid`. Keys are refetched when they are
+    /// stale, or when `kid` is unknown and they were not fetched recently.
+    async fn signing_key(&self, kid: &str) -> Result<DecodingKey, Status> {
+        let age = {
+            let signing_keys = self.signing_keys.read().unwrap();
+            let age = signing_keys
+                .fetched_at
+                .and_then(|fetched_at| fetched_at.elapsed().ok());
+            match (signing_keys.keys.get(kid), age) {
+                (Some(key), Some(age)) if age < KEYS_TTL => return Ok(key.clone()),
+                _ => age,
+            }
+        };
+        if matches!(age, Some(age) if age < MIN_KEYS_REFRESH) {
+            return Err(Status::permission_denied(format!(
+                "ID token is signed with unknown key '{kid}'."
+            )));
+        }
+
+        let keys = fetch_keys().await?;
+        let key = keys.get(kid).cloned();
+        *self.signing_keys.write().unwrap() = SigningKeys {
+            keys,
+            fetched_at: Some(SystemTime::now()),
+        };
+
+        match key {
+            Some(key) => Ok(key),
+            None => Err(Status::permission_denied(format!(
+                "ID token is signed with unknown key '{kid}'."
+            ))),
+        }
+    }
+}
+
+/// Returns true if `token` has the shape of a JWT, which tells ID tokens apart
+/// from sync tokens that are sent in the same header.
+pub fn is_id_token(token: &str) -> bool {
+    token.split('.').count() == 3
+}
+
+/// Fetches the public keys that Firebase ID tokens are signed with.
+async fn fetch_keys() -> Result<HashMap<String, DecodingKey>, Status> {
+    let jwks = http_client::shared()
+        .get(FIREBASE_JWKS_URL)
+        .send()
+        .await?
+        .json::<Jwks>()
+        .await?;
+
+    Ok(jwks
+        .keys
+        .into_iter()
+        .filter_map(|jwk| {
+            let key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e).ok()?;
+            Some((jwk.kid, key))
+        })
+        .collect())
+}
+
+#[derive(Deserialize, Debug)]
+struct IdTokenClaims {
+    #[serde(default)]
+    sub: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Jwks {
+    #[serde(default)]
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Jwk {
+    kid: String,
+    n: String,
+    e: String,
+}
+
+const FIREBASE_ISSUER: &str = "https://securetoken.google.com";
+const FIREBASE_JWKS_URL: &str =
+    "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+
+// Google rotates the signing keys every few days and publishes them hours
+// ahead of use.
+const KEYS_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Tokens with unknown key ids trigger a refetch at most this often.
+const MIN_KEYS_REFRESH: Duration = Duration::from_secs(60);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn id_tokens_are_told_apart_from_sync_tokens() {
+        assert!(is_id_token("eyJhbGciOi.eyJzdWIiOi.c2lnbmF0dXJl"));
+        assert!(!is_id_token(&"a".repeat(64)));
+    }
+}
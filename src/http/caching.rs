@@ -0,0 +1,140 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use warp::http::{header, Response, StatusCode};
+
+use crate::Status;
+
+/// How long clients and shared caches, e.g. a CDN, can reuse a response.
+#[derive(Clone, Copy, Debug)]
+pub struct CachePolicy {
+    /// Seconds that browsers can reuse the response.
+    pub max_age_secs: u64,
+
+    /// Seconds that shared caches can reuse the response.
+    pub s_maxage_secs: u64,
+
+    /// True for responses of versioned URLs whose content never changes.
+    pub immutable: bool,
+}
+
+impl CachePolicy {
+    pub const fn new(max_age_secs: u64, s_maxage_secs: u64) -> Self {
+        CachePolicy {
+            max_age_secs,
+            s_maxage_secs,
+            immutable: false,
+        }
+    }
+
+    pub const fn immutable(max_age_secs: u64) -> Self {
+        CachePolicy {
+            max_age_secs,
+            s_maxage_secs: max_age_secs,
+            immutable: true,
+        }
+    }
+
+    fn header_value(&self) -> String {
+        let mut value = format!(
+            "public, max-age={}, s-maxage={}",
+            self.max_age_secs, self.s_maxage_secs
+        );
+        if self.immutable {
+            value.push_str(", immutable");
+        }
+        value
+    }
+}
+
+/// Serializes `value` in a JSON response that clients and shared caches can
+/// reuse according to `policy`.
+///
+/// The response carries an ETag of its body. If `if_none_match` matches it,
+/// an empty 304 response is returned instead.
+pub fn json_reply<T: Serialize>(
+    value: &T,
+    if_none_match: Option<&str>,
+    policy: &CachePolicy,
+) -> Result<Response<Vec<u8>>, Status> {
+    let body = serde_json::to_vec(value)?;
+    let etag = etag(&body);
+
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, policy.header_value());
+
+    let response = match if_none_match {
+        Some(if_none_match) if matches(if_none_match, &etag) => {
+            builder.status(StatusCode::NOT_MODIFIED).body(vec![])
+        }
+        _ => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body),
+    };
+    response.map_err(|e| Status::internal(e.to_string()))
+}
+
+/// Returns a temporary redirect to `location` that shared caches can reuse
+/// according to `policy`. It is used to send requests of stale versioned URLs
+/// to the current version.
+pub fn redirect(location: &str, policy: &CachePolicy) -> Result<Response<Vec<u8>>, Status> {
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header(header::LOCATION, location)
+        .header(header::CACHE_CONTROL, policy.header_value())
+        .body(vec![])
+        .map_err(|e| Status::internal(e.to_string()))
+}
+
+/// Returns a strong ETag for `body`.
+fn etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Returns true if the `If-None-Match` header value matches `etag`. Weak
+/// comparison is used, as is required for `If-None-Match`.
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_none_match() {
+        let etag = etag(b"{}");
+        assert!(matches(&etag, &etag));
+        assert!(matches(&format!("\"other\", W/{etag}"), &etag));
+        assert!(matches("*", &etag));
+        assert!(!matches("\"other\"", &etag));
+    }
+
+    #[test]
+    fn not_modified_reply() {
+        let policy = CachePolicy::new(60, 300);
+        let reply = json_reply(&vec![1, 2, 3], None, &policy).unwrap();
+        assert_eq!(reply.status(), StatusCode::OK);
+        assert_eq!(
+            reply.headers()[header::CACHE_CONTROL],
+            "public, max-age=60, s-maxage=300"
+        );
+
+        let etag = reply.headers()[header::ETAG].to_str().unwrap().to_owned();
+        let reply = json_reply(&vec![1, 2, 3], Some(&etag), &policy).unwrap();
+        assert_eq!(reply.status(), StatusCode::NOT_MODIFIED);
+        assert!(reply.body().is_empty());
+    }
+
+    #[test]
+    fn immutable_policy() {
+        assert_eq!(
+            CachePolicy::immutable(3600).header_value(),
+            "public, max-age=3600, s-maxage=3600, immutable"
+        );
+    }
+}
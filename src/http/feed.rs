@@ -0,0 +1,126 @@
+use std::{collections::HashSet, fmt::Write};
+
+use chrono::{NaiveDateTime, SecondsFormat, TimeZone, Utc};
+
+use crate::documents::{Frontpage, GameDigest};
+
+/// Renders the releases of `frontpage` as an Atom feed.
+///
+/// `self_url` is the URL the feed is served from. Entries are listed in the
+/// order of the frontpage sections: released today, recent and upcoming.
+pub fn frontpage_atom(frontpage: &Frontpage, self_url: Option<&str>) -> String {
+    let updated = rfc3339(frontpage.last_updated as i64);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str("  <id>urn:espy:frontpage</id>\n");
+    out.push_str("  <title>espy releases</title>\n");
+    let _ = writeln!(out, "  <updated>{updated}</updated>");
+    out.push_str("  <author><name>espy</name></author>\n");
+    if let Some(self_url) = self_url {
+        let _ = writeln!(out, "  <link rel=\"self\" href=\"{}\"/>", escape(self_url));
+    }
+
+    let mut seen = HashSet::new();
+    for (section, digests) in [
+        ("today", &frontpage.today),
+        ("recent", &frontpage.recent),
+        ("upcoming", &frontpage.upcoming),
+    ] {
+        for digest in digests {
+            if seen.insert(digest.id) {
+                write_entry(&mut out, section, digest, &updated);
+            }
+        }
+    }
+
+    out.push_str("</feed>\n");
+    out
+}
+
+fn write_entry(out: &mut String, section: &str, digest: &GameDigest, updated: &str) {
+    let release_date = digest
+        .release_date
+        .and_then(|date| NaiveDateTime::from_timestamp_opt(date, 0))
+        .map(|date| date.format("%e %B %Y").to_string());
+    let summary = match (section, release_date) {
+        ("upcoming", Some(date)) => format!("Releases on {}", date.trim()),
+        (_, Some(date)) => format!("Released on {}", date.trim()),
+        (_, None) => "Release date unknown".to_owned(),
+    };
+
+    out.push_str("  <entry>\n");
+    let _ = writeln!(out, "    <id>urn:espy:game:{}</id>", digest.id);
+    let _ = writeln!(out, "    <title>{}</title>", escape(&digest.name));
+    let _ = writeln!(out, "    <updated>{updated}</updated>");
+    if let Some(date) = digest.release_date {
+        let _ = writeln!(out, "    <published>{}</published>", rfc3339(date));
+    }
+    let _ = writeln!(out, "    <category term=\"{section}\"/>");
+    let _ = writeln!(out, "    <summary>{}</summary>", escape(&summary));
+    if let Some(cover) = &digest.cover {
+        let _ = writeln!(
+            out,
+            "    <link rel=\"enclosure\" type=\"image/jpeg\" href=\"https://images.igdb.com/igdb/image/upload/t_cover_big/{}.jpg\"/>",
+            escape(cover)
+        );
+    }
+    out.push_str("  </entry>\n");
+}
+
+fn rfc3339(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(id: u64, name: &str, release_date: i64) -> GameDigest {
+        GameDigest {
+            id,
+            name: name.to_owned(),
+            release_date: Some(release_date),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_sections_once() {
+        let frontpage = Frontpage {
+            last_updated: 1_700_000_000,
+            recent: vec![digest(1, "Tom & Jerry", 1_699_000_000)],
+            upcoming: vec![
+                digest(2, "Hades II", 1_701_000_000),
+                digest(1, "Tom & Jerry", 1_699_000_000),
+            ],
+            ..Default::default()
+        };
+
+        let feed = frontpage_atom(&frontpage, Some("https://example.com/frontpage/rss"));
+        assert!(feed.contains("<updated>2023-11-14T22:13:20Z</updated>"));
+        assert!(feed.contains("<link rel=\"self\" href=\"https://example.com/frontpage/rss\"/>"));
+        assert!(feed.contains("<title>Tom &amp; Jerry</title>"));
+        assert!(feed.contains("<summary>Releases on 26 November 2023</summary>"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+    }
+}
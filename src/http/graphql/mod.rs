@@ -0,0 +1,34 @@
+mod query;
+mod types;
+
+use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use std::sync::Arc;
+
+use crate::api::FirestoreApi;
+
+pub use query::QueryRoot;
+
+/// Read-only GraphQL schema over espy documents. Clients select only the
+/// fields they need instead of fetching full documents.
+pub type EspySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// User whose ID token authenticated the request, which is attached to each
+/// request. User data can only be queried by its owner.
+pub struct Viewer(pub Option<String>);
+
+/// Builds the GraphQL schema with `firestore` available to all resolvers.
+pub fn schema(firestore: Arc<FirestoreApi>) -> EspySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(firestore)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+// GameEntry nests digests that nest scores; anything deeper than this is not
+// a query the frontend needs to make.
+const MAX_QUERY_DEPTH: usize = 8;
+
+// Bounds the number of fields a single query resolves, which also bounds
+// aliased copies of expensive fields.
+const MAX_QUERY_COMPLEXITY: usize = 500;
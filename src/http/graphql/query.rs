@@ -0,0 +1,77 @@
+use async_graphql::{Context, Error, Object, Result};
+use std::sync::Arc;
+
+use crate::{
+    api::FirestoreApi,
+    library::firestore::{frontpage, games, library, timeline, wishlist},
+};
+
+use super::{
+    types::{FrontpageObject, GameEntryObject, LibraryObject, LibrarySortInput, TimelineObject},
+    Viewer,
+};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Returns the game entry with the given IGDB id.
+    async fn game(&self, ctx: &Context<'_>, id: u64) -> Result<GameEntryObject> {
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        Ok(GameEntryObject(games::read(firestore, id).await?))
+    }
+
+    /// Returns the game entries with the given IGDB ids. Ids that are not
+    /// found are skipped.
+    async fn games(&self, ctx: &Context<'_>, ids: Vec<u64>) -> Result<Vec<GameEntryObject>> {
+        if ids.len() > MAX_GAME_IDS {
+            return Err(Error::new(format!(
+                "At most {MAX_GAME_IDS} games can be requested at once."
+            )));
+        }
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        let result = games::batch_read(firestore, &ids).await?;
+        Ok(result.documents.into_iter().map(GameEntryObject).collect())
+    }
+
+    /// Returns the library of the signed-in user, latest releases first unless
+    /// another order is requested.
+    async fn library(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] sort: LibrarySortInput,
+    ) -> Result<LibraryObject> {
+        let user_id = viewer(ctx)?;
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        let mut library = library::read(firestore, user_id).await?;
+        library.sort(sort.into());
+        Ok(LibraryObject(library))
+    }
+
+    /// Returns the wishlist of the signed-in user.
+    async fn wishlist(&self, ctx: &Context<'_>) -> Result<LibraryObject> {
+        let user_id = viewer(ctx)?;
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        Ok(LibraryObject(wishlist::read(firestore, user_id).await?))
+    }
+
+    async fn timeline(&self, ctx: &Context<'_>) -> Result<TimelineObject> {
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        Ok(TimelineObject(timeline::lookup(firestore).await?))
+    }
+
+    async fn frontpage(&self, ctx: &Context<'_>) -> Result<FrontpageObject> {
+        let firestore = ctx.data::<Arc<FirestoreApi>>()?;
+        Ok(FrontpageObject(frontpage::lookup(firestore).await?))
+    }
+}
+
+/// Returns the user id of the request's ID token.
+fn viewer<'a>(ctx: &Context<'a>) -> Result<&'a str> {
+    match ctx.data::<Viewer>()?.0.as_deref() {
+        Some(user_id) => Ok(user_id),
+        None => Err(Error::new("Request carries no valid ID token.")),
+    }
+}
+
+const MAX_GAME_IDS: usize = 100;
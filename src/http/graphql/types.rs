@@ -0,0 +1,526 @@
+use async_graphql::{Enum, Object};
+
+use crate::documents::{
+    CollectionDigest, CompanyDigest, Frontpage, GameDigest, GameEntry, Image, Library,
+    LibraryEntry, LibrarySort, Playtime, ReleaseEvent, Scores, StoreEntry, Timeline, Website,
+};
+
+/// GraphQL view of a GameEntry. Raw IGDB, Steam and GOG payloads are not
+/// exposed; their useful parts are already folded into the entry's fields.
+pub struct GameEntryObject(pub GameEntry);
+
+#[Object(name = "GameEntry")]
+impl GameEntryObject {
+    async fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn alternative_names(&self) -> &[String] {
+        &self.0.alternative_names
+    }
+
+    async fn category(&self) -> String {
+        self.0.category.to_string()
+    }
+
+    async fn status(&self) -> String {
+        self.0.status.to_string()
+    }
+
+    async fn archived(&self) -> bool {
+        self.0.archived
+    }
+
+    async fn release_date(&self) -> i64 {
+        self.0.release_date
+    }
+
+    async fn release_precision(&self) -> String {
+        format!("{:?}", self.0.release_precision)
+    }
+
+    async fn scores(&self) -> ScoresObject<'_> {
+        ScoresObject(&self.0.scores)
+    }
+
+    async fn playtime(&self) -> Option<PlaytimeObject<'_>> {
+        self.0.playtime.as_ref().map(PlaytimeObject)
+    }
+
+    async fn cover(&self) -> Option<ImageObject<'_>> {
+        self.0.cover.as_ref().map(ImageObject)
+    }
+
+    async fn espy_genres(&self) -> Vec<String> {
+        self.0
+            .espy_genres
+            .iter()
+            .map(|genre| format!("{:?}", genre))
+            .collect()
+    }
+
+    async fn igdb_genres(&self) -> Vec<String> {
+        self.0
+            .igdb_genres
+            .iter()
+            .map(|genre| format!("{:?}", genre))
+            .collect()
+    }
+
+    async fn keywords(&self) -> &[String] {
+        &self.0.keywords
+    }
+
+    async fn collections(&self) -> Vec<CollectionDigestObject<'_>> {
+        self.0
+            .collections
+            .iter()
+            .map(CollectionDigestObject)
+            .collect()
+    }
+
+    async fn franchises(&self) -> Vec<CollectionDigestObject<'_>> {
+        self.0
+            .franchises
+            .iter()
+            .map(CollectionDigestObject)
+            .collect()
+    }
+
+    async fn developers(&self) -> Vec<CompanyDigestObject<'_>> {
+        self.0.developers.iter().map(CompanyDigestObject).collect()
+    }
+
+    async fn publishers(&self) -> Vec<CompanyDigestObject<'_>> {
+        self.0.publishers.iter().map(CompanyDigestObject).collect()
+    }
+
+    async fn parent(&self) -> Option<GameDigestObject<'_>> {
+        self.0.parent.as_ref().map(GameDigestObject)
+    }
+
+    async fn expansions(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.expansions.iter().map(GameDigestObject).collect()
+    }
+
+    async fn dlcs(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.dlcs.iter().map(GameDigestObject).collect()
+    }
+
+    async fn remakes(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.remakes.iter().map(GameDigestObject).collect()
+    }
+
+    async fn remasters(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.remasters.iter().map(GameDigestObject).collect()
+    }
+
+    async fn contents(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.contents.iter().map(GameDigestObject).collect()
+    }
+
+    async fn screenshots(&self) -> Vec<ImageObject<'_>> {
+        self.0.screenshots.iter().map(ImageObject).collect()
+    }
+
+    async fn artwork(&self) -> Vec<ImageObject<'_>> {
+        self.0.artwork.iter().map(ImageObject).collect()
+    }
+
+    async fn websites(&self) -> Vec<WebsiteObject<'_>> {
+        self.0.websites.iter().map(WebsiteObject).collect()
+    }
+}
+
+pub struct GameDigestObject<'a>(pub &'a GameDigest);
+
+#[Object(name = "GameDigest")]
+impl<'a> GameDigestObject<'a> {
+    async fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn category(&self) -> String {
+        self.0.category.to_string()
+    }
+
+    async fn status(&self) -> String {
+        self.0.status.to_string()
+    }
+
+    async fn archived(&self) -> bool {
+        self.0.archived
+    }
+
+    async fn cover(&self) -> Option<&str> {
+        self.0.cover.as_deref()
+    }
+
+    async fn release_date(&self) -> Option<i64> {
+        self.0.release_date
+    }
+
+    async fn release_precision(&self) -> String {
+        format!("{:?}", self.0.release_precision)
+    }
+
+    /// Days until release for upcoming games, computed at query time.
+    async fn days_until_release(&self) -> Option<i64> {
+        self.0
+            .release_countdown()
+            .map(|countdown| countdown.days_until_release)
+    }
+
+    async fn scores(&self) -> ScoresObject<'_> {
+        ScoresObject(&self.0.scores)
+    }
+
+    async fn playtime(&self) -> Option<PlaytimeObject<'_>> {
+        self.0.playtime.as_ref().map(PlaytimeObject)
+    }
+
+    async fn parent_id(&self) -> Option<u64> {
+        self.0.parent_id
+    }
+
+    async fn collections(&self) -> &[String] {
+        &self.0.collections
+    }
+
+    async fn franchises(&self) -> &[String] {
+        &self.0.franchises
+    }
+
+    async fn developers(&self) -> &[String] {
+        &self.0.developers
+    }
+
+    async fn publishers(&self) -> &[String] {
+        &self.0.publishers
+    }
+
+    async fn espy_genres(&self) -> Vec<String> {
+        self.0
+            .espy_genres
+            .iter()
+            .map(|genre| format!("{:?}", genre))
+            .collect()
+    }
+
+    async fn espy_genre_groups(&self) -> Vec<String> {
+        self.0
+            .espy_genre_groups
+            .iter()
+            .map(|group| format!("{:?}", group))
+            .collect()
+    }
+
+    async fn igdb_genres(&self) -> Vec<String> {
+        self.0
+            .igdb_genres
+            .iter()
+            .map(|genre| format!("{:?}", genre))
+            .collect()
+    }
+
+    async fn keywords(&self) -> &[String] {
+        &self.0.keywords
+    }
+}
+
+pub struct LibraryObject(pub Library);
+
+#[Object(name = "Library")]
+impl LibraryObject {
+    async fn entries(&self) -> Vec<LibraryEntryObject<'_>> {
+        self.0.entries.iter().map(LibraryEntryObject).collect()
+    }
+}
+
+/// GraphQL mirror of LibrarySort.
+#[derive(Enum, Default, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "LibrarySort")]
+pub enum LibrarySortInput {
+    #[default]
+    ReleaseDate,
+    Name,
+    AddedDate,
+    Score,
+}
+
+impl From<LibrarySortInput> for LibrarySort {
+    fn from(sort: LibrarySortInput) -> Self {
+        match sort {
+            LibrarySortInput::ReleaseDate => LibrarySort::ReleaseDate,
+            LibrarySortInput::Name => LibrarySort::Name,
+            LibrarySortInput::AddedDate => LibrarySort::AddedDate,
+            LibrarySortInput::Score => LibrarySort::Score,
+        }
+    }
+}
+
+pub struct LibraryEntryObject<'a>(&'a LibraryEntry);
+
+#[Object(name = "LibraryEntry")]
+impl<'a> LibraryEntryObject<'a> {
+    async fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    async fn digest(&self) -> GameDigestObject<'_> {
+        GameDigestObject(&self.0.digest)
+    }
+
+    async fn store_entries(&self) -> Vec<StoreEntryObject<'_>> {
+        self.0.store_entries.iter().map(StoreEntryObject).collect()
+    }
+
+    async fn added_date(&self) -> Option<u64> {
+        self.0.added_date
+    }
+
+    async fn playtime_minutes(&self) -> Option<u64> {
+        self.0.playtime_minutes
+    }
+
+    async fn achievements_unlocked(&self) -> Option<u64> {
+        self.0.achievements_unlocked
+    }
+
+    async fn achievements_total(&self) -> Option<u64> {
+        self.0.achievements_total
+    }
+}
+
+pub struct StoreEntryObject<'a>(&'a StoreEntry);
+
+#[Object(name = "StoreEntry")]
+impl<'a> StoreEntryObject<'a> {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn storefront_name(&self) -> &str {
+        &self.0.storefront_name
+    }
+
+    async fn url(&self) -> &str {
+        &self.0.url
+    }
+
+    async fn image(&self) -> &str {
+        &self.0.image
+    }
+}
+
+pub struct TimelineObject(pub Timeline);
+
+#[Object(name = "Timeline")]
+impl TimelineObject {
+    async fn last_updated(&self) -> u64 {
+        self.0.last_updated
+    }
+
+    async fn releases(&self) -> Vec<ReleaseEventObject<'_>> {
+        self.0.releases.iter().map(ReleaseEventObject).collect()
+    }
+}
+
+pub struct FrontpageObject(pub Frontpage);
+
+#[Object(name = "Frontpage")]
+impl FrontpageObject {
+    async fn last_updated(&self) -> u64 {
+        self.0.last_updated
+    }
+
+    async fn releases(&self) -> Vec<ReleaseEventObject<'_>> {
+        self.0.releases.iter().map(ReleaseEventObject).collect()
+    }
+
+    async fn today(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.today.iter().map(GameDigestObject).collect()
+    }
+
+    async fn recent(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.recent.iter().map(GameDigestObject).collect()
+    }
+
+    async fn upcoming(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.upcoming.iter().map(GameDigestObject).collect()
+    }
+
+    async fn new(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.new.iter().map(GameDigestObject).collect()
+    }
+
+    async fn hyped(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.hyped.iter().map(GameDigestObject).collect()
+    }
+}
+
+pub struct ReleaseEventObject<'a>(&'a ReleaseEvent);
+
+#[Object(name = "ReleaseEvent")]
+impl<'a> ReleaseEventObject<'a> {
+    async fn label(&self) -> &str {
+        &self.0.label
+    }
+
+    async fn year(&self) -> &str {
+        &self.0.year
+    }
+
+    async fn games(&self) -> Vec<GameDigestObject<'_>> {
+        self.0.games.iter().map(GameDigestObject).collect()
+    }
+}
+
+pub struct ScoresObject<'a>(&'a Scores);
+
+#[Object(name = "Scores")]
+impl<'a> ScoresObject<'a> {
+    async fn thumbs(&self) -> Option<u64> {
+        self.0.thumbs
+    }
+
+    async fn steam_reviews(&self) -> Option<u64> {
+        self.0.steam_reviews
+    }
+
+    async fn popularity(&self) -> Option<u64> {
+        self.0.popularity
+    }
+
+    async fn hype(&self) -> Option<u64> {
+        self.0.hype
+    }
+
+    async fn metacritic(&self) -> Option<u64> {
+        self.0.metacritic
+    }
+
+    async fn metacritic_source(&self) -> String {
+        format!("{:?}", self.0.metacritic_source)
+    }
+
+    async fn metacritic_reviews(&self) -> Option<u64> {
+        self.0.metacritic_reviews
+    }
+
+    async fn gog_votes(&self) -> Option<u64> {
+        self.0.gog_votes
+    }
+
+    async fn espy_score(&self) -> Option<u64> {
+        self.0.espy_score
+    }
+
+    async fn espy_tier(&self) -> Option<String> {
+        self.0.espy_tier.as_ref().map(|tier| format!("{:?}", tier))
+    }
+
+    async fn confidence(&self) -> Option<String> {
+        self.0
+            .confidence
+            .as_ref()
+            .map(|confidence| format!("{:?}", confidence))
+    }
+}
+
+pub struct PlaytimeObject<'a>(&'a Playtime);
+
+#[Object(name = "Playtime")]
+impl<'a> PlaytimeObject<'a> {
+    async fn main(&self) -> Option<u64> {
+        self.0.main
+    }
+
+    async fn extra(&self) -> Option<u64> {
+        self.0.extra
+    }
+
+    async fn completionist(&self) -> Option<u64> {
+        self.0.completionist
+    }
+}
+
+pub struct ImageObject<'a>(&'a Image);
+
+#[Object(name = "Image")]
+impl<'a> ImageObject<'a> {
+    async fn image_id(&self) -> &str {
+        &self.0.image_id
+    }
+
+    async fn height(&self) -> i32 {
+        self.0.height
+    }
+
+    async fn width(&self) -> i32 {
+        self.0.width
+    }
+}
+
+pub struct CollectionDigestObject<'a>(&'a CollectionDigest);
+
+#[Object(name = "CollectionDigest")]
+impl<'a> CollectionDigestObject<'a> {
+    async fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+}
+
+pub struct CompanyDigestObject<'a>(&'a CompanyDigest);
+
+#[Object(name = "CompanyDigest")]
+impl<'a> CompanyDigestObject<'a> {
+    async fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+
+    async fn role(&self) -> String {
+        format!("{:?}", self.0.role)
+    }
+}
+
+pub struct WebsiteObject<'a>(&'a Website);
+
+#[Object(name = "Website")]
+impl<'a> WebsiteObject<'a> {
+    async fn url(&self) -> &str {
+        &self.0.url
+    }
+
+    async fn authority(&self) -> String {
+        format!("{:?}", self.0.authority)
+    }
+}
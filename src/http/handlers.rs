@@ -1,14 +1,44 @@
 use crate::{
-    api::{FirestoreApi, IgdbApi, IgdbSearch},
+    api::{remove_digests, update_digests, FirestoreApi, IgdbApi, IgdbSearch, ItadApi},
+    documents::{
+        EspyGenreGroup, GameDigest, GameEdit, GameEditKind, GameEntry, LibraryDiff, Override,
+        PlatformScope, PlayerPerspective, RejectedGame, ReleaseCountdown, Report,
+    },
+    genres::genre_groups,
     http::models,
-    library::{firestore::games, LibraryManager, User},
-    util, Status,
+    library::{
+        account,
+        firestore::{
+            collections, external_games, filters, follows, franchises, frontpage, game_edits,
+            games, genres, library, library_events, outbound_webhooks, overrides, rejected,
+            reports, storefront, timeline, user_annotations, user_feed, wishlist,
+        },
+        import as library_import, merge, prefetch, suggestions, sync_tokens, triage_report, views,
+        LibraryManager, User,
+    },
+    logging::HttpCounters,
+    util::{self, health, http_client},
+    webhooks::outbound,
+    Status,
+};
+use chrono::Utc;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    sync::Arc,
+    time::Duration,
 };
-use std::{convert::Infallible, sync::Arc};
-use tracing::{info, instrument, warn};
+use tracing::{info, instrument, trace_span, warn, Instrument};
+use utoipa::OpenApi;
 use warp::http::StatusCode;
 
-use super::query_logs::*;
+use super::{
+    caching::{self, CachePolicy},
+    feed, graphql, openapi,
+    pending_search::PendingSearches,
+    query_logs::*,
+    steam_openid,
+};
 
 #[instrument(level = "trace")]
 pub async fn welcome() -> Result<impl warp::Reply, Infallible> {
@@ -22,28 +52,407 @@ pub async fn welcome() -> Result<impl warp::Reply, Infallible> {
     Ok("welcome")
 }
 
-#[instrument(level = "trace", skip(igdb))]
+#[instrument(level = "trace")]
+pub async fn get_metrics() -> Result<impl warp::Reply, Infallible> {
+    Ok(HttpCounters::render())
+}
+
+/// Liveness probe that fails when the server needs a restart.
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn get_healthz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let report = health::check(&firestore, &igdb).await;
+    let status = match report.live {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+/// Readiness probe that fails while a dependency of the server is failing.
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn get_readyz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let report = health::check(&firestore, &igdb).await;
+    let status = match report.ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+#[instrument(level = "trace")]
+pub async fn get_openapi() -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&openapi::ApiDoc::openapi()))
+}
+
+#[instrument(level = "trace")]
+pub async fn get_docs() -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::html(openapi::SWAGGER_UI))
+}
+
+/// Searches IGDB and the espy catalog in parallel. If IGDB does not respond
+/// within `SEARCH_BUDGET`, the catalog matches are returned as a partial
+/// response with a token to collect the IGDB results from `/search/{token}`.
+/// Complete results keep the plain array shape that existing clients expect.
+#[utoipa::path(
+    post,
+    path = "/search",
+    request_body = models::Search,
+    responses(
+        (status = 200, description = "Games matching the title, or a partial response", body = models::SearchResult),
+        (status = 404, description = "Search failed"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, igdb, pending_searches))]
 pub async fn post_search(
     search: models::Search,
+    firestore: Arc<FirestoreApi>,
     igdb: Arc<IgdbApi>,
+    pending_searches: Arc<PendingSearches>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let event = SearchEvent::new(&search);
+    let deadline = tokio::time::Instant::now() + SEARCH_BUDGET;
+    let scope = igdb.platform_scope().clone();
+
+    let mut igdb_task = {
+        let search = search.clone();
+        let firestore = Arc::clone(&firestore);
+        tokio::spawn(
+            async move {
+                let candidates = IgdbSearch::new(igdb)
+                    .search_by_title_with_cover(
+                        &search.title,
+                        search.base_game_only,
+                        search.include_archived,
+                    )
+                    .await?;
+                let candidates = retain_perspectives(candidates, &search.player_perspectives);
+                let candidates =
+                    retain_moddable(&firestore, candidates, search.moddable_only).await;
+                Ok::<_, Status>(
+                    retain_genre_groups(&firestore, candidates, &search.espy_genre_groups).await,
+                )
+            }
+            .instrument(trace_span!("spawn_igdb_search")),
+        )
+    };
+
+    let local = match tokio::time::timeout_at(
+        deadline,
+        games::search_by_title(&firestore, &search.title, LOCAL_SEARCH_LIMIT),
+    )
+    .await
+    {
+        Ok(Ok(candidates)) => retain_local_matches(&search, &scope, candidates),
+        Ok(Err(status)) => {
+            warn!("Catalog search failed: {status}");
+            vec![]
+        }
+        Err(_) => vec![],
+    };
+
+    let status = match tokio::time::timeout_at(deadline, &mut igdb_task).await {
+        Ok(Ok(Ok(candidates))) => {
+            event.log(&candidates);
+            return Ok(Box::new(warp::reply::json(&candidates)));
+        }
+        Ok(Ok(Err(status))) => status,
+        Ok(Err(e)) => Status::internal(format!("IGDB search task failed: {e}")),
+        Err(_) => {
+            let token = match pending_searches.insert(igdb_task) {
+                Ok(token) => Some(token),
+                Err(status) => {
+                    warn!("{status}");
+                    None
+                }
+            };
+            event.log(&local);
+            return Ok(Box::new(warp::reply::json(&models::SearchResult {
+                candidates: local,
+                partial: true,
+                token,
+            })));
+        }
+    };
+
+    // IGDB failed, so catalog matches are the best available answer.
+    match local.is_empty() {
+        true => {
+            event.log_error(status);
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        false => {
+            warn!("IGDB search failed, returning catalog matches: {status}");
+            event.log(&local);
+            Ok(Box::new(warp::reply::json(&models::SearchResult {
+                candidates: local,
+                partial: true,
+                token: None,
+            })))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/search/{token}",
+    params(("token" = String, Path, description = "Token of a partial search response")),
+    responses(
+        (status = 200, description = "IGDB results of the search", body = [Object]),
+        (status = 404, description = "Search is unknown, expired or failed"),
+        (status = 504, description = "IGDB did not respond in time"),
+    )
+)]
+#[instrument(level = "trace", skip(pending_searches))]
+pub async fn get_search_results(
+    token: String,
+    pending_searches: Arc<PendingSearches>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let igdb_task = match pending_searches.take(&token) {
+        Some(igdb_task) => igdb_task,
+        None => return Ok(Box::new(StatusCode::NOT_FOUND)),
+    };
+
+    match tokio::time::timeout(SEARCH_FOLLOW_UP_BUDGET, igdb_task).await {
+        Ok(Ok(Ok(candidates))) => Ok(Box::new(warp::reply::json(&candidates))),
+        Ok(Ok(Err(status))) => {
+            warn!("IGDB search failed: {status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Ok(Err(e)) => {
+            warn!("IGDB search task failed: {e}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+        Err(_) => Ok(Box::new(StatusCode::GATEWAY_TIMEOUT)),
+    }
+}
+
+/// Keeps the catalog `candidates` that satisfy the filters of `search`.
+/// Catalog games carry their espy genres, so no lookups are needed.
+fn retain_local_matches(
+    search: &models::Search,
+    scope: &PlatformScope,
+    candidates: Vec<GameEntry>,
+) -> Vec<GameEntry> {
+    let candidates = candidates
+        .into_iter()
+        .filter(|game_entry| game_entry.igdb_game.is_in_scope(scope))
+        .filter(|game_entry| !search.base_game_only || game_entry.parent.is_none())
+        .filter(|game_entry| search.include_archived || !game_entry.archived)
+        .filter(|game_entry| !search.moddable_only || game_entry.is_moddable())
+        .filter(|game_entry| {
+            search.espy_genre_groups.is_empty()
+                || genre_groups(&game_entry.espy_genres)
+                    .iter()
+                    .any(|group| search.espy_genre_groups.contains(group))
+        })
+        .collect();
+    retain_perspectives(candidates, &search.player_perspectives)
+}
+
+#[utoipa::path(
+    get,
+    path = "/lookup",
+    params(models::Lookup),
+    responses(
+        (status = 200, description = "Best match for the title", body = models::LookupResult),
+        (status = 404, description = "No game matches the title"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn get_lookup(
+    lookup: models::Lookup,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = LookupEvent::new(&lookup);
     let igdb_search = IgdbSearch::new(igdb);
     match igdb_search
-        .search_by_title_with_cover(&search.title, search.base_game_only)
+        .lookup(&firestore, &lookup.title, lookup.year)
+        .await
+    {
+        Ok(game_entry) => {
+            event.log(&game_entry);
+            Ok(Box::new(warp::reply::json(&models::LookupResult::from(
+                game_entry,
+            ))))
+        }
+        Err(Status::NotFound(status)) => {
+            event.log_error(Status::not_found(status));
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Err(status) => {
+            event.log_error(status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+/// Same as `post_search` but annotates each candidate with whether it is
+/// already in the user's library or wishlist.
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/search",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::Search,
+    responses(
+        (status = 200, description = "Games annotated with their library status", body = [models::SearchCandidate]),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+        (status = 404, description = "Search failed"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn post_library_search(
+    user_id: String,
+    search: models::Search,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = SearchEvent::new(&search);
+    let igdb_search = IgdbSearch::new(igdb);
+    let candidates = match igdb_search
+        .search_by_title_with_cover(
+            &search.title,
+            search.base_game_only,
+            search.include_archived,
+        )
         .await
     {
         Ok(candidates) => {
-            event.log(&candidates);
-            Ok(Box::new(warp::reply::json(&candidates)))
+            let candidates = retain_perspectives(candidates, &search.player_perspectives);
+            let candidates = retain_moddable(&firestore, candidates, search.moddable_only).await;
+            retain_genre_groups(&firestore, candidates, &search.espy_genre_groups).await
         }
         Err(status) => {
             event.log_error(status);
-            Ok(Box::new(StatusCode::NOT_FOUND))
+            return Ok(Box::new(StatusCode::NOT_FOUND));
+        }
+    };
+    event.log(&candidates);
+
+    let library = match library::read(&firestore, &user_id).await {
+        Ok(library) => HashSet::<u64>::from_iter(library.entries.iter().map(|e| e.id)),
+        Err(status) => {
+            warn!("{status}");
+            HashSet::new()
+        }
+    };
+    let wishlist = match wishlist::read(&firestore, &user_id).await {
+        Ok(wishlist) => HashSet::<u64>::from_iter(wishlist.entries.iter().map(|e| e.id)),
+        Err(status) => {
+            warn!("{status}");
+            HashSet::new()
+        }
+    };
+
+    let candidates = candidates
+        .into_iter()
+        .map(|game_entry| models::SearchCandidate {
+            in_library: library.contains(&game_entry.id),
+            in_wishlist: wishlist.contains(&game_entry.id),
+            release_countdown: ReleaseCountdown::new(
+                game_entry.release_date,
+                game_entry.release_precision,
+                Utc::now().timestamp(),
+            ),
+            game_entry,
+        })
+        .collect::<Vec<_>>();
+    Ok(Box::new(warp::reply::json(&candidates)))
+}
+
+/// Keeps only the `candidates` with any of the player `perspectives`.
+fn retain_perspectives(
+    mut candidates: Vec<GameEntry>,
+    perspectives: &[PlayerPerspective],
+) -> Vec<GameEntry> {
+    if !perspectives.is_empty() {
+        candidates.retain(|game_entry| {
+            game_entry
+                .player_perspectives
+                .iter()
+                .any(|perspective| perspectives.contains(perspective))
+        });
+    }
+    candidates
+}
+
+/// Keeps only the `candidates` that support mods if `moddable_only` is set.
+/// IGDB search results carry no Steam data, so they are looked up in Firestore.
+async fn retain_moddable(
+    firestore: &FirestoreApi,
+    candidates: Vec<GameEntry>,
+    moddable_only: bool,
+) -> Vec<GameEntry> {
+    if !moddable_only {
+        return candidates;
+    }
+
+    let ids = candidates
+        .iter()
+        .map(|game_entry| game_entry.id)
+        .collect::<Vec<_>>();
+    let moddable = match games::batch_read(firestore, &ids).await {
+        Ok(result) => result
+            .documents
+            .iter()
+            .filter(|game_entry| game_entry.is_moddable())
+            .map(|game_entry| game_entry.id)
+            .collect::<HashSet<_>>(),
+        Err(status) => {
+            warn!("Failed to read candidates of moddable search: {status}");
+            HashSet::new()
+        }
+    };
+    candidates
+        .into_iter()
+        .filter(|game_entry| moddable.contains(&game_entry.id))
+        .collect()
+}
+
+/// Keeps only the `candidates` with a genre in any of the `groups`. IGDB search
+/// results carry no espy genres, so they are looked up in Firestore.
+async fn retain_genre_groups(
+    firestore: &FirestoreApi,
+    candidates: Vec<GameEntry>,
+    groups: &[EspyGenreGroup],
+) -> Vec<GameEntry> {
+    if groups.is_empty() {
+        return candidates;
+    }
+
+    let mut matches = vec![];
+    for mut game_entry in candidates {
+        match genres::read(firestore, game_entry.id).await {
+            Ok(genre) => game_entry.espy_genres = genre.espy_genres,
+            Err(Status::NotFound(_)) => {}
+            Err(status) => warn!("Failed to read genres of {}: {status}", game_entry.id),
+        }
+        if genre_groups(&game_entry.espy_genres)
+            .iter()
+            .any(|group| groups.contains(group))
+        {
+            matches.push(game_entry);
         }
     }
+    matches
 }
 
+#[utoipa::path(
+    post,
+    path = "/resolve",
+    request_body = models::Resolve,
+    responses(
+        (status = 200, description = "Game was resolved"),
+        (status = 404, description = "Game was not found in IGDB"),
+    )
+)]
 #[instrument(level = "trace", skip(firestore, igdb))]
 pub async fn post_resolve(
     resolve: models::Resolve,
@@ -51,7 +460,11 @@ pub async fn post_resolve(
     igdb: Arc<IgdbApi>,
 ) -> Result<impl warp::Reply, Infallible> {
     let event = ResolveEvent::new(&resolve);
-    match igdb.get(resolve.game_id).await {
+    let igdb_game = match (&resolve.slug, resolve.game_id) {
+        (Some(slug), 0) => igdb.get_by_slug(slug).await,
+        (_, game_id) => igdb.get(game_id).await,
+    };
+    match igdb_game {
         Ok(igdb_game) => match igdb.resolve(firestore, igdb_game).await {
             Ok(game_entry) => {
                 event.log(game_entry);
@@ -69,211 +482,1733 @@ pub async fn post_resolve(
     }
 }
 
-#[instrument(level = "trace", skip(firestore))]
-pub async fn post_delete(
-    resolve: models::Resolve,
+#[utoipa::path(
+    post,
+    path = "/resolve/prefetch",
+    request_body = models::Prefetch,
+    responses(
+        (status = 202, description = "Missing candidates are resolved in the background", body = models::PrefetchResult),
+        (status = 400, description = "Request is missing a user id"),
+        (status = 429, description = "Too many prefetch requests by the user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn post_prefetch(
+    request: models::Prefetch,
     firestore: Arc<FirestoreApi>,
-) -> Result<impl warp::Reply, Infallible> {
-    match games::delete(&firestore, resolve.game_id).await {
-        Ok(()) => Ok(StatusCode::OK),
-        Err(_) => Ok(StatusCode::INTERNAL_SERVER_ERROR),
+    igdb: Arc<IgdbApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    if request.user_id.is_empty() {
+        return Ok(Box::new(StatusCode::BAD_REQUEST));
+    }
+    if !prefetch::allow(&request.user_id) {
+        return Ok(Box::new(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    match prefetch::prefetch(
+        firestore,
+        igdb,
+        &request.user_id,
+        &request.game_ids,
+        request.depth,
+    )
+    .await
+    {
+        Ok(scheduled) => Ok(Box::new(warp::reply::with_status(
+            warp::reply::json(&models::PrefetchResult { scheduled }),
+            StatusCode::ACCEPTED,
+        ))),
+        Err(status) => {
+            warn!("Failed to prefetch games: {status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/delete",
+    request_body = models::Delete,
+    responses(
+        (status = 200, description = "Game was deleted"),
+        (status = 404, description = "Game was not found"),
+        (status = 500, description = "Failed to delete game"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
 #[instrument(level = "trace", skip(firestore))]
-pub async fn post_update(
-    user_id: String,
-    update: models::UpdateOp,
+pub async fn post_delete(
+    delete: models::Delete,
     firestore: Arc<FirestoreApi>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let event = UpdateEvent::new(&update);
-
-    let game_entry = match games::read(&firestore, update.game_id).await {
-        Ok(game_entry) => game_entry,
+    // Deleted games are kept as tombstones, so that IGDB webhooks do not add
+    // them back.
+    match games::soft_delete(&firestore, delete.game_id).await {
+        Ok(game_entry) => {
+            remove_digests(&firestore, &game_entry).await;
+            Ok(StatusCode::OK)
+        }
+        Err(Status::NotFound(_)) => Ok(StatusCode::NOT_FOUND),
         Err(status) => {
-            event.log_error(&user_id, status);
-            return Ok(StatusCode::NOT_FOUND);
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
         }
-    };
+    }
+}
 
-    let manager = LibraryManager::new(&user_id);
-    match manager.update_game(firestore, game_entry).await {
-        Ok(()) => {
-            event.log(&user_id);
+#[utoipa::path(
+    post,
+    path = "/admin/games/{game_id}/undelete",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Game was restored"),
+        (status = 400, description = "Game is not deleted"),
+        (status = 404, description = "Game was not found"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_undelete(
+    game_id: u64,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    match games::undelete(&firestore, game_id).await {
+        Ok(game_entry) => {
+            update_digests(&firestore, &game_entry).await;
             Ok(StatusCode::OK)
         }
+        Err(Status::InvalidArgument(msg)) => {
+            warn!("{msg}");
+            Ok(StatusCode::BAD_REQUEST)
+        }
+        Err(Status::NotFound(_)) => Ok(StatusCode::NOT_FOUND),
         Err(status) => {
-            event.log_error(&user_id, status);
+            warn!("{status}");
             Ok(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-#[instrument(
-    level = "trace",
-    skip(match_op, firestore, igdb),
-    fields(
-        title = %match_op.store_entry.title,
+#[utoipa::path(
+    post,
+    path = "/games/{game_id}/report",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    request_body = models::GameReport,
+    responses(
+        (status = 200, description = "Report was filed"),
+        (status = 400, description = "Report is missing a user id"),
+        (status = 404, description = "Game was not found"),
     )
 )]
-pub async fn post_match(
-    user_id: String,
-    match_op: models::MatchOp,
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_report(
+    game_id: u64,
+    game_report: models::GameReport,
     firestore: Arc<FirestoreApi>,
-    igdb: Arc<IgdbApi>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let event = MatchEvent::new(match_op.clone());
+    let event = ReportEvent::new_report(game_id, &game_report);
 
-    let game_entry = match match_op.game_entry {
-        Some(game_entry) => match games::read(&firestore, game_entry.id).await {
-            Ok(game_entry) => Some(game_entry),
-            Err(Status::NotFound(_)) => {
-                // TODO: move inside igdb service.
-                let igdb_game = match igdb.get(game_entry.id).await {
-                    Ok(igdb_game) => igdb_game,
-                    Err(status) => {
-                        event.log_error(&user_id, status);
-                        return Ok(StatusCode::NOT_FOUND);
-                    }
-                };
-                match igdb.resolve(Arc::clone(&firestore), igdb_game).await {
-                    Ok(digest) => Some(digest),
-                    Err(status) => {
-                        event.log_error(&user_id, status);
-                        return Ok(StatusCode::NOT_FOUND);
-                    }
-                }
-            }
-            Err(status) => {
-                event.log_error(&user_id, status);
-                return Ok(StatusCode::NOT_FOUND);
-            }
-        },
-        None => None,
-    };
+    if game_report.user_id.is_empty() {
+        event.log_error(Status::invalid_argument("Missing user_id argument."));
+        return Ok(StatusCode::BAD_REQUEST);
+    }
+    if let Err(status) = games::read(&firestore, game_id).await {
+        event.log_error(status);
+        return Ok(StatusCode::NOT_FOUND);
+    }
 
-    let manager = LibraryManager::new(&user_id);
-    let status = match (game_entry, match_op.unmatch_entry) {
-        // Match StoreEntry to GameEntry and add in Library.
-        (Some(game_entry), None) => {
-            manager
-                .create_library_entry(firestore, match_op.store_entry, game_entry)
-                .await
-        }
-        // Remove StoreEntry from Library.
-        (None, Some(_)) => {
-            manager
-                .unmatch_game(firestore, match_op.store_entry, match_op.delete_unmatched)
-                .await
-        }
-        // Match StoreEntry with a different GameEntry.
-        (Some(game_entry), Some(_library_entry)) => {
-            manager
-                .rematch_game(firestore, match_op.store_entry, game_entry)
-                .await
-        }
-        // Bad request, at least one must be present.
-        (None, None) => Err(Status::invalid_argument(
-            "Missing both game_entry and unmatch_entry args.",
-        )),
+    let report = Report {
+        id: Report::doc_id(game_id, &game_report.user_id),
+        game_id,
+        user_id: game_report.user_id,
+        reason: game_report.reason,
+        comment: game_report.comment,
+        timestamp: Utc::now().naive_utc().timestamp(),
+        ..Default::default()
     };
-
-    match status {
+    match reports::write(&firestore, &report).await {
         Ok(()) => {
-            event.log(&user_id);
+            event.log();
+            tokio::spawn(
+                async move {
+                    if let Err(status) = triage_report(&firestore, report).await {
+                        warn!("Failed to triage report: {status}");
+                    }
+                }
+                .instrument(trace_span!("spawn_report_triage")),
+            );
             Ok(StatusCode::OK)
         }
-        Err(Status::InvalidArgument(status)) => {
-            event.log_error(&user_id, Status::invalid_argument(status));
-            Ok(StatusCode::BAD_REQUEST)
-        }
         Err(status) => {
-            event.log_error(&user_id, status);
+            event.log_error(status);
             Ok(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/admin/reports",
+    responses(
+        (status = 200, description = "Open reports for curation", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
 #[instrument(level = "trace", skip(firestore))]
-pub async fn post_wishlist(
-    user_id: String,
-    wishlist: models::WishlistOp,
-    firestore: Arc<FirestoreApi>,
-) -> Result<impl warp::Reply, Infallible> {
-    let event = WishlistEvent::new(wishlist.clone());
+pub async fn get_reports(firestore: Arc<FirestoreApi>) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = ReportEvent::new_list();
 
-    let manager = LibraryManager::new(&user_id);
-    match (wishlist.add_game, wishlist.remove_game) {
-        (Some(library_entry), _) => match manager.add_to_wishlist(firestore, library_entry).await {
+    match reports::list(&firestore).await {
+        Ok(reports) => {
+            event.log();
+            Ok(Box::new(warp::reply::json(&reports)))
+        }
+        Err(status) => {
+            event.log_error(status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/games/{game_id}/archive",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    request_body = models::Archive,
+    responses(
+        (status = 200, description = "Archive state of the game was updated"),
+        (status = 404, description = "Game was not found"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_archive(
+    game_id: u64,
+    curator: String,
+    archive: models::Archive,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut game_entry = match games::read(&firestore, game_id).await {
+        Ok(game_entry) => game_entry,
+        Err(Status::NotFound(_)) => return Ok(StatusCode::NOT_FOUND),
+        Err(status) => {
+            warn!("{status}");
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let edit = GameEdit::new(
+        game_id,
+        &curator,
+        GameEditKind::ArchiveOverride,
+        &game_entry.archived_override,
+        &archive.archived,
+        Utc::now().timestamp_millis(),
+    );
+    game_entry.archived_override = archive.archived;
+    game_entry.update_archived();
+    if let Err(status) = games::write(&firestore, &mut game_entry).await {
+        warn!("{status}");
+        return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    if let Err(status) = game_edits::add(&firestore, &edit).await {
+        warn!("Failed to record edit of game={game_id}: {status}");
+    }
+
+    // Archiving removes the game from company and collection digests, while
+    // unarchiving adds it back.
+    update_digests(&firestore, &game_entry).await;
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/games/{game_id}/override",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Fields of the game pinned by curators", body = Object),
+        (status = 404, description = "Game has no override"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_override(
+    game_id: u64,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match overrides::read(&firestore, game_id).await {
+        Ok(game_override) => Ok(Box::new(warp::reply::json(&game_override))),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/games/{game_id}/override",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    request_body = models::GameOverride,
+    responses(
+        (status = 200, description = "Override of the game was updated"),
+        (status = 404, description = "Game was not found"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_override(
+    game_id: u64,
+    curator: String,
+    request: models::GameOverride,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut game_entry = match games::read(&firestore, game_id).await {
+        Ok(game_entry) => game_entry,
+        Err(Status::NotFound(_)) => return Ok(StatusCode::NOT_FOUND),
+        Err(status) => {
+            warn!("{status}");
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let previous = match overrides::read(&firestore, game_id).await {
+        Ok(game_override) => Some(game_override),
+        Err(Status::NotFound(_)) => None,
+        Err(status) => {
+            warn!("{status}");
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let timestamp = Utc::now().timestamp_millis();
+    let game_override = Override {
+        game_id,
+        name: request.name,
+        release_date: request.release_date,
+        espy_genres: request.espy_genres,
+        cover: request.cover,
+        curator,
+        timestamp,
+    };
+    let edit = GameEdit::new(
+        game_id,
+        &game_override.curator,
+        GameEditKind::FieldOverride,
+        &previous,
+        &match game_override.is_empty() {
+            true => None,
+            false => Some(game_override.clone()),
+        },
+        timestamp,
+    );
+
+    // Clearing the override keeps the pinned values on the game until it is
+    // resolved again.
+    let result = match game_override.is_empty() {
+        true => overrides::delete(&firestore, game_id).await,
+        false => overrides::write(&firestore, &game_override).await,
+    };
+    if let Err(status) = result {
+        warn!("{status}");
+        return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    game_override.apply(&mut game_entry);
+    if let Err(status) = games::write(&firestore, &mut game_entry).await {
+        warn!("{status}");
+        return Ok(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    if let Err(status) = game_edits::add(&firestore, &edit).await {
+        warn!("Failed to record edit of game={game_id}: {status}");
+    }
+
+    // Pinned fields are also part of the game's digests.
+    update_digests(&firestore, &game_entry).await;
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/games/{game_id}/edits",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Curator edits of the game with the most recent first", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_game_edits(
+    game_id: u64,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match game_edits::list(&firestore, game_id).await {
+        Ok(edits) => Ok(Box::new(warp::reply::json(&edits))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/merge",
+    request_body = models::Merge,
+    responses(
+        (status = 200, description = "Duplicate game was merged, with counts of the moved references", body = Object),
+        (status = 400, description = "Games cannot be merged"),
+        (status = 404, description = "Either game was not found"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_merge(
+    curator: String,
+    merge: models::Merge,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match merge::merge_games(&firestore, merge.duplicate_id, merge.target_id, &curator).await {
+        Ok(result) => Ok(Box::new(warp::reply::json(&result))),
+        Err(Status::InvalidArgument(msg)) => {
+            warn!("{msg}");
+            Ok(Box::new(StatusCode::BAD_REQUEST))
+        }
+        Err(Status::NotFound(msg)) => {
+            warn!("{msg}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/rejected",
+    responses(
+        (status = 200, description = "Blocklisted games with the most recent first", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_rejected(
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match rejected::list(&firestore).await {
+        Ok(rejected) => Ok(Box::new(warp::reply::json(&rejected))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/rejected/{game_id}",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    request_body = models::Reject,
+    responses(
+        (status = 200, description = "Game was blocklisted"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_rejected(
+    game_id: u64,
+    curator: String,
+    reject: models::Reject,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    // Games can be blocklisted before they are ever stored, in which case
+    // their name is unknown.
+    let name = match games::read(&firestore, game_id).await {
+        Ok(game_entry) => game_entry.name,
+        Err(_) => String::default(),
+    };
+
+    let rejected = RejectedGame {
+        id: game_id,
+        name,
+        reason: reject.reason,
+        curator,
+        timestamp: Utc::now().naive_utc().timestamp(),
+    };
+    match rejected::add(&firestore, &rejected).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(status) => {
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/rejected/{game_id}",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Game was removed from the blocklist"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "User is not a curator"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn delete_rejected(
+    game_id: u64,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    match rejected::remove(&firestore, game_id).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(status) => {
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/prices/{game_id}",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Game digest with its best current price and historical low", body = Object),
+        (status = 404, description = "Game was not found or it is not tracked by IsThereAnyDeal"),
+        (status = 503, description = "Price comparison is not configured"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, itad))]
+pub async fn get_prices(
+    game_id: u64,
+    firestore: Arc<FirestoreApi>,
+    itad: Arc<ItadApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    if !itad.is_enabled() {
+        return Ok(Box::new(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    let game_entry = match games::read(&firestore, game_id).await {
+        Ok(game_entry) if game_entry.deleted_at.is_some() => {
+            return Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Ok(game_entry) => game_entry,
+        Err(Status::NotFound(_)) => return Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    // Steam ids map to plains unambiguously, titles are only a fallback.
+    let plain = match external_games::get_steam_id(&firestore, game_id).await {
+        Ok(steam_appid) => itad.get_plain_by_steam_id(&steam_appid).await,
+        Err(_) => Ok(None),
+    };
+    let plain = match plain {
+        Ok(Some(plain)) => Ok(Some(plain)),
+        Ok(None) => itad.get_plain_by_title(&game_entry.name).await,
+        Err(status) => Err(status),
+    };
+    let plain = match plain {
+        Ok(Some(plain)) => plain,
+        Ok(None) => return Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!(
+                "Failed to find ITAD plain for '{}': {status}",
+                game_entry.name
+            );
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    match itad.get_prices(&plain).await {
+        Ok(prices) => {
+            let mut digest = GameDigest::from(game_entry);
+            digest.prices = Some(prices);
+            Ok(Box::new(warp::reply::json(&digest)))
+        }
+        Err(status) => {
+            warn!("Failed to retrieve prices for '{plain}': {status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/update",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::UpdateOp,
+    responses(
+        (status = 200, description = "Library entry was updated"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Game was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_update(
+    user_id: String,
+    update: models::UpdateOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = UpdateEvent::new(&update);
+
+    let game_entry = match games::read(&firestore, update.game_id).await {
+        Ok(game_entry) => game_entry,
+        Err(status) => {
+            event.log_error(&user_id, status);
+            return Ok(StatusCode::NOT_FOUND);
+        }
+    };
+
+    let manager = LibraryManager::new(&user_id);
+    match manager.update_game(firestore, game_entry).await {
+        Ok(()) => {
+            event.log(&user_id);
+            Ok(StatusCode::OK)
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/match",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::MatchOp,
+    responses(
+        (status = 200, description = "Storefront entry was (un)matched"),
+        (status = 400, description = "Invalid match operation"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Game was not found"),
+    )
+)]
+#[instrument(
+    level = "trace",
+    skip(match_op, firestore, igdb),
+    fields(
+        title = %match_op.store_entry.title,
+    )
+)]
+pub async fn post_match(
+    user_id: String,
+    match_op: models::MatchOp,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = MatchEvent::new(match_op.clone());
+
+    let game_entry = match match_op.game_entry {
+        Some(game_entry) => match games::read(&firestore, game_entry.id).await {
+            Ok(game_entry) => Some(game_entry),
+            Err(Status::NotFound(_)) => {
+                // TODO: move inside igdb service.
+                let igdb_game = match igdb.get(game_entry.id).await {
+                    Ok(igdb_game) => igdb_game,
+                    Err(status) => {
+                        event.log_error(&user_id, status);
+                        return Ok(StatusCode::NOT_FOUND);
+                    }
+                };
+                match igdb.resolve(Arc::clone(&firestore), igdb_game).await {
+                    Ok(digest) => Some(digest),
+                    Err(status) => {
+                        event.log_error(&user_id, status);
+                        return Ok(StatusCode::NOT_FOUND);
+                    }
+                }
+            }
+            Err(status) => {
+                event.log_error(&user_id, status);
+                return Ok(StatusCode::NOT_FOUND);
+            }
+        },
+        None => None,
+    };
+
+    let manager = LibraryManager::new(&user_id);
+    let status = match (game_entry, match_op.unmatch_entry) {
+        // Match StoreEntry to GameEntry and add in Library.
+        (Some(game_entry), None) => {
+            manager
+                .create_library_entry(firestore, match_op.store_entry, game_entry)
+                .await
+        }
+        // Remove StoreEntry from Library.
+        (None, Some(_)) => {
+            manager
+                .unmatch_game(firestore, match_op.store_entry, match_op.delete_unmatched)
+                .await
+        }
+        // Match StoreEntry with a different GameEntry.
+        (Some(game_entry), Some(_library_entry)) => {
+            manager
+                .rematch_game(firestore, match_op.store_entry, game_entry)
+                .await
+        }
+        // Bad request, at least one must be present.
+        (None, None) => Err(Status::invalid_argument(
+            "Missing both game_entry and unmatch_entry args.",
+        )),
+    };
+
+    match status {
+        Ok(()) => {
+            event.log(&user_id);
+            Ok(StatusCode::OK)
+        }
+        Err(Status::InvalidArgument(status)) => {
+            event.log_error(&user_id, Status::invalid_argument(status));
+            Ok(StatusCode::BAD_REQUEST)
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/wishlist",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::WishlistOp,
+    responses(
+        (status = 200, description = "Wishlist was updated"),
+        (status = 400, description = "Invalid wishlist operation"),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_wishlist(
+    user_id: String,
+    wishlist: models::WishlistOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = WishlistEvent::new(wishlist.clone());
+
+    let manager = LibraryManager::new(&user_id);
+    match (wishlist.add_game, wishlist.remove_game) {
+        (Some(library_entry), _) => match manager.add_to_wishlist(firestore, library_entry).await {
+            Ok(()) => {
+                event.log(&user_id);
+                Ok(StatusCode::OK)
+            }
+            Err(status) => {
+                event.log_error(&user_id, status);
+                Ok(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        },
+        (_, Some(game_id)) => match manager.remove_from_wishlist(firestore, game_id).await {
+            Ok(()) => {
+                event.log(&user_id);
+                Ok(StatusCode::OK)
+            }
+            Err(status) => {
+                event.log_error(&user_id, status);
+                Ok(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        },
+        _ => {
+            event.log_error(
+                &user_id,
+                Status::invalid_argument("Missing both add_game and remove_game arguments."),
+            );
+            Ok(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/filters",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "User's smart filters", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_filters(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = FilterEvent::new_list();
+
+    match filters::read(&firestore, &user_id).await {
+        Ok(filters) => {
+            event.log(&user_id);
+            Ok(Box::new(warp::reply::json(&filters.filters)))
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/filters",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::FilterOp,
+    responses(
+        (status = 200, description = "Smart filters were updated"),
+        (status = 400, description = "Invalid filter operation"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Filter was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_filters(
+    user_id: String,
+    filter_op: models::FilterOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = FilterEvent::new_op(&filter_op);
+
+    let manager = LibraryManager::new(&user_id);
+    let status = match (filter_op.save, filter_op.delete) {
+        (Some(filter), _) => manager.save_filter(firestore, filter).await,
+        (_, Some(name)) => manager.delete_filter(firestore, &name).await,
+        _ => Err(Status::invalid_argument(
+            "Missing both save and delete arguments.",
+        )),
+    };
+
+    match status {
+        Ok(()) => {
+            event.log(&user_id);
+            Ok(StatusCode::OK)
+        }
+        Err(Status::InvalidArgument(status)) => {
+            event.log_error(&user_id, Status::invalid_argument(status));
+            Ok(StatusCode::BAD_REQUEST)
+        }
+        Err(Status::NotFound(status)) => {
+            event.log_error(&user_id, Status::not_found(status));
+            Ok(StatusCode::NOT_FOUND)
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/follows",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "Companies, franchises and collections followed by the user", body = Object),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_follows(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match follows::read(&firestore, &user_id).await {
+        Ok(follows) => Ok(Box::new(warp::reply::json(&follows))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/follows",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::FollowOp,
+    responses(
+        (status = 200, description = "Follows were updated"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Entity to unfollow was not followed"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_follows(
+    user_id: String,
+    follow_op: models::FollowOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let result = match follow_op.unfollow {
+        false => follows::follow(&firestore, &user_id, follow_op.kind, follow_op.id).await,
+        true => follows::unfollow(&firestore, &user_id, follow_op.kind, follow_op.id).await,
+    };
+
+    match result {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(Status::NotFound(_)) => Ok(StatusCode::NOT_FOUND),
+        Err(status) => {
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/evaluate_filter",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::EvaluateFilter,
+    responses(
+        (status = 200, description = "Library entries matching the filter", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Filter was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_evaluate_filter(
+    user_id: String,
+    evaluate: models::EvaluateFilter,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = FilterEvent::new_evaluate(&evaluate);
+
+    let manager = LibraryManager::new(&user_id);
+    match manager.evaluate_filter(firestore, &evaluate.name).await {
+        Ok(entries) => {
+            event.log(&user_id);
+            Ok(Box::new(warp::reply::json(&entries)))
+        }
+        Err(Status::NotFound(status)) => {
+            event.log_error(&user_id, Status::not_found(status));
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/webhooks",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "User's outbound webhooks", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_webhooks(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = WebhookEvent::new_list();
+
+    match outbound_webhooks::list_by_owner(&firestore, &user_id).await {
+        Ok(mut webhooks) => {
+            event.log(&user_id);
+            for webhook in &mut webhooks {
+                webhook.secret.clear();
+            }
+            Ok(Box::new(warp::reply::json(&webhooks)))
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/webhooks",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::WebhookOp,
+    responses(
+        (status = 200, description = "Outbound webhooks were updated"),
+        (status = 400, description = "Invalid webhook operation"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Webhook was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, webhook_op))]
+pub async fn post_webhooks(
+    user_id: String,
+    webhook_op: models::WebhookOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = WebhookEvent::new_op(&webhook_op);
+
+    let status = match (webhook_op.register, webhook_op.unregister) {
+        (Some(webhook), _) => outbound::register(&firestore, &user_id, webhook)
+            .await
+            .map(|_| ()),
+        (_, Some(webhook_id)) => outbound::unregister(&firestore, &user_id, &webhook_id).await,
+        _ => Err(Status::invalid_argument(
+            "Missing both register and unregister arguments.",
+        )),
+    };
+
+    match status {
+        Ok(()) => {
+            event.log(&user_id);
+            Ok(StatusCode::OK)
+        }
+        Err(Status::InvalidArgument(status)) => {
+            event.log_error(&user_id, Status::invalid_argument(status));
+            Ok(StatusCode::BAD_REQUEST)
+        }
+        Err(Status::NotFound(status)) => {
+            event.log_error(&user_id, Status::not_found(status));
+            Ok(StatusCode::NOT_FOUND)
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/tokens",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "User's scoped access tokens, without the tokens themselves", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_sync_tokens(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match sync_tokens::list(&firestore, &user_id).await {
+        Ok(tokens) => Ok(Box::new(warp::reply::json(&tokens))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/tokens",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::SyncTokenRequest,
+    responses(
+        (status = 200, description = "Minted access token", body = models::SyncTokenResponse),
+        (status = 400, description = "Invalid scopes or lifetime"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, request))]
+pub async fn post_sync_token(
+    user_id: String,
+    request: models::SyncTokenRequest,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match sync_tokens::mint(
+        &firestore,
+        &user_id,
+        request.scopes,
+        request.label,
+        request.ttl_secs.unwrap_or(sync_tokens::DEFAULT_TTL_SECS),
+    )
+    .await
+    {
+        Ok((token, info)) => Ok(Box::new(warp::reply::json(&models::SyncTokenResponse {
+            token,
+            info,
+        }))),
+        Err(Status::InvalidArgument(msg)) => {
+            warn!("{msg}");
+            Ok(Box::new(StatusCode::BAD_REQUEST))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/library/{user_id}/tokens/{token_id}",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        ("token_id" = String, Path, description = "Id of the access token"),
+    ),
+    responses(
+        (status = 200, description = "Access token was revoked"),
+        (status = 404, description = "Access token was not found"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn delete_sync_token(
+    user_id: String,
+    token_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    match sync_tokens::revoke(&firestore, &user_id, &token_id).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(Status::NotFound(msg)) => {
+            warn!("{msg}");
+            Ok(StatusCode::NOT_FOUND)
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/unlink",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::Unlink,
+    responses(
+        (status = 200, description = "Storefront was unlinked"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_unlink(
+    user_id: String,
+    unlink: models::Unlink,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = UnlinkEvent::new(&unlink);
+
+    match User::fetch(Arc::clone(&firestore), &user_id).await {
+        // Remove storefront credentials from UserData.
+        Ok(mut user) => match user.remove_storefront(&unlink.storefront_id).await {
             Ok(()) => {
-                event.log(&user_id);
-                Ok(StatusCode::OK)
+                // Remove storefront library entries.
+                let manager = LibraryManager::new(&user_id);
+                match manager
+                    .remove_storefront(firestore, &unlink.storefront_id)
+                    .await
+                {
+                    Ok(()) => {
+                        event.log(&user_id);
+                        Ok(StatusCode::OK)
+                    }
+                    Err(status) => {
+                        event.log_error(&user_id, status);
+                        Ok(StatusCode::INTERNAL_SERVER_ERROR)
+                    }
+                }
             }
             Err(status) => {
                 event.log_error(&user_id, status);
                 Ok(StatusCode::INTERNAL_SERVER_ERROR)
             }
         },
-        (_, Some(game_id)) => match manager.remove_from_wishlist(firestore, game_id).await {
-            Ok(()) => {
-                event.log(&user_id);
-                Ok(StatusCode::OK)
-            }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/steam/login",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::SteamLogin,
+    ),
+    responses(
+        (status = 302, description = "Redirect to the Steam sign in page"),
+        (status = 400, description = "return_to is not a page of the frontend"),
+    )
+)]
+#[instrument(level = "trace", skip(keys))]
+pub async fn get_steam_login(
+    user_id: String,
+    login: models::SteamLogin,
+    keys: Arc<util::keys::Keys>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match steam_openid::login_url(&keys.steam.openid_origin, &login.return_to, &user_id) {
+        Ok(url) => Ok(Box::new(warp::reply::with_header(
+            StatusCode::FOUND,
+            "Location",
+            url,
+        ))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::BAD_REQUEST))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/steam/verify",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::SteamVerify,
+    responses(
+        (status = 200, description = "Steam account was linked", body = models::SteamLink),
+        (status = 400, description = "Steam sign in could not be verified"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(verify, keys, firestore))]
+pub async fn post_steam_verify(
+    user_id: String,
+    verify: models::SteamVerify,
+    keys: Arc<util::keys::Keys>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let steam_user_id =
+        match steam_openid::verify(&verify.params, &keys.steam.openid_origin, &user_id).await {
+            Ok(steam_user_id) => steam_user_id,
             Err(status) => {
-                event.log_error(&user_id, status);
-                Ok(StatusCode::INTERNAL_SERVER_ERROR)
+                warn!("Failed to verify Steam sign in of '{user_id}': {status}");
+                return Ok(Box::new(StatusCode::BAD_REQUEST));
             }
-        },
-        _ => {
-            event.log_error(
-                &user_id,
-                Status::invalid_argument("Missing both add_game and remove_game arguments."),
-            );
+        };
+
+    let result = match User::fetch(firestore, &user_id).await {
+        Ok(mut user) => user.link_steam(&steam_user_id).await,
+        Err(status) => Err(status),
+    };
+    match result {
+        Ok(()) => Ok(Box::new(warp::reply::json(&models::SteamLink {
+            steam_user_id,
+        }))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/feed/{user_id}",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "Weekly feed of the user", body = Object),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_feed(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match user_feed::read(&firestore, &user_id).await {
+        Ok(feed) => Ok(Box::new(warp::reply::json(&feed))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/games/{game_id}/full",
+    params(
+        ("game_id" = u64, Path, description = "IGDB game id"),
+        models::GameQuery,
+    ),
+    responses(
+        (status = 200, description = "Game entry with its prices, Wikipedia page, similar games and ownership flags", body = Object),
+        (status = 404, description = "Game was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_game_view(
+    game_id: u64,
+    query: models::GameQuery,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match views::game_view(&firestore, game_id, query.user_id.as_deref()).await {
+        Ok(view) => Ok(Box::new(warp::reply::json(&view))),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/franchise/{franchise_id}",
+    params(
+        ("franchise_id" = u64, Path, description = "IGDB franchise id"),
+        models::FranchiseQuery,
+    ),
+    responses(
+        (status = 200, description = "Franchise games in release order with aggregate stats", body = Object),
+        (status = 404, description = "Franchise was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_franchise(
+    franchise_id: u64,
+    query: models::FranchiseQuery,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match views::franchise_view(&firestore, franchise_id, query.user_id.as_deref()).await {
+        Ok(view) => Ok(Box::new(warp::reply::json(&view))),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/company/{company_id}",
+    params(
+        ("company_id" = u64, Path, description = "IGDB company id"),
+        models::CompanyQuery,
+    ),
+    responses(
+        (status = 200, description = "Company with a page of its developed and published games", body = Object),
+        (status = 404, description = "Company was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_company(
+    company_id: u64,
+    query: models::CompanyQuery,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match views::company_view(
+        &firestore,
+        company_id,
+        query.user_id.as_deref(),
+        query.offset,
+        query.limit,
+    )
+    .await
+    {
+        Ok(view) => Ok(Box::new(warp::reply::json(&view))),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::LibraryView,
+    ),
+    responses(
+        (status = 200, description = "User's library in the requested order", body = Object),
+        (status = 404, description = "Library was not found"),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_library(
+    user_id: String,
+    view: models::LibraryView,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match library::read(&firestore, &user_id).await {
+        Ok(mut library) => {
+            library.sort(view.sort);
+            Ok(Box::new(warp::reply::json(&library)))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/entries",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::LibraryPageQuery,
+    ),
+    responses(
+        (status = 200, description = "Page of the user's library ordered by game id", body = models::LibraryPage),
+        (status = 500, description = "Library failed to read"),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_library_page(
+    user_id: String,
+    query: models::LibraryPageQuery,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let limit = query.limit.clamp(1, MAX_LIBRARY_PAGE_SIZE);
+    match library::read_page(&firestore, &user_id, query.after, limit).await {
+        Ok(entries) => {
+            let next = match entries.len() == limit as usize {
+                true => entries.last().map(|entry| entry.id),
+                false => None,
+            };
+            Ok(Box::new(warp::reply::json(&models::LibraryPage {
+                entries,
+                next,
+            })))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+const MAX_LIBRARY_PAGE_SIZE: u32 = 500;
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/diff",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::LibraryDiffQuery,
+    ),
+    responses(
+        (status = 200, description = "Games added, removed and rematched in the user's library within the period", body = Object),
+        (status = 400, description = "Period ends before it starts"),
+        (status = 500, description = "Library events failed to read"),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_library_diff(
+    user_id: String,
+    query: models::LibraryDiffQuery,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let to = query.to.unwrap_or_else(|| Utc::now().timestamp());
+    if to < query.from {
+        warn!("Invalid library diff period [{}, {to}]", query.from);
+        return Ok(Box::new(StatusCode::BAD_REQUEST));
+    }
+
+    match library_events::list(&firestore, &user_id, query.from, to).await {
+        Ok(events) => Ok(Box::new(warp::reply::json(&LibraryDiff::new(events)))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/stats",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::Stats,
+    ),
+    responses(
+        (status = 200, description = "Genre breakdown of the user's library", body = models::LibraryStats),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+        (status = 404, description = "Library was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_stats(
+    user_id: String,
+    stats: models::Stats,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match library::read(&firestore, &user_id).await {
+        Ok(library) => Ok(Box::new(warp::reply::json(&models::LibraryStats::new(
+            &library,
+            stats.group,
+        )))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/suggestions",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "Proposed cleanups of the user's library", body = [Object]),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Library was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_suggestions(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match library::read(&firestore, &user_id).await {
+        Ok(library) => Ok(Box::new(warp::reply::json(&suggestions::suggest(&library)))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/suggestions",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::SuggestionOp,
+    responses(
+        (status = 200, description = "Suggestion was applied"),
+        (status = 400, description = "Suggestion is missing its target game"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+        (status = 404, description = "Game of the suggestion was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn post_suggestions(
+    user_id: String,
+    suggestion_op: models::SuggestionOp,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let manager = LibraryManager::new(&user_id);
+    match manager
+        .apply_suggestion(firestore, suggestion_op.apply)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(Status::InvalidArgument(msg)) => {
+            warn!("{msg}");
             Ok(StatusCode::BAD_REQUEST)
         }
+        Err(Status::NotFound(msg)) => {
+            warn!("{msg}");
+            Ok(StatusCode::NOT_FOUND)
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/library/{user_id}/export",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::Export,
+    ),
+    responses(
+        (status = 200, description = "User's library", body = [models::ExportEntry], content_type = ["application/json", "text/csv"]),
+        (status = 401, description = "Request carries no valid ID token or access token"),
+        (status = 403, description = "Token does not grant access to the user's data"),
+        (status = 404, description = "Library was not found"),
+    )
+)]
 #[instrument(level = "trace", skip(firestore))]
-pub async fn post_unlink(
+pub async fn get_export(
     user_id: String,
-    unlink: models::Unlink,
+    export: models::Export,
     firestore: Arc<FirestoreApi>,
-) -> Result<impl warp::Reply, Infallible> {
-    let event = UnlinkEvent::new(&unlink);
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = ExportEvent::new(&export);
 
-    match User::fetch(Arc::clone(&firestore), &user_id).await {
-        // Remove storefront credentials from UserData.
-        Ok(mut user) => match user.remove_storefront(&unlink.storefront_id).await {
-            Ok(()) => {
-                // Remove storefront library entries.
-                let manager = LibraryManager::new(&user_id);
-                match manager
-                    .remove_storefront(firestore, &unlink.storefront_id)
-                    .await
-                {
-                    Ok(()) => {
-                        event.log(&user_id);
-                        Ok(StatusCode::OK)
-                    }
-                    Err(status) => {
-                        event.log_error(&user_id, status);
-                        Ok(StatusCode::INTERNAL_SERVER_ERROR)
-                    }
-                }
-            }
-            Err(status) => {
-                event.log_error(&user_id, status);
-                Ok(StatusCode::INTERNAL_SERVER_ERROR)
+    let mut library = match library::read(&firestore, &user_id).await {
+        Ok(library) => library,
+        Err(status) => {
+            event.log_error(&user_id, status);
+            return Ok(Box::new(StatusCode::NOT_FOUND));
+        }
+    };
+    let annotations = match user_annotations::read(&firestore, &user_id).await {
+        Ok(annotations) => annotations,
+        Err(status) => {
+            warn!("{status}");
+            Default::default()
+        }
+    };
+
+    let mut user_tags = HashMap::<u64, Vec<&str>>::new();
+    for tag in &annotations.user_tags {
+        for game_id in &tag.game_ids {
+            user_tags.entry(*game_id).or_default().push(&tag.name);
+        }
+    }
+
+    library.sort(export.sort);
+    let entries = library
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let tags = user_tags.get(&entry.id).map_or(&[][..], |tags| &tags[..]);
+            models::ExportEntry::new(entry, tags)
+        })
+        .collect::<Vec<_>>();
+    let size = entries.len();
+
+    let (body, content_type, filename) = match export.format {
+        models::ExportFormat::Json => match serde_json::to_vec(&entries) {
+            Ok(body) => (body, "application/json", "library.json"),
+            Err(e) => {
+                event.log_error(&user_id, Status::from(e));
+                return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
             }
         },
+        models::ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            let body = entries
+                .iter()
+                .try_for_each(|entry| writer.serialize(entry))
+                .map_err(|e| Status::internal(e.to_string()))
+                .and_then(|()| {
+                    writer
+                        .into_inner()
+                        .map_err(|e| Status::internal(e.to_string()))
+                });
+            match body {
+                Ok(body) => (body, "text/csv", "library.csv"),
+                Err(status) => {
+                    event.log_error(&user_id, status);
+                    return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+                }
+            }
+        }
+    };
+
+    event.log(&user_id, size);
+    Ok(Box::new(warp::reply::with_header(
+        warp::reply::with_header(body, "Content-Type", content_type),
+        "Content-Disposition",
+        format!("attachment; filename=\"{filename}\""),
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/import",
+    params(
+        ("user_id" = String, Path, description = "Espy user id"),
+        models::Import,
+    ),
+    request_body(content = String, description = "Library file in the given format", content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Titles found in the file", body = models::ImportResult),
+        (status = 400, description = "File failed to parse"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(data, firestore, igdb))]
+pub async fn post_import(
+    user_id: String,
+    import: models::Import,
+    data: warp::hyper::body::Bytes,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let event = ImportEvent::new(&import);
+
+    let store_entries = match import.format {
+        models::ImportFormat::Csv => library_import::parse_csv(&data),
+        models::ImportFormat::Json => library_import::parse_json(&data),
+        models::ImportFormat::Text => library_import::parse_text(&data),
+    };
+    let store_entries = match store_entries {
+        Ok(store_entries) => store_entries,
         Err(status) => {
             event.log_error(&user_id, status);
-            Ok(StatusCode::INTERNAL_SERVER_ERROR)
+            return Ok(Box::new(StatusCode::BAD_REQUEST));
+        }
+    };
+    let titles = store_entries.len();
+
+    let store_entries = match storefront::diff_entries(&firestore, &user_id, store_entries).await {
+        Ok(store_entries) => store_entries,
+        Err(status) => {
+            event.log_error(&user_id, status);
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+    let result = models::ImportResult {
+        titles,
+        new_titles: store_entries.len(),
+    };
+
+    let manager = LibraryManager::new(&user_id);
+    match manager
+        .batch_recon_store_entries(firestore, igdb, store_entries)
+        .await
+    {
+        Ok(()) => {
+            event.log(&user_id, &result);
+            Ok(Box::new(warp::reply::json(&result)))
+        }
+        Err(status) => {
+            event.log_error(&user_id, status);
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
         }
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/library/{user_id}/sync",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "Library was synced with the user's storefronts"),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
 #[instrument(level = "trace", skip(api_keys, firestore, igdb))]
 pub async fn post_sync(
     user_id: String,
@@ -283,13 +2218,17 @@ pub async fn post_sync(
 ) -> Result<impl warp::Reply, Infallible> {
     let event = SyncEvent::new();
 
-    let store_entries = match User::fetch(Arc::clone(&firestore), &user_id).await {
-        Ok(mut user) => user.sync_accounts(&api_keys).await,
-        Err(status) => Err(status),
+    let (diff, steam_api, gog_api) = match User::fetch(Arc::clone(&firestore), &user_id).await {
+        Ok(mut user) => (
+            user.sync_accounts(&api_keys).await,
+            user.steam_api(&api_keys),
+            user.gog_api(),
+        ),
+        Err(status) => (Err(status), None, None),
     };
 
-    let store_entries = match store_entries {
-        Ok(store_entries) => store_entries,
+    let diff = match diff {
+        Ok(diff) => diff,
         Err(status) => {
             event.log_error(&user_id, status);
             return Ok(StatusCode::INTERNAL_SERVER_ERROR);
@@ -297,10 +2236,33 @@ pub async fn post_sync(
     };
 
     let manager = LibraryManager::new(&user_id);
-    match manager
-        .batch_recon_store_entries(firestore, igdb, store_entries)
+    let status = match manager
+        .remove_store_entries(Arc::clone(&firestore), diff.removed)
         .await
     {
+        Ok(()) => {
+            manager
+                .batch_recon_store_entries(Arc::clone(&firestore), igdb, diff.added)
+                .await
+        }
+        Err(status) => Err(status),
+    };
+
+    if let Some(steam_api) = steam_api {
+        if let Err(status) = manager
+            .refresh_steam_stats(Arc::clone(&firestore), &steam_api)
+            .await
+        {
+            warn!("Failed to refresh Steam stats: {status}");
+        }
+    }
+    if let Some(gog_api) = gog_api {
+        if let Err(status) = manager.refresh_gog_playtime(firestore, &gog_api).await {
+            warn!("Failed to refresh GOG playtime: {status}");
+        }
+    }
+
+    match status {
         Ok(()) => {
             event.log(&user_id);
             Ok(StatusCode::OK)
@@ -312,9 +2274,423 @@ pub async fn post_sync(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/account/{user_id}/export",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    responses(
+        (status = 200, description = "Archive with all of the user's data", body = Object),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_account_export(
+    user_id: String,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let archive = match account::export(&firestore, &user_id).await {
+        Ok(archive) => archive,
+        Err(status) => {
+            warn!("Failed to export account '{user_id}': {status}");
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    Ok(Box::new(warp::reply::with_header(
+        warp::reply::json(&archive),
+        "Content-Disposition",
+        "attachment; filename=\"espy_account.json\"",
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/account/{user_id}/delete",
+    params(("user_id" = String, Path, description = "Espy user id")),
+    request_body = models::AccountDelete,
+    responses(
+        (status = 200, description = "State of the deletion request", body = models::AccountDeleteResult),
+        (status = 401, description = "Request carries no valid ID token"),
+        (status = 403, description = "ID token belongs to another user, or confirmation token is invalid or expired"),
+        (status = 404, description = "No deletion was requested"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore, request))]
+pub async fn post_account_delete(
+    user_id: String,
+    request: models::AccountDelete,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    if request.cancel {
+        return match account::cancel_deletion(&firestore, &user_id).await {
+            Ok(()) => Ok(Box::new(StatusCode::OK)),
+            Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+            Err(status) => {
+                warn!("{status}");
+                Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+            }
+        };
+    }
+
+    let result = match &request.token {
+        Some(token) => account::confirm_deletion(&firestore, &user_id, token).await,
+        None => account::request_deletion(&firestore, &user_id).await,
+    };
+    match result {
+        Ok(deletion) => Ok(Box::new(warp::reply::json(&models::AccountDeleteResult {
+            // The token is only handed out when the deletion is requested.
+            token: match request.token {
+                Some(_) => String::default(),
+                None => deletion.token,
+            },
+            confirmed: deletion.confirmed,
+            scheduled_at: match deletion.confirmed {
+                true => Some(deletion.scheduled_at),
+                false => None,
+            },
+        }))),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(Status::PermissionDenied(_)) => Ok(Box::new(StatusCode::FORBIDDEN)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/frontpage/rss",
+    responses(
+        (status = 200, description = "Recent and upcoming releases as an Atom feed", content_type = "application/atom+xml"),
+        (status = 404, description = "Frontpage was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_frontpage_feed(
+    host: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let frontpage = match frontpage::lookup(&firestore).await {
+        Ok(frontpage) => frontpage,
+        Err(status) => {
+            warn!("{status}");
+            return Ok(Box::new(StatusCode::NOT_FOUND));
+        }
+    };
+
+    let self_url = host.map(|host| format!("https://{host}/frontpage/rss"));
+    Ok(Box::new(warp::reply::with_header(
+        feed::frontpage_atom(&frontpage, self_url.as_deref()),
+        "Content-Type",
+        "application/atom+xml; charset=utf-8",
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/timeline",
+    params(models::PublicDocQuery),
+    responses(
+        (status = 200, description = "Timeline of recent and upcoming releases", body = Object),
+        (status = 304, description = "Timeline has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Timeline was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_timeline(
+    query: models::PublicDocQuery,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match timeline::lookup(&firestore).await {
+        Ok(mut timeline) => {
+            if !query.debug {
+                timeline.strip_explanations();
+            }
+            Ok(Box::new(warp::reply::with_header(
+                public_reply(&timeline, if_none_match, &PUBLIC_DOC_CACHE),
+                "Content-Location",
+                format!("/v1/timeline/{}", timeline.last_updated),
+            )))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+/// Returns the timeline if it was last updated at `version`. Stale versions
+/// are redirected to the current one, so that versioned URLs can be cached
+/// indefinitely.
+#[utoipa::path(
+    get,
+    path = "/v1/timeline/{version}",
+    params(("version" = u64, Path, description = "Timestamp that the timeline was last updated")),
+    responses(
+        (status = 200, description = "Timeline of recent and upcoming releases", body = Object),
+        (status = 302, description = "Version is stale, redirects to the current one"),
+        (status = 304, description = "Timeline has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Timeline was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_timeline_version(
+    version: u64,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match timeline::lookup(&firestore).await {
+        Ok(mut timeline) if timeline.last_updated == version => {
+            timeline.strip_explanations();
+            Ok(public_reply(
+                &timeline,
+                if_none_match,
+                &PUBLIC_VERSIONED_DOC_CACHE,
+            ))
+        }
+        Ok(timeline) => Ok(public_redirect(&format!(
+            "/v1/timeline/{}",
+            timeline.last_updated
+        ))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/frontpage",
+    params(models::PublicDocQuery),
+    responses(
+        (status = 200, description = "Frontpage releases", body = Object),
+        (status = 304, description = "Frontpage has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Frontpage was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_frontpage(
+    query: models::PublicDocQuery,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match frontpage::lookup(&firestore).await {
+        Ok(mut frontpage) => {
+            if !query.debug {
+                frontpage.strip_explanations();
+            }
+            Ok(Box::new(warp::reply::with_header(
+                public_reply(&frontpage, if_none_match, &PUBLIC_DOC_CACHE),
+                "Content-Location",
+                format!("/v1/frontpage/{}", frontpage.last_updated),
+            )))
+        }
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+/// Returns the frontpage if it was last updated at `version`. Stale versions
+/// are redirected to the current one.
+#[utoipa::path(
+    get,
+    path = "/v1/frontpage/{version}",
+    params(("version" = u64, Path, description = "Timestamp that the frontpage was last updated")),
+    responses(
+        (status = 200, description = "Frontpage releases", body = Object),
+        (status = 302, description = "Version is stale, redirects to the current one"),
+        (status = 304, description = "Frontpage has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Frontpage was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_frontpage_version(
+    version: u64,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match frontpage::lookup(&firestore).await {
+        Ok(mut frontpage) if frontpage.last_updated == version => {
+            frontpage.strip_explanations();
+            Ok(public_reply(
+                &frontpage,
+                if_none_match,
+                &PUBLIC_VERSIONED_DOC_CACHE,
+            ))
+        }
+        Ok(frontpage) => Ok(public_redirect(&format!(
+            "/v1/frontpage/{}",
+            frontpage.last_updated
+        ))),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+/// Returns a game entry without the raw IGDB, Steam and GOG payloads it was
+/// built from.
+#[utoipa::path(
+    get,
+    path = "/v1/games/{game_id}",
+    params(("game_id" = u64, Path, description = "IGDB game id")),
+    responses(
+        (status = 200, description = "Game entry", body = Object),
+        (status = 304, description = "Game has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Game was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_game(
+    game_id: u64,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let game_entry = match games::read(&firestore, game_id).await {
+        Ok(game_entry) if game_entry.deleted_at.is_some() => {
+            return Ok(Box::new(StatusCode::NOT_FOUND))
+        }
+        Ok(game_entry) => game_entry,
+        Err(Status::NotFound(_)) => return Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    let mut game_entry = match serde_json::to_value(game_entry) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("{e}");
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+    if let Some(fields) = game_entry.as_object_mut() {
+        for field in ["igdb_game", "steam_data", "gog_data", "missing_sources"] {
+            fields.remove(field);
+        }
+    }
+    Ok(public_reply(&game_entry, if_none_match, &PUBLIC_GAME_CACHE))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/collections/{collection_id}",
+    params(("collection_id" = u64, Path, description = "IGDB collection id")),
+    responses(
+        (status = 200, description = "Collection with the digests of its games", body = Object),
+        (status = 304, description = "Collection has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Collection was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_collection(
+    collection_id: u64,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match collections::read(&firestore, collection_id).await {
+        Ok(collection) => Ok(public_reply(
+            &collection,
+            if_none_match,
+            &PUBLIC_COLLECTION_CACHE,
+        )),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/franchises/{franchise_id}",
+    params(("franchise_id" = u64, Path, description = "IGDB franchise id")),
+    responses(
+        (status = 200, description = "Franchise with the digests of its games", body = Object),
+        (status = 304, description = "Franchise has not changed since the ETag in If-None-Match"),
+        (status = 404, description = "Franchise was not found"),
+    )
+)]
+#[instrument(level = "trace", skip(firestore))]
+pub async fn get_public_franchise(
+    franchise_id: u64,
+    if_none_match: Option<String>,
+    firestore: Arc<FirestoreApi>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match franchises::read(&firestore, franchise_id).await {
+        Ok(franchise) => Ok(public_reply(
+            &franchise,
+            if_none_match,
+            &PUBLIC_COLLECTION_CACHE,
+        )),
+        Err(Status::NotFound(_)) => Ok(Box::new(StatusCode::NOT_FOUND)),
+        Err(status) => {
+            warn!("{status}");
+            Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+fn public_reply<T: serde::Serialize>(
+    value: &T,
+    if_none_match: Option<String>,
+    policy: &CachePolicy,
+) -> Box<dyn warp::Reply> {
+    match caching::json_reply(value, if_none_match.as_deref(), policy) {
+        Ok(reply) => Box::new(reply),
+        Err(status) => {
+            warn!("{status}");
+            Box::new(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn public_redirect(location: &str) -> Box<dyn warp::Reply> {
+    match caching::redirect(location, &PUBLIC_DOC_CACHE) {
+        Ok(reply) => Box::new(reply),
+        Err(status) => {
+            warn!("{status}");
+            Box::new(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Time that `/search` waits for IGDB before it returns catalog matches, and
+// that `/search/{token}` waits for the rest.
+const SEARCH_BUDGET: Duration = Duration::from_millis(1500);
+const SEARCH_FOLLOW_UP_BUDGET: Duration = Duration::from_secs(10);
+const LOCAL_SEARCH_LIMIT: u32 = 20;
+
+// Timeline and frontpage are rebuilt a few times per day. Browsers revalidate
+// them often, while a CDN absorbs most reads. Their versioned URLs, keyed on
+// `last_updated`, never change.
+const PUBLIC_DOC_CACHE: CachePolicy = CachePolicy::new(60, 5 * 60);
+const PUBLIC_VERSIONED_DOC_CACHE: CachePolicy = CachePolicy::immutable(365 * 24 * 60 * 60);
+const PUBLIC_GAME_CACHE: CachePolicy = CachePolicy::new(60 * 60, 60 * 60);
+// Collections only change when one of their games is resolved.
+const PUBLIC_COLLECTION_CACHE: CachePolicy = CachePolicy::new(60 * 60, 6 * 60 * 60);
+
+#[utoipa::path(
+    get,
+    path = "/images/{uri}",
+    params(("uri" = String, Path, description = "Image URL to proxy")),
+    responses(
+        (status = 200, description = "Proxied image", content_type = "image/jpeg"),
+        (status = 404, description = "Image was not found"),
+    )
+)]
 #[instrument(level = "trace")]
 pub async fn get_images(uri: String) -> Result<Box<dyn warp::Reply>, Infallible> {
-    let resp = match reqwest::Client::new().get(&uri).send().await {
+    let resp = match http_client::shared().get(&uri).send().await {
         Ok(resp) => resp,
         Err(err) => {
             warn!("{err}");
@@ -332,3 +2708,14 @@ pub async fn get_images(uri: String) -> Result<Box<dyn warp::Reply>, Infallible>
         Err(_) => Ok(Box::new(StatusCode::NOT_FOUND)),
     }
 }
+
+#[instrument(level = "trace", skip(schema, request, viewer))]
+pub async fn post_graphql(
+    (schema, request): (graphql::EspySchema, async_graphql::Request),
+    viewer: Option<String>,
+) -> Result<impl warp::Reply, Infallible> {
+    let request = request.data(graphql::Viewer(viewer));
+    Ok(async_graphql_warp::GraphQLResponse::from(
+        schema.execute(request).await,
+    ))
+}
@@ -1,6 +1,13 @@
+mod auth;
+mod caching;
+mod feed;
+mod graphql;
 mod handlers;
 mod models;
+mod openapi;
+mod pending_search;
 mod query_logs;
 mod resources;
+mod steam_openid;
 
 pub mod routes;
@@ -1,12 +1,33 @@
-use crate::documents;
+use crate::{documents, genres, library};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct Search {
     pub title: String,
 
     #[serde(default)]
     pub base_game_only: bool,
+
+    // Include cancelled or abandoned games that are hidden by default.
+    #[serde(default)]
+    pub include_archived: bool,
+
+    // Only return games with a genre in any of the groups.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub espy_genre_groups: Vec<documents::EspyGenreGroup>,
+
+    // Only return games with any of the player perspectives.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub player_perspectives: Vec<documents::PlayerPerspective>,
+
+    // Only return games that support mods through the Steam Workshop.
+    #[serde(default)]
+    pub moddable_only: bool,
 }
 
 impl std::fmt::Display for Search {
@@ -15,26 +36,155 @@ impl std::fmt::Display for Search {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+/// Partial response of `/search` when IGDB did not respond in time. Its IGDB
+/// results can be collected from `/search/{token}` if it has a token. Complete
+/// responses are plain arrays of candidates.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SearchResult {
+    #[schema(value_type = Vec<Object>)]
+    pub candidates: Vec<documents::GameEntry>,
+
+    pub partial: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// A search result annotated with its presence in the user's library.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SearchCandidate {
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub game_entry: documents::GameEntry,
+
+    pub in_library: bool,
+    pub in_wishlist: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub release_countdown: Option<documents::ReleaseCountdown>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Lookup {
+    pub title: String,
+
+    #[serde(default)]
+    pub year: Option<i32>,
+}
+
+/// Compact game description returned by lookups.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct LookupResult {
+    pub id: u64,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_url: Option<String>,
+
+    pub release_date: i64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub release_countdown: Option<documents::ReleaseCountdown>,
+
+    #[schema(value_type = Object)]
+    pub scores: documents::Scores,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<Object>)]
+    pub store_links: Vec<documents::Website>,
+}
+
+impl From<documents::GameEntry> for LookupResult {
+    fn from(game_entry: documents::GameEntry) -> Self {
+        LookupResult {
+            id: game_entry.id,
+            name: game_entry.name,
+            cover_url: game_entry.cover.map(|cover| {
+                format!(
+                    "https://images.igdb.com/igdb/image/upload/t_cover_big/{}.jpg",
+                    cover.image_id
+                )
+            }),
+            release_date: game_entry.release_date,
+            release_countdown: documents::ReleaseCountdown::new(
+                game_entry.release_date,
+                game_entry.release_precision,
+                Utc::now().timestamp(),
+            ),
+            scores: game_entry.scores,
+            store_links: game_entry
+                .websites
+                .into_iter()
+                .filter(|website| {
+                    matches!(
+                        website.authority,
+                        documents::WebsiteAuthority::Steam
+                            | documents::WebsiteAuthority::Gog
+                            | documents::WebsiteAuthority::Egs
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct Resolve {
+    #[serde(default)]
+    pub game_id: u64,
+
+    /// IGDB slug or igdb.com game URL that is used when `game_id` is not
+    /// provided.
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+/// Game that is deleted from the catalog. Unlike `Resolve`, the game id is
+/// required so that a body without it does not target game 0.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct Delete {
     pub game_id: u64,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct Prefetch {
+    pub user_id: String,
+
+    /// Candidate game ids in the order they are ranked in search results.
+    pub game_ids: Vec<u64>,
+
+    /// Maximum number of candidates to prefetch.
+    #[serde(default)]
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct PrefetchResult {
+    /// Candidate game ids that were not in Firestore and are being resolved.
+    pub scheduled: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct MatchOp {
     /// The storefront entry that is {un}matched.
+    #[schema(value_type = Object)]
     pub store_entry: documents::StoreEntry,
 
     /// A game entry to match the storefront entry with, if one is provided.
     /// Usually, the storefront entry will be matched with the base game of this
     /// entry, unless `exact_match` is set to `true`.
     #[serde(default)]
+    #[schema(value_type = Option<Object>)]
     pub game_entry: Option<documents::GameEntry>,
 
     /// The library entry that the storefront entry will be unmatched from, if
     /// one is provided. The library entry will be also be deleted from the
     /// library if it contains no other storefront entry.
     #[serde(default)]
+    #[schema(value_type = Option<Object>)]
     pub unmatch_entry: Option<documents::LibraryEntry>,
 
     /// If true, deletes the store_entry from the library. Otherwise, it moves
@@ -44,21 +194,451 @@ pub struct MatchOp {
     pub delete_unmatched: bool,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+/// Applies a cleanup suggestion returned by `GET /library/{user_id}/suggestions`.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct SuggestionOp {
+    #[schema(value_type = Object)]
+    pub apply: library::suggestions::Suggestion,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct UpdateOp {
     pub game_id: u64,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct WishlistOp {
     #[serde(default)]
+    #[schema(value_type = Option<Object>)]
     pub add_game: Option<documents::LibraryEntry>,
 
     #[serde(default)]
     pub remove_game: Option<u64>,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct FilterOp {
+    /// Saves a smart filter, replacing any existing filter with the same name.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub save: Option<documents::SmartFilter>,
+
+    /// Deletes the smart filter with the given name.
+    #[serde(default)]
+    pub delete: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct FollowOp {
+    #[schema(value_type = String)]
+    pub kind: documents::FollowKind,
+
+    /// IGDB id of the company, franchise or collection.
+    pub id: u64,
+
+    #[serde(default)]
+    pub unfollow: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct EvaluateFilter {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct WebhookOp {
+    /// Registers an outbound webhook, replacing any existing webhook with the
+    /// same URL.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub register: Option<documents::OutboundWebhook>,
+
+    /// Unregisters the outbound webhook with the given id.
+    #[serde(default)]
+    pub unregister: Option<String>,
+}
+
+/// Request to mint a scoped access token, e.g. for the mobile companion app.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SyncTokenRequest {
+    /// Scopes that the token grants, e.g. "library:read" or "wishlist:write".
+    #[schema(value_type = Vec<String>)]
+    pub scopes: Vec<documents::TokenScope>,
+
+    /// Name of the token, e.g. the device it is minted for.
+    #[serde(default)]
+    pub label: String,
+
+    /// Lifetime of the token in seconds. Defaults to a week.
+    #[serde(default)]
+    pub ttl_secs: Option<i64>,
+}
+
+/// A minted access token. The token itself is only returned once.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SyncTokenResponse {
+    pub token: String,
+
+    #[schema(value_type = Object)]
+    pub info: documents::SyncToken,
+}
+
+/// A user report about bad data on a game.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct GameReport {
+    pub user_id: String,
+    #[schema(value_type = String)]
+    pub reason: documents::ReportReason,
+
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// Curator decision on whether a game is archived. A null `archived` clears
+/// the override and lets heuristics decide.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct Archive {
+    #[serde(default)]
+    pub archived: Option<bool>,
+}
+
+/// Curator fields to pin on a game. Null fields are not pinned, so a request
+/// without any field clears the override.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct GameOverride {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Timestamp (in seconds) of the release date.
+    #[serde(default)]
+    pub release_date: Option<i64>,
+
+    #[serde(default)]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub espy_genres: Option<Vec<documents::EspyGenre>>,
+
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub cover: Option<documents::Image>,
+}
+
+/// Curator request to permanently blocklist a game, e.g. spam or an asset
+/// flip, so that IGDB webhooks do not bring it back.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct Reject {
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Curator request to merge the duplicate IGDB entry `duplicate_id` into
+/// `target_id`.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct Merge {
+    pub duplicate_id: u64,
+    pub target_id: u64,
+}
+
+/// Account deletion step. An empty request starts a deletion and returns a
+/// confirmation token, a request with the `token` confirms it and `cancel`
+/// aborts it during the grace period.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct AccountDelete {
+    #[serde(default)]
+    pub token: Option<String>,
+
+    #[serde(default)]
+    pub cancel: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct AccountDeleteResult {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub token: String,
+
+    pub confirmed: bool,
+
+    /// Timestamp (in seconds) after which the account is deleted.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
 pub struct Unlink {
     pub storefront_id: String,
 }
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SteamLogin {
+    /// Page of the frontend that Steam redirects the user to after signing in.
+    pub return_to: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SteamVerify {
+    /// Query parameters that Steam appended to the `return_to` page.
+    pub params: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SteamLink {
+    pub steam_user_id: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LibraryView {
+    /// One of `release_date` (default), `name`, `added_date` or `score`.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    #[schema(value_type = Option<String>)]
+    pub sort: documents::LibrarySort,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FranchiseQuery {
+    /// Espy user whose library and wishlist set the ownership flags.
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GameQuery {
+    /// Espy user whose library and wishlist set the ownership flags.
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LibraryPageQuery {
+    /// Game id of the last entry of the previous page.
+    #[serde(default)]
+    pub after: Option<u64>,
+
+    /// Maximum number of entries to return, up to 500.
+    #[serde(default = "LibraryPageQuery::default_limit")]
+    pub limit: u32,
+}
+
+impl LibraryPageQuery {
+    fn default_limit() -> u32 {
+        100
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LibraryDiffQuery {
+    /// Start of the period as a UNIX timestamp in seconds.
+    pub from: i64,
+
+    /// End of the period as a UNIX timestamp in seconds. Defaults to now.
+    #[serde(default)]
+    pub to: Option<i64>,
+}
+
+/// A page of library entries ordered by game id.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct LibraryPage {
+    #[schema(value_type = Vec<Object>)]
+    pub entries: Vec<documents::LibraryEntry>,
+
+    /// The `after` of the next page. It is missing on the last page.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PublicDocQuery {
+    /// Include the reasons that each game was picked by the timeline
+    /// filters, for curators that tune them.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CompanyQuery {
+    /// Espy user whose library and wishlist set the ownership flags.
+    #[serde(default)]
+    pub user_id: Option<String>,
+
+    /// Number of developed and published games to skip.
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Maximum number of developed and published games to return, up to 100.
+    #[serde(default = "CompanyQuery::default_limit")]
+    pub limit: usize,
+}
+
+impl CompanyQuery {
+    fn default_limit() -> usize {
+        50
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Stats {
+    /// Only count entries with a genre in this group.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    #[schema(value_type = Option<String>)]
+    pub group: Option<documents::EspyGenreGroup>,
+}
+
+/// Genre breakdown of a user's library.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct LibraryStats {
+    pub total: usize,
+
+    /// Number of entries per genre group. An entry is counted once in each
+    /// group its genres roll up to.
+    #[schema(value_type = Object)]
+    pub genre_groups: BTreeMap<documents::EspyGenreGroup, usize>,
+
+    /// Number of entries per espy genre.
+    pub espy_genres: BTreeMap<String, usize>,
+}
+
+impl LibraryStats {
+    pub fn new(library: &documents::Library, group: Option<documents::EspyGenreGroup>) -> Self {
+        let mut stats = LibraryStats::default();
+        for entry in &library.entries {
+            let groups = genres::genre_groups(&entry.digest.espy_genres);
+            if let Some(group) = &group {
+                if !groups.contains(group) {
+                    continue;
+                }
+            }
+
+            stats.total += 1;
+            for group in groups {
+                *stats.genre_groups.entry(group).or_default() += 1;
+            }
+            for genre in &entry.digest.espy_genres {
+                if let Some(group) = &group {
+                    if genres::genre_group(genre) != *group {
+                        continue;
+                    }
+                }
+                *stats.espy_genres.entry(format!("{genre:?}")).or_default() += 1;
+            }
+        }
+        stats
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Export {
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    #[schema(value_type = Option<String>)]
+    pub sort: documents::LibrarySort,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Import {
+    #[serde(default)]
+    pub format: ImportFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    #[default]
+    Csv,
+    Json,
+    Text,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct ImportResult {
+    /// Number of titles found in the imported file.
+    pub titles: usize,
+
+    /// Number of titles that were not already in the user's storefronts and
+    /// are reconciled.
+    pub new_titles: usize,
+}
+
+/// A flat view of a library entry used for exporting a user's library.
+///
+/// List fields are joined with ';' so that the same rows can be written both
+/// as JSON and CSV.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct ExportEntry {
+    pub id: u64,
+    pub name: String,
+    pub release_date: Option<i64>,
+    pub added_date: Option<u64>,
+    pub storefronts: String,
+    pub store_ids: String,
+    pub store_titles: String,
+    pub user_tags: String,
+    pub espy_score: Option<u64>,
+    pub metacritic: Option<u64>,
+    pub thumbs: Option<u64>,
+    pub popularity: Option<u64>,
+    pub playtime_minutes: Option<u64>,
+    pub achievements_unlocked: Option<u64>,
+    pub achievements_total: Option<u64>,
+    pub developers: String,
+    pub publishers: String,
+    pub collections: String,
+}
+
+impl ExportEntry {
+    pub fn new(entry: documents::LibraryEntry, user_tags: &[&str]) -> Self {
+        let store_field = |field: fn(&documents::StoreEntry) -> &str| {
+            entry
+                .store_entries
+                .iter()
+                .map(field)
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+
+        ExportEntry {
+            id: entry.id,
+            storefronts: store_field(|store_entry| &store_entry.storefront_name),
+            store_ids: store_field(|store_entry| &store_entry.id),
+            store_titles: store_field(|store_entry| &store_entry.title),
+            user_tags: user_tags.join(";"),
+            added_date: entry.added_date,
+            playtime_minutes: entry.playtime_minutes,
+            achievements_unlocked: entry.achievements_unlocked,
+            achievements_total: entry.achievements_total,
+            name: entry.digest.name,
+            release_date: entry.digest.release_date,
+            espy_score: entry.digest.scores.espy_score,
+            metacritic: entry.digest.scores.metacritic,
+            thumbs: entry.digest.scores.thumbs,
+            popularity: entry.digest.scores.popularity,
+            developers: entry.digest.developers.join(";"),
+            publishers: entry.digest.publishers.join(";"),
+            collections: entry.digest.collections.join(";"),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use utoipa::OpenApi;
+
+use super::{handlers, models};
+
+/// OpenAPI description of the espy http server, served at `/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "espy",
+        description = "Game library management and game metadata API."
+    ),
+    paths(
+        handlers::post_search,
+        handlers::get_search_results,
+        handlers::get_lookup,
+        handlers::post_library_search,
+        handlers::post_resolve,
+        handlers::post_prefetch,
+        handlers::post_delete,
+        handlers::post_undelete,
+        handlers::post_report,
+        handlers::get_reports,
+        handlers::post_archive,
+        handlers::get_game_edits,
+        handlers::get_override,
+        handlers::post_override,
+        handlers::post_merge,
+        handlers::get_rejected,
+        handlers::post_rejected,
+        handlers::delete_rejected,
+        handlers::get_prices,
+        handlers::post_match,
+        handlers::post_update,
+        handlers::post_wishlist,
+        handlers::get_filters,
+        handlers::post_filters,
+        handlers::post_evaluate_filter,
+        handlers::get_follows,
+        handlers::post_follows,
+        handlers::get_webhooks,
+        handlers::post_webhooks,
+        handlers::get_sync_tokens,
+        handlers::post_sync_token,
+        handlers::delete_sync_token,
+        handlers::post_unlink,
+        handlers::get_steam_login,
+        handlers::post_steam_verify,
+        handlers::get_library,
+        handlers::get_library_page,
+        handlers::get_library_diff,
+        handlers::get_stats,
+        handlers::get_suggestions,
+        handlers::post_suggestions,
+        handlers::get_feed,
+        handlers::get_game_view,
+        handlers::get_franchise,
+        handlers::get_company,
+        handlers::get_export,
+        handlers::post_import,
+        handlers::post_sync,
+        handlers::get_account_export,
+        handlers::post_account_delete,
+        handlers::get_frontpage_feed,
+        handlers::get_public_timeline,
+        handlers::get_public_timeline_version,
+        handlers::get_public_frontpage,
+        handlers::get_public_frontpage_version,
+        handlers::get_public_game,
+        handlers::get_public_collection,
+        handlers::get_public_franchise,
+        handlers::get_images,
+    ),
+    components(schemas(
+        models::Search,
+        models::SearchResult,
+        models::SearchCandidate,
+        models::Lookup,
+        models::LookupResult,
+        models::Resolve,
+        models::Delete,
+        models::Prefetch,
+        models::PrefetchResult,
+        models::MatchOp,
+        models::UpdateOp,
+        models::WishlistOp,
+        models::FilterOp,
+        models::EvaluateFilter,
+        models::FollowOp,
+        models::WebhookOp,
+        models::SyncTokenRequest,
+        models::SyncTokenResponse,
+        models::GameReport,
+        models::Archive,
+        models::GameOverride,
+        models::Merge,
+        models::Reject,
+        models::Unlink,
+        models::SteamLogin,
+        models::SteamVerify,
+        models::SteamLink,
+        models::AccountDelete,
+        models::AccountDeleteResult,
+        models::LibraryView,
+        models::LibraryPageQuery,
+        models::LibraryPage,
+        models::LibraryDiffQuery,
+        models::Stats,
+        models::LibraryStats,
+        models::SuggestionOp,
+        models::GameQuery,
+        models::FranchiseQuery,
+        models::CompanyQuery,
+        models::PublicDocQuery,
+        models::Export,
+        models::ExportFormat,
+        models::ExportEntry,
+        models::Import,
+        models::ImportFormat,
+        models::ImportResult,
+    ))
+)]
+pub struct ApiDoc;
+
+/// Swagger UI page that renders the spec served at `/openapi.json`.
+pub const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>espy API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
@@ -0,0 +1,80 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{documents::GameEntry, util::random, Status};
+
+pub type SearchTask = JoinHandle<Result<Vec<GameEntry>, Status>>;
+
+/// IGDB searches that did not complete within the latency budget of
+/// `/search`. Clients collect their results with the follow-up token that was
+/// returned with the partial response.
+#[derive(Default)]
+pub struct PendingSearches {
+    searches: Mutex<HashMap<String, (Instant, SearchTask)>>,
+}
+
+impl PendingSearches {
+    /// Keeps the running search `task` and returns the token to collect its
+    /// results with. Searches that were not collected in time are dropped.
+    pub fn insert(&self, task: SearchTask) -> Result<String, Status> {
+        let token = match random::token(TOKEN_BYTES) {
+            Ok(token) => token,
+            Err(status) => {
+                task.abort();
+                return Err(status);
+            }
+        };
+
+        let mut searches = self.searches.lock().unwrap();
+        searches.retain(|_, (inserted, task)| {
+            let expired = inserted.elapsed() > PENDING_TTL;
+            if expired {
+                task.abort();
+            }
+            !expired
+        });
+        searches.insert(token.clone(), (Instant::now(), task));
+
+        Ok(token)
+    }
+
+    /// Removes and returns the search of `token` if it is still pending.
+    pub fn take(&self, token: &str) -> Option<SearchTask> {
+        match self.searches.lock().unwrap().remove(token) {
+            Some((inserted, task)) if inserted.elapsed() <= PENDING_TTL => Some(task),
+            Some((_, task)) => {
+                task.abort();
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+const PENDING_TTL: Duration = Duration::from_secs(60);
+const TOKEN_BYTES: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pending_search_is_taken_once() {
+        let pending = PendingSearches::default();
+        let token = pending.insert(tokio::spawn(async { Ok(vec![]) })).unwrap();
+        assert_ne!(
+            token,
+            pending.insert(tokio::spawn(async { Ok(vec![]) })).unwrap()
+        );
+
+        let task = pending.take(&token).unwrap();
+        assert!(task.await.unwrap().unwrap().is_empty());
+        assert!(pending.take(&token).is_none());
+        assert!(pending.take("unknown").is_none());
+    }
+}
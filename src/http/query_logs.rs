@@ -54,6 +54,56 @@ impl<'a> SearchEvent<'a> {
     }
 }
 
+pub struct LookupEvent<'a> {
+    request: &'a models::Lookup,
+    start: SystemTime,
+}
+
+impl<'a> LookupEvent<'a> {
+    pub fn new(request: &'a models::Lookup) -> Self {
+        Self {
+            request,
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self, game_entry: &GameEntry) {
+        info!(
+            http_request.request_method = "GET",
+            http_request.request_url = "/lookup",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = LOOKUP_HANDLER,
+            request.title = self.request.title,
+            request.year = self.request.year.unwrap_or_default(),
+            response.game_id = game_entry.id,
+            lookup.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "lookup '{}'",
+            self.request.title
+        )
+    }
+
+    pub fn log_error(self, status: Status) {
+        error!(
+            http_request.request_method = "GET",
+            http_request.request_url = "/lookup",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = LOOKUP_HANDLER,
+            labels.status = status.to_string(),
+            request.title = self.request.title,
+            request.year = self.request.year.unwrap_or_default(),
+            lookup.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "lookup '{}'",
+            self.request.title
+        )
+    }
+}
+
 pub struct ResolveEvent<'a> {
     request: &'a models::Resolve,
     start: SystemTime,
@@ -74,6 +124,7 @@ impl<'a> ResolveEvent<'a> {
             labels.log_type = QUERY_LOGS,
             labels.handler = RESOLVE_HANDLER,
             request.game_id = self.request.game_id,
+            request.slug = self.request.slug.as_deref().unwrap_or_default(),
             resolve.title = game_entry.name,
             resolve.latency = SystemTime::now()
                 .duration_since(self.start)
@@ -93,6 +144,7 @@ impl<'a> ResolveEvent<'a> {
             labels.handler = RESOLVE_HANDLER,
             labels.status = status.to_string(),
             request.game_id = self.request.game_id,
+            request.slug = self.request.slug.as_deref().unwrap_or_default(),
             resolve.latency = SystemTime::now()
                 .duration_since(self.start)
                 .unwrap()
@@ -338,6 +390,332 @@ impl<'a> UnlinkEvent<'a> {
     }
 }
 
+pub struct FilterEvent {
+    method: &'static str,
+    url: &'static str,
+    op: &'static str,
+    name: String,
+    start: SystemTime,
+}
+
+impl FilterEvent {
+    pub fn new_list() -> Self {
+        Self::new(
+            "GET",
+            "/library/_/filters",
+            "list_filters",
+            String::default(),
+        )
+    }
+
+    pub fn new_op(request: &models::FilterOp) -> Self {
+        let (op, name) = match (&request.save, &request.delete) {
+            (Some(filter), _) => ("save_filter", filter.name.clone()),
+            (_, Some(name)) => ("delete_filter", name.clone()),
+            _ => ("bad_request", String::default()),
+        };
+        Self::new("POST", "/library/_/filters", op, name)
+    }
+
+    pub fn new_evaluate(request: &models::EvaluateFilter) -> Self {
+        Self::new(
+            "POST",
+            "/library/_/evaluate_filter",
+            "evaluate_filter",
+            request.name.clone(),
+        )
+    }
+
+    fn new(method: &'static str, url: &'static str, op: &'static str, name: String) -> Self {
+        Self {
+            method,
+            url,
+            op,
+            name,
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self, user_id: &str) {
+        info!(
+            http_request.request_method = self.method,
+            http_request.request_url = self.url,
+            labels.log_type = QUERY_LOGS,
+            labels.handler = FILTER_HANDLER,
+            request.op = self.op,
+            request.name = self.name,
+            filter.user_id = user_id,
+            filter.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} '{}'",
+            self.op,
+            self.name,
+        )
+    }
+
+    pub fn log_error(self, user_id: &str, status: Status) {
+        error!(
+            http_request.request_method = self.method,
+            http_request.request_url = self.url,
+            labels.log_type = QUERY_LOGS,
+            labels.handler = FILTER_HANDLER,
+            labels.status = status.to_string(),
+            request.op = self.op,
+            request.name = self.name,
+            filter.user_id = user_id,
+            filter.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} '{}'",
+            self.op,
+            self.name,
+        )
+    }
+}
+
+pub struct WebhookEvent {
+    method: &'static str,
+    op: &'static str,
+    target: String,
+    start: SystemTime,
+}
+
+impl WebhookEvent {
+    pub fn new_list() -> Self {
+        Self::new("GET", "list_webhooks", String::default())
+    }
+
+    pub fn new_op(request: &models::WebhookOp) -> Self {
+        let (op, target) = match (&request.register, &request.unregister) {
+            (Some(webhook), _) => ("register_webhook", webhook.url.clone()),
+            (_, Some(webhook_id)) => ("unregister_webhook", webhook_id.clone()),
+            _ => ("bad_request", String::default()),
+        };
+        Self::new("POST", op, target)
+    }
+
+    fn new(method: &'static str, op: &'static str, target: String) -> Self {
+        Self {
+            method,
+            op,
+            target,
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self, user_id: &str) {
+        info!(
+            http_request.request_method = self.method,
+            http_request.request_url = "/library/_/webhooks",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = WEBHOOK_HANDLER,
+            request.op = self.op,
+            request.target = self.target,
+            webhook.user_id = user_id,
+            webhook.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} '{}'",
+            self.op,
+            self.target,
+        )
+    }
+
+    pub fn log_error(self, user_id: &str, status: Status) {
+        error!(
+            http_request.request_method = self.method,
+            http_request.request_url = "/library/_/webhooks",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = WEBHOOK_HANDLER,
+            labels.status = status.to_string(),
+            request.op = self.op,
+            request.target = self.target,
+            webhook.user_id = user_id,
+            webhook.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} '{}'",
+            self.op,
+            self.target,
+        )
+    }
+}
+
+pub struct ReportEvent {
+    method: &'static str,
+    url: &'static str,
+    op: &'static str,
+    game_id: u64,
+    user_id: String,
+    start: SystemTime,
+}
+
+impl ReportEvent {
+    pub fn new_report(game_id: u64, request: &models::GameReport) -> Self {
+        Self {
+            method: "POST",
+            url: "/games/_/report",
+            op: "report",
+            game_id,
+            user_id: request.user_id.clone(),
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn new_list() -> Self {
+        Self {
+            method: "GET",
+            url: "/admin/reports",
+            op: "list_reports",
+            game_id: 0,
+            user_id: String::default(),
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self) {
+        info!(
+            http_request.request_method = self.method,
+            http_request.request_url = self.url,
+            labels.log_type = QUERY_LOGS,
+            labels.handler = REPORT_HANDLER,
+            request.op = self.op,
+            request.game_id = self.game_id,
+            report.user_id = self.user_id,
+            report.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} {}",
+            self.op,
+            self.game_id,
+        )
+    }
+
+    pub fn log_error(self, status: Status) {
+        error!(
+            http_request.request_method = self.method,
+            http_request.request_url = self.url,
+            labels.log_type = QUERY_LOGS,
+            labels.handler = REPORT_HANDLER,
+            labels.status = status.to_string(),
+            request.op = self.op,
+            request.game_id = self.game_id,
+            report.user_id = self.user_id,
+            report.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "{} {}",
+            self.op,
+            self.game_id,
+        )
+    }
+}
+
+pub struct ExportEvent {
+    format: models::ExportFormat,
+    start: SystemTime,
+}
+
+impl ExportEvent {
+    pub fn new(request: &models::Export) -> Self {
+        Self {
+            format: request.format,
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self, user_id: &str, entries: usize) {
+        info!(
+            http_request.request_method = "GET",
+            http_request.request_url = "/library/_/export",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = EXPORT_HANDLER,
+            request.format = format!("{:?}", self.format),
+            export.user_id = user_id,
+            export.entries = entries,
+            export.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "export {entries} entries"
+        )
+    }
+
+    pub fn log_error(self, user_id: &str, status: Status) {
+        error!(
+            http_request.request_method = "GET",
+            http_request.request_url = "/library/_/export",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = EXPORT_HANDLER,
+            labels.status = status.to_string(),
+            request.format = format!("{:?}", self.format),
+            export.user_id = user_id,
+            export.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "export"
+        )
+    }
+}
+
+pub struct ImportEvent {
+    format: models::ImportFormat,
+    start: SystemTime,
+}
+
+impl ImportEvent {
+    pub fn new(request: &models::Import) -> Self {
+        Self {
+            format: request.format,
+            start: SystemTime::now(),
+        }
+    }
+
+    pub fn log(self, user_id: &str, result: &models::ImportResult) {
+        info!(
+            http_request.request_method = "POST",
+            http_request.request_url = "/library/_/import",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = IMPORT_HANDLER,
+            request.format = format!("{:?}", self.format),
+            import.user_id = user_id,
+            import.titles = result.titles,
+            import.new_titles = result.new_titles,
+            import.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "import {} titles",
+            result.new_titles
+        )
+    }
+
+    pub fn log_error(self, user_id: &str, status: Status) {
+        error!(
+            http_request.request_method = "POST",
+            http_request.request_url = "/library/_/import",
+            labels.log_type = QUERY_LOGS,
+            labels.handler = IMPORT_HANDLER,
+            labels.status = status.to_string(),
+            request.format = format!("{:?}", self.format),
+            import.user_id = user_id,
+            import.latency = SystemTime::now()
+                .duration_since(self.start)
+                .unwrap()
+                .as_millis(),
+            "import"
+        )
+    }
+}
+
 pub struct SyncEvent {
     start: SystemTime,
 }
@@ -383,9 +761,15 @@ impl SyncEvent {
 
 const QUERY_LOGS: &str = "query_logs";
 const SEARCH_HANDLER: &str = "search";
+const LOOKUP_HANDLER: &str = "lookup";
 const RESOLVE_HANDLER: &str = "resolve";
 const UPDATE_HANDLER: &str = "update";
 const MATCH_HANDLER: &str = "match";
 const WISHLIST_HANDLER: &str = "wishlist";
+const FILTER_HANDLER: &str = "filter";
+const REPORT_HANDLER: &str = "report";
+const WEBHOOK_HANDLER: &str = "webhook";
 const UNLINK_HANDLER: &str = "unlink";
 const SYNC_HANDLER: &str = "sync";
+const EXPORT_HANDLER: &str = "export";
+const IMPORT_HANDLER: &str = "import";
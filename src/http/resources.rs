@@ -1,10 +1,12 @@
 use crate::{
-    api::{FirestoreApi, IgdbApi},
+    api::{FirestoreApi, IgdbApi, ItadApi},
     util,
 };
 use std::{convert::Infallible, sync::Arc};
 use warp::{self, Filter};
 
+use super::{auth::Authenticator, pending_search::PendingSearches};
+
 pub fn with_igdb(
     igdb: Arc<IgdbApi>,
 ) -> impl Filter<Extract = (Arc<IgdbApi>,), Error = Infallible> + Clone {
@@ -17,8 +19,26 @@ pub fn with_firestore(
     warp::any().map(move || Arc::clone(&firestore))
 }
 
+pub fn with_itad(
+    itad: Arc<ItadApi>,
+) -> impl Filter<Extract = (Arc<ItadApi>,), Error = Infallible> + Clone {
+    warp::any().map(move || Arc::clone(&itad))
+}
+
 pub fn with_keys(
     keys: Arc<util::keys::Keys>,
 ) -> impl Filter<Extract = (Arc<util::keys::Keys>,), Error = Infallible> + Clone {
     warp::any().map(move || Arc::clone(&keys))
 }
+
+pub fn with_pending_searches(
+    pending_searches: Arc<PendingSearches>,
+) -> impl Filter<Extract = (Arc<PendingSearches>,), Error = Infallible> + Clone {
+    warp::any().map(move || Arc::clone(&pending_searches))
+}
+
+pub fn with_auth(
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (Arc<Authenticator>,), Error = Infallible> + Clone {
+    warp::any().map(move || Arc::clone(&auth))
+}
@@ -1,12 +1,20 @@
 use crate::{
-    api::{FirestoreApi, IgdbApi},
-    util,
+    api::{FirestoreApi, IgdbApi, ItadApi},
+    documents::TokenScope,
+    library::sync_tokens,
+    logging::HttpCounters,
+    util, Status,
 };
 use std::sync::Arc;
 use tracing::warn;
-use warp::{self, Filter};
+use warp::{self, http::StatusCode, reject::Reject, Filter, Rejection};
 
-use super::{handlers, models, resources::*};
+use super::{
+    auth::{self, Authenticator},
+    graphql, handlers, models,
+    pending_search::PendingSearches,
+    resources::*,
+};
 
 /// Returns a Filter with all available routes.
 pub fn routes(
@@ -14,20 +22,126 @@ pub fn routes(
     igdb: Arc<IgdbApi>,
     firestore: Arc<FirestoreApi>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let itad = Arc::new(ItadApi::new(&keys.itad.api_key));
+    let pending_searches = Arc::new(PendingSearches::default());
+    let auth = Arc::new(Authenticator::new(
+        &keys.auth.firebase_project_id,
+        &keys.auth.admin_uids,
+    ));
+
     home()
-        .or(post_search(Arc::clone(&igdb)))
+        .or(get_metrics())
+        .or(get_healthz(Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(get_readyz(Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(get_openapi())
+        .or(get_docs())
+        .or(post_search(
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&pending_searches),
+        ))
+        .or(get_search_results(pending_searches))
+        .or(get_lookup(Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(post_library_search(
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&auth),
+        ))
         .or(post_resolve(Arc::clone(&firestore), Arc::clone(&igdb)))
-        .or(post_delete(Arc::clone(&firestore)))
-        .or(post_match(Arc::clone(&firestore), Arc::clone(&igdb)))
-        .or(post_update(Arc::clone(&firestore)))
-        .or(post_wishlist(Arc::clone(&firestore)))
-        .or(post_unlink(Arc::clone(&firestore)))
-        .or(post_sync(keys, Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(post_prefetch(Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(post_delete(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_undelete(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_report(Arc::clone(&firestore)))
+        .or(get_reports(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_archive(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_game_edits(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_override(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_override(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_merge(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_rejected(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_rejected(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(delete_rejected(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_prices(Arc::clone(&firestore), itad))
+        .or(post_match(
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&auth),
+        ))
+        .or(post_update(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_wishlist(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_filters(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_filters(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_evaluate_filter(
+            Arc::clone(&firestore),
+            Arc::clone(&auth),
+        ))
+        .or(get_follows(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_follows(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_webhooks(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_webhooks(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_sync_tokens(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_sync_token(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(delete_sync_token(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_unlink(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_steam_login(Arc::clone(&keys)))
+        .or(post_steam_verify(
+            Arc::clone(&keys),
+            Arc::clone(&firestore),
+            Arc::clone(&auth),
+        ))
+        .or(get_library(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_library_page(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_library_diff(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_stats(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_suggestions(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_suggestions(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_feed(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(get_game_view(Arc::clone(&firestore)))
+        .or(get_franchise(Arc::clone(&firestore)))
+        .or(get_company(Arc::clone(&firestore)))
+        .or(get_export(Arc::clone(&firestore), Arc::clone(&auth)))
+        .or(post_import(
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&auth),
+        ))
+        .or(post_sync(
+            keys,
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&auth),
+        ))
+        .or(get_account_export(
+            Arc::clone(&firestore),
+            Arc::clone(&auth),
+        ))
+        .or(post_account_delete(
+            Arc::clone(&firestore),
+            Arc::clone(&auth),
+        ))
+        .or(get_frontpage_feed(Arc::clone(&firestore)))
+        .or(get_public_timeline(Arc::clone(&firestore)))
+        .or(get_public_timeline_version(Arc::clone(&firestore)))
+        .or(get_public_frontpage(Arc::clone(&firestore)))
+        .or(get_public_frontpage_version(Arc::clone(&firestore)))
+        .or(get_public_game(Arc::clone(&firestore)))
+        .or(get_public_collection(Arc::clone(&firestore)))
+        .or(get_public_franchise(Arc::clone(&firestore)))
         .or(get_images())
+        .or(post_graphql(Arc::clone(&firestore), Arc::clone(&auth)))
         .or_else(|e| async {
             warn! {"Rejected route: {:?}", e};
             Err(e)
         })
+        .recover(token_rejected)
+        .with(warp::log::custom(|info| {
+            HttpCounters::record(
+                info.method().as_str(),
+                info.path(),
+                info.status().as_u16(),
+                info.elapsed(),
+            )
+        }))
 }
 
 /// GET /
@@ -35,17 +149,109 @@ fn home() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection>
     warp::path!().and(warp::get()).and_then(handlers::welcome)
 }
 
+/// GET /metrics
+fn get_metrics() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .and_then(handlers::get_metrics)
+}
+
+/// GET /healthz
+fn get_healthz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("healthz")
+        .and(warp::get())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::get_healthz)
+}
+
+/// GET /readyz
+fn get_readyz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("readyz")
+        .and(warp::get())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::get_readyz)
+}
+
+/// GET /openapi.json
+fn get_openapi() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("openapi.json")
+        .and(warp::get())
+        .and_then(handlers::get_openapi)
+}
+
+/// GET /docs
+fn get_docs() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("docs")
+        .and(warp::get())
+        .and_then(handlers::get_docs)
+}
+
 /// POST /search
 fn post_search(
+    firestore: Arc<FirestoreApi>,
     igdb: Arc<IgdbApi>,
+    pending_searches: Arc<PendingSearches>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("search")
         .and(warp::post())
         .and(json_body::<models::Search>())
+        .and(with_firestore(firestore))
         .and(with_igdb(igdb))
+        .and(with_pending_searches(pending_searches))
         .and_then(handlers::post_search)
 }
 
+/// GET /search/{token}
+fn get_search_results(
+    pending_searches: Arc<PendingSearches>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("search" / String)
+        .and(warp::get())
+        .and(with_pending_searches(pending_searches))
+        .and_then(handlers::get_search_results)
+}
+
+/// GET /lookup?title={title}&year={year}
+fn get_lookup(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("lookup")
+        .and(warp::get())
+        .and(warp::query::<models::Lookup>())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::get_lookup)
+}
+
+/// POST /library/{user_id}/search
+fn post_library_search(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "search")
+        .and(warp::post())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(json_body::<models::Search>())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::post_library_search)
+}
+
 /// POST /resolve
 fn post_resolve(
     firestore: Arc<FirestoreApi>,
@@ -59,24 +265,189 @@ fn post_resolve(
         .and_then(handlers::post_resolve)
 }
 
+/// POST /resolve/prefetch
+fn post_prefetch(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("resolve" / "prefetch")
+        .and(warp::post())
+        .and(json_body::<models::Prefetch>())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::post_prefetch)
+}
+
 /// POST /delete
 fn post_delete(
     firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("delete")
         .and(warp::post())
-        .and(json_body::<models::Resolve>())
+        .and(admin_only(auth))
+        .and(json_body::<models::Delete>())
         .and(with_firestore(firestore))
         .and_then(handlers::post_delete)
 }
 
+/// POST /admin/games/{game_id}/undelete
+fn post_undelete(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "games" / u64 / "undelete")
+        .and(warp::post())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_undelete)
+}
+
+/// POST /games/{game_id}/report
+fn post_report(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("games" / u64 / "report")
+        .and(warp::post())
+        .and(json_body::<models::GameReport>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_report)
+}
+
+/// GET /admin/reports
+fn get_reports(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "reports")
+        .and(warp::get())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_reports)
+}
+
+/// POST /admin/games/{game_id}/archive
+fn post_archive(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "games" / u64 / "archive")
+        .and(warp::post())
+        .and(with_admin(auth))
+        .and(json_body::<models::Archive>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_archive)
+}
+
+/// GET /admin/games/{game_id}/edits
+fn get_game_edits(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "games" / u64 / "edits")
+        .and(warp::get())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_game_edits)
+}
+
+/// GET /admin/games/{game_id}/override
+fn get_override(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "games" / u64 / "override")
+        .and(warp::get())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_override)
+}
+
+/// POST /admin/games/{game_id}/override
+fn post_override(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "games" / u64 / "override")
+        .and(warp::post())
+        .and(with_admin(auth))
+        .and(json_body::<models::GameOverride>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_override)
+}
+
+/// POST /admin/merge
+fn post_merge(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "merge")
+        .and(warp::post())
+        .and(with_admin(auth))
+        .and(json_body::<models::Merge>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_merge)
+}
+
+/// GET /admin/rejected
+fn get_rejected(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "rejected")
+        .and(warp::get())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_rejected)
+}
+
+/// POST /admin/rejected/{game_id}
+fn post_rejected(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "rejected" / u64)
+        .and(warp::post())
+        .and(with_admin(auth))
+        .and(json_body::<models::Reject>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_rejected)
+}
+
+/// DELETE /admin/rejected/{game_id}
+fn delete_rejected(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "rejected" / u64)
+        .and(warp::delete())
+        .and(admin_only(auth))
+        .and(with_firestore(firestore))
+        .and_then(handlers::delete_rejected)
+}
+
+/// GET /prices/{game_id}
+fn get_prices(
+    firestore: Arc<FirestoreApi>,
+    itad: Arc<ItadApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("prices" / u64)
+        .and(warp::get())
+        .and(with_firestore(firestore))
+        .and(with_itad(itad))
+        .and_then(handlers::get_prices)
+}
+
 /// POST /library/{user_id}/match
 fn post_match(
     firestore: Arc<FirestoreApi>,
     igdb: Arc<IgdbApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("library" / String / "match")
         .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
         .and(json_body::<models::MatchOp>())
         .and(with_firestore(firestore))
         .and(with_igdb(igdb))
@@ -86,9 +457,12 @@ fn post_match(
 /// POST /library/{user_id}/update
 fn post_update(
     firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("library" / String / "update")
         .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
         .and(json_body::<models::UpdateOp>())
         .and(with_firestore(firestore))
         .and_then(handlers::post_update)
@@ -97,39 +471,521 @@ fn post_update(
 /// POST /library/{user_id}/wishlist
 fn post_wishlist(
     firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("library" / String / "wishlist")
         .and(warp::post())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::WishlistWrite,
+        ))
+        .and_then(check_scope)
         .and(json_body::<models::WishlistOp>())
         .and(with_firestore(firestore))
         .and_then(handlers::post_wishlist)
 }
 
+/// GET /library/{user_id}/filters
+fn get_filters(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "filters")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_filters)
+}
+
+/// POST /library/{user_id}/filters
+fn post_filters(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "filters")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::FilterOp>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_filters)
+}
+
+/// GET /library/{user_id}/follows
+fn get_follows(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "follows")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_follows)
+}
+
+/// POST /library/{user_id}/follows
+fn post_follows(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "follows")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::FollowOp>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_follows)
+}
+
+/// POST /library/{user_id}/evaluate_filter
+fn post_evaluate_filter(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "evaluate_filter")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::EvaluateFilter>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_evaluate_filter)
+}
+
+/// GET /library/{user_id}/webhooks
+fn get_webhooks(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "webhooks")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_webhooks)
+}
+
+/// POST /library/{user_id}/webhooks
+fn post_webhooks(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "webhooks")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::WebhookOp>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_webhooks)
+}
+
+/// GET /library/{user_id}/tokens
+fn get_sync_tokens(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "tokens")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_sync_tokens)
+}
+
+/// POST /library/{user_id}/tokens
+fn post_sync_token(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "tokens")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::SyncTokenRequest>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_sync_token)
+}
+
+/// DELETE /library/{user_id}/tokens/{token_id}
+fn delete_sync_token(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "tokens" / String)
+        .and(warp::delete())
+        .and(with_user_auth(auth))
+        .and_then(
+            |user_id: String,
+             token_id: String,
+             authorization: Option<String>,
+             auth: Arc<Authenticator>| async move {
+                let user_id = check_user(user_id, authorization, auth).await?;
+                Ok::<_, Rejection>((user_id, token_id))
+            },
+        )
+        .untuple_one()
+        .and(with_firestore(firestore))
+        .and_then(handlers::delete_sync_token)
+}
+
 /// POST /library/{user_id}/unlink
 fn post_unlink(
     firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("library" / String / "unlink")
         .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
         .and(json_body::<models::Unlink>())
         .and(with_firestore(firestore))
         .and_then(handlers::post_unlink)
 }
 
+/// GET /library/{user_id}/steam/login?return_to={url}
+fn get_steam_login(
+    keys: Arc<util::keys::Keys>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "steam" / "login")
+        .and(warp::get())
+        .and(warp::query::<models::SteamLogin>())
+        .and(with_keys(keys))
+        .and_then(handlers::get_steam_login)
+}
+
+/// POST /library/{user_id}/steam/verify
+fn post_steam_verify(
+    keys: Arc<util::keys::Keys>,
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "steam" / "verify")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::SteamVerify>())
+        .and(with_keys(keys))
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_steam_verify)
+}
+
+/// GET /library/{user_id}?sort={release_date|name|added_date|score}
+fn get_library(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String)
+        .and(warp::get())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(warp::query::<models::LibraryView>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_library)
+}
+
+/// GET /library/{user_id}/entries?after={game_id}&limit={limit}
+fn get_library_page(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "entries")
+        .and(warp::get())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(warp::query::<models::LibraryPageQuery>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_library_page)
+}
+
+/// GET /library/{user_id}/diff?from={timestamp}&to={timestamp}
+fn get_library_diff(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "diff")
+        .and(warp::get())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(warp::query::<models::LibraryDiffQuery>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_library_diff)
+}
+
+/// GET /library/{user_id}/stats?group={group}
+fn get_stats(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "stats")
+        .and(warp::get())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(warp::query::<models::Stats>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_stats)
+}
+
+/// GET /library/{user_id}/suggestions
+fn get_suggestions(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "suggestions")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_suggestions)
+}
+
+/// POST /library/{user_id}/suggestions
+fn post_suggestions(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "suggestions")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::SuggestionOp>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_suggestions)
+}
+
+/// GET /feed/{user_id}
+fn get_feed(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("feed" / String)
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_feed)
+}
+
+/// GET /games/{game_id}/full?user_id={user_id}
+fn get_game_view(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("games" / u64 / "full")
+        .and(warp::get())
+        .and(warp::query::<models::GameQuery>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_game_view)
+}
+
+/// GET /franchise/{franchise_id}?user_id={user_id}
+fn get_franchise(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("franchise" / u64)
+        .and(warp::get())
+        .and(warp::query::<models::FranchiseQuery>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_franchise)
+}
+
+/// GET /company/{company_id}?user_id={user_id}&offset={offset}&limit={limit}
+fn get_company(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("company" / u64)
+        .and(warp::get())
+        .and(warp::query::<models::CompanyQuery>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_company)
+}
+
+/// GET /library/{user_id}/export?format={csv|json}&sort={sort}
+fn get_export(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "export")
+        .and(warp::get())
+        .and(with_scope(
+            Arc::clone(&firestore),
+            auth,
+            TokenScope::LibraryRead,
+        ))
+        .and_then(check_scope)
+        .and(warp::query::<models::Export>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_export)
+}
+
+/// POST /library/{user_id}/import?format={csv|json|text}
+fn post_import(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("library" / String / "import")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(warp::query::<models::Import>())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::bytes())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::post_import)
+}
+
 /// POST /library/{user_id}/sync
 fn post_sync(
     keys: Arc<util::keys::Keys>,
     firestore: Arc<FirestoreApi>,
     igdb: Arc<IgdbApi>,
+    auth: Arc<Authenticator>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("library" / String / "sync")
         .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
         .and(with_keys(keys))
         .and(with_firestore(firestore))
         .and(with_igdb(igdb))
         .and_then(handlers::post_sync)
 }
 
+/// GET /account/{user_id}/export
+fn get_account_export(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account" / String / "export")
+        .and(warp::get())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_account_export)
+}
+
+/// POST /account/{user_id}/delete
+fn post_account_delete(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account" / String / "delete")
+        .and(warp::post())
+        .and(with_user_auth(auth))
+        .and_then(check_user)
+        .and(json_body::<models::AccountDelete>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::post_account_delete)
+}
+
+/// GET /frontpage/rss
+fn get_frontpage_feed(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("frontpage" / "rss")
+        .and(warp::get())
+        .and(warp::header::optional::<String>("host"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_frontpage_feed)
+}
+
+/// GET /v1/timeline?debug={true|false}
+fn get_public_timeline(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "timeline")
+        .and(warp::get())
+        .and(warp::query::<models::PublicDocQuery>())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_timeline)
+}
+
+/// GET /v1/timeline/{version}
+fn get_public_timeline_version(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "timeline" / u64)
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_timeline_version)
+}
+
+/// GET /v1/frontpage?debug={true|false}
+fn get_public_frontpage(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "frontpage")
+        .and(warp::get())
+        .and(warp::query::<models::PublicDocQuery>())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_frontpage)
+}
+
+/// GET /v1/frontpage/{version}
+fn get_public_frontpage_version(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "frontpage" / u64)
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_frontpage_version)
+}
+
+/// GET /v1/games/{game_id}
+fn get_public_game(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "games" / u64)
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_game)
+}
+
+/// GET /v1/collections/{collection_id}
+fn get_public_collection(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "collections" / u64)
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_collection)
+}
+
+/// GET /v1/franchises/{franchise_id}
+fn get_public_franchise(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "franchises" / u64)
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_firestore(firestore))
+        .and_then(handlers::get_public_franchise)
+}
+
 /// GET /images/{resolution}/{image_id}
 fn get_images() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("images" / String)
@@ -137,7 +993,212 @@ fn get_images() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Reje
         .and_then(handlers::get_images)
 }
 
+/// POST /graphql
+fn post_graphql(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("graphql")
+        .and(warp::post())
+        .and(async_graphql_warp::graphql(graphql::schema(firestore)))
+        .and(with_viewer(auth))
+        .and_then(handlers::post_graphql)
+}
+
 fn json_body<T: serde::de::DeserializeOwned + Send>(
 ) -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone {
     warp::body::content_length_limit(16 * 1024).and(warp::body::json())
 }
+
+/// Extracts what `check_scope` needs to authorize a request for `scope`.
+fn with_scope(
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+    scope: TokenScope,
+) -> impl Filter<
+    Extract = (
+        Option<String>,
+        Arc<FirestoreApi>,
+        Arc<Authenticator>,
+        TokenScope,
+    ),
+    Error = Rejection,
+> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(with_firestore(firestore))
+        .and(with_auth(auth))
+        .and(warp::any().map(move || scope))
+}
+
+/// Authorizes requests on the data of `user_id` that carry either the user's
+/// ID token or a scoped access token that grants `scope`, both sent as
+/// `Authorization: Bearer {token}`. Returns `user_id` for the handler.
+async fn check_scope(
+    user_id: String,
+    authorization: Option<String>,
+    firestore: Arc<FirestoreApi>,
+    auth: Arc<Authenticator>,
+    scope: TokenScope,
+) -> Result<String, Rejection> {
+    let token = bearer_token(authorization)?;
+    if auth::is_id_token(&token) {
+        return verify_user(&auth, &token, user_id).await;
+    }
+
+    match sync_tokens::authorize(&firestore, &token, &user_id, scope).await {
+        Ok(()) => Ok(user_id),
+        Err(Status::PermissionDenied(msg)) => {
+            warn!("{msg}");
+            Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN)))
+        }
+        Err(status) => {
+            warn!("Failed to check sync token: {status}");
+            Err(warp::reject::custom(TokenRejected(
+                StatusCode::SERVICE_UNAVAILABLE,
+            )))
+        }
+    }
+}
+
+/// Extracts what `check_user` needs to authenticate a request.
+fn with_user_auth(
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (Option<String>, Arc<Authenticator>), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization").and(with_auth(auth))
+}
+
+/// Authorizes requests on the data of `user_id` that carry the user's own ID
+/// token, for routes that scoped access tokens cannot use, e.g. so that
+/// tokens cannot mint wider ones. Returns `user_id` for the handler.
+async fn check_user(
+    user_id: String,
+    authorization: Option<String>,
+    auth: Arc<Authenticator>,
+) -> Result<String, Rejection> {
+    let token = bearer_token(authorization)?;
+    match auth::is_id_token(&token) {
+        true => verify_user(&auth, &token, user_id).await,
+        false => Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN))),
+    }
+}
+
+/// Returns the token of an `Authorization: Bearer {token}` header. Requests
+/// without one are rejected as unauthenticated.
+fn bearer_token(authorization: Option<String>) -> Result<String, Rejection> {
+    match authorization
+        .as_deref()
+        .and_then(|authorization| authorization.strip_prefix("Bearer "))
+    {
+        Some(token) if !token.trim().is_empty() => Ok(token.trim().to_owned()),
+        _ => Err(warp::reject::custom(TokenRejected(
+            StatusCode::UNAUTHORIZED,
+        ))),
+    }
+}
+
+/// Checks that `id_token` belongs to `user_id`. Returns `user_id`.
+async fn verify_user(
+    auth: &Authenticator,
+    id_token: &str,
+    user_id: String,
+) -> Result<String, Rejection> {
+    let uid = authenticate(auth, id_token).await?;
+    match uid == user_id {
+        true => Ok(user_id),
+        false => {
+            warn!("User '{uid}' tried to access the data of '{user_id}'");
+            Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN)))
+        }
+    }
+}
+
+/// Returns the user id of a valid `id_token`.
+async fn authenticate(auth: &Authenticator, id_token: &str) -> Result<String, Rejection> {
+    match auth.verify(id_token).await {
+        Ok(uid) => Ok(uid),
+        Err(Status::PermissionDenied(msg)) => {
+            warn!("{msg}");
+            Err(warp::reject::custom(TokenRejected(
+                StatusCode::UNAUTHORIZED,
+            )))
+        }
+        Err(status) => {
+            warn!("Failed to verify ID token: {status}");
+            Err(warp::reject::custom(TokenRejected(
+                StatusCode::SERVICE_UNAVAILABLE,
+            )))
+        }
+    }
+}
+
+/// Authenticates requests that may carry the user's ID token. Returns the
+/// user id, or None for anonymous requests.
+fn with_viewer(
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(with_auth(auth))
+        .and_then(check_viewer)
+}
+
+async fn check_viewer(
+    authorization: Option<String>,
+    auth: Arc<Authenticator>,
+) -> Result<Option<String>, Rejection> {
+    if authorization.is_none() {
+        return Ok(None);
+    }
+
+    let token = bearer_token(authorization)?;
+    match auth::is_id_token(&token) {
+        true => authenticate(&auth, &token).await.map(Some),
+        false => Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN))),
+    }
+}
+
+/// Authorizes requests of curators, i.e. users in the admin list that carry
+/// their ID token. Returns the curator's user id for the handler, which is
+/// recorded in edit histories.
+fn with_admin(
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(with_auth(auth))
+        .and_then(check_admin)
+}
+
+/// Like `with_admin` for routes that do not need the curator.
+fn admin_only(auth: Arc<Authenticator>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    with_admin(auth).map(|_: String| ()).untuple_one()
+}
+
+async fn check_admin(
+    authorization: Option<String>,
+    auth: Arc<Authenticator>,
+) -> Result<String, Rejection> {
+    let token = bearer_token(authorization)?;
+    if !auth::is_id_token(&token) {
+        return Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN)));
+    }
+
+    let uid = authenticate(&auth, &token).await?;
+    match auth.is_admin(&uid) {
+        true => Ok(uid),
+        false => {
+            warn!("User '{uid}' tried to access an admin route");
+            Err(warp::reject::custom(TokenRejected(StatusCode::FORBIDDEN)))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenRejected(StatusCode);
+
+impl Reject for TokenRejected {}
+
+async fn token_rejected(rejection: Rejection) -> Result<StatusCode, Rejection> {
+    match rejection.find::<TokenRejected>() {
+        Some(TokenRejected(status)) => Ok(*status),
+        None => Err(rejection),
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use tracing::instrument;
+
+use crate::{util::http_client, Status};
+
+/// Returns the URL of the Steam sign in page for `user_id`. After signing in,
+/// Steam redirects the user to `return_to` with the OpenID assertion in its
+/// query. `return_to` needs to be a page of the frontend at `origin`.
+pub fn login_url(origin: &str, return_to: &str, user_id: &str) -> Result<String, Status> {
+    let mut return_to = frontend_url(origin, return_to)?;
+    return_to.query_pairs_mut().append_pair(USER_PARAM, user_id);
+    let realm = return_to.origin().ascii_serialization();
+
+    let url = Url::parse_with_params(
+        STEAM_OPENID_URL,
+        &[
+            ("openid.ns", OPENID_NS),
+            ("openid.mode", "checkid_setup"),
+            ("openid.return_to", return_to.as_str()),
+            ("openid.realm", &realm),
+            ("openid.identity", IDENTIFIER_SELECT),
+            ("openid.claimed_id", IDENTIFIER_SELECT),
+        ],
+    )
+    .map_err(|e| Status::internal(format!("Invalid Steam OpenID URL: {e}")))?;
+    Ok(url.to_string())
+}
+
+/// Verifies the OpenID assertion `params` that Steam sent to the frontend at
+/// `origin` for a sign in of `user_id` and returns the SteamID64 of the signed
+/// in user.
+///
+/// The assertion is sent back to Steam to check its signature, which also
+/// makes sure that it cannot be replayed.
+#[instrument(level = "trace", skip(params))]
+pub async fn verify(
+    params: &HashMap<String, String>,
+    origin: &str,
+    user_id: &str,
+) -> Result<String, Status> {
+    let param = |name: &str| params.get(name).map(String::as_str).unwrap_or_default();
+
+    if param("openid.mode") != "id_res" {
+        return Err(Status::invalid_argument(
+            "Steam OpenID response is not a positive assertion",
+        ));
+    }
+    if param("openid.op_endpoint") != STEAM_OPENID_URL {
+        return Err(Status::invalid_argument(
+            "Steam OpenID response is not from Steam",
+        ));
+    }
+    // The return_to page is covered by Steam's signature, so it binds the
+    // assertion to the sign in that espy started for `user_id`.
+    if !param("openid.signed")
+        .split(',')
+        .any(|field| field == "return_to")
+    {
+        return Err(Status::invalid_argument(
+            "Steam OpenID response does not sign its return_to page",
+        ));
+    }
+    let return_to = frontend_url(origin, param("openid.return_to"))?;
+    if !return_to
+        .query_pairs()
+        .any(|(name, value)| name == USER_PARAM && value == user_id)
+    {
+        return Err(Status::invalid_argument(
+            "Steam OpenID response was not sent for this user",
+        ));
+    }
+    let steam_id = match claimed_steam_id(param("openid.claimed_id")) {
+        Some(steam_id) => steam_id,
+        None => {
+            return Err(Status::invalid_argument(
+                "Steam OpenID response has no valid SteamID",
+            ))
+        }
+    };
+
+    let mut form = params
+        .iter()
+        .filter(|(name, _)| name.starts_with("openid."))
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect::<HashMap<_, _>>();
+    form.insert("openid.mode", "check_authentication");
+
+    let resp = http_client::shared()
+        .post(STEAM_OPENID_URL)
+        .form(&form)
+        .send()
+        .await?;
+    let text = resp.text().await?;
+
+    match text.lines().any(|line| line.trim() == "is_valid:true") {
+        true => Ok(steam_id),
+        false => Err(Status::invalid_argument(
+            "Steam rejected the OpenID response",
+        )),
+    }
+}
+
+/// Parses `return_to` and checks that it is a page of the frontend at
+/// `origin`.
+fn frontend_url(origin: &str, return_to: &str) -> Result<Url, Status> {
+    if origin.is_empty() {
+        return Err(Status::unavailable("Steam sign in is not configured."));
+    }
+
+    let url = Url::parse(return_to)
+        .map_err(|e| Status::invalid_argument(format!("Invalid return_to URL: {e}")))?;
+    match url.origin().ascii_serialization() == origin.trim_end_matches('/') {
+        true => Ok(url),
+        false => Err(Status::invalid_argument(format!(
+            "return_to URL '{return_to}' is not a page of '{origin}'"
+        ))),
+    }
+}
+
+/// Extracts the SteamID64 from an OpenID `claimed_id` of the form
+/// 'https://steamcommunity.com/openid/id/{steam_id}'.
+fn claimed_steam_id(claimed_id: &str) -> Option<String> {
+    let steam_id = claimed_id
+        .strip_prefix("https://steamcommunity.com/openid/id/")
+        .or_else(|| claimed_id.strip_prefix("http://steamcommunity.com/openid/id/"))?;
+    match !steam_id.is_empty() && steam_id.chars().all(|c| c.is_ascii_digit()) {
+        true => Some(steam_id.to_owned()),
+        false => None,
+    }
+}
+
+const STEAM_OPENID_URL: &str = "https://steamcommunity.com/openid/login";
+const OPENID_NS: &str = "http://specs.openid.net/auth/2.0";
+const IDENTIFIER_SELECT: &str = "http://specs.openid.net/auth/2.0/identifier_select";
+
+// Query parameter of the return_to page with the espy user that signs in.
+const USER_PARAM: &str = "espy_user";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steam_id_from_claimed_id() {
+        assert_eq!(
+            claimed_steam_id("https://steamcommunity.com/openid/id/76561197960435530"),
+            Some("76561197960435530".to_owned())
+        );
+        assert_eq!(
+            claimed_steam_id("https://evil.com/openid/id/76561197960435530"),
+            None
+        );
+        assert_eq!(
+            claimed_steam_id("https://steamcommunity.com/openid/id/7656/../1"),
+            None
+        );
+        assert_eq!(
+            claimed_steam_id("https://steamcommunity.com/openid/id/"),
+            None
+        );
+    }
+
+    #[test]
+    fn login_url_uses_origin_as_realm() {
+        let url = Url::parse(
+            &login_url(
+                "https://espy.example",
+                "https://espy.example/settings?tab=steam",
+                "user",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let query = url.query_pairs().into_owned().collect::<HashMap<_, _>>();
+
+        assert_eq!(query["openid.mode"], "checkid_setup");
+        assert_eq!(
+            query["openid.return_to"],
+            "https://espy.example/settings?tab=steam&espy_user=user"
+        );
+        assert_eq!(query["openid.realm"], "https://espy.example");
+        assert!(login_url("https://espy.example", "javascript:alert(1)", "user").is_err());
+        assert!(login_url("https://espy.example", "https://evil.example/", "user").is_err());
+        assert!(login_url("", "https://espy.example/", "user").is_err());
+    }
+
+    #[tokio::test]
+    async fn assertions_are_bound_to_user() {
+        let params = |return_to: &str| {
+            HashMap::from([
+                ("openid.mode".to_owned(), "id_res".to_owned()),
+                ("openid.op_endpoint".to_owned(), STEAM_OPENID_URL.to_owned()),
+                (
+                    "openid.signed".to_owned(),
+                    "return_to,claimed_id".to_owned(),
+                ),
+                ("openid.return_to".to_owned(), return_to.to_owned()),
+                (
+                    "openid.claimed_id".to_owned(),
+                    "https://steamcommunity.com/openid/id/76561197960435530".to_owned(),
+                ),
+            ])
+        };
+
+        for return_to in [
+            "https://espy.example/settings?espy_user=other",
+            "https://evil.example/settings?espy_user=user",
+            "",
+        ] {
+            assert!(
+                verify(&params(return_to), "https://espy.example", "user")
+                    .await
+                    .is_err(),
+                "{return_to}"
+            );
+        }
+    }
+}
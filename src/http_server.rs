@@ -1,9 +1,16 @@
 use clap::Parser;
 use espy_backend::{
-    api::{FirestoreApi, IgdbApi},
-    http, util, Status, Tracing,
+    api::{FirestoreApi, IgdbApi, ResolveBudgets},
+    http,
+    library::firestore::{access::Service, frontpage, platform_scope, timeline},
+    util::{
+        self,
+        preflight::{self, Dependency},
+    },
+    Status, Tracing,
 };
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Duration};
+use tracing::warn;
 use warp::{self, Filter};
 
 #[derive(Parser)]
@@ -18,23 +25,48 @@ struct Opts {
 
     #[clap(long)]
     prod_tracing: bool,
+
+    /// Start without validating keys and connectivity to dependencies.
+    #[clap(long)]
+    skip_preflight: bool,
+
+    /// Time budget (in seconds) for each external source queried while
+    /// resolving a game. When unset, per-source defaults are used.
+    #[clap(long)]
+    resolve_budget_secs: Option<u64>,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Status> {
     let opts: Opts = Opts::parse();
 
-    match opts.prod_tracing {
+    let _tracing = match opts.prod_tracing {
         false => Tracing::setup("espy-httpserver")?,
         true => Tracing::setup_prod("espy-library")?,
-    }
+    };
 
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
 
+    let firestore = FirestoreApi::connect_as(Service::HttpServer).await?;
+    if !opts.skip_preflight {
+        preflight::run(
+            &opts.key_store,
+            &keys,
+            &firestore,
+            &[Dependency::Igdb, Dependency::Steam, Dependency::Auth],
+        )
+        .await?;
+    }
+
     let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    igdb.set_platform_scope(platform_scope::read(&firestore).await?);
+    if let Some(secs) = opts.resolve_budget_secs {
+        igdb.set_resolve_budgets(ResolveBudgets::uniform(Duration::from_secs(secs)));
+    }
     igdb.connect().await?;
 
-    let firestore = FirestoreApi::connect().await?;
+    let firestore = Arc::new(firestore);
+    tokio::spawn(keep_warm(Arc::clone(&firestore)));
 
     // Let ENV VAR override flag.
     let port: u16 = match env::var("PORT") {
@@ -46,10 +78,11 @@ async fn main() -> Result<(), Status> {
     };
 
     warp::serve(
-        http::routes::routes(Arc::new(keys), Arc::new(igdb), Arc::new(firestore)).with(
+        http::routes::routes(Arc::new(keys), Arc::new(igdb), firestore).with(
             warp::cors()
-                .allow_methods(vec!["GET", "POST"])
-                .allow_headers(vec!["Content-Type", "Authorization"])
+                .allow_methods(vec!["GET", "POST", "DELETE"])
+                .allow_headers(vec!["Content-Type", "Authorization", "If-None-Match"])
+                .expose_headers(vec!["ETag"])
                 .allow_any_origin()
                 .allow_credentials(true),
         ),
@@ -59,3 +92,21 @@ async fn main() -> Result<(), Status> {
 
     Ok(())
 }
+
+/// Refreshes the frontpage and timeline docs in the connection cache, so that
+/// their reads are served from memory. They are rebuilt by batch jobs a few
+/// times per day and changes are picked up within `HOT_DOCS_REFRESH`.
+async fn keep_warm(firestore: Arc<FirestoreApi>) {
+    let mut interval = tokio::time::interval(HOT_DOCS_REFRESH);
+    loop {
+        interval.tick().await;
+        if let Err(status) = frontpage::refresh(&firestore).await {
+            warn!("Failed to refresh frontpage: {status}");
+        }
+        if let Err(status) = timeline::refresh(&firestore).await {
+            warn!("Failed to refresh timeline: {status}");
+        }
+    }
+}
+
+const HOT_DOCS_REFRESH: Duration = Duration::from_secs(60);
@@ -6,12 +6,13 @@ pub mod genres;
 pub mod http;
 pub mod library;
 pub mod logging;
+pub mod notifications;
 pub mod traits;
 pub mod util;
 pub mod webhooks;
 
 mod status;
-pub use status::Status;
+pub use status::{Status, StatusKind};
 
 mod tracing;
 pub use crate::tracing::Tracing;
@@ -0,0 +1,212 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{
+        AccountDeletion, DealAlerts, Follows, Library, Notifications, OutboundWebhook,
+        SmartFilters, Storefront, UnresolvedEntries, UserAnnotations, UserData, UserFeed,
+    },
+    util::random,
+    Status,
+};
+
+use super::{
+    firestore::{
+        account_deletions, deal_alerts, filters, follows, library, library_events, notifications,
+        outbound_webhooks, storefront, unresolved, user_annotations, user_data, user_feed,
+        wishlist,
+    },
+    sync_tokens,
+};
+
+/// Archive with all documents that espy keeps for a user.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct AccountExport {
+    pub user_id: String,
+    pub exported_at: i64,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_data: Option<UserData>,
+
+    pub library: Library,
+    pub wishlist: Library,
+    pub unresolved: UnresolvedEntries,
+    pub storefront: Storefront,
+    pub annotations: UserAnnotations,
+    pub filters: SmartFilters,
+    pub deals: DealAlerts,
+    pub notifications: Notifications,
+    pub follows: Follows,
+    pub feed: UserFeed,
+    pub webhooks: Vec<OutboundWebhook>,
+}
+
+/// Collects all documents owned by `user_id` in a single archive.
+///
+/// Storefront OAuth tokens are left out, as they are credentials issued to
+/// espy and not data provided by the user.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn export(firestore: &FirestoreApi, user_id: &str) -> Result<AccountExport, Status> {
+    let user_data = match user_data::read(firestore, user_id).await {
+        Ok(mut user_data) => {
+            if let Some(keys) = &mut user_data.keys {
+                keys.gog_token = None;
+            }
+            Some(user_data)
+        }
+        Err(Status::NotFound(_)) => None,
+        Err(status) => return Err(status),
+    };
+
+    // Signing secrets are only handed out when webhooks are registered.
+    let mut webhooks = outbound_webhooks::list_by_owner(firestore, user_id).await?;
+    for webhook in &mut webhooks {
+        webhook.secret.clear();
+    }
+
+    Ok(AccountExport {
+        user_id: user_id.to_owned(),
+        exported_at: Utc::now().timestamp(),
+        user_data,
+        library: library::read(firestore, user_id).await?,
+        wishlist: wishlist::read(firestore, user_id).await?,
+        unresolved: unresolved::read(firestore, user_id).await?,
+        storefront: storefront::read(firestore, user_id).await?,
+        annotations: user_annotations::read(firestore, user_id).await?,
+        filters: filters::read(firestore, user_id).await?,
+        deals: deal_alerts::read(firestore, user_id).await?,
+        notifications: notifications::read(firestore, user_id).await?,
+        follows: follows::read(firestore, user_id).await?,
+        feed: user_feed::read(firestore, user_id).await?,
+        webhooks,
+    })
+}
+
+/// Starts the deletion of an account. The returned request carries the token
+/// that needs to be passed to `confirm_deletion()`. Requesting again issues a
+/// new token and resets any previous confirmation.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn request_deletion(
+    firestore: &FirestoreApi,
+    user_id: &str,
+) -> Result<AccountDeletion, Status> {
+    let now = Utc::now().timestamp();
+    let deletion = AccountDeletion {
+        user_id: user_id.to_owned(),
+        token: random::token(TOKEN_BYTES)?,
+        requested_at: now,
+        ..Default::default()
+    };
+    account_deletions::write(firestore, &deletion).await?;
+    Ok(deletion)
+}
+
+/// Confirms a pending deletion request with its `token`. The account is
+/// deleted once `DELETION_GRACE_PERIOD_SECS` have passed.
+#[instrument(level = "trace", skip(firestore, token))]
+pub async fn confirm_deletion(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    token: &str,
+) -> Result<AccountDeletion, Status> {
+    let mut deletion = account_deletions::read(firestore, user_id).await?;
+
+    let now = Utc::now().timestamp();
+    if deletion.token != token {
+        return Err(Status::permission_denied("Invalid confirmation token."));
+    }
+    if now - deletion.requested_at > TOKEN_TTL_SECS {
+        return Err(Status::permission_denied("Confirmation token has expired."));
+    }
+
+    if !deletion.confirmed {
+        deletion.confirmed = true;
+        deletion.scheduled_at = now + DELETION_GRACE_PERIOD_SECS;
+        account_deletions::write(firestore, &deletion).await?;
+    }
+    Ok(deletion)
+}
+
+/// Cancels a pending or confirmed deletion that has not been carried out yet.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn cancel_deletion(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    account_deletions::read(firestore, user_id).await?;
+    account_deletions::delete(firestore, user_id).await
+}
+
+/// Deletes all documents owned by `user_id`.
+///
+/// Failures on individual documents do not stop the deletion of the rest, but
+/// are reported in the result so that the purge can be retried.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let mut failures = vec![];
+
+    for (doc, result) in [
+        ("library", library::purge(firestore, user_id).await),
+        (
+            "library_events",
+            library_events::purge(firestore, user_id).await,
+        ),
+        ("wishlist", wishlist::purge(firestore, user_id).await),
+        ("unresolved", unresolved::purge(firestore, user_id).await),
+        ("storefront", storefront::purge(firestore, user_id).await),
+        (
+            "annotations",
+            user_annotations::purge(firestore, user_id).await,
+        ),
+        ("filters", filters::purge(firestore, user_id).await),
+        ("deals", deal_alerts::purge(firestore, user_id).await),
+        (
+            "notifications",
+            notifications::purge(firestore, user_id).await,
+        ),
+        ("follows", follows::purge(firestore, user_id).await),
+        ("feed", user_feed::purge(firestore, user_id).await),
+        ("sync_tokens", sync_tokens::purge(firestore, user_id).await),
+    ] {
+        if let Err(status) = result {
+            warn!("Failed to delete {doc} of user '{user_id}': {status}");
+            failures.push(doc);
+        }
+    }
+
+    match outbound_webhooks::list_by_owner(firestore, user_id).await {
+        Ok(webhooks) => {
+            for webhook in webhooks {
+                if let Err(status) = outbound_webhooks::delete(firestore, &webhook.id).await {
+                    warn!("Failed to delete webhook '{}': {status}", webhook.id);
+                    failures.push("webhooks");
+                }
+            }
+        }
+        Err(status) => {
+            warn!("Failed to list webhooks of user '{user_id}': {status}");
+            failures.push("webhooks");
+        }
+    }
+
+    // The user doc goes last, as it holds the storefront credentials that
+    // would otherwise allow the library to be synced back.
+    if failures.is_empty() {
+        user_data::delete(firestore, user_id).await?;
+        account_deletions::delete(firestore, user_id).await?;
+        Ok(())
+    } else {
+        Err(Status::internal(format!(
+            "Failed to delete {} of user '{user_id}'",
+            failures.join(", ")
+        )))
+    }
+}
+
+// Time after a confirmed deletion request during which it can be cancelled.
+const DELETION_GRACE_PERIOD_SECS: i64 = 7 * 24 * 60 * 60;
+
+// Time during which a deletion request can be confirmed.
+const TOKEN_TTL_SECS: i64 = 24 * 60 * 60;
+
+const TOKEN_BYTES: usize = 32;
@@ -0,0 +1,119 @@
+use tracing::error;
+
+use crate::{api::FirestoreApi, Status};
+
+/// Service identity that owns a Firestore connection.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Service {
+    HttpServer,
+    WebhookHandlers,
+
+    /// Batch jobs and command line utils that maintain the whole catalog.
+    Offline,
+
+    /// Local simulations that read the catalog but must not persist anything.
+    DryRun,
+}
+
+/// Returns true if `firestore` is connected for a dry run. Side effects that
+/// are not guarded by `check_write`, e.g. user notifications, are skipped.
+pub fn is_dry_run(firestore: &FirestoreApi) -> bool {
+    firestore.service() == Service::DryRun
+}
+
+/// Checks that the service connected to `firestore` is allowed to write into
+/// the global `collection`. Violations are logged and returned as
+/// `Status::PermissionDenied`.
+///
+/// User documents are guarded separately by the user id they are keyed on.
+pub fn check_write(firestore: &FirestoreApi, collection: &str) -> Result<(), Status> {
+    let service = firestore.service();
+    if can_write(service, collection) {
+        return Ok(());
+    }
+
+    let status = Status::permission_denied(format!(
+        "{service:?} is not allowed to write in Firestore '{collection}'"
+    ));
+    error!("{status}");
+    Err(status)
+}
+
+fn can_write(service: Service, collection: &str) -> bool {
+    match service {
+        Service::Offline => true,
+        Service::HttpServer => HTTP_SERVER_COLLECTIONS.contains(&collection),
+        Service::WebhookHandlers => WEBHOOK_HANDLERS_COLLECTIONS.contains(&collection),
+        Service::DryRun => false,
+    }
+}
+
+// The http server resolves games on user request, which also updates their
+// digests in companies and collections. Curator merges rewrite the store
+// mappings in external_games and curator overrides pin fields of games.
+const HTTP_SERVER_COLLECTIONS: &[&str] = &[
+    "account_deletions",
+    "game_edits",
+    "games",
+    "backfill_queue",
+    "companies",
+    "collections",
+    "external_games",
+    "franchises",
+    "needs_annotation",
+    "outbound_webhooks",
+    "overrides",
+    "rejected",
+    "reports",
+    "sync_tokens",
+    "tech_info",
+    "webhook_deliveries",
+];
+
+const WEBHOOK_HANDLERS_COLLECTIONS: &[&str] = &[
+    "games",
+    "backfill_queue",
+    "companies",
+    "collections",
+    "franchises",
+    "external_games",
+    "igdb_snapshots",
+    "keywords",
+    "needs_annotation",
+    "resolve_queue",
+    "retry_queue",
+    "tech_info",
+    "webhook_deliveries",
+    "wikipedia",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_writes_everywhere() {
+        assert!(can_write(Service::Offline, "espy"));
+        assert!(can_write(Service::Offline, "genres"));
+    }
+
+    #[test]
+    fn http_server_cannot_write_curated_collections() {
+        assert!(can_write(Service::HttpServer, "games"));
+        assert!(!can_write(Service::HttpServer, "espy"));
+        assert!(!can_write(Service::HttpServer, "keywords"));
+    }
+
+    #[test]
+    fn webhooks_cannot_write_curated_collections() {
+        assert!(can_write(Service::WebhookHandlers, "keywords"));
+        assert!(!can_write(Service::WebhookHandlers, "espy"));
+        assert!(!can_write(Service::WebhookHandlers, "scores"));
+    }
+
+    #[test]
+    fn dry_run_writes_nowhere() {
+        assert!(!can_write(Service::DryRun, "games"));
+        assert!(!can_write(Service::DryRun, "companies"));
+    }
+}
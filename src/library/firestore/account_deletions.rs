@@ -0,0 +1,82 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::AccountDeletion, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "account_deletions::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<AccountDeletion, Status> {
+    utils::read(firestore, ACCOUNT_DELETIONS, user_id.to_string()).await
+}
+
+/// Returns all confirmed deletions whose grace period is over at `now`.
+#[instrument(name = "account_deletions::list_due", level = "trace", skip(firestore))]
+pub async fn list_due(firestore: &FirestoreApi, now: i64) -> Result<Vec<AccountDeletion>, Status> {
+    utils::run("account_deletions::list_due", || async move {
+        let deletions: BoxStream<FirestoreResult<AccountDeletion>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(ACCOUNT_DELETIONS).as_str())
+            .filter(|q| {
+                q.for_all([
+                    q.field(path!(AccountDeletion::confirmed)).equal(true),
+                    q.field(path!(AccountDeletion::scheduled_at))
+                        .less_than_or_equal(now),
+                ])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        deletions.try_collect::<Vec<AccountDeletion>>().await
+    })
+    .await
+}
+
+#[instrument(
+    name = "account_deletions::write",
+    level = "trace",
+    skip(firestore, deletion),
+    fields(
+        user_id = %deletion.user_id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, deletion: &AccountDeletion) -> Result<(), Status> {
+    access::check_write(firestore, ACCOUNT_DELETIONS)?;
+
+    utils::run("account_deletions::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(ACCOUNT_DELETIONS))
+            .document_id(&deletion.user_id)
+            .object(deletion)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "account_deletions::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    access::check_write(firestore, ACCOUNT_DELETIONS)?;
+
+    utils::run("account_deletions::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(ACCOUNT_DELETIONS).as_str())
+            .document_id(user_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const ACCOUNT_DELETIONS: &str = "account_deletions";
@@ -0,0 +1,109 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{
+    api::FirestoreApi,
+    documents::{BackfillEntry, GameEntry},
+    Status,
+};
+
+use super::{access, utils};
+
+#[instrument(name = "backfill_queue::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<BackfillEntry, Status> {
+    utils::read(firestore, BACKFILL_QUEUE, doc_id.to_string()).await
+}
+
+/// Returns all entries whose next attempt is due at `now`.
+#[instrument(name = "backfill_queue::list_due", level = "trace", skip(firestore))]
+pub async fn list_due(firestore: &FirestoreApi, now: i64) -> Result<Vec<BackfillEntry>, Status> {
+    utils::run("backfill_queue::list_due", || async move {
+        let entries: BoxStream<FirestoreResult<BackfillEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(BACKFILL_QUEUE).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(BackfillEntry::next_attempt))
+                    .less_than_or_equal(now)])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        entries.try_collect::<Vec<BackfillEntry>>().await
+    })
+    .await
+}
+
+/// Adds `game_entry` in the backfill queue if it is missing any external
+/// sources. The entry is due for backfilling immediately.
+#[instrument(
+    name = "backfill_queue::add",
+    level = "trace",
+    skip(firestore, game_entry),
+    fields(
+        game_id = %game_entry.id,
+    )
+)]
+pub async fn add(firestore: &FirestoreApi, game_entry: &GameEntry) -> Result<(), Status> {
+    if game_entry.missing_sources.is_empty() {
+        return Ok(());
+    }
+
+    let entry = BackfillEntry {
+        id: game_entry.id,
+        name: game_entry.name.clone(),
+        missing_sources: game_entry.missing_sources.clone(),
+        attempts: 0,
+        next_attempt: 0,
+    };
+    write(firestore, &entry).await
+}
+
+#[instrument(
+    name = "backfill_queue::write",
+    level = "trace",
+    skip(firestore, entry),
+    fields(
+        game_id = %entry.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, entry: &BackfillEntry) -> Result<(), Status> {
+    access::check_write(firestore, BACKFILL_QUEUE)?;
+
+    utils::run("backfill_queue::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(BACKFILL_QUEUE))
+            .document_id(entry.id.to_string())
+            .object(entry)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "backfill_queue::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
+    access::check_write(firestore, BACKFILL_QUEUE)?;
+
+    utils::run("backfill_queue::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(BACKFILL_QUEUE).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const BACKFILL_QUEUE: &str = "backfill_queue";
@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::documents::{Company, Frontpage, Genre, Keyword, Notable, Timeline};
+
+/// In-process read-through cache for Firestore documents that are read
+/// repeatedly while resolving games but rarely change.
+///
+/// Entries expire after a TTL, so changes made by other services become
+/// visible eventually. Writes made through this connection update the cache
+/// immediately.
+#[derive(Debug)]
+pub struct FirestoreCache {
+    pub(super) keywords: DocCache<Keyword>,
+    pub(super) genres: DocCache<Genre>,
+    pub(super) companies: DocCache<Company>,
+    pub(super) notable: DocCache<Notable>,
+    pub(super) rejected: DocCache<HashSet<u64>>,
+
+    // Hot docs that are served on every frontpage and timeline read. They are
+    // refreshed in the background by services that serve them, and expire
+    // quickly in case the refresh stops.
+    pub(super) frontpage: DocCache<Frontpage>,
+    pub(super) timeline: DocCache<Timeline>,
+}
+
+impl FirestoreCache {
+    pub fn new(ttl: Duration) -> Self {
+        FirestoreCache {
+            keywords: DocCache::new(ttl),
+            genres: DocCache::new(ttl),
+            companies: DocCache::new(ttl),
+            notable: DocCache::new(ttl),
+            rejected: DocCache::new(ttl),
+            frontpage: DocCache::new(HOT_DOC_TTL),
+            timeline: DocCache::new(HOT_DOC_TTL),
+        }
+    }
+
+    /// Drops all cached documents.
+    pub fn clear(&self) {
+        self.keywords.clear();
+        self.genres.clear();
+        self.companies.clear();
+        self.notable.clear();
+        self.rejected.clear();
+        self.frontpage.clear();
+        self.timeline.clear();
+    }
+}
+
+impl Default for FirestoreCache {
+    fn default() -> Self {
+        FirestoreCache::new(DEFAULT_TTL)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct DocCache<Document> {
+    ttl: Duration,
+    entries: Mutex<HashMap<u64, (Instant, Document)>>,
+}
+
+impl<Document: Clone> DocCache<Document> {
+    fn new(ttl: Duration) -> Self {
+        DocCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached document with `id` if it has not expired.
+    pub(super) fn get(&self, id: u64) -> Option<Document> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&id) {
+            Some((inserted, doc)) if inserted.elapsed() < self.ttl => Some(doc.clone()),
+            Some(_) => {
+                entries.remove(&id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(super) fn insert(&self, id: u64, doc: Document) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(id, (Instant::now(), doc));
+    }
+
+    pub(super) fn invalidate(&self, id: u64) {
+        self.entries.lock().unwrap().remove(&id);
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+const HOT_DOC_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_returns_inserted_document() {
+        let cache = DocCache::new(Duration::from_secs(60));
+        cache.insert(7, "seven".to_owned());
+
+        assert_eq!(cache.get(7), Some("seven".to_owned()));
+        assert_eq!(cache.get(8), None);
+    }
+
+    #[test]
+    fn get_drops_expired_document() {
+        let cache = DocCache::new(Duration::ZERO);
+        cache.insert(7, "seven".to_owned());
+
+        assert_eq!(cache.get(7), None);
+        assert!(cache.entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalidate_removes_document() {
+        let cache = DocCache::new(Duration::from_secs(60));
+        cache.insert(7, "seven".to_owned());
+        cache.invalidate(7);
+
+        assert_eq!(cache.get(7), None);
+    }
+}
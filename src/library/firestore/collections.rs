@@ -1,7 +1,7 @@
 use crate::{api::FirestoreApi, documents::Collection, Status};
 use tracing::instrument;
 
-use super::{utils, BatchReadResult};
+use super::{access, utils, BatchReadResult};
 
 #[instrument(name = "collections::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Collection, Status> {
@@ -33,29 +33,58 @@ pub async fn batch_read(
     )
 )]
 pub async fn write(firestore: &FirestoreApi, collection: &Collection) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(COLLECTIONS)
-        .document_id(collection.id.to_string())
-        .object(collection)
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, COLLECTIONS)?;
+
+    utils::run("collections::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(COLLECTIONS))
+            .document_id(collection.id.to_string())
+            .object(collection)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Writes `collections` with batched writes instead of a write per doc.
+#[instrument(
+    name = "collections::batch_write",
+    level = "trace",
+    skip(firestore, collections)
+)]
+pub async fn batch_write(
+    firestore: &FirestoreApi,
+    collections: &[Collection],
+) -> Result<(), Status> {
+    access::check_write(firestore, COLLECTIONS)?;
+
+    let docs = collections
+        .iter()
+        .map(|doc| (doc.id.to_string(), doc))
+        .collect::<Vec<_>>();
+    utils::batch_write(firestore, COLLECTIONS, &docs).await
 }
 
 #[instrument(name = "collections::delete", level = "trace", skip(firestore))]
 pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .delete()
-        .from(COLLECTIONS)
-        .document_id(doc_id.to_string())
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, COLLECTIONS)?;
+
+    utils::run("collections::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(COLLECTIONS).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const COLLECTIONS: &str = "collections";
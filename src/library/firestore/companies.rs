@@ -3,18 +3,21 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Company, Status};
 
-use super::utils;
+use super::{access, utils, BatchReadResult};
 
 #[instrument(name = "companies::list", level = "trace", skip(firestore))]
 pub async fn list(firestore: &FirestoreApi) -> Result<Vec<Company>, Status> {
-    let doc_stream: BoxStream<Company> = firestore
-        .db()
-        .fluent()
-        .list()
-        .from(COMPANIES)
-        .obj()
-        .stream_all()
-        .await?;
+    let doc_stream: BoxStream<Company> = utils::run("companies::list", || async move {
+        firestore
+            .db()
+            .fluent()
+            .list()
+            .from(firestore.collection(COMPANIES).as_str())
+            .obj()
+            .stream_all()
+            .await
+    })
+    .await?;
 
     Ok(doc_stream.collect().await)
 }
@@ -24,6 +27,35 @@ pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Company, Stat
     utils::read(firestore, COMPANIES, doc_id.to_string()).await
 }
 
+/// Batch reads companies by id.
+#[instrument(
+    name = "companies::batch_read",
+    level = "trace",
+    skip(firestore, doc_ids)
+)]
+pub async fn batch_read(
+    firestore: &FirestoreApi,
+    doc_ids: &[u64],
+) -> Result<BatchReadResult<Company>, Status> {
+    utils::batch_read(firestore, COMPANIES, doc_ids).await
+}
+
+/// Returns the company doc from the connection cache when available.
+///
+/// Suitable for looking up company names during resolve. Use `read()` when
+/// the company doc is going to be modified and written back, as the cached
+/// doc may miss recent updates by other services.
+#[instrument(name = "companies::lookup", level = "trace", skip(firestore))]
+pub async fn lookup(firestore: &FirestoreApi, doc_id: u64) -> Result<Company, Status> {
+    if let Some(company) = firestore.cache().companies.get(doc_id) {
+        return Ok(company);
+    }
+
+    let company = read(firestore, doc_id).await?;
+    firestore.cache().companies.insert(doc_id, company.clone());
+    Ok(company)
+}
+
 #[instrument(
     name = "companies::write",
     level = "trace",
@@ -33,28 +65,69 @@ pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Company, Stat
     )
 )]
 pub async fn write(firestore: &FirestoreApi, company: &Company) -> Result<(), Status> {
+    access::check_write(firestore, COMPANIES)?;
+
+    utils::run("companies::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(COMPANIES))
+            .document_id(company.id.to_string())
+            .object(company)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
     firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(COMPANIES)
-        .document_id(company.id.to_string())
-        .object(company)
-        .execute()
-        .await?;
+        .cache()
+        .companies
+        .insert(company.id, company.clone());
+    Ok(())
+}
+
+/// Writes `companies` with batched writes instead of a write per company.
+#[instrument(
+    name = "companies::batch_write",
+    level = "trace",
+    skip(firestore, companies)
+)]
+pub async fn batch_write(firestore: &FirestoreApi, companies: &[Company]) -> Result<(), Status> {
+    access::check_write(firestore, COMPANIES)?;
+
+    let docs = companies
+        .iter()
+        .map(|company| (company.id.to_string(), company))
+        .collect::<Vec<_>>();
+    utils::batch_write(firestore, COMPANIES, &docs).await?;
+
+    for company in companies {
+        firestore
+            .cache()
+            .companies
+            .insert(company.id, company.clone());
+    }
     Ok(())
 }
 
 #[instrument(name = "companies::delete", level = "trace", skip(firestore))]
 pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .delete()
-        .from(COMPANIES)
-        .document_id(doc_id.to_string())
-        .execute()
-        .await?;
+    access::check_write(firestore, COMPANIES)?;
+
+    utils::run("companies::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(COMPANIES).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+    firestore.cache().companies.invalidate(doc_id);
     Ok(())
 }
 
@@ -0,0 +1,49 @@
+use crate::{api::FirestoreApi, documents::DealAlerts, Status};
+use tracing::instrument;
+
+use super::utils;
+
+#[instrument(name = "deal_alerts::read", level = "trace", skip(firestore, user_id))]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<DealAlerts, Status> {
+    utils::users_read(firestore, user_id, USER_DATA, DEALS_DOC).await
+}
+
+/// Deletes the user's deal alerts document.
+#[instrument(name = "deal_alerts::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, USER_DATA, DEALS_DOC).await
+}
+
+#[instrument(
+    name = "deal_alerts::write",
+    level = "trace",
+    skip(firestore, user_id, alerts)
+)]
+pub async fn write(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    alerts: &DealAlerts,
+) -> Result<(), Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("deal_alerts::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(USER_DATA)
+            .document_id(DEALS_DOC)
+            .parent(parent_path)
+            .object(alerts)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const USER_DATA: &str = "user_data";
+const DEALS_DOC: &str = "deals";
@@ -9,7 +9,7 @@ use firestore::{path, FirestoreResult};
 use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use tracing::{instrument, warn};
 
-use super::utils;
+use super::{access, utils};
 
 #[instrument(name = "external_games::read", level = "trace", skip(firestore))]
 pub async fn read(
@@ -37,13 +37,19 @@ pub async fn batch_read(
             .map(|e| (format!("{}_{}", &e.storefront_name, &e.id), e)),
     );
 
-    let mut docs: BoxStream<FirestoreResult<(String, Option<ExternalGame>)>> = firestore
-        .db()
-        .fluent()
-        .select()
-        .by_id_in(EXTERNAL_GAMES)
-        .obj()
-        .batch_with_errors(store_entries.keys())
+    let doc_ids = store_entries.keys().cloned().collect::<Vec<_>>();
+    let doc_ids = &doc_ids;
+    let mut docs: BoxStream<FirestoreResult<(String, Option<ExternalGame>)>> =
+        utils::run("external_games::batch_read", || async move {
+            firestore
+                .db()
+                .fluent()
+                .select()
+                .by_id_in(&firestore.collection(EXTERNAL_GAMES))
+                .obj()
+                .batch_with_errors(doc_ids.iter())
+                .await
+        })
         .await?;
 
     let mut matches = vec![];
@@ -88,52 +94,66 @@ pub struct ExternalMatch {
     )
 )]
 pub async fn write(firestore: &FirestoreApi, external_game: &ExternalGame) -> Result<(), Status> {
-    let doc_id = format!("{}_{}", &external_game.store_name, &external_game.store_id);
+    access::check_write(firestore, EXTERNAL_GAMES)?;
 
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(EXTERNAL_GAMES)
-        .document_id(doc_id)
-        .object(external_game)
-        .execute()
-        .await?;
-    Ok(())
+    let doc_id = format!("{}_{}", &external_game.store_name, &external_game.store_id);
+    let doc_id = &doc_id;
+
+    utils::run("external_games::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(EXTERNAL_GAMES))
+            .document_id(doc_id)
+            .object(external_game)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 #[instrument(name = "external_games::delete", level = "trace", skip(firestore))]
 pub async fn delete(firestore: &FirestoreApi, store: &str, store_id: &str) -> Result<(), Status> {
-    let doc_id = format!("{}_{}", store, store_id);
+    access::check_write(firestore, EXTERNAL_GAMES)?;
 
-    firestore
-        .db()
-        .fluent()
-        .delete()
-        .from(EXTERNAL_GAMES)
-        .document_id(doc_id)
-        .execute()
-        .await?;
-    Ok(())
+    let doc_id = format!("{}_{}", store, store_id);
+    let doc_id = &doc_id;
+
+    utils::run("external_games::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(EXTERNAL_GAMES).as_str())
+            .document_id(doc_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 pub async fn get_steam_id(firestore: &FirestoreApi, igdb_id: u64) -> Result<String, Status> {
-    let external_games: BoxStream<FirestoreResult<ExternalGame>> = firestore
-        .db()
-        .fluent()
-        .select()
-        .from("external_games")
-        .filter(|q| {
-            q.for_all([
-                q.field(path!(ExternalGame::igdb_id)).equal(igdb_id),
-                q.field(path!(ExternalGame::store_name)).equal("steam"),
-            ])
-        })
-        .obj()
-        .stream_query_with_errors()
-        .await?;
-
-    let external_games = external_games.try_collect::<Vec<ExternalGame>>().await?;
+    let external_games = utils::run("external_games::get_steam_id", || async move {
+        let external_games: BoxStream<FirestoreResult<ExternalGame>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection("external_games").as_str())
+            .filter(|q| {
+                q.for_all([
+                    q.field(path!(ExternalGame::igdb_id)).equal(igdb_id),
+                    q.field(path!(ExternalGame::store_name)).equal("steam"),
+                ])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        external_games.try_collect::<Vec<ExternalGame>>().await
+    })
+    .await?;
     match external_games.is_empty() {
         false => Ok(external_games[0].store_id.clone()),
         true => Err(Status::not_found(format!(
@@ -146,17 +166,19 @@ pub async fn get_external_games(
     firestore: &FirestoreApi,
     igdb_id: u64,
 ) -> Result<Vec<ExternalGame>, Status> {
-    let external_games: BoxStream<FirestoreResult<ExternalGame>> = firestore
-        .db()
-        .fluent()
-        .select()
-        .from("external_games")
-        .filter(|q| q.for_all([q.field(path!(ExternalGame::igdb_id)).equal(igdb_id)]))
-        .obj()
-        .stream_query_with_errors()
-        .await?;
-
-    Ok(external_games.try_collect::<Vec<ExternalGame>>().await?)
+    utils::run("external_games::get_external_games", || async move {
+        let external_games: BoxStream<FirestoreResult<ExternalGame>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection("external_games").as_str())
+            .filter(|q| q.for_all([q.field(path!(ExternalGame::igdb_id)).equal(igdb_id)]))
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        external_games.try_collect::<Vec<ExternalGame>>().await
+    })
+    .await
 }
 
 const EXTERNAL_GAMES: &str = "external_games";
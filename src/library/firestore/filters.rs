@@ -0,0 +1,87 @@
+use crate::{
+    api::FirestoreApi,
+    documents::{SmartFilter, SmartFilters},
+    Status,
+};
+use tracing::instrument;
+
+use super::utils;
+
+#[instrument(name = "filters::read", level = "trace", skip(firestore, user_id))]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<SmartFilters, Status> {
+    utils::users_read(firestore, user_id, USER_DATA, FILTERS_DOC).await
+}
+
+/// Deletes the user's filters document.
+#[instrument(name = "filters::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, USER_DATA, FILTERS_DOC).await
+}
+
+/// Saves `filter` in user's smart filters. A filter with the same name is
+/// replaced.
+#[instrument(
+    name = "filters::save",
+    level = "trace",
+    skip(firestore, user_id, filter),
+    fields(filter = %filter.name),
+)]
+pub async fn save(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    filter: SmartFilter,
+) -> Result<(), Status> {
+    let mut filters = read(firestore, user_id).await?;
+    match filters.filters.iter_mut().find(|f| f.name == filter.name) {
+        Some(existing) => *existing = filter,
+        None => filters.filters.push(filter),
+    }
+    write(firestore, user_id, &filters).await
+}
+
+#[instrument(name = "filters::delete", level = "trace", skip(firestore, user_id))]
+pub async fn delete(firestore: &FirestoreApi, user_id: &str, name: &str) -> Result<(), Status> {
+    let mut filters = read(firestore, user_id).await?;
+    let len = filters.filters.len();
+    filters.filters.retain(|f| f.name != name);
+    if filters.filters.len() == len {
+        return Err(Status::not_found(format!(
+            "Smart filter '{name}' was not found"
+        )));
+    }
+    write(firestore, user_id, &filters).await
+}
+
+#[instrument(
+    name = "filters::write",
+    level = "trace",
+    skip(firestore, user_id, filters)
+)]
+async fn write(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    filters: &SmartFilters,
+) -> Result<(), Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("filters::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(USER_DATA)
+            .document_id(FILTERS_DOC)
+            .parent(parent_path)
+            .object(filters)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const USER_DATA: &str = "user_data";
+const FILTERS_DOC: &str = "filters";
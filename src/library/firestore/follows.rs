@@ -0,0 +1,146 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{
+    api::FirestoreApi,
+    documents::{FollowKind, Follows},
+    Status,
+};
+
+use super::utils;
+
+#[instrument(name = "follows::read", level = "trace", skip(firestore, user_id))]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Follows, Status> {
+    let mut follows: Follows = match utils::read(firestore, FOLLOWS, user_id.to_owned()).await {
+        Ok(follows) => follows,
+        // Follows that were not written since they moved out of the user's
+        // 'user_data' are read from their legacy location.
+        Err(Status::NotFound(_)) => {
+            utils::users_read(firestore, user_id, USER_DATA, FOLLOWS_DOC).await?
+        }
+        Err(status) => return Err(status),
+    };
+    follows.user_id = user_id.to_owned();
+    Ok(follows)
+}
+
+/// Adds the entity of `kind` with `id` in the user's follows.
+#[instrument(name = "follows::follow", level = "trace", skip(firestore, user_id))]
+pub async fn follow(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    kind: FollowKind,
+    id: u64,
+) -> Result<(), Status> {
+    let mut follows = read(firestore, user_id).await?;
+    if follows.follow(kind, id) {
+        write(firestore, &follows).await?;
+    }
+    Ok(())
+}
+
+/// Removes the entity of `kind` with `id` from the user's follows.
+#[instrument(name = "follows::unfollow", level = "trace", skip(firestore, user_id))]
+pub async fn unfollow(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    kind: FollowKind,
+    id: u64,
+) -> Result<(), Status> {
+    let mut follows = read(firestore, user_id).await?;
+    match follows.unfollow(kind, id) {
+        true => write(firestore, &follows).await,
+        false => Err(Status::not_found(format!("{kind:?} {id} is not followed"))),
+    }
+}
+
+/// Returns the follows of all users that follow any of the entities of `kind`
+/// with `ids`.
+#[instrument(name = "follows::list_followers", level = "trace", skip(firestore))]
+pub async fn list_followers(
+    firestore: &FirestoreApi,
+    kind: FollowKind,
+    ids: &[u64],
+) -> Result<Vec<Follows>, Status> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let field = match kind {
+        FollowKind::Company => path!(Follows::companies),
+        FollowKind::Franchise => path!(Follows::franchises),
+        FollowKind::Collection => path!(Follows::collections),
+    };
+
+    let field = &field;
+
+    utils::run("follows::list_followers", || async move {
+        let follows: BoxStream<FirestoreResult<Follows>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(FOLLOWS).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(field.clone())
+                    .array_contains_any(ids.iter().take(MAX_ARRAY_CONTAINS_ANY).cloned())])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        follows.try_collect::<Vec<Follows>>().await
+    })
+    .await
+}
+
+/// Deletes the user's follows document, including its legacy copy.
+#[instrument(name = "follows::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::run("follows::purge", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(FOLLOWS).as_str())
+            .document_id(user_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+    utils::users_delete(firestore, user_id, USER_DATA, FOLLOWS_DOC).await
+}
+
+#[instrument(
+    name = "follows::write",
+    level = "trace",
+    skip(firestore, follows),
+    fields(user_id = %follows.user_id),
+)]
+async fn write(firestore: &FirestoreApi, follows: &Follows) -> Result<(), Status> {
+    utils::run("follows::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(FOLLOWS))
+            .document_id(&follows.user_id)
+            .object(follows)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+// Follows live in a root collection keyed by user id, so that followers are
+// found with a query over the prefixed collection of the environment.
+const FOLLOWS: &str = "follows";
+
+// Legacy location of follows under 'users/{user_id}/user_data'.
+const USER_DATA: &str = "user_data";
+const FOLLOWS_DOC: &str = "follows";
+
+// Firestore limit on the values of an array-contains-any filter.
+const MAX_ARRAY_CONTAINS_ANY: usize = 30;
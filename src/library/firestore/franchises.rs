@@ -2,7 +2,7 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Collection, Status};
 
-use super::{utils, BatchReadResult};
+use super::{access, utils, BatchReadResult};
 
 #[instrument(name = "franchises::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Collection, Status> {
@@ -34,16 +34,40 @@ pub async fn batch_read(
     )
 )]
 pub async fn write(firestore: &FirestoreApi, franchise: &Collection) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(FRANCHISES)
-        .document_id(franchise.id.to_string())
-        .object(franchise)
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, FRANCHISES)?;
+
+    utils::run("franchises::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(FRANCHISES))
+            .document_id(franchise.id.to_string())
+            .object(franchise)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Writes `franchises` with batched writes instead of a write per doc.
+#[instrument(
+    name = "franchises::batch_write",
+    level = "trace",
+    skip(firestore, franchises)
+)]
+pub async fn batch_write(
+    firestore: &FirestoreApi,
+    franchises: &[Collection],
+) -> Result<(), Status> {
+    access::check_write(firestore, FRANCHISES)?;
+
+    let docs = franchises
+        .iter()
+        .map(|doc| (doc.id.to_string(), doc))
+        .collect::<Vec<_>>();
+    utils::batch_write(firestore, FRANCHISES, &docs).await
 }
 
 const FRANCHISES: &str = "franchises";
@@ -2,16 +2,59 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Frontpage, Status};
 
+use super::{access, utils};
+
+#[instrument(name = "frontpage::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi) -> Result<Frontpage, Status> {
+    utils::read(firestore, "espy", "frontpage".to_string()).await
+}
+
+/// Returns the frontpage doc from the connection cache when available.
+///
+/// Suitable for serving reads. Services that serve the frontpage keep it warm
+/// with `refresh()`.
+#[instrument(name = "frontpage::lookup", level = "trace", skip(firestore))]
+pub async fn lookup(firestore: &FirestoreApi) -> Result<Frontpage, Status> {
+    match firestore.cache().frontpage.get(DOC_KEY) {
+        Some(frontpage) => Ok(frontpage),
+        None => refresh(firestore).await,
+    }
+}
+
+/// Reads the frontpage doc from Firestore and stores it in the connection cache.
+#[instrument(name = "frontpage::refresh", level = "trace", skip(firestore))]
+pub async fn refresh(firestore: &FirestoreApi) -> Result<Frontpage, Status> {
+    let frontpage = read(firestore).await?;
+    firestore
+        .cache()
+        .frontpage
+        .insert(DOC_KEY, frontpage.clone());
+    Ok(frontpage)
+}
+
 #[instrument(name = "frontpage::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, frontpage: &Frontpage) -> Result<(), Status> {
+    access::check_write(firestore, "espy")?;
+
+    utils::run("frontpage::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection("espy"))
+            .document_id("frontpage")
+            .object(frontpage)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
     firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col("espy")
-        .document_id("frontpage")
-        .object(frontpage)
-        .execute()
-        .await?;
+        .cache()
+        .frontpage
+        .insert(DOC_KEY, frontpage.clone());
     Ok(())
 }
+
+// The connection cache is keyed by id, the frontpage is a single doc.
+const DOC_KEY: u64 = 0;
@@ -0,0 +1,71 @@
+use firestore::{path, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::GameEdit, Status};
+
+use super::{access, utils};
+
+/// Returns the edits of a game with the most recent first.
+#[instrument(name = "game_edits::list", level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi, game_id: u64) -> Result<Vec<GameEdit>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(GAME_EDITS), game_id.to_string())?;
+    let parent_path = &parent_path;
+
+    utils::run("game_edits::list", || async move {
+        let edits: BoxStream<FirestoreResult<GameEdit>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(EDITS)
+            .parent(parent_path)
+            .order_by([(
+                path!(GameEdit::timestamp),
+                FirestoreQueryDirection::Descending,
+            )])
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        edits.try_collect::<Vec<GameEdit>>().await
+    })
+    .await
+}
+
+/// Records `edit` in the edit history of its game.
+#[instrument(
+    name = "game_edits::add",
+    level = "trace",
+    skip(firestore, edit),
+    fields(
+        game_id = %edit.game_id,
+        kind = ?edit.kind,
+    )
+)]
+pub async fn add(firestore: &FirestoreApi, edit: &GameEdit) -> Result<(), Status> {
+    access::check_write(firestore, GAME_EDITS)?;
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(GAME_EDITS), edit.game_id.to_string())?;
+    let parent_path = &parent_path;
+
+    utils::run("game_edits::add", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(EDITS)
+            .document_id(&edit.id)
+            .parent(parent_path)
+            .object(edit)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const GAME_EDITS: &str = "game_edits";
+const EDITS: &str = "edits";
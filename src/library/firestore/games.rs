@@ -1,24 +1,27 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use futures::{stream::BoxStream, StreamExt};
+use firestore::FirestoreResult;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::GameEntry, Status};
 
-use super::{utils, BatchReadResult};
+use super::{access, utils, BatchReadResult};
 
 #[instrument(name = "games::list", level = "trace", skip(firestore))]
 pub async fn list(firestore: &FirestoreApi) -> Result<Vec<GameEntry>, Status> {
-    let doc_stream: BoxStream<GameEntry> = firestore
-        .db()
-        .fluent()
-        .list()
-        .from(GAMES)
-        .obj()
-        .stream_all()
-        .await?;
-
-    Ok(doc_stream.collect().await)
+    utils::run("games::list", || async move {
+        let doc_stream: BoxStream<GameEntry> = firestore
+            .db()
+            .fluent()
+            .list()
+            .from(firestore.collection(GAMES).as_str())
+            .obj()
+            .stream_all()
+            .await?;
+        Ok(doc_stream.collect().await)
+    })
+    .await
 }
 
 #[instrument(name = "games::read", level = "trace", skip(firestore))]
@@ -38,36 +41,173 @@ pub async fn batch_read(
     utils::batch_read(firestore, GAMES, doc_ids).await
 }
 
+/// Returns up to `limit` games whose IGDB slug starts with the slug of
+/// `title`. It serves searches from the catalog without going to IGDB.
+#[instrument(name = "games::search_by_title", level = "trace", skip(firestore))]
+pub async fn search_by_title(
+    firestore: &FirestoreApi,
+    title: &str,
+    limit: u32,
+) -> Result<Vec<GameEntry>, Status> {
+    let prefix = slug(title);
+    if prefix.is_empty() {
+        return Ok(vec![]);
+    }
+    let end = format!("{prefix}\u{f8ff}");
+    let (prefix, end) = (prefix.as_str(), end.as_str());
+
+    let mut game_entries = utils::run("games::search_by_title", || async move {
+        let game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(GAMES).as_str())
+            .filter(|q| {
+                q.for_all([
+                    q.field(SLUG_FIELD).greater_than_or_equal(prefix),
+                    q.field(SLUG_FIELD).less_than(end),
+                ])
+            })
+            .limit(limit)
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        game_entries.try_collect::<Vec<GameEntry>>().await
+    })
+    .await?;
+    game_entries.retain(|game_entry| game_entry.deleted_at.is_none());
+
+    Ok(game_entries)
+}
+
+/// Returns the IGDB-style slug of `title`, e.g. "Baldur's Gate 3" becomes
+/// "baldurs-gate-3".
+fn slug(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace('\'', "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 #[instrument(name = "games::write", level = "trace", skip(firestore, game_entry))]
 pub async fn write(firestore: &FirestoreApi, game_entry: &mut GameEntry) -> Result<(), Status> {
+    access::check_write(firestore, GAMES)?;
+
     game_entry.last_updated = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap()
+        .unwrap_or_default()
         .as_secs() as i64;
+    game_entry.update_tags();
+    let game_entry = &*game_entry;
 
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(GAMES)
-        .document_id(game_entry.id.to_string())
-        .object(game_entry)
-        .execute()
-        .await?;
-    Ok(())
+    utils::run("games::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(GAMES))
+            .document_id(game_entry.id.to_string())
+            .object(game_entry)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Updates only the `fields` of the game document from `game_entry`. Fields
+/// that are missing from the serialized `game_entry` are removed from the doc.
+#[instrument(name = "games::patch", level = "trace", skip(firestore, game_entry))]
+pub async fn patch(
+    firestore: &FirestoreApi,
+    game_entry: &GameEntry,
+    fields: Vec<String>,
+) -> Result<(), Status> {
+    access::check_write(firestore, GAMES)?;
+    let fields = &fields;
+
+    utils::run("games::patch", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .fields(fields.clone())
+            .in_col(&firestore.collection(GAMES))
+            .document_id(game_entry.id.to_string())
+            .object(game_entry)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Marks the game with `doc_id` as deleted and returns its tombstone. The
+/// document is kept, so that webhooks and batch jobs do not recreate it.
+#[instrument(name = "games::soft_delete", level = "trace", skip(firestore))]
+pub async fn soft_delete(firestore: &FirestoreApi, doc_id: u64) -> Result<GameEntry, Status> {
+    let mut game_entry = read(firestore, doc_id).await?;
+    game_entry.deleted_at = Some(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64,
+    );
+    game_entry.update_archived();
+    write(firestore, &mut game_entry).await?;
+    Ok(game_entry)
+}
+
+/// Restores a game that was soft deleted and returns it.
+#[instrument(name = "games::undelete", level = "trace", skip(firestore))]
+pub async fn undelete(firestore: &FirestoreApi, doc_id: u64) -> Result<GameEntry, Status> {
+    let mut game_entry = read(firestore, doc_id).await?;
+    if game_entry.deleted_at.is_none() {
+        return Err(Status::invalid_argument(format!(
+            "Game {doc_id} is not deleted"
+        )));
+    }
+    game_entry.deleted_at = None;
+    game_entry.update_archived();
+    write(firestore, &mut game_entry).await?;
+    Ok(game_entry)
 }
 
 #[instrument(name = "games::delete", level = "trace", skip(firestore))]
 pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .delete()
-        .from(GAMES)
-        .document_id(doc_id.to_string())
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, GAMES)?;
+
+    utils::run("games::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(GAMES).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const GAMES: &str = "games";
+
+// Field of the IGDB slug in game documents.
+const SLUG_FIELD: &str = "igdb_game.slug";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_of_title() {
+        assert_eq!(slug("Baldur's Gate 3"), "baldurs-gate-3");
+        assert_eq!(slug("  Hades II: "), "hades-ii");
+        assert_eq!(slug("S.T.A.L.K.E.R."), "s-t-a-l-k-e-r");
+        assert_eq!(slug("!!!"), "");
+    }
+}
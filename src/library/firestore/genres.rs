@@ -6,24 +6,40 @@ use crate::{
     Status,
 };
 
-use super::utils;
+use super::{access, utils};
 
 #[instrument(name = "genres::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Genre, Status> {
-    utils::read(firestore, GENRES, doc_id.to_string()).await
+    if let Some(genre) = firestore.cache().genres.get(doc_id) {
+        return Ok(genre);
+    }
+
+    let genre: Genre = utils::read(firestore, GENRES, doc_id.to_string()).await?;
+    firestore.cache().genres.insert(doc_id, genre.clone());
+    Ok(genre)
 }
 
 #[instrument(name = "genres::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, genre: &Genre) -> Result<(), Status> {
+    access::check_write(firestore, GENRES)?;
+
+    utils::run("genres::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(GENRES))
+            .document_id(genre.game_id.to_string())
+            .object(genre)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
     firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(GENRES)
-        .document_id(genre.game_id.to_string())
-        .object(genre)
-        .execute()
-        .await?;
+        .cache()
+        .genres
+        .insert(genre.game_id, genre.clone());
     Ok(())
 }
 
@@ -32,22 +48,28 @@ pub async fn needs_annotation(
     firestore: &FirestoreApi,
     game_entry: &GameEntry,
 ) -> Result<(), Status> {
+    access::check_write(firestore, NEEDS_ANNOTATION)?;
+
     let clone = GameEntry {
         id: game_entry.id,
         name: game_entry.name.clone(),
         ..Default::default()
     };
+    let clone = &clone;
 
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(NEEDS_ANNOTATION)
-        .document_id(game_entry.id.to_string())
-        .object(&clone)
-        .execute()
-        .await?;
-    Ok(())
+    utils::run("genres::needs_annotation", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(NEEDS_ANNOTATION))
+            .document_id(game_entry.id.to_string())
+            .object(clone)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const GENRES: &str = "genres";
@@ -0,0 +1,101 @@
+use firestore::{path, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::{instrument, warn};
+
+use crate::{api::FirestoreApi, documents::IgdbSnapshot, Status};
+
+use super::{access, utils};
+
+/// Returns the stored snapshots of a game, most recent first.
+#[instrument(name = "igdb_snapshots::list", level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi, game_id: u64) -> Result<Vec<IgdbSnapshot>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(GAMES), game_id.to_string())?;
+    let parent_path = &parent_path;
+
+    utils::run("igdb_snapshots::list", || async move {
+        let snapshots: BoxStream<FirestoreResult<IgdbSnapshot>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(IGDB_SNAPSHOTS)
+            .parent(parent_path)
+            .order_by([(
+                path!(IgdbSnapshot::timestamp),
+                FirestoreQueryDirection::Descending,
+            )])
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        snapshots.try_collect::<Vec<IgdbSnapshot>>().await
+    })
+    .await
+}
+
+/// Stores `snapshot` and drops the oldest snapshots of the game so that only
+/// the last `MAX_SNAPSHOTS` are kept.
+#[instrument(
+    name = "igdb_snapshots::add",
+    level = "trace",
+    skip(firestore, snapshot),
+    fields(
+        game_id = %snapshot.game_id,
+    )
+)]
+pub async fn add(firestore: &FirestoreApi, snapshot: &IgdbSnapshot) -> Result<(), Status> {
+    access::check_write(firestore, IGDB_SNAPSHOTS)?;
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(GAMES), snapshot.game_id.to_string())?;
+    let parent_path = &parent_path;
+
+    utils::run("igdb_snapshots::add", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(IGDB_SNAPSHOTS)
+            .document_id(snapshot.timestamp.to_string())
+            .parent(parent_path)
+            .object(snapshot)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+
+    for expired in list(firestore, snapshot.game_id)
+        .await?
+        .into_iter()
+        .skip(MAX_SNAPSHOTS)
+    {
+        let expired = &expired;
+        if let Err(e) = utils::run("igdb_snapshots::add", || async move {
+            firestore
+                .db()
+                .fluent()
+                .delete()
+                .from(IGDB_SNAPSHOTS)
+                .parent(parent_path)
+                .document_id(expired.timestamp.to_string())
+                .execute()
+                .await
+        })
+        .await
+        {
+            warn!(
+                "Failed to delete snapshot {}/{}: {e}",
+                expired.game_id, expired.timestamp
+            );
+        }
+    }
+    Ok(())
+}
+
+// Number of snapshots that are kept per game.
+const MAX_SNAPSHOTS: usize = 10;
+
+const GAMES: &str = "games";
+const IGDB_SNAPSHOTS: &str = "igdb_snapshots";
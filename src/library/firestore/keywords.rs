@@ -2,11 +2,17 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Keyword, Status};
 
-use super::{utils, BatchReadResult};
+use super::{access, utils, BatchReadResult};
 
 #[instrument(name = "keywords::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<Keyword, Status> {
-    utils::read(firestore, KEYWORDS, doc_id.to_string()).await
+    if let Some(keyword) = firestore.cache().keywords.get(doc_id) {
+        return Ok(keyword);
+    }
+
+    let keyword: Keyword = utils::read(firestore, KEYWORDS, doc_id.to_string()).await?;
+    firestore.cache().keywords.insert(doc_id, keyword.clone());
+    Ok(keyword)
 }
 
 #[instrument(name = "keywords::batch_read", level = "trace", skip(firestore))]
@@ -14,20 +20,56 @@ pub async fn batch_read(
     firestore: &FirestoreApi,
     doc_ids: &[u64],
 ) -> Result<BatchReadResult<Keyword>, Status> {
-    utils::batch_read(firestore, KEYWORDS, doc_ids).await
+    let cache = &firestore.cache().keywords;
+
+    let mut documents = vec![];
+    let mut missing = vec![];
+    for id in doc_ids {
+        match cache.get(*id) {
+            Some(keyword) => documents.push(keyword),
+            None => missing.push(*id),
+        }
+    }
+    if missing.is_empty() {
+        return Ok(BatchReadResult {
+            documents,
+            not_found: vec![],
+        });
+    }
+
+    let result: BatchReadResult<Keyword> = utils::batch_read(firestore, KEYWORDS, &missing).await?;
+    for keyword in &result.documents {
+        cache.insert(keyword.id, keyword.clone());
+    }
+    documents.extend(result.documents);
+
+    Ok(BatchReadResult {
+        documents,
+        not_found: result.not_found,
+    })
 }
 
 #[instrument(name = "keywords::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, keyword: &Keyword) -> Result<(), Status> {
+    access::check_write(firestore, KEYWORDS)?;
+
+    utils::run("keywords::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(KEYWORDS))
+            .document_id(keyword.id.to_string())
+            .object(keyword)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
     firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(KEYWORDS)
-        .document_id(keyword.id.to_string())
-        .object(keyword)
-        .execute()
-        .await?;
+        .cache()
+        .keywords
+        .insert(keyword.id, keyword.clone());
     Ok(())
 }
 
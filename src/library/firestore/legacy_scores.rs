@@ -0,0 +1,35 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::LegacyScore, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "legacy_scores::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<LegacyScore, Status> {
+    utils::read(firestore, LEGACY_SCORES, doc_id.to_string()).await
+}
+
+#[instrument(
+    name = "legacy_scores::write",
+    level = "trace",
+    skip(firestore, legacy_score)
+)]
+pub async fn write(firestore: &FirestoreApi, legacy_score: &LegacyScore) -> Result<(), Status> {
+    access::check_write(firestore, LEGACY_SCORES)?;
+
+    utils::run("legacy_scores::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(LEGACY_SCORES))
+            .document_id(legacy_score.id.to_string())
+            .object(legacy_score)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const LEGACY_SCORES: &str = "legacy_scores";
@@ -1,17 +1,136 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::{
     api::FirestoreApi,
-    documents::{GameDigest, Library, LibraryEntry, StoreEntry},
+    documents::{GameDigest, Library, LibraryEntry, LibraryEvent, LibraryEventKind, StoreEntry},
     Status,
 };
-use tracing::instrument;
+use chrono::Utc;
+use firestore::{path, paths, FirestoreDocument, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use tracing::{info, instrument, warn};
 
-use super::utils;
+use super::{library_events, utils};
 
+/// Returns the user's library.
+///
+/// Entries are stored as one doc per game under 'users/{user_id}/library'.
+/// Libraries that are still in the legacy single doc are read from it until
+/// their first write migrates them.
 #[instrument(name = "library::read", level = "trace", skip(firestore, user_id))]
 pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Library, Status> {
-    utils::users_read(firestore, user_id, GAMES, LIBRARY_DOC).await
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    let mut entries = utils::run("library::read", || async move {
+        let entries: BoxStream<FirestoreResult<LibraryEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(ENTRIES)
+            .parent(parent_path)
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        entries.try_collect::<Vec<LibraryEntry>>().await
+    })
+    .await?;
+
+    match entries.is_empty() {
+        true => read_legacy(firestore, user_id).await,
+        false => {
+            entries.sort_by_key(|entry| entry.added_date);
+            Ok(Library { entries })
+        }
+    }
+}
+
+/// Returns the ids of users that have `game_id` in their library.
+///
+/// Libraries that are still in the legacy single doc are not found.
+#[instrument(name = "library::list_owners", level = "trace", skip(firestore))]
+pub async fn list_owners(firestore: &FirestoreApi, game_id: u64) -> Result<Vec<String>, Status> {
+    // Collection group query over the per game docs of all users.
+    let docs = utils::run("library::list_owners", || async move {
+        let docs: Vec<FirestoreDocument> = firestore
+            .db()
+            .fluent()
+            .select()
+            .fields(paths!(LibraryEntry::id))
+            .from(ENTRIES)
+            .all_descendants()
+            .filter(|q| q.for_all([q.field(path!(LibraryEntry::id)).equal(game_id)]))
+            .query()
+            .await?;
+        Ok(docs)
+    })
+    .await?;
+
+    Ok(docs
+        .iter()
+        .filter_map(|doc| utils::users_doc_owner(firestore, &doc.name, ENTRIES))
+        .collect())
+}
+
+/// Returns up to `limit` library entries ordered by game id, starting after
+/// game id `after`. The last id of a page is the `after` of the next one.
+#[instrument(name = "library::read_page", level = "trace", skip(firestore, user_id))]
+pub async fn read_page(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    after: Option<u64>,
+    limit: u32,
+) -> Result<Vec<LibraryEntry>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    let after = after.unwrap_or_default();
+    let entries = utils::run("library::read_page", || async move {
+        let entries: BoxStream<FirestoreResult<LibraryEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(ENTRIES)
+            .parent(parent_path)
+            .filter(|q| q.for_all([q.field(path!(LibraryEntry::id)).greater_than(after)]))
+            .order_by([(path!(LibraryEntry::id), FirestoreQueryDirection::Ascending)])
+            .limit(limit)
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        entries.try_collect::<Vec<LibraryEntry>>().await
+    })
+    .await?;
+
+    match entries.is_empty() && after == 0 {
+        true => Ok(legacy_page(
+            read_legacy(firestore, user_id).await?,
+            after,
+            limit as usize,
+        )),
+        false => Ok(entries),
+    }
+}
+
+/// Deletes all entries of the user's library.
+#[instrument(name = "library::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let library = read(firestore, user_id).await?;
+    let ids = library
+        .entries
+        .iter()
+        .map(|entry| entry.id)
+        .collect::<Vec<_>>();
+    commit(firestore, user_id, &[], &ids).await?;
+    utils::users_delete(firestore, user_id, GAMES, LIBRARY_DOC).await
 }
 
+/// Replaces the user's library with `library`. Only entries that changed are
+/// written.
 #[instrument(
     name = "library::write",
     level = "trace",
@@ -20,30 +139,13 @@ pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Library, St
 pub async fn write(
     firestore: &FirestoreApi,
     user_id: &str,
-    mut library: Library,
+    library: Library,
 ) -> Result<(), Status> {
-    library
-        .entries
-        .sort_by(|l, r| r.digest.release_date.cmp(&l.digest.release_date));
-
-    let parent_path = firestore.db().parent_path(utils::USERS, user_id)?;
-
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(GAMES)
-        .document_id(LIBRARY_DOC)
-        .parent(&parent_path)
-        .object(&library)
-        .execute()
-        .await?;
-    Ok(())
+    migrate(firestore, user_id).await?;
+    let current = read(firestore, user_id).await?;
+    write_changes(firestore, user_id, &current, &library).await
 }
 
-const GAMES: &str = "games";
-const LIBRARY_DOC: &str = "library";
-
 #[instrument(
     name = "library::add_entry",
     level = "trace",
@@ -55,14 +157,15 @@ pub async fn add_entry(
     store_entry: StoreEntry,
     digests: Vec<GameDigest>,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
-
-    for digest in digests {
-        add(LibraryEntry::new(digest, store_entry.clone()), &mut library);
-    }
-    write(firestore, user_id, library).await
+    let library_entries = digests
+        .into_iter()
+        .map(|digest| LibraryEntry::new(digest, store_entry.clone()))
+        .collect();
+    add_entries(firestore, user_id, library_entries).await
 }
 
+/// Adds `library_entries` in the library. Only the entries of the same games
+/// are read and written.
 #[instrument(
     name = "library::add_entries",
     level = "trace",
@@ -73,12 +176,25 @@ pub async fn add_entries(
     user_id: &str,
     library_entries: Vec<LibraryEntry>,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
-
+    migrate(firestore, user_id).await?;
+
+    let ids = library_entries
+        .iter()
+        .map(|entry| entry.id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let current = Library {
+        entries: read_entries(firestore, user_id, &ids).await?,
+    };
+
+    let mut library = Library {
+        entries: current.entries.clone(),
+    };
     for library_entry in library_entries {
         add(library_entry, &mut library);
     }
-    write(firestore, user_id, library).await
+    write_changes(firestore, user_id, &current, &library).await
 }
 
 #[instrument(
@@ -91,9 +207,14 @@ pub async fn remove_entry(
     user_id: &str,
     store_entry: &StoreEntry,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
+    migrate(firestore, user_id).await?;
+
+    let current = read(firestore, user_id).await?;
+    let mut library = Library {
+        entries: current.entries.clone(),
+    };
     if remove(store_entry, &mut library) {
-        write(firestore, user_id, library).await?;
+        write_changes(firestore, user_id, &current, &library).await?;
     }
     Ok(())
 }
@@ -109,15 +230,42 @@ pub async fn replace_entry(
     store_entry: &StoreEntry,
     library_entries: Vec<LibraryEntry>,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
+    migrate(firestore, user_id).await?;
 
+    let current = read(firestore, user_id).await?;
+    let previous_game_id = current
+        .entries
+        .iter()
+        .find(|entry| entry.store_entries.contains(store_entry))
+        .map(|entry| entry.id);
+
+    let now = Utc::now().timestamp();
+    let events = library_entries
+        .iter()
+        .filter(|entry| Some(entry.id) != previous_game_id)
+        .map(|entry| LibraryEvent {
+            previous_game_id,
+            store_entry: Some(store_entry.clone()),
+            ..LibraryEvent::new(
+                LibraryEventKind::Rematched,
+                entry.id,
+                &entry.digest.name,
+                now,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut library = Library {
+        entries: current.entries.clone(),
+    };
     remove(store_entry, &mut library);
     for library_entry in library_entries {
         add(library_entry, &mut library);
     }
 
-    write(firestore, user_id, library).await?;
-
+    let (updated, removed) = diff(&current, &library);
+    commit(firestore, user_id, &updated, &removed).await?;
+    record(firestore, user_id, events).await;
     Ok(())
 }
 
@@ -131,16 +279,18 @@ pub async fn update_entry(
     user_id: &str,
     game_digest: GameDigest,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
+    migrate(firestore, user_id).await?;
 
-    match library.entries.iter_mut().find(|e| e.id == game_digest.id) {
-        Some(existing_entry) => existing_entry.digest = game_digest,
-        None => {
-            return Err(Status::not_found("not in library"));
-        }
-    }
+    let mut library_entry = match read_entries(firestore, user_id, &[game_digest.id])
+        .await?
+        .pop()
+    {
+        Some(library_entry) => library_entry,
+        None => return Err(Status::not_found("not in library")),
+    };
+    library_entry.digest = game_digest;
 
-    write(firestore, user_id, library).await
+    commit(firestore, user_id, &[&library_entry], &[]).await
 }
 
 #[instrument(
@@ -153,11 +303,263 @@ pub async fn remove_storefront(
     user_id: &str,
     storefront_id: &str,
 ) -> Result<(), Status> {
-    let mut library = read(firestore, user_id).await?;
+    migrate(firestore, user_id).await?;
+
+    let current = read(firestore, user_id).await?;
+    let mut library = Library {
+        entries: current.entries.clone(),
+    };
     remove_storefront_entries(storefront_id, &mut library);
-    write(firestore, user_id, library).await
+    write_changes(firestore, user_id, &current, &library).await
 }
 
+/// Reads the entries of games `ids` that are in the library.
+async fn read_entries(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    ids: &[u64],
+) -> Result<Vec<LibraryEntry>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    let mut docs: BoxStream<FirestoreResult<(String, Option<LibraryEntry>)>> =
+        utils::run("library::read_entries", || async move {
+            firestore
+                .db()
+                .fluent()
+                .select()
+                .by_id_in(ENTRIES)
+                .parent(parent_path)
+                .obj()
+                .batch_with_errors(ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+                .await
+        })
+        .await?;
+
+    let mut entries = vec![];
+    while let Some(doc) = docs.next().await {
+        match doc {
+            Ok((_, Some(entry))) => entries.push(entry),
+            Ok((_, None)) => {}
+            Err(e) => {
+                return Err(utils::make_status(
+                    e,
+                    &format!("{}/{user_id}/{ENTRIES}", utils::USERS),
+                    "?",
+                ))
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Writes the entries of `library` that differ from `current` and deletes the
+/// ones that were removed. Games that were added or removed are recorded in
+/// the library events.
+async fn write_changes(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    current: &Library,
+    library: &Library,
+) -> Result<(), Status> {
+    let (updated, removed) = diff(current, library);
+    commit(firestore, user_id, &updated, &removed).await?;
+    record(
+        firestore,
+        user_id,
+        membership_events(current, library, Utc::now().timestamp()),
+    )
+    .await;
+    Ok(())
+}
+
+/// Records `events` in the user's library history. Failures are only logged,
+/// as the library changes are already committed.
+async fn record(firestore: &FirestoreApi, user_id: &str, events: Vec<LibraryEvent>) {
+    if let Err(status) = library_events::add(firestore, user_id, &events).await {
+        warn!(
+            "Failed to record {} library events of user '{user_id}': {status}",
+            events.len()
+        );
+    }
+}
+
+/// Writes `updated` entries and deletes the entries of `removed` game ids in
+/// batches.
+async fn commit(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    updated: &[&LibraryEntry],
+    removed: &[u64],
+) -> Result<(), Status> {
+    if updated.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+    let batch_writer = &firestore.db().create_simple_batch_writer().await?;
+
+    for chunk in updated.chunks(BATCH_SIZE) {
+        utils::run("library::commit", || async move {
+            let mut batch = batch_writer.new_batch();
+            for library_entry in chunk {
+                firestore
+                    .db()
+                    .fluent()
+                    .update()
+                    .in_col(ENTRIES)
+                    .document_id(library_entry.id.to_string())
+                    .parent(parent_path)
+                    .object(*library_entry)
+                    .add_to_batch(&mut batch)?;
+            }
+            batch.write().await?;
+            Ok(())
+        })
+        .await?;
+    }
+
+    for chunk in removed.chunks(BATCH_SIZE) {
+        utils::run("library::commit", || async move {
+            let mut batch = batch_writer.new_batch();
+            for id in chunk {
+                firestore
+                    .db()
+                    .fluent()
+                    .delete()
+                    .from(ENTRIES)
+                    .parent(parent_path)
+                    .document_id(id.to_string())
+                    .add_to_batch(&mut batch)?;
+            }
+            batch.write().await?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Moves a library that is stored in the legacy single doc to per game docs.
+async fn migrate(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let library = read_legacy(firestore, user_id).await?;
+    if library.entries.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "Migrating library of user '{user_id}' with {} entries.",
+        library.entries.len()
+    );
+    let entries = library.entries.iter().collect::<Vec<_>>();
+    commit(firestore, user_id, &entries, &[]).await?;
+    utils::users_delete(firestore, user_id, GAMES, LIBRARY_DOC).await
+}
+
+async fn read_legacy(firestore: &FirestoreApi, user_id: &str) -> Result<Library, Status> {
+    utils::users_read(firestore, user_id, GAMES, LIBRARY_DOC).await
+}
+
+/// Returns the page of the legacy `library` with the same order as per game
+/// docs.
+fn legacy_page(mut library: Library, after: u64, limit: usize) -> Vec<LibraryEntry> {
+    library.entries.sort_by_key(|entry| entry.id);
+    library
+        .entries
+        .into_iter()
+        .filter(|entry| entry.id > after)
+        .take(limit)
+        .collect()
+}
+
+/// Returns the entries of `library` that are new or changed compared to
+/// `current`, and the ids of the entries that were removed.
+fn diff<'a>(current: &Library, library: &'a Library) -> (Vec<&'a LibraryEntry>, Vec<u64>) {
+    let current = current
+        .entries
+        .iter()
+        .map(|entry| (entry.id, serde_json::to_value(entry).ok()))
+        .collect::<HashMap<_, _>>();
+
+    let updated = library
+        .entries
+        .iter()
+        .filter(|entry| match current.get(&entry.id) {
+            Some(Some(value)) => serde_json::to_value(entry).ok().as_ref() != Some(value),
+            _ => true,
+        })
+        .collect();
+
+    let ids = library
+        .entries
+        .iter()
+        .map(|entry| entry.id)
+        .collect::<HashSet<_>>();
+    let removed = current
+        .keys()
+        .filter(|id| !ids.contains(id))
+        .copied()
+        .collect();
+
+    (updated, removed)
+}
+
+/// Returns the events of games that are in `library` but not in `current` and
+/// the reverse.
+fn membership_events(current: &Library, library: &Library, timestamp: i64) -> Vec<LibraryEvent> {
+    let current_ids = current
+        .entries
+        .iter()
+        .map(|entry| entry.id)
+        .collect::<HashSet<_>>();
+    let ids = library
+        .entries
+        .iter()
+        .map(|entry| entry.id)
+        .collect::<HashSet<_>>();
+
+    let added = library
+        .entries
+        .iter()
+        .filter(|entry| !current_ids.contains(&entry.id))
+        .map(|entry| {
+            LibraryEvent::new(
+                LibraryEventKind::Added,
+                entry.id,
+                &entry.digest.name,
+                timestamp,
+            )
+        });
+    let removed = current
+        .entries
+        .iter()
+        .filter(|entry| !ids.contains(&entry.id))
+        .map(|entry| {
+            LibraryEvent::new(
+                LibraryEventKind::Removed,
+                entry.id,
+                &entry.digest.name,
+                timestamp,
+            )
+        });
+    added.chain(removed).collect()
+}
+
+// Subcollection of 'users/{user_id}' with a doc per library entry.
+const ENTRIES: &str = "library";
+
+// Legacy doc under 'users/{user_id}/games' with the whole library.
+const GAMES: &str = "games";
+const LIBRARY_DOC: &str = "library";
+
+// Firestore limits batch writes to 500 documents.
+const BATCH_SIZE: usize = 500;
+
 /// Adds `LibraryEntry` in the library.
 ///
 /// If an entry exists for the same game, it merges their store entries. Returns
@@ -420,4 +822,60 @@ mod tests {
         remove_storefront_entries("gog", &mut library);
         assert_eq!(library.entries.len(), 3);
     }
+
+    #[test]
+    fn diff_writes_only_changed_entries() {
+        let current = Library {
+            entries: vec![library_entry(7), library_entry(3), library_entry(5)],
+        };
+        let mut library = Library {
+            entries: current.entries.clone(),
+        };
+        library.entries.retain(|entry| entry.id != 5);
+        library.entries[1].digest.name = "Renamed".to_owned();
+        library.entries.push(library_entry(9));
+
+        let (updated, removed) = diff(&current, &library);
+        assert_eq!(
+            updated.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            vec![3, 9]
+        );
+        assert_eq!(removed, vec![5]);
+    }
+
+    #[test]
+    fn membership_events_of_added_and_removed_games() {
+        let current = Library {
+            entries: vec![library_entry(7), library_entry(3)],
+        };
+        let mut library = Library {
+            entries: current.entries.clone(),
+        };
+        library.entries.retain(|entry| entry.id != 7);
+        library.entries[0].digest.name = "Renamed".to_owned();
+        library.entries.push(library_entry(9));
+
+        assert_eq!(
+            membership_events(&current, &library, 100)
+                .iter()
+                .map(|event| (event.kind, event.game_id))
+                .collect::<Vec<_>>(),
+            vec![(LibraryEventKind::Added, 9), (LibraryEventKind::Removed, 7)]
+        );
+    }
+
+    #[test]
+    fn legacy_page_is_ordered_by_id() {
+        let library = Library {
+            entries: vec![library_entry(7), library_entry(3), library_entry(5)],
+        };
+
+        assert_eq!(
+            legacy_page(library, 3, 1)
+                .iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>(),
+            vec![5]
+        );
+    }
 }
@@ -0,0 +1,137 @@
+use firestore::{path, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::LibraryEvent, Status};
+
+use super::utils;
+
+/// Returns the library events of the user with timestamps (in seconds) in
+/// [`from`, `to`], in the order they happened.
+#[instrument(
+    name = "library_events::list",
+    level = "trace",
+    skip(firestore, user_id)
+)]
+pub async fn list(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<LibraryEvent>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("library_events::list", || async move {
+        let events: BoxStream<FirestoreResult<LibraryEvent>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(LIBRARY_EVENTS)
+            .parent(parent_path)
+            .filter(|q| {
+                q.for_all([
+                    q.field(path!(LibraryEvent::timestamp))
+                        .greater_than_or_equal(from),
+                    q.field(path!(LibraryEvent::timestamp))
+                        .less_than_or_equal(to),
+                ])
+            })
+            .order_by([(
+                path!(LibraryEvent::timestamp),
+                FirestoreQueryDirection::Ascending,
+            )])
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        events.try_collect::<Vec<LibraryEvent>>().await
+    })
+    .await
+}
+
+/// Records `events` in the user's library history.
+#[instrument(
+    name = "library_events::add",
+    level = "trace",
+    skip(firestore, user_id, events)
+)]
+pub async fn add(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    events: &[LibraryEvent],
+) -> Result<(), Status> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+    let batch_writer = &firestore.db().create_simple_batch_writer().await?;
+
+    for chunk in events.chunks(BATCH_SIZE) {
+        utils::run("library_events::add", || async move {
+            let mut batch = batch_writer.new_batch();
+            for event in chunk {
+                firestore
+                    .db()
+                    .fluent()
+                    .update()
+                    .in_col(LIBRARY_EVENTS)
+                    .document_id(&event.id)
+                    .parent(parent_path)
+                    .object(event)
+                    .add_to_batch(&mut batch)?;
+            }
+            batch.write().await?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Deletes the user's library history.
+#[instrument(
+    name = "library_events::purge",
+    level = "trace",
+    skip(firestore, user_id)
+)]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let events = list(firestore, user_id, i64::MIN, i64::MAX).await?;
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+    let batch_writer = &firestore.db().create_simple_batch_writer().await?;
+
+    for chunk in events.chunks(BATCH_SIZE) {
+        utils::run("library_events::purge", || async move {
+            let mut batch = batch_writer.new_batch();
+            for event in chunk {
+                firestore
+                    .db()
+                    .fluent()
+                    .delete()
+                    .from(LIBRARY_EVENTS)
+                    .parent(parent_path)
+                    .document_id(&event.id)
+                    .add_to_batch(&mut batch)?;
+            }
+            batch.write().await?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+// Subcollection of 'users/{user_id}' with a doc per library event.
+const LIBRARY_EVENTS: &str = "library_events";
+
+// Firestore limits batch writes to 500 documents.
+const BATCH_SIZE: usize = 500;
@@ -1,21 +1,46 @@
+pub mod access;
+pub mod account_deletions;
+pub mod backfill_queue;
+pub mod cache;
 pub mod collections;
 pub mod companies;
+pub mod deal_alerts;
 pub mod external_games;
+pub mod filters;
+pub mod follows;
 pub mod franchises;
 pub mod frontpage;
+pub mod game_edits;
 pub mod games;
 pub mod genres;
+pub mod igdb_snapshots;
 pub mod keywords;
+pub mod legacy_scores;
 pub mod library;
+pub mod library_events;
 pub mod notable;
+pub mod notifications;
+pub mod outbound_webhooks;
+pub mod overrides;
+pub mod platform_scope;
+pub mod prices;
+pub mod rejected;
+pub mod reports;
+pub mod resolve_queue;
+pub mod retry_queue;
 pub mod scores;
 pub mod storefront;
+pub mod sync_tokens;
+pub mod tech_info;
 pub mod timeline;
 pub mod unresolved;
 pub mod user_annotations;
 pub mod user_data;
+pub mod user_feed;
+pub mod webhook_deliveries;
+pub mod wikipedia;
 pub mod wishlist;
 pub mod year;
 
 mod utils;
-pub use utils::BatchReadResult;
+pub use utils::{BatchReadResult, ErrorClass};
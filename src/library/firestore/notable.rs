@@ -2,25 +2,51 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Notable, Status};
 
-use super::utils;
+use super::{access, utils};
 
 #[instrument(name = "notable::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi) -> Result<Notable, Status> {
-    Ok(utils::read(firestore, "espy", "notable".to_string())
-        .await
-        .unwrap_or_default())
+    if let Some(notable) = firestore.cache().notable.get(NOTABLE_CACHE_ID) {
+        return Ok(notable);
+    }
+
+    // Read errors are returned instead of caching an empty Notable, which
+    // would treat all games as not notable until the cache expires.
+    let notable = match utils::read(firestore, "espy", "notable".to_string()).await {
+        Ok(notable) => notable,
+        Err(Status::NotFound(_)) => Notable::default(),
+        Err(status) => return Err(status),
+    };
+    firestore
+        .cache()
+        .notable
+        .insert(NOTABLE_CACHE_ID, notable.clone());
+    Ok(notable)
 }
 
 #[instrument(name = "notable::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, notable: &Notable) -> Result<(), Status> {
+    access::check_write(firestore, "espy")?;
+
+    utils::run("notable::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection("espy"))
+            .document_id("notable")
+            .object(notable)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
     firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col("espy")
-        .document_id("notable")
-        .object(notable)
-        .execute()
-        .await?;
+        .cache()
+        .notable
+        .insert(NOTABLE_CACHE_ID, notable.clone());
     Ok(())
 }
+
+// Notable is a single document that is cached under a fixed key.
+const NOTABLE_CACHE_ID: u64 = 0;
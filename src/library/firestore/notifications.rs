@@ -0,0 +1,78 @@
+use crate::{
+    api::FirestoreApi,
+    documents::{Notification, Notifications},
+    Status,
+};
+use tracing::instrument;
+
+use super::utils;
+
+#[instrument(
+    name = "notifications::read",
+    level = "trace",
+    skip(firestore, user_id)
+)]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Notifications, Status> {
+    utils::users_read(firestore, user_id, USER_DATA, NOTIFICATIONS_DOC).await
+}
+
+/// Adds `notification` in the user's notifications.
+#[instrument(
+    name = "notifications::add",
+    level = "trace",
+    skip(firestore, user_id, notification),
+    fields(game_id = %notification.game_id),
+)]
+pub async fn add(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    notification: Notification,
+) -> Result<(), Status> {
+    let mut notifications = read(firestore, user_id).await?;
+    notifications.add(notification);
+    write(firestore, user_id, &notifications).await
+}
+
+/// Deletes the user's notifications document.
+#[instrument(
+    name = "notifications::purge",
+    level = "trace",
+    skip(firestore, user_id)
+)]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, USER_DATA, NOTIFICATIONS_DOC).await
+}
+
+#[instrument(
+    name = "notifications::write",
+    level = "trace",
+    skip(firestore, user_id, notifications)
+)]
+async fn write(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    notifications: &Notifications,
+) -> Result<(), Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("notifications::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(USER_DATA)
+            .document_id(NOTIFICATIONS_DOC)
+            .parent(parent_path)
+            .object(notifications)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const USER_DATA: &str = "user_data";
+const NOTIFICATIONS_DOC: &str = "notifications";
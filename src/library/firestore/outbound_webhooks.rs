@@ -0,0 +1,112 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::OutboundWebhook, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "outbound_webhooks::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: &str) -> Result<OutboundWebhook, Status> {
+    utils::read(firestore, OUTBOUND_WEBHOOKS, doc_id.to_owned()).await
+}
+
+/// Returns all outbound webhooks registered by `owner`.
+#[instrument(
+    name = "outbound_webhooks::list_by_owner",
+    level = "trace",
+    skip(firestore)
+)]
+pub async fn list_by_owner(
+    firestore: &FirestoreApi,
+    owner: &str,
+) -> Result<Vec<OutboundWebhook>, Status> {
+    utils::run("outbound_webhooks::list_by_owner", || async move {
+        let webhooks: BoxStream<FirestoreResult<OutboundWebhook>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(OUTBOUND_WEBHOOKS).as_str())
+            .filter(|q| q.for_all([q.field(path!(OutboundWebhook::owner)).equal(owner)]))
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        webhooks.try_collect::<Vec<OutboundWebhook>>().await
+    })
+    .await
+}
+
+/// Returns all outbound webhooks that follow updates of `game_id`.
+#[instrument(
+    name = "outbound_webhooks::list_following",
+    level = "trace",
+    skip(firestore)
+)]
+pub async fn list_following(
+    firestore: &FirestoreApi,
+    game_id: u64,
+) -> Result<Vec<OutboundWebhook>, Status> {
+    utils::run("outbound_webhooks::list_following", || async move {
+        let webhooks: BoxStream<FirestoreResult<OutboundWebhook>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(OUTBOUND_WEBHOOKS).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(OutboundWebhook::followed_games))
+                    .array_contains(game_id)])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        webhooks.try_collect::<Vec<OutboundWebhook>>().await
+    })
+    .await
+}
+
+#[instrument(
+    name = "outbound_webhooks::write",
+    level = "trace",
+    skip(firestore, webhook),
+    fields(
+        webhook_id = %webhook.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, webhook: &OutboundWebhook) -> Result<(), Status> {
+    access::check_write(firestore, OUTBOUND_WEBHOOKS)?;
+
+    utils::run("outbound_webhooks::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(OUTBOUND_WEBHOOKS))
+            .document_id(&webhook.id)
+            .object(webhook)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "outbound_webhooks::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: &str) -> Result<(), Status> {
+    access::check_write(firestore, OUTBOUND_WEBHOOKS)?;
+
+    utils::run("outbound_webhooks::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(OUTBOUND_WEBHOOKS).as_str())
+            .document_id(doc_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const OUTBOUND_WEBHOOKS: &str = "outbound_webhooks";
@@ -0,0 +1,54 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::Override, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "overrides::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, game_id: u64) -> Result<Override, Status> {
+    utils::read(firestore, OVERRIDES, game_id.to_string()).await
+}
+
+#[instrument(
+    name = "overrides::write",
+    level = "trace",
+    skip(firestore, game_override),
+    fields(game_id = %game_override.game_id)
+)]
+pub async fn write(firestore: &FirestoreApi, game_override: &Override) -> Result<(), Status> {
+    access::check_write(firestore, OVERRIDES)?;
+
+    utils::run("overrides::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(OVERRIDES))
+            .document_id(game_override.game_id.to_string())
+            .object(game_override)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "overrides::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, game_id: u64) -> Result<(), Status> {
+    access::check_write(firestore, OVERRIDES)?;
+
+    utils::run("overrides::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(OVERRIDES).as_str())
+            .document_id(game_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const OVERRIDES: &str = "overrides";
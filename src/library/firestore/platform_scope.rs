@@ -0,0 +1,37 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::PlatformScope, Status};
+
+use super::{access, utils};
+
+/// Returns the platforms that the deployment tracks. Deployments without a
+/// scope document use the default scope.
+#[instrument(name = "platform_scope::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi) -> Result<PlatformScope, Status> {
+    let scope: PlatformScope = utils::read(firestore, "espy", "platform_scope".to_string())
+        .await
+        .unwrap_or_default();
+    match scope.platforms.is_empty() {
+        true => Ok(PlatformScope::default()),
+        false => Ok(scope),
+    }
+}
+
+#[instrument(name = "platform_scope::write", level = "trace", skip(firestore))]
+pub async fn write(firestore: &FirestoreApi, scope: &PlatformScope) -> Result<(), Status> {
+    access::check_write(firestore, "espy")?;
+
+    utils::run("platform_scope::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection("espy"))
+            .document_id("platform_scope")
+            .object(scope)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
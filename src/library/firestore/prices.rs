@@ -0,0 +1,38 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::PriceHistory, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "prices::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, game_id: u64) -> Result<PriceHistory, Status> {
+    utils::read(firestore, PRICES, game_id.to_string()).await
+}
+
+#[instrument(
+    name = "prices::write",
+    level = "trace",
+    skip(firestore, prices),
+    fields(
+        game_id = %prices.game_id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, prices: &PriceHistory) -> Result<(), Status> {
+    access::check_write(firestore, PRICES)?;
+
+    utils::run("prices::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(PRICES))
+            .document_id(prices.game_id.to_string())
+            .object(prices)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const PRICES: &str = "prices";
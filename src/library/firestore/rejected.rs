@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use firestore::{path, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::{instrument, warn};
+
+use crate::{api::FirestoreApi, documents::RejectedGame, Status};
+
+use super::{access, utils};
+
+/// Returns all blocklisted games with the most recent first.
+#[instrument(name = "rejected::list", level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi) -> Result<Vec<RejectedGame>, Status> {
+    utils::run("rejected::list", || async move {
+        let rejected: BoxStream<FirestoreResult<RejectedGame>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(REJECTED).as_str())
+            .order_by([(
+                path!(RejectedGame::timestamp),
+                FirestoreQueryDirection::Descending,
+            )])
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        rejected.try_collect::<Vec<RejectedGame>>().await
+    })
+    .await
+}
+
+/// Returns the ids of all blocklisted games.
+#[instrument(name = "rejected::ids", level = "trace", skip(firestore))]
+pub async fn ids(firestore: &FirestoreApi) -> Result<HashSet<u64>, Status> {
+    if let Some(ids) = firestore.cache().rejected.get(REJECTED_CACHE_ID) {
+        return Ok(ids);
+    }
+
+    let ids = HashSet::from_iter(list(firestore).await?.into_iter().map(|game| game.id));
+    firestore
+        .cache()
+        .rejected
+        .insert(REJECTED_CACHE_ID, ids.clone());
+    Ok(ids)
+}
+
+/// Returns true if the game with `id` is blocklisted. Failures to read the
+/// blocklist are logged and treated as not blocklisted.
+pub async fn contains(firestore: &FirestoreApi, id: u64) -> bool {
+    match ids(firestore).await {
+        Ok(ids) => ids.contains(&id),
+        Err(status) => {
+            warn!("Failed to read blocklisted games: {status}");
+            false
+        }
+    }
+}
+
+#[instrument(
+    name = "rejected::add",
+    level = "trace",
+    skip(firestore, rejected),
+    fields(
+        game_id = %rejected.id,
+    )
+)]
+pub async fn add(firestore: &FirestoreApi, rejected: &RejectedGame) -> Result<(), Status> {
+    access::check_write(firestore, REJECTED)?;
+
+    utils::run("rejected::add", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(REJECTED))
+            .document_id(rejected.id.to_string())
+            .object(rejected)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+    firestore.cache().rejected.invalidate(REJECTED_CACHE_ID);
+    Ok(())
+}
+
+#[instrument(name = "rejected::remove", level = "trace", skip(firestore))]
+pub async fn remove(firestore: &FirestoreApi, id: u64) -> Result<(), Status> {
+    access::check_write(firestore, REJECTED)?;
+
+    utils::run("rejected::remove", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(REJECTED).as_str())
+            .document_id(id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+    firestore.cache().rejected.invalidate(REJECTED_CACHE_ID);
+    Ok(())
+}
+
+const REJECTED: &str = "rejected";
+
+// The blocklist is cached as a single set under a fixed key.
+const REJECTED_CACHE_ID: u64 = 0;
@@ -0,0 +1,59 @@
+use firestore::{path, FirestoreQueryDirection, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::Report, Status};
+
+use super::{access, utils};
+
+/// Returns all game reports with the most recent first.
+#[instrument(name = "reports::list", level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi) -> Result<Vec<Report>, Status> {
+    utils::run("reports::list", || async move {
+        let reports: BoxStream<FirestoreResult<Report>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(REPORTS).as_str())
+            .order_by([(
+                path!(Report::timestamp),
+                FirestoreQueryDirection::Descending,
+            )])
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        reports.try_collect::<Vec<Report>>().await
+    })
+    .await
+}
+
+/// Writes `report` in the reports collection. A report is keyed on the game
+/// and user ids, so that a user that reports the same game again replaces
+/// their previous report.
+#[instrument(
+    name = "reports::write",
+    level = "trace",
+    skip(firestore, report),
+    fields(
+        report_id = %report.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, report: &Report) -> Result<(), Status> {
+    access::check_write(firestore, REPORTS)?;
+
+    utils::run("reports::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(REPORTS))
+            .document_id(&report.id)
+            .object(report)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const REPORTS: &str = "reports";
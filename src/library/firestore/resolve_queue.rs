@@ -0,0 +1,85 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::ResolveTask, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "resolve_queue::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<ResolveTask, Status> {
+    utils::read(firestore, RESOLVE_QUEUE, doc_id.to_string()).await
+}
+
+/// Returns up to `limit` tasks that are not leased at `now`.
+#[instrument(name = "resolve_queue::list_ready", level = "trace", skip(firestore))]
+pub async fn list_ready(
+    firestore: &FirestoreApi,
+    now: i64,
+    limit: u32,
+) -> Result<Vec<ResolveTask>, Status> {
+    utils::run("resolve_queue::list_ready", || async move {
+        let tasks: BoxStream<FirestoreResult<ResolveTask>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(RESOLVE_QUEUE).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(ResolveTask::lease_until))
+                    .less_than_or_equal(now)])
+            })
+            .limit(limit)
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        tasks.try_collect::<Vec<ResolveTask>>().await
+    })
+    .await
+}
+
+#[instrument(
+    name = "resolve_queue::write",
+    level = "trace",
+    skip(firestore, task),
+    fields(
+        game_id = %task.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, task: &ResolveTask) -> Result<(), Status> {
+    access::check_write(firestore, RESOLVE_QUEUE)?;
+
+    utils::run("resolve_queue::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(RESOLVE_QUEUE))
+            .document_id(task.id.to_string())
+            .object(task)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "resolve_queue::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
+    access::check_write(firestore, RESOLVE_QUEUE)?;
+
+    utils::run("resolve_queue::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(RESOLVE_QUEUE).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const RESOLVE_QUEUE: &str = "resolve_queue";
@@ -0,0 +1,80 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::RetryEntry, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "retry_queue::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<RetryEntry, Status> {
+    utils::read(firestore, RETRY_QUEUE, doc_id.to_string()).await
+}
+
+/// Returns all entries whose next attempt is due at `now`.
+#[instrument(name = "retry_queue::list_due", level = "trace", skip(firestore))]
+pub async fn list_due(firestore: &FirestoreApi, now: i64) -> Result<Vec<RetryEntry>, Status> {
+    utils::run("retry_queue::list_due", || async move {
+        let entries: BoxStream<FirestoreResult<RetryEntry>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(RETRY_QUEUE).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(RetryEntry::next_attempt))
+                    .less_than_or_equal(now)])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        entries.try_collect::<Vec<RetryEntry>>().await
+    })
+    .await
+}
+
+#[instrument(
+    name = "retry_queue::write",
+    level = "trace",
+    skip(firestore, entry),
+    fields(
+        game_id = %entry.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, entry: &RetryEntry) -> Result<(), Status> {
+    access::check_write(firestore, RETRY_QUEUE)?;
+
+    utils::run("retry_queue::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(RETRY_QUEUE))
+            .document_id(entry.id.to_string())
+            .object(entry)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "retry_queue::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: u64) -> Result<(), Status> {
+    access::check_write(firestore, RETRY_QUEUE)?;
+
+    utils::run("retry_queue::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(RETRY_QUEUE).as_str())
+            .document_id(doc_id.to_string())
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const RETRY_QUEUE: &str = "retry_queue";
@@ -2,7 +2,7 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::ScoresDoc, Status};
 
-use super::utils;
+use super::{access, utils};
 
 #[instrument(name = "scores::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<ScoresDoc, Status> {
@@ -11,16 +11,21 @@ pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<ScoresDoc, St
 
 #[instrument(name = "scores::write", level = "trace", skip(firestore, game_entry))]
 pub async fn write(firestore: &FirestoreApi, game_entry: &ScoresDoc) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(SCORES)
-        .document_id(game_entry.id.to_string())
-        .object(game_entry)
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, SCORES)?;
+
+    utils::run("scores::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(SCORES))
+            .document_id(game_entry.id.to_string())
+            .object(game_entry)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const SCORES: &str = "scores";
@@ -1,21 +1,41 @@
-use std::collections::HashSet;
-use tracing::instrument;
+use std::collections::{BTreeMap, HashSet};
 
-use crate::{api::FirestoreApi, documents::StoreEntry, documents::Storefront, Status};
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{StoreEntry, Storefront, StorefrontShard},
+    Status,
+};
 
 use super::utils;
 
 /// Returns all store entries owned by user.
 ///
-/// Reads `users/{user_id}/games/storefront` document in Firestore.
+/// Reads the `users/{user_id}/storefronts` shards in Firestore.
 #[instrument(name = "storefront::read", level = "trace", skip(firestore, user_id))]
 pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Storefront, Status> {
-    utils::users_read(firestore, user_id, GAMES, STOREFRONT_DOC).await
+    let shards = read_shards(firestore, user_id, None).await?;
+    let mut storefront = read_legacy(firestore, user_id).await?;
+    storefront
+        .entries
+        .extend(shards.into_iter().flat_map(|shard| shard.entries));
+    Ok(storefront)
+}
+
+/// Deletes all storefront shards of the user.
+#[instrument(name = "storefront::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let shards = read_shards(firestore, user_id, None).await?;
+    commit(firestore, user_id, &[], &shards).await?;
+    utils::users_delete(firestore, user_id, GAMES, STOREFRONT_DOC).await
 }
 
-/// Writes the Storefront doc containing games owned by user.
+/// Replaces all store entries owned by user with the entries of `storefront`.
 ///
-/// Writes `users/{user_id}/games/storefront` document in Firestore.
+/// Writes the `users/{user_id}/storefronts` shards in Firestore.
 #[instrument(
     name = "storefront::write",
     level = "trace",
@@ -26,25 +46,35 @@ pub async fn write(
     user_id: &str,
     storefront: &Storefront,
 ) -> Result<(), Status> {
-    let parent_path = firestore.db().parent_path(utils::USERS, user_id)?;
+    let current = read_shards(firestore, user_id, None).await?;
 
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(GAMES)
-        .document_id(STOREFRONT_DOC)
-        .parent(&parent_path)
-        .object(storefront)
-        .execute()
-        .await?;
-    Ok(())
+    let mut stores = BTreeMap::<&str, Vec<StoreEntry>>::new();
+    for store_entry in &storefront.entries {
+        stores
+            .entry(&store_entry.storefront_name)
+            .or_default()
+            .push(store_entry.clone());
+    }
+    let shards = stores
+        .into_iter()
+        .flat_map(|(storefront_name, entries)| paginate(storefront_name, entries))
+        .collect::<Vec<_>>();
+
+    let doc_ids = shards
+        .iter()
+        .map(|shard| shard.doc_id())
+        .collect::<HashSet<_>>();
+    let removed = current
+        .into_iter()
+        .filter(|shard| !doc_ids.contains(&shard.doc_id()))
+        .collect::<Vec<_>>();
+
+    commit(firestore, user_id, &shards, &removed).await?;
+    utils::users_delete(firestore, user_id, GAMES, STOREFRONT_DOC).await
 }
 
 /// Returns input StoreEntries that are not already contained in user's
-/// Storefront document.
-///
-/// Reads `users/{user_id}/games/storefront` document in Firestore.
+/// storefronts.
 #[instrument(
     name = "storefront::diff_entries",
     level = "trace",
@@ -53,51 +83,60 @@ pub async fn write(
 pub async fn diff_entries(
     firestore: &FirestoreApi,
     user_id: &str,
-    mut store_entries: Vec<StoreEntry>,
+    store_entries: Vec<StoreEntry>,
 ) -> Result<Vec<StoreEntry>, Status> {
-    let game_ids = get_ids(firestore, user_id).await?;
-    store_entries.retain(|entry| !game_ids.contains(&entry.id));
+    Ok(diff(firestore, user_id, store_entries, &[]).await?.added)
+}
 
-    Ok(store_entries)
+/// Changes between the store entries fetched from storefronts and the ones
+/// already in the user's storefronts.
+#[derive(Default, Debug)]
+pub struct StorefrontDiff {
+    /// Fetched entries that are not in the user's storefronts.
+    pub added: Vec<StoreEntry>,
+
+    /// Entries of synced storefronts that were not fetched, e.g. refunded
+    /// games.
+    pub removed: Vec<StoreEntry>,
 }
 
-/// Returns set of store game ids owned by user from specified storefront.
-///
-/// Reads `users/{user_id}/games/storefront` document in Firestore.
+/// Compares `fetched` store entries against the user's storefronts. Entries
+/// are only reported as removed for the `synced` storefronts, whose full
+/// list of games was fetched.
 #[instrument(
-    name = "storefront::get_ids",
+    name = "storefront::diff",
     level = "trace",
-    skip(firestore, user_id)
+    skip(firestore, user_id, fetched)
 )]
-async fn get_ids(firestore: &FirestoreApi, user_id: &str) -> Result<HashSet<String>, Status> {
-    Ok(HashSet::from_iter(
-        read(firestore, user_id)
-            .await?
-            .entries
-            .into_iter()
-            .map(|e| e.id),
-    ))
+pub async fn diff(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    fetched: Vec<StoreEntry>,
+    synced: &[&str],
+) -> Result<StorefrontDiff, Status> {
+    let storefront = read(firestore, user_id).await?;
+    Ok(diff_store_entries(storefront.entries, fetched, synced))
 }
 
 /// Deletes all StoreEntries from specified storefront.
 ///
-/// Reads/Writes `users/{user_id}/games/storefront` document in Firestore.
+/// Deletes the shards of the storefront without reading other storefronts.
 #[instrument(name = "storefront::delete", level = "trace", skip(firestore, user_id))]
 pub async fn remove_store(
     firestore: &FirestoreApi,
     user_id: &str,
     storefront_name: &str,
 ) -> Result<(), Status> {
-    let mut storefront = read(firestore, user_id).await?;
-    storefront
-        .entries
-        .retain(|entry| entry.storefront_name != *storefront_name);
-    write(firestore, user_id, &storefront).await
+    migrate(firestore, user_id).await?;
+
+    let shards = read_shards(firestore, user_id, Some(storefront_name)).await?;
+    commit(firestore, user_id, &[], &shards).await
 }
 
-/// Add StoreEntry to the user's Storefront document.
+/// Add StoreEntries to the user's storefronts.
 ///
-/// Reads/Writes `users/{user_id}/games/storefront` document in Firestore.
+/// Entries are appended to the last shard of their storefront and new shards
+/// are created when it is full. Only shards that change are written.
 #[instrument(
     name = "storefront::add_entries",
     level = "trace",
@@ -108,27 +147,331 @@ pub async fn add_entries(
     user_id: &str,
     store_entries: Vec<StoreEntry>,
 ) -> Result<(), Status> {
-    let mut storefront = read(firestore, user_id).await?;
-    storefront.entries.extend(store_entries.into_iter());
-    write(firestore, user_id, &storefront).await
+    migrate(firestore, user_id).await?;
+
+    let mut stores = BTreeMap::<String, Vec<StoreEntry>>::new();
+    for store_entry in store_entries {
+        stores
+            .entry(store_entry.storefront_name.clone())
+            .or_default()
+            .push(store_entry);
+    }
+
+    for (storefront_name, store_entries) in stores {
+        let mut shards = read_shards(firestore, user_id, Some(&storefront_name)).await?;
+        let changed = append(&mut shards, &storefront_name, store_entries);
+        commit(firestore, user_id, &shards[changed..], &[]).await?;
+    }
+    Ok(())
 }
 
-/// Remove a StoreEntry from its Storefront.
+/// Remove a StoreEntry from its storefront.
 ///
-/// Reads/writes `users/{user}/storefronts/{storefront_name}` document in
-/// Firestore.
+/// Reads the shards of the entry's storefront and writes the one that
+/// contained it.
 #[instrument(name = "storefront::remove", level = "trace", skip(firestore, user_id))]
 pub async fn remove_entry(
     firestore: &FirestoreApi,
     user_id: &str,
     store_entry: &StoreEntry,
 ) -> Result<(), Status> {
-    let mut storefront = read(firestore, user_id).await?;
-    storefront
-        .entries
-        .retain(|e| e.id != store_entry.id || e.storefront_name != store_entry.storefront_name);
-    write(firestore, user_id, &storefront).await
+    migrate(firestore, user_id).await?;
+
+    let shards = read_shards(firestore, user_id, Some(&store_entry.storefront_name)).await?;
+    for mut shard in shards {
+        let len = shard.entries.len();
+        shard
+            .entries
+            .retain(|e| e.id != store_entry.id || e.storefront_name != store_entry.storefront_name);
+        if shard.entries.len() != len {
+            commit(firestore, user_id, &[shard], &[]).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the storefront shards of the user, ordered by page. If
+/// `storefront_name` is set only its shards are returned.
+async fn read_shards(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    storefront_name: Option<&str>,
+) -> Result<Vec<StorefrontShard>, Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    let mut shards = utils::run("storefront::read_shards", || async move {
+        let shards: BoxStream<FirestoreResult<StorefrontShard>> = match storefront_name {
+            Some(storefront_name) => {
+                firestore
+                    .db()
+                    .fluent()
+                    .select()
+                    .from(STOREFRONTS)
+                    .parent(parent_path)
+                    .filter(|q| {
+                        q.for_all([q
+                            .field(path!(StorefrontShard::storefront_name))
+                            .eq(storefront_name)])
+                    })
+                    .obj()
+                    .stream_query_with_errors()
+                    .await?
+            }
+            None => {
+                firestore
+                    .db()
+                    .fluent()
+                    .select()
+                    .from(STOREFRONTS)
+                    .parent(parent_path)
+                    .obj()
+                    .stream_query_with_errors()
+                    .await?
+            }
+        };
+
+        shards.try_collect::<Vec<StorefrontShard>>().await
+    })
+    .await?;
+    shards.sort_by(|l, r| (&l.storefront_name, l.page).cmp(&(&r.storefront_name, r.page)));
+    Ok(shards)
+}
+
+/// Writes `updated` shards and deletes `removed` shards in a batch.
+async fn commit(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    updated: &[StorefrontShard],
+    removed: &[StorefrontShard],
+) -> Result<(), Status> {
+    if updated.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+    let batch_writer = &firestore.db().create_simple_batch_writer().await?;
+
+    utils::run("storefront::commit", || async move {
+        let mut batch = batch_writer.new_batch();
+        for shard in updated {
+            firestore
+                .db()
+                .fluent()
+                .update()
+                .in_col(STOREFRONTS)
+                .document_id(shard.doc_id())
+                .parent(parent_path)
+                .object(shard)
+                .add_to_batch(&mut batch)?;
+        }
+        for shard in removed {
+            firestore
+                .db()
+                .fluent()
+                .delete()
+                .from(STOREFRONTS)
+                .parent(parent_path)
+                .document_id(shard.doc_id())
+                .add_to_batch(&mut batch)?;
+        }
+
+        batch.write().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Moves store entries of the legacy single storefront doc to shards.
+async fn migrate(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    let legacy = read_legacy(firestore, user_id).await?;
+    if legacy.entries.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "Migrating storefront of user '{user_id}' with {} entries.",
+        legacy.entries.len()
+    );
+    write(firestore, user_id, &read(firestore, user_id).await?).await
+}
+
+async fn read_legacy(firestore: &FirestoreApi, user_id: &str) -> Result<Storefront, Status> {
+    utils::users_read(firestore, user_id, GAMES, STOREFRONT_DOC).await
+}
+
+fn diff_store_entries(
+    known: Vec<StoreEntry>,
+    fetched: Vec<StoreEntry>,
+    synced: &[&str],
+) -> StorefrontDiff {
+    let key = |entry: &StoreEntry| (entry.storefront_name.clone(), entry.id.clone());
+    let known_keys = known.iter().map(key).collect::<HashSet<_>>();
+    let fetched_keys = fetched.iter().map(key).collect::<HashSet<_>>();
+
+    // A storefront that returns no games, e.g. a Steam profile that turned
+    // private, is not treated as if all its games were removed.
+    let synced = synced
+        .iter()
+        .copied()
+        .filter(|storefront_name| {
+            let empty = fetched
+                .iter()
+                .all(|entry| entry.storefront_name != *storefront_name);
+            if empty {
+                warn!("No games were fetched from '{storefront_name}', skipping removals.");
+            }
+            !empty
+        })
+        .collect::<HashSet<_>>();
+
+    StorefrontDiff {
+        added: fetched
+            .into_iter()
+            .filter(|entry| !known_keys.contains(&key(entry)))
+            .collect(),
+        removed: known
+            .into_iter()
+            .filter(|entry| synced.contains(entry.storefront_name.as_str()))
+            .filter(|entry| !fetched_keys.contains(&key(entry)))
+            .collect(),
+    }
+}
+
+/// Splits `entries` of a storefront in shards of `SHARD_SIZE`.
+fn paginate(storefront_name: &str, entries: Vec<StoreEntry>) -> Vec<StorefrontShard> {
+    let mut shards = vec![];
+    append(&mut shards, storefront_name, entries);
+    shards
+}
+
+/// Appends `entries` to the last of the storefront `shards`, adding new shards
+/// as they fill up. Returns the index of the first shard that changed.
+fn append(
+    shards: &mut Vec<StorefrontShard>,
+    storefront_name: &str,
+    entries: Vec<StoreEntry>,
+) -> usize {
+    if entries.is_empty() {
+        return shards.len();
+    }
+    let first_changed = match shards.last() {
+        Some(shard) if shard.entries.len() < SHARD_SIZE => shards.len() - 1,
+        _ => shards.len(),
+    };
+
+    for store_entry in entries {
+        if shards
+            .last()
+            .map_or(true, |shard| shard.entries.len() >= SHARD_SIZE)
+        {
+            let page = shards.last().map_or(0, |shard| shard.page + 1);
+            shards.push(StorefrontShard {
+                storefront_name: storefront_name.to_owned(),
+                page,
+                entries: vec![],
+            });
+        }
+        if let Some(shard) = shards.last_mut() {
+            shard.entries.push(store_entry);
+        }
+    }
+    first_changed
 }
 
+// Subcollection of 'users/{user_id}' with the storefront shards.
+const STOREFRONTS: &str = "storefronts";
+
+// Legacy doc under 'users/{user_id}/games' with all store entries.
 const GAMES: &str = "games";
 const STOREFRONT_DOC: &str = "storefront";
+
+// Store entries are a few hundred bytes, shards stay well below the 1MB limit.
+const SHARD_SIZE: usize = 1000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_entries(count: usize) -> Vec<StoreEntry> {
+        (0..count)
+            .map(|i| StoreEntry {
+                id: i.to_string(),
+                storefront_name: "steam".to_owned(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paginate_in_full_shards() {
+        let shards = paginate("steam", store_entries(2 * SHARD_SIZE + 1));
+
+        assert_eq!(
+            shards
+                .iter()
+                .map(|shard| (shard.doc_id(), shard.entries.len()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("steam_0".to_owned(), SHARD_SIZE),
+                ("steam_1".to_owned(), SHARD_SIZE),
+                ("steam_2".to_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn append_fills_last_shard() {
+        let mut shards = paginate("steam", store_entries(SHARD_SIZE + 1));
+
+        let changed = append(&mut shards, "steam", store_entries(SHARD_SIZE));
+        assert_eq!(changed, 1);
+        assert_eq!(shards.len(), 3);
+        assert_eq!(shards[1].entries.len(), SHARD_SIZE);
+        assert_eq!(shards[2].entries.len(), 1);
+    }
+
+    #[test]
+    fn append_to_full_shard_creates_new_one() {
+        let mut shards = paginate("steam", store_entries(SHARD_SIZE));
+
+        let changed = append(&mut shards, "steam", store_entries(1));
+        assert_eq!(changed, 1);
+        assert_eq!(shards[1].page, 1);
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed_of_synced_stores() {
+        let entry = |id: &str, storefront_name: &str| StoreEntry {
+            id: id.to_owned(),
+            storefront_name: storefront_name.to_owned(),
+            ..Default::default()
+        };
+
+        let diff = diff_store_entries(
+            vec![entry("1", "steam"), entry("2", "steam"), entry("3", "gog")],
+            vec![entry("1", "steam"), entry("4", "steam"), entry("2", "gog")],
+            &["steam"],
+        );
+        assert_eq!(diff.added, vec![entry("4", "steam"), entry("2", "gog")]);
+        assert_eq!(diff.removed, vec![entry("2", "steam")]);
+    }
+
+    #[test]
+    fn diff_skips_removals_of_empty_stores() {
+        let entry = StoreEntry {
+            id: "1".to_owned(),
+            storefront_name: "steam".to_owned(),
+            ..Default::default()
+        };
+
+        let diff = diff_store_entries(vec![entry], vec![], &["steam"]);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::SyncToken, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "sync_tokens::read", level = "trace", skip(firestore, doc_id))]
+pub async fn read(firestore: &FirestoreApi, doc_id: &str) -> Result<SyncToken, Status> {
+    utils::read(firestore, SYNC_TOKENS, doc_id.to_owned()).await
+}
+
+/// Returns all sync tokens minted for `user_id`.
+#[instrument(name = "sync_tokens::list_by_user", level = "trace", skip(firestore))]
+pub async fn list_by_user(
+    firestore: &FirestoreApi,
+    user_id: &str,
+) -> Result<Vec<SyncToken>, Status> {
+    utils::run("sync_tokens::list_by_user", || async move {
+        let tokens: BoxStream<FirestoreResult<SyncToken>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(SYNC_TOKENS).as_str())
+            .filter(|q| q.for_all([q.field(path!(SyncToken::user_id)).equal(user_id)]))
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        tokens.try_collect::<Vec<SyncToken>>().await
+    })
+    .await
+}
+
+#[instrument(
+    name = "sync_tokens::write",
+    level = "trace",
+    skip(firestore, token),
+    fields(
+        user_id = %token.user_id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, token: &SyncToken) -> Result<(), Status> {
+    access::check_write(firestore, SYNC_TOKENS)?;
+
+    utils::run("sync_tokens::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(SYNC_TOKENS))
+            .document_id(&token.id)
+            .object(token)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "sync_tokens::delete", level = "trace", skip(firestore, doc_id))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: &str) -> Result<(), Status> {
+    access::check_write(firestore, SYNC_TOKENS)?;
+
+    utils::run("sync_tokens::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(SYNC_TOKENS).as_str())
+            .document_id(doc_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const SYNC_TOKENS: &str = "sync_tokens";
@@ -0,0 +1,35 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::TechInfo, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "tech_info::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, game_id: u64) -> Result<TechInfo, Status> {
+    utils::read(firestore, TECH_INFO, game_id.to_string()).await
+}
+
+#[instrument(name = "tech_info::write", level = "trace", skip(firestore, tech_info))]
+pub async fn write(
+    firestore: &FirestoreApi,
+    game_id: u64,
+    tech_info: &TechInfo,
+) -> Result<(), Status> {
+    access::check_write(firestore, TECH_INFO)?;
+
+    utils::run("tech_info::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(TECH_INFO))
+            .document_id(game_id.to_string())
+            .object(tech_info)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const TECH_INFO: &str = "tech_info";
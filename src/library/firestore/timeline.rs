@@ -2,16 +2,53 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::Timeline, Status};
 
+use super::{access, utils};
+
+#[instrument(name = "timeline::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi) -> Result<Timeline, Status> {
+    utils::read(firestore, "espy", "timeline".to_string()).await
+}
+
+/// Returns the timeline doc from the connection cache when available.
+///
+/// Suitable for serving reads. Services that serve the timeline keep it warm
+/// with `refresh()`.
+#[instrument(name = "timeline::lookup", level = "trace", skip(firestore))]
+pub async fn lookup(firestore: &FirestoreApi) -> Result<Timeline, Status> {
+    match firestore.cache().timeline.get(DOC_KEY) {
+        Some(timeline) => Ok(timeline),
+        None => refresh(firestore).await,
+    }
+}
+
+/// Reads the timeline doc from Firestore and stores it in the connection cache.
+#[instrument(name = "timeline::refresh", level = "trace", skip(firestore))]
+pub async fn refresh(firestore: &FirestoreApi) -> Result<Timeline, Status> {
+    let timeline = read(firestore).await?;
+    firestore.cache().timeline.insert(DOC_KEY, timeline.clone());
+    Ok(timeline)
+}
+
 #[instrument(name = "timeline::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, timeline: &Timeline) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col("espy")
-        .document_id("timeline")
-        .object(timeline)
-        .execute()
-        .await?;
+    access::check_write(firestore, "espy")?;
+
+    utils::run("timeline::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection("espy"))
+            .document_id("timeline")
+            .object(timeline)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await?;
+    firestore.cache().timeline.insert(DOC_KEY, timeline.clone());
     Ok(())
 }
+
+// The connection cache is keyed by id, the timeline is a single doc.
+const DOC_KEY: u64 = 0;
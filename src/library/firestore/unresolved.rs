@@ -106,6 +106,12 @@ pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<UnresolvedE
     utils::users_read(firestore, user_id, GAMES, UNRESOLVED_DOC).await
 }
 
+/// Deletes the user's unresolved document.
+#[instrument(name = "unresolved::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, GAMES, UNRESOLVED_DOC).await
+}
+
 #[instrument(
     name = "unresolved::write",
     level = "trace",
@@ -116,19 +122,25 @@ pub async fn write(
     user_id: &str,
     unresolved: &UnresolvedEntries,
 ) -> Result<(), Status> {
-    let parent_path = firestore.db().parent_path(utils::USERS, user_id)?;
-
-    firestore
+    let parent_path = firestore
         .db()
-        .fluent()
-        .update()
-        .in_col(GAMES)
-        .document_id(UNRESOLVED_DOC)
-        .parent(&parent_path)
-        .object(unresolved)
-        .execute()
-        .await?;
-    Ok(())
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("unresolved::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(GAMES)
+            .document_id(UNRESOLVED_DOC)
+            .parent(parent_path)
+            .object(unresolved)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const GAMES: &str = "games";
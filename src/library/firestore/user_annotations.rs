@@ -12,6 +12,16 @@ pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<UserAnnotat
     utils::users_read(firestore, user_id, USER_DATA, TAGS_DOC).await
 }
 
+/// Deletes the user's annotations document.
+#[instrument(
+    name = "user_annotations::purge",
+    level = "trace",
+    skip(firestore, user_id)
+)]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, USER_DATA, TAGS_DOC).await
+}
+
 #[instrument(
     name = "user_annotations::write",
     level = "trace",
@@ -22,19 +32,25 @@ async fn write(
     user_id: &str,
     user_annotations: &UserAnnotations,
 ) -> Result<(), Status> {
-    let parent_path = firestore.db().parent_path(utils::USERS, user_id)?;
-
-    firestore
+    let parent_path = firestore
         .db()
-        .fluent()
-        .update()
-        .in_col(USER_DATA)
-        .document_id(TAGS_DOC)
-        .parent(&parent_path)
-        .object(user_annotations)
-        .execute()
-        .await?;
-    Ok(())
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("user_annotations::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(USER_DATA)
+            .document_id(TAGS_DOC)
+            .parent(parent_path)
+            .object(user_annotations)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const USER_DATA: &str = "user_data";
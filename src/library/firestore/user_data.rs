@@ -1,17 +1,24 @@
+use firestore::FirestoreResult;
+use futures::{stream::BoxStream, TryStreamExt};
 use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::UserData, Status};
 
+use super::utils;
+
 #[instrument(name = "users::read", level = "trace", skip(firestore))]
 pub async fn read(firestore: &FirestoreApi, doc_id: &str) -> Result<UserData, Status> {
-    let doc = firestore
-        .db()
-        .fluent()
-        .select()
-        .by_id_in(USERS)
-        .obj()
-        .one(doc_id)
-        .await?;
+    let doc = utils::run("users::read", || async move {
+        firestore
+            .db()
+            .fluent()
+            .select()
+            .by_id_in(&firestore.collection(USERS))
+            .obj()
+            .one(doc_id)
+            .await
+    })
+    .await?;
 
     match doc {
         Some(doc) => Ok(doc),
@@ -21,18 +28,54 @@ pub async fn read(firestore: &FirestoreApi, doc_id: &str) -> Result<UserData, St
     }
 }
 
+/// Returns all users.
+#[instrument(name = "users::list", level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi) -> Result<Vec<UserData>, Status> {
+    utils::run("users::list", || async move {
+        let users: BoxStream<FirestoreResult<UserData>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(USERS).as_str())
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        users.try_collect::<Vec<UserData>>().await
+    })
+    .await
+}
+
 #[instrument(name = "users::write", level = "trace", skip(firestore))]
 pub async fn write(firestore: &FirestoreApi, user_data: &UserData) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col(USERS)
-        .document_id(&user_data.uid)
-        .object(user_data)
-        .execute()
-        .await?;
-    Ok(())
+    utils::run("users::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(USERS))
+            .document_id(&user_data.uid)
+            .object(user_data)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+#[instrument(name = "users::delete", level = "trace", skip(firestore))]
+pub async fn delete(firestore: &FirestoreApi, doc_id: &str) -> Result<(), Status> {
+    utils::run("users::delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(firestore.collection(USERS).as_str())
+            .document_id(doc_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 const USERS: &str = "users";
@@ -0,0 +1,45 @@
+use crate::{api::FirestoreApi, documents::UserFeed, Status};
+use tracing::instrument;
+
+use super::utils;
+
+#[instrument(name = "user_feed::read", level = "trace", skip(firestore, user_id))]
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<UserFeed, Status> {
+    utils::users_read(firestore, user_id, USER_DATA, FEED_DOC).await
+}
+
+/// Deletes the user's feed document.
+#[instrument(name = "user_feed::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, USER_DATA, FEED_DOC).await
+}
+
+#[instrument(
+    name = "user_feed::write",
+    level = "trace",
+    skip(firestore, user_id, feed)
+)]
+pub async fn write(firestore: &FirestoreApi, user_id: &str, feed: &UserFeed) -> Result<(), Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    utils::run("user_feed::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(USER_DATA)
+            .document_id(FEED_DOC)
+            .parent(parent_path)
+            .object(feed)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const USER_DATA: &str = "user_data";
+const FEED_DOC: &str = "feed";
@@ -1,24 +1,118 @@
-use std::fmt::Display;
+use std::{fmt::Display, future::Future, time::Duration};
 
 use firestore::{errors::FirestoreError, FirestoreResult};
 use futures::{stream::BoxStream, StreamExt};
 use tracing::warn;
 
-use crate::{api::FirestoreApi, Status};
+use crate::{api::FirestoreApi, logging::FirestoreEvent, Status};
+
+/// Classes of Firestore errors that callers handle differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transient errors, e.g. network failures or an unavailable backend.
+    Retryable,
+
+    /// Operations that conflicted with concurrent writes to the same
+    /// documents.
+    Contention,
+
+    NotFound,
+    Permission,
+
+    /// Errors that retrying does not fix, e.g. documents that fail to parse.
+    Permanent,
+}
+
+impl ErrorClass {
+    pub fn of(error: &FirestoreError) -> Self {
+        match error {
+            FirestoreError::DataNotFoundError(_) => ErrorClass::NotFound,
+            FirestoreError::DataConflictError(_) => ErrorClass::Contention,
+            FirestoreError::NetworkError(_) => ErrorClass::Retryable,
+            FirestoreError::DatabaseError(e) => match e.public.code.as_str() {
+                "Aborted" => ErrorClass::Contention,
+                "NotFound" => ErrorClass::NotFound,
+                "PermissionDenied" | "Unauthenticated" => ErrorClass::Permission,
+                _ if e.retry_possible => ErrorClass::Retryable,
+                _ => ErrorClass::Permanent,
+            },
+            _ => ErrorClass::Permanent,
+        }
+    }
+
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorClass::Retryable | ErrorClass::Contention)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::Retryable => "retryable",
+            ErrorClass::Contention => "contention",
+            ErrorClass::NotFound => "not_found",
+            ErrorClass::Permission => "permission",
+            ErrorClass::Permanent => "permanent",
+        }
+    }
+}
+
+/// Runs the Firestore operation `op` and converts its error to a `Status`.
+///
+/// Operations that fail with retryable or contention errors are retried with
+/// exponential backoff up to `ATTEMPTS` times. `name` identifies the
+/// operation in the emitted `FirestoreEvent` counters, e.g. "games::write".
+pub async fn run<T, F, Fut>(name: &str, op: F) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = FirestoreResult<T>>,
+{
+    retry(name, op).await.map_err(Status::from)
+}
+
+/// Like `run` but returns the Firestore error of the last attempt, for callers
+/// that add context to it.
+pub async fn retry<T, F, Fut>(name: &str, mut op: F) -> FirestoreResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = FirestoreResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(result) => return Ok(result),
+            Err(error) => {
+                let class = ErrorClass::of(&error);
+                if class.is_retryable() && attempt < ATTEMPTS {
+                    let backoff = BASE_BACKOFF * 2u32.pow(attempt - 1);
+                    FirestoreEvent::retry(name, class.as_str(), attempt, backoff);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                    continue;
+                }
+
+                FirestoreEvent::error(name, class.as_str(), attempt, &error.to_string());
+                return Err(error);
+            }
+        }
+    }
+}
 
 pub async fn read<Document: serde::de::DeserializeOwned + Send>(
     firestore: &FirestoreApi,
     collection: &str,
     doc_id: String,
 ) -> Result<Document, Status> {
-    let doc = firestore
-        .db()
-        .fluent()
-        .select()
-        .by_id_in(collection)
-        .obj()
-        .one(doc_id.clone())
-        .await;
+    let doc_id = &doc_id;
+    let doc = retry("utils::read", || async move {
+        firestore
+            .db()
+            .fluent()
+            .select()
+            .by_id_in(&firestore.collection(collection))
+            .obj()
+            .one(doc_id)
+            .await
+    })
+    .await;
 
     match doc {
         Ok(doc) => match doc {
@@ -37,17 +131,23 @@ pub async fn users_read<Document: serde::de::DeserializeOwned + Default + Send>(
     collection: &str,
     doc_id: &str,
 ) -> Result<Document, Status> {
-    let parent_path = firestore.db().parent_path(USERS, user_id)?;
-
-    let doc = firestore
+    let parent_path = firestore
         .db()
-        .fluent()
-        .select()
-        .by_id_in(collection)
-        .parent(&parent_path)
-        .obj()
-        .one(doc_id)
-        .await;
+        .parent_path(&firestore.collection(USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    let doc = retry("utils::users_read", || async move {
+        firestore
+            .db()
+            .fluent()
+            .select()
+            .by_id_in(collection)
+            .parent(parent_path)
+            .obj()
+            .one(doc_id)
+            .await
+    })
+    .await;
 
     match doc {
         Ok(doc) => match doc {
@@ -62,18 +162,49 @@ pub async fn users_read<Document: serde::de::DeserializeOwned + Default + Send>(
     }
 }
 
+/// Deletes the `users/{user_id}/{collection}/{doc_id}` document.
+pub async fn users_delete(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    collection: &str,
+    doc_id: &str,
+) -> Result<(), Status> {
+    let parent_path = firestore
+        .db()
+        .parent_path(&firestore.collection(USERS), user_id)?;
+    let parent_path = &parent_path;
+
+    run("utils::users_delete", || async move {
+        firestore
+            .db()
+            .fluent()
+            .delete()
+            .from(collection)
+            .parent(parent_path)
+            .document_id(doc_id)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
 pub async fn batch_read<Document: serde::de::DeserializeOwned + Send>(
     firestore: &FirestoreApi,
     collection: &str,
     doc_ids: &[u64],
 ) -> Result<BatchReadResult<Document>, Status> {
-    let mut docs: BoxStream<FirestoreResult<(String, Option<Document>)>> = firestore
-        .db()
-        .fluent()
-        .select()
-        .by_id_in(collection)
-        .obj()
-        .batch_with_errors(doc_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+    let mut docs: BoxStream<FirestoreResult<(String, Option<Document>)>> =
+        run("utils::batch_read", || async move {
+            firestore
+                .db()
+                .fluent()
+                .select()
+                .by_id_in(&firestore.collection(collection))
+                .obj()
+                .batch_with_errors(doc_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+                .await
+        })
         .await?;
 
     let mut documents = vec![];
@@ -95,6 +226,42 @@ pub async fn batch_read<Document: serde::de::DeserializeOwned + Send>(
     })
 }
 
+/// Writes `docs` under their doc ids in batches of up to `BATCH_SIZE`
+/// documents. Each batch is applied atomically, so a failed write leaves all
+/// documents of the batch unmodified.
+pub async fn batch_write<Document: serde::Serialize + Send + Sync>(
+    firestore: &FirestoreApi,
+    collection: &str,
+    docs: &[(String, &Document)],
+) -> Result<(), Status> {
+    if docs.is_empty() {
+        return Ok(());
+    }
+
+    let collection = &firestore.collection(collection);
+    let batch_writer = &firestore.db().create_simple_batch_writer().await?;
+
+    for chunk in docs.chunks(BATCH_SIZE) {
+        run("utils::batch_write", || async move {
+            let mut batch = batch_writer.new_batch();
+            for (doc_id, doc) in chunk {
+                firestore
+                    .db()
+                    .fluent()
+                    .update()
+                    .in_col(collection)
+                    .document_id(doc_id)
+                    .object(*doc)
+                    .add_to_batch(&mut batch)?;
+            }
+            batch.write().await?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct BatchReadResult<Document> {
     pub documents: Vec<Document>,
@@ -111,8 +278,44 @@ pub fn make_status<S: Into<String> + Display>(
             "Firestore '{collection}/{doc_id}' document failed to parse with error '{}'",
             e.message,
         )),
-        e => Status::internal(format!("Firestore '{collection}/{doc_id}' error: {e}")),
+        e => {
+            let msg = format!("Firestore '{collection}/{doc_id}' error: {e}");
+            match ErrorClass::of(&e) {
+                ErrorClass::Retryable | ErrorClass::Contention => Status::unavailable(msg),
+                ErrorClass::NotFound => Status::not_found(msg),
+                ErrorClass::Permission => Status::permission_denied(msg),
+                ErrorClass::Permanent => Status::internal(msg),
+            }
+        }
+    }
+}
+
+/// Returns the user id of a 'users/{user_id}/{collection}/{doc_id}' document
+/// with the full resource `doc_name`, or None for documents elsewhere, e.g.
+/// of environments with another collection prefix.
+pub fn users_doc_owner(
+    firestore: &FirestoreApi,
+    doc_name: &str,
+    collection: &str,
+) -> Option<String> {
+    let (_, path) = doc_name.split_once("/documents/")?;
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [users, user_id, subcollection, _]
+            if *users == firestore.collection(USERS) && *subcollection == collection =>
+        {
+            Some((*user_id).to_owned())
+        }
+        _ => None,
     }
 }
 
 pub const USERS: &str = "users";
+
+// Attempts of Firestore operations that fail with retryable errors.
+const ATTEMPTS: u32 = 3;
+
+// Backoff before the first retry, which doubles on each following one.
+const BASE_BACKOFF: Duration = Duration::from_millis(200);
+
+// Firestore limits batch writes to 500 documents.
+const BATCH_SIZE: usize = 500;
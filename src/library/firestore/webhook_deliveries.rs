@@ -0,0 +1,69 @@
+use firestore::{path, FirestoreResult};
+use futures::{stream::BoxStream, TryStreamExt};
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::WebhookDelivery, Status};
+
+use super::{access, utils};
+
+/// Returns all pending deliveries whose next attempt is due at `now`.
+#[instrument(
+    name = "webhook_deliveries::list_due",
+    level = "trace",
+    skip(firestore)
+)]
+pub async fn list_due(firestore: &FirestoreApi, now: i64) -> Result<Vec<WebhookDelivery>, Status> {
+    // Filter due deliveries locally to avoid the need of a composite index.
+    let deliveries = utils::run("webhook_deliveries::list_due", || async move {
+        let deliveries: BoxStream<FirestoreResult<WebhookDelivery>> = firestore
+            .db()
+            .fluent()
+            .select()
+            .from(firestore.collection(WEBHOOK_DELIVERIES).as_str())
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(WebhookDelivery::status))
+                    .equal(PENDING_STATUS)])
+            })
+            .obj()
+            .stream_query_with_errors()
+            .await?;
+        deliveries.try_collect::<Vec<WebhookDelivery>>().await
+    })
+    .await?;
+    Ok(deliveries
+        .into_iter()
+        .filter(|delivery| delivery.next_attempt <= now)
+        .collect())
+}
+
+#[instrument(
+    name = "webhook_deliveries::write",
+    level = "trace",
+    skip(firestore, delivery),
+    fields(
+        delivery_id = %delivery.id,
+    )
+)]
+pub async fn write(firestore: &FirestoreApi, delivery: &WebhookDelivery) -> Result<(), Status> {
+    access::check_write(firestore, WEBHOOK_DELIVERIES)?;
+
+    utils::run("webhook_deliveries::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(WEBHOOK_DELIVERIES))
+            .document_id(&delivery.id)
+            .object(delivery)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const WEBHOOK_DELIVERIES: &str = "webhook_deliveries";
+
+// Serialized value of `DeliveryStatus::Pending`.
+const PENDING_STATUS: &str = "Pending";
@@ -0,0 +1,31 @@
+use tracing::instrument;
+
+use crate::{api::FirestoreApi, documents::WikipediaPage, Status};
+
+use super::{access, utils};
+
+#[instrument(name = "wikipedia::read", level = "trace", skip(firestore))]
+pub async fn read(firestore: &FirestoreApi, doc_id: u64) -> Result<WikipediaPage, Status> {
+    utils::read(firestore, WIKIPEDIA, doc_id.to_string()).await
+}
+
+#[instrument(name = "wikipedia::write", level = "trace", skip(firestore, page))]
+pub async fn write(firestore: &FirestoreApi, page: &WikipediaPage) -> Result<(), Status> {
+    access::check_write(firestore, WIKIPEDIA)?;
+
+    utils::run("wikipedia::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection(WIKIPEDIA))
+            .document_id(page.id.to_string())
+            .object(page)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+const WIKIPEDIA: &str = "wikipedia";
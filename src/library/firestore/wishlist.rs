@@ -3,6 +3,8 @@ use crate::{
     documents::{GameDigest, Library, LibraryEntry},
     Status,
 };
+use firestore::{path, paths, FirestoreDocument};
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use super::utils;
@@ -104,37 +106,97 @@ fn remove(game_id: u64, wishlist: &mut Library) -> bool {
 }
 
 #[instrument(name = "wishlist::read", level = "trace", skip(firestore, user_id))]
-async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Library, Status> {
+pub async fn read(firestore: &FirestoreApi, user_id: &str) -> Result<Library, Status> {
     utils::users_read(firestore, user_id, GAMES, WISHLIST_DOC).await
 }
 
+/// Returns the ids of users that have `game_id` in their wishlist.
+///
+/// Wishlists that were not written since they started to index their game
+/// ids are not found.
+#[instrument(name = "wishlist::list_owners", level = "trace", skip(firestore))]
+pub async fn list_owners(firestore: &FirestoreApi, game_id: u64) -> Result<Vec<String>, Status> {
+    // Collection group query over the 'games' docs of all users. Only
+    // wishlist docs index their game ids.
+    let docs = utils::run("wishlist::list_owners", || async move {
+        let docs: Vec<FirestoreDocument> = firestore
+            .db()
+            .fluent()
+            .select()
+            .fields(paths!(WishlistDoc::game_ids))
+            .from(GAMES)
+            .all_descendants()
+            .filter(|q| {
+                q.for_all([q
+                    .field(path!(WishlistDoc::game_ids))
+                    .array_contains(game_id)])
+            })
+            .query()
+            .await?;
+        Ok(docs)
+    })
+    .await?;
+
+    Ok(docs
+        .iter()
+        .filter_map(|doc| utils::users_doc_owner(firestore, &doc.name, GAMES))
+        .collect())
+}
+
+/// Deletes the user's wishlist document.
+#[instrument(name = "wishlist::purge", level = "trace", skip(firestore, user_id))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    utils::users_delete(firestore, user_id, GAMES, WISHLIST_DOC).await
+}
+
 #[instrument(
     name = "wishlist::write",
     level = "trace",
     skip(firestore, user_id, library)
 )]
-async fn write(
+pub async fn write(
     firestore: &FirestoreApi,
     user_id: &str,
     mut library: Library,
 ) -> Result<(), Status> {
-    library
-        .entries
-        .sort_by(|l, r| r.digest.release_date.cmp(&l.digest.release_date));
+    // Entries with placeholder dates are sorted after exact dates that fall on
+    // the same day, as they can be released any time within their period.
+    library.entries.sort_by(|l, r| {
+        (r.digest.release_date, r.digest.release_precision)
+            .cmp(&(l.digest.release_date, l.digest.release_precision))
+    });
 
-    let parent_path = firestore.db().parent_path(utils::USERS, user_id)?;
-
-    firestore
+    let parent_path = firestore
         .db()
-        .fluent()
-        .update()
-        .in_col(GAMES)
-        .document_id(WISHLIST_DOC)
-        .parent(&parent_path)
-        .object(&library)
-        .execute()
-        .await?;
-    Ok(())
+        .parent_path(&firestore.collection(utils::USERS), user_id)?;
+    let parent_path = &parent_path;
+    let doc = &WishlistDoc {
+        game_ids: library.entries.iter().map(|entry| entry.id).collect(),
+        entries: library.entries,
+    };
+
+    utils::run("wishlist::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(GAMES)
+            .document_id(WISHLIST_DOC)
+            .parent(parent_path)
+            .object(doc)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Stored form of a wishlist, which also indexes its game ids so that the
+/// owners of a game can be queried.
+#[derive(Serialize, Deserialize, Debug)]
+struct WishlistDoc {
+    entries: Vec<LibraryEntry>,
+    game_ids: Vec<u64>,
 }
 
 const GAMES: &str = "games";
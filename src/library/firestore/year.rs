@@ -2,20 +2,27 @@ use tracing::instrument;
 
 use crate::{api::FirestoreApi, documents::AnnualReview, Status};
 
+use super::{access, utils};
+
 #[instrument(name = "year::write", level = "trace", skip(firestore))]
 pub async fn write(
     firestore: &FirestoreApi,
     review: &AnnualReview,
     year: u64,
 ) -> Result<(), Status> {
-    firestore
-        .db()
-        .fluent()
-        .update()
-        .in_col("espy")
-        .document_id(format!("{year}"))
-        .object(review)
-        .execute()
-        .await?;
-    Ok(())
+    access::check_write(firestore, "espy")?;
+
+    utils::run("year::write", || async move {
+        firestore
+            .db()
+            .fluent()
+            .update()
+            .in_col(&firestore.collection("espy"))
+            .document_id(format!("{year}"))
+            .object(review)
+            .execute()
+            .await?;
+        Ok(())
+    })
+    .await
 }
@@ -0,0 +1,209 @@
+use serde::Deserialize;
+
+use crate::{documents::StoreEntry, Status};
+
+/// Parses a CSV file with a header row into StoreEntries.
+///
+/// Recognizes title columns as exported by common trackers (e.g. Backloggd)
+/// and the `releaseKey` column of GOG Galaxy exports, e.g. `steam_1091500`.
+pub fn parse_csv(data: &[u8]) -> Result<Vec<StoreEntry>, Status> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let mut store_entries = vec![];
+    for row in reader.deserialize::<ImportRow>() {
+        match row {
+            Ok(row) => store_entries.extend(row.into_store_entry()),
+            Err(e) => return Err(Status::invalid_argument(format!("Invalid CSV row: {e}"))),
+        }
+    }
+    Ok(dedup(store_entries))
+}
+
+/// Parses a JSON array into StoreEntries. Array items can be either objects
+/// with the same fields as CSV rows or plain titles.
+pub fn parse_json(data: &[u8]) -> Result<Vec<StoreEntry>, Status> {
+    let rows = match serde_json::from_slice::<Vec<JsonRow>>(data) {
+        Ok(rows) => rows,
+        Err(e) => return Err(Status::invalid_argument(format!("Invalid JSON: {e}"))),
+    };
+
+    Ok(dedup(
+        rows.into_iter()
+            .filter_map(|row| match row {
+                JsonRow::Title(title) => ImportRow {
+                    title,
+                    ..Default::default()
+                }
+                .into_store_entry(),
+                JsonRow::Row(row) => row.into_store_entry(),
+            })
+            .collect(),
+    ))
+}
+
+/// Parses a plain text list with one title per line into StoreEntries.
+pub fn parse_text(data: &[u8]) -> Result<Vec<StoreEntry>, Status> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(e) => return Err(Status::invalid_argument(format!("Invalid text: {e}"))),
+    };
+
+    Ok(dedup(
+        text.lines()
+            .filter_map(|line| {
+                ImportRow {
+                    title: line.to_owned(),
+                    ..Default::default()
+                }
+                .into_store_entry()
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct ImportRow {
+    #[serde(default)]
+    #[serde(alias = "Title", alias = "name", alias = "Name", alias = "Game")]
+    title: String,
+
+    #[serde(default)]
+    #[serde(alias = "Storefront", alias = "platform", alias = "Platform")]
+    storefront: String,
+
+    #[serde(default)]
+    #[serde(alias = "appid", alias = "product_id")]
+    id: Option<ImportId>,
+
+    #[serde(default)]
+    #[serde(rename = "releaseKey")]
+    release_key: String,
+}
+
+impl ImportRow {
+    /// Returns a StoreEntry for the row. Rows that reference a supported
+    /// storefront keep their store id so that they can be matched directly
+    /// through external games, while the rest are keyed by their title and
+    /// go through title search.
+    fn into_store_entry(self) -> Option<StoreEntry> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return None;
+        }
+
+        let id = match self.id {
+            Some(ImportId::Number(id)) => id.to_string(),
+            Some(ImportId::Text(id)) => id.trim().to_owned(),
+            None => String::default(),
+        };
+        let (storefront, id) = match self.release_key.split_once('_') {
+            Some((storefront, id)) if !id.is_empty() => (storefront.to_lowercase(), id.to_owned()),
+            _ => (self.storefront.trim().to_lowercase(), id),
+        };
+
+        Some(
+            match STOREFRONTS.contains(&storefront.as_str()) && !id.is_empty() {
+                true => StoreEntry {
+                    id,
+                    title: title.to_owned(),
+                    storefront_name: storefront,
+                    ..Default::default()
+                },
+                false => StoreEntry {
+                    id: title_id(title),
+                    title: title.to_owned(),
+                    storefront_name: IMPORT_STOREFRONT.to_owned(),
+                    ..Default::default()
+                },
+            },
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ImportId {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum JsonRow {
+    Title(String),
+    Row(ImportRow),
+}
+
+/// Returns a stable id for a title-only entry, so that importing the same
+/// list twice does not create duplicates.
+fn title_id(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn dedup(mut store_entries: Vec<StoreEntry>) -> Vec<StoreEntry> {
+    let mut seen = std::collections::HashSet::new();
+    store_entries.retain(|e| seen.insert((e.storefront_name.clone(), e.id.clone())));
+    store_entries
+}
+
+const STOREFRONTS: [&str; 3] = ["steam", "gog", "egs"];
+const IMPORT_STOREFRONT: &str = "import";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_with_titles() {
+        let data = b"Title,Status\nHades,Played\nCeleste,Backlog\n";
+
+        let entries = parse_csv(data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Hades");
+        assert_eq!(entries[0].id, "hades");
+        assert_eq!(entries[0].storefront_name, "import");
+    }
+
+    #[test]
+    fn csv_with_release_keys() {
+        let data = b"title,releaseKey\nCyberpunk 2077,steam_1091500\nThe Witcher 3,gog_1207664643\nOld Game,generic_123\n";
+
+        let entries = parse_csv(data).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, "1091500");
+        assert_eq!(entries[0].storefront_name, "steam");
+        assert_eq!(entries[1].id, "1207664643");
+        assert_eq!(entries[1].storefront_name, "gog");
+        assert_eq!(entries[2].id, "old-game");
+        assert_eq!(entries[2].storefront_name, "import");
+    }
+
+    #[test]
+    fn json_with_rows_and_titles() {
+        let data =
+            br#"[{"name": "Hades", "storefront": "Steam", "appid": 1145360}, "Celeste", ""]"#;
+
+        let entries = parse_json(data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, "1145360");
+        assert_eq!(entries[0].storefront_name, "steam");
+        assert_eq!(entries[1].id, "celeste");
+    }
+
+    #[test]
+    fn text_skips_blank_lines_and_duplicates() {
+        let data = b"Hades\n\n  Celeste  \nhades\n";
+
+        let entries = parse_text(data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].title, "Celeste");
+    }
+}
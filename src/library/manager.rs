@@ -1,16 +1,24 @@
 use crate::{
-    api::{FirestoreApi, IgdbApi, IgdbSearch},
-    documents::{GameDigest, GameEntry, LibraryEntry, StoreEntry, Unresolved},
+    api::{FirestoreApi, GogApi, IgdbApi, IgdbSearch, SteamApi},
+    documents::{
+        GameDigest, GameEntry, LibraryEntry, OutboundEvent, SmartFilter, StoreEntry, Unresolved,
+    },
+    traits::Storefront,
+    webhooks::outbound,
     Status,
 };
+use futures::{stream, StreamExt};
 use itertools::Itertools;
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
 };
-use tracing::{error, instrument, trace_span, Instrument};
+use tracing::{error, instrument, trace_span, warn, Instrument};
 
-use super::firestore::{self, external_games, games};
+use super::{
+    firestore::{self, external_games, games},
+    suggestions::{Suggestion, SuggestionKind},
+};
 
 pub struct LibraryManager {
     user_id: String,
@@ -77,8 +85,13 @@ impl LibraryManager {
 
         if !library_entries.is_empty() {
             let game_ids = library_entries.iter().map(|e| e.id).collect_vec();
+            let digests = library_entries
+                .iter()
+                .map(|e| e.digest.clone())
+                .collect_vec();
             firestore::library::add_entries(&firestore, &self.user_id, library_entries).await?;
             firestore::wishlist::remove_entries(&firestore, &self.user_id, &game_ids).await?;
+            self.notify_added(&firestore, digests);
         }
 
         // For games that were not found in ExternalGames generate candidates
@@ -110,6 +123,131 @@ impl LibraryManager {
         Ok(())
     }
 
+    /// Refreshes playtime and achievements of Steam games in user's library.
+    ///
+    /// Achievements are only fetched for games whose playtime changed since
+    /// the last refresh, as they require a Steam request per game. At most
+    /// `STEAM_GAMES_PER_SYNC` games are refreshed per sync, the rest are left
+    /// unchanged and are picked up by the next one.
+    #[instrument(level = "trace", skip(self, firestore, steam_api))]
+    pub async fn refresh_steam_stats(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        steam_api: &SteamApi,
+    ) -> Result<(), Status> {
+        let playtimes = steam_api.get_playtimes().await?;
+        let mut library = firestore::library::read(&firestore, &self.user_id).await?;
+
+        let changed = library
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, library_entry)| {
+                let (steam_appid, playtime) = library_entry
+                    .store_entries
+                    .iter()
+                    .filter(|e| e.storefront_name == SteamApi::id())
+                    .filter_map(|e| {
+                        playtimes
+                            .get(&e.id)
+                            .map(|playtime| (e.id.clone(), *playtime))
+                    })
+                    .max_by_key(|(_, playtime)| *playtime)?;
+                let stored = library_entry
+                    .store_playtimes
+                    .iter()
+                    .find(|p| p.storefront_name == SteamApi::id())
+                    .map(|p| p.minutes);
+                match stored == Some(playtime) {
+                    true => None,
+                    false => Some((i, steam_appid, playtime)),
+                }
+            })
+            .take(STEAM_GAMES_PER_SYNC)
+            .collect_vec();
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let mut responses = stream::iter(changed)
+            .map(|(i, steam_appid, playtime)| async move {
+                let achievements = match playtime {
+                    0 => Ok(None),
+                    _ => steam_api.get_achievements(&steam_appid).await,
+                };
+                (i, playtime, achievements)
+            })
+            .buffer_unordered(STEAM_CONCURRENT_REQUESTS);
+        while let Some((i, playtime, achievements)) = responses.next().await {
+            let library_entry = &mut library.entries[i];
+            library_entry.set_store_playtime(&SteamApi::id(), playtime);
+            match achievements {
+                Ok(Some((unlocked, total))) => {
+                    library_entry.achievements_unlocked = Some(unlocked);
+                    library_entry.achievements_total = Some(total);
+                }
+                Ok(None) => {}
+                Err(status) => warn!(
+                    "Failed to retrieve achievements for '{}': {status}",
+                    library_entry.digest.name
+                ),
+            }
+        }
+
+        firestore::library::write(&firestore, &self.user_id, library).await
+    }
+
+    /// Refreshes GOG Galaxy playtime of GOG games in user's library.
+    #[instrument(level = "trace", skip(self, firestore, gog_api))]
+    pub async fn refresh_gog_playtime(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        gog_api: &GogApi,
+    ) -> Result<(), Status> {
+        let mut library = firestore::library::read(&firestore, &self.user_id).await?;
+
+        let gog_entries = library
+            .entries
+            .iter()
+            .enumerate()
+            .flat_map(|(i, library_entry)| {
+                library_entry
+                    .store_entries
+                    .iter()
+                    .filter(|e| e.storefront_name == GogApi::id())
+                    .map(move |e| (i, e.id.clone()))
+            })
+            .collect_vec();
+
+        let mut playtimes = HashMap::<usize, u64>::new();
+        let mut responses = stream::iter(gog_entries)
+            .map(|(i, product_id)| async move { (i, gog_api.get_playtime(&product_id).await) })
+            .buffer_unordered(GOG_CONCURRENT_REQUESTS);
+        while let Some((i, playtime)) = responses.next().await {
+            match playtime {
+                Ok(Some(playtime)) => {
+                    let max_playtime = playtimes.entry(i).or_default();
+                    *max_playtime = playtime.max(*max_playtime);
+                }
+                Ok(None) => {}
+                Err(status) => warn!(
+                    "Failed to retrieve GOG playtime for '{}': {status}",
+                    library.entries[i].digest.name
+                ),
+            }
+        }
+
+        let mut dirty = false;
+        for (i, playtime) in playtimes {
+            dirty |= library.entries[i].set_store_playtime(&GogApi::id(), playtime);
+        }
+
+        match dirty {
+            true => firestore::library::write(&firestore, &self.user_id, library).await,
+            false => Ok(()),
+        }
+    }
+
     #[instrument(
         level = "trace",
         skip(self, firestore, store_entry, game_entry)
@@ -132,7 +270,13 @@ impl LibraryManager {
             &library_entries.iter().map(|e| e.id).collect_vec(),
         )
         .await?;
-        firestore::library::add_entries(&firestore, &self.user_id, library_entries).await
+        let digests = library_entries
+            .iter()
+            .map(|e| e.digest.clone())
+            .collect_vec();
+        firestore::library::add_entries(&firestore, &self.user_id, library_entries).await?;
+        self.notify_added(&firestore, digests);
+        Ok(())
     }
 
     /// Unmatch a `StoreEntry` from user's library.
@@ -147,6 +291,13 @@ impl LibraryManager {
         delete: bool,
     ) -> Result<(), Status> {
         firestore::library::remove_entry(&firestore, &self.user_id, &store_entry).await?;
+        outbound::notify(
+            Arc::clone(&firestore),
+            OutboundEvent::LibraryRemoved {
+                user_id: self.user_id.clone(),
+                store_entry: store_entry.clone(),
+            },
+        );
         if delete {
             firestore::storefront::remove_entry(&firestore, &self.user_id, &store_entry).await
         } else {
@@ -154,6 +305,29 @@ impl LibraryManager {
         }
     }
 
+    /// Removes `store_entries` that are no longer owned in their storefronts,
+    /// e.g. refunded games, from user's library.
+    #[instrument(level = "trace", skip(self, firestore, store_entries))]
+    pub async fn remove_store_entries(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        store_entries: Vec<StoreEntry>,
+    ) -> Result<(), Status> {
+        for store_entry in store_entries {
+            firestore::library::remove_entry(&firestore, &self.user_id, &store_entry).await?;
+            firestore::unresolved::remove_entry(&firestore, &self.user_id, &store_entry).await?;
+            firestore::storefront::remove_entry(&firestore, &self.user_id, &store_entry).await?;
+            outbound::notify(
+                Arc::clone(&firestore),
+                OutboundEvent::LibraryRemoved {
+                    user_id: self.user_id.clone(),
+                    store_entry,
+                },
+            );
+        }
+        Ok(())
+    }
+
     #[instrument(
         level = "trace",
         skip(self, firestore, store_entry, game_entry)
@@ -217,7 +391,52 @@ impl LibraryManager {
         firestore::wishlist::remove_entry(&firestore, &self.user_id, game_id).await
     }
 
-    /// Remove all entries in user library from specified storefront.
+    #[instrument(level = "trace", skip(self, firestore))]
+    pub async fn save_filter(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        filter: SmartFilter,
+    ) -> Result<(), Status> {
+        firestore::filters::save(&firestore, &self.user_id, filter).await
+    }
+
+    #[instrument(level = "trace", skip(self, firestore))]
+    pub async fn delete_filter(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        name: &str,
+    ) -> Result<(), Status> {
+        firestore::filters::delete(&firestore, &self.user_id, name).await
+    }
+
+    /// Returns the entries in user library that match the saved smart filter
+    /// with `name`.
+    #[instrument(level = "trace", skip(self, firestore))]
+    pub async fn evaluate_filter(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        name: &str,
+    ) -> Result<Vec<LibraryEntry>, Status> {
+        let filters = firestore::filters::read(&firestore, &self.user_id).await?;
+        let filter = match filters.filters.into_iter().find(|f| f.name == name) {
+            Some(filter) => filter,
+            None => {
+                return Err(Status::not_found(format!(
+                    "Smart filter '{name}' was not found"
+                )))
+            }
+        };
+
+        let library = firestore::library::read(&firestore, &self.user_id).await?;
+        Ok(library
+            .entries
+            .into_iter()
+            .filter(|entry| filter.matches(&entry.digest))
+            .collect())
+    }
+
+    /// Remove all entries in user library from specified storefront. The
+    /// storefront's shards are deleted without touching other storefronts.
     #[instrument(level = "trace", skip(self, firestore))]
     pub async fn remove_storefront(
         &self,
@@ -228,6 +447,57 @@ impl LibraryManager {
         firestore::unresolved::remove_storefront(&firestore, &self.user_id, storefront_id).await?;
         firestore::storefront::remove_store(&firestore, &self.user_id, storefront_id).await
     }
+
+    /// Applies a cleanup `suggestion` on the user's library. Entries are either
+    /// merged into the suggested target game or removed from the library. The
+    /// store entries of removed entries are kept in the storefront, so that
+    /// they are not matched again on the next sync.
+    #[instrument(level = "trace", skip(self, firestore))]
+    pub async fn apply_suggestion(
+        &self,
+        firestore: Arc<FirestoreApi>,
+        suggestion: Suggestion,
+    ) -> Result<(), Status> {
+        let mut library = firestore::library::read(&firestore, &self.user_id).await?;
+
+        let changed = match (suggestion.kind, suggestion.target_id) {
+            (SuggestionKind::Soundtrack, _) => {
+                let len = library.entries.len();
+                library.entries.retain(|e| e.id != suggestion.game_id);
+                library.entries.len() != len
+            }
+            (SuggestionKind::BaseGame | SuggestionKind::Duplicate, Some(target_id)) => {
+                let target = GameDigest::from(games::read(&firestore, target_id).await?);
+                library.merge_game(suggestion.game_id, &target)
+            }
+            (kind, None) => {
+                return Err(Status::invalid_argument(format!(
+                    "{kind:?} suggestion for game={} is missing a target",
+                    suggestion.game_id
+                )))
+            }
+        };
+
+        match changed {
+            true => firestore::library::write(&firestore, &self.user_id, library).await,
+            false => Err(Status::not_found(format!(
+                "game={} is not in the library",
+                suggestion.game_id
+            ))),
+        }
+    }
+
+    /// Notifies outbound webhooks of the user about games added in their
+    /// library.
+    fn notify_added(&self, firestore: &Arc<FirestoreApi>, digests: Vec<GameDigest>) {
+        outbound::notify(
+            Arc::clone(firestore),
+            OutboundEvent::LibraryAdded {
+                user_id: self.user_id.clone(),
+                games: digests,
+            },
+        );
+    }
 }
 
 async fn igdb_resolve(
@@ -302,3 +572,12 @@ async fn search_candidates(
         error!("{status}");
     }
 }
+
+// GOG reports playtime per game, so requests are issued concurrently.
+const GOG_CONCURRENT_REQUESTS: usize = 8;
+
+// Steam reports achievements per game, so requests are issued concurrently.
+const STEAM_CONCURRENT_REQUESTS: usize = 8;
+
+// Caps the achievement requests of a single sync for large libraries.
+const STEAM_GAMES_PER_SYNC: usize = 200;
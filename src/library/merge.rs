@@ -0,0 +1,97 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::{
+    api::{update_digests, FirestoreApi},
+    documents::{GameDigest, GameEdit, GameEditKind},
+    Status,
+};
+
+use super::firestore::{external_games, game_edits, games, library, user_data, wishlist};
+
+/// Counts of the references that a merge moved to the target game.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct MergeResult {
+    pub external_games: usize,
+    pub libraries: usize,
+    pub wishlists: usize,
+}
+
+/// Merges the game `duplicate_id` into `target_id`, for IGDB entries of the
+/// same game that got matched with different store entries.
+///
+/// Store mappings of the duplicate are moved to the target, as are its
+/// entries in the libraries and wishlists of all users. The duplicate is kept
+/// as an archived tombstone that points to the target.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn merge_games(
+    firestore: &FirestoreApi,
+    duplicate_id: u64,
+    target_id: u64,
+    curator: &str,
+) -> Result<MergeResult, Status> {
+    if duplicate_id == target_id {
+        return Err(Status::invalid_argument("Cannot merge a game into itself."));
+    }
+
+    let mut duplicate = games::read(firestore, duplicate_id).await?;
+    if let Some(merged_into) = duplicate.merged_into {
+        return Err(Status::invalid_argument(format!(
+            "Game {duplicate_id} is already merged into {merged_into}"
+        )));
+    }
+    let target = games::read(firestore, target_id).await?;
+    if target.merged_into.is_some() {
+        return Err(Status::invalid_argument(format!(
+            "Cannot merge into game {target_id} that is itself merged"
+        )));
+    }
+
+    let mut result = MergeResult::default();
+    for mut external_game in external_games::get_external_games(firestore, duplicate_id).await? {
+        external_game.igdb_id = target_id;
+        external_games::write(firestore, &external_game).await?;
+        result.external_games += 1;
+    }
+
+    // There is no index from games to users, so all libraries are scanned.
+    let digest = GameDigest::from(target.clone());
+    for user in user_data::list(firestore).await? {
+        let mut user_library = library::read(firestore, &user.uid).await?;
+        if user_library.merge_game(duplicate_id, &digest) {
+            library::write(firestore, &user.uid, user_library).await?;
+            result.libraries += 1;
+        }
+
+        let mut user_wishlist = wishlist::read(firestore, &user.uid).await?;
+        if user_wishlist.merge_game(duplicate_id, &digest) {
+            wishlist::write(firestore, &user.uid, user_wishlist).await?;
+            result.wishlists += 1;
+        }
+    }
+
+    duplicate.merged_into = Some(target_id);
+    duplicate.archived_override = Some(true);
+    duplicate.update_archived();
+    games::write(firestore, &mut duplicate).await?;
+    // Archiving removes the duplicate from company and collection digests.
+    update_digests(firestore, &duplicate).await;
+
+    let timestamp = Utc::now().timestamp_millis();
+    for game_id in [duplicate_id, target_id] {
+        let edit = GameEdit::new(
+            game_id,
+            curator,
+            GameEditKind::Merge,
+            &None::<u64>,
+            &Some(target_id),
+            timestamp,
+        );
+        if let Err(status) = game_edits::add(firestore, &edit).await {
+            warn!("Failed to record merge of game={duplicate_id}: {status}");
+        }
+    }
+
+    Ok(result)
+}
@@ -1,6 +1,15 @@
+pub mod account;
 pub mod firestore;
+pub mod import;
 mod manager;
+pub mod merge;
+pub mod prefetch;
+pub mod suggestions;
+pub mod sync_tokens;
+mod triage;
 mod user;
+pub mod views;
 
 pub use manager::LibraryManager;
+pub use triage::triage_report;
 pub use user::User;
@@ -0,0 +1,249 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use tracing::{instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::{FirestoreApi, IgdbApi},
+    util::rate_limiter::RateLimiter,
+    webhooks::{filtering::GameFilter, prefiltering::IgdbPrefilter},
+    Status,
+};
+
+use super::firestore::{backfill_queue, games, notable, rejected, wishlist};
+
+lazy_static! {
+    static ref USER_LIMITERS: Mutex<HashMap<String, Arc<RateLimiter>>> = Mutex::new(HashMap::new());
+    static ref GLOBAL_LIMITER: RateLimiter =
+        RateLimiter::new(GLOBAL_REQUESTS_PER_PERIOD, USER_QUOTA_PERIOD, 1);
+    static ref IN_FLIGHT: Mutex<HashSet<u64>> = Mutex::new(HashSet::new());
+}
+
+/// Returns true if `user_id` is allowed another prefetch request now.
+///
+/// Requests are limited both per user and across all users, as user ids are
+/// not a reliable key on their own. Requests that the global limit rejects do
+/// not count against the user's quota.
+pub fn allow(user_id: &str) -> bool {
+    let limiter = {
+        let mut limiters = USER_LIMITERS.lock().unwrap();
+        // Limiters only hold state for the current period, so dropping them
+        // all at once only forgets a few seconds of quota.
+        if limiters.len() > MAX_TRACKED_USERS {
+            limiters.clear();
+        }
+        Arc::clone(limiters.entry(user_id.to_owned()).or_insert_with(|| {
+            Arc::new(RateLimiter::new(
+                USER_REQUESTS_PER_PERIOD,
+                USER_QUOTA_PERIOD,
+                1,
+            ))
+        }))
+    };
+    if limiter.try_wait() != Duration::ZERO {
+        return false;
+    }
+    match GLOBAL_LIMITER.try_wait() == Duration::ZERO {
+        true => true,
+        false => {
+            limiter.refund();
+            false
+        }
+    }
+}
+
+/// Resolves in the background candidate games that are not yet in Firestore,
+/// so that their detailed page loads instantly if the user picks them.
+///
+/// Candidates are deduplicated and the ones in the user's wishlist go first.
+/// Only the first `depth` candidates (capped at `MAX_DEPTH`) are considered
+/// and games that are already being prefetched are skipped. Candidates go
+/// through the same filters as games that arrive through webhooks, so that
+/// prefetching does not add blocklisted or non-notable games to the catalog.
+/// Returns the ids that were scheduled for resolving.
+#[instrument(level = "trace", skip(firestore, igdb, game_ids))]
+pub async fn prefetch(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    user_id: &str,
+    game_ids: &[u64],
+    depth: Option<usize>,
+) -> Result<Vec<u64>, Status> {
+    let wishlisted = match wishlist::read(&firestore, user_id).await {
+        Ok(wishlist) => HashSet::from_iter(wishlist.entries.iter().map(|e| e.id)),
+        Err(status) => {
+            warn!("Failed to read wishlist of '{user_id}': {status}");
+            HashSet::new()
+        }
+    };
+    let candidates = select_candidates(
+        game_ids,
+        &wishlisted,
+        depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH),
+    );
+
+    let claimed = claim(&candidates);
+    if claimed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let missing = match games::batch_read(&firestore, &claimed).await {
+        Ok(result) => result.not_found,
+        Err(status) => {
+            release(&claimed);
+            return Err(status);
+        }
+    };
+    release(
+        &claimed
+            .iter()
+            .filter(|id| !missing.contains(id))
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+
+    let game_filter = match game_filter(&firestore).await {
+        Ok(game_filter) => game_filter,
+        Err(status) => {
+            release(&missing);
+            return Err(status);
+        }
+    };
+    let (missing, blocklisted): (Vec<u64>, Vec<u64>) = missing
+        .into_iter()
+        .partition(|id| !game_filter.is_blocklisted(*id));
+    release(&blocklisted);
+
+    let scheduled = missing.clone();
+    tokio::spawn(
+        async move {
+            // Resolved one at a time so that prefetching does not compete
+            // for IGDB quota with resolves that users are waiting on.
+            for game_id in &missing {
+                if let Err(status) = resolve(&firestore, &igdb, *game_id, &game_filter).await {
+                    warn!("Failed to prefetch game {game_id}: {status}");
+                }
+                release(&[*game_id]);
+            }
+        }
+        .instrument(trace_span!("spawn_prefetch")),
+    );
+
+    Ok(scheduled)
+}
+
+/// Returns the filter that webhooks apply to new games.
+async fn game_filter(firestore: &FirestoreApi) -> Result<GameFilter, Status> {
+    Ok(GameFilter::new(notable::read(firestore).await?)
+        .with_blocklist(rejected::ids(firestore).await?))
+}
+
+/// Resolves and stores the game with `game_id` if it passes `game_filter`.
+async fn resolve(
+    firestore: &Arc<FirestoreApi>,
+    igdb: &IgdbApi,
+    game_id: u64,
+    game_filter: &GameFilter,
+) -> Result<(), Status> {
+    let igdb_game = igdb.get(game_id).await?;
+    if !IgdbPrefilter::filter(&igdb_game, igdb.platform_scope()) {
+        return Ok(());
+    }
+
+    let mut game_entry = match igdb
+        .resolve_only(Arc::clone(firestore), igdb_game, game_filter)
+        .await?
+    {
+        (_, Some(_)) => return Ok(()),
+        (game_entry, None) => game_entry,
+    };
+    games::write(firestore, &mut game_entry).await?;
+    if let Err(status) = backfill_queue::add(firestore, &game_entry).await {
+        warn!(
+            "Failed to schedule backfill for '{}': {status}",
+            game_entry.name
+        );
+    }
+    Ok(())
+}
+
+/// Returns the deduplicated `game_ids` with wishlisted games first, limited
+/// to `depth` entries. The relative order of candidates is kept otherwise.
+fn select_candidates(game_ids: &[u64], wishlisted: &HashSet<u64>, depth: usize) -> Vec<u64> {
+    let mut seen = HashSet::new();
+    let unique = game_ids
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let (mut candidates, rest): (Vec<u64>, Vec<u64>) =
+        unique.into_iter().partition(|id| wishlisted.contains(id));
+    candidates.extend(rest);
+    candidates.truncate(depth);
+    candidates
+}
+
+/// Marks `game_ids` as in flight. Returns the ids that were not already,
+/// as long as fewer than `MAX_IN_FLIGHT` games are prefetched in total.
+fn claim(game_ids: &[u64]) -> Vec<u64> {
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    let mut claimed = vec![];
+    for id in game_ids {
+        if in_flight.len() >= MAX_IN_FLIGHT {
+            break;
+        }
+        if in_flight.insert(*id) {
+            claimed.push(*id);
+        }
+    }
+    claimed
+}
+
+fn release(game_ids: &[u64]) {
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    for id in game_ids {
+        in_flight.remove(id);
+    }
+}
+
+// Maximum number of candidates that are prefetched per request.
+const MAX_DEPTH: usize = 5;
+
+const USER_REQUESTS_PER_PERIOD: i32 = 10;
+const USER_QUOTA_PERIOD: Duration = Duration::from_secs(60);
+const MAX_TRACKED_USERS: usize = 10_000;
+
+// Prefetch requests allowed across all users per quota period.
+const GLOBAL_REQUESTS_PER_PERIOD: i32 = 100;
+
+// Maximum number of games that are prefetched at any time across all users.
+const MAX_IN_FLIGHT: usize = 50;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_candidates_dedups_and_prioritises_wishlist() {
+        let wishlisted = HashSet::from([4, 5]);
+        assert_eq!(
+            select_candidates(&[1, 2, 1, 3, 5, 4, 2], &wishlisted, 10),
+            vec![5, 4, 1, 2, 3]
+        );
+        assert_eq!(select_candidates(&[1, 2, 3, 5], &wishlisted, 2), vec![5, 1]);
+    }
+
+    #[test]
+    fn allow_limits_requests_per_user() {
+        for _ in 0..USER_REQUESTS_PER_PERIOD {
+            assert!(allow("prefetch_test_user"));
+        }
+        assert!(!allow("prefetch_test_user"));
+        assert!(allow("prefetch_other_user"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::documents::{GameCategory, Library, LibraryEntry};
+
+/// Cleanup of a user's library entry that can be applied in one call.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+
+    /// Library entry that the suggestion is about.
+    pub game_id: u64,
+    pub name: String,
+
+    /// Game that the entry should be merged into. It is only set for
+    /// suggestions that are applied by merging.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SuggestionKind {
+    /// Entry is matched to a version or bundle of a game and should point at
+    /// the base game instead.
+    BaseGame,
+
+    /// Entry is a soundtrack that the storefront lists as a game and should be
+    /// removed from the library.
+    Soundtrack,
+
+    /// Entry is the same game as another entry that was matched from a
+    /// different storefront and should be merged into it.
+    Duplicate,
+}
+
+/// Returns cleanups proposed for the entries of `library`. An entry appears in
+/// at most one suggestion.
+pub fn suggest(library: &Library) -> Vec<Suggestion> {
+    let mut suggestions = vec![];
+    let mut suggested = HashSet::new();
+
+    for entry in &library.entries {
+        if is_soundtrack(entry) {
+            suggestions.push(Suggestion {
+                kind: SuggestionKind::Soundtrack,
+                game_id: entry.id,
+                name: entry.digest.name.clone(),
+                target_id: None,
+            });
+            suggested.insert(entry.id);
+            continue;
+        }
+
+        if let (GameCategory::Version | GameCategory::Bundle, Some(parent_id)) =
+            (entry.digest.category, entry.digest.parent_id)
+        {
+            suggestions.push(Suggestion {
+                kind: SuggestionKind::BaseGame,
+                game_id: entry.id,
+                name: entry.digest.name.clone(),
+                target_id: Some(parent_id),
+            });
+            suggested.insert(entry.id);
+        }
+    }
+
+    let mut by_name = HashMap::<String, Vec<&LibraryEntry>>::new();
+    for entry in &library.entries {
+        if !suggested.contains(&entry.id) {
+            by_name
+                .entry(normalized_name(&entry.digest.name))
+                .or_default()
+                .push(entry);
+        }
+    }
+
+    let mut duplicates = by_name
+        .into_values()
+        .filter(|entries| entries.len() > 1 && spans_storefronts(entries))
+        .flat_map(|mut entries| {
+            // Keep the main game, or the one owned in most storefronts.
+            entries.sort_by_key(|entry| {
+                (
+                    entry.digest.category != GameCategory::Main,
+                    Reverse(entry.store_entries.len()),
+                    entry.id,
+                )
+            });
+            let target_id = entries[0].id;
+            entries.into_iter().skip(1).map(move |entry| Suggestion {
+                kind: SuggestionKind::Duplicate,
+                game_id: entry.id,
+                name: entry.digest.name.clone(),
+                target_id: Some(target_id),
+            })
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort_by_key(|suggestion| suggestion.game_id);
+    suggestions.extend(duplicates);
+
+    suggestions
+}
+
+fn is_soundtrack(entry: &LibraryEntry) -> bool {
+    let name = entry.digest.name.to_lowercase();
+    name.contains("soundtrack")
+        || name.contains("original score")
+        || name
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word == "ost")
+}
+
+fn spans_storefronts(entries: &[&LibraryEntry]) -> bool {
+    entries
+        .iter()
+        .flat_map(|entry| entry.store_entries.iter())
+        .map(|store_entry| store_entry.storefront_name.as_str())
+        .collect::<HashSet<_>>()
+        .len()
+        > 1
+}
+
+fn normalized_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::{GameDigest, StoreEntry};
+
+    fn entry(id: u64, name: &str, category: GameCategory, storefront: &str) -> LibraryEntry {
+        LibraryEntry {
+            id,
+            digest: GameDigest {
+                id,
+                name: name.to_owned(),
+                category,
+                ..Default::default()
+            },
+            store_entries: vec![StoreEntry {
+                id: id.to_string(),
+                title: name.to_owned(),
+                storefront_name: storefront.to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn suggests_cleanups() {
+        let mut version = entry(2, "Hades: Deluxe", GameCategory::Version, "steam");
+        version.digest.parent_id = Some(1);
+
+        let library = Library {
+            entries: vec![
+                entry(1, "Hades", GameCategory::Main, "gog"),
+                version,
+                entry(3, "Hades - Original Soundtrack", GameCategory::Dlc, "steam"),
+                entry(4, "Celeste", GameCategory::Main, "steam"),
+                entry(5, "CELESTE", GameCategory::Version, "gog"),
+                entry(6, "Into the Breach", GameCategory::Main, "steam"),
+                entry(7, "Into the Breach", GameCategory::Main, "steam"),
+            ],
+        };
+
+        assert_eq!(
+            suggest(&library),
+            vec![
+                Suggestion {
+                    kind: SuggestionKind::BaseGame,
+                    game_id: 2,
+                    name: "Hades: Deluxe".to_owned(),
+                    target_id: Some(1),
+                },
+                Suggestion {
+                    kind: SuggestionKind::Soundtrack,
+                    game_id: 3,
+                    name: "Hades - Original Soundtrack".to_owned(),
+                    target_id: None,
+                },
+                Suggestion {
+                    kind: SuggestionKind::Duplicate,
+                    game_id: 5,
+                    name: "CELESTE".to_owned(),
+                    target_id: Some(4),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::{
+    api::FirestoreApi,
+    documents::{SyncToken, TokenScope},
+    util::random,
+    Status,
+};
+
+use super::firestore::sync_tokens;
+
+/// Mints a token that grants `scopes` on the data of `user_id` for `ttl_secs`.
+///
+/// Returns the token, which is only known to the caller, together with its
+/// stored doc.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn mint(
+    firestore: &FirestoreApi,
+    user_id: &str,
+    scopes: Vec<TokenScope>,
+    label: String,
+    ttl_secs: i64,
+) -> Result<(String, SyncToken), Status> {
+    if scopes.is_empty() {
+        return Err(Status::invalid_argument(
+            "Sync token needs at least one scope.",
+        ));
+    }
+    if ttl_secs <= 0 || ttl_secs > MAX_TTL_SECS {
+        return Err(Status::invalid_argument(format!(
+            "Sync token lifetime must be within (0, {MAX_TTL_SECS}] seconds."
+        )));
+    }
+
+    let now = Utc::now().timestamp();
+    let token = random::token(TOKEN_BYTES)?;
+    let sync_token = SyncToken {
+        id: token_id(&token),
+        user_id: user_id.to_owned(),
+        scopes,
+        label,
+        created: now,
+        expires_at: now + ttl_secs,
+    };
+    sync_tokens::write(firestore, &sync_token).await?;
+
+    Ok((token, sync_token))
+}
+
+/// Checks that `token` grants `scope` on the data of `user_id`.
+///
+/// Unknown, expired and revoked tokens are rejected with
+/// `Status::PermissionDenied` like tokens that lack the scope, so that callers
+/// cannot probe which tokens exist.
+#[instrument(level = "trace", skip(firestore, token))]
+pub async fn authorize(
+    firestore: &FirestoreApi,
+    token: &str,
+    user_id: &str,
+    scope: TokenScope,
+) -> Result<(), Status> {
+    let denied = || Status::permission_denied(format!("Sync token does not grant {scope:?}"));
+
+    match sync_tokens::read(firestore, &token_id(token)).await {
+        Ok(sync_token) if sync_token.allows(user_id, scope, Utc::now().timestamp()) => Ok(()),
+        Ok(_) | Err(Status::NotFound(_)) => Err(denied()),
+        Err(status) => Err(status),
+    }
+}
+
+/// Returns the tokens minted for `user_id`. The tokens themselves are not
+/// stored, only their ids.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn list(firestore: &FirestoreApi, user_id: &str) -> Result<Vec<SyncToken>, Status> {
+    sync_tokens::list_by_user(firestore, user_id).await
+}
+
+/// Revokes the token with `token_id` if it was minted for `user_id`.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn revoke(firestore: &FirestoreApi, user_id: &str, token_id: &str) -> Result<(), Status> {
+    let sync_token = sync_tokens::read(firestore, token_id).await?;
+    if sync_token.user_id != user_id {
+        return Err(Status::not_found(format!(
+            "Sync token '{token_id}' was not found"
+        )));
+    }
+    sync_tokens::delete(firestore, token_id).await
+}
+
+/// Deletes all tokens of `user_id`.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn purge(firestore: &FirestoreApi, user_id: &str) -> Result<(), Status> {
+    for sync_token in sync_tokens::list_by_user(firestore, user_id).await? {
+        sync_tokens::delete(firestore, &sync_token.id).await?;
+    }
+    Ok(())
+}
+
+/// Returns the id of the doc that stores `token`.
+fn token_id(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Lifetime of sync tokens that are minted without one.
+pub const DEFAULT_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+// Sync tokens are short-lived, so that a lost device loses access on its own.
+const MAX_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+
+const TOKEN_BYTES: usize = 32;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_unique_and_stored_by_hash() {
+        let token = random::token(TOKEN_BYTES).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, random::token(TOKEN_BYTES).unwrap());
+        assert_ne!(token_id(&token), token);
+        assert_eq!(token_id(&token), token_id(&token));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    api::{FirestoreApi, SteamApi, WikipediaScrape},
+    documents::{GameEntry, Report, ReportReason, ReportSuggestion, ResolveSource, SteamData},
+    Status,
+};
+use tracing::{instrument, warn};
+
+use super::firestore::{games, reports};
+
+/// Runs automated checks on the field flagged by `report`, comparing the
+/// game's value against external sources, and attaches to the report doc a
+/// suggested correction if the sources disagree.
+#[instrument(
+    level = "trace",
+    skip(firestore, report),
+    fields(
+        report_id = %report.id,
+    )
+)]
+pub async fn triage_report(firestore: &FirestoreApi, mut report: Report) -> Result<(), Status> {
+    let game_entry = games::read(firestore, report.game_id).await?;
+
+    let steam_data = match (report.reason, &game_entry.steam_data) {
+        (ReportReason::WrongReleaseDate | ReportReason::WrongScore, Some(steam_data)) => {
+            match SteamApi::get_app_details(&steam_data.steam_appid.to_string()).await {
+                Ok(Some(steam_data)) => Some(steam_data),
+                Ok(None) => Some(steam_data.clone()),
+                Err(status) => {
+                    warn!(
+                        "Using stored Steam data for '{}': {status}",
+                        game_entry.name
+                    );
+                    Some(steam_data.clone())
+                }
+            }
+        }
+        _ => None,
+    };
+    let wikipedia_score = match (report.reason, game_entry.get_wikipedia_url()) {
+        (ReportReason::WrongScore, Some(url)) => WikipediaScrape::scrape(url)
+            .await
+            .and_then(|wiki| wiki.score),
+        _ => None,
+    };
+
+    report.suggestion = suggest(
+        report.reason,
+        &game_entry,
+        steam_data.as_ref(),
+        wikipedia_score,
+    );
+    match report.suggestion {
+        Some(_) => reports::write(firestore, &report).await,
+        None => Ok(()),
+    }
+}
+
+/// Returns a correction for the field flagged by `reason` if external source
+/// values disagree with the value in `game_entry`.
+fn suggest(
+    reason: ReportReason,
+    game_entry: &GameEntry,
+    steam_data: Option<&SteamData>,
+    wikipedia_score: Option<u64>,
+) -> Option<ReportSuggestion> {
+    match reason {
+        ReportReason::WrongReleaseDate => {
+            let steam_date = steam_data?.release_timestamp()?;
+            match (steam_date - game_entry.release_date).abs() > RELEASE_DATE_TOLERANCE {
+                true => Some(ReportSuggestion {
+                    field: "release_date".to_owned(),
+                    current_value: game_entry.release_date.to_string(),
+                    suggested_value: steam_date.to_string(),
+                    source: Some(ResolveSource::SteamData),
+                }),
+                false => None,
+            }
+        }
+        ReportReason::WrongScore => {
+            let current = game_entry.scores.metacritic;
+            [
+                (
+                    steam_data
+                        .and_then(|steam_data| steam_data.metacritic.as_ref())
+                        .map(|metacritic| metacritic.score),
+                    ResolveSource::SteamData,
+                ),
+                (wikipedia_score, ResolveSource::Wikipedia),
+            ]
+            .into_iter()
+            .find_map(|(score, source)| match (score, current) {
+                (Some(score), Some(current)) if score.abs_diff(current) <= SCORE_TOLERANCE => None,
+                (Some(score), _) => Some(ReportSuggestion {
+                    field: "metacritic".to_owned(),
+                    current_value: match current {
+                        Some(current) => current.to_string(),
+                        None => String::default(),
+                    },
+                    suggested_value: score.to_string(),
+                    source: Some(source),
+                }),
+                (None, _) => None,
+            })
+        }
+        // Steam header images are banners, not covers, so there is no
+        // alternative cover to suggest.
+        ReportReason::WrongCover | ReportReason::WrongMatch | ReportReason::Other => None,
+    }
+}
+
+// Steam release dates are parsed at noon while IGDB dates are usually at
+// midnight, so small differences are expected.
+const RELEASE_DATE_TOLERANCE: i64 = 24 * 60 * 60;
+const SCORE_TOLERANCE: u64 = 3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::{Metacritic, ReleaseDate, Scores};
+
+    fn game_entry(release_date: i64, metacritic: Option<u64>) -> GameEntry {
+        GameEntry {
+            release_date,
+            scores: Scores {
+                metacritic,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn steam_data(date: &str, metacritic: Option<u64>) -> SteamData {
+        SteamData {
+            release_date: Some(ReleaseDate {
+                date: date.to_owned(),
+                ..Default::default()
+            }),
+            header_image: Some("https://steam/header.jpg".to_owned()),
+            metacritic: metacritic.map(|score| Metacritic {
+                score,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn release_date_matches_steam() {
+        // 2020-09-17 00:00:00 UTC
+        let game_entry = game_entry(1600300800, None);
+        let steam_data = steam_data("Sep 17, 2020", None);
+
+        assert_eq!(
+            suggest(
+                ReportReason::WrongReleaseDate,
+                &game_entry,
+                Some(&steam_data),
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn release_date_differs_from_steam() {
+        let game_entry = game_entry(1600300800, None);
+        let steam_data = steam_data("Oct 17, 2020", None);
+
+        let suggestion = suggest(
+            ReportReason::WrongReleaseDate,
+            &game_entry,
+            Some(&steam_data),
+            None,
+        )
+        .unwrap();
+        assert_eq!(suggestion.field, "release_date");
+        assert_eq!(suggestion.source, Some(ResolveSource::SteamData));
+    }
+
+    #[test]
+    fn score_falls_back_to_wikipedia() {
+        let game_entry = game_entry(0, Some(70));
+        let steam_data = steam_data("", Some(71));
+
+        let suggestion = suggest(
+            ReportReason::WrongScore,
+            &game_entry,
+            Some(&steam_data),
+            Some(85),
+        )
+        .unwrap();
+        assert_eq!(suggestion.suggested_value, "85");
+        assert_eq!(suggestion.source, Some(ResolveSource::Wikipedia));
+    }
+
+    #[test]
+    fn wrong_match_has_no_suggestion() {
+        let game_entry = game_entry(0, None);
+        assert_eq!(
+            suggest(ReportReason::WrongMatch, &game_entry, None, None),
+            None
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     api::{FirestoreApi, GogApi, GogToken, SteamApi},
-    documents::{StoreEntry, UserData},
+    documents::{Keys, UserData},
+    library::firestore::storefront::StorefrontDiff,
     traits::Storefront,
     util, Status,
 };
@@ -48,13 +49,26 @@ impl User {
         }
     }
 
+    /// Links the user's Steam account with `steam_user_id` that was verified
+    /// through Steam sign in.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn link_steam(&mut self, steam_user_id: &str) -> Result<(), Status> {
+        let keys = self.data.keys.get_or_insert_with(Keys::default);
+        keys.steam_user_id = steam_user_id.to_owned();
+        firestore::user_data::write(&self.firestore, &self.data).await
+    }
+
     /// Sync user library with connected storefronts to retrieve updates.
+    ///
+    /// Returns the store entries that were added or removed since the last
+    /// sync, so that only these need to be reconciled.
     #[instrument(level = "trace", skip(self, keys))]
     pub async fn sync_accounts(
         &mut self,
         keys: &util::keys::Keys,
-    ) -> Result<Vec<StoreEntry>, Status> {
+    ) -> Result<StorefrontDiff, Status> {
         let mut store_entries = vec![];
+        let mut synced = vec![];
 
         let gog_api = match self.gog_token().await {
             Some(token) => Some(GogApi::new(token.clone())),
@@ -62,17 +76,46 @@ impl User {
         };
         if let Some(api) = gog_api {
             store_entries.extend(api.get_owned_games().await?);
+            synced.push(GogApi::id());
         }
 
-        let steam_api = match self.steam_user_id() {
-            Some(user_id) => Some(SteamApi::new(&keys.steam.client_key, user_id)),
-            None => None,
-        };
-        if let Some(api) = steam_api {
+        if let Some(api) = self.steam_api(keys) {
             store_entries.extend(api.get_owned_games().await?);
+            synced.push(SteamApi::id());
+        }
+
+        let synced = synced.iter().map(String::as_str).collect::<Vec<_>>();
+        let diff =
+            firestore::storefront::diff(&self.firestore, &self.data.uid, store_entries, &synced)
+                .await?;
+        info!(
+            "Sync of user '{}' found {} new and {} removed store entries.",
+            self.data.uid,
+            diff.added.len(),
+            diff.removed.len()
+        );
+        Ok(diff)
+    }
+
+    /// Returns a SteamApi for the user's Steam account if one is connected.
+    pub fn steam_api(&self, keys: &util::keys::Keys) -> Option<SteamApi> {
+        match self.steam_user_id() {
+            Some(user_id) if !user_id.is_empty() => {
+                Some(SteamApi::new(&keys.steam.client_key, user_id))
+            }
+            _ => None,
         }
+    }
 
-        firestore::storefront::diff_entries(&self.firestore, &self.data.uid, store_entries).await
+    /// Returns a GogApi for the user's GOG account if one is connected.
+    ///
+    /// The GOG token is not validated, so it is expected to be called after
+    /// `sync_accounts` that refreshes it.
+    pub fn gog_api(&self) -> Option<GogApi> {
+        match &self.data.keys {
+            Some(keys) => keys.gog_token.clone().map(|token| GogApi::new(token)),
+            None => None,
+        }
     }
 
     /// Returns a valid GOG token if available.
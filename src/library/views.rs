@@ -0,0 +1,473 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{Collection, GameDigest, GameEntry, Image, PriceHistory, WikipediaPage},
+    Status,
+};
+
+use super::firestore::{
+    collections, companies, franchises, games, library, prices, wikipedia, wishlist,
+};
+
+/// A franchise with its games in release order and aggregate stats.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct FranchiseView {
+    pub id: u64,
+    pub name: String,
+
+    #[serde(default)]
+    pub slug: String,
+
+    #[serde(default)]
+    pub url: String,
+
+    pub games: Vec<UserGame>,
+    pub stats: FranchiseStats,
+}
+
+/// A company with a page of the games it developed and published.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct CompanyView {
+    pub id: u64,
+    pub name: String,
+
+    #[serde(default)]
+    pub slug: String,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Image>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
+    pub developed: GamePage,
+    pub published: GamePage,
+
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct GamePage {
+    /// Number of games in all pages.
+    pub total: usize,
+    pub games: Vec<UserGame>,
+}
+
+/// A game with the data of all sources that its detail page shows.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct GameView {
+    pub game: GameEntry,
+
+    /// Store prices of the game, if they are tracked.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prices: Option<PriceHistory>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wikipedia: Option<WikipediaPage>,
+
+    /// Other games of the game's collections and franchises, highest scored
+    /// first.
+    pub similar: Vec<UserGame>,
+
+    /// True if the game is in the library of the requesting user.
+    #[serde(default)]
+    pub owned: bool,
+
+    /// True if the game is in the wishlist of the requesting user.
+    #[serde(default)]
+    pub wishlisted: bool,
+}
+
+/// A game digest with the ownership status of the requesting user.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct UserGame {
+    #[serde(flatten)]
+    pub digest: GameDigest,
+
+    /// True if the game is in the library of the requesting user.
+    #[serde(default)]
+    pub owned: bool,
+
+    /// True if the game is in the wishlist of the requesting user.
+    #[serde(default)]
+    pub wishlisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct FranchiseStats {
+    pub total_entries: usize,
+
+    /// Average espy score of the games that have one.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_score: Option<f64>,
+
+    /// Number of games owned or wishlisted by the requesting user.
+    pub owned: usize,
+    pub wishlisted: usize,
+}
+
+/// Returns the view of franchise `franchise_id`. Ownership flags are set from
+/// the library and wishlist of `user_id`, if one is provided.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn franchise_view(
+    firestore: &FirestoreApi,
+    franchise_id: u64,
+    user_id: Option<&str>,
+) -> Result<FranchiseView, Status> {
+    let franchise = franchises::read(firestore, franchise_id).await?;
+    let digests = hydrate(firestore, &franchise.games).await?;
+    let ownership = Ownership::read(firestore, user_id).await;
+
+    Ok(build_franchise_view(franchise, digests, &ownership))
+}
+
+/// Returns the view of company `company_id` with a page of its developed and
+/// published games, newest first. Both lists are paged with the same `offset`
+/// and `limit`, which is capped at `MAX_PAGE_SIZE`.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn company_view(
+    firestore: &FirestoreApi,
+    company_id: u64,
+    user_id: Option<&str>,
+    offset: usize,
+    limit: usize,
+) -> Result<CompanyView, Status> {
+    let mut company = companies::read(firestore, company_id).await?;
+    let ownership = Ownership::read(firestore, user_id).await;
+    let limit = limit.min(MAX_PAGE_SIZE);
+
+    let developed = page(std::mem::take(&mut company.developed), offset, limit);
+    let published = page(std::mem::take(&mut company.published), offset, limit);
+
+    Ok(CompanyView {
+        id: company.id,
+        name: company.name,
+        slug: company.slug,
+        logo: company.logo,
+        description: company.description,
+        developed: GamePage {
+            total: developed.0,
+            games: ownership.annotate(hydrate(firestore, &developed.1).await?),
+        },
+        published: GamePage {
+            total: published.0,
+            games: ownership.annotate(hydrate(firestore, &published.1).await?),
+        },
+        offset,
+        limit,
+    })
+}
+
+/// Returns the view of game `game_id`. All sources besides the game entry are
+/// read in parallel and are left out if they fail to read, so that the view is
+/// still served.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn game_view(
+    firestore: &FirestoreApi,
+    game_id: u64,
+    user_id: Option<&str>,
+) -> Result<GameView, Status> {
+    let game = games::read(firestore, game_id).await?;
+    if game.deleted_at.is_some() {
+        return Err(Status::not_found(format!("Game {game_id} was deleted")));
+    }
+
+    let (prices, wikipedia, similar, ownership) = tokio::join!(
+        optional(prices::read(firestore, game_id)),
+        optional(wikipedia::read(firestore, game_id)),
+        similar(firestore, &game),
+        Ownership::read(firestore, user_id),
+    );
+
+    Ok(GameView {
+        prices,
+        wikipedia,
+        similar: ownership.annotate(similar),
+        owned: ownership.owned.contains(&game_id),
+        wishlisted: ownership.wishlisted.contains(&game_id),
+        game,
+    })
+}
+
+/// Returns the doc of `read` or None if it fails. Docs are missing for games
+/// that a source does not cover, other failures are logged.
+async fn optional<T>(read: impl Future<Output = Result<T, Status>>) -> Option<T> {
+    match read.await {
+        Ok(doc) => Some(doc),
+        Err(Status::NotFound(_)) => None,
+        Err(status) => {
+            warn!("{status}");
+            None
+        }
+    }
+}
+
+/// Returns the other games of the collections and franchises of `game_entry`.
+/// Failures to read any of them are logged and skipped.
+async fn similar(firestore: &FirestoreApi, game_entry: &GameEntry) -> Vec<GameDigest> {
+    let mut digests = vec![];
+    for collection in &game_entry.collections {
+        match collections::read(firestore, collection.id).await {
+            Ok(collection) => digests.extend(collection.games),
+            Err(status) => warn!("Failed to read collection {}: {status}", collection.id),
+        }
+    }
+    for franchise in &game_entry.franchises {
+        match franchises::read(firestore, franchise.id).await {
+            Ok(franchise) => digests.extend(franchise.games),
+            Err(status) => warn!("Failed to read franchise {}: {status}", franchise.id),
+        }
+    }
+    rank_similar(game_entry.id, digests)
+}
+
+/// Returns up to `MAX_SIMILAR` of `digests` without duplicates and the game
+/// itself, highest scored first.
+fn rank_similar(game_id: u64, digests: Vec<GameDigest>) -> Vec<GameDigest> {
+    let mut seen = HashSet::from([game_id]);
+    let mut digests = digests
+        .into_iter()
+        .filter(|digest| seen.insert(digest.id))
+        .collect::<Vec<_>>();
+    digests.sort_by_key(|digest| std::cmp::Reverse(digest.scores.espy_score));
+    digests.truncate(MAX_SIMILAR);
+    digests
+}
+
+/// Returns the total number of `digests` and the page of them at `offset`,
+/// newest first. Digests without a release date go last.
+fn page(mut digests: Vec<GameDigest>, offset: usize, limit: usize) -> (usize, Vec<GameDigest>) {
+    digests.sort_by_key(|digest| {
+        (
+            digest.release_date.is_none(),
+            std::cmp::Reverse(digest.release_date),
+        )
+    });
+    let total = digests.len();
+    (
+        total,
+        digests.into_iter().skip(offset).take(limit).collect(),
+    )
+}
+
+/// Returns fresh digests of `digests` from the games collection, as digests
+/// embedded in franchise and company docs are only refreshed when a game is
+/// resolved. Order is preserved and deleted games are dropped. Games missing
+/// from the catalog keep their embedded digest.
+async fn hydrate(
+    firestore: &FirestoreApi,
+    digests: &[GameDigest],
+) -> Result<Vec<GameDigest>, Status> {
+    let ids = digests.iter().map(|digest| digest.id).collect::<Vec<_>>();
+    let mut fresh = HashMap::new();
+    for chunk in ids.chunks(BATCH_READ_SIZE) {
+        let result = games::batch_read(firestore, chunk).await?;
+        fresh.extend(
+            result
+                .documents
+                .into_iter()
+                .map(|game_entry| (game_entry.id, game_entry)),
+        );
+    }
+
+    Ok(digests
+        .iter()
+        .filter_map(|digest| match fresh.remove(&digest.id) {
+            Some(game_entry) if game_entry.is_tombstone() => None,
+            Some(game_entry) => Some(GameDigest::from(game_entry)),
+            None => Some(digest.clone()),
+        })
+        .collect())
+}
+
+/// Game ids in the library and wishlist of the requesting user.
+#[derive(Default)]
+struct Ownership {
+    owned: HashSet<u64>,
+    wishlisted: HashSet<u64>,
+}
+
+impl Ownership {
+    /// Reads the library and wishlist of `user_id`. Failures are logged and
+    /// return no ids, so that the view is still served.
+    async fn read(firestore: &FirestoreApi, user_id: Option<&str>) -> Self {
+        let user_id = match user_id {
+            Some(user_id) => user_id,
+            None => return Ownership::default(),
+        };
+
+        let owned = match library::read(firestore, user_id).await {
+            Ok(library) => library.entries.iter().map(|entry| entry.id).collect(),
+            Err(status) => {
+                warn!("Failed to read library of user '{user_id}': {status}");
+                HashSet::new()
+            }
+        };
+        let wishlisted = match wishlist::read(firestore, user_id).await {
+            Ok(wishlist) => wishlist.entries.iter().map(|entry| entry.id).collect(),
+            Err(status) => {
+                warn!("Failed to read wishlist of user '{user_id}': {status}");
+                HashSet::new()
+            }
+        };
+        Ownership { owned, wishlisted }
+    }
+
+    fn annotate(&self, digests: Vec<GameDigest>) -> Vec<UserGame> {
+        digests
+            .into_iter()
+            .map(|digest| UserGame {
+                owned: self.owned.contains(&digest.id),
+                wishlisted: self.wishlisted.contains(&digest.id),
+                digest,
+            })
+            .collect()
+    }
+}
+
+fn build_franchise_view(
+    franchise: Collection,
+    mut digests: Vec<GameDigest>,
+    ownership: &Ownership,
+) -> FranchiseView {
+    // Unknown release dates go last.
+    digests.sort_by_key(|digest| (digest.release_date.is_none(), digest.release_date));
+
+    let scores = digests
+        .iter()
+        .filter_map(|digest| digest.scores.espy_score)
+        .collect::<Vec<_>>();
+    let average_score = match scores.is_empty() {
+        true => None,
+        false => Some(scores.iter().sum::<u64>() as f64 / scores.len() as f64),
+    };
+
+    let games = ownership.annotate(digests);
+
+    FranchiseView {
+        id: franchise.id,
+        name: franchise.name,
+        slug: franchise.slug,
+        url: franchise.url,
+        stats: FranchiseStats {
+            total_entries: games.len(),
+            average_score,
+            owned: games.iter().filter(|game| game.owned).count(),
+            wishlisted: games.iter().filter(|game| game.wishlisted).count(),
+        },
+        games,
+    }
+}
+
+pub const MAX_PAGE_SIZE: usize = 100;
+
+// Similar games shown on a game view.
+const MAX_SIMILAR: usize = 12;
+
+// Firestore batch reads are kept small to stay within request size limits.
+const BATCH_READ_SIZE: usize = 100;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Scores;
+
+    fn digest(id: u64, release_date: Option<i64>, espy_score: Option<u64>) -> GameDigest {
+        GameDigest {
+            id,
+            release_date,
+            scores: Scores {
+                espy_score,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn franchise_view_is_chronological() {
+        let view = build_franchise_view(
+            Collection {
+                id: 7,
+                name: "Half-Life".to_owned(),
+                ..Default::default()
+            },
+            vec![
+                digest(3, None, None),
+                digest(2, Some(1_100_000_000), Some(96)),
+                digest(1, Some(910_000_000), Some(90)),
+            ],
+            &Ownership {
+                owned: HashSet::from([1]),
+                wishlisted: HashSet::from([3]),
+            },
+        );
+
+        assert_eq!(
+            view.games
+                .iter()
+                .map(|game| game.digest.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            view.stats,
+            FranchiseStats {
+                total_entries: 3,
+                average_score: Some(93.0),
+                owned: 1,
+                wishlisted: 1,
+            }
+        );
+        assert!(view.games[0].owned && !view.games[0].wishlisted);
+    }
+
+    #[test]
+    fn similar_games_are_unique_and_best_first() {
+        let similar = rank_similar(
+            1,
+            vec![
+                digest(1, None, Some(99)),
+                digest(2, None, Some(80)),
+                digest(3, None, None),
+                digest(4, None, Some(90)),
+                digest(2, None, Some(80)),
+            ],
+        );
+
+        assert_eq!(
+            similar.iter().map(|digest| digest.id).collect::<Vec<_>>(),
+            vec![4, 2, 3]
+        );
+    }
+
+    #[test]
+    fn company_pages_are_newest_first() {
+        let digests = vec![
+            digest(1, Some(910_000_000), None),
+            digest(2, None, None),
+            digest(3, Some(1_100_000_000), None),
+            digest(4, Some(1_000_000_000), None),
+        ];
+
+        let (total, games) = page(digests, 1, 2);
+        assert_eq!(total, 4);
+        assert_eq!(
+            games.iter().map(|digest| digest.id).collect::<Vec<_>>(),
+            vec![4, 1]
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use tracing::info;
+
+/// Counters of the digest write coalescer that batches company and collection
+/// updates.
+pub struct DigestWriteCounters;
+
+impl DigestWriteCounters {
+    /// Records a flush of buffered digest updates. `updates` is the number of
+    /// digest updates that were buffered and `writes` the number of Firestore
+    /// writes that were issued for them.
+    pub fn flush(docs: usize, updates: usize, writes: usize, latency: Duration) {
+        let saved = updates.saturating_sub(writes);
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = DIGEST_WRITES,
+            counter.name = "flush",
+            counter.docs = docs,
+            counter.updates = updates,
+            counter.writes = writes,
+            counter.saved = saved,
+            counter.latency = latency.as_millis(),
+            "Flushed {updates} digest updates with {writes} writes ({saved} saved)",
+        )
+    }
+
+    pub fn write_fail(doc: &str) {
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = DIGEST_WRITES,
+            counter.name = "write_fail",
+            counter.doc = doc,
+            "Failed to write {doc}",
+        )
+    }
+}
+
+const COUNTERS: &str = "counters";
+const DIGEST_WRITES: &str = "digest_writes";
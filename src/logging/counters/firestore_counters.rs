@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+/// Counters of Firestore operations that failed, grouped by the class of the
+/// error, e.g. "retryable" or "contention".
+pub struct FirestoreEvent;
+
+impl FirestoreEvent {
+    pub fn retry(op: &str, class: &str, attempt: u32, backoff: Duration) {
+        warn!(
+            labels.log_type = COUNTERS,
+            counter.group = FIRESTORE,
+            counter.name = "retry",
+            counter.op = op,
+            counter.class = class,
+            counter.attempt = attempt,
+            counter.backoff = backoff.as_millis(),
+            "Firestore '{op}' failed with {class} error, retrying in {}ms",
+            backoff.as_millis(),
+        )
+    }
+
+    pub fn error(op: &str, class: &str, attempts: u32, error: &str) {
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = FIRESTORE,
+            counter.name = "error",
+            counter.op = op,
+            counter.class = class,
+            counter.attempts = attempts,
+            counter.error = error,
+            "Firestore '{op}' failed with {class} error",
+        )
+    }
+}
+
+const COUNTERS: &str = "counters";
+const FIRESTORE: &str = "firestore";
@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+use lazy_static::lazy_static;
+use tracing::info;
+
+lazy_static! {
+    static ref ROUTES: Mutex<BTreeMap<(String, &'static str), RouteStats>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Per-route latency and error counters of the http server.
+///
+/// Each request is logged as a counter and aggregated in-process so that it
+/// can be scraped from `/metrics` in Prometheus text format.
+pub struct HttpCounters;
+
+impl HttpCounters {
+    /// Records a served request. `path` is the raw request path; it is mapped
+    /// to its route template so user and game ids do not explode the labels.
+    /// Paths and methods that no route serves share a single label.
+    pub fn record(method: &str, path: &str, status: u16, latency: Duration) {
+        let route = format!("{} {}", method_label(method), route_template(path));
+        let status_class = status_class(status);
+
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = HTTP,
+            counter.name = "request",
+            counter.route = route,
+            counter.status_class = status_class,
+            counter.latency = latency.as_millis(),
+            "{route} -> {status}",
+        );
+
+        ROUTES
+            .lock()
+            .unwrap()
+            .entry((route, status_class))
+            .or_default()
+            .observe(latency);
+    }
+
+    /// Renders all recorded counters in Prometheus text exposition format.
+    pub fn render() -> String {
+        let routes = ROUTES.lock().unwrap();
+
+        let mut out = String::new();
+        out.push_str("# TYPE espy_http_requests_total counter\n");
+        for ((route, status_class), stats) in routes.iter() {
+            let _ = writeln!(
+                out,
+                "espy_http_requests_total{{route=\"{route}\",status_class=\"{status_class}\"}} {}",
+                stats.count
+            );
+        }
+
+        out.push_str("# TYPE espy_http_request_duration_ms histogram\n");
+        for ((route, status_class), stats) in routes.iter() {
+            let labels = format!("route=\"{route}\",status_class=\"{status_class}\"");
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(stats.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "espy_http_request_duration_ms_bucket{{{labels},le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "espy_http_request_duration_ms_bucket{{{labels},le=\"+Inf\"}} {}",
+                stats.count
+            );
+            let _ = writeln!(
+                out,
+                "espy_http_request_duration_ms_sum{{{labels}}} {}",
+                stats.sum_ms
+            );
+            let _ = writeln!(
+                out,
+                "espy_http_request_duration_ms_count{{{labels}}} {}",
+                stats.count
+            );
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct RouteStats {
+    count: u64,
+    sum_ms: u64,
+    // Non-cumulative counts per bucket of LATENCY_BUCKETS_MS. Requests slower
+    // than the last bound are only reflected in `count`.
+    buckets: [u64; LATENCY_BUCKETS_MS.len()],
+}
+
+impl RouteStats {
+    fn observe(&mut self, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        self.count += 1;
+        self.sum_ms += latency_ms;
+        if let Some(i) = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+        {
+            self.buckets[i] += 1;
+        }
+    }
+}
+
+/// Maps a request path to the route it was served by, e.g.
+/// `/library/abc123/sync` -> `/library/{user_id}/sync`. Paths that match no
+/// route map to `OTHER`.
+fn route_template(path: &str) -> &'static str {
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [] => "/",
+        ["images", _, ..] => "/images/{image}",
+        segments => ROUTE_TEMPLATES
+            .iter()
+            .find(|template| matches_template(template, segments))
+            .copied()
+            .unwrap_or(OTHER),
+    }
+}
+
+/// Returns true if `segments` match the route `template`, where segments in
+/// braces match any value.
+fn matches_template(template: &str, segments: &[&str]) -> bool {
+    let parts = template.trim_start_matches('/').split('/');
+    parts.clone().count() == segments.len()
+        && parts
+            .zip(segments)
+            .all(|(part, segment)| part.starts_with('{') || part == *segment)
+}
+
+fn method_label(method: &str) -> &'static str {
+    match method {
+        "GET" => "GET",
+        "POST" => "POST",
+        "PUT" => "PUT",
+        "DELETE" => "DELETE",
+        "PATCH" => "PATCH",
+        "HEAD" => "HEAD",
+        "OPTIONS" => "OPTIONS",
+        _ => "OTHER",
+    }
+}
+
+fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+const LATENCY_BUCKETS_MS: [u64; 12] =
+    [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000];
+
+// Templates of all routes in `http::routes`, which bound the route labels.
+const ROUTE_TEMPLATES: &[&str] = &[
+    "/metrics",
+    "/healthz",
+    "/readyz",
+    "/openapi.json",
+    "/docs",
+    "/search",
+    "/search/{token}",
+    "/lookup",
+    "/resolve",
+    "/resolve/prefetch",
+    "/delete",
+    "/graphql",
+    "/prices/{game_id}",
+    "/games/{game_id}/report",
+    "/games/{game_id}/full",
+    "/franchise/{franchise_id}",
+    "/company/{company_id}",
+    "/feed/{user_id}",
+    "/frontpage/rss",
+    "/admin/reports",
+    "/admin/merge",
+    "/admin/rejected",
+    "/admin/rejected/{game_id}",
+    "/admin/games/{game_id}/undelete",
+    "/admin/games/{game_id}/archive",
+    "/admin/games/{game_id}/edits",
+    "/admin/games/{game_id}/override",
+    "/library/{user_id}",
+    "/library/{user_id}/search",
+    "/library/{user_id}/match",
+    "/library/{user_id}/update",
+    "/library/{user_id}/wishlist",
+    "/library/{user_id}/filters",
+    "/library/{user_id}/follows",
+    "/library/{user_id}/evaluate_filter",
+    "/library/{user_id}/webhooks",
+    "/library/{user_id}/tokens",
+    "/library/{user_id}/tokens/{token_id}",
+    "/library/{user_id}/unlink",
+    "/library/{user_id}/steam/login",
+    "/library/{user_id}/steam/verify",
+    "/library/{user_id}/entries",
+    "/library/{user_id}/diff",
+    "/library/{user_id}/stats",
+    "/library/{user_id}/suggestions",
+    "/library/{user_id}/export",
+    "/library/{user_id}/import",
+    "/library/{user_id}/sync",
+    "/account/{user_id}/export",
+    "/account/{user_id}/delete",
+    "/v1/timeline",
+    "/v1/timeline/{version}",
+    "/v1/frontpage",
+    "/v1/frontpage/{version}",
+    "/v1/games/{game_id}",
+    "/v1/collections/{collection_id}",
+    "/v1/franchises/{franchise_id}",
+];
+
+// Label of paths and methods that no route serves.
+const OTHER: &str = "other";
+
+const COUNTERS: &str = "counters";
+const HTTP: &str = "http";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_template_hides_ids() {
+        assert_eq!(route_template("/"), "/");
+        assert_eq!(route_template("/search"), "/search");
+        assert_eq!(route_template("/library/abc123"), "/library/{user_id}");
+        assert_eq!(
+            route_template("/library/abc123/sync"),
+            "/library/{user_id}/sync"
+        );
+        assert_eq!(
+            route_template("/games/1942/report"),
+            "/games/{game_id}/report"
+        );
+        assert_eq!(
+            route_template("/images/t_cover_big/co1r"),
+            "/images/{image}"
+        );
+        assert_eq!(route_template("/prices/1942"), "/prices/{game_id}");
+        assert_eq!(route_template("/feed/abc123"), "/feed/{user_id}");
+        assert_eq!(route_template("/v1/games/1942"), "/v1/games/{game_id}");
+        assert_eq!(route_template("/admin/reports"), "/admin/reports");
+        assert_eq!(
+            route_template("/admin/games/1942/override"),
+            "/admin/games/{game_id}/override"
+        );
+        assert_eq!(
+            route_template("/library/abc123/tokens/def456"),
+            "/library/{user_id}/tokens/{token_id}"
+        );
+        assert_eq!(
+            route_template("/library/abc123/steam/verify"),
+            "/library/{user_id}/steam/verify"
+        );
+        assert_eq!(route_template("/v1/timeline/17"), "/v1/timeline/{version}");
+        assert_eq!(
+            route_template("/account/abc123/export"),
+            "/account/{user_id}/export"
+        );
+    }
+
+    #[test]
+    fn unknown_routes_share_a_label() {
+        assert_eq!(route_template("/wp-admin/login.php"), OTHER);
+        assert_eq!(route_template("/library/abc123/unknown"), OTHER);
+        assert_eq!(route_template("/search/abc/def"), OTHER);
+        assert_eq!(method_label("PROPFIND"), "OTHER");
+    }
+
+    #[test]
+    fn route_stats_buckets() {
+        let mut stats = RouteStats::default();
+        stats.observe(Duration::from_millis(3));
+        stats.observe(Duration::from_millis(120));
+        stats.observe(Duration::from_secs(60));
+
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.sum_ms, 60123);
+        assert_eq!(stats.buckets[0], 1);
+        assert_eq!(stats.buckets[5], 1);
+        assert_eq!(stats.buckets.iter().sum::<u64>(), 2);
+    }
+}
@@ -1,6 +1,6 @@
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{documents::GameEntry, Status};
 
@@ -16,6 +16,46 @@ impl IgdbCounters {
             "IGDB connection failed",
         )
     }
+
+    pub fn throttled(request: &str, backoff: Duration, effective_qps: i32) {
+        warn!(
+            labels.log_type = COUNTERS,
+            counter.group = IGDB,
+            counter.name = "throttled",
+            counter.request = request,
+            counter.backoff = backoff.as_millis(),
+            counter.effective_qps = effective_qps,
+            "IGDB throttled request: {request}, backing off for {}ms",
+            backoff.as_millis(),
+        )
+    }
+
+    /// Records a resolve that joined an in-flight resolve of the same game
+    /// instead of running its own.
+    pub fn resolve_joined(game_id: u64) {
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = IGDB,
+            counter.name = "resolve_joined",
+            counter.game_id = game_id,
+            "IGDB resolve of {game_id} joined an in-flight resolve",
+        )
+    }
+
+    /// Records the outcome of a sweep of recent IGDB releases. `missed` are
+    /// games that passed the webhook filters but were not in Firestore, i.e.
+    /// the webhook pipeline missed them.
+    pub fn release_sweep(checked: usize, missing: usize, missed: usize) {
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = IGDB,
+            counter.name = "release_sweep",
+            counter.checked = checked,
+            counter.missing = missing,
+            counter.missed = missed,
+            "IGDB release sweep: {missed} games missed by webhooks",
+        )
+    }
 }
 
 pub struct IgdbResolveCounter {
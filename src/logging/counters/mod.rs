@@ -1,5 +1,11 @@
+mod digest_counters;
+mod firestore_counters;
+mod http_counters;
 mod igdb_counters;
 mod steam_counters;
 
+pub use digest_counters::*;
+pub use firestore_counters::*;
+pub use http_counters::*;
 pub use igdb_counters::*;
 pub use steam_counters::*;
@@ -2,7 +2,7 @@ use std::time::SystemTime;
 
 use tracing::info;
 
-use crate::Status;
+use crate::{documents::SteamAvailability, Status};
 
 pub struct SteamFetchCounter {
     start: SystemTime,
@@ -38,6 +38,16 @@ impl SteamFetchCounter {
         )
     }
 
+    pub fn log_availability(&self, availability: SteamAvailability) {
+        info!(
+            labels.log_type = COUNTERS,
+            counter.group = STEAM,
+            counter.name = "unavailable",
+            counter.availability = format!("{availability:?}"),
+            "Steam app unavailable: {availability:?}",
+        )
+    }
+
     pub fn log_error(self, status: &Status) {
         info!(
             labels.log_type = COUNTERS,
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    documents::{ChannelKind, DealAlert, Notification, NotificationEvent},
+    Status,
+};
+
+/// A medium that notifications are delivered to outside of the app.
+#[async_trait]
+pub trait Channel: Send + Sync {
+    fn kind(&self) -> ChannelKind;
+
+    /// Delivers `message` to `target`, the user's address on the channel.
+    async fn send(&self, target: &str, message: &Message) -> Result<(), Status>;
+}
+
+/// Channel agnostic rendering of a notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub title: String,
+    pub body: String,
+}
+
+impl From<&Notification> for Message {
+    fn from(notification: &Notification) -> Self {
+        let body = match &notification.event {
+            NotificationEvent::ReleaseDateChanged {
+                old_release_date,
+                new_release_date,
+                ..
+            } => format!(
+                "Release date moved from {} to {}.",
+                date(*old_release_date),
+                date(*new_release_date)
+            ),
+            NotificationEvent::NewRelease { followed_name, .. } => {
+                format!("New game from {followed_name}.")
+            }
+            NotificationEvent::StatusChanged {
+                old_status,
+                new_status,
+            } => format!("Status changed from {old_status} to {new_status}."),
+        };
+
+        Message {
+            title: notification.name.clone(),
+            body,
+        }
+    }
+}
+
+impl From<&DealAlert> for Message {
+    fn from(deal: &DealAlert) -> Self {
+        Message {
+            title: deal.name.clone(),
+            body: format!(
+                "{}% off on {}: {}.{:02} {}.",
+                deal.discount_percent,
+                deal.store,
+                deal.final_price / 100,
+                deal.final_price % 100,
+                deal.currency
+            ),
+        }
+    }
+}
+
+fn date(timestamp: i64) -> String {
+    match timestamp {
+        0 => String::from("TBA"),
+        timestamp => NaiveDateTime::from_timestamp_opt(timestamp, 0)
+            .map(|date| date.format("%e %B %Y").to_string().trim().to_owned())
+            .unwrap_or_else(|| String::from("TBA")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::ReleasePrecision;
+
+    #[test]
+    fn renders_notifications() {
+        let notification = Notification {
+            game_id: 1,
+            name: String::from("Hades II"),
+            event: NotificationEvent::ReleaseDateChanged {
+                old_release_date: 0,
+                new_release_date: 1_701_000_000,
+                release_precision: ReleasePrecision::Exact,
+            },
+            timestamp: 0,
+            seen: false,
+        };
+        assert_eq!(
+            Message::from(&notification),
+            Message {
+                title: String::from("Hades II"),
+                body: String::from("Release date moved from TBA to 26 November 2023."),
+            }
+        );
+
+        let deal = DealAlert {
+            name: String::from("Hades II"),
+            store: String::from("steam"),
+            currency: String::from("USD"),
+            final_price: 1499,
+            discount_percent: 50,
+            ..Default::default()
+        };
+        assert_eq!(Message::from(&deal).body, "50% off on steam: 14.99 USD.");
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::instrument;
+
+use crate::{documents::ChannelKind, util::http_client, Status};
+
+use super::{Channel, Message};
+
+/// Delivers notifications to Discord webhooks that users create in their own
+/// servers. It needs no keys, as the webhook URL is the credential.
+pub struct DiscordChannel {
+    client: reqwest::Client,
+}
+
+impl DiscordChannel {
+    pub fn new() -> DiscordChannel {
+        DiscordChannel {
+            client: http_client::shared(),
+        }
+    }
+}
+
+impl Default for DiscordChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Channel for DiscordChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Discord
+    }
+
+    #[instrument(name = "discord::send", level = "trace", skip(self, target, message))]
+    async fn send(&self, target: &str, message: &Message) -> Result<(), Status> {
+        // Only Discord URLs are accepted so that user data cannot turn the
+        // service into a proxy for arbitrary requests.
+        if !target.starts_with(DISCORD_WEBHOOKS_URL) {
+            return Err(Status::invalid_argument(format!(
+                "'{target}' is not a Discord webhook URL"
+            )));
+        }
+
+        let resp = self
+            .client
+            .post(target)
+            .json(&json!({
+                "content": format!("**{}**\n{}", message.title, message.body),
+            }))
+            .send()
+            .await?;
+
+        match resp.status().is_success() {
+            true => Ok(()),
+            false => Err(Status::internal(format!(
+                "Discord responded with {}",
+                resp.status()
+            ))),
+        }
+    }
+}
+
+const DISCORD_WEBHOOKS_URL: &str = "https://discord.com/api/webhooks/";
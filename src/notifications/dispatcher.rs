@@ -0,0 +1,115 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use tracing::{instrument, warn};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{ChannelKind, Notification},
+    library::firestore::{self, access},
+    util::keys::NotificationKeys,
+    Status,
+};
+
+use super::{Channel, DiscordChannel, EmailChannel, Message, WebPushChannel};
+
+/// Routes notifications to the channels that each user has opted into.
+///
+/// Producers hand their notifications to the dispatcher instead of writing
+/// them directly, so that adding a channel does not touch any producer.
+#[derive(Default)]
+pub struct Dispatcher {
+    channels: HashMap<ChannelKind, Box<dyn Channel>>,
+}
+
+impl Dispatcher {
+    /// Returns a dispatcher with all channels whose keys are configured.
+    pub fn from_keys(keys: &NotificationKeys) -> Dispatcher {
+        let mut dispatcher = Dispatcher::default().with_channel(Box::new(DiscordChannel::new()));
+        if !keys.sendgrid_api_key.is_empty() && !keys.email_sender.is_empty() {
+            dispatcher = dispatcher.with_channel(Box::new(EmailChannel::new(
+                &keys.sendgrid_api_key,
+                &keys.email_sender,
+            )));
+        }
+        if !keys.fcm_project_id.is_empty() {
+            dispatcher =
+                dispatcher.with_channel(Box::new(WebPushChannel::new(&keys.fcm_project_id)));
+        }
+        dispatcher
+    }
+
+    /// Adds `channel`, replacing any channel of the same kind.
+    pub fn with_channel(mut self, channel: Box<dyn Channel>) -> Self {
+        self.channels.insert(channel.kind(), channel);
+        self
+    }
+
+    /// Makes this the dispatcher returned by `global()`. It can be installed
+    /// only once per process.
+    pub fn install(self) {
+        if DISPATCHER.set(self).is_err() {
+            warn!("Notification dispatcher is already installed.");
+        }
+    }
+
+    /// Returns the installed dispatcher. If none was installed notifications
+    /// are only stored in-app.
+    pub fn global() -> &'static Dispatcher {
+        DISPATCHER.get_or_init(Dispatcher::default)
+    }
+
+    /// Stores `notification` in the user's in-app notifications and delivers
+    /// it to the user's channels.
+    #[instrument(
+        level = "trace",
+        skip(self, firestore, notification),
+        fields(game_id = %notification.game_id),
+    )]
+    pub async fn notify(
+        &self,
+        firestore: &FirestoreApi,
+        user_id: &str,
+        notification: Notification,
+    ) -> Result<(), Status> {
+        if access::is_dry_run(firestore) {
+            return Ok(());
+        }
+
+        let message = Message::from(&notification);
+        firestore::notifications::add(firestore, user_id, notification).await?;
+        self.deliver(firestore, user_id, &message).await;
+        Ok(())
+    }
+
+    /// Delivers `message` to the user's channels. Failed deliveries are
+    /// logged, as they should not fail the producer.
+    #[instrument(level = "trace", skip(self, firestore, message))]
+    pub async fn deliver(&self, firestore: &FirestoreApi, user_id: &str, message: &Message) {
+        if self.channels.is_empty() {
+            return;
+        }
+
+        let user_data = match firestore::user_data::read(firestore, user_id).await {
+            Ok(user_data) => user_data,
+            Err(status) => {
+                warn!("Failed to read channels of user '{user_id}': {status}");
+                return;
+            }
+        };
+
+        for preference in &user_data.notification_channels {
+            let channel = match self.channels.get(&preference.kind) {
+                Some(channel) => channel,
+                None => continue,
+            };
+            if let Err(status) = channel.send(&preference.target, message).await {
+                warn!(
+                    "Failed to deliver {:?} notification to user '{user_id}': {status}",
+                    preference.kind
+                );
+            }
+        }
+    }
+}
+
+static DISPATCHER: OnceLock<Dispatcher> = OnceLock::new();
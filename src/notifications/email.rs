@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::instrument;
+
+use crate::{documents::ChannelKind, util::http_client, Status};
+
+use super::{Channel, Message};
+
+/// Delivers notifications as emails through SendGrid.
+pub struct EmailChannel {
+    api_key: String,
+    sender: String,
+    client: reqwest::Client,
+}
+
+impl EmailChannel {
+    pub fn new(api_key: &str, sender: &str) -> EmailChannel {
+        EmailChannel {
+            api_key: String::from(api_key),
+            sender: String::from(sender),
+            client: http_client::shared(),
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for EmailChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Email
+    }
+
+    #[instrument(name = "email::send", level = "trace", skip(self, message))]
+    async fn send(&self, target: &str, message: &Message) -> Result<(), Status> {
+        if !target.contains('@') {
+            return Err(Status::invalid_argument(format!(
+                "Invalid email address '{target}'"
+            )));
+        }
+
+        let resp = self
+            .client
+            .post(SENDGRID_URL)
+            .bearer_auth(&self.api_key)
+            .json(&json!({
+                "personalizations": [{ "to": [{ "email": target }] }],
+                "from": { "email": self.sender },
+                "subject": format!("espy: {}", message.title),
+                "content": [{ "type": "text/plain", "value": message.body }],
+            }))
+            .send()
+            .await?;
+
+        match resp.status().is_success() {
+            true => Ok(()),
+            false => Err(Status::internal(format!(
+                "SendGrid responded with {}",
+                resp.status()
+            ))),
+        }
+    }
+}
+
+const SENDGRID_URL: &str = "https://api.sendgrid.com/v3/mail/send";
@@ -0,0 +1,11 @@
+mod channel;
+mod discord;
+mod dispatcher;
+mod email;
+mod web_push;
+
+pub use channel::{Channel, Message};
+pub use discord::DiscordChannel;
+pub use dispatcher::Dispatcher;
+pub use email::EmailChannel;
+pub use web_push::WebPushChannel;
@@ -0,0 +1,115 @@
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::{documents::ChannelKind, util::http_client, Status};
+
+use super::{Channel, Message};
+
+/// Delivers notifications as Web Push messages through the FCM HTTP v1 API.
+/// Targets are the FCM registration tokens of users' browsers.
+///
+/// It authenticates as the service account of the GCP runtime through the
+/// metadata server. Outside GCP the `ESPY_FCM_TOKEN` environment variable can
+/// provide an access token instead, e.g. from `gcloud auth print-access-token`.
+pub struct WebPushChannel {
+    project_id: String,
+    client: reqwest::Client,
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl WebPushChannel {
+    pub fn new(project_id: &str) -> WebPushChannel {
+        WebPushChannel {
+            project_id: String::from(project_id),
+            client: http_client::shared(),
+            access_token: Mutex::new(None),
+        }
+    }
+
+    /// Returns an OAuth access token for FCM. Tokens from the metadata server
+    /// are cached until shortly before they expire.
+    async fn access_token(&self) -> Result<String, Status> {
+        if let Ok(token) = env::var("ESPY_FCM_TOKEN") {
+            return Ok(token);
+        }
+
+        let mut access_token = self.access_token.lock().await;
+        if let Some((token, expires_at)) = &*access_token {
+            if Instant::now() < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+
+        let resp = self
+            .client
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?;
+        let ttl = Duration::from_secs(resp.expires_in).saturating_sub(TOKEN_EXPIRY_MARGIN);
+        *access_token = Some((resp.access_token.clone(), Instant::now() + ttl));
+        Ok(resp.access_token)
+    }
+}
+
+#[async_trait]
+impl Channel for WebPushChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::WebPush
+    }
+
+    #[instrument(name = "web_push::send", level = "trace", skip(self, target, message))]
+    async fn send(&self, target: &str, message: &Message) -> Result<(), Status> {
+        let resp = self
+            .client
+            .post(format!(
+                "{FCM_URL}/projects/{}/messages:send",
+                self.project_id
+            ))
+            .bearer_auth(self.access_token().await?)
+            .json(&json!({
+                "message": {
+                    "token": target,
+                    "notification": {
+                        "title": message.title,
+                        "body": message.body,
+                    },
+                },
+            }))
+            .send()
+            .await?;
+
+        match resp.status().is_success() {
+            true => Ok(()),
+            false => Err(Status::internal(format!(
+                "FCM responded with {}",
+                resp.status()
+            ))),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+}
+
+const FCM_URL: &str = "https://fcm.googleapis.com/v1";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+// Tokens are refreshed this long before they expire.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
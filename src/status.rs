@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use std::{error::Error, fmt};
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub enum Status {
     #[default]
     Ok,
@@ -11,11 +11,39 @@ pub enum Status {
     Internal(String),
     InvalidArgument(String),
     NotFound(String),
+    PermissionDenied(String),
+
+    /// The dependency could not be reached or did not respond in time, e.g.
+    /// connection errors, timeouts and 5xx responses.
+    Unavailable(String),
+
+    /// The request was throttled by the dependency, e.g. IGDB 429 responses.
+    ResourceExhausted(String),
+}
+
+/// Kind of a `Status` without its message, for callers that branch on how to
+/// handle an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    Ok,
+    Internal,
+    InvalidArgument,
+    NotFound,
+    PermissionDenied,
+    Unavailable,
+    ResourceExhausted,
 }
 
 impl Status {
-    pub fn new(msg: &str, err: impl Error) -> Self {
-        Status::Internal(format!("{msg}: '{err}'"))
+    /// Wraps `err` in an internal error. If `err` is itself a `Status` its
+    /// kind is kept. The messages of the error's sources are included, so
+    /// that the root cause is not lost.
+    pub fn new(msg: &str, err: impl Error + 'static) -> Self {
+        let chain = error_chain(&err);
+        match (&err as &dyn Error).downcast_ref::<Status>() {
+            Some(status) => status.with_message(format!("{msg}: '{chain}'")),
+            None => Status::Internal(format!("{msg}: '{chain}'")),
+        }
     }
 
     pub fn internal(msg: impl Into<String>) -> Self {
@@ -29,6 +57,63 @@ impl Status {
     pub fn not_found(msg: impl Into<String>) -> Self {
         Status::NotFound(msg.into())
     }
+
+    pub fn permission_denied(msg: impl Into<String>) -> Self {
+        Status::PermissionDenied(msg.into())
+    }
+
+    pub fn unavailable(msg: impl Into<String>) -> Self {
+        Status::Unavailable(msg.into())
+    }
+
+    pub fn resource_exhausted(msg: impl Into<String>) -> Self {
+        Status::ResourceExhausted(msg.into())
+    }
+
+    pub fn kind(&self) -> StatusKind {
+        match self {
+            Status::Ok => StatusKind::Ok,
+            Status::Internal(_) => StatusKind::Internal,
+            Status::InvalidArgument(_) => StatusKind::InvalidArgument,
+            Status::NotFound(_) => StatusKind::NotFound,
+            Status::PermissionDenied(_) => StatusKind::PermissionDenied,
+            Status::Unavailable(_) => StatusKind::Unavailable,
+            Status::ResourceExhausted(_) => StatusKind::ResourceExhausted,
+        }
+    }
+
+    /// Returns true if the failed operation may succeed when it is retried
+    /// as is. Internal errors are not retryable, as they are mostly caused by
+    /// unexpected data that does not change between attempts.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            StatusKind::Unavailable | StatusKind::ResourceExhausted
+        )
+    }
+
+    /// Returns a status of the same kind with `msg`.
+    fn with_message(&self, msg: String) -> Self {
+        match self.kind() {
+            StatusKind::Ok | StatusKind::Internal => Status::Internal(msg),
+            StatusKind::InvalidArgument => Status::InvalidArgument(msg),
+            StatusKind::NotFound => Status::NotFound(msg),
+            StatusKind::PermissionDenied => Status::PermissionDenied(msg),
+            StatusKind::Unavailable => Status::Unavailable(msg),
+            StatusKind::ResourceExhausted => Status::ResourceExhausted(msg),
+        }
+    }
+}
+
+/// Returns the messages of `err` and its sources separated by colons.
+fn error_chain(err: &dyn Error) -> String {
+    let mut chain = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        chain.push_str(&format!(": {err}"));
+        source = err.source();
+    }
+    chain
 }
 
 impl From<std::io::Error> for Status {
@@ -45,10 +130,19 @@ impl From<serde_json::Error> for Status {
 
 impl From<reqwest::Error> for Status {
     fn from(err: reqwest::Error) -> Self {
-        Self::new("reqwest error", err)
+        let msg = format!("reqwest error: '{}'", error_chain(&err));
+        match err.status() {
+            Some(status) if status == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                Self::resource_exhausted(msg)
+            }
+            Some(status) if status.is_server_error() => Self::unavailable(msg),
+            _ if err.is_timeout() || err.is_connect() => Self::unavailable(msg),
+            _ => Self::internal(msg),
+        }
     }
 }
 
+use crate::library::firestore::ErrorClass;
 use firestore::errors::FirestoreError;
 impl From<FirestoreError> for Status {
     fn from(err: FirestoreError) -> Self {
@@ -63,7 +157,16 @@ impl From<FirestoreError> for Status {
                 "Failed to parse document with error '{}'",
                 err.message
             )),
-            err => Self::new("Firestore error", err),
+            err => match ErrorClass::of(&err) {
+                ErrorClass::Retryable | ErrorClass::Contention => {
+                    Self::unavailable(format!("Firestore error: '{err}'"))
+                }
+                ErrorClass::NotFound => Self::not_found(format!("Firestore error: '{err}'")),
+                ErrorClass::Permission => {
+                    Self::permission_denied(format!("Firestore error: '{err}'"))
+                }
+                ErrorClass::Permanent => Self::new("Firestore error", err),
+            },
         }
     }
 }
@@ -77,6 +180,32 @@ impl fmt::Display for Status {
             Status::Internal(msg) => write!(f, "Interal error: {msg}"),
             Status::InvalidArgument(msg) => write!(f, "Invalid argument error: {msg}"),
             Status::NotFound(msg) => write!(f, "Not found error: {msg}"),
+            Status::PermissionDenied(msg) => write!(f, "Permission denied error: {msg}"),
+            Status::Unavailable(msg) => write!(f, "Unavailable error: {msg}"),
+            Status::ResourceExhausted(msg) => write!(f, "Resource exhausted error: {msg}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_keeps_kind() {
+        let status = Status::new(
+            "Failed to resolve",
+            Status::unavailable("deadline exceeded"),
+        );
+        assert_eq!(status.kind(), StatusKind::Unavailable);
+        assert!(status.is_retryable());
+
+        let status = Status::new(
+            "Failed to read",
+            std::io::Error::new(std::io::ErrorKind::Other, "disk"),
+        );
+        assert_eq!(status.kind(), StatusKind::Internal);
+        assert!(!status.is_retryable());
+        assert!(!Status::not_found("game").is_retryable());
+    }
+}
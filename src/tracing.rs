@@ -1,4 +1,8 @@
 use crate::Status;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use std::env;
 use tracing::Level;
 use tracing_stackdriver::CloudTraceConfiguration;
 use tracing_subscriber::{
@@ -7,23 +11,40 @@ use tracing_subscriber::{
 
 pub struct Tracing;
 
+/// Flushes spans that are not exported yet when it is dropped. Binaries keep
+/// it alive until they exit, as spans are exported to OpenTelemetry
+/// collectors in batches.
+#[must_use = "spans are flushed when the guard is dropped"]
+pub struct TracingGuard;
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        Tracing::shutdown();
+    }
+}
+
 impl Tracing {
-    pub fn setup(name: &str) -> Result<(), Status> {
+    /// Sets up tracing for local runs. Spans are exported to a Jaeger agent,
+    /// or to an OpenTelemetry collector if `ESPY_OTLP_ENDPOINT` is set.
+    pub fn setup(name: &str) -> Result<TracingGuard, Status> {
         opentelemetry::global::set_text_map_propagator(opentelemetry_jaeger::Propagator::new());
 
-        let jaeger_tracer = match opentelemetry_jaeger::new_agent_pipeline()
-            .with_service_name(name)
-            .install_simple()
-        {
-            Ok(tracer) => tracer,
-            Err(e) => {
-                eprintln!("{e}");
-                return Err(Status::new("Failed to setup tracing", e));
-            }
+        let tracer = match otlp_endpoint() {
+            Some(endpoint) => otlp_tracer(name, &endpoint)?,
+            None => match opentelemetry_jaeger::new_agent_pipeline()
+                .with_service_name(name)
+                .install_simple()
+            {
+                Ok(tracer) => tracer,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return Err(Status::new("Failed to setup tracing", e));
+                }
+            },
         };
 
         match tracing_subscriber::registry()
-            .with(tracing_opentelemetry::layer().with_tracer(jaeger_tracer))
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
             .with(
                 // Log also to stdout.
                 tracing_subscriber::fmt::Layer::new()
@@ -31,7 +52,7 @@ impl Tracing {
             )
             .try_init()
         {
-            Ok(()) => Ok(()),
+            Ok(()) => Ok(TracingGuard),
             Err(e) => {
                 eprintln!("{e}");
                 return Err(Status::new("Failed to setup tracing", e));
@@ -39,9 +60,21 @@ impl Tracing {
         }
     }
 
-    pub fn setup_prod(project_id: &str) -> Result<(), Status> {
+    /// Sets up tracing for production that logs to Stackdriver. If
+    /// `ESPY_OTLP_ENDPOINT` is set, spans are also exported to an
+    /// OpenTelemetry collector.
+    pub fn setup_prod(project_id: &str) -> Result<TracingGuard, Status> {
+        let otlp_layer = match otlp_endpoint() {
+            Some(endpoint) => Some(
+                tracing_opentelemetry::layer().with_tracer(otlp_tracer(project_id, &endpoint)?),
+            ),
+            None => None,
+        };
+        let exporting = otlp_layer.is_some();
+
         match tracing_subscriber::registry()
-            .with(tracing_opentelemetry::layer())
+            .with(otlp_layer)
+            .with((!exporting).then(tracing_opentelemetry::layer))
             .with(
                 tracing_stackdriver::layer()
                     .with_cloud_trace(CloudTraceConfiguration {
@@ -51,11 +84,43 @@ impl Tracing {
             )
             .try_init()
         {
-            Ok(()) => Ok(()),
+            Ok(()) => Ok(TracingGuard),
             Err(e) => {
                 eprintln!("{e}");
                 return Err(Status::new("Failed to setup tracing", e));
             }
         }
     }
+
+    /// Flushes spans that are not exported yet. It is called when the guard
+    /// returned by `setup()` is dropped.
+    pub fn shutdown() {
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+}
+
+fn otlp_endpoint() -> Option<String> {
+    env::var("ESPY_OTLP_ENDPOINT")
+        .ok()
+        .filter(|endpoint| !endpoint.is_empty())
+}
+
+/// Returns a tracer that exports spans of service `name` over OTLP/gRPC to
+/// the collector at `endpoint`, e.g. "http://localhost:4317".
+fn otlp_tracer(name: &str, endpoint: &str) -> Result<trace::Tracer, Status> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                name.to_owned(),
+            )])),
+        )
+        .install_batch(runtime::Tokio)
+        .map_err(|e| Status::new("Failed to setup OTLP tracing", e))
 }
@@ -0,0 +1,28 @@
+use chrono::Utc;
+
+/// Source of the current time for time-based logic, so that it can be tested
+/// against a frozen time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time in seconds since the UNIX epoch.
+    fn now(&self) -> i64;
+}
+
+/// Clock that reads the system time.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+}
+
+/// Clock that is frozen at a fixed timestamp.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FrozenClock(pub i64);
+
+impl Clock for FrozenClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
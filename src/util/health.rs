@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+use tracing::{instrument, warn};
+
+use crate::{
+    api::{FirestoreApi, IgdbApi},
+    documents::Notable,
+};
+
+use super::rate_limiter::RateLimiterUsage;
+
+/// Health of a service and its dependencies, as served on `/healthz` and
+/// `/readyz`.
+#[derive(Serialize, Debug)]
+pub struct HealthReport {
+    /// False when the service cannot recover without a restart, e.g. its IGDB
+    /// token expired.
+    pub live: bool,
+
+    /// False when the service cannot serve requests at the moment.
+    pub ready: bool,
+
+    pub igdb_auth: Check,
+    pub igdb_rate_limiter: Check,
+    pub firestore: Check,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Check {
+    pub status: CheckStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Eq, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+
+    /// The dependency works but needs attention soon.
+    Degraded,
+
+    Failing,
+}
+
+impl Check {
+    fn new(status: CheckStatus, detail: impl Into<String>) -> Self {
+        Check {
+            status,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Checks the dependencies of a service that resolves games from IGDB and
+/// stores them in Firestore.
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn check(firestore: &FirestoreApi, igdb: &IgdbApi) -> HealthReport {
+    let (igdb_auth, igdb_rate_limiter) = match igdb.connection() {
+        Ok(connection) => (
+            check_igdb_auth(connection.oauth_expires_at, SystemTime::now()),
+            check_rate_limiter(&connection.qps.usage()),
+        ),
+        Err(status) => (
+            Check::new(CheckStatus::Failing, status.to_string()),
+            Check::new(CheckStatus::Failing, "IGDB is not connected"),
+        ),
+    };
+    let firestore = check_firestore(firestore).await;
+
+    let report = HealthReport {
+        live: igdb_auth.status != CheckStatus::Failing,
+        ready: [&igdb_auth, &igdb_rate_limiter, &firestore]
+            .iter()
+            .all(|check| check.status != CheckStatus::Failing),
+        igdb_auth,
+        igdb_rate_limiter,
+        firestore,
+    };
+    if !report.ready {
+        warn!("Health check failed: {report:?}");
+    }
+    report
+}
+
+/// IGDB tokens are not refreshed, so a token close to its expiration means
+/// that the service needs a restart.
+fn check_igdb_auth(expires_at: SystemTime, now: SystemTime) -> Check {
+    match expires_at.duration_since(now) {
+        Ok(remaining) if remaining > AUTH_EXPIRY_WARNING => Check::new(
+            CheckStatus::Ok,
+            format!("token expires in {}h", remaining.as_secs() / 3600),
+        ),
+        Ok(remaining) => Check::new(
+            CheckStatus::Degraded,
+            format!("token expires in {}m", remaining.as_secs() / 60),
+        ),
+        Err(_) => Check::new(CheckStatus::Failing, "token expired"),
+    }
+}
+
+/// The limiter is failing while it backs off after IGDB throttled requests,
+/// and degraded while its quota is reduced.
+fn check_rate_limiter(usage: &RateLimiterUsage) -> Check {
+    let saturation = usage.saturation();
+    if usage.backoff > Duration::from_micros(0) {
+        Check::new(
+            CheckStatus::Failing,
+            format!(
+                "saturation {saturation:.2}, backing off for {}ms",
+                usage.backoff.as_millis()
+            ),
+        )
+    } else if usage.effective_quota < usage.quota {
+        Check::new(
+            CheckStatus::Degraded,
+            format!(
+                "saturation {saturation:.2}, quota reduced to {}/{} after throttling",
+                usage.effective_quota, usage.quota
+            ),
+        )
+    } else {
+        Check::new(CheckStatus::Ok, format!("saturation {saturation:.2}"))
+    }
+}
+
+/// Reads a small document to verify that Firestore is reachable.
+async fn check_firestore(firestore: &FirestoreApi) -> Check {
+    let start = Instant::now();
+    let read = tokio::time::timeout(FIRESTORE_TIMEOUT, async {
+        let doc: Result<Option<Notable>, _> = firestore
+            .db()
+            .fluent()
+            .select()
+            .by_id_in(&firestore.collection("espy"))
+            .obj()
+            .one("notable")
+            .await;
+        doc
+    })
+    .await;
+
+    match read {
+        Ok(Ok(_)) => Check::new(
+            CheckStatus::Ok,
+            format!("read in {}ms", start.elapsed().as_millis()),
+        ),
+        Ok(Err(e)) => Check::new(CheckStatus::Failing, format!("read failed: {e}")),
+        Err(_) => Check::new(
+            CheckStatus::Failing,
+            format!("read timed out after {}ms", FIRESTORE_TIMEOUT.as_millis()),
+        ),
+    }
+}
+
+const AUTH_EXPIRY_WARNING: Duration = Duration::from_secs(24 * 60 * 60);
+const FIRESTORE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn igdb_auth_fails_when_token_expires() {
+        let now = SystemTime::now();
+        let check = |remaining| check_igdb_auth(now + remaining, now).status;
+
+        assert_eq!(check(Duration::from_secs(48 * 3600)), CheckStatus::Ok);
+        assert_eq!(check(Duration::from_secs(3600)), CheckStatus::Degraded);
+        assert_eq!(
+            check_igdb_auth(now - Duration::from_secs(1), now).status,
+            CheckStatus::Failing
+        );
+    }
+
+    #[test]
+    fn rate_limiter_fails_while_backing_off() {
+        let usage = RateLimiterUsage {
+            quota: 4,
+            effective_quota: 4,
+            available_quota: 1,
+            backoff: Duration::from_micros(0),
+        };
+        assert_eq!(check_rate_limiter(&usage).status, CheckStatus::Ok);
+
+        let usage = RateLimiterUsage {
+            effective_quota: 2,
+            ..usage
+        };
+        assert_eq!(check_rate_limiter(&usage).status, CheckStatus::Degraded);
+
+        let usage = RateLimiterUsage {
+            backoff: Duration::from_secs(1),
+            ..usage
+        };
+        assert_eq!(check_rate_limiter(&usage).status, CheckStatus::Failing);
+    }
+}
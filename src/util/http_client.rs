@@ -0,0 +1,69 @@
+use lazy_static::lazy_static;
+use std::{env, time::Duration};
+use tracing::warn;
+
+lazy_static! {
+    static ref CLIENT: reqwest::Client = builder().build().expect("Failed to build HTTP client");
+}
+
+/// Returns the HTTP client that is shared by all outbound API calls.
+///
+/// Clones of the client share the same connection pool, so connections (and
+/// HTTP/2 sessions when the server supports them) are reused across requests
+/// instead of being established on every call.
+pub fn shared() -> reqwest::Client {
+    CLIENT.clone()
+}
+
+/// Returns a client builder with the same user agent, timeouts and proxy as
+/// the shared client.
+///
+/// Use it only for clients that need their own state, e.g. a cookie jar for
+/// scraping, that should not leak into other requests.
+///
+/// Defaults can be overridden with the environment variables:
+///   * `ESPY_HTTP_USER_AGENT`
+///   * `ESPY_HTTP_TIMEOUT_SECS`
+///   * `ESPY_HTTP_CONNECT_TIMEOUT_SECS`
+///   * `ESPY_HTTP_PROXY`, a proxy URL for all outbound requests
+pub fn builder() -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(env::var("ESPY_HTTP_USER_AGENT").unwrap_or(USER_AGENT.to_owned()))
+        .timeout(Duration::from_secs(env_secs(
+            "ESPY_HTTP_TIMEOUT_SECS",
+            TIMEOUT_SECS,
+        )))
+        .connect_timeout(Duration::from_secs(env_secs(
+            "ESPY_HTTP_CONNECT_TIMEOUT_SECS",
+            CONNECT_TIMEOUT_SECS,
+        )))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .tcp_keepalive(Duration::from_secs(60));
+
+    if let Ok(proxy) = env::var("ESPY_HTTP_PROXY") {
+        match reqwest::Proxy::all(&proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => warn!("Ignoring invalid ESPY_HTTP_PROXY '{proxy}': {e}"),
+        }
+    }
+    builder
+}
+
+fn env_secs(name: &str, default: u64) -> u64 {
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            warn!("Ignoring invalid {name}='{value}'");
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+const USER_AGENT: &str = concat!("espy/", env!("CARGO_PKG_VERSION"));
+
+// Upper bound for a whole request, including reading the response body.
+// Callers that need a tighter deadline set a per-request timeout.
+const TIMEOUT_SECS: u64 = 60;
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
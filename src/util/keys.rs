@@ -1,27 +1,73 @@
 use crate::Status;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+// Missing fields are deserialized as empty so that startup preflight can
+// report them all instead of failing on the first one.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Keys {
     pub igdb: IgdbKeys,
     pub steam: SteamKeys,
+    pub itad: ItadKeys,
+    pub notifications: NotificationKeys,
+    pub auth: AuthKeys,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct IgdbKeys {
     pub client_id: String,
     pub secret: String,
+
+    /// Secret that IGDB sends in the `X-Secret` header of webhook posts.
+    pub webhook_secret: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SteamKeys {
     pub client_key: String,
     pub user_id: String,
+
+    /// Origin of the frontend that Steam sign in returns to, e.g.
+    /// 'https://espy.example'. Steam sign in is disabled if it is missing.
+    pub openid_origin: String,
+}
+
+/// IsThereAnyDeal API key. Price comparison is disabled if it is missing.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItadKeys {
+    pub api_key: String,
+}
+
+/// Keys of notification channels. Email and Web Push are disabled if their
+/// keys are missing.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationKeys {
+    pub sendgrid_api_key: String,
+    pub email_sender: String,
+
+    /// Firebase project that Web Push messages are sent through.
+    pub fcm_project_id: String,
+}
+
+/// Firebase project whose ID tokens authenticate users of the http server.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthKeys {
+    pub firebase_project_id: String,
+
+    /// User ids of curators that can access the `/admin` routes.
+    pub admin_uids: Vec<String>,
 }
 
 impl Keys {
     pub fn from_file(path: &str) -> Result<Keys, Status> {
-        let keys = std::fs::read(path)?;
-        Ok(serde_json::from_slice(&keys)?)
+        let keys = std::fs::read(path)
+            .map_err(|e| Status::new(&format!("Failed to read keys from '{path}'"), e))?;
+        serde_json::from_slice(&keys)
+            .map_err(|e| Status::new(&format!("Failed to parse keys in '{path}'"), e))
     }
 }
@@ -1,2 +1,8 @@
+pub mod clock;
+pub mod health;
+pub mod http_client;
 pub mod keys;
+pub mod preflight;
+pub mod random;
 pub mod rate_limiter;
+pub mod retry;
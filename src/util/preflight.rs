@@ -0,0 +1,133 @@
+use tracing::{info, instrument};
+
+use crate::{
+    api::{FirestoreApi, IgdbApi},
+    documents::Notable,
+    Status,
+};
+
+use super::keys::Keys;
+
+/// External dependency of a service that is validated before it starts.
+#[derive(Clone, Copy, Debug)]
+pub enum Dependency {
+    Igdb,
+
+    /// Webhooks posted by IGDB, which need the shared webhook secret.
+    IgdbWebhooks,
+
+    Steam,
+
+    /// Firebase project that signs the ID tokens of users.
+    Auth,
+}
+
+/// Validates at startup that the service can reach its dependencies with the
+/// keys it was given, so that a misconfigured deployment fails immediately
+/// instead of on its first request.
+///
+/// `key_store` is the path `keys` were loaded from and is only used for
+/// reporting.
+#[instrument(level = "trace", skip(keys, firestore))]
+pub async fn run(
+    key_store: &str,
+    keys: &Keys,
+    firestore: &FirestoreApi,
+    dependencies: &[Dependency],
+) -> Result<(), Status> {
+    check_keys(key_store, keys, dependencies)?;
+    check_firestore(firestore).await?;
+    if dependencies.iter().any(|d| matches!(d, Dependency::Igdb)) {
+        check_igdb(key_store, keys).await?;
+    }
+
+    info!("preflight checks passed");
+    Ok(())
+}
+
+/// Returns an error listing every required key field that is empty.
+fn check_keys(key_store: &str, keys: &Keys, dependencies: &[Dependency]) -> Result<(), Status> {
+    let missing = dependencies
+        .iter()
+        .flat_map(|dependency| match dependency {
+            Dependency::Igdb => vec![
+                ("igdb.client_id", &keys.igdb.client_id),
+                ("igdb.secret", &keys.igdb.secret),
+            ],
+            Dependency::IgdbWebhooks => vec![("igdb.webhook_secret", &keys.igdb.webhook_secret)],
+            Dependency::Steam => vec![("steam.client_key", &keys.steam.client_key)],
+            Dependency::Auth => vec![("auth.firebase_project_id", &keys.auth.firebase_project_id)],
+        })
+        .filter(|(_, value)| value.is_empty())
+        .map(|(field, _)| field)
+        .collect::<Vec<_>>();
+
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(Status::invalid_argument(format!(
+            "Preflight: '{key_store}' is missing required keys: {}",
+            missing.join(", ")
+        ))),
+    }
+}
+
+/// Verifies that Firestore credentials are valid by reading a small document.
+async fn check_firestore(firestore: &FirestoreApi) -> Result<(), Status> {
+    let doc: Result<Option<Notable>, _> = firestore
+        .db()
+        .fluent()
+        .select()
+        .by_id_in(&firestore.collection("espy"))
+        .obj()
+        .one("notable")
+        .await;
+
+    match doc {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Status::internal(format!(
+            "Preflight: cannot read from Firestore, check GOOGLE_APPLICATION_CREDENTIALS and \
+             the service account's permissions: {e}"
+        ))),
+    }
+}
+
+/// Verifies that an IGDB token can be acquired with the configured client.
+async fn check_igdb(key_store: &str, keys: &Keys) -> Result<(), Status> {
+    let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    match igdb.connect().await {
+        Ok(()) => Ok(()),
+        Err(status) => Err(Status::internal(format!(
+            "Preflight: failed to acquire an IGDB token, check that igdb.client_id and \
+             igdb.secret in '{key_store}' are valid Twitch client credentials: {status}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::keys::{IgdbKeys, SteamKeys};
+
+    #[test]
+    fn missing_keys_are_reported() {
+        let keys = Keys {
+            igdb: IgdbKeys {
+                client_id: "id".to_owned(),
+                secret: String::default(),
+                webhook_secret: "secret".to_owned(),
+            },
+            steam: SteamKeys::default(),
+            ..Default::default()
+        };
+
+        assert!(check_keys("keys.json", &keys, &[]).is_ok());
+        match check_keys("keys.json", &keys, &[Dependency::Igdb, Dependency::Steam]) {
+            Err(Status::InvalidArgument(msg)) => {
+                assert!(msg.contains("igdb.secret"));
+                assert!(msg.contains("steam.client_key"));
+                assert!(!msg.contains("igdb.client_id"));
+            }
+            _ => panic!("expected missing keys"),
+        }
+    }
+}
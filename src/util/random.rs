@@ -0,0 +1,21 @@
+use crate::Status;
+
+/// Returns an unguessable hex token made of `len` random bytes from the OS.
+pub fn token(len: usize) -> Result<String, Status> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| Status::internal(format!("Failed to generate random token: {e}")))?;
+    Ok(hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_unique() {
+        let first = token(32).unwrap();
+        assert_eq!(first.len(), 64);
+        assert_ne!(first, token(32).unwrap());
+    }
+}
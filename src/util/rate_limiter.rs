@@ -6,6 +6,10 @@ use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::instrument;
 
 /// Thread-safe RateLimiter for fixed amount of queries per second (QPS).
+///
+/// The limiter adapts to throttling signals from the rate limited service. On
+/// each throttle it halves its quota and blocks for an exponentially growing
+/// backoff. The quota recovers by one for every period without throttling.
 #[derive(Debug)]
 pub struct RateLimiter {
     quota: i32,
@@ -18,6 +22,11 @@ pub struct RateLimiter {
 struct RateLimiterState {
     available_quota: i32,
     next_reset: SystemTime,
+
+    effective_quota: i32,
+    backoff_until: SystemTime,
+    consecutive_throttles: u32,
+    throttled_in_period: bool,
 }
 
 impl RateLimiter {
@@ -31,6 +40,10 @@ impl RateLimiter {
             state: Mutex::new(RateLimiterState {
                 available_quota: quota,
                 next_reset: SystemTime::now(),
+                effective_quota: quota,
+                backoff_until: SystemTime::now(),
+                consecutive_throttles: 0,
+                throttled_in_period: false,
             }),
         }
     }
@@ -55,14 +68,44 @@ impl RateLimiter {
         total_wait_time
     }
 
+    /// Async variation of `wait()` that yields to the runtime instead of
+    /// blocking the thread, e.g. during backoffs after throttling.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn wait_async(&self) -> Duration {
+        let mut total_wait_time = Duration::from_micros(0);
+
+        loop {
+            let wait_time = self.try_wait();
+            if wait_time == Duration::from_micros(0) {
+                break;
+            }
+
+            total_wait_time += wait_time;
+            tokio::time::sleep(wait_time).await;
+        }
+
+        total_wait_time
+    }
+
     // Non-blocking variation of the above. Returns duration before the resource
     // can be tried again. Retuns 0 if execution can proceed immidiately.
     pub fn try_wait(&self) -> Duration {
         let now = SystemTime::now();
         let mut state = self.state.lock().unwrap();
 
+        if state.backoff_until > now {
+            return state.backoff_until.duration_since(now).unwrap();
+        }
+
         if state.next_reset < now {
-            state.available_quota = self.quota;
+            if !state.throttled_in_period && state.effective_quota < self.quota {
+                state.effective_quota += 1;
+            }
+            if !state.throttled_in_period {
+                state.consecutive_throttles = 0;
+            }
+            state.throttled_in_period = false;
+            state.available_quota = state.effective_quota;
             state.next_reset = now.checked_add(self.quota_period).unwrap();
         }
 
@@ -75,12 +118,92 @@ impl RateLimiter {
         }
     }
 
+    /// Returns one unit of quota that `try_wait()` handed out in the current
+    /// period, e.g. when the operation was rejected by another limiter.
+    pub fn refund(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.available_quota = (state.available_quota + 1).min(state.effective_quota);
+    }
+
+    /// Signals that the service throttled a request. Halves the effective
+    /// quota and blocks all operations for a backoff period that doubles on
+    /// consecutive throttles. Returns the backoff duration.
+    pub fn throttled(&self) -> Duration {
+        let now = SystemTime::now();
+        let mut state = self.state.lock().unwrap();
+
+        state.effective_quota = (state.effective_quota / 2).max(1);
+        state.available_quota = 0;
+        state.throttled_in_period = true;
+
+        let backoff = std::cmp::min(
+            self.quota_period * 2u32.pow(state.consecutive_throttles.min(MAX_BACKOFF_EXPONENT)),
+            MAX_BACKOFF,
+        );
+        state.consecutive_throttles += 1;
+        state.backoff_until = now.checked_add(backoff).unwrap();
+        state.next_reset = state.backoff_until;
+
+        backoff
+    }
+
+    /// Returns the current quota per period that can be lower than the
+    /// configured one after throttling.
+    pub fn effective_quota(&self) -> i32 {
+        self.state.lock().unwrap().effective_quota
+    }
+
+    /// Returns the current state of the limiter without consuming quota.
+    pub fn usage(&self) -> RateLimiterUsage {
+        let now = SystemTime::now();
+        let state = self.state.lock().unwrap();
+
+        RateLimiterUsage {
+            quota: self.quota,
+            effective_quota: state.effective_quota,
+            available_quota: match state.next_reset < now {
+                true => state.effective_quota,
+                false => state.available_quota,
+            },
+            backoff: state
+                .backoff_until
+                .duration_since(now)
+                .unwrap_or(Duration::from_micros(0)),
+        }
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub async fn connection(&self) -> SemaphorePermit {
         self.active_connections.acquire().await.unwrap()
     }
 }
 
+/// Snapshot of a RateLimiter's quota, e.g. for health checks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimiterUsage {
+    pub quota: i32,
+    pub effective_quota: i32,
+    pub available_quota: i32,
+
+    /// Remaining time that operations are blocked after a throttle.
+    pub backoff: Duration,
+}
+
+impl RateLimiterUsage {
+    /// Returns the fraction of the configured quota that cannot be used in
+    /// the current period, from 0.0 when all of it is available to 1.0 when
+    /// operations block.
+    pub fn saturation(&self) -> f64 {
+        match self.backoff > Duration::from_micros(0) {
+            true => 1.0,
+            false => 1.0 - self.available_quota.max(0) as f64 / self.quota as f64,
+        }
+    }
+}
+
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_BACKOFF_EXPONENT: u32 = 6;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,4 +279,68 @@ mod tests {
         }
         assert!(start.elapsed().unwrap() > Duration::from_secs(1));
     }
+
+    #[test]
+    fn throttled_backs_off() {
+        let limiter = RateLimiter::new(4, Duration::from_millis(50), 4);
+
+        assert_eq!(limiter.try_wait(), Duration::from_micros(0));
+        let backoff = limiter.throttled();
+        assert_eq!(backoff, Duration::from_millis(50));
+        assert_eq!(limiter.effective_quota(), 2);
+        assert!(limiter.try_wait() > Duration::from_micros(0));
+
+        let backoff = limiter.throttled();
+        assert_eq!(backoff, Duration::from_millis(100));
+        assert_eq!(limiter.effective_quota(), 1);
+    }
+
+    #[test]
+    fn usage_reports_saturation() {
+        let limiter = RateLimiter::new(4, Duration::from_secs(1), 4);
+        assert_eq!(limiter.usage().saturation(), 0.0);
+
+        limiter.wait();
+        assert_eq!(limiter.usage().available_quota, 3);
+        assert_eq!(limiter.usage().saturation(), 0.25);
+
+        limiter.throttled();
+        let usage = limiter.usage();
+        assert_eq!(usage.effective_quota, 2);
+        assert!(usage.backoff > Duration::from_micros(0));
+        assert_eq!(usage.saturation(), 1.0);
+    }
+
+    #[test]
+    fn refund_restores_quota() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1), 2);
+
+        assert_eq!(limiter.try_wait(), Duration::from_micros(0));
+        assert_eq!(limiter.try_wait(), Duration::from_micros(0));
+        assert!(limiter.try_wait() > Duration::from_micros(0));
+
+        limiter.refund();
+        assert_eq!(limiter.try_wait(), Duration::from_micros(0));
+
+        limiter.refund();
+        limiter.refund();
+        limiter.refund();
+        assert_eq!(limiter.usage().available_quota, 2);
+    }
+
+    #[test]
+    fn throttled_recovers_quota() {
+        let limiter = RateLimiter::new(4, Duration::from_millis(20), 4);
+
+        limiter.throttled();
+        assert_eq!(limiter.effective_quota(), 2);
+
+        // First period after the backoff was throttled, following periods
+        // recover one unit of quota each.
+        for _ in 0..4 {
+            thread::sleep(Duration::from_millis(25));
+            limiter.wait();
+        }
+        assert_eq!(limiter.effective_quota(), 4);
+    }
 }
@@ -0,0 +1,31 @@
+use std::{future::Future, time::Duration};
+
+use tracing::warn;
+
+use crate::Status;
+
+/// Runs `op` until it succeeds, fails with an error that is not retryable or
+/// has been attempted `attempts` times. Retries back off exponentially.
+///
+/// Firestore calls already retry through `library::firestore::utils::run` and
+/// should not be wrapped again.
+pub async fn with_retries<T, F, Fut>(attempts: u32, mut op: F) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Status>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(status) if status.is_retryable() && attempt < attempts => {
+                let backoff = BASE_BACKOFF * 2u32.pow(attempt - 1);
+                warn!("Attempt {attempt} failed, retrying in {backoff:?}: {status}");
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
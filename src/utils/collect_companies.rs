@@ -36,7 +36,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/collect_companies")?;
+    let _tracing = Tracing::setup("utils/collect_companies")?;
 
     let opts: Opts = Opts::parse();
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
@@ -84,8 +84,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 id: igdb_company.id,
                 name: igdb_company.name,
                 slug: igdb_company.slug,
-                developed: vec![],
-                published: vec![],
+                description: igdb_company.description,
+                ..Default::default()
             };
 
             let mut games: HashMap<u64, GameDigest> = HashMap::new();
@@ -18,7 +18,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/count_docs")?;
+    let _tracing = Tracing::setup("utils/count_docs")?;
 
     let opts: Opts = Opts::parse();
     let firestore = Arc::new(FirestoreApi::connect().await?);
@@ -28,7 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from(collection.as_str())
+            .from(firestore.collection(&collection).as_str())
             // .filter(|q| q.for_all([q.field(path!(documents::GameEntry::release_date)).equal(0)]))
             .aggregate(|a| a.fields([a.field(path!(AggregationStats::count)).count()]))
             .obj()
@@ -13,7 +13,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Status> {
-    Tracing::setup("utils/gog_scrape")?;
+    let _tracing = Tracing::setup("utils/gog_scrape")?;
 
     let opts: Opts = Opts::parse();
 
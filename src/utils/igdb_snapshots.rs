@@ -0,0 +1,34 @@
+use clap::Parser;
+use espy_backend::{api::FirestoreApi, library::firestore::igdb_snapshots, Tracing};
+
+/// Espy util for inspecting the IGDB payloads that were received by webhooks
+/// for a game.
+#[derive(Parser)]
+struct Opts {
+    /// IGDB id of the game.
+    #[clap(long)]
+    id: u64,
+
+    /// Print only the N most recent snapshots.
+    #[clap(long)]
+    limit: Option<usize>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("utils/igdb_snapshots")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let snapshots = igdb_snapshots::list(&firestore, opts.id).await?;
+    println!("Found {} snapshots for {}.", snapshots.len(), opts.id);
+
+    for snapshot in snapshots.iter().take(opts.limit.unwrap_or(usize::MAX)) {
+        let igdb_game = snapshot.decode()?;
+        println!("--- {}", snapshot.timestamp);
+        println!("{}", serde_json::to_string_pretty(&igdb_game)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use clap::Parser;
+use espy_backend::{
+    api::FirestoreApi, documents::LegacyScore, library::firestore::legacy_scores, Tracing,
+};
+use tracing::error;
+
+/// Espy util for ingesting archived critic scores (e.g. GameRankings) for
+/// games that predate Metacritic.
+///
+/// Expects a CSV file with a header row and the columns `id` (IGDB game id),
+/// `name`, `score` and optionally `review_count` and `source`.
+#[derive(Parser)]
+struct Opts {
+    /// CSV file with the archived scores.
+    #[clap(long)]
+    input: String,
+
+    /// Name of the archive that is recorded for rows without a source.
+    #[clap(long, default_value = "GameRankings")]
+    source: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("utils/ingest_legacy_scores")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(&opts.input)?;
+
+    let mut written = 0;
+    for (i, row) in reader.deserialize::<LegacyScore>().enumerate() {
+        let mut legacy_score = match row {
+            Ok(legacy_score) => legacy_score,
+            Err(e) => {
+                error!("Skipping row #{i}: {e}");
+                continue;
+            }
+        };
+        if legacy_score.score == 0 || legacy_score.score > 100 {
+            error!(
+                "Skipping '{}' with invalid score {}",
+                legacy_score.name, legacy_score.score
+            );
+            continue;
+        }
+        if legacy_score.source.is_empty() {
+            legacy_score.source = opts.source.clone();
+        }
+
+        match legacy_scores::write(&firestore, &legacy_score).await {
+            Ok(()) => written += 1,
+            Err(status) => error!("Failed to write '{}': {status}", legacy_score.name),
+        }
+    }
+    println!("Ingested {written} legacy scores.");
+
+    Ok(())
+}
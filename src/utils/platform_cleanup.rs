@@ -0,0 +1,91 @@
+use clap::Parser;
+use espy_backend::{
+    api::{remove_digests, FirestoreApi},
+    documents::GameEntry,
+    library::firestore::{games, platform_scope},
+    Tracing,
+};
+use firestore::FirestoreResult;
+use futures::{stream::BoxStream, StreamExt};
+use tracing::{error, info};
+
+/// Espy util for cleaning up games that are not released on any platform in
+/// the scope of the deployment.
+#[derive(Parser)]
+struct Opts {
+    /// Delete out of scope entries from the games collection. Otherwise,
+    /// entries are only reported.
+    #[clap(long)]
+    delete: bool,
+}
+
+/// Verifies that all entries in the games collection are released on a
+/// platform in scope. Entries that are not are reported and if `--delete` is
+/// set they are removed along with their digests from companies and
+/// collections.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _tracing = Tracing::setup("utils/platform_cleanup")?;
+
+    let opts: Opts = Opts::parse();
+    let firestore = FirestoreApi::connect().await?;
+    let scope = platform_scope::read(&firestore).await?;
+
+    let mut game_entries: BoxStream<FirestoreResult<GameEntry>> = firestore
+        .db()
+        .fluent()
+        .list()
+        .from(firestore.collection("games").as_str())
+        .obj()
+        .stream_all_with_errors()
+        .await?;
+
+    let mut total = 0;
+    let mut unknown = 0;
+    let mut out_of_scope = vec![];
+    while let Some(game_entry) = game_entries.next().await {
+        total += 1;
+        let game_entry = match game_entry {
+            Ok(game_entry) => game_entry,
+            Err(status) => {
+                error!("{status}");
+                continue;
+            }
+        };
+
+        // Entries without platform info cannot be classified safely.
+        if game_entry.igdb_game.platforms.is_empty() {
+            unknown += 1;
+            continue;
+        }
+        if game_entry.igdb_game.is_in_scope(&scope) {
+            continue;
+        }
+
+        println!(
+            "#{} -- {} -- id={} -- platforms={:?}",
+            out_of_scope.len(),
+            game_entry.name,
+            game_entry.id,
+            game_entry.igdb_game.platforms,
+        );
+
+        if opts.delete {
+            remove_digests(&firestore, &game_entry).await;
+            if let Err(status) = games::delete(&firestore, game_entry.id).await {
+                error!("Failed to delete '{}': {status}", game_entry.name);
+            }
+        }
+        out_of_scope.push(game_entry.id);
+    }
+
+    info!(
+        "Scanned {total} games, found {} out of scope entries and {unknown} without platform info.",
+        out_of_scope.len()
+    );
+    if !opts.delete {
+        println!("ids={:?}", out_of_scope);
+    }
+
+    Ok(())
+}
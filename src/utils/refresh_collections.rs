@@ -26,7 +26,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/refresh_collections")?;
+    let _tracing = Tracing::setup("utils/refresh_collections")?;
 
     let opts: Opts = Opts::parse();
     let mut cursor = opts.cursor;
@@ -39,10 +39,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from(match opts.franchises {
-                false => "collections",
-                true => "franchises",
-            })
+            .from(
+                firestore
+                    .collection(match opts.franchises {
+                        false => "collections",
+                        true => "franchises",
+                    })
+                    .as_str(),
+            )
             .filter(|q| q.for_all([q.field(path!(Collection::id)).greater_than_or_equal(cursor)]))
             .order_by([(path!(Collection::id), FirestoreQueryDirection::Ascending)])
             .limit(BATCH_SIZE)
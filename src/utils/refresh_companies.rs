@@ -26,7 +26,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/refresh_companies")?;
+    let _tracing = Tracing::setup("utils/refresh_companies")?;
 
     let opts: Opts = Opts::parse();
     let mut cursor = opts.cursor;
@@ -39,7 +39,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from("companies")
+            .from(firestore.collection("companies").as_str())
             .filter(|q| q.for_all([q.field(path!(Company::id)).greater_than_or_equal(cursor)]))
             .order_by([(path!(Company::id), FirestoreQueryDirection::Ascending)])
             .limit(BATCH_SIZE)
@@ -108,6 +108,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                         id: company.id,
                         name: company.name,
                         slug: company.slug,
+                        logo: company.logo,
+                        description: company.description,
                         developed: developed_games
                             .documents
                             .into_iter()
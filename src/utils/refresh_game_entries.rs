@@ -23,7 +23,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/refresh_game_entries")?;
+    let _tracing = Tracing::setup("utils/refresh_game_entries")?;
 
     let opts: Opts = Opts::parse();
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
@@ -46,7 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             .db()
             .fluent()
             .select()
-            .from("games")
+            .from(firestore.collection("games").as_str())
             // .start_at(FirestoreQueryCursor::AfterValue(vec![(&cursor).into()]))
             .filter(|q| {
                 q.for_all([
@@ -113,6 +113,9 @@ async fn refresh_game(
     game_entry: GameEntry,
     igdb: &api::IgdbApi,
 ) -> Result<(), Status> {
+    if game_entry.is_tombstone() {
+        return Ok(());
+    }
     let igdb_game = igdb.get(game_entry.id).await?;
     igdb.resolve(firestore, igdb_game).await?;
 
@@ -34,7 +34,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/refresh_library_entries")?;
+    let _tracing = Tracing::setup("utils/refresh_library_entries")?;
 
     let opts: Opts = Opts::parse();
     let firestore = FirestoreApi::connect().await?;
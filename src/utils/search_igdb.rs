@@ -36,7 +36,7 @@ struct Opts {
 /// Quickly retrieve game info from IGDB based on title or external id matching.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/search_igdb")?;
+    let _tracing = Tracing::setup("utils/search_igdb")?;
 
     let opts: Opts = Opts::parse();
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
@@ -19,7 +19,7 @@ struct Opts {
 /// be picked up again for recon on the next storefront sync.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("util/storefront_cleanup")?;
+    let _tracing = Tracing::setup("util/storefront_cleanup")?;
 
     let opts: Opts = Opts::parse();
 
@@ -16,10 +16,10 @@ struct Opts {
 }
 
 /// Syncs user library with connected storefront retrieving new games and
-/// reconciling them.
+/// reconciling them. Games that are no longer owned are removed.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    Tracing::setup("utils/sync_library")?;
+    let _tracing = Tracing::setup("utils/sync_library")?;
 
     let opts: Opts = Opts::parse();
 
@@ -35,11 +35,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let _guard = span.enter();
 
     let mut user = library::User::fetch(Arc::clone(&firestore), &opts.user).await?;
-    let store_entries = user.sync_accounts(&keys).await?;
+    let diff = user.sync_accounts(&keys).await?;
 
     let manager = library::LibraryManager::new(&opts.user);
     manager
-        .batch_recon_store_entries(firestore, igdb, store_entries)
+        .remove_store_entries(Arc::clone(&firestore), diff.removed)
+        .await?;
+    manager
+        .batch_recon_store_entries(firestore, igdb, diff.added)
         .await?;
     Ok(())
 }
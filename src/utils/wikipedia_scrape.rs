@@ -26,7 +26,7 @@ struct Opts {
 
 #[tokio::main]
 async fn main() -> Result<(), Status> {
-    Tracing::setup("utils/wikipedia_scrape")?;
+    let _tracing = Tracing::setup("utils/wikipedia_scrape")?;
 
     let opts: Opts = Opts::parse();
 
@@ -44,7 +44,7 @@ async fn main() -> Result<(), Status> {
             .db()
             .fluent()
             .select()
-            .from("games")
+            .from(firestore.collection("games").as_str())
             // .start_at(FirestoreQueryCursor::AfterValue(vec![(&cursor).into()]))
             .filter(|q| {
                 q.for_all([
@@ -87,8 +87,8 @@ async fn main() -> Result<(), Status> {
                             .find(|e| matches!(e.authority, WebsiteAuthority::Wikipedia));
                         if let Some(website) = website {
                             let response = WikipediaScrape::scrape(&website.url).await;
-                            if let Some(response) = response {
-                                game_entry.scores.add_wikipedia(response);
+                            if let Some(response) = response.filter(|e| e.score.is_some()) {
+                                game_entry.scores.add_wikipedia(&response);
                                 library::firestore::games::write(&firestore, &mut game_entry)
                                     .await?;
 
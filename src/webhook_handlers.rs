@@ -1,13 +1,18 @@
 use clap::Parser;
 use espy_backend::{
-    api::{FirestoreApi, IgdbApi},
-    library::firestore::notable,
-    util,
-    webhooks::{self, filtering::GameFilter},
+    api::{self, FirestoreApi, IgdbApi, ResolveBudgets},
+    library::firestore::{access::Service, notable, platform_scope, rejected},
+    notifications::Dispatcher,
+    util::{
+        self,
+        preflight::{self, Dependency},
+    },
+    webhooks::{self, filtering::GameFilter, simulate::Simulation},
     Status, Tracing,
 };
-use std::{env, sync::Arc};
-use tracing::info;
+use std::{env, sync::Arc, time::Duration};
+use tokio::signal;
+use tracing::{info, warn};
 use warp::{self, Filter};
 
 #[derive(Parser)]
@@ -22,24 +27,71 @@ struct Opts {
 
     #[clap(long)]
     prod_tracing: bool,
+
+    /// Start without validating keys and connectivity to dependencies.
+    #[clap(long)]
+    skip_preflight: bool,
+
+    /// Time budget (in seconds) for each external source queried while
+    /// resolving a game. When unset, per-source defaults are used.
+    #[clap(long)]
+    resolve_budget_secs: Option<u64>,
+
+    /// Enqueue webhook resolves in the Firestore resolve queue and respond
+    /// immediately, instead of resolving before responding.
+    #[clap(long)]
+    resolve_queue: bool,
+
+    /// Number of games resolved concurrently from the resolve queue.
+    #[clap(long, default_value = "4")]
+    resolve_concurrency: usize,
+
+    /// Window (in seconds) during which repeated deliveries of the same game
+    /// update are ignored. Zero disables deduplication.
+    #[clap(long, default_value = "600")]
+    dedup_window_secs: i64,
+
+    /// Serve `/webhooks/simulate/{kind}` that runs stored fixtures or raw
+    /// payloads through the handlers without persisting anything. Only meant
+    /// for local development.
+    #[clap(long)]
+    simulate: bool,
+
+    /// Directory with webhook fixtures for simulations, stored as
+    /// '{kind}/{fixture}.json'.
+    #[clap(long, default_value = "fixtures/webhooks")]
+    fixtures_dir: String,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Status> {
     let opts: Opts = Opts::parse();
 
-    match opts.prod_tracing {
+    let _tracing = match opts.prod_tracing {
         false => Tracing::setup("espy-webhook-handlers")?,
         true => Tracing::setup_prod("espy-webhook-handlers")?,
-    }
+    };
 
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
 
+    let firestore = FirestoreApi::connect_as(Service::WebhookHandlers).await?;
+    if !opts.skip_preflight {
+        preflight::run(
+            &opts.key_store,
+            &keys,
+            &firestore,
+            &[Dependency::Igdb, Dependency::IgdbWebhooks],
+        )
+        .await?;
+    }
+
     let mut igdb = IgdbApi::new(&keys.igdb.client_id, &keys.igdb.secret);
+    igdb.set_platform_scope(platform_scope::read(&firestore).await?);
+    if let Some(secs) = opts.resolve_budget_secs {
+        igdb.set_resolve_budgets(ResolveBudgets::uniform(Duration::from_secs(secs)));
+    }
     igdb.connect().await?;
 
-    let firestore = FirestoreApi::connect().await?;
-
     // Let ENV VAR override flag.
     let port: u16 = match env::var("PORT") {
         Ok(port) => match port.parse::<u16>() {
@@ -50,12 +102,52 @@ async fn main() -> Result<(), Status> {
     };
 
     let notable = notable::read(&firestore).await?;
-    let classifier = GameFilter::new(notable);
+    let classifier = GameFilter::new(notable).with_blocklist(rejected::ids(&firestore).await?);
+
+    let igdb = Arc::new(igdb);
+    let firestore = Arc::new(firestore);
+    let classifier = Arc::new(classifier);
+
+    Dispatcher::from_keys(&keys.notifications).install();
+    webhooks::dedup::set_window(opts.dedup_window_secs);
+
+    let flusher_firestore = Arc::clone(&firestore);
+    api::spawn_digest_flusher(Arc::clone(&flusher_firestore));
+    webhooks::backfill::spawn_worker(Arc::clone(&firestore));
+    webhooks::outbound::spawn_worker(Arc::clone(&firestore));
+    webhooks::retry_queue::spawn_worker(
+        Arc::clone(&firestore),
+        Arc::clone(&igdb),
+        Arc::clone(&classifier),
+    );
+    if opts.resolve_queue {
+        webhooks::resolve_queue::spawn_consumer(
+            Arc::clone(&firestore),
+            Arc::clone(&igdb),
+            Arc::clone(&classifier),
+            opts.resolve_concurrency,
+        );
+    }
+
+    let simulation = match opts.simulate {
+        true => Some(Arc::new(Simulation::new(
+            FirestoreApi::connect_as(Service::DryRun).await?,
+            &opts.fixtures_dir,
+        ))),
+        false => None,
+    };
 
     info!("webhooks handler started");
 
-    warp::serve(
-        webhooks::routes::routes(Arc::new(igdb), Arc::new(firestore), Arc::new(classifier)).with(
+    let (_, server) = warp::serve(
+        webhooks::routes::routes(
+            igdb,
+            firestore,
+            classifier,
+            &keys.igdb.webhook_secret,
+            simulation,
+        )
+        .with(
             warp::cors()
                 .allow_methods(vec!["POST"])
                 .allow_headers(vec!["Content-Type", "Authorization"])
@@ -63,8 +155,29 @@ async fn main() -> Result<(), Status> {
                 .allow_credentials(true),
         ),
     )
-    .run(([0, 0, 0, 0], port))
-    .await;
+    .bind_with_graceful_shutdown(([0, 0, 0, 0], port), shutdown_signal());
+    server.await;
+
+    // Digest updates of the last flush window are still buffered.
+    api::flush_digests(&flusher_firestore).await;
+    info!("webhooks handler stopped");
 
     Ok(())
 }
+
+/// Resolves when the service is asked to stop, either by SIGTERM that the
+/// container runtime sends before shutting down an instance or by Ctrl-C.
+async fn shutdown_signal() {
+    let mut sigterm = match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(status) => {
+            warn!("Failed to listen for SIGTERM: {status}");
+            let _ = signal::ctrl_c().await;
+            return;
+        }
+    };
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = signal::ctrl_c() => {}
+    }
+}
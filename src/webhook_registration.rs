@@ -19,10 +19,10 @@ struct Opts {
 async fn main() -> Result<(), Status> {
     let opts: Opts = Opts::parse();
 
-    match opts.prod_tracing {
+    let _tracing = match opts.prod_tracing {
         false => Tracing::setup("espy-webhook-registration")?,
         true => Tracing::setup_prod("espy-webhook-registration")?,
-    }
+    };
 
     let keys = util::keys::Keys::from_file(&opts.key_store).unwrap();
 
@@ -32,7 +32,10 @@ async fn main() -> Result<(), Status> {
     info!("webhooks registration");
     let webhooks_api = IgdbWebhooksApi::new(igdb.clone());
     webhooks_api
-        .register_games_webhook("https://webhooks-fjxkoqq4wq-ew.a.run.app", "foo")
+        .register_games_webhook(
+            "https://webhooks-fjxkoqq4wq-ew.a.run.app",
+            &keys.igdb.webhook_secret,
+        )
         .await?;
 
     Ok(())
@@ -0,0 +1,263 @@
+use std::{sync::Arc, time::Duration};
+
+use ::firestore::path;
+use chrono::Utc;
+use tracing::{error, info, instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::{
+        update_digests, FirestoreApi, HltbApi, MetacriticApi, PcgwApi, SteamDataApi, SteamScrape,
+        SteamSpyApi, WikidataApi, WikipediaScrape,
+    },
+    documents::{
+        BackfillEntry, GameEntry, ResolveSource, Website, WebsiteAuthority, WikipediaPage,
+    },
+    library::firestore,
+    Status,
+};
+
+use super::retry_queue::backoff_secs;
+
+/// Spawns a background task that periodically fetches the external sources
+/// that partially resolved games are missing.
+///
+/// Only the missing sources are fetched and only the affected fields of the
+/// GameEntry are updated, instead of resolving the game again.
+pub fn spawn_worker(firestore: Arc<FirestoreApi>) {
+    tokio::spawn(
+        async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let now = Utc::now().naive_utc().timestamp();
+                let entries = match firestore::backfill_queue::list_due(&firestore, now).await {
+                    Ok(entries) => entries,
+                    Err(status) => {
+                        warn!("Failed to read backfill queue: {status}");
+                        continue;
+                    }
+                };
+
+                for entry in entries {
+                    process(&firestore, entry).await;
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_backfill_worker")),
+    );
+}
+
+#[instrument(
+    level = "trace",
+    skip(firestore, entry),
+    fields(
+        game_id = %entry.id,
+        attempts = %entry.attempts,
+    )
+)]
+async fn process(firestore: &FirestoreApi, mut entry: BackfillEntry) {
+    let mut game_entry = match firestore::games::read(firestore, entry.id).await {
+        Ok(game_entry) => game_entry,
+        Err(Status::NotFound(_)) => {
+            if let Err(status) = firestore::backfill_queue::delete(firestore, entry.id).await {
+                warn!("{status}");
+            }
+            return;
+        }
+        Err(status) => {
+            warn!("{status}");
+            return;
+        }
+    };
+
+    let scores = game_entry.scores.clone();
+    let mut fields = vec![path!(GameEntry::missing_sources)];
+    let mut missing_sources = vec![];
+    for source in &entry.missing_sources {
+        match backfill(firestore, &mut game_entry, *source).await {
+            Ok(Some(field)) => fields.push(field),
+            Ok(None) => {}
+            Err(status) if status.is_retryable() => {
+                warn!(
+                    "Failed to backfill {source:?} for '{}': {status}",
+                    entry.name
+                );
+                missing_sources.push(*source);
+            }
+            Err(status) => warn!(
+                "Dropping {source:?} backfill for '{}' that failed permanently: {status}",
+                entry.name
+            ),
+        }
+    }
+    if game_entry.scores != scores {
+        fields.push(path!(GameEntry::scores));
+    }
+    fields.sort();
+    fields.dedup();
+
+    game_entry.missing_sources = missing_sources;
+    if let Err(status) = firestore::games::patch(firestore, &game_entry, fields).await {
+        error!("Failed to patch '{}': {status}", entry.name);
+        return;
+    }
+    if game_entry.scores.espy_score != scores.espy_score {
+        update_digests(firestore, &game_entry).await;
+    }
+
+    if game_entry.missing_sources.is_empty() {
+        info!("Backfilled '{}'", entry.name);
+        if let Err(status) = firestore::backfill_queue::delete(firestore, entry.id).await {
+            warn!("{status}");
+        }
+        return;
+    }
+
+    entry.attempts += 1;
+    if entry.attempts >= MAX_ATTEMPTS {
+        error!(
+            "Giving up backfilling {:?} for '{}' after {} attempts",
+            game_entry.missing_sources, entry.name, entry.attempts
+        );
+        if let Err(status) = firestore::backfill_queue::delete(firestore, entry.id).await {
+            warn!("{status}");
+        }
+        return;
+    }
+
+    entry.missing_sources = game_entry.missing_sources;
+    entry.next_attempt = Utc::now().naive_utc().timestamp() + backoff_secs(entry.attempts);
+    if let Err(status) = firestore::backfill_queue::write(firestore, &entry).await {
+        warn!("{status}");
+    }
+}
+
+/// Fetches a single external `source` for `game_entry`.
+///
+/// Returns the path of the GameEntry field that was updated or None if the
+/// source has no data for the game.
+async fn backfill(
+    firestore: &FirestoreApi,
+    game_entry: &mut GameEntry,
+    source: ResolveSource,
+) -> Result<Option<String>, Status> {
+    match source {
+        ResolveSource::SteamData => {
+            let steam_appid =
+                firestore::external_games::get_steam_id(firestore, game_entry.id).await?;
+            let steam_data = SteamDataApi::new()
+                .retrieve_steam_data(&steam_appid)
+                .await?;
+            game_entry.add_steam_data(steam_data);
+            Ok(Some(path!(GameEntry::steam_data)))
+        }
+        ResolveSource::SteamScrape => {
+            let steam_data = match &mut game_entry.steam_data {
+                Some(steam_data) => steam_data,
+                None => return Ok(None),
+            };
+            let website = format!(
+                "https://store.steampowered.com/app/{}/",
+                steam_data.steam_appid
+            );
+            match SteamScrape::scrape(&website).await {
+                Some(steam_scrape_data) => {
+                    steam_data.user_tags = steam_scrape_data.user_tags;
+                    Ok(Some(path!(GameEntry::steam_data)))
+                }
+                None => Ok(None),
+            }
+        }
+        ResolveSource::SteamSpy => {
+            let steam_appid =
+                firestore::external_games::get_steam_id(firestore, game_entry.id).await?;
+            if let Some(steam_spy) = SteamSpyApi::get_app(&steam_appid).await? {
+                game_entry
+                    .scores
+                    .add_steam_spy(&steam_spy, game_entry.release_date);
+            }
+            Ok(None)
+        }
+        ResolveSource::Metacritic => {
+            let slug = MetacriticApi::guess_id(&game_entry.igdb_game.url).to_owned();
+            if let Some(metacritic) = MetacriticApi::get_score(&slug).await {
+                game_entry
+                    .scores
+                    .add_metacritic(metacritic, game_entry.release_date);
+            }
+            Ok(None)
+        }
+        ResolveSource::Wikipedia => {
+            if !game_entry.needs_wikipedia() {
+                return Ok(None);
+            }
+
+            // Pages that IGDB does not link to are discovered through Wikidata.
+            let mut field = None;
+            let url = match game_entry.get_wikipedia_url() {
+                Some(url) => url.to_owned(),
+                None if game_entry.release_date == 0 => return Ok(None),
+                None => {
+                    match WikidataApi::find_wikipedia_url(
+                        &game_entry.name,
+                        game_entry.release_year(),
+                    )
+                    .await?
+                    {
+                        Some(url) => {
+                            game_entry.websites.push(Website {
+                                url: url.clone(),
+                                authority: WebsiteAuthority::Wikipedia,
+                            });
+                            field = Some(path!(GameEntry::websites));
+                            url
+                        }
+                        None => return Ok(None),
+                    }
+                }
+            };
+
+            let wikipedia = WikipediaScrape::scrape(&url).await.unwrap_or_default();
+            if game_entry.scores.metacritic.is_none() {
+                game_entry.scores.add_wikipedia(&wikipedia);
+            }
+            firestore::wikipedia::write(
+                firestore,
+                &WikipediaPage {
+                    id: game_entry.id,
+                    name: game_entry.name.clone(),
+                    url,
+                    developers: wikipedia.developers,
+                    publishers: wikipedia.publishers,
+                },
+            )
+            .await?;
+            Ok(field)
+        }
+        ResolveSource::PcGamingWiki => {
+            let steam_appid = game_entry.steam_data.as_ref().map(|data| data.steam_appid);
+            match PcgwApi::get_tech_info(&game_entry.name, steam_appid).await? {
+                Some(tech_info) => {
+                    firestore::tech_info::write(firestore, game_entry.id, &tech_info).await?;
+                    game_entry.tech_info = Some(tech_info);
+                    Ok(Some(path!(GameEntry::tech_info)))
+                }
+                None => Ok(None),
+            }
+        }
+        ResolveSource::Hltb => {
+            let playtime = HltbApi::get_playtime(&game_entry.name, game_entry.release_year()).await;
+            match playtime {
+                Some(playtime) => {
+                    game_entry.playtime = Some(playtime);
+                    Ok(Some(path!(GameEntry::playtime)))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_ATTEMPTS: u32 = 5;
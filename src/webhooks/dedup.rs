@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+
+use crate::api::IgdbGame;
+
+/// Sets the window (in seconds) during which webhook events that were already
+/// processed are suppressed. A zero window disables deduplication.
+pub fn set_window(secs: i64) {
+    WINDOW_SECS.store(secs, Ordering::SeqCst);
+}
+
+/// Returns true if an event for the same update of `igdb_game` was received
+/// within the dedup window. Otherwise the event is recorded and false is
+/// returned.
+///
+/// IGDB may deliver the same webhook more than once. An update is identified
+/// by the game id and its `updated_at` timestamp, so games without it are
+/// never considered duplicates.
+pub fn is_duplicate(igdb_game: &IgdbGame) -> bool {
+    let window = WINDOW_SECS.load(Ordering::SeqCst);
+    match igdb_game.updated_at {
+        Some(updated_at) if window > 0 => {
+            RECENT_EVENTS.check((igdb_game.id, updated_at), Utc::now().timestamp(), window)
+        }
+        _ => false,
+    }
+}
+
+/// Recently received webhook events keyed by game id and update timestamp,
+/// mapped to the time they were received.
+#[derive(Default)]
+struct RecentEvents {
+    events: Mutex<HashMap<(u64, i64), i64>>,
+}
+
+impl RecentEvents {
+    fn check(&self, key: (u64, i64), now: i64, window: i64) -> bool {
+        let mut events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(received) = events.get(&key) {
+            if now - received < window {
+                return true;
+            }
+        }
+
+        if events.len() >= PRUNE_THRESHOLD {
+            events.retain(|_, received| now - *received < window);
+        }
+        events.insert(key, now);
+        false
+    }
+}
+
+lazy_static! {
+    static ref RECENT_EVENTS: RecentEvents = RecentEvents::default();
+}
+
+static WINDOW_SECS: AtomicI64 = AtomicI64::new(DEFAULT_WINDOW_SECS);
+
+const DEFAULT_WINDOW_SECS: i64 = 10 * 60;
+
+// Expired events are only dropped once the cache grows past this size.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppresses_events_within_window() {
+        let events = RecentEvents::default();
+        assert!(!events.check((1, 100), 1000, 60));
+        assert!(events.check((1, 100), 1030, 60));
+        assert!(!events.check((1, 200), 1030, 60));
+        assert!(!events.check((2, 100), 1030, 60));
+
+        assert!(!events.check((1, 100), 1060, 60));
+        assert!(events.check((1, 100), 1100, 60));
+    }
+}
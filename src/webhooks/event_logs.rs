@@ -2,7 +2,7 @@ use tracing::{error, info};
 
 use crate::{
     api::IgdbGameDiff,
-    documents::{ExternalGame, Keyword},
+    documents::{CollectionType, ExternalGame, Keyword},
     Status,
 };
 
@@ -53,6 +53,17 @@ impl AddGameEvent {
         )
     }
 
+    pub fn log_duplicate(self) {
+        info!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = ADD_GAME_HANDLER,
+            add_game.id = self.id,
+            add_game.name = self.name,
+            "duplicate event for game {}",
+            self.id
+        )
+    }
+
     pub fn log_error(self, status: Status) {
         error!(
             labels.log_type = WEBHOOK_LOGS,
@@ -127,6 +138,17 @@ impl UpdateGameEvent {
         )
     }
 
+    pub fn log_duplicate(self) {
+        info!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = UPDATE_GAME_HANDLER,
+            update_game.id = self.id,
+            update_game.name = self.name,
+            "duplicate event for game {}",
+            self.id
+        )
+    }
+
     pub fn log_error(self, status: Status) {
         error!(
             labels.log_type = WEBHOOK_LOGS,
@@ -204,8 +226,89 @@ impl KeywordsEvent {
     }
 }
 
+pub struct CompaniesEvent {
+    company_id: u64,
+    company_slug: String,
+}
+
+impl CompaniesEvent {
+    pub fn new(company_id: u64, company_slug: String) -> Self {
+        CompaniesEvent {
+            company_id,
+            company_slug,
+        }
+    }
+
+    pub fn log(self) {
+        info!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = COMPANIES_HANDLER,
+            company.id = self.company_id,
+            company.slug = self.company_slug,
+            "company updated"
+        )
+    }
+
+    pub fn log_error(self, status: Status) {
+        error!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = COMPANIES_HANDLER,
+            labels.status = status.to_string(),
+            company.id = self.company_id,
+            company.slug = self.company_slug,
+            "failed to update company"
+        )
+    }
+}
+
+pub struct CollectionsEvent {
+    collection_type: CollectionType,
+    collection_id: u64,
+    collection_slug: String,
+}
+
+impl CollectionsEvent {
+    pub fn new(
+        collection_type: CollectionType,
+        collection_id: u64,
+        collection_slug: String,
+    ) -> Self {
+        CollectionsEvent {
+            collection_type,
+            collection_id,
+            collection_slug,
+        }
+    }
+
+    pub fn log(self, updated_games: usize) {
+        info!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = COLLECTIONS_HANDLER,
+            collection.kind = format!("{:?}", self.collection_type),
+            collection.id = self.collection_id,
+            collection.slug = self.collection_slug,
+            collection.updated_games = updated_games,
+            "collection updated"
+        )
+    }
+
+    pub fn log_error(self, status: Status) {
+        error!(
+            labels.log_type = WEBHOOK_LOGS,
+            labels.handler = COLLECTIONS_HANDLER,
+            labels.status = status.to_string(),
+            collection.kind = format!("{:?}", self.collection_type),
+            collection.id = self.collection_id,
+            collection.slug = self.collection_slug,
+            "failed to update collection"
+        )
+    }
+}
+
 const WEBHOOK_LOGS: &str = "webhook_logs";
 const ADD_GAME_HANDLER: &str = "post_add_game";
 const UPDATE_GAME_HANDLER: &str = "post_update_game";
 const EXTERNAL_GAME_HANDLER: &str = "post_external_game";
 const KEYWORDS_HANDLER: &str = "post_keywords";
+const COMPANIES_HANDLER: &str = "post_companies";
+const COLLECTIONS_HANDLER: &str = "post_collections";
@@ -2,8 +2,11 @@ use std::collections::HashSet;
 
 use tracing::warn;
 
-use crate::documents::{
-    GameCategory, GameEntry, GameStatus, IgdbGenre, Notable, SteamData, WebsiteAuthority,
+use crate::{
+    documents::{
+        GameCategory, GameEntry, GameStatus, IgdbGenre, Notable, SteamData, WebsiteAuthority,
+    },
+    util::clock::{Clock, SystemClock},
 };
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -21,6 +24,8 @@ pub enum GameEntryClass {
 pub struct GameFilter {
     companies: HashSet<String>,
     collections: HashSet<String>,
+    blocklist: HashSet<u64>,
+    clock: Box<dyn Clock>,
 }
 
 impl GameFilter {
@@ -28,15 +33,36 @@ impl GameFilter {
         Self {
             companies: HashSet::<String>::from_iter(notable.legacy_companies.into_iter()),
             collections: HashSet::<String>::from_iter(notable.collections.into_iter()),
+            blocklist: HashSet::new(),
+            clock: Box::new(SystemClock),
         }
     }
 
+    /// Ignores games with ids in `blocklist`, regardless of their signals.
+    pub fn with_blocklist(mut self, blocklist: HashSet<u64>) -> Self {
+        self.blocklist = blocklist;
+        self
+    }
+
+    /// Uses `clock` instead of the system time to tell released games apart.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Returns true if the game with `id` is in the blocklist.
+    pub fn is_blocklisted(&self, id: u64) -> bool {
+        self.blocklist.contains(&id)
+    }
+
     pub fn filter(&self, game: &GameEntry) -> bool {
         !matches!(self.classify(game), GameEntryClass::Ignore)
     }
 
     pub fn classify(&self, game: &GameEntry) -> GameEntryClass {
-        if is_popular_early_access(&game) {
+        if self.blocklist.contains(&game.id) {
+            GameEntryClass::Ignore
+        } else if is_popular_early_access(&game) {
             GameEntryClass::EarlyAccess
         } else if is_expansion(&game) {
             GameEntryClass::Expansion
@@ -70,7 +96,9 @@ impl GameFilter {
     }
 
     pub fn explain(&self, game: &GameEntry) -> RejectionReason {
-        if !game.is_released() {
+        if self.blocklist.contains(&game.id) {
+            RejectionReason::Blocklisted
+        } else if !game.is_released(self.clock.as_ref()) {
             if game.scores.hype.unwrap_or_default() == 0 {
                 RejectionReason::FutureReleaseNoHype
             } else if game.scores.thumbs.is_some() {
@@ -96,6 +124,7 @@ impl GameFilter {
 
 #[derive(Clone, Copy, Debug)]
 pub enum RejectionReason {
+    Blocklisted,
     FutureReleaseNoHype,
     FutureReleaseWithThumbsUp,
     FutureReleaseCasual,
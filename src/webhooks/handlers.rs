@@ -1,23 +1,71 @@
 use crate::{
     api::{
-        FirestoreApi, GogScrape, IgdbApi, IgdbExternalGame, IgdbGame, MetacriticApi, SteamDataApi,
-        SteamScrape,
+        update_digests, FirestoreApi, GogScrape, IgdbApi, IgdbCollection, IgdbCompany,
+        IgdbExternalGame, IgdbGame, MetacriticApi, SteamDataApi, SteamScrape,
+    },
+    documents::{
+        Collection, CollectionDigest, CollectionType, Company, ExternalGame, FollowKind,
+        GameDigest, GameEntry, IgdbSnapshot, Keyword, Notification, NotificationEvent,
+        OutboundEvent, ResolveTaskKind,
     },
-    documents::{ExternalGame, GameEntry, Keyword},
     library::firestore,
+    notifications::Dispatcher,
+    util::{
+        clock::{Clock, SystemClock},
+        health,
+    },
     Status,
 };
+use ::firestore::path;
 use chrono::Utc;
-use std::{convert::Infallible, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    sync::Arc,
+};
 use tracing::{instrument, trace_span, warn, Instrument};
 use warp::http::StatusCode;
 
 use super::{
-    event_logs::{AddGameEvent, ExternalGameEvent, KeywordsEvent, UpdateGameEvent},
+    dedup,
+    event_logs::{
+        AddGameEvent, CollectionsEvent, CompaniesEvent, ExternalGameEvent, KeywordsEvent,
+        UpdateGameEvent,
+    },
     filtering::GameFilter,
-    prefiltering::IgdbPrefilter,
+    outbound,
+    prefiltering::{IgdbPrefilter, PrefilterRejectionReason},
+    resolve_queue, retry_queue,
 };
 
+/// Liveness probe that fails when the resolver needs a restart.
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn healthz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let report = health::check(&firestore, &igdb).await;
+    let status = match report.live {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+/// Readiness probe that fails while a dependency of the resolver is failing.
+#[instrument(level = "trace", skip(firestore, igdb))]
+pub async fn readyz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let report = health::check(&firestore, &igdb).await;
+    let status = match report.ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
 #[instrument(level = "trace", skip(igdb_game, firestore, igdb, game_filter))]
 pub async fn add_game_webhook(
     igdb_game: IgdbGame,
@@ -25,30 +73,72 @@ pub async fn add_game_webhook(
     igdb: Arc<IgdbApi>,
     game_filter: Arc<GameFilter>,
 ) -> Result<impl warp::Reply, Infallible> {
+    if dedup::is_duplicate(&igdb_game) {
+        AddGameEvent::new(igdb_game.id, igdb_game.name).log_duplicate();
+        return Ok(StatusCode::OK);
+    }
+
+    if resolve_queue::is_enabled() {
+        match resolve_queue::enqueue(&firestore, &igdb_game, ResolveTaskKind::AddGame).await {
+            Ok(()) => return Ok(StatusCode::OK),
+            Err(status) => warn!(
+                "Failed to enqueue '{}', resolving directly: {status}",
+                igdb_game.name
+            ),
+        }
+    }
+
+    add_game(igdb_game, firestore, igdb, game_filter).await;
+    Ok(StatusCode::OK)
+}
+
+/// Resolves a game received by the `/add_game` webhook, either directly or
+/// when delivered by the resolve queue.
+#[instrument(level = "trace", skip(igdb_game, firestore, igdb, game_filter))]
+pub(super) async fn add_game(
+    igdb_game: IgdbGame,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+) {
     let event = AddGameEvent::new(igdb_game.id, igdb_game.name.clone());
 
-    if !IgdbPrefilter::filter(&igdb_game) {
-        event.log_prefilter_reject(IgdbPrefilter::explain(&igdb_game));
-        return Ok(StatusCode::OK);
+    if firestore::rejected::contains(&firestore, igdb_game.id).await {
+        event.log_prefilter_reject(PrefilterRejectionReason::Blocklisted);
+        return;
+    }
+    if !IgdbPrefilter::filter(&igdb_game, igdb.platform_scope()) {
+        event.log_prefilter_reject(IgdbPrefilter::explain(&igdb_game, igdb.platform_scope()));
+        return;
+    }
+    if let Ok(game_entry) = firestore::games::read(&firestore, igdb_game.id).await {
+        if game_entry.is_tombstone() {
+            event.log_prefilter_reject(PrefilterRejectionReason::Tombstone);
+            return;
+        }
     }
 
     match igdb
-        .resolve_only(Arc::clone(&firestore), igdb_game, &game_filter)
+        .resolve_only(Arc::clone(&firestore), igdb_game.clone(), &game_filter)
         .await
     {
         Ok((mut game_entry, rejection)) => {
             if let Some(rejection) = rejection {
                 event.log_reject(rejection);
             } else if let Err(status) = firestore::games::write(&firestore, &mut game_entry).await {
+                retry_queue::enqueue(&firestore, igdb_game, &status).await;
                 event.log_error(status);
             } else {
+                schedule_backfill(&firestore, &game_entry).await;
+                schedule_new_release_notifications(&firestore, &game_entry);
                 event.log()
             }
         }
-        Err(status) => event.log_error(status),
+        Err(status) => {
+            retry_queue::enqueue(&firestore, igdb_game, &status).await;
+            event.log_error(status)
+        }
     }
-
-    Ok(StatusCode::OK)
 }
 
 #[instrument(level = "trace", skip(igdb_game, firestore, igdb, game_filter))]
@@ -58,19 +148,58 @@ pub async fn update_game_webhook(
     igdb: Arc<IgdbApi>,
     game_filter: Arc<GameFilter>,
 ) -> Result<impl warp::Reply, Infallible> {
+    if dedup::is_duplicate(&igdb_game) {
+        UpdateGameEvent::new(igdb_game.id, igdb_game.name).log_duplicate();
+        return Ok(StatusCode::OK);
+    }
+
+    if resolve_queue::is_enabled() {
+        match resolve_queue::enqueue(&firestore, &igdb_game, ResolveTaskKind::UpdateGame).await {
+            Ok(()) => return Ok(StatusCode::OK),
+            Err(status) => warn!(
+                "Failed to enqueue '{}', resolving directly: {status}",
+                igdb_game.name
+            ),
+        }
+    }
+
+    update_game(igdb_game, firestore, igdb, game_filter).await;
+    Ok(StatusCode::OK)
+}
+
+/// Resolves a game received by the `/update_game` webhook, either directly or
+/// when delivered by the resolve queue.
+#[instrument(level = "trace", skip(igdb_game, firestore, igdb, game_filter))]
+pub(super) async fn update_game(
+    igdb_game: IgdbGame,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+) {
     let event = UpdateGameEvent::new(igdb_game.id, igdb_game.name.clone());
+    schedule_snapshot(&firestore, &igdb_game);
 
-    if !IgdbPrefilter::filter(&igdb_game) {
-        event.log_prefilter_reject(IgdbPrefilter::explain(&igdb_game));
-        return Ok(StatusCode::OK);
+    if firestore::rejected::contains(&firestore, igdb_game.id).await {
+        event.log_prefilter_reject(PrefilterRejectionReason::Blocklisted);
+        return;
+    }
+    if !IgdbPrefilter::filter(&igdb_game, igdb.platform_scope()) {
+        event.log_prefilter_reject(IgdbPrefilter::explain(&igdb_game, igdb.platform_scope()));
+        if !igdb_game.is_main_category() {
+            schedule_digest_removal(firestore, igdb_game);
+        }
+        return;
     }
 
     let game_entry = firestore::games::read(&firestore, igdb_game.id).await;
 
     match game_entry {
+        // Merged duplicates and deleted games are tombstones that are not
+        // updated anymore.
+        Ok(game_entry) if game_entry.is_tombstone() => event.log(None),
         Ok(mut game_entry) => match game_entry.igdb_game.diff(&igdb_game) {
             diff if diff.empty() => {
-                if needs_update(&game_entry) {
+                if needs_update(&game_entry, &SystemClock) {
                     match update_steam_data(firestore, &mut game_entry, igdb_game).await {
                         Ok(()) => event.log(Some(diff)),
                         Err(status) => event.log_error(status),
@@ -79,18 +208,41 @@ pub async fn update_game_webhook(
                     event.log(None)
                 }
             }
-            diff if diff.needs_resolve() => match igdb.resolve(firestore, igdb_game).await {
-                Ok(_) => event.log(Some(diff)),
-                Err(status) => event.log_error(status),
-            },
-            diff => match update_steam_data(firestore, &mut game_entry, igdb_game).await {
-                Ok(()) => event.log(Some(diff)),
-                Err(status) => event.log_error(status),
-            },
+            diff if diff.needs_resolve() => {
+                match igdb
+                    .resolve(Arc::clone(&firestore), igdb_game.clone())
+                    .await
+                {
+                    Ok(resolved) => {
+                        notify_followers(&firestore, &resolved);
+                        if diff.release_date_changed() || diff.status.is_some() {
+                            schedule_update_notifications(&firestore, &game_entry, &resolved);
+                        }
+                        event.log(Some(diff))
+                    }
+                    Err(status) => {
+                        retry_queue::enqueue(&firestore, igdb_game, &status).await;
+                        event.log_error(status)
+                    }
+                }
+            }
+            diff => {
+                let before = diff.status.is_some().then(|| game_entry.clone());
+                match update_steam_data(Arc::clone(&firestore), &mut game_entry, igdb_game).await {
+                    Ok(()) => {
+                        notify_followers(&firestore, &game_entry);
+                        if let Some(before) = before {
+                            schedule_update_notifications(&firestore, &before, &game_entry);
+                        }
+                        event.log(Some(diff))
+                    }
+                    Err(status) => event.log_error(status),
+                }
+            }
         },
         Err(Status::NotFound(_)) => {
             match igdb
-                .resolve_only(Arc::clone(&firestore), igdb_game, &game_filter)
+                .resolve_only(Arc::clone(&firestore), igdb_game.clone(), &game_filter)
                 .await
             {
                 Ok((mut game_entry, rejection)) => {
@@ -99,22 +251,247 @@ pub async fn update_game_webhook(
                     } else if let Err(status) =
                         firestore::games::write(&firestore, &mut game_entry).await
                     {
+                        retry_queue::enqueue(&firestore, igdb_game, &status).await;
                         event.log_error(status);
                     } else {
+                        schedule_backfill(&firestore, &game_entry).await;
+                        schedule_new_release_notifications(&firestore, &game_entry);
                         event.log_added()
                     }
                 }
-                Err(status) => event.log_error(status),
+                Err(status) => {
+                    retry_queue::enqueue(&firestore, igdb_game, &status).await;
+                    event.log_error(status)
+                }
             }
         }
         Err(status) => event.log_error(status),
     }
+}
 
-    Ok(StatusCode::OK)
+/// Spawns a task that removes the digest of a game that changed to a category
+/// that is no longer tracked from the companies and collections that include it.
+fn schedule_digest_removal(firestore: Arc<FirestoreApi>, igdb_game: IgdbGame) {
+    tokio::spawn(
+        async move {
+            let mut game_entry = match firestore::games::read(&firestore, igdb_game.id).await {
+                Ok(game_entry) => game_entry,
+                Err(Status::NotFound(_)) => return,
+                Err(status) => {
+                    warn!("{status}");
+                    return;
+                }
+            };
+
+            if game_entry.is_main_category() {
+                game_entry.update(igdb_game);
+                apply_override(&firestore, &mut game_entry).await;
+                update_digests(&firestore, &game_entry).await;
+            }
+        }
+        .instrument(trace_span!("spawn_digest_removal")),
+    );
+}
+
+/// Stores the raw IGDB payload of an update for inspecting bad resolves.
+fn schedule_snapshot(firestore: &Arc<FirestoreApi>, igdb_game: &IgdbGame) {
+    let snapshot = match IgdbSnapshot::new(igdb_game, Utc::now().timestamp_millis()) {
+        Ok(snapshot) => snapshot,
+        Err(status) => {
+            warn!("Failed to snapshot '{}': {status}", igdb_game.name);
+            return;
+        }
+    };
+
+    let firestore = Arc::clone(firestore);
+    tokio::spawn(
+        async move {
+            if let Err(status) = firestore::igdb_snapshots::add(&firestore, &snapshot).await {
+                warn!("Failed to store snapshot of {}: {status}", snapshot.game_id);
+            }
+        }
+        .instrument(trace_span!("spawn_igdb_snapshot")),
+    );
+}
+
+/// Notifies outbound webhooks that follow the game about its update.
+fn notify_followers(firestore: &Arc<FirestoreApi>, game_entry: &GameEntry) {
+    outbound::notify(
+        Arc::clone(firestore),
+        OutboundEvent::GameUpdated {
+            game: GameDigest::from(game_entry.clone()),
+        },
+    );
+}
+
+/// Notifies users that have the game in their library or wishlist that its
+/// release date or status changed.
+fn schedule_update_notifications(
+    firestore: &Arc<FirestoreApi>,
+    before: &GameEntry,
+    after: &GameEntry,
+) {
+    let mut events = vec![];
+    if before.release_date != after.release_date {
+        events.push(NotificationEvent::ReleaseDateChanged {
+            old_release_date: before.release_date,
+            new_release_date: after.release_date,
+            release_precision: after.release_precision,
+        });
+    }
+    if before.status != after.status {
+        events.push(NotificationEvent::StatusChanged {
+            old_status: before.status,
+            new_status: after.status,
+        });
+    }
+    if events.is_empty() {
+        return;
+    }
+
+    let firestore = Arc::clone(firestore);
+    let notifications = events
+        .into_iter()
+        .map(|event| Notification {
+            game_id: after.id,
+            name: after.name.clone(),
+            event,
+            timestamp: Utc::now().timestamp(),
+            seen: false,
+        })
+        .collect::<Vec<_>>();
+    let game_id = after.id;
+    tokio::spawn(
+        async move {
+            let user_ids = match owners(&firestore, game_id).await {
+                Ok(user_ids) => user_ids,
+                Err(status) => {
+                    warn!("{status}");
+                    return;
+                }
+            };
+
+            for user_id in user_ids {
+                for notification in &notifications {
+                    if let Err(status) = Dispatcher::global()
+                        .notify(&firestore, &user_id, notification.clone())
+                        .await
+                    {
+                        warn!("Failed to notify user '{user_id}': {status}");
+                    }
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_update_notifications")),
+    );
+}
+
+/// Notifies users that follow a developer, publisher, franchise or collection
+/// of a newly added game.
+fn schedule_new_release_notifications(firestore: &Arc<FirestoreApi>, game_entry: &GameEntry) {
+    let followed = [
+        (
+            FollowKind::Company,
+            game_entry
+                .developers
+                .iter()
+                .chain(game_entry.publishers.iter())
+                .map(|company| (company.id, company.name.clone()))
+                .collect::<HashMap<_, _>>(),
+        ),
+        (
+            FollowKind::Franchise,
+            game_entry
+                .franchises
+                .iter()
+                .map(|franchise| (franchise.id, franchise.name.clone()))
+                .collect(),
+        ),
+        (
+            FollowKind::Collection,
+            game_entry
+                .collections
+                .iter()
+                .map(|collection| (collection.id, collection.name.clone()))
+                .collect(),
+        ),
+    ];
+
+    let firestore = Arc::clone(firestore);
+    let (game_id, name) = (game_entry.id, game_entry.name.clone());
+    tokio::spawn(
+        async move {
+            // A user following more than one of the game's entities is only
+            // notified once.
+            let mut notified = HashSet::new();
+            for (kind, entities) in followed {
+                let ids = entities.keys().cloned().collect::<Vec<_>>();
+                let followers =
+                    match firestore::follows::list_followers(&firestore, kind, &ids).await {
+                        Ok(followers) => followers,
+                        Err(status) => {
+                            warn!("{status}");
+                            continue;
+                        }
+                    };
+
+                for follows in followers {
+                    let followed_id = match follows
+                        .ids(kind)
+                        .iter()
+                        .find(|id| entities.contains_key(*id))
+                    {
+                        Some(id) => *id,
+                        None => continue,
+                    };
+                    if !notified.insert(follows.user_id.clone()) {
+                        continue;
+                    }
+
+                    let notification = Notification {
+                        game_id,
+                        name: name.clone(),
+                        event: NotificationEvent::NewRelease {
+                            followed_kind: kind,
+                            followed_id,
+                            followed_name: entities[&followed_id].clone(),
+                        },
+                        timestamp: Utc::now().timestamp(),
+                        seen: false,
+                    };
+                    if let Err(status) = Dispatcher::global()
+                        .notify(&firestore, &follows.user_id, notification)
+                        .await
+                    {
+                        warn!("Failed to notify user '{}': {status}", follows.user_id);
+                    }
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_new_release_notifications")),
+    );
 }
 
-fn needs_update(game_entry: &GameEntry) -> bool {
-    let today = Utc::now().naive_utc().timestamp();
+/// Returns the ids of users that have the game in their library or wishlist.
+async fn owners(firestore: &FirestoreApi, game_id: u64) -> Result<HashSet<String>, Status> {
+    let mut user_ids =
+        HashSet::<String>::from_iter(firestore::library::list_owners(firestore, game_id).await?);
+    user_ids.extend(firestore::wishlist::list_owners(firestore, game_id).await?);
+    Ok(user_ids)
+}
+
+/// Adds a partially resolved game in the backfill queue.
+async fn schedule_backfill(firestore: &FirestoreApi, game_entry: &GameEntry) {
+    if let Err(status) = firestore::backfill_queue::add(firestore, game_entry).await {
+        warn!(
+            "Failed to schedule backfill for '{}': {status}",
+            game_entry.name
+        );
+    }
+}
+
+fn needs_update(game_entry: &GameEntry, clock: &dyn Clock) -> bool {
+    let today = clock.now();
     let close_to_release = (today - game_entry.release_date).abs() < 8 * DAY_SECS;
 
     // Update if never updated || was not updated in the last 7 days ago ||
@@ -205,9 +582,20 @@ async fn update_steam_data(
         Err(status) => warn!("{status}"),
     }
 
+    apply_override(&firestore, game_entry).await;
     firestore::games::write(&firestore, game_entry).await
 }
 
+/// Re-applies the curator override of `game_entry`, which updates from IGDB
+/// payloads reset.
+async fn apply_override(firestore: &FirestoreApi, game_entry: &mut GameEntry) {
+    match firestore::overrides::read(firestore, game_entry.id).await {
+        Ok(game_override) => game_override.apply(game_entry),
+        Err(Status::NotFound(_)) => {}
+        Err(status) => warn!("Override lookup failed: {status}"),
+    }
+}
+
 #[instrument(level = "trace", skip(external_game, firestore))]
 pub async fn external_games_webhook(
     external_game: IgdbExternalGame,
@@ -256,3 +644,332 @@ pub async fn keywords_webhook(
 
     Ok(StatusCode::OK)
 }
+
+#[instrument(level = "trace", skip(igdb_company, firestore, igdb))]
+pub async fn companies_webhook(
+    igdb_company: IgdbCompany,
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    let event = CompaniesEvent::new(igdb_company.id, normalize_slug(&igdb_company.slug));
+
+    match upsert_company(&firestore, &igdb, igdb_company).await {
+        Ok(()) => event.log(),
+        Err(status) => event.log_error(status),
+    }
+
+    Ok(StatusCode::OK)
+}
+
+/// Updates company info in the companies collection, keeping the digests of
+/// games it has developed or published that are maintained on game updates.
+async fn upsert_company(
+    firestore: &FirestoreApi,
+    igdb: &IgdbApi,
+    igdb_company: IgdbCompany,
+) -> Result<(), Status> {
+    let mut company = match firestore::companies::read(firestore, igdb_company.id).await {
+        Ok(company) => company,
+        Err(Status::NotFound(_)) => Company {
+            id: igdb_company.id,
+            ..Default::default()
+        },
+        Err(status) => return Err(status),
+    };
+
+    company.name = igdb_company.name;
+    company.slug = normalize_slug(&igdb_company.slug);
+    company.description = igdb_company.description;
+    company.logo = match igdb_company.logo {
+        Some(logo_id) => match igdb.get_company_logo(logo_id).await {
+            Ok(logo) => logo,
+            Err(status) => {
+                warn!(
+                    "Failed to retrieve logo for company '{}': {status}",
+                    company.slug
+                );
+                company.logo
+            }
+        },
+        None => None,
+    };
+
+    firestore::companies::write(firestore, &company).await
+}
+
+fn normalize_slug(slug: &str) -> String {
+    slug.trim().to_lowercase()
+}
+
+#[instrument(level = "trace", skip(igdb_collection, firestore))]
+pub async fn collections_webhook(
+    igdb_collection: IgdbCollection,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    sync_collection_webhook(&firestore, CollectionType::Collection, igdb_collection).await;
+    Ok(StatusCode::OK)
+}
+
+#[instrument(level = "trace", skip(igdb_franchise, firestore))]
+pub async fn franchises_webhook(
+    igdb_franchise: IgdbCollection,
+    firestore: Arc<FirestoreApi>,
+) -> Result<impl warp::Reply, Infallible> {
+    sync_collection_webhook(&firestore, CollectionType::Franchise, igdb_franchise).await;
+    Ok(StatusCode::OK)
+}
+
+async fn sync_collection_webhook(
+    firestore: &FirestoreApi,
+    collection_type: CollectionType,
+    igdb_collection: IgdbCollection,
+) {
+    let event = CollectionsEvent::new(
+        collection_type,
+        igdb_collection.id,
+        normalize_slug(&igdb_collection.slug),
+    );
+
+    match sync_collection(firestore, collection_type, igdb_collection).await {
+        Ok(updated_games) => event.log(updated_games),
+        Err(status) => event.log_error(status),
+    }
+}
+
+/// Syncs a collection / franchise doc with its IGDB counterpart.
+///
+/// Game entries in the catalog that are part of the collection get a fresh
+/// `CollectionDigest`, while games that IGDB no longer lists have it removed.
+/// Returns the number of game entries that were updated.
+async fn sync_collection(
+    firestore: &FirestoreApi,
+    collection_type: CollectionType,
+    igdb_collection: IgdbCollection,
+) -> Result<usize, Status> {
+    let mut collection = match read_collection(firestore, collection_type, igdb_collection.id).await
+    {
+        Ok(collection) => collection,
+        Err(Status::NotFound(_)) => Collection {
+            id: igdb_collection.id,
+            ..Default::default()
+        },
+        Err(status) => return Err(status),
+    };
+    let is_new = collection.games.is_empty() && collection.name.is_empty();
+
+    let digest = CollectionDigest {
+        id: igdb_collection.id,
+        name: igdb_collection.name.clone(),
+        slug: normalize_slug(&igdb_collection.slug),
+        igdb_type: collection_type,
+    };
+
+    // Check both games listed by IGDB and games that were previously part of
+    // the collection in case some of them were removed.
+    let mut game_ids = igdb_collection.games.clone();
+    game_ids.extend(collection.games.iter().map(|game| game.id));
+    game_ids.sort();
+    game_ids.dedup();
+    let result = firestore::games::batch_read(firestore, &game_ids).await?;
+
+    let mut updated_games = 0;
+    let mut games = vec![];
+    for mut game_entry in result.documents {
+        let keep = igdb_collection.games.contains(&game_entry.id);
+        let (digests, field) = match collection_type {
+            CollectionType::Franchise => (&mut game_entry.franchises, path!(GameEntry::franchises)),
+            _ => (&mut game_entry.collections, path!(GameEntry::collections)),
+        };
+
+        if update_collection_digest(digests, &digest, keep) {
+            match firestore::games::patch(firestore, &game_entry, vec![field]).await {
+                Ok(()) => {
+                    // Game digests include collection names that need to be
+                    // refreshed in companies and other collections.
+                    update_digests(firestore, &game_entry).await;
+                    updated_games += 1;
+                }
+                Err(status) => warn!(
+                    "Failed to update collection '{}' in game '{}': {status}",
+                    digest.slug, game_entry.name
+                ),
+            }
+        }
+
+        if keep && game_entry.is_main_category() && !game_entry.archived {
+            games.push(GameDigest::from(game_entry));
+        }
+    }
+
+    // No need to create a collection that contains no tracked games.
+    if is_new && games.is_empty() {
+        return Ok(updated_games);
+    }
+
+    collection.name = igdb_collection.name;
+    collection.slug = digest.slug;
+    collection.url = igdb_collection.url;
+    collection.games = games;
+
+    write_collection(firestore, collection_type, &collection).await?;
+    Ok(updated_games)
+}
+
+/// Updates `digests` of a game with a fresh collection `digest`. If `keep` is
+/// false the collection is removed from `digests` instead.
+///
+/// Returns true if `digests` was modified.
+fn update_collection_digest(
+    digests: &mut Vec<CollectionDigest>,
+    digest: &CollectionDigest,
+    keep: bool,
+) -> bool {
+    match digests.iter().position(|e| e.id == digest.id) {
+        Some(pos) if keep => {
+            let modified = digests[pos].name != digest.name || digests[pos].slug != digest.slug;
+            digests[pos] = digest.clone();
+            modified
+        }
+        Some(pos) => {
+            digests.remove(pos);
+            true
+        }
+        None if keep => {
+            digests.push(digest.clone());
+            true
+        }
+        None => false,
+    }
+}
+
+async fn read_collection(
+    firestore: &FirestoreApi,
+    collection_type: CollectionType,
+    id: u64,
+) -> Result<Collection, Status> {
+    match collection_type {
+        CollectionType::Franchise => firestore::franchises::read(firestore, id).await,
+        _ => firestore::collections::read(firestore, id).await,
+    }
+}
+
+async fn write_collection(
+    firestore: &FirestoreApi,
+    collection_type: CollectionType,
+    collection: &Collection,
+) -> Result<(), Status> {
+    match collection_type {
+        CollectionType::Franchise => firestore::franchises::write(firestore, collection).await,
+        _ => firestore::collections::write(firestore, collection).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::clock::FrozenClock;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn entry(release_date: i64, last_updated: i64) -> GameEntry {
+        GameEntry {
+            release_date,
+            last_updated,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn needs_update_when_never_updated() {
+        assert!(needs_update(
+            &entry(NOW - 365 * DAY_SECS, 0),
+            &FrozenClock(NOW)
+        ));
+    }
+
+    #[test]
+    fn needs_update_weekly_away_from_release() {
+        let release_date = NOW - 365 * DAY_SECS;
+        assert!(!needs_update(
+            &entry(release_date, NOW - 6 * DAY_SECS),
+            &FrozenClock(NOW)
+        ));
+        assert!(needs_update(
+            &entry(release_date, NOW - 8 * DAY_SECS),
+            &FrozenClock(NOW)
+        ));
+    }
+
+    #[test]
+    fn needs_update_daily_close_to_release() {
+        for release_date in [NOW - 7 * DAY_SECS, NOW + 7 * DAY_SECS] {
+            assert!(!needs_update(
+                &entry(release_date, NOW - DAY_SECS / 2),
+                &FrozenClock(NOW)
+            ));
+            assert!(needs_update(
+                &entry(release_date, NOW - 2 * DAY_SECS),
+                &FrozenClock(NOW)
+            ));
+        }
+    }
+
+    fn digest(id: u64, name: &str) -> CollectionDigest {
+        CollectionDigest {
+            id,
+            name: name.to_owned(),
+            slug: name.to_owned(),
+            igdb_type: CollectionType::Collection,
+        }
+    }
+
+    #[test]
+    fn update_collection_digest_renames_collection() {
+        let mut digests = vec![digest(1, "a"), digest(2, "b")];
+
+        assert!(update_collection_digest(
+            &mut digests,
+            &digest(1, "c"),
+            true
+        ));
+        assert_eq!(digests.len(), 2);
+        assert_eq!(digests[0].name, "c");
+    }
+
+    #[test]
+    fn update_collection_digest_ignores_unchanged_collection() {
+        let mut digests = vec![digest(1, "a")];
+
+        assert!(!update_collection_digest(
+            &mut digests,
+            &digest(1, "a"),
+            true
+        ));
+        assert_eq!(digests.len(), 1);
+    }
+
+    #[test]
+    fn update_collection_digest_adds_missing_collection() {
+        let mut digests = vec![digest(1, "a")];
+
+        assert!(update_collection_digest(
+            &mut digests,
+            &digest(2, "b"),
+            true
+        ));
+        assert_eq!(digests.len(), 2);
+    }
+
+    #[test]
+    fn update_collection_digest_removes_collection() {
+        let mut digests = vec![digest(1, "a"), digest(2, "b")];
+
+        assert!(update_collection_digest(
+            &mut digests,
+            &digest(1, "a"),
+            false
+        ));
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].id, 2);
+    }
+}
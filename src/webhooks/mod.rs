@@ -1,6 +1,16 @@
+// Webhook events are handled in spawned tasks that nobody waits on. Errors are
+// logged as Status instead of panics that silently drop the event.
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 mod event_logs;
 mod handlers;
 
+pub mod backfill;
+pub mod dedup;
 pub mod filtering;
+pub mod outbound;
 pub mod prefiltering;
+pub mod resolve_queue;
+pub mod retry_queue;
 pub mod routes;
+pub mod simulate;
@@ -0,0 +1,363 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tracing::{error, info, instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::FirestoreApi,
+    documents::{DeliveryStatus, OutboundEvent, OutboundWebhook, WebhookDelivery},
+    library::firestore::{self, access},
+    util::http_client,
+    Status,
+};
+
+use super::retry_queue::backoff_secs;
+
+/// Registers `webhook` for `owner`. Registering the same URL again replaces
+/// the previous registration.
+#[instrument(level = "trace", skip(firestore, webhook))]
+pub async fn register(
+    firestore: &FirestoreApi,
+    owner: &str,
+    mut webhook: OutboundWebhook,
+) -> Result<OutboundWebhook, Status> {
+    check_target(&webhook.url).await?;
+    if webhook.secret.is_empty() {
+        return Err(Status::invalid_argument("Missing webhook secret."));
+    }
+
+    webhook.id = webhook_id(owner, &webhook.url);
+    webhook.owner = owner.to_owned();
+    firestore::outbound_webhooks::write(firestore, &webhook).await?;
+    Ok(webhook)
+}
+
+/// Removes the webhook with `webhook_id` if it is owned by `owner`.
+#[instrument(level = "trace", skip(firestore))]
+pub async fn unregister(
+    firestore: &FirestoreApi,
+    owner: &str,
+    webhook_id: &str,
+) -> Result<(), Status> {
+    let webhook = firestore::outbound_webhooks::read(firestore, webhook_id).await?;
+    if webhook.owner != owner {
+        return Err(Status::not_found(format!(
+            "Webhook '{webhook_id}' was not found"
+        )));
+    }
+    firestore::outbound_webhooks::delete(firestore, webhook_id).await
+}
+
+/// Spawns a task that delivers `event` to all outbound webhooks that are
+/// subscribed to it.
+pub fn notify(firestore: Arc<FirestoreApi>, event: OutboundEvent) {
+    if access::is_dry_run(&firestore) {
+        return;
+    }
+
+    tokio::spawn(
+        async move {
+            let webhooks = match subscribers(&firestore, &event).await {
+                Ok(webhooks) => webhooks,
+                Err(status) => {
+                    warn!("Failed to read outbound webhooks: {status}");
+                    return;
+                }
+            };
+
+            for webhook in webhooks {
+                dispatch(&firestore, &webhook, &event).await;
+            }
+        }
+        .instrument(trace_span!("spawn_outbound_notify")),
+    );
+}
+
+/// Spawns a background task that periodically retries pending deliveries
+/// whose backoff has expired.
+pub fn spawn_worker(firestore: Arc<FirestoreApi>) {
+    tokio::spawn(
+        async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let now = Utc::now().naive_utc().timestamp();
+                let deliveries =
+                    match firestore::webhook_deliveries::list_due(&firestore, now).await {
+                        Ok(deliveries) => deliveries,
+                        Err(status) => {
+                            warn!("Failed to read webhook deliveries: {status}");
+                            continue;
+                        }
+                    };
+
+                for mut delivery in deliveries {
+                    match firestore::outbound_webhooks::read(&firestore, &delivery.webhook_id).await
+                    {
+                        Ok(webhook) => attempt(&firestore, &webhook.secret, &mut delivery).await,
+                        Err(Status::NotFound(_)) => {
+                            delivery.status = DeliveryStatus::Failed;
+                            delivery.last_error = "webhook was unregistered".to_owned();
+                            if let Err(status) =
+                                firestore::webhook_deliveries::write(&firestore, &delivery).await
+                            {
+                                warn!("{status}");
+                            }
+                        }
+                        Err(status) => warn!("{status}"),
+                    }
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_outbound_worker")),
+    );
+}
+
+async fn subscribers(
+    firestore: &FirestoreApi,
+    event: &OutboundEvent,
+) -> Result<Vec<OutboundWebhook>, Status> {
+    let webhooks = match event {
+        OutboundEvent::LibraryAdded { user_id, .. }
+        | OutboundEvent::LibraryRemoved { user_id, .. } => {
+            firestore::outbound_webhooks::list_by_owner(firestore, user_id).await?
+        }
+        OutboundEvent::GameUpdated { game } => {
+            firestore::outbound_webhooks::list_following(firestore, game.id).await?
+        }
+    };
+
+    Ok(webhooks
+        .into_iter()
+        .filter(|webhook| webhook.events.contains(&event.event_type()))
+        .collect())
+}
+
+async fn dispatch(firestore: &FirestoreApi, webhook: &OutboundWebhook, event: &OutboundEvent) {
+    let payload = match serde_json::to_string(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize {} event: {e}", event.name());
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let mut delivery = WebhookDelivery {
+        id: format!(
+            "{}_{}",
+            webhook.id,
+            now.timestamp_nanos_opt().unwrap_or_default()
+        ),
+        webhook_id: webhook.id.clone(),
+        url: webhook.url.clone(),
+        event: event.name().to_owned(),
+        payload,
+        created: now.naive_utc().timestamp(),
+        ..Default::default()
+    };
+    attempt(firestore, &webhook.secret, &mut delivery).await;
+}
+
+/// Attempts to deliver the event and records the outcome in the delivery log.
+#[instrument(
+    level = "trace",
+    skip(firestore, secret, delivery),
+    fields(
+        delivery_id = %delivery.id,
+        attempts = %delivery.attempts,
+    )
+)]
+async fn attempt(firestore: &FirestoreApi, secret: &str, delivery: &mut WebhookDelivery) {
+    match send(delivery, secret).await {
+        Ok(()) => {
+            delivery.status = DeliveryStatus::Delivered;
+            delivery.last_error.clear();
+            info!("Delivered {} event to '{}'", delivery.event, delivery.url);
+        }
+        Err(status) => {
+            delivery.attempts += 1;
+            delivery.last_error = status.to_string();
+
+            if delivery.attempts >= MAX_ATTEMPTS {
+                delivery.status = DeliveryStatus::Failed;
+                error!(
+                    "Giving up on delivering {} event to '{}' after {} attempts: {status}",
+                    delivery.event, delivery.url, delivery.attempts
+                );
+            } else {
+                delivery.next_attempt =
+                    Utc::now().naive_utc().timestamp() + backoff_secs(delivery.attempts);
+                warn!(
+                    "Failed to deliver {} event to '{}': {status}",
+                    delivery.event, delivery.url
+                );
+            }
+        }
+    }
+
+    if let Err(status) = firestore::webhook_deliveries::write(firestore, delivery).await {
+        warn!("Failed to log webhook delivery: {status}");
+    }
+}
+
+async fn send(delivery: &WebhookDelivery, secret: &str) -> Result<(), Status> {
+    // Hosts are checked again on delivery, as their DNS records may have
+    // changed since registration.
+    check_target(&delivery.url).await?;
+
+    let resp = http_client::shared()
+        .post(&delivery.url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header("X-Espy-Event", &delivery.event)
+        .header(
+            "X-Espy-Signature",
+            format!("sha256={}", sign(secret, &delivery.payload)),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await?;
+
+    match resp.status().is_success() {
+        true => Ok(()),
+        false => Err(Status::internal(format!(
+            "Webhook responded with {}",
+            resp.status()
+        ))),
+    }
+}
+
+/// Checks that webhook `url` uses https and that its host resolves only to
+/// public addresses, so that deliveries cannot reach internal services such as
+/// the metadata server.
+async fn check_target(url: &str) -> Result<(), Status> {
+    let parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "https" => parsed,
+        _ => {
+            return Err(Status::invalid_argument(format!(
+                "Webhook URL '{url}' must use https"
+            )))
+        }
+    };
+    let host = match parsed.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => {
+            return Err(Status::invalid_argument(format!(
+                "Webhook URL '{url}' has no host"
+            )))
+        }
+    };
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addrs = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| Status::invalid_argument(format!("Failed to resolve '{host}': {e}")))?
+        .collect::<Vec<_>>();
+    match !addrs.is_empty() && addrs.iter().all(|addr| is_public(addr.ip())) {
+        true => Ok(()),
+        false => Err(Status::invalid_argument(format!(
+            "Webhook host '{host}' does not resolve to a public address"
+        ))),
+    }
+}
+
+/// Returns true if `ip` is routable on the public internet.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.is_unspecified()
+                    || ip.is_multicast())
+            }
+        },
+    }
+}
+
+/// Returns the hex encoded HMAC-SHA256 signature of `payload` that consumers
+/// use to verify that events originate from espy.
+#[allow(clippy::expect_used)] // HMAC accepts keys of any size.
+fn sign(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC key");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Returns a stable id for the webhook of `owner` that targets `url`.
+fn webhook_id(owner: &str, url: &str) -> String {
+    let digest = hex::encode(Sha256::digest(url.as_bytes()));
+    format!("{owner}_{}", &digest[..16])
+}
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 6;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_payload() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn webhook_id_is_stable_per_url() {
+        assert_eq!(
+            webhook_id("user", "https://example.com/hook"),
+            webhook_id("user", "https://example.com/hook")
+        );
+        assert_ne!(
+            webhook_id("user", "https://example.com/hook"),
+            webhook_id("user", "https://example.com/other")
+        );
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public("8.8.8.8".parse().unwrap()));
+        assert!(is_public("2001:4860:4860::8888".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn webhooks_target_public_https_hosts() {
+        assert!(check_target("http://8.8.8.8/hook").await.is_err());
+        assert!(check_target("https://169.254.169.254/latest")
+            .await
+            .is_err());
+        assert!(check_target("https://[::1]:8443/hook").await.is_err());
+        assert!(check_target("https://8.8.8.8/hook").await.is_ok());
+    }
+}
@@ -1,21 +1,23 @@
 use tracing::warn;
 
-use crate::api::IgdbGame;
+use crate::{api::IgdbGame, documents::PlatformScope};
 
 pub struct IgdbPrefilter;
 
 impl IgdbPrefilter {
-    pub fn filter(igdb_game: &IgdbGame) -> bool {
-        igdb_game.is_pc_game()
+    /// Returns true if `igdb_game` is worth resolving, i.e. it is a main game
+    /// released on a platform in `scope` that has any user metrics.
+    pub fn filter(igdb_game: &IgdbGame, scope: &PlatformScope) -> bool {
+        igdb_game.is_in_scope(scope)
             && igdb_game.is_main_category()
             && (igdb_game.follows.unwrap_or_default() > 0
                 || igdb_game.hypes.unwrap_or_default() > 0
                 || igdb_game.aggregated_rating.unwrap_or_default() > 0.0)
     }
 
-    pub fn explain(igdb_game: &IgdbGame) -> PrefilterRejectionReason {
-        if !igdb_game.is_pc_game() {
-            PrefilterRejectionReason::NotPcGame
+    pub fn explain(igdb_game: &IgdbGame, scope: &PlatformScope) -> PrefilterRejectionReason {
+        if !igdb_game.is_in_scope(scope) {
+            PrefilterRejectionReason::OutOfPlatformScope
         } else if !igdb_game.is_main_category() {
             PrefilterRejectionReason::NotMainCategory
         } else if igdb_game.follows.unwrap_or_default() == 0
@@ -35,7 +37,9 @@ impl IgdbPrefilter {
 
 #[derive(Clone, Copy, Debug)]
 pub enum PrefilterRejectionReason {
-    NotPcGame,
+    Blocklisted,
+    Tombstone,
+    OutOfPlatformScope,
     NotMainCategory,
     NoUserMetrics,
     Unknown,
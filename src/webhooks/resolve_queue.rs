@@ -0,0 +1,187 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use chrono::Utc;
+use tokio::sync::Semaphore;
+use tracing::{error, instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::{FirestoreApi, IgdbApi, IgdbGame},
+    documents::{ResolveTask, ResolveTaskKind},
+    library::firestore,
+    Status,
+};
+
+use super::{filtering::GameFilter, handlers};
+
+/// Returns true if webhooks hand their resolves to the resolve queue instead
+/// of resolving them before they respond.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Adds `igdb_game` in the resolve queue. A pending task of the same game is
+/// replaced, so that only its latest update is resolved.
+#[instrument(level = "trace", skip(firestore, igdb_game))]
+pub async fn enqueue(
+    firestore: &FirestoreApi,
+    igdb_game: &IgdbGame,
+    kind: ResolveTaskKind,
+) -> Result<(), Status> {
+    // An update also covers games that are not in the catalog yet.
+    let kind = match firestore::resolve_queue::read(firestore, igdb_game.id).await {
+        Ok(task) if task.kind == ResolveTaskKind::UpdateGame => ResolveTaskKind::UpdateGame,
+        Ok(_) | Err(Status::NotFound(_)) => kind,
+        Err(status) => return Err(status),
+    };
+
+    firestore::resolve_queue::write(
+        firestore,
+        &ResolveTask {
+            id: igdb_game.id,
+            igdb_game: igdb_game.clone(),
+            kind,
+            enqueued: Utc::now().timestamp_millis(),
+            lease_until: 0,
+            deliveries: 0,
+        },
+    )
+    .await
+}
+
+/// Spawns a background task that consumes the resolve queue, resolving up to
+/// `concurrency` games at a time, and makes webhooks enqueue their resolves.
+///
+/// Tasks are leased while they resolve and acked when done. Tasks of a
+/// consumer that dies mid-resolve are delivered again when their lease
+/// expires. Failed resolves are handed to the retry queue by the handlers.
+pub fn spawn_consumer(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+    concurrency: usize,
+) {
+    ENABLED.store(true, Ordering::SeqCst);
+    let permits = Arc::new(Semaphore::new(concurrency));
+
+    tokio::spawn(
+        async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                // Only pull as many tasks as there are free resolve slots.
+                let available = permits.available_permits();
+                if available == 0 {
+                    continue;
+                }
+
+                let now = Utc::now().timestamp();
+                let tasks =
+                    match firestore::resolve_queue::list_ready(&firestore, now, available as u32)
+                        .await
+                    {
+                        Ok(tasks) => tasks,
+                        Err(status) => {
+                            warn!("Failed to read resolve queue: {status}");
+                            continue;
+                        }
+                    };
+
+                for task in tasks {
+                    let permit = match Arc::clone(&permits).acquire_owned().await {
+                        Ok(permit) => permit,
+                        Err(_) => return,
+                    };
+                    let firestore = Arc::clone(&firestore);
+                    let igdb = Arc::clone(&igdb);
+                    let game_filter = Arc::clone(&game_filter);
+                    tokio::spawn(
+                        async move {
+                            process(firestore, igdb, game_filter, task).await;
+                            drop(permit);
+                        }
+                        .instrument(trace_span!("spawn_resolve_task")),
+                    );
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_resolve_consumer")),
+    );
+}
+
+#[instrument(
+    level = "trace",
+    skip(firestore, igdb, game_filter, task),
+    fields(
+        game_id = %task.id,
+        deliveries = %task.deliveries,
+    )
+)]
+async fn process(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+    mut task: ResolveTask,
+) {
+    if task.deliveries >= MAX_DELIVERIES {
+        error!(
+            "Dropping '{}' from the resolve queue after {} deliveries",
+            task.igdb_game.name, task.deliveries
+        );
+        ack(&firestore, &task).await;
+        return;
+    }
+
+    task.deliveries += 1;
+    task.lease_until = Utc::now().timestamp() + LEASE_SECS;
+    if let Err(status) = firestore::resolve_queue::write(&firestore, &task).await {
+        warn!("Failed to lease '{}': {status}", task.igdb_game.name);
+        return;
+    }
+
+    let igdb_game = task.igdb_game.clone();
+    match task.kind {
+        ResolveTaskKind::AddGame => {
+            handlers::add_game(igdb_game, Arc::clone(&firestore), igdb, game_filter).await
+        }
+        ResolveTaskKind::UpdateGame => {
+            handlers::update_game(igdb_game, Arc::clone(&firestore), igdb, game_filter).await
+        }
+    }
+
+    ack(&firestore, &task).await;
+}
+
+/// Removes `task` from the queue, unless a newer update of the game was
+/// enqueued while it was resolving.
+async fn ack(firestore: &FirestoreApi, task: &ResolveTask) {
+    match firestore::resolve_queue::read(firestore, task.id).await {
+        Ok(current) if current.enqueued != task.enqueued => return,
+        Ok(_) => {}
+        Err(Status::NotFound(_)) => return,
+        Err(status) => {
+            warn!("{status}");
+            return;
+        }
+    }
+
+    if let Err(status) = firestore::resolve_queue::delete(firestore, task.id).await {
+        warn!("{status}");
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// Resolves that take longer are assumed to have died with their consumer.
+const LEASE_SECS: i64 = 5 * 60;
+
+// Tasks that keep killing their consumer are dropped eventually.
+const MAX_DELIVERIES: u32 = 5;
@@ -0,0 +1,192 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tracing::{error, info, instrument, trace_span, warn, Instrument};
+
+use crate::{
+    api::{FirestoreApi, IgdbApi, IgdbGame},
+    documents::RetryEntry,
+    library::firestore,
+    Status,
+};
+
+use super::filtering::GameFilter;
+
+/// Adds `igdb_game` in the retry queue after its resolve failed with `status`.
+///
+/// If the game is already queued its update is replaced with the latest one,
+/// but it keeps its backoff. Failures that retrying cannot fix are dropped.
+#[instrument(level = "trace", skip(firestore, igdb_game, status))]
+pub async fn enqueue(firestore: &FirestoreApi, igdb_game: IgdbGame, status: &Status) {
+    if !status.is_retryable() {
+        warn!(
+            "Not retrying '{}' that failed permanently: {status}",
+            igdb_game.name
+        );
+        return;
+    }
+
+    let entry = match firestore::retry_queue::read(firestore, igdb_game.id).await {
+        Ok(entry) => RetryEntry {
+            igdb_game,
+            last_error: status.to_string(),
+            ..entry
+        },
+        Err(Status::NotFound(_)) => RetryEntry {
+            id: igdb_game.id,
+            igdb_game,
+            attempts: 0,
+            next_attempt: Utc::now().naive_utc().timestamp() + backoff_secs(0),
+            last_error: status.to_string(),
+        },
+        Err(status) => {
+            error!("Failed to read retry queue: {status}");
+            return;
+        }
+    };
+
+    if let Err(status) = firestore::retry_queue::write(firestore, &entry).await {
+        error!(
+            "Failed to enqueue '{}' for retry: {status}",
+            entry.igdb_game.name
+        );
+    }
+}
+
+/// Spawns a background task that periodically retries resolving the games in
+/// the retry queue whose backoff has expired.
+pub fn spawn_worker(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+) {
+    tokio::spawn(
+        async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let now = Utc::now().naive_utc().timestamp();
+                let entries = match firestore::retry_queue::list_due(&firestore, now).await {
+                    Ok(entries) => entries,
+                    Err(status) => {
+                        warn!("Failed to read retry queue: {status}");
+                        continue;
+                    }
+                };
+
+                for entry in entries {
+                    process(&firestore, &igdb, &game_filter, entry).await;
+                }
+            }
+        }
+        .instrument(trace_span!("spawn_retry_worker")),
+    );
+}
+
+#[instrument(
+    level = "trace",
+    skip(firestore, igdb, game_filter, entry),
+    fields(
+        game_id = %entry.id,
+        attempts = %entry.attempts,
+    )
+)]
+async fn process(
+    firestore: &Arc<FirestoreApi>,
+    igdb: &IgdbApi,
+    game_filter: &GameFilter,
+    mut entry: RetryEntry,
+) {
+    match retry(firestore, igdb, game_filter, entry.igdb_game.clone()).await {
+        Ok(()) => {
+            info!(
+                "Resolved '{}' after {} retries",
+                entry.igdb_game.name,
+                entry.attempts + 1
+            );
+            if let Err(status) = firestore::retry_queue::delete(firestore, entry.id).await {
+                warn!("{status}");
+            }
+        }
+        Err(status) => {
+            entry.attempts += 1;
+            entry.last_error = status.to_string();
+
+            if entry.attempts >= MAX_ATTEMPTS || !status.is_retryable() {
+                error!(
+                    "Giving up on '{}' after {} attempts: {status}",
+                    entry.igdb_game.name, entry.attempts
+                );
+                if let Err(status) = firestore::retry_queue::delete(firestore, entry.id).await {
+                    warn!("{status}");
+                }
+                return;
+            }
+
+            entry.next_attempt = Utc::now().naive_utc().timestamp() + backoff_secs(entry.attempts);
+            if let Err(status) = firestore::retry_queue::write(firestore, &entry).await {
+                warn!("{status}");
+            }
+        }
+    }
+}
+
+/// Resolves `igdb_game` the same way the webhook that received it would have.
+/// Games that are already in the catalog are resolved unconditionally, while
+/// new ones still need to pass the `game_filter`.
+async fn retry(
+    firestore: &Arc<FirestoreApi>,
+    igdb: &IgdbApi,
+    game_filter: &GameFilter,
+    igdb_game: IgdbGame,
+) -> Result<(), Status> {
+    match firestore::games::read(firestore, igdb_game.id).await {
+        Ok(game_entry) if game_entry.is_tombstone() => Ok(()),
+        Ok(_) => {
+            igdb.resolve(Arc::clone(firestore), igdb_game).await?;
+            Ok(())
+        }
+        Err(Status::NotFound(_)) => {
+            let (mut game_entry, rejection) = igdb
+                .resolve_only(Arc::clone(firestore), igdb_game, game_filter)
+                .await?;
+            if rejection.is_some() {
+                return Ok(());
+            }
+            firestore::games::write(firestore, &mut game_entry).await?;
+            firestore::backfill_queue::add(firestore, &game_entry).await
+        }
+        Err(status) => Err(status),
+    }
+}
+
+// Exponential backoff starting at 1 minute and capped at 12 hours.
+pub(super) fn backoff_secs(attempts: u32) -> i64 {
+    const BASE_SECS: i64 = 60;
+    const MAX_SECS: i64 = 12 * 60 * 60;
+    BASE_SECS
+        .saturating_mul(1 << attempts.min(16))
+        .min(MAX_SECS)
+}
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_ATTEMPTS: u32 = 8;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles() {
+        assert_eq!(backoff_secs(0), 60);
+        assert_eq!(backoff_secs(1), 120);
+        assert_eq!(backoff_secs(3), 480);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff_secs(10), 12 * 60 * 60);
+        assert_eq!(backoff_secs(40), 12 * 60 * 60);
+    }
+}
@@ -1,36 +1,132 @@
+use sha2::{Digest, Sha256};
 use std::{convert::Infallible, sync::Arc};
 use tracing::warn;
-use warp::{self, Filter};
+use warp::{self, http::StatusCode, reject::Reject, Filter, Rejection};
 
 use crate::{
-    api::{FirestoreApi, IgdbApi, IgdbExternalGame, IgdbGame},
+    api::{FirestoreApi, IgdbApi, IgdbCollection, IgdbCompany, IgdbExternalGame, IgdbGame},
     documents::Keyword,
 };
 
-use super::{filtering::GameFilter, handlers};
+use super::{
+    filtering::GameFilter,
+    handlers,
+    simulate::{self, SimulateRequest, Simulation},
+};
 
 /// Returns a Filter with all available routes.
+///
+/// Apart from the health probes, only posts that carry `webhook_secret` in
+/// their `X-Secret` header, as IGDB sends it, are accepted. Others are
+/// rejected with 401 Unauthorized.
+///
+/// `/webhooks/simulate/{kind}` is only served when a `simulation` is set.
 pub fn routes(
     igdb: Arc<IgdbApi>,
     firestore: Arc<FirestoreApi>,
     classifier: Arc<GameFilter>,
+    webhook_secret: &str,
+    simulation: Option<Arc<Simulation>>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    post_add_game(
-        Arc::clone(&firestore),
-        Arc::clone(&igdb),
-        Arc::clone(&classifier),
-    )
-    .or(post_update_game(
-        Arc::clone(&firestore),
-        Arc::clone(&igdb),
-        Arc::clone(&classifier),
-    ))
-    .or(post_external_game(Arc::clone(&firestore)))
-    .or(post_keywords(Arc::clone(&firestore)))
-    .or_else(|e| async {
-        warn! {"Rejected route: {:?}", e};
-        Err(e)
-    })
+    get_healthz(Arc::clone(&firestore), Arc::clone(&igdb))
+        .or(get_readyz(Arc::clone(&firestore), Arc::clone(&igdb)))
+        .or(authenticated(webhook_secret).and(
+            post_add_game(
+                Arc::clone(&firestore),
+                Arc::clone(&igdb),
+                Arc::clone(&classifier),
+            )
+            .or(post_update_game(
+                Arc::clone(&firestore),
+                Arc::clone(&igdb),
+                Arc::clone(&classifier),
+            ))
+            .or(post_external_game(Arc::clone(&firestore)))
+            .or(post_keywords(Arc::clone(&firestore)))
+            .or(post_companies(Arc::clone(&firestore), Arc::clone(&igdb)))
+            .or(post_collections(Arc::clone(&firestore)))
+            .or(post_franchises(Arc::clone(&firestore)))
+            .or(post_simulate(
+                simulation,
+                Arc::clone(&igdb),
+                Arc::clone(&classifier),
+            )),
+        ))
+        .or_else(|e| async {
+            warn! {"Rejected route: {:?}", e};
+            Err(e)
+        })
+        .recover(unauthorized)
+}
+
+/// Rejects requests whose `X-Secret` header does not match `webhook_secret`.
+/// An empty `webhook_secret` rejects all requests.
+fn authenticated(webhook_secret: &str) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let webhook_secret = Arc::new(webhook_secret.to_owned());
+    warp::header::optional::<String>("X-Secret")
+        .and_then(move |secret: Option<String>| {
+            let webhook_secret = Arc::clone(&webhook_secret);
+            async move {
+                match secret {
+                    Some(secret)
+                        if !webhook_secret.is_empty()
+                            && secrets_match(&webhook_secret, &secret) =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err(warp::reject::custom(Unauthorized)),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Compares secrets in constant time, so that response timing does not leak
+/// how much of a guessed secret is correct.
+fn secrets_match(expected: &str, provided: &str) -> bool {
+    let expected = Sha256::digest(expected.as_bytes());
+    let provided = Sha256::digest(provided.as_bytes());
+    expected
+        .iter()
+        .zip(provided.iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+async fn unauthorized(rejection: Rejection) -> Result<StatusCode, Rejection> {
+    match rejection.find::<Unauthorized>() {
+        Some(_) => Ok(StatusCode::UNAUTHORIZED),
+        None => Err(rejection),
+    }
+}
+
+/// GET /healthz
+fn get_healthz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("healthz")
+        .and(warp::get())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::healthz)
+}
+
+/// GET /readyz
+fn get_readyz(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("readyz")
+        .and(warp::get())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::readyz)
 }
 
 /// POST /add_game
@@ -85,6 +181,56 @@ fn post_keywords(
         .and_then(handlers::keywords_webhook)
 }
 
+/// POST /companies
+fn post_companies(
+    firestore: Arc<FirestoreApi>,
+    igdb: Arc<IgdbApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("companies")
+        .and(warp::post())
+        .and(json_body::<IgdbCompany>())
+        .and(with_firestore(firestore))
+        .and(with_igdb(igdb))
+        .and_then(handlers::companies_webhook)
+}
+
+/// POST /collections
+fn post_collections(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collections")
+        .and(warp::post())
+        .and(json_body::<IgdbCollection>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::collections_webhook)
+}
+
+/// POST /franchises
+fn post_franchises(
+    firestore: Arc<FirestoreApi>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("franchises")
+        .and(warp::post())
+        .and(json_body::<IgdbCollection>())
+        .and(with_firestore(firestore))
+        .and_then(handlers::franchises_webhook)
+}
+
+/// POST /webhooks/simulate/{kind}
+fn post_simulate(
+    simulation: Option<Arc<Simulation>>,
+    igdb: Arc<IgdbApi>,
+    classifier: Arc<GameFilter>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("webhooks" / "simulate" / String)
+        .and(warp::post())
+        .and(json_body::<SimulateRequest>())
+        .and(with_simulation(simulation))
+        .and(with_igdb(igdb))
+        .and(with_classifier(classifier))
+        .and_then(simulate::simulate_webhook)
+}
+
 fn json_body<T: serde::de::DeserializeOwned + Send>(
 ) -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone {
     warp::body::content_length_limit(32 * 1024).and(warp::body::json())
@@ -107,3 +253,25 @@ pub fn with_classifier(
 ) -> impl Filter<Extract = (Arc<GameFilter>,), Error = Infallible> + Clone {
     warp::any().map(move || Arc::clone(&classifier))
 }
+
+/// Rejects requests as not found unless a `simulation` is set.
+fn with_simulation(
+    simulation: Option<Arc<Simulation>>,
+) -> impl Filter<Extract = (Arc<Simulation>,), Error = Rejection> + Clone {
+    warp::any().and_then(move || {
+        let simulation = simulation.clone();
+        async move { simulation.ok_or_else(warp::reject::not_found) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_compared_exactly() {
+        assert!(secrets_match("secret", "secret"));
+        assert!(!secrets_match("secret", "secret2"));
+        assert!(!secrets_match("secret", ""));
+    }
+}
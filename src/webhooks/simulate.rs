@@ -0,0 +1,176 @@
+use std::{convert::Infallible, path::PathBuf, sync::Arc};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+
+use crate::{
+    api::{FirestoreApi, IgdbApi, IgdbCollection, IgdbCompany, IgdbExternalGame, IgdbGame},
+    documents::Keyword,
+    Status,
+};
+
+use super::{filtering::GameFilter, handlers};
+
+/// Runs webhook payloads through the handler pipeline for local development,
+/// without waiting for IGDB to send them.
+///
+/// Handlers use a dry run Firestore connection, so they read the catalog but
+/// their writes and user notifications are skipped.
+pub struct Simulation {
+    firestore: Arc<FirestoreApi>,
+    fixtures_dir: PathBuf,
+}
+
+impl Simulation {
+    /// `firestore` must be connected as `Service::DryRun`. Stored fixtures are
+    /// looked up in `fixtures_dir` as '{kind}/{fixture}.json'.
+    pub fn new(firestore: FirestoreApi, fixtures_dir: impl Into<PathBuf>) -> Self {
+        Simulation {
+            firestore: Arc::new(firestore),
+            fixtures_dir: fixtures_dir.into(),
+        }
+    }
+
+    /// Returns the payload of the request, either raw or from a stored
+    /// fixture.
+    async fn payload(
+        &self,
+        kind: &str,
+        request: SimulateRequest,
+    ) -> Result<serde_json::Value, Status> {
+        match request {
+            SimulateRequest {
+                payload: Some(payload),
+                ..
+            } => Ok(payload),
+            SimulateRequest {
+                fixture: Some(fixture),
+                ..
+            } => {
+                if !is_valid_name(kind) || !is_valid_name(&fixture) {
+                    return Err(Status::invalid_argument(format!(
+                        "Invalid fixture name '{kind}/{fixture}'"
+                    )));
+                }
+
+                let path = self.fixtures_dir.join(kind).join(format!("{fixture}.json"));
+                let text = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    Status::not_found(format!("Failed to read fixture '{}': {e}", path.display()))
+                })?;
+                Ok(serde_json::from_str(&text)?)
+            }
+            _ => Err(Status::invalid_argument(
+                "Simulation needs either a 'fixture' or a 'payload'",
+            )),
+        }
+    }
+}
+
+/// Body of `/webhooks/simulate/{kind}` requests.
+#[derive(Deserialize, Default, Debug)]
+pub struct SimulateRequest {
+    /// Name of a stored fixture of the webhook kind.
+    #[serde(default)]
+    pub fixture: Option<String>,
+
+    /// Raw webhook payload, as IGDB would send it.
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+#[instrument(level = "trace", skip(request, simulation, igdb, game_filter))]
+pub async fn simulate_webhook(
+    kind: String,
+    request: SimulateRequest,
+    simulation: Arc<Simulation>,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match simulate(&kind, request, &simulation, igdb, game_filter).await {
+        Ok(()) => {
+            info!("Simulated '{kind}' webhook");
+            Ok(Box::new(StatusCode::OK))
+        }
+        Err(status) => Ok(Box::new(warp::reply::with_status(
+            status.to_string(),
+            match status {
+                Status::NotFound(_) => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_REQUEST,
+            },
+        ))),
+    }
+}
+
+/// Runs the handler of webhook `kind` on the payload of `request`. Dedup and
+/// the resolve queue are bypassed, so that the same payload can be simulated
+/// repeatedly.
+async fn simulate(
+    kind: &str,
+    request: SimulateRequest,
+    simulation: &Simulation,
+    igdb: Arc<IgdbApi>,
+    game_filter: Arc<GameFilter>,
+) -> Result<(), Status> {
+    let payload = simulation.payload(kind, request).await?;
+    let firestore = Arc::clone(&simulation.firestore);
+
+    match kind {
+        "add_game" => {
+            handlers::add_game(parse::<IgdbGame>(payload)?, firestore, igdb, game_filter).await
+        }
+        "update_game" => {
+            handlers::update_game(parse::<IgdbGame>(payload)?, firestore, igdb, game_filter).await
+        }
+        "external_games" => {
+            let _ =
+                handlers::external_games_webhook(parse::<IgdbExternalGame>(payload)?, firestore)
+                    .await;
+        }
+        "keywords" => {
+            let _ = handlers::keywords_webhook(parse::<Keyword>(payload)?, firestore).await;
+        }
+        "companies" => {
+            let _ =
+                handlers::companies_webhook(parse::<IgdbCompany>(payload)?, firestore, igdb).await;
+        }
+        "collections" => {
+            let _ =
+                handlers::collections_webhook(parse::<IgdbCollection>(payload)?, firestore).await;
+        }
+        "franchises" => {
+            let _ =
+                handlers::franchises_webhook(parse::<IgdbCollection>(payload)?, firestore).await;
+        }
+        _ => return Err(Status::not_found(format!("Unknown webhook kind '{kind}'"))),
+    }
+    Ok(())
+}
+
+fn parse<T: DeserializeOwned>(payload: serde_json::Value) -> Result<T, Status> {
+    serde_json::from_value(payload)
+        .map_err(|e| Status::invalid_argument(format!("Invalid webhook payload: {e}")))
+}
+
+/// Fixture names are plain file names, so that requests cannot read outside
+/// the fixtures directory.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture_names_stay_in_fixtures_dir() {
+        assert!(is_valid_name("zelda_botw"));
+        assert!(is_valid_name("add-game-1"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../keys"));
+        assert!(!is_valid_name("a/b"));
+    }
+}